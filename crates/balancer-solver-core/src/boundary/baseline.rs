@@ -5,13 +5,14 @@ use {
         boundary::{self, liquidity::erc4626 as boundary_erc4626},
         domain::{eth, liquidity, order, solver},
     },
-    contracts::alloy::UniswapV3QuoterV2,
+    contracts::alloy::{UniswapV3QuoterV2, UniswapV4Quoter},
     ethereum_types::{H160, U256},
     ethrpc::alloy::conversions::{IntoAlloy, IntoLegacy},
     model::TokenPair,
     shared::{
         baseline_solver::{self, BaseTokens, BaselineSolvable},
         ethrpc::Web3,
+        sources::uniswap_v4,
     },
     std::{
         collections::{HashMap, HashSet},
@@ -19,6 +20,14 @@ use {
     },
 };
 
+/// The most forward swaps of a path to simulate when searching the minimal
+/// sell amount of an exact-out route.
+const MAX_EXACT_OUT_REFINEMENTS: usize = 12;
+
+/// The precision of the minimal sell amount of exact-out routes, in basis
+/// points of the sell amount.
+const EXACT_OUT_TOLERANCE_BPS: u64 = 1;
+
 pub struct Solver<'a> {
     base_tokens: BaseTokens,
     onchain_liquidity: HashMap<TokenPair, Vec<OnchainLiquidity>>,
@@ -31,11 +40,17 @@ impl<'a> Solver<'a> {
         base_tokens: &HashSet<eth::TokenAddress>,
         liquidity: &'a [liquidity::Liquidity],
         uni_v3_quoter_v2: Option<Arc<UniswapV3QuoterV2::Instance>>,
+        uni_v4_quoter: Option<Arc<UniswapV4Quoter::Instance>>,
         erc4626_web3: Option<&Web3>,
     ) -> Self {
         Self {
             base_tokens: to_boundary_base_tokens(weth, base_tokens),
-            onchain_liquidity: to_boundary_liquidity(liquidity, uni_v3_quoter_v2, erc4626_web3),
+            onchain_liquidity: to_boundary_liquidity(
+                liquidity,
+                uni_v3_quoter_v2,
+                uni_v4_quoter,
+                erc4626_web3,
+            ),
             liquidity: liquidity
                 .iter()
                 .map(|liquidity| (liquidity.id.clone(), liquidity))
@@ -120,6 +135,53 @@ impl<'a> Solver<'a> {
         solver::Route::new(segments)
     }
 
+    /// Routes an exact-out request by propagating the buy amount backwards
+    /// along every candidate path, and then searching the smallest sell
+    /// amount that still buys it when swapped forward along the path. Pools
+    /// with inexact exact-out math overshoot their input amounts, which
+    /// compounds over the hops of a path, so the backwards estimate alone
+    /// overstates the sell amount.
+    pub async fn route_exact_out(
+        &self,
+        request: solver::Request,
+        max_hops: usize,
+    ) -> Option<solver::Route<'a>> {
+        let candidates = self.base_tokens.path_candidates_with_hops(
+            request.sell.token.0,
+            request.buy.token.0,
+            max_hops,
+        );
+
+        let futures = candidates.iter().map(|path| async {
+            let sell = baseline_solver::estimate_sell_amount(
+                request.buy.amount,
+                path,
+                &self.onchain_liquidity,
+            )
+            .await?;
+            let segments = min_sell_amount(sell.value, async |amount| {
+                self.traverse_path(&sell.path, request.sell.token.0, amount)
+                    .await
+                    .filter(|segments| {
+                        segments
+                            .last()
+                            .is_some_and(|segment| segment.output.amount >= request.buy.amount)
+                    })
+            })
+            .await?;
+
+            let sell = segments.first()?.input.amount;
+            (sell <= request.sell.amount).then_some((segments, sell))
+        });
+        let (segments, _) = futures::future::join_all(futures)
+            .await
+            .into_iter()
+            .flatten()
+            .min_by_key(|(_, sell)| *sell)?;
+
+        solver::Route::new(segments)
+    }
+
     async fn traverse_path(
         &self,
         path: &[&OnchainLiquidity],
@@ -161,9 +223,76 @@ impl<'a> Solver<'a> {
     }
 }
 
+/// Searches the smallest sell amount for which `swap` succeeds, starting from
+/// an estimate that is expected to succeed. Sell amounts below the estimate
+/// are stepped down exponentially until one fails, and the gap is then
+/// bisected. Returns the result of `swap` for the smallest sell amount found,
+/// or `None` if the estimate itself fails.
+async fn min_sell_amount<T>(estimate: U256, swap: impl AsyncFn(U256) -> Option<T>) -> Option<T> {
+    let mut best = swap(estimate).await?;
+    let mut high = estimate;
+    let mut low = U256::zero();
+    let mut refinements = 0;
+
+    let mut step = (estimate / 100).max(U256::one());
+    while refinements < MAX_EXACT_OUT_REFINEMENTS && !high.is_zero() {
+        refinements += 1;
+        let candidate = high.saturating_sub(step);
+        match swap(candidate).await {
+            Some(result) => {
+                (high, best) = (candidate, result);
+                step = step.saturating_mul(2.into());
+            }
+            None => {
+                low = candidate;
+                break;
+            }
+        }
+    }
+
+    let tolerance = |amount: U256| {
+        (amount.saturating_mul(EXACT_OUT_TOLERANCE_BPS.into()) / 10_000).max(1.into())
+    };
+    while refinements < MAX_EXACT_OUT_REFINEMENTS && high - low > tolerance(high) {
+        refinements += 1;
+        let candidate = low + (high - low) / 2;
+        match swap(candidate).await {
+            Some(result) => (high, best) = (candidate, result),
+            None => low = candidate,
+        }
+    }
+
+    Some(best)
+}
+
+/// Computes the amount of `output` tokens received for swapping `input`
+/// through the specified liquidity.
+pub async fn amount_out(
+    liquidity: &liquidity::Liquidity,
+    input: eth::Asset,
+    output: eth::TokenAddress,
+    uni_v3_quoter_v2: Option<Arc<UniswapV3QuoterV2::Instance>>,
+    uni_v4_quoter: Option<Arc<UniswapV4Quoter::Instance>>,
+    erc4626_web3: Option<&Web3>,
+) -> Option<U256> {
+    let pair = TokenPair::new(input.token.0.into_alloy(), output.0.into_alloy())?;
+    let onchain_liquidity = to_boundary_liquidity(
+        std::slice::from_ref(liquidity),
+        uni_v3_quoter_v2,
+        uni_v4_quoter,
+        erc4626_web3,
+    );
+    onchain_liquidity
+        .get(&pair)?
+        .first()?
+        .get_amount_out(output.0, (input.amount, input.token.0))
+        .await
+}
+
 fn to_boundary_liquidity(
     liquidity: &[liquidity::Liquidity],
     uni_v3_quoter_v2: Option<Arc<contracts::alloy::UniswapV3QuoterV2::Instance>>,
+    uni_v4_quoter: Option<Arc<UniswapV4Quoter::Instance>>,
     erc4626_web3: Option<&Web3>,
 ) -> HashMap<TokenPair, Vec<OnchainLiquidity>> {
     liquidity
@@ -261,6 +390,61 @@ fn to_boundary_liquidity(
                             ),
                         })
                 }
+                liquidity::State::UniswapV4(pool) => {
+                    let Some(ref uni_v4_quoter) = uni_v4_quoter else {
+                        // Uniswap V4 pools can only be quoted on-chain
+                        return onchain_liquidity;
+                    };
+                    let (currency0, currency1) = pool.tokens.get();
+                    let token_pair = to_boundary_token_pair(&pool.tokens);
+                    onchain_liquidity
+                        .entry(token_pair)
+                        .or_default()
+                        .push(OnchainLiquidity {
+                            id: liquidity.id.clone(),
+                            token_pair,
+                            source: LiquiditySource::UniswapV4(uniswap_v4::Pool {
+                                quoter: uni_v4_quoter.clone(),
+                                key: uniswap_v4::PoolKey {
+                                    currency0: currency0.0,
+                                    currency1: currency1.0,
+                                    fee: pool.fee,
+                                    tick_spacing: pool.tick_spacing,
+                                    hooks: pool.hooks,
+                                },
+                            }),
+                        })
+                }
+                liquidity::State::MaverickV2(pool) => {
+                    if let Some(boundary_pool) =
+                        boundary::liquidity::maverick_v2::to_boundary_pool(liquidity.address, pool)
+                    {
+                        let token_pair = to_boundary_token_pair(&pool.tokens);
+                        onchain_liquidity
+                            .entry(token_pair)
+                            .or_default()
+                            .push(OnchainLiquidity {
+                                id: liquidity.id.clone(),
+                                token_pair,
+                                source: LiquiditySource::MaverickV2(boundary_pool),
+                            })
+                    }
+                }
+                liquidity::State::Curve(pool) => {
+                    let boundary_pool =
+                        boundary::liquidity::curve::to_boundary_pool(liquidity.address, pool);
+                    for pair in pool.token_pairs() {
+                        let token_pair = to_boundary_token_pair(&pair);
+                        onchain_liquidity
+                            .entry(token_pair)
+                            .or_default()
+                            .push(OnchainLiquidity {
+                                id: liquidity.id.clone(),
+                                token_pair,
+                                source: LiquiditySource::Curve(boundary_pool.clone()),
+                            });
+                    }
+                }
                 liquidity::State::GyroE(pool) => {
                     let pool = pool.as_ref();
                     if let Some(boundary_pool) =
@@ -401,6 +585,9 @@ enum LiquiditySource {
     ReClamm(boundary::liquidity::reclamm::Pool),
     LimitOrder(liquidity::limit_order::LimitOrder),
     Concentrated(boundary::liquidity::concentrated::Pool),
+    UniswapV4(uniswap_v4::Pool),
+    MaverickV2(boundary::liquidity::maverick_v2::Pool),
+    Curve(boundary::liquidity::curve::Pool),
     QuantAmm(boundary::liquidity::quantamm::Pool),
     Erc4626(boundary_erc4626::Edge),
 }
@@ -420,6 +607,9 @@ impl BaselineSolvable for OnchainLiquidity {
                 limit_order.get_amount_out(out_token, input).await
             }
             LiquiditySource::Concentrated(pool) => pool.get_amount_out(out_token, input).await,
+            LiquiditySource::UniswapV4(pool) => pool.get_amount_out(out_token, input).await,
+            LiquiditySource::MaverickV2(pool) => pool.get_amount_out(out_token, input).await,
+            LiquiditySource::Curve(pool) => pool.get_amount_out(out_token, input).await,
             LiquiditySource::Erc4626(edge) => edge.get_amount_out(out_token, input).await,
         }
     }
@@ -438,6 +628,9 @@ impl BaselineSolvable for OnchainLiquidity {
                 limit_order.get_amount_in(in_token, out).await
             }
             LiquiditySource::Concentrated(pool) => pool.get_amount_in(in_token, out).await,
+            LiquiditySource::UniswapV4(pool) => pool.get_amount_in(in_token, out).await,
+            LiquiditySource::MaverickV2(pool) => pool.get_amount_in(in_token, out).await,
+            LiquiditySource::Curve(pool) => pool.get_amount_in(in_token, out).await,
             LiquiditySource::Erc4626(edge) => edge.get_amount_in(in_token, out).await,
         }
     }
@@ -454,6 +647,9 @@ impl BaselineSolvable for OnchainLiquidity {
             LiquiditySource::QuantAmm(pool) => pool.gas_cost().await,
             LiquiditySource::LimitOrder(limit_order) => limit_order.gas_cost().await,
             LiquiditySource::Concentrated(pool) => pool.gas_cost().await,
+            LiquiditySource::UniswapV4(pool) => pool.gas_cost().await,
+            LiquiditySource::MaverickV2(pool) => pool.gas_cost().await,
+            LiquiditySource::Curve(pool) => pool.gas_cost().await,
             LiquiditySource::Erc4626(edge) => edge.gas_cost().await,
         }
     }
@@ -471,3 +667,20 @@ fn to_boundary_token_pair(pair: &liquidity::TokenPair) -> TokenPair {
     let (a, b) = pair.get();
     TokenPair::new(a.0.into_alloy(), b.0.into_alloy()).unwrap()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn searches_minimal_sell_amount() {
+        // Swapping buys half of the sell amount, so 1000 buy tokens need at
+        // least 2000 sell tokens, while the estimate overshoots by 5%.
+        let swap = async |amount: U256| (amount / 2 >= 1000.into()).then_some(amount);
+        let sell = min_sell_amount(2100.into(), swap).await.unwrap();
+        assert_eq!(sell, 2000.into());
+
+        // Estimates that don't succeed yield nothing.
+        assert_eq!(min_sell_amount(1900.into(), swap).await, None);
+    }
+}
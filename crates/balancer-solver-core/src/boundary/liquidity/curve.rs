@@ -0,0 +1,15 @@
+pub use shared::sources::curve::Pool;
+use {crate::domain::liquidity, ethereum_types::H160};
+
+/// Converts a domain pool into a [`shared`] Curve pool.
+pub fn to_boundary_pool(address: H160, pool: &liquidity::curve::Pool) -> Pool {
+    Pool {
+        address,
+        coins: pool.coins.iter().map(|coin| coin.asset.token.0).collect(),
+        balances: pool.coins.iter().map(|coin| coin.asset.amount).collect(),
+        rates: pool.coins.iter().map(|coin| coin.rate).collect(),
+        amplification: pool.amplification,
+        fee: pool.fee,
+        base_pool: pool.base_pool,
+    }
+}
@@ -0,0 +1,40 @@
+pub use shared::sources::maverick_v2::Pool;
+use {
+    crate::domain::{eth, liquidity},
+    ethereum_types::{H160, U256},
+    shared::sources::maverick_v2::math::TickReserves,
+};
+
+/// Converts a domain pool into a [`shared`] Maverick V2 pool. Returns `None`
+/// if the fees can't be represented as 18 decimal fixed point numbers.
+pub fn to_boundary_pool(address: H160, pool: &liquidity::maverick_v2::Pool) -> Option<Pool> {
+    let (token_a, token_b) = pool.tokens.get();
+    Some(Pool {
+        address,
+        token_a: token_a.0,
+        token_b: token_b.0,
+        tick_spacing: pool.tick_spacing,
+        active_tick: pool.active_tick,
+        ticks: pool
+            .ticks
+            .iter()
+            .map(|(tick, reserves)| {
+                (
+                    *tick,
+                    TickReserves {
+                        reserve_a: reserves.a,
+                        reserve_b: reserves.b,
+                    },
+                )
+            })
+            .collect(),
+        fee_a_in: to_fixed_point(&pool.fee_a_in)?,
+        fee_b_in: to_fixed_point(&pool.fee_b_in)?,
+    })
+}
+
+fn to_fixed_point(fee: &eth::Rational) -> Option<U256> {
+    fee.numer()
+        .checked_mul(U256::exp10(18))?
+        .checked_div(*fee.denom())
+}
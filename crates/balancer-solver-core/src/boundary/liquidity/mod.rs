@@ -1,10 +1,12 @@
 pub mod concentrated;
 pub mod constant_product;
+pub mod curve;
 pub mod erc4626;
 pub mod gyro_2clp;
 pub mod gyro_3clp;
 pub mod gyro_e;
 mod limit_order;
+pub mod maverick_v2;
 pub mod quantamm;
 pub mod reclamm;
 pub mod stable;
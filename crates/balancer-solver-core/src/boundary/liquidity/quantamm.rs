@@ -4,7 +4,7 @@ use {
     ethcontract::I256,
     ethereum_types::{H160, U256},
     shared::sources::balancer_v3::{
-        pool_fetching::{CommonPoolState, QuantAmmPoolVersion, TokenState},
+        pool_fetching::{CommonPoolState, QuantAmmPoolVersion, QuantAmmStrategy, TokenState},
         swap::fixed_point::Bfp,
     },
 };
@@ -39,6 +39,15 @@ pub fn to_boundary_pool(address: H160, pool: &liquidity::quantamm::Pool) -> Opti
         reserves,
         version: QuantAmmPoolVersion::V1,
         max_trade_size_ratio: to_fixed_point(&pool.max_trade_size_ratio)?,
+        strategy: pool
+            .strategy
+            .as_ref()
+            .map(|strategy| QuantAmmStrategy {
+                rule: strategy.rule.clone(),
+                oracles: strategy.oracles.clone(),
+                update_interval: strategy.update_interval,
+            })
+            .unwrap_or_default(),
         first_four_weights_and_multipliers: pool
             .first_four_weights_and_multipliers
             .iter()
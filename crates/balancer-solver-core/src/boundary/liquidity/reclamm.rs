@@ -49,6 +49,7 @@ pub fn to_boundary_pool(address: H160, pool: &liquidity::reclamm::Pool) -> Optio
         end_fourth_root_price_ratio: to_fixed_point(&pool.end_fourth_root_price_ratio)?,
         price_ratio_update_start_time: pool.price_ratio_update_start_time,
         price_ratio_update_end_time: pool.price_ratio_update_end_time,
+        current_timestamp: pool.current_timestamp,
     })
 }
 
@@ -55,6 +55,30 @@ pub fn to_boundary_pool(address: H160, pool: &liquidity::weighted_product::Pool)
     })
 }
 
+/// Returns the amount of pool tokens minted for joining the pool with a single
+/// token, given the current pool token supply.
+pub fn join(
+    address: H160,
+    pool: &liquidity::weighted_product::Pool,
+    input: eth::Asset,
+    total_supply: U256,
+) -> Option<U256> {
+    to_boundary_pool(address, pool)?
+        .bpt_out_given_exact_token_in((input.amount, input.token.0), total_supply)
+}
+
+/// Returns the amount of a single token received for exiting the pool with
+/// the specified amount of pool tokens, given the current pool token supply.
+pub fn exit(
+    address: H160,
+    pool: &liquidity::weighted_product::Pool,
+    bpt_in: U256,
+    output: eth::TokenAddress,
+    total_supply: U256,
+) -> Option<U256> {
+    to_boundary_pool(address, pool)?.token_out_given_exact_bpt_in(output.0, bpt_in, total_supply)
+}
+
 /// Converts a rational to a Balancer fixed point number.
 fn to_fixed_point(ratio: &eth::Rational) -> Option<Bfp> {
     // Balancer "fixed point numbers" are in a weird decimal FP format (instead
@@ -17,10 +17,13 @@ pub struct Auction {
     pub liquidity: Vec<liquidity::Liquidity>,
     pub gas_price: GasPrice,
     pub deadline: Deadline,
+    /// The maximum amount of gas a solution may use, if the auction specifies
+    /// one.
+    pub gas_limit: Option<eth::Gas>,
 }
 
 /// Information about tokens used in the auction.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Tokens(pub HashMap<eth::TokenAddress, Token>);
 
 impl Tokens {
@@ -53,7 +56,7 @@ impl Display for Id {
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Token {
     pub decimals: Option<u8>,
     pub symbol: Option<String>,
@@ -76,6 +79,14 @@ impl Price {
     pub fn ether_value(&self, eth: eth::Ether) -> Option<U256> {
         eth.0.checked_mul(Self::BASE.into())?.checked_div(self.0.0)
     }
+
+    /// Computes the [`eth::Ether`] value of the specified token amount at the
+    /// given price.
+    pub fn native_value(&self, amount: U256) -> Option<eth::Ether> {
+        Some(eth::Ether(
+            amount.checked_mul(self.0.0)? / U256::from(Self::BASE),
+        ))
+    }
 }
 
 /// The estimated effective gas price that will likely be used for executing the
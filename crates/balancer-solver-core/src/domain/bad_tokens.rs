@@ -0,0 +1,251 @@
+//! Heuristic detection of tokens that break settlements.
+//!
+//! This mirrors the bad token detection of the driver, but based on the
+//! notifications this solver receives about its own solutions: the tokens
+//! traded by a solution are candidates for its settlement failing, and tokens
+//! that fail too often are quarantined for a while. Tokens the auction marks as
+//! trusted are never blamed.
+
+use {
+    crate::{
+        domain::{auction, eth, solution},
+        infra::metrics,
+    },
+    std::{
+        collections::{BTreeSet, HashMap, HashSet, VecDeque},
+        sync::Mutex,
+        time::{Duration, Instant},
+    },
+};
+
+/// The number of recent solutions to remember the candidate tokens of.
+const MAX_TRACKED_SOLUTIONS: usize = 1000;
+
+pub struct Config {
+    /// The ratio of failing settlements at which a token gets quarantined.
+    pub failure_ratio: f64,
+    /// The number of settlement outcomes a token needs to be part of before
+    /// it can get quarantined.
+    pub required_measurements: u32,
+    /// How long a token stays quarantined before it gets another chance.
+    pub freeze_time: Duration,
+}
+
+/// The outcome of settling a solution.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Success,
+    Failure,
+}
+
+pub struct Detector {
+    config: Config,
+    inner: Mutex<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    /// The candidate tokens of recently proposed solutions by auction and
+    /// solution ID.
+    solutions: VecDeque<(i64, u64, BTreeSet<eth::TokenAddress>)>,
+    tokens: HashMap<eth::TokenAddress, Statistics>,
+}
+
+#[derive(Default)]
+struct Statistics {
+    attempts: u32,
+    fails: u32,
+    quarantined_at: Option<Instant>,
+}
+
+impl Detector {
+    pub fn new(config: Config) -> Self {
+        Self {
+            config,
+            inner: Default::default(),
+        }
+    }
+
+    /// Remembers the candidate tokens of the solutions proposed for an
+    /// auction, so that settlement outcomes can later be attributed to them.
+    pub fn record_solutions(
+        &self,
+        auction: i64,
+        solutions: &[solution::Solution],
+        trusted: &HashSet<eth::TokenAddress>,
+    ) {
+        let mut inner = self.inner.lock().unwrap();
+        for solution in solutions {
+            let candidates = candidates(solution)
+                .filter(|token| !trusted.contains(token))
+                .collect();
+            inner
+                .solutions
+                .push_back((auction, solution.id.0, candidates));
+        }
+        while inner.solutions.len() > MAX_TRACKED_SOLUTIONS {
+            inner.solutions.pop_front();
+        }
+    }
+
+    /// Attributes the outcome of settling the specified solutions to their
+    /// candidate tokens, quarantining tokens that fail too often.
+    pub fn record_outcome(&self, auction: i64, solutions: &[u64], outcome: Outcome) {
+        let now = Instant::now();
+        let mut inner = self.inner.lock().unwrap();
+        let Inner {
+            solutions: recent,
+            tokens,
+        } = &mut *inner;
+
+        let candidates = recent
+            .iter()
+            .filter(|(id, solution, _)| *id == auction && solutions.contains(solution))
+            .flat_map(|(_, _, candidates)| candidates)
+            .collect::<BTreeSet<_>>();
+
+        for token in candidates {
+            let stats = tokens.entry(*token).or_default();
+            stats.attempts += 1;
+            stats.fails += u32::from(outcome == Outcome::Failure);
+
+            if self.fails_too_often(stats) && !self.is_frozen(stats, now) {
+                tracing::info!(
+                    ?token,
+                    stats.attempts,
+                    stats.fails,
+                    "quarantining bad token"
+                );
+                stats.quarantined_at = Some(now);
+                metrics::bad_token_detected();
+            }
+        }
+    }
+
+    /// Returns whether the token is currently quarantined.
+    pub fn is_quarantined(&self, token: &eth::TokenAddress, now: Instant) -> bool {
+        self.inner
+            .lock()
+            .unwrap()
+            .tokens
+            .get(token)
+            .is_some_and(|stats| self.is_frozen(stats, now))
+    }
+
+    /// Removes all orders trading quarantined tokens from the auction.
+    pub fn filter(&self, auction: &mut auction::Auction) {
+        let now = Instant::now();
+        auction.orders.retain(|order| {
+            let quarantined = [order.sell.token, order.buy.token]
+                .into_iter()
+                .filter(|token| self.is_quarantined(token, now))
+                .collect::<Vec<_>>();
+            for token in &quarantined {
+                tracing::debug!(uid = %order.uid, ?token, "filtering order trading bad token");
+                metrics::denied_token_order(token);
+            }
+            quarantined.is_empty()
+        });
+    }
+
+    fn fails_too_often(&self, stats: &Statistics) -> bool {
+        stats.attempts >= self.config.required_measurements
+            && f64::from(stats.fails) / f64::from(stats.attempts) >= self.config.failure_ratio
+    }
+
+    fn is_frozen(&self, stats: &Statistics, now: Instant) -> bool {
+        stats
+            .quarantined_at
+            .is_some_and(|at| now.duration_since(at) <= self.config.freeze_time)
+    }
+}
+
+/// The tokens that could be responsible for a solution failing to settle,
+/// that is all tokens that get transferred when executing it.
+fn candidates(solution: &solution::Solution) -> impl Iterator<Item = eth::TokenAddress> + '_ {
+    let traded = solution.trades.iter().flat_map(|trade| match trade {
+        solution::Trade::Fulfillment(fulfillment) => {
+            let order = fulfillment.order();
+            vec![order.sell.token, order.buy.token]
+        }
+        solution::Trade::Jit(jit) => vec![jit.order.sell.token, jit.order.buy.token],
+    });
+    let swapped = solution
+        .interactions
+        .iter()
+        .flat_map(|interaction| match interaction {
+            solution::Interaction::Liquidity(interaction) => {
+                vec![interaction.input.token, interaction.output.token]
+            }
+            solution::Interaction::Custom(interaction) => interaction
+                .inputs
+                .iter()
+                .chain(&interaction.outputs)
+                .map(|asset| asset.token)
+                .collect(),
+        });
+    traded.chain(swapped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solution(id: u64, tokens: [u64; 2]) -> solution::Solution {
+        let [input, output] = tokens.map(|token| eth::Asset {
+            token: eth::TokenAddress(eth::H160::from_low_u64_be(token)),
+            amount: 1.into(),
+        });
+        solution::Solution {
+            id: solution::Id(id),
+            interactions: vec![solution::Interaction::Custom(solution::CustomInteraction {
+                target: Default::default(),
+                value: eth::Ether(0.into()),
+                calldata: Vec::new(),
+                internalize: false,
+                inputs: vec![input],
+                outputs: vec![output],
+                allowances: Vec::new(),
+            })],
+            ..Default::default()
+        }
+    }
+
+    /// Tests that a token only gets quarantined temporarily after failing
+    /// repeatedly, and that trusted tokens are never blamed.
+    #[tokio::test]
+    async fn quarantines_repeat_offenders() {
+        const FREEZE_TIME: Duration = Duration::from_millis(50);
+        let detector = Detector::new(Config {
+            failure_ratio: 0.5,
+            required_measurements: 2,
+            freeze_time: FREEZE_TIME,
+        });
+
+        let bad = eth::TokenAddress(eth::H160::from_low_u64_be(1));
+        let trusted = eth::TokenAddress(eth::H160::from_low_u64_be(2));
+        let is_quarantined = |token| detector.is_quarantined(&token, Instant::now());
+
+        detector.record_solutions(1, &[solution(0, [1, 2])], &HashSet::from([trusted]));
+        detector.record_outcome(1, &[0], Outcome::Failure);
+        assert!(!is_quarantined(bad));
+
+        detector.record_solutions(2, &[solution(0, [1, 2])], &HashSet::from([trusted]));
+        detector.record_outcome(2, &[0], Outcome::Failure);
+        assert!(is_quarantined(bad));
+        assert!(!is_quarantined(trusted));
+
+        // after the freeze time the token gets another chance
+        tokio::time::sleep(FREEZE_TIME).await;
+        assert!(!is_quarantined(bad));
+
+        // outcomes of unknown solutions are ignored
+        detector.record_outcome(3, &[0], Outcome::Failure);
+        assert!(!is_quarantined(bad));
+
+        // the next failure quarantines it again
+        detector.record_solutions(4, &[solution(0, [1, 2])], &HashSet::from([trusted]));
+        detector.record_outcome(4, &[0], Outcome::Failure);
+        assert!(is_quarantined(bad));
+    }
+}
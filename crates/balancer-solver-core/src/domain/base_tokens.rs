@@ -0,0 +1,294 @@
+//! Automatic selection of the base tokens used for routing.
+//!
+//! Instead of only routing through a static list of base tokens, the most
+//! connected tokens of the auction liquidity get selected as base tokens: the
+//! tokens held by the most pools with a meaningful TVL, with ties broken by
+//! the total TVL of these pools. The selection is refreshed periodically and
+//! persisted, so that a restarted solver starts out with the latest selection
+//! instead of only the configured base tokens.
+
+use {
+    crate::domain::{auction, eth, liquidity},
+    serde::{Deserialize, Serialize},
+    std::{
+        collections::{HashMap, HashSet},
+        path::{Path, PathBuf},
+        sync::Mutex,
+        time::{Duration, Instant},
+    },
+};
+
+pub struct Config {
+    /// The number of tokens to select.
+    pub count: usize,
+    /// How often the selection gets refreshed.
+    pub interval: Duration,
+    /// The TVL in the native token a pool needs for counting towards the
+    /// connectedness of its tokens.
+    pub min_pool_tvl: eth::Ether,
+    /// The file the selection gets persisted to and restored from.
+    pub path: Option<PathBuf>,
+}
+
+pub struct Selector {
+    config: Config,
+    chain_id: u64,
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    updated_at: Option<Instant>,
+    selection: Option<Vec<eth::TokenAddress>>,
+}
+
+/// A selection as persisted to disk. Selections persisted for a different
+/// chain are ignored when restoring.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Persisted {
+    chain_id: u64,
+    tokens: Vec<eth::H160>,
+}
+
+impl Selector {
+    /// Creates a new selector, restoring the persisted selection if there is
+    /// one for the chain.
+    pub async fn new(config: Config, chain_id: u64) -> Self {
+        let selection = match &config.path {
+            Some(path) => restore(path, chain_id).await,
+            None => None,
+        };
+        Self {
+            config,
+            chain_id,
+            state: Mutex::new(State {
+                updated_at: None,
+                selection,
+            }),
+        }
+    }
+
+    /// Returns the latest selection, if any.
+    pub fn selection(&self) -> Option<Vec<eth::TokenAddress>> {
+        self.state.lock().unwrap().selection.clone()
+    }
+
+    /// Selects the base tokens from the auction's liquidity if the refresh
+    /// interval elapsed since the last selection, returning the new
+    /// selection. Auctions without liquidity keep the previous selection.
+    pub fn update(&self, auction: &auction::Auction) -> Option<Vec<eth::TokenAddress>> {
+        if auction.liquidity.is_empty() {
+            return None;
+        }
+        let selection = {
+            let mut state = self.state.lock().unwrap();
+            if state
+                .updated_at
+                .is_some_and(|updated_at| updated_at.elapsed() < self.config.interval)
+            {
+                return None;
+            }
+            let selection = select(
+                &auction.tokens,
+                &auction.liquidity,
+                self.config.count,
+                self.config.min_pool_tvl,
+            );
+            state.updated_at = Some(Instant::now());
+            state.selection = Some(selection.clone());
+            selection
+        };
+        tracing::info!(tokens = ?selection, "selected base tokens");
+        if let Some(path) = &self.config.path {
+            // Writing the file doesn't hold up the auction being solved.
+            tokio::spawn(persist(path.clone(), self.chain_id, selection.clone()));
+        }
+        Some(selection)
+    }
+}
+
+/// Selects up to `count` of the most connected tokens of the liquidity.
+pub fn select(
+    tokens: &auction::Tokens,
+    liquidity: &[liquidity::Liquidity],
+    count: usize,
+    min_pool_tvl: eth::Ether,
+) -> Vec<eth::TokenAddress> {
+    #[derive(Default)]
+    struct Connectedness {
+        pools: usize,
+        tvl: eth::U256,
+    }
+
+    let mut connectedness = HashMap::<eth::TokenAddress, Connectedness>::new();
+    for liquidity in liquidity {
+        // Pools with reserves of unknown value don't count.
+        let Some(tvl) = liquidity
+            .reserves()
+            .iter()
+            .try_fold(eth::U256::zero(), |tvl, reserve| {
+                let value = tokens
+                    .reference_price(&reserve.token)?
+                    .native_value(reserve.amount)?;
+                tvl.checked_add(value.0)
+            })
+        else {
+            continue;
+        };
+        if tvl.is_zero() || tvl < min_pool_tvl.0 {
+            continue;
+        }
+        for token in liquidity.tokens().into_iter().collect::<HashSet<_>>() {
+            let entry = connectedness.entry(token).or_default();
+            entry.pools += 1;
+            entry.tvl = entry.tvl.saturating_add(tvl);
+        }
+    }
+
+    let mut ranked = connectedness.into_iter().collect::<Vec<_>>();
+    ranked.sort_by(|(a, a_connectedness), (b, b_connectedness)| {
+        b_connectedness
+            .pools
+            .cmp(&a_connectedness.pools)
+            .then(b_connectedness.tvl.cmp(&a_connectedness.tvl))
+            .then(a.cmp(b))
+    });
+    ranked
+        .into_iter()
+        .take(count)
+        .map(|(token, _)| token)
+        .collect()
+}
+
+async fn restore(path: &Path, chain_id: u64) -> Option<Vec<eth::TokenAddress>> {
+    let content = match tokio::fs::read(path).await {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return None,
+        Err(err) => {
+            tracing::warn!(?err, ?path, "failed to read persisted base tokens");
+            return None;
+        }
+    };
+    match serde_json::from_slice::<Persisted>(&content) {
+        Ok(persisted) if persisted.chain_id == chain_id => {
+            tracing::info!(tokens = ?persisted.tokens, "restored persisted base tokens");
+            Some(
+                persisted
+                    .tokens
+                    .into_iter()
+                    .map(eth::TokenAddress)
+                    .collect(),
+            )
+        }
+        Ok(persisted) => {
+            tracing::warn!(
+                chain_id = persisted.chain_id,
+                ?path,
+                "ignoring base tokens persisted for another chain"
+            );
+            None
+        }
+        Err(err) => {
+            tracing::warn!(?err, ?path, "failed to parse persisted base tokens");
+            None
+        }
+    }
+}
+
+async fn persist(path: PathBuf, chain_id: u64, selection: Vec<eth::TokenAddress>) {
+    let persisted = Persisted {
+        chain_id,
+        tokens: selection.iter().map(|token| token.0).collect(),
+    };
+    let result = match serde_json::to_vec_pretty(&persisted) {
+        Ok(content) => tokio::fs::write(&path, content)
+            .await
+            .map_err(anyhow::Error::from),
+        Err(err) => Err(err.into()),
+    };
+    if let Err(err) = result {
+        tracing::warn!(?err, ?path, "failed to persist base tokens");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::domain::liquidity::constant_product};
+
+    fn token(token: u64) -> eth::TokenAddress {
+        eth::TokenAddress(eth::H160::from_low_u64_be(token))
+    }
+
+    fn pool(a: u64, b: u64, amount: u64) -> liquidity::Liquidity {
+        let asset = |token| eth::Asset {
+            token: self::token(token),
+            amount: amount.into(),
+        };
+        liquidity::Liquidity {
+            id: liquidity::Id(format!("{a}-{b}")),
+            address: Default::default(),
+            balancer_pool_id: None,
+            gas: eth::Gas(100_000.into()),
+            state: liquidity::State::ConstantProduct(constant_product::Pool {
+                reserves: constant_product::Reserves::new(asset(a), asset(b)).unwrap(),
+                fee: eth::Rational::new_raw(3.into(), 1000.into()),
+            }),
+        }
+    }
+
+    fn tokens(tokens: &[u64]) -> auction::Tokens {
+        auction::Tokens(
+            tokens
+                .iter()
+                .map(|address| {
+                    let token = auction::Token {
+                        decimals: Some(18),
+                        symbol: None,
+                        reference_price: Some(auction::Price(eth::Ether(eth::U256::exp10(18)))),
+                        available_balance: Default::default(),
+                        trusted: false,
+                    };
+                    (self::token(*address), token)
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn selects_most_connected_tokens() {
+        let tokens = tokens(&[1, 2, 3, 4, 5]);
+        let liquidity = [
+            pool(1, 2, 100),
+            pool(1, 3, 500),
+            pool(1, 4, 100),
+            pool(2, 3, 1000),
+            // too shallow to count
+            pool(4, 5, 1),
+            pool(4, 5, 1),
+            // unpriced
+            pool(4, 6, 100),
+            pool(4, 6, 100),
+        ];
+
+        let select = |count| select(&tokens, &liquidity, count, eth::Ether(100.into()));
+        // 1 is in the most pools, while 2 and 3 are in as many pools as each
+        // other, but 3's pools are deeper
+        assert_eq!(select(2), vec![token(1), token(3)]);
+        assert_eq!(select(10), vec![token(1), token(3), token(2), token(4)]);
+    }
+
+    #[tokio::test]
+    async fn persists_selection_per_chain() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("base_tokens.json");
+        persist(path.clone(), 1, vec![token(1), token(2)]).await;
+
+        assert_eq!(restore(&path, 1).await, Some(vec![token(1), token(2)]));
+        assert_eq!(restore(&path, 100).await, None);
+        assert_eq!(
+            restore(&directory.path().join("missing.json"), 1).await,
+            None
+        );
+    }
+}
@@ -0,0 +1,255 @@
+//! Encoding of boosted Balancer V3 routes as a single batch router swap.
+//!
+//! Boosted pools hold ERC4626 tokens, so routing from or to the underlying
+//! token requires wrapping or unwrapping as part of the route. The Balancer V3
+//! Vault keeps buffers for that, which the batch router can use as steps of a
+//! swap path. Encoding the whole route as one `swapExactIn` call avoids
+//! separate wrap and unwrap interactions and the token transfers between
+//! them.
+
+use {
+    crate::domain::{eth, liquidity, solution},
+    alloy::{primitives, sol_types::SolCall},
+    contracts::alloy::BalancerV3BatchRouter::{
+        BalancerV3BatchRouter::swapExactInCall,
+        IBatchRouter::{SwapPathExactAmountIn, SwapPathStep},
+    },
+    ethrpc::alloy::conversions::IntoAlloy,
+    std::collections::HashSet,
+};
+
+pub struct BatchRouter {
+    address: eth::Address,
+    /// The ERC4626 tokens with an initialized buffer in the Vault.
+    buffers: HashSet<eth::TokenAddress>,
+}
+
+impl BatchRouter {
+    pub fn new(
+        address: eth::Address,
+        buffers: impl IntoIterator<Item = eth::TokenAddress>,
+    ) -> Self {
+        Self {
+            address,
+            buffers: buffers.into_iter().collect(),
+        }
+    }
+
+    /// Replaces the interactions of a route swapping through Balancer V3
+    /// pools and wrapping or unwrapping ERC4626 tokens through Vault buffers
+    /// with a single batch router swap. Other routes are returned unchanged.
+    pub fn encode(&self, interactions: Vec<solution::Interaction>) -> Vec<solution::Interaction> {
+        match self.path(&interactions) {
+            Some(path) => vec![self.swap(path)],
+            None => interactions,
+        }
+    }
+
+    fn path(&self, interactions: &[solution::Interaction]) -> Option<Path> {
+        let mut hops = Vec::with_capacity(interactions.len());
+        for interaction in interactions {
+            let solution::Interaction::Liquidity(interaction) = interaction else {
+                return None;
+            };
+            hops.push(interaction.as_ref());
+        }
+        let (first, last) = (hops.first()?, hops.last()?);
+        if hops
+            .windows(2)
+            .any(|pair| pair[0].output.token != pair[1].input.token)
+        {
+            return None;
+        }
+
+        let mut buffered = false;
+        let steps = hops
+            .iter()
+            .map(|hop| {
+                let (pool, is_buffer) = match &hop.liquidity.state {
+                    liquidity::State::Erc4626(edge) if self.buffers.contains(&edge.vault) => {
+                        buffered = true;
+                        (edge.vault.0, true)
+                    }
+                    _ if hop.liquidity.is_balancer_v3() => (hop.liquidity.address, false),
+                    _ => return None,
+                };
+                Some(SwapPathStep {
+                    pool: pool.into_alloy(),
+                    tokenOut: hop.output.token.0.into_alloy(),
+                    isBuffer: is_buffer,
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        // Routes without a buffer step get encoded per pool by the driver.
+        buffered.then_some(Path {
+            input: first.input,
+            output: last.output,
+            steps,
+        })
+    }
+
+    fn swap(&self, path: Path) -> solution::Interaction {
+        let calldata = swapExactInCall {
+            paths: vec![SwapPathExactAmountIn {
+                tokenIn: path.input.token.0.into_alloy(),
+                steps: path.steps,
+                exactAmountIn: path.input.amount.into_alloy(),
+                minAmountOut: path.output.amount.into_alloy(),
+            }],
+            deadline: primitives::U256::ONE << 255,
+            wethIsEth: false,
+            userData: primitives::Bytes::new(),
+        }
+        .abi_encode();
+
+        solution::Interaction::Custom(solution::CustomInteraction {
+            target: self.address.0,
+            value: eth::Ether(eth::U256::zero()),
+            calldata,
+            internalize: false,
+            inputs: vec![path.input],
+            outputs: vec![path.output],
+            allowances: vec![solution::Allowance {
+                spender: self.address.0,
+                asset: path.input,
+            }],
+        })
+    }
+}
+
+struct Path {
+    input: eth::Asset,
+    output: eth::Asset,
+    steps: Vec<SwapPathStep>,
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::domain::liquidity::{erc4626, weighted_product},
+    };
+
+    fn token(byte: u8) -> eth::TokenAddress {
+        eth::TokenAddress(eth::H160::repeat_byte(byte))
+    }
+
+    fn asset(byte: u8, amount: u64) -> eth::Asset {
+        eth::Asset {
+            token: token(byte),
+            amount: amount.into(),
+        }
+    }
+
+    fn hop(
+        state: liquidity::State,
+        balancer_pool_id: Option<eth::H256>,
+        input: eth::Asset,
+        output: eth::Asset,
+    ) -> solution::Interaction {
+        solution::Interaction::Liquidity(Box::new(solution::LiquidityInteraction {
+            liquidity: liquidity::Liquidity {
+                id: liquidity::Id("0".to_owned()),
+                address: eth::H160::repeat_byte(0xee),
+                balancer_pool_id,
+                gas: eth::Gas(100_000.into()),
+                state,
+            },
+            input,
+            output,
+            internalize: false,
+        }))
+    }
+
+    fn wrap(input: eth::Asset, output: eth::Asset) -> solution::Interaction {
+        hop(
+            liquidity::State::Erc4626(erc4626::Edge {
+                asset: input.token,
+                vault: output.token,
+            }),
+            None,
+            input,
+            output,
+        )
+    }
+
+    fn weighted(
+        balancer_pool_id: Option<eth::H256>,
+        input: eth::Asset,
+        output: eth::Asset,
+    ) -> solution::Interaction {
+        let reserve = |asset| weighted_product::Reserve {
+            asset,
+            weight: eth::Rational::new_raw(1.into(), 2.into()),
+            scale: liquidity::ScalingFactor::new(eth::Rational::new_raw(1.into(), 1.into()))
+                .unwrap(),
+            rate: eth::Rational::new_raw(1.into(), 1.into()),
+        };
+        hop(
+            liquidity::State::WeightedProduct(weighted_product::Pool {
+                reserves: weighted_product::Reserves::new(vec![reserve(input), reserve(output)])
+                    .unwrap(),
+                fee: eth::Rational::new_raw(0.into(), 1.into()),
+                version: weighted_product::Version::V3Plus,
+            }),
+            balancer_pool_id,
+            input,
+            output,
+        )
+    }
+
+    #[test]
+    fn encodes_boosted_route_as_single_swap() {
+        let router = BatchRouter::new(eth::Address(eth::H160::repeat_byte(0xaa)), [token(2)]);
+        let interactions = router.encode(vec![
+            wrap(asset(1, 100), asset(2, 90)),
+            weighted(None, asset(2, 90), asset(3, 80)),
+        ]);
+
+        let [solution::Interaction::Custom(swap)] = &interactions[..] else {
+            panic!("expected a single batch router swap");
+        };
+        assert_eq!(swap.target, eth::H160::repeat_byte(0xaa));
+        assert_eq!(swap.inputs[0].token, token(1));
+        assert_eq!(swap.inputs[0].amount, 100.into());
+        assert_eq!(swap.outputs[0].token, token(3));
+        assert_eq!(swap.outputs[0].amount, 80.into());
+        assert_eq!(swap.allowances[0].spender, eth::H160::repeat_byte(0xaa));
+
+        let call = swapExactInCall::abi_decode(&swap.calldata).unwrap();
+        let steps = &call.paths[0].steps;
+        assert_eq!(steps.len(), 2);
+        assert_eq!(steps[0].pool, token(2).0.into_alloy());
+        assert!(steps[0].isBuffer);
+        assert_eq!(steps[1].pool, eth::H160::repeat_byte(0xee).into_alloy());
+        assert!(!steps[1].isBuffer);
+        assert_eq!(call.paths[0].minAmountOut, primitives::U256::from(80));
+    }
+
+    #[test]
+    fn keeps_routes_that_cannot_use_buffers() {
+        let router = BatchRouter::new(eth::Address(eth::H160::repeat_byte(0xaa)), [token(2)]);
+
+        // no buffer for the wrapped token
+        let interactions = router.encode(vec![
+            wrap(asset(1, 100), asset(4, 90)),
+            weighted(None, asset(4, 90), asset(3, 80)),
+        ]);
+        assert_eq!(interactions.len(), 2);
+
+        // Balancer V2 pool
+        let interactions = router.encode(vec![
+            wrap(asset(1, 100), asset(2, 90)),
+            weighted(Some(eth::H256::repeat_byte(1)), asset(2, 90), asset(3, 80)),
+        ]);
+        assert_eq!(interactions.len(), 2);
+
+        // no buffer step
+        let interactions = router.encode(vec![weighted(None, asset(2, 90), asset(3, 80))]);
+        assert!(matches!(
+            &interactions[..],
+            [solution::Interaction::Liquidity(_)]
+        ));
+    }
+}
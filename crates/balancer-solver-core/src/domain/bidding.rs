@@ -0,0 +1,227 @@
+//! Rival-aware bidding.
+//!
+//! The solver competition is won by the solution with the highest score, that
+//! is the surplus it delivers to users. Keeping a share of the surplus as
+//! solver margin therefore trades the value of a win off against the chance
+//! of winning. The calibration models the distribution of the best rival
+//! scores per token pair and order size from the data of past competitions,
+//! and picks the surplus share maximizing the expected kept surplus instead
+//! of keeping a fixed share.
+
+use {
+    crate::domain::{auction, liquidity, order, solution},
+    ethereum_types::U256,
+    std::{
+        collections::{HashMap, VecDeque},
+        sync::Mutex,
+    },
+};
+
+pub struct Config {
+    /// The largest share of the surplus that may be kept.
+    pub max_surplus_share: solution::SurplusShare,
+    /// The number of rival scores a bucket needs before it is used.
+    pub min_samples: usize,
+    /// The number of most recent rival scores kept per bucket.
+    pub max_samples: usize,
+}
+
+/// The number of recent auctions whose orders are kept for attributing the
+/// competition data, which is only available a while after solving.
+const MAX_AUCTIONS: usize = 64;
+
+/// The step between the candidate surplus shares.
+const STEP_BPS: u32 = 100;
+
+/// The order sizes are bucketed by their order of magnitude in ETH, clamped
+/// to this range.
+const SIZES: std::ops::RangeInclusive<i32> = -3..=3;
+
+/// Calibration of the surplus share on the rival scores of past competitions.
+pub struct Calibration {
+    config: Config,
+    inner: Mutex<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    /// The buckets and native sell values of the orders of recent auctions.
+    auctions: VecDeque<(i64, HashMap<order::Uid, (Bucket, f64)>)>,
+    /// The best rival scores as a fraction of the native value of the sold
+    /// tokens, most recent last.
+    rates: HashMap<Bucket, VecDeque<f64>>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+struct Bucket {
+    pair: liquidity::TokenPair,
+    size: i32,
+}
+
+impl Bucket {
+    fn new(order: &order::Order, value: f64) -> Option<Self> {
+        let size = (value / 1e18).log10().floor() as i32;
+        Some(Self {
+            pair: liquidity::TokenPair::new(order.sell.token, order.buy.token)?,
+            size: size.clamp(*SIZES.start(), *SIZES.end()),
+        })
+    }
+}
+
+impl Calibration {
+    pub fn new(config: Config) -> Self {
+        Self {
+            config,
+            inner: Default::default(),
+        }
+    }
+
+    /// Records the orders of a competition auction, for attributing its
+    /// competition data to them later on.
+    pub fn record_auction(&self, auction: &auction::Auction) {
+        let auction::Id::Solve(id) = auction.id else {
+            return;
+        };
+        let orders = auction
+            .orders
+            .iter()
+            .filter_map(|order| {
+                let value = sell_value(&auction.tokens, order)?;
+                Some((order.uid, (Bucket::new(order, value)?, value)))
+            })
+            .collect();
+
+        let mut inner = self.inner.lock().unwrap();
+        inner.auctions.push_back((id, orders));
+        while inner.auctions.len() > MAX_AUCTIONS {
+            inner.auctions.pop_front();
+        }
+    }
+
+    /// Records the best rival scores of the orders traded in the competition
+    /// of an auction.
+    pub fn record_competition(&self, auction: i64, scores: &HashMap<order::Uid, U256>) {
+        let mut inner = self.inner.lock().unwrap();
+        let inner = &mut *inner;
+        let Some((_, orders)) = inner.auctions.iter().find(|(id, _)| *id == auction) else {
+            return;
+        };
+        for (uid, score) in scores {
+            let Some((bucket, value)) = orders.get(uid) else {
+                continue;
+            };
+            let rates = inner.rates.entry(*bucket).or_default();
+            rates.push_back(score.to_f64_lossy() / value);
+            while rates.len() > self.config.max_samples {
+                rates.pop_front();
+            }
+        }
+    }
+
+    /// Returns the surplus share maximizing the expected kept surplus of the
+    /// single order solution, or `None` if there are not enough rival scores
+    /// for its token pair and size.
+    pub fn surplus_share(
+        &self,
+        tokens: &auction::Tokens,
+        single: &solution::Single,
+    ) -> Option<solution::SurplusShare> {
+        let value = sell_value(tokens, &single.order)?;
+        let surplus = surplus(tokens, single)?;
+        let bucket = Bucket::new(&single.order, value)?;
+
+        let inner = self.inner.lock().unwrap();
+        let rates = inner.rates.get(&bucket)?;
+        if rates.len() < self.config.min_samples {
+            return None;
+        }
+        let bps = optimal_bps(
+            rates.iter().copied(),
+            surplus / value,
+            self.config.max_surplus_share.bps(),
+        );
+        solution::SurplusShare::new(bps)
+    }
+}
+
+/// Picks the share in basis points maximizing the kept share of the surplus
+/// times the probability of beating the best rival score, both as fractions
+/// of the native value of the sold tokens. Ties go to the smaller share.
+fn optimal_bps(rates: impl Iterator<Item = f64> + Clone, surplus: f64, max_bps: u32) -> u32 {
+    let samples = rates.clone().count() as f64;
+    let expected = |bps: u32| {
+        let share = f64::from(bps) / 10_000.;
+        let score = (1. - share) * surplus;
+        let wins = rates.clone().filter(|rate| *rate < score).count() as f64;
+        share * surplus * wins / samples
+    };
+
+    let mut best = (0, 0.);
+    for bps in (STEP_BPS..=max_bps).step_by(STEP_BPS as usize) {
+        let value = expected(bps);
+        if value > best.1 {
+            best = (bps, value);
+        }
+    }
+    best.0
+}
+
+/// The native value of the tokens sold by the order.
+fn sell_value(tokens: &auction::Tokens, order: &order::Order) -> Option<f64> {
+    let value = tokens
+        .reference_price(&order.sell.token)?
+        .native_value(order.sell.amount)?;
+    (!value.0.is_zero()).then(|| value.0.to_f64_lossy())
+}
+
+/// The native value of the price improvement of the single order solution
+/// over the order's limit price.
+fn surplus(tokens: &auction::Tokens, single: &solution::Single) -> Option<f64> {
+    let order = &single.order;
+    let (token, amount) = match order.side {
+        order::Side::Sell => {
+            let limit = order
+                .buy
+                .amount
+                .checked_mul(single.input.amount)?
+                .checked_div(order.sell.amount)?;
+            (order.buy.token, single.output.amount.checked_sub(limit)?)
+        }
+        order::Side::Buy => {
+            let limit = order
+                .sell
+                .amount
+                .checked_mul(single.output.amount)?
+                .checked_div(order.buy.amount)?;
+            (order.sell.token, limit.checked_sub(single.input.amount)?)
+        }
+    };
+    let value = tokens.reference_price(&token)?.native_value(amount)?;
+    Some(value.0.to_f64_lossy())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn optimal_share_undercuts_rivals() {
+        // Rivals score half of the surplus in every competition, so keeping
+        // just under half of it wins all of them.
+        let rates = std::iter::repeat_n(0.005, 10);
+        assert_eq!(optimal_bps(rates, 0.01, 10_000), 4_900);
+
+        // Rivals beat the surplus half of the time and score nothing
+        // otherwise, so keeping nearly everything wins the other half.
+        let rates = [0., 0.02].into_iter().cycle().take(10);
+        assert_eq!(optimal_bps(rates, 0.01, 10_000), 9_900);
+
+        // The share is capped.
+        let rates = std::iter::repeat_n(0.005, 10);
+        assert_eq!(optimal_bps(rates, 0.01, 2_000), 2_000);
+
+        // Without any chance of winning nothing is kept.
+        let rates = std::iter::repeat_n(0.02, 10);
+        assert_eq!(optimal_bps(rates, 0.01, 10_000), 0);
+    }
+}
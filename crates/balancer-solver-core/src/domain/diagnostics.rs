@@ -0,0 +1,209 @@
+//! Diagnostics of unsolved auctions.
+//!
+//! When an auction goes unsolved, the reason each of its orders was not solved
+//! gets reported, so that drivers and dashboards can aggregate why auctions go
+//! unsolved. The reasons are derived from the auction after solving instead of
+//! being tracked while routing, so that diagnosing does not slow down solving.
+
+use {
+    crate::domain::{auction, eth, liquidity, order},
+    std::collections::{HashMap, HashSet},
+};
+
+/// The reason an order was not solved.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Reason {
+    /// No liquidity connects the traded tokens within the routing
+    /// constraints.
+    NoLiquidity,
+    /// Routes exist, but none of them satisfies the order's limit price.
+    LimitPriceUnreachable,
+    /// The deadline was reached before the order was solved.
+    DeadlineExhausted,
+    /// The order trades a denied or quarantined token.
+    AllPoolsDenied,
+}
+
+/// An order that was not solved.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Unsolved {
+    pub order: order::Uid,
+    pub reason: Reason,
+}
+
+/// The information needed for diagnosing an auction after it was handed off
+/// for solving.
+pub struct Context {
+    denied: Vec<order::Uid>,
+    /// The remaining orders and whether their tokens are connected by the
+    /// auction's liquidity.
+    orders: Vec<(order::Uid, bool)>,
+}
+
+impl Context {
+    /// Captures the diagnostics context of the auction. `denied` are the
+    /// orders removed from the auction for trading denied tokens.
+    pub fn new(
+        auction: &auction::Auction,
+        denied: Vec<order::Uid>,
+        base_tokens: &HashSet<eth::TokenAddress>,
+        max_hops: usize,
+    ) -> Self {
+        let graph = Graph::new(&auction.liquidity);
+        Self {
+            denied,
+            orders: auction
+                .orders
+                .iter()
+                .map(|order| {
+                    let connected =
+                        graph.connects(order.sell.token, order.buy.token, base_tokens, max_hops);
+                    (order.uid, connected)
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the reasons all orders went unsolved, given whether the
+    /// deadline was reached while solving.
+    pub fn diagnose(self, deadline_exhausted: bool) -> Vec<Unsolved> {
+        let denied = self.denied.into_iter().map(|order| Unsolved {
+            order,
+            reason: Reason::AllPoolsDenied,
+        });
+        let remaining = self.orders.into_iter().map(|(order, connected)| Unsolved {
+            order,
+            reason: match (connected, deadline_exhausted) {
+                (false, _) => Reason::NoLiquidity,
+                (true, true) => Reason::DeadlineExhausted,
+                (true, false) => Reason::LimitPriceUnreachable,
+            },
+        });
+        denied.chain(remaining).collect()
+    }
+}
+
+/// The tokens connected by liquidity.
+struct Graph(HashMap<eth::TokenAddress, HashSet<eth::TokenAddress>>);
+
+impl Graph {
+    fn new(liquidity: &[liquidity::Liquidity]) -> Self {
+        let mut edges = HashMap::<_, HashSet<_>>::new();
+        for liquidity in liquidity {
+            let tokens = liquidity.tokens();
+            for a in &tokens {
+                for b in &tokens {
+                    if a != b {
+                        edges.entry(*a).or_default().insert(*b);
+                    }
+                }
+            }
+        }
+        Self(edges)
+    }
+
+    /// Returns whether a path of at most `max_hops` intermediate base tokens
+    /// connects the tokens, mirroring the paths considered by the router.
+    fn connects(
+        &self,
+        sell: eth::TokenAddress,
+        buy: eth::TokenAddress,
+        base_tokens: &HashSet<eth::TokenAddress>,
+        max_hops: usize,
+    ) -> bool {
+        let mut visited = HashSet::from([sell]);
+        let mut frontier = vec![sell];
+        for _ in 0..=max_hops {
+            let mut next = Vec::new();
+            for token in frontier {
+                let Some(neighbours) = self.0.get(&token) else {
+                    continue;
+                };
+                if neighbours.contains(&buy) {
+                    return true;
+                }
+                next.extend(
+                    neighbours
+                        .iter()
+                        .filter(|neighbour| base_tokens.contains(neighbour))
+                        .filter(|neighbour| visited.insert(**neighbour))
+                        .copied(),
+                );
+            }
+            frontier = next;
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::domain::liquidity::constant_product};
+
+    fn token(token: u64) -> eth::TokenAddress {
+        eth::TokenAddress(eth::H160::from_low_u64_be(token))
+    }
+
+    fn pool(a: u64, b: u64) -> liquidity::Liquidity {
+        let asset = |token| eth::Asset {
+            token: self::token(token),
+            amount: 1000.into(),
+        };
+        liquidity::Liquidity {
+            id: liquidity::Id(format!("{a}-{b}")),
+            address: Default::default(),
+            balancer_pool_id: None,
+            gas: eth::Gas(100_000.into()),
+            state: liquidity::State::ConstantProduct(constant_product::Pool {
+                reserves: constant_product::Reserves::new(asset(a), asset(b)).unwrap(),
+                fee: eth::Rational::new_raw(3.into(), 1000.into()),
+            }),
+        }
+    }
+
+    #[test]
+    fn connects_tokens_through_base_tokens() {
+        let graph = Graph::new(&[pool(1, 2), pool(2, 3), pool(3, 4)]);
+        let base_tokens = HashSet::from([token(2), token(3)]);
+
+        assert!(graph.connects(token(1), token(2), &base_tokens, 0));
+        assert!(!graph.connects(token(1), token(3), &base_tokens, 0));
+        assert!(graph.connects(token(1), token(3), &base_tokens, 1));
+        assert!(!graph.connects(token(1), token(4), &base_tokens, 1));
+        assert!(graph.connects(token(1), token(4), &base_tokens, 2));
+        // only base tokens can be intermediate hops
+        assert!(!graph.connects(token(1), token(3), &HashSet::new(), 2));
+        assert!(!graph.connects(token(1), token(5), &base_tokens, 2));
+    }
+
+    #[test]
+    fn diagnoses_unsolved_orders() {
+        let uid = |byte| order::Uid([byte; 56]);
+        let context = |deadline_exhausted| {
+            Context {
+                denied: vec![uid(1)],
+                orders: vec![(uid(2), false), (uid(3), true)],
+            }
+            .diagnose(deadline_exhausted)
+        };
+
+        assert_eq!(
+            context(false),
+            vec![
+                Unsolved {
+                    order: uid(1),
+                    reason: Reason::AllPoolsDenied,
+                },
+                Unsolved {
+                    order: uid(2),
+                    reason: Reason::NoLiquidity,
+                },
+                Unsolved {
+                    order: uid(3),
+                    reason: Reason::LimitPriceUnreachable,
+                },
+            ]
+        );
+        assert_eq!(context(true)[2].reason, Reason::DeadlineExhausted);
+    }
+}
@@ -47,8 +47,16 @@ impl From<i64> for SignedGas {
     }
 }
 
+impl std::ops::Neg for SignedGas {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self(self.0.saturating_neg())
+    }
+}
+
 /// Gas amount.
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Gas(pub U256);
 
 impl std::ops::Add<SignedGas> for Gas {
@@ -86,7 +94,7 @@ pub struct Tx {
 
 /// An arbitrary ethereum interaction that is required for the settlement
 /// execution.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Interaction {
     pub target: Address,
     pub value: Ether,
@@ -0,0 +1,118 @@
+//! Lifecycle events of the auctions handled by the solver.
+//!
+//! Events get published on an in-memory bus that subscribers, like the
+//! `/events` websocket, can follow live. Publishing never blocks, and
+//! subscribers falling behind miss the oldest events instead of holding up
+//! solving.
+
+use {
+    crate::domain::auction,
+    std::time::Duration,
+    tokio::sync::broadcast,
+};
+
+/// The number of events buffered for subscribers that fall behind.
+const CAPACITY: usize = 1024;
+
+/// A step in handling an auction.
+#[derive(Clone, Debug)]
+pub enum Event {
+    /// A solve request was received.
+    AuctionReceived {
+        auction: auction::Id,
+        orders: usize,
+        tokens: usize,
+    },
+    /// The liquidity for the auction is available, either included in the
+    /// auction or fetched from the liquidity driver.
+    LiquidityFetched {
+        auction: auction::Id,
+        liquidity: usize,
+        deadline_exceeded: bool,
+    },
+    /// The solver finished computing solutions.
+    SolutionsComputed {
+        auction: auction::Id,
+        solutions: usize,
+    },
+    /// The solutions were returned to the caller.
+    SolutionsReturned {
+        auction: auction::Id,
+        solutions: usize,
+        elapsed: Duration,
+    },
+    /// The returned solutions were verified against on-chain quotes.
+    VerificationFinished {
+        auction: auction::Id,
+        solutions: usize,
+        /// The number of swaps that could not be quoted.
+        quote_errors: usize,
+    },
+}
+
+/// An event along with the time it was published at.
+#[derive(Clone, Debug)]
+pub struct Entry {
+    pub time: chrono::DateTime<chrono::Utc>,
+    pub event: Event,
+}
+
+#[derive(Clone)]
+pub struct Bus(broadcast::Sender<Entry>);
+
+impl Bus {
+    pub fn new() -> Self {
+        Self(broadcast::channel(CAPACITY).0)
+    }
+
+    /// Publishes the event to all current subscribers.
+    pub fn publish(&self, event: Event) {
+        // Sending only fails without subscribers, in which case nobody is
+        // interested in the event.
+        let _ = self.0.send(Entry {
+            time: chrono::Utc::now(),
+            event,
+        });
+    }
+
+    /// Subscribes to all events published from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<Entry> {
+        self.0.subscribe()
+    }
+}
+
+impl Default for Bus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn delivers_events_published_after_subscribing() {
+        let bus = Bus::new();
+        bus.publish(Event::SolutionsComputed {
+            auction: auction::Id::Solve(1),
+            solutions: 1,
+        });
+
+        let mut events = bus.subscribe();
+        bus.publish(Event::SolutionsComputed {
+            auction: auction::Id::Solve(2),
+            solutions: 3,
+        });
+
+        let entry = events.recv().await.unwrap();
+        assert!(matches!(
+            entry.event,
+            Event::SolutionsComputed {
+                auction: auction::Id::Solve(2),
+                solutions: 3,
+            }
+        ));
+        assert!(events.try_recv().is_err());
+    }
+}
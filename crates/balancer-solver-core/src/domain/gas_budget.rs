@@ -0,0 +1,106 @@
+//! Sizing of solutions to the amount of gas that can be spent settling them.
+//!
+//! Solutions using more gas than a settlement transaction may consume can
+//! never be executed. The budget is the smaller of the configured cap and the
+//! gas limit of the auction. Every proposed solution gets settled in a
+//! transaction of its own, so the budget applies to each of them separately:
+//! routes exceeding it are discarded in favour of smaller ones, and solutions
+//! are only merged as long as the joint solution still fits.
+
+use crate::domain::{auction, eth, solution};
+
+#[derive(Clone, Copy, Debug)]
+pub struct Budget(pub eth::Gas);
+
+impl Budget {
+    /// Returns the gas budget for an auction, or `None` if neither a cap is
+    /// configured nor the auction specifies a gas limit.
+    pub fn new(cap: Option<eth::Gas>, auction: &auction::Auction) -> Option<Self> {
+        let limit = match (cap, auction.gas_limit) {
+            (Some(cap), Some(limit)) => cap.min(limit),
+            (cap, limit) => cap.or(limit)?,
+        };
+        Some(Self(limit))
+    }
+
+    /// Returns whether a solution using the specified amount of gas fits into
+    /// the budget.
+    pub fn fits(&self, gas: eth::Gas) -> bool {
+        gas <= self.0
+    }
+
+    /// Removes the solutions that don't fit into the budget on their own.
+    /// Solutions without a gas estimate can't be shown to fit, so they get
+    /// removed as well.
+    pub fn trim(&self, solutions: &mut Vec<solution::Solution>) {
+        solutions.retain(|solution| {
+            let keep = solution.gas.is_some_and(|gas| self.fits(gas));
+            if !keep {
+                tracing::debug!(
+                    id = ?solution.id,
+                    gas = ?solution.gas,
+                    budget = ?self.0,
+                    "trimming solution exceeding gas budget"
+                );
+            }
+            keep
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::domain::order};
+
+    fn solution(id: u64, gas: Option<u64>) -> solution::Solution {
+        let order = order::Order {
+            uid: order::Uid([id as u8; 56]),
+            sell: eth::Asset {
+                token: eth::TokenAddress(eth::H160::from_low_u64_be(1)),
+                amount: 1.into(),
+            },
+            buy: eth::Asset {
+                token: eth::TokenAddress(eth::H160::from_low_u64_be(2)),
+                amount: 1.into(),
+            },
+            ..Default::default()
+        };
+        solution::Solution {
+            id: solution::Id(id),
+            trades: vec![solution::Trade::Fulfillment(
+                solution::Fulfillment::fill(order).unwrap(),
+            )],
+            gas: gas.map(|gas| eth::Gas(gas.into())),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn applies_budget_to_each_solution() {
+        let budget = Budget(eth::Gas(300_000.into()));
+        let mut solutions = vec![
+            solution(0, Some(100_000)),
+            solution(1, Some(300_000)),
+            solution(2, Some(300_001)),
+            solution(3, Some(200_000)),
+        ];
+        budget.trim(&mut solutions);
+
+        // the solutions get settled separately, so they only need to fit on
+        // their own
+        let ids = solutions
+            .iter()
+            .map(|solution| solution.id.0)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, [0, 1, 3]);
+    }
+
+    #[test]
+    fn drops_solutions_without_gas_estimate() {
+        let budget = Budget(eth::Gas(100_000.into()));
+        let mut solutions = vec![solution(0, None), solution(1, Some(100_000))];
+        budget.trim(&mut solutions);
+        assert_eq!(solutions.len(), 1);
+        assert_eq!(solutions[0].id.0, 1);
+    }
+}
@@ -0,0 +1,626 @@
+//! Accounting of the token inventory held by the settlement contract.
+//!
+//! The settlement contract holds buffers of tokens that interactions can be
+//! netted against: an internalized interaction is not executed on-chain and
+//! instead pays its outputs from the buffers while keeping its inputs, saving
+//! the gas of the interaction. Without inventory accounting, internalization
+//! may drain a buffer completely. The inventory keeps a configured reserve
+//! of each buffer out of internalization, so that the reserved amounts stay
+//! available for the most valuable uses, and periodically reports how the
+//! buffers should be rebalanced.
+//!
+//! The inventory can additionally fill orders itself: when the on-chain
+//! liquidity trades a pair at a worse price than the configured reference
+//! prices, the part of the order the inventory can cover gets filled from the
+//! inventory at the reference prices instead, and only the remainder gets
+//! routed. Fills are paid either from the settlement contract buffers, which
+//! requires no interactions, or from an account that approved the settlement
+//! contract, in which case the fill is settled by transferring the tokens
+//! between the settlement contract and the account.
+
+use {
+    crate::domain::{auction, eth, order, solution},
+    alloy::sol_types::SolCall,
+    contracts::alloy::ERC20::ERC20::{transferCall, transferFromCall},
+    ethrpc::alloy::conversions::IntoAlloy,
+    std::{
+        cmp,
+        collections::HashMap,
+        sync::{Arc, Mutex, Weak},
+        time::Duration,
+    },
+};
+
+pub struct Config {
+    /// The bounds to keep the settlement contract balances of tokens in.
+    pub buffers: HashMap<eth::TokenAddress, Bounds>,
+    /// How often the rebalancing report gets logged.
+    pub report_interval: Duration,
+    /// Filling orders from the inventory, if enabled.
+    pub fills: Option<Fills>,
+}
+
+/// The configuration of filling orders from the inventory.
+#[derive(Clone, Debug)]
+pub struct Fills {
+    /// The account holding the inventory, or `None` to fill orders from the
+    /// settlement contract buffers. The account must have approved the
+    /// settlement contract to transfer its tokens.
+    pub account: Option<eth::Address>,
+    /// The settlement contract the fills get transferred to.
+    pub settlement: eth::Address,
+    /// The tokens the inventory trades.
+    pub tokens: HashMap<eth::TokenAddress, FillToken>,
+}
+
+/// How the inventory trades a token.
+#[derive(Clone, Copy, Debug)]
+pub struct FillToken {
+    /// The reference price the inventory trades the token at.
+    pub price: auction::Price,
+    /// The maximum amount of the token the inventory pays out per fill.
+    pub max_amount: eth::U256,
+}
+
+/// The part of an order filled from the inventory at the reference prices.
+#[derive(Clone, Copy, Debug)]
+pub struct Fill {
+    /// The sell tokens the inventory receives.
+    pub input: eth::Asset,
+    /// The buy tokens the inventory pays.
+    pub output: eth::Asset,
+}
+
+/// The bounds of the settlement contract balance of a token.
+#[derive(Clone, Copy, Debug)]
+pub struct Bounds {
+    /// The balance that is reserved and never used for internalization.
+    /// Balances below it should be topped up.
+    pub min: eth::U256,
+    /// The balance above which the excess should be withdrawn.
+    pub max: eth::U256,
+}
+
+/// A suggested rebalancing of a settlement contract buffer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rebalance {
+    /// The buffer is below its reserve and should be topped up by the amount.
+    TopUp {
+        token: eth::TokenAddress,
+        amount: eth::U256,
+    },
+    /// The buffer exceeds its maximum and the amount should be withdrawn.
+    Withdraw {
+        token: eth::TokenAddress,
+        amount: eth::U256,
+    },
+}
+
+/// The amounts of a token that internalized interactions of proposed
+/// solutions netted against the inventory.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Flow {
+    /// The amount kept by the settlement contract as interaction input.
+    pub received: eth::U256,
+    /// The amount paid out by the settlement contract as interaction output.
+    pub paid: eth::U256,
+}
+
+pub struct Inventory {
+    buffers: HashMap<eth::TokenAddress, Bounds>,
+    fills: Option<Fills>,
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    /// The latest observed settlement contract balance of each token.
+    balances: HashMap<eth::TokenAddress, eth::U256>,
+    /// The flows since the last report.
+    flows: HashMap<eth::TokenAddress, Flow>,
+}
+
+impl Inventory {
+    /// Creates a new inventory, spawning a background task periodically
+    /// logging the rebalancing report.
+    pub fn new(config: Config) -> Arc<Self> {
+        let inventory = Arc::new(Self {
+            buffers: config.buffers,
+            fills: config.fills,
+            state: Default::default(),
+        });
+        tokio::spawn(report(Arc::downgrade(&inventory), config.report_interval));
+        inventory
+    }
+
+    /// Records the settlement contract balances of the auction's tokens and
+    /// returns the tokens with the reserves deducted from the balances
+    /// available for internalization.
+    pub fn observe(&self, tokens: &auction::Tokens) -> auction::Tokens {
+        let mut state = self.state.lock().unwrap();
+        auction::Tokens(
+            tokens
+                .0
+                .iter()
+                .map(|(address, token)| {
+                    state.balances.insert(*address, token.available_balance);
+                    let reserve = self
+                        .buffers
+                        .get(address)
+                        .map(|bounds| bounds.min)
+                        .unwrap_or_default();
+                    let token = auction::Token {
+                        available_balance: token.available_balance.saturating_sub(reserve),
+                        ..token.clone()
+                    };
+                    (*address, token)
+                })
+                .collect(),
+        )
+    }
+
+    /// Accounts the internalized interactions of the proposed solutions.
+    pub fn record_solutions(&self, solutions: &[solution::Solution]) {
+        let mut state = self.state.lock().unwrap();
+        for solution in solutions {
+            for interaction in &solution.interactions {
+                let (inputs, outputs) = match interaction {
+                    solution::Interaction::Liquidity(interaction) if interaction.internalize => (
+                        std::slice::from_ref(&interaction.input),
+                        std::slice::from_ref(&interaction.output),
+                    ),
+                    solution::Interaction::Custom(interaction) if interaction.internalize => {
+                        (&interaction.inputs[..], &interaction.outputs[..])
+                    }
+                    _ => continue,
+                };
+                for input in inputs {
+                    let flow = state.flows.entry(input.token).or_default();
+                    flow.received = flow.received.saturating_add(input.amount);
+                }
+                for output in outputs {
+                    let flow = state.flows.entry(output.token).or_default();
+                    flow.paid = flow.paid.saturating_add(output.amount);
+                }
+            }
+        }
+    }
+
+    /// Returns the suggested rebalancing of the buffers with configured bounds
+    /// based on their latest observed balances. Buffers that were not
+    /// observed yet are skipped.
+    pub fn rebalancing(&self) -> Vec<Rebalance> {
+        let state = self.state.lock().unwrap();
+        let mut suggestions = self
+            .buffers
+            .iter()
+            .filter_map(|(token, bounds)| {
+                let balance = *state.balances.get(token)?;
+                if balance < bounds.min {
+                    Some(Rebalance::TopUp {
+                        token: *token,
+                        amount: bounds.min - balance,
+                    })
+                } else if balance > bounds.max {
+                    Some(Rebalance::Withdraw {
+                        token: *token,
+                        amount: balance - bounds.max,
+                    })
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+        suggestions.sort_by_key(|suggestion| match suggestion {
+            Rebalance::TopUp { token, .. } | Rebalance::Withdraw { token, .. } => *token,
+        });
+        suggestions
+    }
+
+    /// Returns the flows accounted since the last call, resetting them.
+    pub fn take_flows(&self) -> HashMap<eth::TokenAddress, Flow> {
+        std::mem::take(&mut self.state.lock().unwrap().flows)
+    }
+
+    /// Returns the part of a request selling `sell` for `buy` to fill from
+    /// the inventory, given the `input` and `output` of the route found for
+    /// it over the on-chain liquidity. Orders only get filled if the route
+    /// trades at a worse price than the reference prices, and at most up to
+    /// the available amount of the buy token. Fills are priced at the
+    /// reference prices, rounding in favour of the inventory.
+    pub fn fill(
+        &self,
+        side: order::Side,
+        sell: eth::Asset,
+        buy: eth::Asset,
+        (input, output): (eth::Asset, eth::Asset),
+        tokens: &auction::Tokens,
+    ) -> Option<Fill> {
+        let fills = self.fills.as_ref()?;
+        let sell_price = fills.tokens.get(&sell.token)?.price.0.0;
+        let buy_token = fills.tokens.get(&buy.token)?;
+        let buy_price = buy_token.price.0.0;
+
+        // The route trades at a worse price if its output is worth less than
+        // its input.
+        if output.amount.checked_mul(buy_price)? >= input.amount.checked_mul(sell_price)? {
+            return None;
+        }
+
+        let available = match fills.account {
+            Some(_) => buy_token.max_amount,
+            None => cmp::min(
+                buy_token.max_amount,
+                tokens.get(&buy.token)?.available_balance,
+            ),
+        };
+        let (sold, bought) = match side {
+            order::Side::Sell => {
+                let max_sold = available.checked_mul(buy_price)?.checked_div(sell_price)?;
+                let sold = cmp::min(sell.amount, max_sold);
+                let bought = sold.checked_mul(sell_price)?.checked_div(buy_price)?;
+                (sold, bought)
+            }
+            order::Side::Buy => {
+                let bought = cmp::min(buy.amount, available);
+                let sold = bought
+                    .checked_mul(buy_price)?
+                    .checked_add(sell_price.checked_sub(1.into())?)?
+                    .checked_div(sell_price)?;
+                (sold, bought)
+            }
+        };
+        if sold.is_zero() || bought.is_zero() {
+            return None;
+        }
+        Some(Fill {
+            input: eth::Asset {
+                token: sell.token,
+                amount: sold,
+            },
+            output: eth::Asset {
+                token: buy.token,
+                amount: bought,
+            },
+        })
+    }
+
+    /// The interactions settling the fill. Fills from the settlement contract
+    /// buffers need none, while fills from an account transfer the bought
+    /// tokens from the account and the sold tokens to it.
+    pub fn interactions(&self, fill: &Fill) -> Vec<solution::Interaction> {
+        let Some(Fills {
+            account: Some(account),
+            settlement,
+            ..
+        }) = &self.fills
+        else {
+            return Vec::new();
+        };
+        let interaction = |token: eth::TokenAddress, calldata, inputs, outputs| {
+            solution::Interaction::Custom(solution::CustomInteraction {
+                target: eth::Address(token.0),
+                value: eth::Ether(eth::U256::zero()),
+                calldata,
+                internalize: false,
+                inputs,
+                outputs,
+                allowances: Vec::new(),
+            })
+        };
+        vec![
+            interaction(
+                fill.output.token,
+                transferFromCall {
+                    sender: account.0.into_alloy(),
+                    recipient: settlement.0.into_alloy(),
+                    amount: fill.output.amount.into_alloy(),
+                }
+                .abi_encode(),
+                Vec::new(),
+                vec![fill.output],
+            ),
+            interaction(
+                fill.input.token,
+                transferCall {
+                    recipient: account.0.into_alloy(),
+                    amount: fill.input.amount.into_alloy(),
+                }
+                .abi_encode(),
+                vec![fill.input],
+                Vec::new(),
+            ),
+        ]
+    }
+
+    /// The gas used by the interactions settling a fill.
+    pub fn fill_gas(&self) -> eth::Gas {
+        match self.fills.as_ref().and_then(|fills| fills.account) {
+            Some(_) => eth::Gas((2 * solution::ERC20_TRANSFER).into()),
+            None => eth::Gas(eth::U256::zero()),
+        }
+    }
+
+    /// Returns the tokens with the amount paid by the fill deducted from the
+    /// available balances if the fill gets paid from the settlement contract
+    /// buffers, so that the rest of the solution doesn't get internalized
+    /// against the same balance.
+    pub fn after_fill(&self, fill: &Fill, tokens: &auction::Tokens) -> Option<auction::Tokens> {
+        if self.fills.as_ref()?.account.is_some() {
+            return None;
+        }
+        let mut tokens = tokens.clone();
+        if let Some(token) = tokens.0.get_mut(&fill.output.token) {
+            token.available_balance = token.available_balance.saturating_sub(fill.output.amount);
+        }
+        Some(tokens)
+    }
+}
+
+/// Periodically logs the flows and the rebalancing suggestions of the
+/// inventory until it is dropped.
+async fn report(inventory: Weak<Inventory>, interval: Duration) {
+    loop {
+        tokio::time::sleep(interval).await;
+        let Some(inventory) = inventory.upgrade() else {
+            return;
+        };
+        for (token, flow) in inventory.take_flows() {
+            tracing::info!(?token, received = %flow.received, paid = %flow.paid, "inventory flow");
+        }
+        for suggestion in inventory.rebalancing() {
+            tracing::info!(?suggestion, "suggested inventory rebalancing");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::domain::liquidity};
+
+    fn token(token: u64) -> eth::TokenAddress {
+        eth::TokenAddress(eth::H160::from_low_u64_be(token))
+    }
+
+    fn tokens(balances: &[(u64, u64)]) -> auction::Tokens {
+        auction::Tokens(
+            balances
+                .iter()
+                .map(|(address, balance)| {
+                    let token = auction::Token {
+                        decimals: Some(18),
+                        symbol: None,
+                        reference_price: None,
+                        available_balance: (*balance).into(),
+                        trusted: true,
+                    };
+                    (self::token(*address), token)
+                })
+                .collect(),
+        )
+    }
+
+    fn inventory() -> Inventory {
+        let bounds = |min: u64, max: u64| Bounds {
+            min: min.into(),
+            max: max.into(),
+        };
+        Inventory {
+            buffers: [
+                (token(1), bounds(100, 1000)),
+                (token(2), bounds(100, 1000)),
+                (token(3), bounds(100, 1000)),
+                (token(4), bounds(100, 1000)),
+            ]
+            .into(),
+            fills: None,
+            state: Default::default(),
+        }
+    }
+
+    fn filling(account: Option<eth::Address>) -> Inventory {
+        let fill = |price: u64| FillToken {
+            price: auction::Price(eth::Ether(eth::U256::exp10(18) * price)),
+            max_amount: 1000.into(),
+        };
+        Inventory {
+            buffers: Default::default(),
+            fills: Some(Fills {
+                account,
+                settlement: eth::Address(eth::H160::from_low_u64_be(9)),
+                tokens: [(token(1), fill(1)), (token(2), fill(2))].into(),
+            }),
+            state: Default::default(),
+        }
+    }
+
+    fn asset(token: u64, amount: u64) -> eth::Asset {
+        eth::Asset {
+            token: self::token(token),
+            amount: amount.into(),
+        }
+    }
+
+    #[test]
+    fn reserves_buffers_and_suggests_rebalancing() {
+        let inventory = inventory();
+        let available = inventory.observe(&tokens(&[(1, 50), (2, 500), (3, 5000), (5, 500)]));
+
+        let balance = |token| {
+            available
+                .get(&self::token(token))
+                .unwrap()
+                .available_balance
+        };
+        assert_eq!(balance(1), 0.into());
+        assert_eq!(balance(2), 400.into());
+        assert_eq!(balance(3), 4900.into());
+        // tokens without bounds can be used entirely
+        assert_eq!(balance(5), 500.into());
+
+        assert_eq!(
+            inventory.rebalancing(),
+            vec![
+                Rebalance::TopUp {
+                    token: token(1),
+                    amount: 50.into(),
+                },
+                Rebalance::Withdraw {
+                    token: token(3),
+                    amount: 4000.into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn accounts_internalized_interactions() {
+        let inventory = inventory();
+        let interaction = |internalize| {
+            solution::Interaction::Liquidity(Box::new(solution::LiquidityInteraction {
+                liquidity: liquidity::Liquidity {
+                    id: liquidity::Id("0".to_owned()),
+                    address: Default::default(),
+                    balancer_pool_id: None,
+                    gas: eth::Gas(100_000.into()),
+                    state: liquidity::State::LimitOrder(liquidity::limit_order::LimitOrder {
+                        maker: eth::Asset {
+                            token: token(2),
+                            amount: 1000.into(),
+                        },
+                        taker: eth::Asset {
+                            token: token(1),
+                            amount: 1000.into(),
+                        },
+                        fee: liquidity::limit_order::TakerAmount(0.into()),
+                    }),
+                },
+                input: eth::Asset {
+                    token: token(1),
+                    amount: 10.into(),
+                },
+                output: eth::Asset {
+                    token: token(2),
+                    amount: 20.into(),
+                },
+                internalize,
+            }))
+        };
+        inventory.record_solutions(&[solution::Solution {
+            interactions: vec![interaction(true), interaction(false), interaction(true)],
+            ..Default::default()
+        }]);
+
+        let flows = inventory.take_flows();
+        assert_eq!(
+            flows[&token(1)],
+            Flow {
+                received: 20.into(),
+                paid: 0.into(),
+            }
+        );
+        assert_eq!(
+            flows[&token(2)],
+            Flow {
+                received: 0.into(),
+                paid: 40.into(),
+            }
+        );
+        assert!(inventory.take_flows().is_empty());
+    }
+
+    #[test]
+    fn fills_orders_at_reference_prices() {
+        let inventory = filling(None);
+        let tokens = tokens(&[(1, 0), (2, 30)]);
+        let amounts = |fill: Option<Fill>| {
+            fill.map(|fill| (fill.input.amount.as_u64(), fill.output.amount.as_u64()))
+        };
+
+        // sell orders get filled up to the buffer balance
+        let fill = inventory.fill(
+            order::Side::Sell,
+            asset(1, 100),
+            asset(2, 40),
+            (asset(1, 100), asset(2, 45)),
+            &tokens,
+        );
+        assert_eq!(amounts(fill), Some((60, 30)));
+        // buy orders get filled entirely if the buffer suffices
+        let fill = inventory.fill(
+            order::Side::Buy,
+            asset(1, 50),
+            asset(2, 20),
+            (asset(1, 45), asset(2, 20)),
+            &tokens,
+        );
+        assert_eq!(amounts(fill), Some((40, 20)));
+        // routes at better prices than the reference prices are kept
+        let fill = inventory.fill(
+            order::Side::Sell,
+            asset(1, 100),
+            asset(2, 40),
+            (asset(1, 100), asset(2, 50)),
+            &tokens,
+        );
+        assert_eq!(amounts(fill), None);
+        // tokens without reference prices are not traded
+        let fill = inventory.fill(
+            order::Side::Sell,
+            asset(1, 100),
+            asset(3, 40),
+            (asset(1, 100), asset(3, 45)),
+            &tokens,
+        );
+        assert_eq!(amounts(fill), None);
+
+        let remaining = inventory
+            .after_fill(
+                &Fill {
+                    input: asset(1, 60),
+                    output: asset(2, 30),
+                },
+                &tokens,
+            )
+            .unwrap();
+        assert_eq!(
+            remaining.get(&token(2)).unwrap().available_balance,
+            0.into()
+        );
+    }
+
+    #[test]
+    fn fills_orders_from_account() {
+        let account = eth::Address(eth::H160::from_low_u64_be(8));
+        let inventory = filling(Some(account));
+
+        // the account balance is not limited by the buffers
+        let fill = inventory
+            .fill(
+                order::Side::Sell,
+                asset(1, 100),
+                asset(2, 40),
+                (asset(1, 100), asset(2, 45)),
+                &tokens(&[]),
+            )
+            .unwrap();
+        assert_eq!(fill.input.amount, 100.into());
+        assert_eq!(fill.output.amount, 50.into());
+        assert!(inventory.after_fill(&fill, &tokens(&[])).is_none());
+
+        let interactions = inventory.interactions(&fill);
+        let [
+            solution::Interaction::Custom(pull),
+            solution::Interaction::Custom(push),
+        ] = &interactions[..]
+        else {
+            panic!("unexpected interactions: {interactions:?}");
+        };
+        assert_eq!(pull.target, eth::Address(token(2).0));
+        assert_eq!(pull.calldata[..4], transferFromCall::SELECTOR);
+        assert_eq!(pull.outputs.len(), 1);
+        assert_eq!(push.target, eth::Address(token(1).0));
+        assert_eq!(push.calldata[..4], transferCall::SELECTOR);
+        assert_eq!(push.inputs.len(), 1);
+    }
+}
@@ -0,0 +1,72 @@
+//! Curve StableSwap pool.
+
+use {
+    crate::domain::{eth, liquidity},
+    ethereum_types::H160,
+    itertools::Itertools as _,
+};
+
+/// The address Curve pools use for native ETH.
+const ETH: eth::TokenAddress = eth::TokenAddress(H160([0xee; 20]));
+
+/// State of a Curve StableSwap pool, including metapools pairing tokens with
+/// the LP token of a base pool.
+#[derive(Clone, Debug)]
+pub struct Pool {
+    /// The coins of the pool in the order of their on-chain indices.
+    pub coins: Vec<Coin>,
+    /// The amplification parameter multiplied by its precision of 100.
+    pub amplification: eth::U256,
+    /// The swap fee relative to `10^10`.
+    pub fee: eth::U256,
+    pub base_pool: Option<H160>,
+}
+
+/// A coin of a Curve pool with its balance.
+#[derive(Clone, Copy, Debug)]
+pub struct Coin {
+    pub asset: eth::Asset,
+    /// The rate normalizing the balance to 18 decimals, scaled by the exchange
+    /// rate of coins that accrue value.
+    pub rate: eth::U256,
+}
+
+impl Pool {
+    /// The denominator of the swap fee.
+    pub const FEE_DENOMINATOR: u64 = 10_000_000_000;
+
+    /// Returns the pool for the coins, or `None` if a coin is listed twice.
+    pub fn new(
+        coins: Vec<Coin>,
+        amplification: eth::U256,
+        fee: eth::U256,
+        base_pool: Option<H160>,
+    ) -> Option<Self> {
+        coins
+            .iter()
+            .map(|coin| coin.asset.token)
+            .all_unique()
+            .then_some(Self {
+                coins,
+                amplification,
+                fee,
+                base_pool,
+            })
+    }
+
+    /// Returns an iterator over the tokens traded by the pool. Native ETH
+    /// can't be traded by the settlement contract, so it is skipped.
+    pub fn tokens(&self) -> impl Iterator<Item = eth::TokenAddress> + '_ {
+        self.coins
+            .iter()
+            .map(|coin| coin.asset.token)
+            .filter(|token| *token != ETH)
+    }
+
+    /// Returns an iterator over the token pairs traded by the pool.
+    pub fn token_pairs(&self) -> impl Iterator<Item = liquidity::TokenPair> + '_ {
+        self.tokens()
+            .tuple_combinations()
+            .map(|(a, b)| liquidity::TokenPair::new(a, b).expect("a != b"))
+    }
+}
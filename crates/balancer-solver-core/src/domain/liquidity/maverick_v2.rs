@@ -0,0 +1,28 @@
+//! Maverick V2 pool.
+
+use {
+    crate::domain::{eth, liquidity},
+    std::collections::BTreeMap,
+};
+
+/// State of a Maverick V2 pool, made of the reserves of the ticks around its
+/// active tick. Swaps moving the price beyond the known ticks can't be quoted.
+#[derive(Clone, Debug)]
+pub struct Pool {
+    /// The tokens A and B of the pool, token A being the lower address.
+    pub tokens: liquidity::TokenPair,
+    pub tick_spacing: u32,
+    pub active_tick: i32,
+    pub ticks: BTreeMap<i32, TickReserves>,
+    /// The fee for swapping token A in, as a fraction of the input amount.
+    pub fee_a_in: eth::Rational,
+    /// The fee for swapping token B in, as a fraction of the input amount.
+    pub fee_b_in: eth::Rational,
+}
+
+/// The reserves of the bins at a tick.
+#[derive(Clone, Copy, Debug)]
+pub struct TickReserves {
+    pub a: eth::U256,
+    pub b: eth::U256,
+}
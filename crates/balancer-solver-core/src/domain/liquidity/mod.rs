@@ -0,0 +1,347 @@
+//! Modelling on-chain liquidity.
+
+pub mod concentrated;
+pub mod constant_product;
+pub mod curve;
+pub mod erc4626;
+pub mod gyro_2clp;
+pub mod gyro_3clp;
+pub mod gyro_e;
+pub mod limit_order;
+pub mod maverick_v2;
+pub mod quantamm;
+pub mod reclamm;
+pub mod stable;
+pub mod uniswap_v4;
+pub mod weighted_product;
+
+use {
+    crate::domain::eth,
+    ethereum_types::{H160, H256},
+    std::cmp::Ordering,
+};
+
+/// A source of liquidity which can be used by the solver.
+#[derive(Clone, Debug)]
+pub struct Liquidity {
+    pub id: Id,
+    pub address: H160,
+    /// The Balancer V2 pool ID. Balancer pools without one are V3 pools.
+    pub balancer_pool_id: Option<H256>,
+    /// Estimation of gas needed to use this liquidity on-chain.
+    pub gas: eth::Gas,
+    pub state: State,
+}
+
+impl Liquidity {
+    /// Returns whether this is a Balancer V3 pool, which gets swapped through
+    /// the V3 batch router.
+    pub fn is_balancer_v3(&self) -> bool {
+        match &self.state {
+            State::WeightedProduct(_)
+            | State::Stable(_)
+            | State::GyroE(_)
+            | State::Gyro2CLP(_)
+            | State::Gyro3CLP(_)
+            | State::QuantAmm(_) => self.balancer_pool_id.is_none(),
+            State::BalancerV3ReClamm(_) => true,
+            State::ConstantProduct(_)
+            | State::Concentrated(_)
+            | State::UniswapV4(_)
+            | State::MaverickV2(_)
+            | State::Curve(_)
+            | State::LimitOrder(_)
+            | State::Erc4626(_) => false,
+        }
+    }
+
+    /// Returns the token balances held by this liquidity. This is empty for
+    /// liquidity whose balances are not known to the solver.
+    pub fn reserves(&self) -> Vec<eth::Asset> {
+        match &self.state {
+            State::ConstantProduct(pool) => {
+                let (a, b) = pool.reserves.get();
+                vec![a, b]
+            }
+            State::WeightedProduct(pool) => pool.reserves.iter().map(|r| r.asset).collect(),
+            State::Stable(pool) => pool.reserves.iter().map(|r| r.asset).collect(),
+            State::GyroE(pool) => pool.reserves.iter().map(|r| r.asset).collect(),
+            State::Gyro2CLP(pool) => pool.reserves.iter().map(|r| r.asset).collect(),
+            State::Gyro3CLP(pool) => pool.reserves.iter().map(|r| r.asset).collect(),
+            State::BalancerV3ReClamm(pool) => pool.reserves.iter().map(|r| r.asset).collect(),
+            State::QuantAmm(pool) => pool.reserves.iter().map(|r| r.asset).collect(),
+            State::Curve(pool) => pool.coins.iter().map(|coin| coin.asset).collect(),
+            State::LimitOrder(order) => vec![order.maker],
+            // Maverick V2 pools only come with the reserves of the ticks
+            // around their active tick.
+            State::Concentrated(_)
+            | State::UniswapV4(_)
+            | State::MaverickV2(_)
+            | State::Erc4626(_) => vec![],
+        }
+    }
+
+    /// Returns the tokens that can be traded with this liquidity.
+    pub fn tokens(&self) -> Vec<eth::TokenAddress> {
+        match &self.state {
+            State::Concentrated(pool) => {
+                let (a, b) = pool.tokens.get();
+                vec![a, b]
+            }
+            State::UniswapV4(pool) => {
+                let (a, b) = pool.tokens.get();
+                vec![a, b]
+            }
+            State::MaverickV2(pool) => {
+                let (a, b) = pool.tokens.get();
+                vec![a, b]
+            }
+            State::Curve(pool) => pool.tokens().collect(),
+            State::LimitOrder(order) => vec![order.maker.token, order.taker.token],
+            State::Erc4626(edge) => vec![edge.asset, edge.vault],
+            _ => self
+                .reserves()
+                .into_iter()
+                .map(|asset| asset.token)
+                .collect(),
+        }
+    }
+
+    /// Returns the liquidity with its state updated to after swapping `input`
+    /// for `output` through it. Returns `None` for liquidity whose state
+    /// changes can't be modelled, like liquidity without known balances.
+    ///
+    /// Only the token balances get updated, so protocol fees leaving the
+    /// pool are not accounted for.
+    pub fn with_swap(&self, input: &eth::Asset, output: &eth::Asset) -> Option<Self> {
+        let state = match &self.state {
+            State::ConstantProduct(pool) => {
+                let (a, b) = pool.reserves.get();
+                let balances = swap_balances(vec![a, b], |asset| asset, input, output)?;
+                State::ConstantProduct(constant_product::Pool {
+                    reserves: constant_product::Reserves::new(balances[0], balances[1])?,
+                    ..pool.clone()
+                })
+            }
+            State::WeightedProduct(pool) => State::WeightedProduct(weighted_product::Pool {
+                reserves: weighted_product::Reserves::new(swap_balances(
+                    pool.reserves.iter().collect::<Vec<_>>(),
+                    |reserve| &mut reserve.asset,
+                    input,
+                    output,
+                )?)?,
+                ..pool.clone()
+            }),
+            State::Stable(pool) => State::Stable(stable::Pool {
+                reserves: stable::Reserves::new(swap_balances(
+                    pool.reserves.iter().collect::<Vec<_>>(),
+                    |reserve| &mut reserve.asset,
+                    input,
+                    output,
+                )?)?,
+                ..pool.clone()
+            }),
+            State::GyroE(pool) => State::GyroE(Box::new(gyro_e::Pool {
+                reserves: gyro_e::Reserves::new(swap_balances(
+                    pool.reserves.iter().collect::<Vec<_>>(),
+                    |reserve| &mut reserve.asset,
+                    input,
+                    output,
+                )?)?,
+                ..pool.as_ref().clone()
+            })),
+            State::Gyro2CLP(pool) => State::Gyro2CLP(gyro_2clp::Pool {
+                reserves: gyro_2clp::Reserves::new(swap_balances(
+                    pool.reserves.iter().collect::<Vec<_>>(),
+                    |reserve| &mut reserve.asset,
+                    input,
+                    output,
+                )?)?,
+                ..pool.clone()
+            }),
+            State::Gyro3CLP(pool) => State::Gyro3CLP(gyro_3clp::Pool {
+                reserves: gyro_3clp::Reserves::new(swap_balances(
+                    pool.reserves.iter().collect::<Vec<_>>(),
+                    |reserve| &mut reserve.asset,
+                    input,
+                    output,
+                )?)?,
+                ..pool.clone()
+            }),
+            State::QuantAmm(pool) => State::QuantAmm(quantamm::Pool {
+                reserves: quantamm::Reserves::new(swap_balances(
+                    pool.reserves.iter().collect::<Vec<_>>(),
+                    |reserve| &mut reserve.asset,
+                    input,
+                    output,
+                )?)?,
+                ..pool.clone()
+            }),
+            State::Curve(pool) => State::Curve(curve::Pool {
+                coins: swap_balances(pool.coins.clone(), |coin| &mut coin.asset, input, output)?,
+                ..pool.clone()
+            }),
+            // ReClamm pools also shift their virtual balances when swapped
+            // through, the others don't expose their balances.
+            State::BalancerV3ReClamm(_)
+            | State::Concentrated(_)
+            | State::UniswapV4(_)
+            | State::MaverickV2(_)
+            | State::LimitOrder(_)
+            | State::Erc4626(_) => return None,
+        };
+        Some(Self {
+            state,
+            ..self.clone()
+        })
+    }
+}
+
+/// Adds the `input` to and removes the `output` from the matching balances.
+/// Returns `None` if the balances don't contain both tokens or the output
+/// exceeds its balance.
+fn swap_balances<R>(
+    mut balances: Vec<R>,
+    asset: impl Fn(&mut R) -> &mut eth::Asset,
+    input: &eth::Asset,
+    output: &eth::Asset,
+) -> Option<Vec<R>> {
+    let mut matched = (false, false);
+    for balance in &mut balances {
+        let balance = asset(balance);
+        if balance.token == input.token {
+            balance.amount = balance.amount.checked_add(input.amount)?;
+            matched.0 = true;
+        } else if balance.token == output.token {
+            balance.amount = balance.amount.checked_sub(output.amount)?;
+            matched.1 = true;
+        }
+    }
+    (matched == (true, true)).then_some(balances)
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Id(pub String);
+
+/// The liquidity state, specific to the type of liquidity.
+#[derive(Clone, Debug)]
+pub enum State {
+    ConstantProduct(constant_product::Pool),
+    WeightedProduct(weighted_product::Pool),
+    Stable(stable::Pool),
+    Concentrated(concentrated::Pool),
+    UniswapV4(uniswap_v4::Pool),
+    MaverickV2(maverick_v2::Pool),
+    Curve(curve::Pool),
+    GyroE(Box<gyro_e::Pool>),
+    Gyro2CLP(gyro_2clp::Pool),
+    Gyro3CLP(gyro_3clp::Pool),
+    BalancerV3ReClamm(reclamm::Pool),
+    QuantAmm(quantamm::Pool),
+    LimitOrder(limit_order::LimitOrder),
+    Erc4626(erc4626::Edge),
+}
+
+impl State {
+    /// Returns the name of the kind of liquidity, matching the `kind` used in
+    /// the solver API.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            State::ConstantProduct(_) => "constantProduct",
+            State::WeightedProduct(_) => "weightedProduct",
+            State::Stable(_) => "stable",
+            State::Concentrated(_) => "concentratedLiquidity",
+            State::UniswapV4(_) => "uniswapV4",
+            State::MaverickV2(_) => "maverickV2",
+            State::Curve(_) => "curve",
+            State::GyroE(_) => "gyroE",
+            State::Gyro2CLP(_) => "gyro2CLP",
+            State::Gyro3CLP(_) => "gyro3CLP",
+            State::BalancerV3ReClamm(_) => "reClamm",
+            State::QuantAmm(_) => "quantAmm",
+            State::LimitOrder(_) => "limitOrder",
+            State::Erc4626(_) => "erc4626",
+        }
+    }
+
+    /// Returns whether the swap fee of the liquidity is below 100%. Pools
+    /// charging the whole swapped amount or more can't be swapped through,
+    /// and their fee math would divide by zero.
+    pub fn has_valid_fee(&self) -> bool {
+        let below_one = |fee: &eth::Rational| fee.numer() < fee.denom();
+        match self {
+            State::ConstantProduct(pool) => below_one(&pool.fee),
+            State::WeightedProduct(pool) => below_one(&pool.fee),
+            State::Stable(pool) => below_one(&pool.fee),
+            // Expressed in hundredths of a basis point.
+            State::Concentrated(pool) => pool.fee.0 < 1_000_000,
+            State::UniswapV4(pool) => {
+                pool.fee == uniswap_v4::Pool::DYNAMIC_FEE_FLAG || pool.fee < 1_000_000
+            }
+            State::MaverickV2(pool) => below_one(&pool.fee_a_in) && below_one(&pool.fee_b_in),
+            State::Curve(pool) => pool.fee < curve::Pool::FEE_DENOMINATOR.into(),
+            State::GyroE(pool) => below_one(&pool.fee),
+            State::Gyro2CLP(pool) => below_one(&pool.fee),
+            State::Gyro3CLP(pool) => below_one(&pool.fee),
+            State::BalancerV3ReClamm(pool) => below_one(&pool.fee),
+            State::QuantAmm(pool) => below_one(&pool.fee),
+            State::LimitOrder(_) | State::Erc4626(_) => true,
+        }
+    }
+
+    /// Returns whether swaps through the liquidity are quoted against a node
+    /// instead of being computed from its state.
+    pub fn is_quoted_on_chain(&self) -> bool {
+        matches!(
+            self,
+            State::Concentrated(_) | State::UniswapV4(_) | State::Erc4626(_)
+        )
+    }
+}
+
+/// An ordered token pair.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct TokenPair(eth::TokenAddress, eth::TokenAddress);
+
+impl TokenPair {
+    /// Returns a token pair for the given tokens, or `None` if `a` and `b` are
+    /// equal.
+    pub fn new(a: eth::TokenAddress, b: eth::TokenAddress) -> Option<Self> {
+        match a.cmp(&b) {
+            Ordering::Less => Some(Self(a, b)),
+            Ordering::Equal => None,
+            Ordering::Greater => Some(Self(b, a)),
+        }
+    }
+
+    /// Returns the wrapped token pair as a tuple.
+    pub fn get(&self) -> (eth::TokenAddress, eth::TokenAddress) {
+        (self.0, self.1)
+    }
+}
+
+/// A scaling factor used for normalizing token amounts.
+#[derive(Clone, Copy, Debug)]
+pub struct ScalingFactor(eth::Rational);
+
+impl ScalingFactor {
+    /// Creates a new scaling factor. Returns `None` if the specified value is
+    /// 0 (as a 0 scaling factor is not allowed).
+    pub fn new(value: eth::Rational) -> Option<Self> {
+        if value.numer().is_zero() || value.denom().is_zero() {
+            return None;
+        }
+        Some(Self(value))
+    }
+
+    /// Returns the underlying scaling factor value.
+    pub fn get(&self) -> eth::Rational {
+        self.0
+    }
+}
+
+impl Default for ScalingFactor {
+    fn default() -> Self {
+        Self(eth::Rational::new_raw(1.into(), 1.into()))
+    }
+}
@@ -10,6 +10,8 @@ pub struct Pool {
     pub version: Version,
     // QuantAMM-specific parameters for weight interpolation
     pub max_trade_size_ratio: eth::Rational,
+    /// Strategy metadata of the pool, if the liquidity source provides it.
+    pub strategy: Option<Strategy>,
     pub first_four_weights_and_multipliers: Vec<eth::SignedRational>,
     pub second_four_weights_and_multipliers: Vec<eth::SignedRational>,
     pub last_update_time: u64,
@@ -62,6 +64,17 @@ pub struct Reserve {
     pub rate: eth::Rational,
 }
 
+/// The strategy driving the weight updates of a QuantAMM pool.
+#[derive(Clone, Debug)]
+pub struct Strategy {
+    /// The weight update rule, e.g. "momentum", if the pool describes it.
+    pub rule: Option<String>,
+    /// The oracles feeding the update rule.
+    pub oracles: Vec<String>,
+    /// The minimum number of seconds between weight updates.
+    pub update_interval: u64,
+}
+
 /// The QuantAMM pool version.
 #[derive(Clone, Copy, Debug)]
 pub enum Version {
@@ -9,6 +9,9 @@ pub struct Pool {
     pub reserves: Reserves,
     pub amplification_parameter: eth::Rational,
     pub fee: eth::Rational,
+    /// The BPT supply of Balancer V2 composable stable pools, net of the BPT
+    /// held by the vault. Swapping the pool's own BPT requires it.
+    pub actual_supply: Option<eth::U256>,
 }
 
 /// A reprensentation of BalancerV2-like weighted pool reserves.
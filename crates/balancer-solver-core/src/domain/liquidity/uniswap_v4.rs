@@ -0,0 +1,18 @@
+use {crate::domain::liquidity, ethereum_types::H160};
+
+/// State for a Uniswap V4 pool of the PoolManager. The balances of the pool
+/// aren't known, it gets quoted on-chain instead.
+#[derive(Clone, Debug)]
+pub struct Pool {
+    pub tokens: liquidity::TokenPair,
+    /// The LP fee in hundredths of a basis point, or the dynamic fee flag for
+    /// pools whose fee is set by their hooks.
+    pub fee: u32,
+    pub tick_spacing: i32,
+    pub hooks: H160,
+}
+
+impl Pool {
+    /// The fee of pools whose LP fee is set by their hooks.
+    pub const DYNAMIC_FEE_FLAG: u32 = 0x80_0000;
+}
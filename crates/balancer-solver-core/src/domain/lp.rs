@@ -0,0 +1,333 @@
+//! Liquidity provision intents.
+//!
+//! Orders buying the pool token of a Balancer V2 weighted pool with one of the
+//! pool's tokens are intents to deposit into the pool, and orders selling the
+//! pool token for one of the pool's tokens are intents to withdraw from it.
+//! Instead of routing them through other liquidity, such orders get settled by
+//! joining or exiting the pool with a single token, priced against the
+//! current pool state.
+
+use {
+    crate::{
+        boundary,
+        domain::{eth, liquidity, order, solution},
+        infra::total_supply::TotalSupply,
+    },
+    alloy::{
+        primitives,
+        sol_types::{SolCall, SolValue},
+    },
+    contracts::alloy::BalancerV2Vault::{
+        BalancerV2Vault::{exitPoolCall, joinPoolCall},
+        IVault::{ExitPoolRequest, JoinPoolRequest},
+    },
+    ethrpc::alloy::conversions::IntoAlloy,
+};
+
+/// Gas used for joining or exiting a weighted pool with a single token.
+const GAS: u64 = 150_000;
+
+/// The weighted pool join kind for depositing exact token amounts.
+const EXACT_TOKENS_IN_FOR_BPT_OUT: u8 = 1;
+/// The weighted pool exit kind for withdrawing a single token.
+const EXACT_BPT_IN_FOR_ONE_TOKEN_OUT: u8 = 0;
+
+/// Whether an order deposits into or withdraws from a pool.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    Join,
+    Exit,
+}
+
+/// A liquidity provision intent of an order.
+#[derive(Debug)]
+pub struct Intent<'a> {
+    pub kind: Kind,
+    pub pool_id: eth::H256,
+    pub address: eth::H160,
+    pub pool: &'a liquidity::weighted_product::Pool,
+}
+
+impl<'a> Intent<'a> {
+    /// Returns the liquidity provision intent of the order, if it trades the
+    /// pool token of one of the specified pools for one of the pool's tokens.
+    /// Only sell orders are supported, since joins and exits are priced for
+    /// exact input amounts.
+    pub fn detect(order: &order::Order, liquidity: &'a [liquidity::Liquidity]) -> Option<Self> {
+        if order.side != order::Side::Sell {
+            return None;
+        }
+        liquidity.iter().find_map(|liquidity| {
+            let liquidity::State::WeightedProduct(pool) = &liquidity.state else {
+                return None;
+            };
+            let pool_id = liquidity.balancer_pool_id?;
+            let pool_token = eth::TokenAddress(liquidity.address);
+            let holds = |token| pool.reserves.iter().any(|r| r.asset.token == token);
+            let kind = if order.buy.token == pool_token && holds(order.sell.token) {
+                Kind::Join
+            } else if order.sell.token == pool_token && holds(order.buy.token) {
+                Kind::Exit
+            } else {
+                return None;
+            };
+            Some(Self {
+                kind,
+                pool_id,
+                address: liquidity.address,
+                pool,
+            })
+        })
+    }
+}
+
+pub struct Config {
+    pub vault: eth::Address,
+    pub settlement: eth::Address,
+    pub total_supply: TotalSupply,
+    /// The share of the computed output that may be lost to changes of the
+    /// pool state before settlement, in basis points.
+    pub slippage_bps: u32,
+}
+
+pub struct Provider(Config);
+
+impl Provider {
+    pub fn new(config: Config) -> Self {
+        Self(config)
+    }
+
+    /// Computes a single order solution joining or exiting the pool with the
+    /// order's full sell amount. Returns `None` if the pool can't be priced or
+    /// the output doesn't satisfy the order's limit price.
+    pub async fn solve(
+        &self,
+        order: &order::Order,
+        intent: &Intent<'_>,
+        gas_offset: eth::SignedGas,
+    ) -> Option<solution::Single> {
+        let total_supply = self
+            .0
+            .total_supply
+            .fetch(eth::TokenAddress(intent.address))
+            .await?;
+        let amount = match intent.kind {
+            Kind::Join => boundary::liquidity::weighted_product::join(
+                intent.address,
+                intent.pool,
+                order.sell,
+                total_supply,
+            )?,
+            Kind::Exit => boundary::liquidity::weighted_product::exit(
+                intent.address,
+                intent.pool,
+                order.sell.amount,
+                order.buy.token,
+                total_supply,
+            )?,
+        };
+        let output = eth::Asset {
+            token: order.buy.token,
+            amount: self.with_slippage(amount),
+        };
+        if output.amount.is_zero() {
+            return None;
+        }
+
+        Some(solution::Single {
+            order: order.clone(),
+            input: order.sell,
+            output,
+            interactions: vec![self.interaction(intent, order.sell, output)],
+            gas: eth::Gas(GAS.into()) + gas_offset,
+            wrappers: order.wrappers.clone(),
+        })
+    }
+
+    fn with_slippage(&self, amount: eth::U256) -> eth::U256 {
+        let kept = eth::U256::from(10_000 - self.0.slippage_bps.min(10_000));
+        (amount.full_mul(kept) / eth::U256::from(10_000))
+            .try_into()
+            .unwrap_or_default()
+    }
+
+    fn interaction(
+        &self,
+        intent: &Intent<'_>,
+        input: eth::Asset,
+        output: eth::Asset,
+    ) -> solution::Interaction {
+        let assets = intent
+            .pool
+            .reserves
+            .iter()
+            .map(|reserve| reserve.asset.token)
+            .collect::<Vec<_>>();
+        let amounts = |asset: eth::Asset| {
+            assets
+                .iter()
+                .map(|token| match *token == asset.token {
+                    true => asset.amount.into_alloy(),
+                    false => primitives::U256::ZERO,
+                })
+                .collect::<Vec<_>>()
+        };
+        let settlement = self.0.settlement.0.into_alloy();
+        let calldata = match intent.kind {
+            Kind::Join => joinPoolCall {
+                poolId: intent.pool_id.0.into(),
+                sender: settlement,
+                recipient: settlement,
+                request: JoinPoolRequest {
+                    assets: assets.iter().map(|token| token.0.into_alloy()).collect(),
+                    maxAmountsIn: amounts(input),
+                    userData: (
+                        primitives::U256::from(EXACT_TOKENS_IN_FOR_BPT_OUT),
+                        amounts(input),
+                        output.amount.into_alloy(),
+                    )
+                        .abi_encode_params()
+                        .into(),
+                    fromInternalBalance: false,
+                },
+            }
+            .abi_encode(),
+            Kind::Exit => exitPoolCall {
+                poolId: intent.pool_id.0.into(),
+                sender: settlement,
+                recipient: settlement,
+                request: ExitPoolRequest {
+                    assets: assets.iter().map(|token| token.0.into_alloy()).collect(),
+                    minAmountsOut: amounts(output),
+                    userData: (
+                        primitives::U256::from(EXACT_BPT_IN_FOR_ONE_TOKEN_OUT),
+                        input.amount.into_alloy(),
+                        primitives::U256::from(
+                            assets
+                                .iter()
+                                .position(|token| *token == output.token)
+                                .unwrap_or_default(),
+                        ),
+                    )
+                        .abi_encode_params()
+                        .into(),
+                    toInternalBalance: false,
+                },
+            }
+            .abi_encode(),
+        };
+
+        solution::Interaction::Custom(solution::CustomInteraction {
+            target: self.0.vault.0,
+            value: eth::Ether(eth::U256::zero()),
+            calldata,
+            internalize: false,
+            inputs: vec![input],
+            outputs: vec![output],
+            // Pool tokens are burned by the pool itself when exiting, so only
+            // joins need an approval.
+            allowances: match intent.kind {
+                Kind::Join => vec![solution::Allowance {
+                    spender: self.0.vault.0,
+                    asset: input,
+                }],
+                Kind::Exit => Vec::new(),
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::domain::liquidity::weighted_product};
+
+    fn token(byte: u8) -> eth::TokenAddress {
+        eth::TokenAddress(eth::H160::repeat_byte(byte))
+    }
+
+    fn pool(balancer_pool_id: Option<eth::H256>) -> liquidity::Liquidity {
+        let reserve = |byte| weighted_product::Reserve {
+            asset: eth::Asset {
+                token: token(byte),
+                amount: eth::U256::exp10(22),
+            },
+            weight: eth::Rational::new_raw(1.into(), 2.into()),
+            scale: liquidity::ScalingFactor::new(eth::Rational::new_raw(1.into(), 1.into()))
+                .unwrap(),
+            rate: eth::Rational::new_raw(1.into(), 1.into()),
+        };
+        liquidity::Liquidity {
+            id: liquidity::Id("0".to_owned()),
+            address: token(0xbb).0,
+            balancer_pool_id,
+            gas: eth::Gas(100_000.into()),
+            state: liquidity::State::WeightedProduct(weighted_product::Pool {
+                reserves: weighted_product::Reserves::new(vec![reserve(1), reserve(2)]).unwrap(),
+                fee: eth::Rational::new_raw(1.into(), 100.into()),
+                version: weighted_product::Version::V3Plus,
+            }),
+        }
+    }
+
+    fn order(sell: eth::TokenAddress, buy: eth::TokenAddress, side: order::Side) -> order::Order {
+        order::Order {
+            uid: order::Uid([1; 56]),
+            sell: eth::Asset {
+                token: sell,
+                amount: eth::U256::exp10(20),
+            },
+            buy: eth::Asset {
+                token: buy,
+                amount: 1.into(),
+            },
+            side,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn detects_join_and_exit_intents() {
+        let liquidity = [pool(Some(eth::H256::repeat_byte(0xbb)))];
+
+        let join = order(token(1), token(0xbb), order::Side::Sell);
+        let intent = Intent::detect(&join, &liquidity).unwrap();
+        assert_eq!(intent.kind, Kind::Join);
+        assert_eq!(intent.pool_id, eth::H256::repeat_byte(0xbb));
+
+        let exit = order(token(0xbb), token(2), order::Side::Sell);
+        assert_eq!(Intent::detect(&exit, &liquidity).unwrap().kind, Kind::Exit);
+
+        // regular swaps and buy orders are routed as usual
+        assert!(
+            Intent::detect(&order(token(1), token(2), order::Side::Sell), &liquidity).is_none()
+        );
+        assert!(
+            Intent::detect(&order(token(1), token(0xbb), order::Side::Buy), &liquidity).is_none()
+        );
+        assert!(
+            Intent::detect(&order(token(3), token(0xbb), order::Side::Sell), &liquidity).is_none()
+        );
+
+        // Balancer V3 pools are not supported
+        assert!(Intent::detect(&join, &[pool(None)]).is_none());
+    }
+
+    #[test]
+    fn prices_joins_against_pool_state() {
+        let liquidity = [pool(Some(eth::H256::repeat_byte(0xbb)))];
+        let join = order(token(1), token(0xbb), order::Side::Sell);
+        let intent = Intent::detect(&join, &liquidity).unwrap();
+
+        // depositing 1% of one of the balances of a 50/50 pool mints slightly
+        // less than 0.5% of the supply
+        let bpt_out = boundary::liquidity::weighted_product::join(
+            intent.address,
+            intent.pool,
+            join.sell,
+            eth::U256::exp10(21),
+        )
+        .unwrap();
+        assert!(bpt_out < eth::U256::exp10(21) / 200);
+        assert!(bpt_out > eth::U256::exp10(21) / 202);
+    }
+}
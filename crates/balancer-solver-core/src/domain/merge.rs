@@ -0,0 +1,673 @@
+//! Merging of per-order solutions into joint solutions.
+//!
+//! Solutions for orders not trading any common tokens have independent
+//! clearing prices and can be settled together, only paying the settlement
+//! overhead once. Their routes may still swap through the same liquidity
+//! though, in which case the amounts of the later route, computed against the
+//! original liquidity state, are wrong. Such conflicting routes get re-priced
+//! sequentially against the liquidity state left behind by the earlier routes,
+//! and are only merged if the re-priced route still covers the solution's
+//! trades. Otherwise they are kept as separate solutions.
+//!
+//! When the liquidity isn't deep enough for all competing routes, the routes
+//! merged first get to swap against the original liquidity state. Solutions
+//! therefore get merged in the order of decreasing surplus, greedily selecting
+//! the combination of orders generating the most surplus instead of favouring
+//! whichever order happens to come first in the auction.
+//!
+//! ERC4626 vaults convert at a rate that isn't moved by swapping through them,
+//! so routes sharing a vault don't conflict. Their vault interactions get
+//! netted in the joint solution instead.
+
+use {
+    crate::domain::{auction, eth, gas_budget, liquidity, netting, order, solution},
+    std::{
+        cmp::Reverse,
+        collections::{HashMap, HashSet},
+    },
+};
+
+/// Combines compatible solutions into joint solutions. The `overhead` is the
+/// gas of a settlement included in the estimate of every solution, and
+/// `amount_out` prices swapping an asset through some liquidity. Solutions
+/// are only combined as long as the joint solution fits into the `budget`.
+pub async fn merge(
+    solutions: Vec<solution::Solution>,
+    overhead: eth::SignedGas,
+    budget: Option<&gas_budget::Budget>,
+    tokens: &auction::Tokens,
+    amount_out: impl AsyncFn(&liquidity::Liquidity, eth::Asset, eth::TokenAddress) -> Option<eth::U256>,
+) -> Vec<solution::Solution> {
+    let mut solutions = solutions;
+    // Ties get broken by the smallest order uid, so that the selection
+    // doesn't depend on the order the solutions were computed in.
+    solutions.sort_by_cached_key(|solution| {
+        (
+            Reverse(surplus(solution, tokens)),
+            first_uid(solution),
+            solution.id.0,
+        )
+    });
+
+    let mut merged = Vec::<Merged>::new();
+    'solutions: for solution in solutions {
+        let traded = traded_tokens(&solution);
+        for group in &mut merged {
+            if !group.solution.wrappers.is_empty()
+                || !solution.wrappers.is_empty()
+                || !group.traded.is_disjoint(&traded)
+            {
+                continue;
+            }
+            if let Some(budget) = budget
+                && !joint_gas(group.solution.gas, solution.gas, overhead)
+                    .is_some_and(|gas| budget.fits(gas))
+            {
+                continue;
+            }
+
+            let conflicts = solution
+                .interactions
+                .iter()
+                .any(|interaction| match interaction {
+                    solution::Interaction::Liquidity(interaction) => {
+                        group.liquidity.contains_key(&interaction.liquidity.id)
+                    }
+                    solution::Interaction::Custom(_) => false,
+                });
+            let interactions = if conflicts {
+                match reprice(&solution, &group.liquidity, &amount_out).await {
+                    Some(interactions) => interactions,
+                    None => {
+                        tracing::debug!(
+                            id = ?solution.id,
+                            merged = ?group.solution.id,
+                            "keeping conflicting solution separate"
+                        );
+                        continue;
+                    }
+                }
+            } else {
+                solution.interactions
+            };
+
+            group.add(
+                solution::Solution {
+                    interactions,
+                    ..solution
+                },
+                traded,
+                overhead,
+            );
+            continue 'solutions;
+        }
+        merged.push(Merged::new(solution, traded));
+    }
+
+    merged
+        .into_iter()
+        .map(|group| match group.count {
+            1 => group.solution,
+            _ => reinternalize(netting::net(group.solution), tokens),
+        })
+        .collect()
+}
+
+/// A joint solution that is being built.
+struct Merged {
+    solution: solution::Solution,
+    count: usize,
+    traded: HashSet<eth::TokenAddress>,
+    /// The state of the liquidity used by the joint solution after executing
+    /// it, or `None` if its state changes can't be modelled.
+    liquidity: HashMap<liquidity::Id, Option<liquidity::Liquidity>>,
+}
+
+impl Merged {
+    fn new(solution: solution::Solution, traded: HashSet<eth::TokenAddress>) -> Self {
+        let mut merged = Self {
+            solution: Default::default(),
+            count: 1,
+            traded,
+            liquidity: Default::default(),
+        };
+        merged.apply_swaps(&solution.interactions);
+        merged.solution = solution;
+        merged
+    }
+
+    fn add(
+        &mut self,
+        solution: solution::Solution,
+        traded: HashSet<eth::TokenAddress>,
+        overhead: eth::SignedGas,
+    ) {
+        self.apply_swaps(&solution.interactions);
+        self.traded.extend(traded);
+        self.count += 1;
+
+        let merged = &mut self.solution;
+        merged.gas = joint_gas(merged.gas, solution.gas, overhead);
+        merged.prices.0.extend(solution.prices.0);
+        merged.trades.extend(solution.trades);
+        merged.pre_interactions.extend(solution.pre_interactions);
+        merged.interactions.extend(solution.interactions);
+        merged.post_interactions.extend(solution.post_interactions);
+    }
+
+    /// Updates the liquidity states with the swaps of the specified
+    /// interactions. Vaults aren't tracked, as swaps don't move their rate.
+    fn apply_swaps(&mut self, interactions: &[solution::Interaction]) {
+        for interaction in interactions {
+            if let solution::Interaction::Liquidity(interaction) = interaction
+                && !matches!(interaction.liquidity.state, liquidity::State::Erc4626(_))
+            {
+                let state = match self.liquidity.get(&interaction.liquidity.id) {
+                    Some(state) => state.as_ref(),
+                    None => Some(&interaction.liquidity),
+                };
+                let state = state
+                    .and_then(|state| state.with_swap(&interaction.input, &interaction.output));
+                self.liquidity
+                    .insert(interaction.liquidity.id.clone(), state);
+            }
+        }
+    }
+}
+
+/// The gas of settling two solutions together, which only pays the settlement
+/// overhead once.
+fn joint_gas(
+    a: Option<eth::Gas>,
+    b: Option<eth::Gas>,
+    overhead: eth::SignedGas,
+) -> Option<eth::Gas> {
+    a.zip(b)
+        .map(|(a, b)| eth::Gas(a.0.saturating_add(b.0)) + -overhead)
+}
+
+/// The tokens traded by the orders of a solution, that is the tokens it has
+/// clearing prices for.
+fn traded_tokens(solution: &solution::Solution) -> HashSet<eth::TokenAddress> {
+    solution
+        .trades
+        .iter()
+        .flat_map(|trade| match trade {
+            solution::Trade::Fulfillment(fulfillment) => {
+                let order = fulfillment.order();
+                [order.sell.token, order.buy.token]
+            }
+            solution::Trade::Jit(jit) => [jit.order.sell.token, jit.order.buy.token],
+        })
+        .chain(solution.prices.0.keys().copied())
+        .collect()
+}
+
+/// The surplus the orders traded by a solution receive over their limit
+/// prices in the native token. Trades of tokens without a reference price
+/// don't contribute to it.
+fn surplus(solution: &solution::Solution, tokens: &auction::Tokens) -> eth::U256 {
+    solution
+        .trades
+        .iter()
+        .filter_map(|trade| {
+            let solution::Trade::Fulfillment(fulfillment) = trade else {
+                return None;
+            };
+            let order = fulfillment.order();
+            let (sell, buy) = transfers(fulfillment, &solution.prices)?;
+            let (token, surplus) = match order.side {
+                order::Side::Sell => {
+                    let limit = order
+                        .buy
+                        .amount
+                        .checked_mul(sell.amount)?
+                        .checked_div(order.sell.amount)?;
+                    (buy.token, buy.amount.checked_sub(limit)?)
+                }
+                order::Side::Buy => {
+                    let limit = order
+                        .sell
+                        .amount
+                        .checked_mul(buy.amount)?
+                        .checked_div(order.buy.amount)?;
+                    (sell.token, limit.checked_sub(sell.amount)?)
+                }
+            };
+            tokens.reference_price(&token)?.native_value(surplus)
+        })
+        .fold(eth::U256::zero(), |acc, value| acc.saturating_add(value.0))
+}
+
+/// The smallest uid of the orders traded by a solution.
+fn first_uid(solution: &solution::Solution) -> Option<[u8; 56]> {
+    solution
+        .trades
+        .iter()
+        .filter_map(|trade| match trade {
+            solution::Trade::Fulfillment(fulfillment) => Some(fulfillment.order().uid.0),
+            solution::Trade::Jit(_) => None,
+        })
+        .min()
+}
+
+/// Re-prices the swaps of a solution against the specified liquidity states,
+/// returning the updated interactions if they still cover its trades.
+async fn reprice(
+    solution: &solution::Solution,
+    states: &HashMap<liquidity::Id, Option<liquidity::Liquidity>>,
+    amount_out: &impl AsyncFn(&liquidity::Liquidity, eth::Asset, eth::TokenAddress) -> Option<eth::U256>,
+) -> Option<Vec<solution::Interaction>> {
+    let mut states = states.clone();
+    // The original and re-priced output of the previous swap, which is the
+    // input of the next swap along the route.
+    let mut previous: Option<(eth::Asset, eth::U256)> = None;
+    let mut interactions = Vec::with_capacity(solution.interactions.len());
+    for interaction in &solution.interactions {
+        let solution::Interaction::Liquidity(interaction) = interaction else {
+            // Custom interactions can't be re-priced.
+            return None;
+        };
+
+        let mut input = interaction.input;
+        if let Some((output, repriced)) = previous
+            && output.token == input.token
+            && output.amount == input.amount
+        {
+            input.amount = repriced;
+        }
+        let state = match states.get(&interaction.liquidity.id) {
+            Some(state) => state.clone()?,
+            None => interaction.liquidity.clone(),
+        };
+        let output = eth::Asset {
+            token: interaction.output.token,
+            amount: amount_out(&state, input, interaction.output.token).await?,
+        };
+        states.insert(
+            interaction.liquidity.id.clone(),
+            state.with_swap(&input, &output),
+        );
+        previous = Some((interaction.output, output.amount));
+
+        interactions.push(solution::Interaction::Liquidity(Box::new(
+            solution::LiquidityInteraction {
+                liquidity: interaction.liquidity.clone(),
+                input,
+                output,
+                internalize: false,
+            },
+        )));
+    }
+
+    is_solvent(solution, &interactions)?.then_some(interactions)
+}
+
+/// Checks that the swaps produce at least as many tokens as the trades of the
+/// solution and subsequent swaps require.
+fn is_solvent(
+    solution: &solution::Solution,
+    interactions: &[solution::Interaction],
+) -> Option<bool> {
+    let mut flows = Vec::new();
+    for trade in &solution.trades {
+        let solution::Trade::Fulfillment(fulfillment) = trade else {
+            return Some(false);
+        };
+        let (sell, buy) = transfers(fulfillment, &solution.prices)?;
+        flows.extend([(sell, true), (buy, false)]);
+    }
+    for interaction in interactions {
+        if let solution::Interaction::Liquidity(interaction) = interaction {
+            flows.extend([(interaction.output, true), (interaction.input, false)]);
+        }
+    }
+
+    let mut balances = HashMap::<eth::TokenAddress, (eth::U256, eth::U256)>::new();
+    for (asset, incoming) in flows {
+        let (credit, debit) = balances.entry(asset.token).or_default();
+        let balance = if incoming { credit } else { debit };
+        *balance = balance.checked_add(asset.amount)?;
+    }
+    Some(balances.values().all(|(credit, debit)| credit >= debit))
+}
+
+/// The sell tokens transferred into and the buy tokens transferred out of the
+/// settlement for a fulfillment at the specified clearing prices.
+pub fn transfers(
+    fulfillment: &solution::Fulfillment,
+    prices: &solution::ClearingPrices,
+) -> Option<(eth::Asset, eth::Asset)> {
+    let order = fulfillment.order();
+    let executed = fulfillment.executed().amount;
+    let fee = fulfillment
+        .surplus_fee()
+        .map(|fee| fee.amount)
+        .unwrap_or_default();
+    let sell_price = *prices.0.get(&order.sell.token)?;
+    let buy_price = *prices.0.get(&order.buy.token)?;
+
+    let (sell, buy) = match order.side {
+        order::Side::Sell => (
+            executed.checked_add(fee)?,
+            executed.checked_mul(sell_price)?.checked_div(buy_price)?,
+        ),
+        order::Side::Buy => (
+            executed
+                .checked_mul(buy_price)?
+                .checked_add(sell_price.checked_sub(1.into())?)?
+                .checked_div(sell_price)?
+                .checked_add(fee)?,
+            executed,
+        ),
+    };
+    Some((
+        eth::Asset {
+            token: order.sell.token,
+            amount: sell,
+        },
+        eth::Asset {
+            token: order.buy.token,
+            amount: buy,
+        },
+    ))
+}
+
+/// Re-evaluates which interactions of a joint solution can be internalized,
+/// as the buffers are now shared between the interactions of all merged
+/// solutions.
+fn reinternalize(mut solution: solution::Solution, tokens: &auction::Tokens) -> solution::Solution {
+    solution.clear_internalizations();
+    solution.with_buffers_internalizations(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::domain::liquidity::constant_product};
+
+    fn token(token: u64) -> eth::TokenAddress {
+        eth::TokenAddress(eth::H160::from_low_u64_be(token))
+    }
+
+    fn asset(token: u64, amount: u64) -> eth::Asset {
+        eth::Asset {
+            token: self::token(token),
+            amount: amount.into(),
+        }
+    }
+
+    fn pool(id: &str, a: eth::Asset, b: eth::Asset) -> liquidity::Liquidity {
+        liquidity::Liquidity {
+            id: liquidity::Id(id.to_owned()),
+            address: Default::default(),
+            balancer_pool_id: None,
+            gas: eth::Gas(100_000.into()),
+            state: liquidity::State::ConstantProduct(constant_product::Pool {
+                reserves: constant_product::Reserves::new(a, b).unwrap(),
+                fee: eth::Rational::new_raw(0.into(), 1.into()),
+            }),
+        }
+    }
+
+    fn swap(
+        liquidity: &liquidity::Liquidity,
+        input: eth::Asset,
+        output: eth::Asset,
+    ) -> solution::Interaction {
+        solution::Interaction::Liquidity(Box::new(solution::LiquidityInteraction {
+            liquidity: liquidity.clone(),
+            input,
+            output,
+            internalize: false,
+        }))
+    }
+
+    /// A solution selling 100 `sell` tokens for `buy` tokens at the specified
+    /// clearing prices.
+    fn solution(
+        id: u64,
+        (sell, sell_price): (u64, u64),
+        (buy, buy_price): (u64, u64),
+        interactions: Vec<solution::Interaction>,
+    ) -> solution::Solution {
+        let order = order::Order {
+            uid: order::Uid([id as u8; 56]),
+            sell: asset(sell, 100),
+            buy: asset(buy, 1),
+            ..Default::default()
+        };
+        solution::Solution {
+            id: solution::Id(id),
+            prices: solution::ClearingPrices::new([
+                (token(sell), sell_price.into()),
+                (token(buy), buy_price.into()),
+            ]),
+            trades: vec![solution::Trade::Fulfillment(
+                solution::Fulfillment::fill(order).unwrap(),
+            )],
+            interactions,
+            gas: Some(eth::Gas(200_000.into())),
+            ..Default::default()
+        }
+    }
+
+    async fn constant_product_out(
+        liquidity: &liquidity::Liquidity,
+        input: eth::Asset,
+        output: eth::TokenAddress,
+    ) -> Option<eth::U256> {
+        let reserves = liquidity.reserves();
+        let reserve = |token| {
+            reserves
+                .iter()
+                .find(|reserve| reserve.token == token)
+                .map(|reserve| reserve.amount)
+        };
+        let (reserve_in, reserve_out) = (reserve(input.token)?, reserve(output)?);
+        Some(reserve_out * input.amount / (reserve_in + input.amount))
+    }
+
+    /// A solution swapping through the pool `P` of tokens 1 and 2 and one
+    /// routing tokens 3 to 4 through `P` as well, paying out the specified
+    /// amount of token 4.
+    fn conflicting(payout: u64) -> [solution::Solution; 2] {
+        let p = pool("P", asset(1, 1000), asset(2, 1000));
+        let q = pool("Q", asset(3, 1_000_000), asset(1, 1_000_000));
+        let r = pool("R", asset(2, 1_000_000), asset(4, 1_000_000));
+
+        let first = solution(
+            0,
+            (1, 90),
+            (2, 100),
+            vec![swap(&p, asset(1, 100), asset(2, 90))],
+        );
+        let second = solution(
+            1,
+            (3, payout),
+            (4, 100),
+            vec![
+                swap(&q, asset(3, 100), asset(1, 99)),
+                swap(&p, asset(1, 99), asset(2, 90)),
+                swap(&r, asset(2, 90), asset(4, 89)),
+            ],
+        );
+        [first, second]
+    }
+
+    async fn merge_conflicting(payout: u64) -> Vec<solution::Solution> {
+        merge(
+            conflicting(payout).into(),
+            100_000.into(),
+            None,
+            &auction::Tokens(Default::default()),
+            constant_product_out,
+        )
+        .await
+    }
+
+    /// Reference prices for the specified tokens.
+    fn tokens(prices: &[(u64, u64)]) -> auction::Tokens {
+        auction::Tokens(
+            prices
+                .iter()
+                .map(|(token, price)| {
+                    (
+                        self::token(*token),
+                        auction::Token {
+                            decimals: Some(18),
+                            symbol: None,
+                            reference_price: Some(auction::Price(eth::Ether(
+                                eth::U256::from(*price) * eth::U256::exp10(18),
+                            ))),
+                            available_balance: Default::default(),
+                            trusted: false,
+                        },
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    /// Merges the specified solutions, returning the ids of the merged
+    /// solutions and the number of trades in each.
+    async fn select_conflicting(
+        solutions: Vec<solution::Solution>,
+        tokens: &auction::Tokens,
+    ) -> Vec<(u64, usize)> {
+        let merged = merge(
+            solutions,
+            100_000.into(),
+            None,
+            tokens,
+            constant_product_out,
+        )
+        .await;
+        merged
+            .iter()
+            .map(|solution| (solution.id.0, solution.trades.len()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn reprices_conflicting_routes() {
+        let merged = merge_conflicting(70).await;
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].trades.len(), 2);
+        assert_eq!(merged[0].gas.unwrap().0, 300_000.into());
+
+        // the second swap through `P` gets priced after the first one moved
+        // its reserves to 1100 and 910
+        let outputs = merged[0]
+            .interactions
+            .iter()
+            .map(|interaction| match interaction {
+                solution::Interaction::Liquidity(interaction) => interaction.output.amount,
+                solution::Interaction::Custom(_) => unreachable!(),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(outputs, [90.into(), 99.into(), 75.into(), 74.into()]);
+    }
+
+    #[tokio::test]
+    async fn splits_conflicting_routes_no_longer_covering_trades() {
+        // the re-priced route only yields 74 of the 89 tokens paid out
+        let merged = merge_conflicting(89).await;
+        let ids = merged
+            .iter()
+            .map(|solution| solution.id.0)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, [0, 1]);
+    }
+
+    #[tokio::test]
+    async fn selects_conflicting_solutions_with_most_surplus() {
+        // only one of the conflicting solutions fits the depth of `P`
+        let [first, second] = conflicting(89);
+        let independent = solution(2, (5, 1), (6, 1), Vec::new());
+
+        // the first solution generates a surplus of 89 tokens, the second one
+        // of 88 tokens worth twice as much, so it gets the depth of `P`
+        let selected = select_conflicting(
+            vec![first, second, independent],
+            &tokens(&[(1, 1), (2, 1), (3, 1), (4, 2)]),
+        )
+        .await;
+        assert_eq!(selected, [(1, 2), (0, 1)]);
+    }
+
+    #[tokio::test]
+    async fn breaks_surplus_ties_by_order_uid() {
+        // only one of the conflicting solutions fits the depth of `P`
+        let [first, second] = conflicting(89);
+        let independent = solution(2, (5, 1), (6, 1), Vec::new());
+
+        // both solutions generate a surplus worth 89 * 88, so the one with the
+        // smaller order uid gets selected regardless of the order they come in
+        let selected = select_conflicting(
+            vec![independent, second, first],
+            &tokens(&[(2, 88), (4, 89)]),
+        )
+        .await;
+        assert_eq!(selected, [(0, 2), (1, 1)]);
+    }
+
+    #[tokio::test]
+    async fn merges_independent_solutions() {
+        let merged = merge(
+            vec![
+                solution(0, (1, 1), (2, 1), Vec::new()),
+                solution(1, (2, 1), (3, 1), Vec::new()),
+                solution(2, (3, 1), (4, 1), Vec::new()),
+            ],
+            100_000.into(),
+            None,
+            &auction::Tokens(Default::default()),
+            constant_product_out,
+        )
+        .await;
+
+        // the first and last solution are independent, but the second one
+        // trades tokens of both of them
+        let ids = merged
+            .iter()
+            .map(|solution| solution.id.0)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, [0, 1]);
+        assert_eq!(merged[0].trades.len(), 2);
+        assert_eq!(merged[0].prices.0.len(), 4);
+        assert_eq!(merged[1].trades.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn keeps_solutions_exceeding_gas_budget_together_separate() {
+        let solutions = || {
+            vec![
+                solution(0, (1, 1), (2, 1), Vec::new()),
+                solution(1, (3, 1), (4, 1), Vec::new()),
+                solution(2, (5, 1), (6, 1), Vec::new()),
+            ]
+        };
+        let merged = async |gas: u64| {
+            merge(
+                solutions(),
+                100_000.into(),
+                Some(&gas_budget::Budget(eth::Gas(gas.into()))),
+                &auction::Tokens(Default::default()),
+                constant_product_out,
+            )
+            .await
+            .iter()
+            .map(|solution| (solution.id.0, solution.gas.unwrap().0.as_u64()))
+            .collect::<Vec<_>>()
+        };
+
+        // every solution uses 200k gas, of which 100k are the settlement
+        // overhead that merged solutions only pay once
+        assert_eq!(merged(400_000).await, [(0, 400_000)]);
+        assert_eq!(merged(399_999).await, [(0, 300_000), (2, 200_000)]);
+        assert_eq!(
+            merged(299_999).await,
+            [(0, 200_000), (1, 200_000), (2, 200_000)]
+        );
+    }
+}
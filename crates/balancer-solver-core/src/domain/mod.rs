@@ -0,0 +1,27 @@
+//! Core solver engine logic.
+
+pub mod auction;
+pub mod bad_tokens;
+pub mod base_tokens;
+pub mod batch_route;
+pub mod bidding;
+pub mod diagnostics;
+pub mod eth;
+pub mod events;
+pub mod gas_budget;
+pub mod inventory;
+pub mod liquidity;
+pub mod lp;
+pub mod merge;
+pub mod netting;
+pub mod notification;
+pub mod order;
+pub mod order_book;
+pub mod price_guard;
+pub mod quote_matrix;
+pub mod self_trade;
+pub mod slippage;
+pub mod solution;
+pub mod solver;
+pub mod stats;
+pub mod validation;
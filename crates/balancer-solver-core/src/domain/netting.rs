@@ -0,0 +1,284 @@
+//! Netting of the vault interactions of joint solutions.
+//!
+//! The routes of merged solutions may wrap or unwrap the same token through
+//! the same ERC4626 vault, e.g. when several orders get routed through a
+//! wrapped token as an intermediate hop. Vaults convert at a rate that doesn't
+//! depend on the converted amount, so these interactions get consolidated into
+//! a single interaction per vault and direction converting the summed amount,
+//! saving the gas of the others. As vaults round converted amounts down, the
+//! consolidated interaction yields at least the summed outputs.
+
+use {
+    crate::domain::{eth, liquidity, merge, solution},
+    std::collections::HashMap,
+};
+
+/// Consolidates the interactions converting the same token through the same
+/// ERC4626 vault. Interactions get reordered so that the consolidated ones
+/// execute once all their inputs are available. Solutions that can't be
+/// netted are returned unchanged.
+pub fn net(mut solution: solution::Solution) -> solution::Solution {
+    let Some(plan) = plan(&solution) else {
+        return solution;
+    };
+    tracing::debug!(
+        id = ?solution.id,
+        interactions = plan.order.len(),
+        saved_gas = ?plan.saved,
+        "netted vault interactions"
+    );
+
+    let mut interactions = solution
+        .interactions
+        .into_iter()
+        .map(Some)
+        .collect::<Vec<_>>();
+    solution.interactions = plan
+        .order
+        .into_iter()
+        .map(|i| {
+            let mut interaction = interactions[i].take().expect("interactions scheduled once");
+            if let (solution::Interaction::Liquidity(swap), Some((input, output))) =
+                (&mut interaction, plan.flows[i])
+            {
+                swap.input = input;
+                swap.output = output;
+            }
+            interaction
+        })
+        .collect();
+    solution.gas = solution
+        .gas
+        .map(|gas| eth::Gas(gas.0.saturating_sub(plan.saved)));
+    solution
+}
+
+/// The netted interactions of a solution.
+struct Plan {
+    /// The indices of the remaining interactions in the order to execute them
+    /// in.
+    order: Vec<usize>,
+    /// The input and output of every interaction, or `None` for interactions
+    /// that got consolidated into an earlier one.
+    flows: Vec<Option<(eth::Asset, eth::Asset)>>,
+    /// The gas of the interactions that got consolidated.
+    saved: eth::U256,
+}
+
+/// Plans the netting of the solution's interactions, returning `None` if
+/// nothing can be netted.
+fn plan(solution: &solution::Solution) -> Option<Plan> {
+    let mut balances = inflows(solution)?;
+
+    let mut flows = Vec::<Option<(eth::Asset, eth::Asset)>>::new();
+    let mut saved = eth::U256::zero();
+    for interaction in &solution.interactions {
+        // The flows of custom interactions are only documented for
+        // internalization, so they aren't rescheduled.
+        let solution::Interaction::Liquidity(swap) = interaction else {
+            return None;
+        };
+        let netted = (0..flows.len())
+            .find(|j| flows[*j].is_some() && nets_with(&solution.interactions[*j], swap));
+        match netted {
+            Some(j) => {
+                let (input, output) = flows[j].as_mut()?;
+                input.amount = input.amount.checked_add(swap.input.amount)?;
+                output.amount = output.amount.checked_add(swap.output.amount)?;
+                saved = saved.checked_add(swap.liquidity.gas.0)?;
+                flows.push(None);
+            }
+            None => flows.push(Some((swap.input, swap.output))),
+        }
+    }
+    if saved.is_zero() {
+        return None;
+    }
+
+    // A consolidated interaction needs the inputs of all the interactions it
+    // replaces, which later routes may only produce further down. Every
+    // interaction therefore gets executed as soon as the settlement holds its
+    // input, keeping the original order otherwise.
+    let mut pending = (0..flows.len())
+        .filter(|i| flows[*i].is_some())
+        .collect::<Vec<_>>();
+    let mut order = Vec::with_capacity(pending.len());
+    while !pending.is_empty() {
+        let next = pending.iter().position(|i| {
+            let (input, _) = flows[*i].expect("pending interactions have flows");
+            balances
+                .get(&input.token)
+                .is_some_and(|balance| *balance >= input.amount)
+        })?;
+        let i = pending.remove(next);
+        let (input, output) = flows[i].expect("pending interactions have flows");
+        let balance = balances.get_mut(&input.token)?;
+        *balance -= input.amount;
+        let balance = balances.entry(output.token).or_default();
+        *balance = balance.checked_add(output.amount)?;
+        order.push(i);
+    }
+
+    Some(Plan {
+        order,
+        flows,
+        saved,
+    })
+}
+
+/// Returns whether the swap converts the same token through the same vault as
+/// the interaction.
+fn nets_with(interaction: &solution::Interaction, swap: &solution::LiquidityInteraction) -> bool {
+    let solution::Interaction::Liquidity(interaction) = interaction else {
+        return false;
+    };
+    matches!(interaction.liquidity.state, liquidity::State::Erc4626(_))
+        && interaction.liquidity.id == swap.liquidity.id
+        && interaction.input.token == swap.input.token
+}
+
+/// The sell tokens transferred into the settlement by the trades of the
+/// solution, which the interactions can use.
+fn inflows(solution: &solution::Solution) -> Option<HashMap<eth::TokenAddress, eth::U256>> {
+    let mut balances = HashMap::<eth::TokenAddress, eth::U256>::new();
+    for trade in &solution.trades {
+        let solution::Trade::Fulfillment(fulfillment) = trade else {
+            return None;
+        };
+        let (sell, _) = merge::transfers(fulfillment, &solution.prices)?;
+        let balance = balances.entry(sell.token).or_default();
+        *balance = balance.checked_add(sell.amount)?;
+    }
+    Some(balances)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::domain::{
+            liquidity::{constant_product, erc4626},
+            order,
+        },
+    };
+
+    fn token(token: u64) -> eth::TokenAddress {
+        eth::TokenAddress(eth::H160::from_low_u64_be(token))
+    }
+
+    fn asset(token: u64, amount: u64) -> eth::Asset {
+        eth::Asset {
+            token: self::token(token),
+            amount: amount.into(),
+        }
+    }
+
+    fn pool(id: &str, a: eth::Asset, b: eth::Asset) -> liquidity::Liquidity {
+        liquidity::Liquidity {
+            id: liquidity::Id(id.to_owned()),
+            address: Default::default(),
+            balancer_pool_id: None,
+            gas: eth::Gas(100_000.into()),
+            state: liquidity::State::ConstantProduct(constant_product::Pool {
+                reserves: constant_product::Reserves::new(a, b).unwrap(),
+                fee: eth::Rational::new_raw(0.into(), 1.into()),
+            }),
+        }
+    }
+
+    fn swap(
+        liquidity: &liquidity::Liquidity,
+        input: eth::Asset,
+        output: eth::Asset,
+    ) -> solution::Interaction {
+        solution::Interaction::Liquidity(Box::new(solution::LiquidityInteraction {
+            liquidity: liquidity.clone(),
+            input,
+            output,
+            internalize: false,
+        }))
+    }
+
+    fn fulfillment(id: u8, sell: u64, buy: u64) -> solution::Trade {
+        solution::Trade::Fulfillment(
+            solution::Fulfillment::fill(order::Order {
+                uid: order::Uid([id; 56]),
+                sell: asset(sell, 100),
+                buy: asset(buy, 1),
+                owner: eth::Address(eth::H160::from_low_u64_be(id.into())),
+                ..Default::default()
+            })
+            .unwrap(),
+        )
+    }
+
+    fn swaps(solution: &solution::Solution) -> Vec<(String, u64, u64)> {
+        solution
+            .interactions
+            .iter()
+            .map(|interaction| match interaction {
+                solution::Interaction::Liquidity(swap) => (
+                    swap.liquidity.id.0.clone(),
+                    swap.input.amount.as_u64(),
+                    swap.output.amount.as_u64(),
+                ),
+                solution::Interaction::Custom(_) => unreachable!(),
+            })
+            .collect()
+    }
+
+    /// Tests that two routes wrapping token 5 into the vault token 6 as an
+    /// intermediate hop get a single wrap, which waits for the inputs of both
+    /// routes.
+    #[test]
+    fn consolidates_vault_interactions() {
+        let vault = liquidity::Liquidity {
+            id: liquidity::Id("V".to_owned()),
+            address: Default::default(),
+            balancer_pool_id: None,
+            gas: eth::Gas(50_000.into()),
+            state: liquidity::State::Erc4626(erc4626::Edge {
+                asset: token(5),
+                vault: token(6),
+            }),
+        };
+        let (p, q, r, s) = (
+            pool("P", asset(1, 1000), asset(5, 1000)),
+            pool("Q", asset(6, 1000), asset(2, 1000)),
+            pool("R", asset(3, 1000), asset(5, 1000)),
+            pool("S", asset(6, 1000), asset(4, 1000)),
+        );
+        let solution = solution::Solution {
+            prices: solution::ClearingPrices::new([
+                (token(1), 80.into()),
+                (token(2), 100.into()),
+                (token(3), 70.into()),
+                (token(4), 100.into()),
+            ]),
+            trades: vec![fulfillment(1, 1, 2), fulfillment(2, 3, 4)],
+            interactions: vec![
+                swap(&p, asset(1, 100), asset(5, 95)),
+                swap(&vault, asset(5, 95), asset(6, 90)),
+                swap(&q, asset(6, 90), asset(2, 85)),
+                swap(&r, asset(3, 100), asset(5, 80)),
+                swap(&vault, asset(5, 80), asset(6, 75)),
+                swap(&s, asset(6, 75), asset(4, 70)),
+            ],
+            gas: Some(eth::Gas(700_000.into())),
+            ..Default::default()
+        };
+
+        let netted = net(solution);
+        assert_eq!(
+            swaps(&netted),
+            [
+                ("P".to_owned(), 100, 95),
+                ("R".to_owned(), 100, 80),
+                ("V".to_owned(), 175, 165),
+                ("Q".to_owned(), 90, 85),
+                ("S".to_owned(), 75, 70),
+            ]
+        );
+        assert_eq!(netted.gas.unwrap().0, 650_000.into());
+    }
+}
@@ -1,7 +1,10 @@
 //! The domain object representing a CoW Protocol order.
 
 use {
-    crate::{domain::eth, util},
+    crate::{
+        domain::{eth, liquidity},
+        util,
+    },
     ethcontract::H160,
     ethereum_types::{Address, H256},
     std::fmt::{self, Debug, Display, Formatter},
@@ -18,6 +21,15 @@ pub struct Order {
     pub partially_fillable: bool,
     pub flashloan_hint: Option<FlashloanHint>,
     pub wrappers: Vec<WrapperCall>,
+    /// Pins the route of the order to specific liquidity, if possible.
+    pub route_pin: Option<RoutePin>,
+    pub owner: eth::Address,
+    pub valid_to: u32,
+    /// The order signature, or `None` if the signature bytes are malformed
+    /// for the order's signing scheme.
+    pub signature: Option<Signature>,
+    pub sell_token_source: SellTokenSource,
+    pub pre_interactions: Vec<eth::Interaction>,
 }
 
 impl Order {
@@ -27,6 +39,34 @@ impl Order {
     }
 }
 
+/// A fill-or-kill market sell order between zero token amounts, for tests to
+/// override the fields they care about.
+#[cfg(test)]
+impl Default for Order {
+    fn default() -> Self {
+        let asset = eth::Asset {
+            token: eth::TokenAddress(H160::zero()),
+            amount: eth::U256::zero(),
+        };
+        Self {
+            uid: Uid([0; 56]),
+            sell: asset,
+            buy: asset,
+            side: Side::Sell,
+            class: Class::Market,
+            partially_fillable: false,
+            flashloan_hint: None,
+            wrappers: Vec::new(),
+            route_pin: None,
+            owner: Default::default(),
+            valid_to: u32::MAX,
+            signature: None,
+            sell_token_source: SellTokenSource::Erc20,
+            pre_interactions: Vec::new(),
+        }
+    }
+}
+
 /// UID of an order.
 #[derive(Clone, Copy, Eq, Hash, PartialEq)]
 pub struct Uid(pub [u8; 56]);
@@ -61,6 +101,17 @@ pub enum Class {
     Limit,
 }
 
+/// Source from which the sell tokens of an order are drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SellTokenSource {
+    /// Direct ERC20 allowances to the GPv2 Vault relayer contract.
+    Erc20,
+    /// ERC20 allowances to the Balancer Vault with GPv2 relayer approval.
+    External,
+    /// Balancer Vault internal balances with GPv2 relayer approval.
+    Internal,
+}
+
 /// An order that can be used to provide just-in-time liquidity in form of a CoW
 /// Protocol order. This is how solvers integrate private market makers into
 /// their solutions.
@@ -114,6 +165,15 @@ pub struct EcdsaSignature {
 }
 
 impl EcdsaSignature {
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let bytes: &[u8; 65] = bytes.try_into().ok()?;
+        Some(Self {
+            r: H256::from_slice(&bytes[..32]),
+            s: H256::from_slice(&bytes[32..64]),
+            v: bytes[64],
+        })
+    }
+
     pub fn to_bytes(self) -> [u8; 65] {
         let mut bytes = [0u8; 65];
         bytes[..32].copy_from_slice(self.r.as_bytes());
@@ -152,3 +212,25 @@ pub struct WrapperCall {
     pub address: H160,
     pub data: Vec<u8>,
 }
+
+/// A hint pinning the route of an order to specific liquidity, for debugging
+/// incidents and running directed experiments. Orders get routed over all
+/// liquidity when no route over the pinned liquidity exists.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RoutePin {
+    /// Route only through liquidity of this kind, as in
+    /// [`liquidity::State::kind`].
+    Protocol(String),
+    /// Route only through the liquidity with this ID.
+    Liquidity(liquidity::Id),
+}
+
+impl RoutePin {
+    /// Returns whether the route of a pinned order may use the liquidity.
+    pub fn allows(&self, liquidity: &liquidity::Liquidity) -> bool {
+        match self {
+            Self::Protocol(kind) => liquidity.state.kind() == kind,
+            Self::Liquidity(id) => liquidity.id == *id,
+        }
+    }
+}
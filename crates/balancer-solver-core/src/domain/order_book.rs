@@ -0,0 +1,300 @@
+//! Resting orders of the CoW Protocol order book as liquidity.
+//!
+//! The order book holds open orders of other users that are not necessarily
+//! part of the auction being solved, e.g. quotes only contain the quoted order.
+//! Signed orders can be settled by any solution as JIT trades at their limit
+//! price, so the resting orders protocol rules allow to be settled this way
+//! are modelled as limit-order liquidity that routes can swap through. The
+//! swaps of a solution through resting orders are then replaced by JIT trades
+//! of these orders, matching the auction's orders peer-to-peer.
+
+use {
+    crate::domain::{auction, eth, liquidity, order, solution},
+    std::collections::{HashMap, HashSet},
+};
+
+/// Gas used for settling a resting order as a JIT trade, including the
+/// transfers in and out of the settlement contract.
+const GAS: u64 = 100_000;
+
+/// The prefix of the liquidity ids of resting orders.
+const ID_PREFIX: &str = "cow-order-";
+
+/// A resting sell order of the order book that can be partially filled.
+#[derive(Clone, Debug)]
+pub struct Order {
+    pub uid: order::Uid,
+    pub owner: eth::Address,
+    /// The receiver of the bought tokens as signed, which is the zero address
+    /// for the owner.
+    pub receiver: eth::Address,
+    pub sell: eth::Asset,
+    pub buy: eth::Asset,
+    /// The amount of the sell token that was already executed.
+    pub executed: eth::U256,
+    pub class: order::Class,
+    pub valid_to: u32,
+    pub app_data: order::AppData,
+    pub signature: order::Signature,
+}
+
+impl Order {
+    /// The sell and buy amounts left to be filled.
+    fn remaining(&self) -> Option<(eth::Asset, eth::Asset)> {
+        let sell = self.sell.amount.checked_sub(self.executed)?;
+        let buy = self
+            .buy
+            .amount
+            .checked_mul(sell)?
+            .checked_div(self.sell.amount)?;
+        (!sell.is_zero() && !buy.is_zero()).then_some((
+            eth::Asset {
+                token: self.sell.token,
+                amount: sell,
+            },
+            eth::Asset {
+                token: self.buy.token,
+                amount: buy,
+            },
+        ))
+    }
+
+    fn jit(&self) -> order::JitOrder {
+        order::JitOrder {
+            owner: self.owner.0,
+            signature: self.signature.clone(),
+            sell: self.sell,
+            buy: self.buy,
+            side: order::Side::Sell,
+            class: self.class,
+            partially_fillable: true,
+            valid_to: self.valid_to,
+            app_data: self.app_data,
+            receiver: self.receiver.0,
+        }
+    }
+}
+
+/// The resting orders an auction can be matched against, by the id of their
+/// liquidity.
+#[derive(Debug, Default)]
+pub struct Resting(HashMap<liquidity::Id, Order>);
+
+impl Resting {
+    /// Selects the resting orders that can be settled along with the auction.
+    /// Orders that are part of the auction get settled as regular trades and
+    /// orders expiring before the auction's deadline can't be settled in time.
+    pub fn new(orders: &[Order], auction: &auction::Auction) -> Self {
+        let uids = auction
+            .orders
+            .iter()
+            .map(|order| order.uid)
+            .collect::<HashSet<_>>();
+        let deadline = auction.deadline.0.timestamp();
+        Self(
+            orders
+                .iter()
+                .filter(|order| {
+                    !uids.contains(&order.uid)
+                        && i64::from(order.valid_to) > deadline
+                        && order.remaining().is_some()
+                })
+                .map(|order| {
+                    (
+                        liquidity::Id(format!("{ID_PREFIX}{}", order.uid)),
+                        order.clone(),
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The resting orders as limit-order liquidity selling their remaining
+    /// amounts.
+    pub fn liquidity(&self) -> impl Iterator<Item = liquidity::Liquidity> + '_ {
+        self.0.iter().filter_map(|(id, order)| {
+            let (sell, buy) = order.remaining()?;
+            Some(liquidity::Liquidity {
+                id: id.clone(),
+                address: order.owner.0,
+                balancer_pool_id: None,
+                gas: eth::Gas(GAS.into()),
+                state: liquidity::State::LimitOrder(liquidity::limit_order::LimitOrder {
+                    maker: sell,
+                    taker: buy,
+                    fee: liquidity::limit_order::TakerAmount(0.into()),
+                }),
+            })
+        })
+    }
+
+    /// Replaces the swaps of a solution through resting orders with JIT
+    /// trades of these orders. Returns `None` if a swap can't be settled at
+    /// the order's limit price, in which case the solution must be dropped.
+    pub fn settle(&self, mut solution: solution::Solution) -> Option<solution::Solution> {
+        let mut settled = HashSet::new();
+        let mut interactions = Vec::with_capacity(solution.interactions.len());
+        for interaction in std::mem::take(&mut solution.interactions) {
+            let order = match &interaction {
+                solution::Interaction::Liquidity(swap) => self.0.get(&swap.liquidity.id),
+                solution::Interaction::Custom(_) => None,
+            };
+            let (Some(order), solution::Interaction::Liquidity(swap)) = (order, &interaction)
+            else {
+                interactions.push(interaction);
+                continue;
+            };
+
+            // The order sells the swap's output and buys its input, paying
+            // out at most the swap's input at its limit price.
+            let executed = swap.output.amount;
+            let (remaining, _) = order.remaining()?;
+            let paid = executed
+                .checked_mul(order.buy.amount)?
+                .checked_add(order.sell.amount.checked_sub(1.into())?)?
+                .checked_div(order.sell.amount)?;
+            if swap.output.token != order.sell.token
+                || swap.input.token != order.buy.token
+                || executed > remaining.amount
+                || paid > swap.input.amount
+                || !settled.insert(order.uid)
+            {
+                return None;
+            }
+            solution
+                .trades
+                .push(solution::Trade::Jit(solution::JitTrade {
+                    order: order.jit(),
+                    executed,
+                    fee: eth::SellTokenAmount(0.into()),
+                }));
+        }
+        solution.interactions = interactions;
+        Some(solution)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(token: u64) -> eth::TokenAddress {
+        eth::TokenAddress(eth::H160::from_low_u64_be(token))
+    }
+
+    fn asset(token: u64, amount: u64) -> eth::Asset {
+        eth::Asset {
+            token: self::token(token),
+            amount: amount.into(),
+        }
+    }
+
+    fn amount(asset: &eth::Asset) -> (eth::TokenAddress, eth::U256) {
+        (asset.token, asset.amount)
+    }
+
+    /// A resting order selling 1000 of token 2 for 500 of token 1.
+    fn resting(uid: u8, executed: u64) -> Order {
+        Order {
+            uid: order::Uid([uid; 56]),
+            owner: eth::Address(eth::H160::from_low_u64_be(7)),
+            receiver: Default::default(),
+            sell: asset(2, 1000),
+            buy: asset(1, 500),
+            executed: executed.into(),
+            class: order::Class::Limit,
+            valid_to: u32::MAX,
+            app_data: Default::default(),
+            signature: order::Signature::Eip712(Default::default()),
+        }
+    }
+
+    fn auction(uids: &[u8]) -> auction::Auction {
+        auction::Auction {
+            id: auction::Id::Quote,
+            tokens: auction::Tokens(Default::default()),
+            orders: uids
+                .iter()
+                .map(|uid| order::Order {
+                    uid: order::Uid([*uid; 56]),
+                    sell: asset(1, 100),
+                    buy: asset(2, 100),
+                    ..Default::default()
+                })
+                .collect(),
+            liquidity: Vec::new(),
+            gas_price: auction::GasPrice(eth::Ether(0.into())),
+            deadline: auction::Deadline(chrono::Utc::now()),
+            gas_limit: None,
+        }
+    }
+
+    fn swap(resting: &Resting, input: eth::Asset, output: eth::Asset) -> solution::Solution {
+        solution::Solution {
+            interactions: vec![solution::Interaction::Liquidity(Box::new(
+                solution::LiquidityInteraction {
+                    liquidity: resting.liquidity().next().unwrap(),
+                    input,
+                    output,
+                    internalize: false,
+                },
+            ))],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn models_remaining_amounts_as_liquidity() {
+        let resting = Resting::new(&[resting(1, 600), resting(2, 1000)], &auction(&[]));
+
+        // the fully executed order can't be matched anymore
+        let liquidity = resting.liquidity().collect::<Vec<_>>();
+        assert_eq!(liquidity.len(), 1);
+        let liquidity::State::LimitOrder(limit_order) = &liquidity[0].state else {
+            panic!("unexpected liquidity {liquidity:?}");
+        };
+        assert_eq!(amount(&limit_order.maker), (token(2), 400.into()));
+        assert_eq!(amount(&limit_order.taker), (token(1), 200.into()));
+    }
+
+    #[test]
+    fn skips_auction_orders_and_expiring_orders() {
+        let expiring = Order {
+            valid_to: 0,
+            ..resting(2, 0)
+        };
+        let resting = Resting::new(&[resting(1, 0), expiring], &auction(&[1]));
+        assert!(resting.is_empty());
+    }
+
+    #[test]
+    fn settles_swaps_as_jit_trades() {
+        let resting = Resting::new(&[resting(1, 0)], &auction(&[]));
+        let solution = resting
+            .settle(swap(&resting, asset(1, 101), asset(2, 200)))
+            .unwrap();
+
+        assert!(solution.interactions.is_empty());
+        let [solution::Trade::Jit(trade)] = &solution.trades[..] else {
+            panic!("unexpected trades {:?}", solution.trades);
+        };
+        assert_eq!(trade.executed, 200.into());
+        assert_eq!(amount(&trade.order.sell), (token(2), 1000.into()));
+        assert_eq!(amount(&trade.order.buy), (token(1), 500.into()));
+    }
+
+    #[test]
+    fn rejects_swaps_beyond_limit_price() {
+        let resting = Resting::new(&[resting(1, 0)], &auction(&[]));
+        // selling 200 tokens requires paying 100 tokens
+        assert!(
+            resting
+                .settle(swap(&resting, asset(1, 99), asset(2, 200)))
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,168 @@
+//! Sanity checking of solutions against external oracle prices.
+//!
+//! The clearing prices of a solution imply an exchange rate for every traded
+//! token pair. If that rate deviates from the rate implied by independent
+//! oracle prices by more than a threshold, the solution is likely the result
+//! of a bug in the pool math or of poisoned pool state. Such solutions get
+//! flagged or rejected depending on the configuration.
+
+use {
+    crate::{
+        domain::{auction, eth, solution},
+        infra::{self, metrics},
+    },
+    std::collections::{HashMap, HashSet},
+};
+
+/// What to do with solutions deviating from the oracle prices.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    /// Log and count the deviating solutions but keep proposing them.
+    Flag,
+    /// Drop the deviating solutions.
+    Reject,
+}
+
+pub struct Config {
+    pub oracle: infra::oracle::Oracle,
+    /// The maximum relative deviation of a clearing price ratio from the
+    /// oracle price ratio, e.g. `0.1` for 10%.
+    pub max_deviation: f64,
+    pub action: Action,
+}
+
+pub struct Guard(Config);
+
+impl Guard {
+    pub fn new(config: Config) -> Self {
+        Self(config)
+    }
+
+    /// Checks the clearing prices of the specified solutions against the
+    /// oracle prices, returning the solutions to propose. Token pairs without
+    /// oracle prices for both tokens are not checked.
+    pub async fn check(
+        &self,
+        mut solutions: Vec<solution::Solution>,
+        tokens: &auction::Tokens,
+    ) -> Vec<solution::Solution> {
+        let traded = solutions
+            .iter()
+            .flat_map(pairs)
+            .flat_map(|(sell, buy)| [sell, buy])
+            .collect::<HashSet<_>>();
+        let prices = futures::future::join_all(traded.into_iter().map(async |token| {
+            let decimals = tokens.0.get(&token).and_then(|token| token.decimals);
+            let price = self.0.oracle.native_price(token, decimals).await?;
+            Some((token, price))
+        }))
+        .await
+        .into_iter()
+        .flatten()
+        .collect::<HashMap<_, _>>();
+
+        solutions.retain(|solution| {
+            let Some(deviation) = max_deviation(solution, &prices) else {
+                return true;
+            };
+            if deviation <= self.0.max_deviation {
+                return true;
+            }
+            tracing::warn!(
+                id = ?solution.id,
+                deviation,
+                action = ?self.0.action,
+                "solution clearing prices deviate from oracle prices"
+            );
+            metrics::oracle_price_deviation(self.0.action);
+            self.0.action == Action::Flag
+        });
+        solutions
+    }
+}
+
+/// The sell and buy token pairs of the orders traded by a solution.
+fn pairs(
+    solution: &solution::Solution,
+) -> impl Iterator<Item = (eth::TokenAddress, eth::TokenAddress)> + '_ {
+    solution.trades.iter().filter_map(|trade| match trade {
+        solution::Trade::Fulfillment(fulfillment) => {
+            let order = fulfillment.order();
+            Some((order.sell.token, order.buy.token))
+        }
+        solution::Trade::Jit(_) => None,
+    })
+}
+
+/// The largest relative deviation of the exchange rate implied by the clearing
+/// prices from the one implied by the oracle prices over all traded pairs, or
+/// `None` if no pair could be checked.
+fn max_deviation(
+    solution: &solution::Solution,
+    prices: &HashMap<eth::TokenAddress, f64>,
+) -> Option<f64> {
+    pairs(solution)
+        .filter_map(|(sell, buy)| {
+            let clearing = solution.prices.0.get(&sell)?.to_f64_lossy()
+                / solution.prices.0.get(&buy)?.to_f64_lossy();
+            let oracle = prices.get(&sell)? / prices.get(&buy)?;
+            let deviation = (clearing / oracle - 1.).abs();
+            deviation.is_finite().then_some(deviation)
+        })
+        .max_by(f64::total_cmp)
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::domain::order};
+
+    fn token(byte: u8) -> eth::TokenAddress {
+        eth::TokenAddress(eth::H160::repeat_byte(byte))
+    }
+
+    fn solution(sell_price: u64, buy_price: u64) -> solution::Solution {
+        let order = order::Order {
+            uid: order::Uid([1; 56]),
+            sell: eth::Asset {
+                token: token(1),
+                amount: 1.into(),
+            },
+            buy: eth::Asset {
+                token: token(2),
+                amount: 1000.into(),
+            },
+            ..Default::default()
+        };
+        solution::Solution {
+            prices: solution::ClearingPrices::new([
+                (token(1), sell_price.into()),
+                (token(2), buy_price.into()),
+            ]),
+            trades: vec![solution::Trade::Fulfillment(
+                solution::Fulfillment::fill(order).unwrap(),
+            )],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn computes_deviation_from_oracle_prices() {
+        // one token 1 atom is worth 2000 token 2 atoms according to the oracle
+        let prices = HashMap::from([(token(1), 1.), (token(2), 0.0005)]);
+
+        let fair = solution(2000, 1);
+        assert!(max_deviation(&fair, &prices).unwrap() < 1e-9);
+
+        let generous = solution(2500, 1);
+        assert!((max_deviation(&generous, &prices).unwrap() - 0.25).abs() < 1e-9);
+
+        let poor = solution(1500, 1);
+        assert!((max_deviation(&poor, &prices).unwrap() - 0.25).abs() < 1e-9);
+    }
+
+    #[test]
+    fn skips_pairs_without_oracle_prices() {
+        let prices = HashMap::from([(token(2), 1.)]);
+        assert_eq!(max_deviation(&solution(2000, 1), &prices), None);
+    }
+}
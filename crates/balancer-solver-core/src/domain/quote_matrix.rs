@@ -0,0 +1,335 @@
+//! Price matrix for low-latency quoting of top token pairs.
+//!
+//! Path search over the full liquidity of an auction dominates the time it
+//! takes to compute a quote. For a configured set of top token pairs, the best
+//! routes get recomputed on every block from the liquidity of the latest solved
+//! auction, together with the prices they imply. Quotes for these pairs are
+//! then priced at the cached routes' prices, which bypasses path search and the
+//! fetching of liquidity.
+
+use {
+    crate::{
+        domain::{auction, eth, liquidity, order},
+        util::math,
+    },
+    std::{
+        collections::{HashMap, HashSet},
+        sync::{Arc, RwLock},
+    },
+};
+
+/// A directed token pair, from the sell to the buy token.
+type Pair = (eth::TokenAddress, eth::TokenAddress);
+
+pub struct Matrix {
+    pairs: HashSet<Pair>,
+    routes: RwLock<HashMap<Pair, Route>>,
+    snapshot: RwLock<Option<Arc<Snapshot>>>,
+}
+
+/// The best route for a token pair at the time the matrix was refreshed.
+#[derive(Clone, Debug)]
+pub struct Route {
+    /// The liquidity swapped through, in order.
+    pub liquidity: Vec<liquidity::Liquidity>,
+    /// The tokens along the route, from the sell to the buy token.
+    pub path: Vec<eth::TokenAddress>,
+    /// The amounts of the tokens along the route when swapping the reference
+    /// amount, which set the prices the route gets quoted at.
+    pub amounts: Vec<eth::U256>,
+}
+
+/// The liquidity of the latest solved auction, which the routes get recomputed
+/// from on every block.
+pub struct Snapshot {
+    pub liquidity: Vec<liquidity::Liquidity>,
+    pub tokens: auction::Tokens,
+}
+
+/// A swap of a quote served from the matrix.
+#[derive(Debug, PartialEq)]
+pub struct Hop {
+    pub liquidity: liquidity::Id,
+    pub input: eth::Asset,
+    pub output: eth::Asset,
+}
+
+impl Matrix {
+    /// Creates an empty matrix for the specified token pairs. Pairs get quoted
+    /// in both directions.
+    pub fn new(pairs: impl IntoIterator<Item = Pair>) -> Self {
+        Self {
+            pairs: pairs
+                .into_iter()
+                .filter(|(a, b)| a != b)
+                .flat_map(|(a, b)| [(a, b), (b, a)])
+                .collect(),
+            routes: Default::default(),
+            snapshot: Default::default(),
+        }
+    }
+
+    /// Returns the directed token pairs the matrix keeps routes for.
+    pub fn pairs(&self) -> impl Iterator<Item = Pair> + '_ {
+        self.pairs.iter().copied()
+    }
+
+    /// Returns whether quotes for all specified directed token pairs can be
+    /// served from the matrix.
+    pub fn covers(&self, pairs: impl IntoIterator<Item = Pair>) -> bool {
+        let routes = self.routes.read().unwrap();
+        let mut pairs = pairs.into_iter().peekable();
+        pairs.peek().is_some() && pairs.all(|pair| routes.contains_key(&pair))
+    }
+
+    /// Quotes swapping `amount` of the sell token for a sell order, or of the
+    /// buy token for a buy order, over the cached route of a directed token
+    /// pair. Every hop is priced at the rate it had for the reference amount,
+    /// so no swaps get simulated. Returns `None` on a miss, in which case the
+    /// quote has to be routed by path search.
+    pub fn quote(
+        &self,
+        (sell, buy): Pair,
+        side: order::Side,
+        amount: eth::U256,
+    ) -> Option<Vec<Hop>> {
+        let routes = self.routes.read().unwrap();
+        let route = routes.get(&(sell, buy))?;
+        let reference = match side {
+            order::Side::Sell => *route.amounts.first()?,
+            order::Side::Buy => *route.amounts.last()?,
+        };
+        if amount.is_zero() || reference.is_zero() {
+            return None;
+        }
+        let amounts = route
+            .amounts
+            .iter()
+            .map(|reference_amount| {
+                let scaled = reference_amount.checked_mul(amount)?;
+                match side {
+                    // Never quote more than the route produces, or less
+                    // than it takes.
+                    order::Side::Sell => Some(scaled / reference),
+                    order::Side::Buy => math::div_ceil(scaled, reference),
+                }
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(
+            route
+                .liquidity
+                .iter()
+                .enumerate()
+                .map(|(i, liquidity)| Hop {
+                    liquidity: liquidity.id.clone(),
+                    input: eth::Asset {
+                        token: route.path[i],
+                        amount: amounts[i],
+                    },
+                    output: eth::Asset {
+                        token: route.path[i + 1],
+                        amount: amounts[i + 1],
+                    },
+                })
+                .collect(),
+        )
+    }
+
+    /// Caches the liquidity of a solved auction for the next refreshes.
+    pub fn cache(&self, snapshot: Snapshot) {
+        *self.snapshot.write().unwrap() = Some(Arc::new(snapshot));
+    }
+
+    /// Returns the liquidity of the latest solved auction, if any.
+    pub fn snapshot(&self) -> Option<Arc<Snapshot>> {
+        self.snapshot.read().unwrap().clone()
+    }
+
+    /// Replaces the routes of the matrix. Pairs without a route can no longer
+    /// be served from the matrix until the next refresh finds one.
+    pub fn update(&self, routes: HashMap<Pair, Route>) {
+        let routes = routes
+            .into_iter()
+            .filter(|(pair, _)| self.pairs.contains(pair))
+            .collect();
+        *self.routes.write().unwrap() = routes;
+    }
+
+    /// Returns the liquidity of the cached routes for the specified orders and
+    /// the intermediate tokens of these routes, or `None` if one of the orders
+    /// can't be served from the matrix.
+    pub fn liquidity(
+        &self,
+        orders: &[order::Order],
+    ) -> Option<(Vec<liquidity::Liquidity>, HashSet<eth::TokenAddress>)> {
+        let routes = self.routes.read().unwrap();
+        let mut ids = HashSet::new();
+        let mut liquidity = Vec::new();
+        let mut hops = HashSet::new();
+        for order in orders {
+            let route = routes.get(&(order.sell.token, order.buy.token))?;
+            liquidity.extend(
+                route
+                    .liquidity
+                    .iter()
+                    .filter(|liquidity| ids.insert(liquidity.id.clone()))
+                    .cloned(),
+            );
+            hops.extend(
+                route
+                    .path
+                    .iter()
+                    .skip(1)
+                    .take(route.path.len().saturating_sub(2)),
+            );
+        }
+        (!orders.is_empty()).then_some((liquidity, hops))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(byte: u8) -> eth::TokenAddress {
+        eth::TokenAddress(eth::H160::repeat_byte(byte))
+    }
+
+    fn pool(id: &str, a: u8, b: u8) -> liquidity::Liquidity {
+        liquidity::Liquidity {
+            id: liquidity::Id(id.to_owned()),
+            address: eth::H160::zero(),
+            balancer_pool_id: None,
+            gas: eth::Gas(100_000.into()),
+            state: liquidity::State::LimitOrder(liquidity::limit_order::LimitOrder {
+                maker: eth::Asset {
+                    token: token(b),
+                    amount: 1000.into(),
+                },
+                taker: eth::Asset {
+                    token: token(a),
+                    amount: 1000.into(),
+                },
+                fee: liquidity::limit_order::TakerAmount(0.into()),
+            }),
+        }
+    }
+
+    /// A route over the specified pools, each of which swaps at a price of
+    /// one half.
+    fn route(pools: &[(&str, u8, u8)]) -> Route {
+        Route {
+            liquidity: pools.iter().map(|&(id, a, b)| pool(id, a, b)).collect(),
+            path: std::iter::once(token(pools[0].1))
+                .chain(pools.iter().map(|&(_, _, b)| token(b)))
+                .collect(),
+            amounts: (0..=pools.len())
+                .map(|i| eth::U256::from(1000_u64 >> i))
+                .collect(),
+        }
+    }
+
+    fn order(sell: u8, buy: u8) -> order::Order {
+        order::Order {
+            uid: order::Uid([sell; 56]),
+            sell: eth::Asset {
+                token: token(sell),
+                amount: 1.into(),
+            },
+            buy: eth::Asset {
+                token: token(buy),
+                amount: 1.into(),
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn serves_configured_pairs_in_both_directions() {
+        let matrix = Matrix::new([(token(1), token(2)), (token(3), token(3))]);
+        assert_eq!(matrix.pairs().count(), 2);
+
+        matrix.update(HashMap::from([
+            ((token(1), token(2)), route(&[("a", 1, 9), ("b", 9, 2)])),
+            ((token(2), token(1)), route(&[("c", 2, 1)])),
+            // not a configured pair
+            ((token(1), token(3)), route(&[("d", 1, 3)])),
+        ]));
+
+        assert!(matrix.covers([(token(1), token(2)), (token(2), token(1))]));
+        assert!(!matrix.covers([(token(1), token(3))]));
+        assert!(!matrix.covers(std::iter::empty()));
+    }
+
+    #[test]
+    fn quotes_at_route_prices() {
+        let matrix = Matrix::new([(token(1), token(2))]);
+        matrix.update(HashMap::from([(
+            (token(1), token(2)),
+            route(&[("a", 1, 9), ("b", 9, 2)]),
+        )]));
+        let hop = |liquidity: &str, (a, input): (u8, u64), (b, output): (u8, u64)| Hop {
+            liquidity: liquidity::Id(liquidity.to_owned()),
+            input: eth::Asset {
+                token: token(a),
+                amount: input.into(),
+            },
+            output: eth::Asset {
+                token: token(b),
+                amount: output.into(),
+            },
+        };
+
+        assert_eq!(
+            matrix.quote((token(1), token(2)), order::Side::Sell, 4001.into()),
+            Some(vec![
+                hop("a", (1, 4001), (9, 2000)),
+                hop("b", (9, 2000), (2, 1000))
+            ])
+        );
+        assert_eq!(
+            matrix.quote((token(1), token(2)), order::Side::Buy, 1001.into()),
+            Some(vec![
+                hop("a", (1, 4004), (9, 2002)),
+                hop("b", (9, 2002), (2, 1001))
+            ])
+        );
+
+        assert!(
+            matrix
+                .quote((token(2), token(1)), order::Side::Sell, 1000.into())
+                .is_none()
+        );
+        assert!(
+            matrix
+                .quote((token(1), token(2)), order::Side::Sell, 0.into())
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn collects_route_liquidity_of_orders() {
+        let matrix = Matrix::new([(token(1), token(2))]);
+        matrix.update(HashMap::from([
+            ((token(1), token(2)), route(&[("a", 1, 9), ("b", 9, 2)])),
+            ((token(2), token(1)), route(&[("b", 2, 9), ("a", 9, 1)])),
+        ]));
+
+        let (liquidity, hops) = matrix.liquidity(&[order(1, 2), order(2, 1)]).unwrap();
+        assert_eq!(
+            liquidity
+                .iter()
+                .map(|liquidity| liquidity.id.0.as_str())
+                .collect::<Vec<_>>(),
+            ["a", "b"]
+        );
+        assert_eq!(hops, HashSet::from([token(9)]));
+
+        assert!(matrix.liquidity(&[order(1, 2), order(1, 3)]).is_none());
+        assert!(matrix.liquidity(&[]).is_none());
+
+        // Refreshing without a route stops serving the pair.
+        matrix.update(HashMap::new());
+        assert!(matrix.liquidity(&[order(1, 2)]).is_none());
+    }
+}
@@ -0,0 +1,267 @@
+//! Prevention of self-trades and wash trades in solutions.
+//!
+//! Solutions may combine trades and interactions coming from different
+//! sources, e.g. merged local routes and solutions of external strategies.
+//! Such combinations can end up trading an account against itself, either
+//! directly by making and taking in the same solution or circularly by selling
+//! and buying the same token. These solutions only generate fake volume, so
+//! they get rejected before they are proposed.
+
+use {
+    crate::{
+        domain::{eth, solution},
+        infra::metrics,
+    },
+    std::collections::{HashMap, HashSet},
+};
+
+/// Why a solution was rejected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Violation {
+    /// A trade buys and sells the same token.
+    SameToken,
+    /// The address takes an order and makes the other side of the trade, e.g.
+    /// with a JIT order or as the target of an interaction.
+    SelfTrade(eth::Address),
+    /// The address both sells and buys the same token over the trades of the
+    /// solution, i.e. the trades form a circle.
+    WashTrade(eth::Address),
+}
+
+impl Violation {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Violation::SameToken => "same_token",
+            Violation::SelfTrade(_) => "self_trade",
+            Violation::WashTrade(_) => "wash_trade",
+        }
+    }
+}
+
+/// Removes the solutions trading an account against itself.
+pub fn filter(solutions: &mut Vec<solution::Solution>) {
+    solutions.retain(|solution| {
+        let Some(violation) = check(solution) else {
+            return true;
+        };
+        tracing::warn!(id = ?solution.id, ?violation, "rejecting self-trading solution");
+        metrics::self_trade(violation);
+        false
+    });
+}
+
+/// Checks the specified solution for self-trades and wash trades.
+pub fn check(solution: &solution::Solution) -> Option<Violation> {
+    // The traded assets of every order as `(owner, sell, buy, is_taker)`.
+    let orders = solution
+        .trades
+        .iter()
+        .map(|trade| match trade {
+            solution::Trade::Fulfillment(fulfillment) => {
+                let order = fulfillment.order();
+                (order.owner, order.sell.token, order.buy.token, true)
+            }
+            solution::Trade::Jit(jit) => {
+                let order = &jit.order;
+                (
+                    eth::Address(order.owner),
+                    order.sell.token,
+                    order.buy.token,
+                    false,
+                )
+            }
+        })
+        .collect::<Vec<_>>();
+
+    if orders.iter().any(|(_, sell, buy, _)| sell == buy) {
+        return Some(Violation::SameToken);
+    }
+
+    let takers = orders
+        .iter()
+        .filter(|(.., taker)| *taker)
+        .map(|(owner, ..)| *owner)
+        .collect::<HashSet<_>>();
+    let makers = orders
+        .iter()
+        .filter(|(.., taker)| !*taker)
+        .map(|(owner, ..)| *owner)
+        .chain(
+            solution
+                .interactions
+                .iter()
+                .map(|interaction| match interaction {
+                    solution::Interaction::Liquidity(interaction) => {
+                        eth::Address(interaction.liquidity.address)
+                    }
+                    solution::Interaction::Custom(interaction) => eth::Address(interaction.target),
+                }),
+        );
+    for maker in makers {
+        if takers.contains(&maker) {
+            return Some(Violation::SelfTrade(maker));
+        }
+    }
+
+    let mut flows = HashMap::<_, (HashSet<_>, HashSet<_>)>::new();
+    for (owner, sell, buy, _) in &orders {
+        let (sold, bought) = flows.entry(*owner).or_default();
+        sold.insert(*sell);
+        bought.insert(*buy);
+    }
+    flows
+        .into_iter()
+        .find(|(_, (sold, bought))| !sold.is_disjoint(bought))
+        .map(|(owner, _)| Violation::WashTrade(owner))
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::domain::{liquidity, order},
+    };
+
+    fn token(byte: u8) -> eth::TokenAddress {
+        eth::TokenAddress(eth::H160::repeat_byte(byte))
+    }
+
+    fn address(byte: u8) -> eth::Address {
+        eth::Address(eth::H160::repeat_byte(byte))
+    }
+
+    fn asset(byte: u8) -> eth::Asset {
+        eth::Asset {
+            token: token(byte),
+            amount: 1000.into(),
+        }
+    }
+
+    fn fulfillment(owner: u8, sell: u8, buy: u8) -> solution::Trade {
+        solution::Trade::Fulfillment(
+            solution::Fulfillment::fill(order::Order {
+                uid: order::Uid([owner; 56]),
+                sell: asset(sell),
+                buy: asset(buy),
+                owner: address(owner),
+                ..Default::default()
+            })
+            .unwrap(),
+        )
+    }
+
+    fn jit(owner: u8, sell: u8, buy: u8) -> solution::Trade {
+        solution::Trade::Jit(solution::JitTrade {
+            order: order::JitOrder {
+                owner: eth::H160::repeat_byte(owner),
+                signature: order::Signature::PreSign,
+                sell: asset(sell),
+                buy: asset(buy),
+                side: order::Side::Sell,
+                class: order::Class::Limit,
+                partially_fillable: false,
+                valid_to: u32::MAX,
+                app_data: order::AppData([0; 32]),
+                receiver: eth::H160::repeat_byte(owner),
+            },
+            executed: 1000.into(),
+            fee: eth::SellTokenAmount(0.into()),
+        })
+    }
+
+    fn swap(pool: u8, input: u8, output: u8) -> solution::Interaction {
+        solution::Interaction::Liquidity(Box::new(solution::LiquidityInteraction {
+            liquidity: liquidity::Liquidity {
+                id: liquidity::Id("0".to_owned()),
+                address: eth::H160::repeat_byte(pool),
+                balancer_pool_id: None,
+                gas: eth::Gas(100_000.into()),
+                state: liquidity::State::LimitOrder(liquidity::limit_order::LimitOrder {
+                    maker: asset(output),
+                    taker: asset(input),
+                    fee: liquidity::limit_order::TakerAmount(0.into()),
+                }),
+            },
+            input: asset(input),
+            output: asset(output),
+            internalize: false,
+        }))
+    }
+
+    fn solution(
+        trades: Vec<solution::Trade>,
+        interactions: Vec<solution::Interaction>,
+    ) -> solution::Solution {
+        solution::Solution {
+            trades,
+            interactions,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn accepts_regular_solutions() {
+        let routed = solution(vec![fulfillment(1, 10, 11)], vec![swap(0xaa, 10, 11)]);
+        assert_eq!(check(&routed), None);
+
+        let cow = solution(
+            vec![fulfillment(1, 10, 11), fulfillment(2, 11, 10)],
+            Vec::new(),
+        );
+        assert_eq!(check(&cow), None);
+
+        let jit_counterparty = solution(vec![fulfillment(1, 10, 11), jit(2, 11, 10)], Vec::new());
+        assert_eq!(check(&jit_counterparty), None);
+    }
+
+    #[test]
+    fn rejects_trades_buying_the_sold_token() {
+        let same_token = solution(vec![fulfillment(1, 10, 10)], Vec::new());
+        assert_eq!(check(&same_token), Some(Violation::SameToken));
+
+        let same_token_jit = solution(vec![fulfillment(1, 10, 11), jit(2, 12, 12)], Vec::new());
+        assert_eq!(check(&same_token_jit), Some(Violation::SameToken));
+    }
+
+    #[test]
+    fn rejects_makers_taking_their_own_orders() {
+        let jit_maker = solution(vec![fulfillment(1, 10, 11), jit(1, 11, 10)], Vec::new());
+        assert_eq!(check(&jit_maker), Some(Violation::SelfTrade(address(1))));
+
+        // The owner of the order poses as the pool the order gets routed
+        // through.
+        let pool_maker = solution(vec![fulfillment(1, 10, 11)], vec![swap(1, 10, 11)]);
+        assert_eq!(check(&pool_maker), Some(Violation::SelfTrade(address(1))));
+    }
+
+    #[test]
+    fn rejects_circular_trades_of_the_same_owner() {
+        let round_trip = solution(
+            vec![fulfillment(1, 10, 11), fulfillment(1, 11, 10)],
+            vec![swap(0xaa, 10, 11), swap(0xbb, 11, 10)],
+        );
+        assert_eq!(check(&round_trip), Some(Violation::WashTrade(address(1))));
+
+        let triangle = solution(
+            vec![
+                fulfillment(1, 10, 11),
+                fulfillment(1, 11, 12),
+                fulfillment(1, 12, 10),
+            ],
+            Vec::new(),
+        );
+        assert_eq!(check(&triangle), Some(Violation::WashTrade(address(1))));
+    }
+
+    #[test]
+    fn filters_violating_solutions() {
+        let mut solutions = vec![
+            solution(vec![fulfillment(1, 10, 11)], vec![swap(0xaa, 10, 11)]),
+            solution(vec![fulfillment(2, 10, 10)], Vec::new()),
+            solution(vec![fulfillment(3, 10, 11), jit(3, 11, 10)], Vec::new()),
+        ];
+        filter(&mut solutions);
+        assert_eq!(solutions.len(), 1);
+        assert_eq!(check(&solutions[0]), None);
+    }
+}
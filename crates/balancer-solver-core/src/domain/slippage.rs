@@ -0,0 +1,372 @@
+//! Realized slippage of settled swaps against the amounts they were quoted at.
+//!
+//! The pool swaps of proposed solutions are remembered with their quoted
+//! amounts. Once a solution gets settled, the swap logs of the settlement
+//! transaction are matched against them, and the price a swap executed at is
+//! compared to the price it was quoted at. The results are kept per pool and
+//! kind of pool over a rolling time window.
+
+use {
+    crate::{
+        domain::{eth, solution},
+        infra::metrics,
+    },
+    std::{
+        collections::{BTreeMap, HashMap, VecDeque},
+        sync::Mutex,
+        time::{Duration, Instant},
+    },
+};
+
+/// The number of recent solutions to remember the quoted swaps of.
+const MAX_TRACKED_SOLUTIONS: usize = 1000;
+
+/// A swap through a single pool.
+#[derive(Clone, Copy, Debug)]
+pub struct Swap {
+    pub pool: eth::H160,
+    pub input: eth::Asset,
+    pub output: eth::Asset,
+}
+
+/// A pool swap logged by a settlement transaction.
+#[derive(Clone, Copy, Debug)]
+pub enum Executed {
+    /// A swap whose log identifies the traded tokens, e.g. the swaps of the
+    /// Balancer vaults.
+    Tokens(Swap),
+    /// A swap of a pool of two tokens sorted by address, e.g. Uniswap pools,
+    /// whose log only contains the amounts of either token.
+    Sorted {
+        pool: eth::H160,
+        amounts_in: [eth::U256; 2],
+        amounts_out: [eth::U256; 2],
+    },
+}
+
+impl Executed {
+    /// Returns the executed amounts of the quoted swap if this is the
+    /// execution of it.
+    fn execution_of(&self, quoted: &Swap) -> Option<Swap> {
+        match self {
+            Self::Tokens(swap) => (swap.pool == quoted.pool
+                && swap.input.token == quoted.input.token
+                && swap.output.token == quoted.output.token)
+                .then_some(*swap),
+            Self::Sorted {
+                pool,
+                amounts_in,
+                amounts_out,
+            } => {
+                if *pool != quoted.pool {
+                    return None;
+                }
+                let (input, output) = if quoted.input.token < quoted.output.token {
+                    (0, 1)
+                } else {
+                    (1, 0)
+                };
+                if amounts_in[input].is_zero() || amounts_out[output].is_zero() {
+                    return None;
+                }
+                Some(Swap {
+                    pool: *pool,
+                    input: eth::Asset {
+                        token: quoted.input.token,
+                        amount: amounts_in[input],
+                    },
+                    output: eth::Asset {
+                        token: quoted.output.token,
+                        amount: amounts_out[output],
+                    },
+                })
+            }
+        }
+    }
+}
+
+/// Tracks the realized slippage of the pool swaps of settled solutions.
+pub struct Tracker {
+    window: Duration,
+    inner: Mutex<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    /// The quoted swaps of recently proposed solutions by auction and
+    /// solution ID.
+    solutions: VecDeque<(i64, u64, Vec<(Swap, &'static str)>)>,
+    samples: VecDeque<(Instant, Sample)>,
+}
+
+struct Sample {
+    pool: eth::H160,
+    kind: &'static str,
+    bps: f64,
+}
+
+/// A summary of the realized slippage of a pool or kind of pool.
+#[derive(Debug)]
+pub struct Summary {
+    /// The number of settled swaps.
+    pub swaps: usize,
+    /// The mean slippage of the settled swaps in basis points. Positive
+    /// values mean that swaps executed at worse prices than quoted.
+    pub mean_bps: f64,
+    pub median_bps: f64,
+    /// The slippage of the swap that executed at the worst price.
+    pub max_bps: f64,
+}
+
+impl Summary {
+    fn new(mut bps: Vec<f64>) -> Self {
+        bps.sort_by(f64::total_cmp);
+        let mid = bps.len() / 2;
+        Self {
+            swaps: bps.len(),
+            mean_bps: bps.iter().sum::<f64>() / bps.len() as f64,
+            median_bps: if bps.len() % 2 == 0 {
+                (bps[mid - 1] + bps[mid]) / 2.
+            } else {
+                bps[mid]
+            },
+            max_bps: bps[bps.len() - 1],
+        }
+    }
+}
+
+/// The realized slippage per pool and per kind of pool.
+#[derive(Debug)]
+pub struct Report {
+    pub pools: Vec<(eth::H160, &'static str, Summary)>,
+    pub kinds: BTreeMap<&'static str, Summary>,
+}
+
+impl Tracker {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            inner: Default::default(),
+        }
+    }
+
+    /// The time window the statistics cover.
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// Remembers the quoted swaps of the solutions proposed for an auction,
+    /// so that they can be compared to their executions once settled.
+    pub fn record_solutions(&self, auction: i64, solutions: &[solution::Solution]) {
+        let mut inner = self.lock(Instant::now());
+        for solution in solutions {
+            let swaps = solution
+                .interactions
+                .iter()
+                .filter_map(|interaction| match interaction {
+                    solution::Interaction::Liquidity(interaction) => Some((
+                        Swap {
+                            pool: interaction.liquidity.address,
+                            input: interaction.input,
+                            output: interaction.output,
+                        },
+                        interaction.liquidity.state.kind(),
+                    )),
+                    solution::Interaction::Custom(_) => None,
+                })
+                .collect::<Vec<_>>();
+            if !swaps.is_empty() {
+                inner.solutions.push_back((auction, solution.id.0, swaps));
+            }
+        }
+        while inner.solutions.len() > MAX_TRACKED_SOLUTIONS {
+            inner.solutions.pop_front();
+        }
+    }
+
+    /// Compares the quoted swaps of the specified solutions to the swaps
+    /// executed by their settlement. Quoted swaps without a matching swap log,
+    /// e.g. because they got internalized, are skipped.
+    pub fn record_settlement(&self, auction: i64, solutions: &[u64], executed: &[Executed]) {
+        let now = Instant::now();
+        let mut inner = self.lock(now);
+        let quoted = inner
+            .solutions
+            .iter()
+            .filter(|(id, solution, _)| *id == auction && solutions.contains(solution))
+            .flat_map(|(_, _, swaps)| swaps.iter().copied())
+            .collect::<Vec<_>>();
+
+        // Every log can only be the execution of a single quoted swap, as a
+        // pool may be swapped through multiple times in a settlement.
+        let mut executed = executed.to_vec();
+        for (quoted, kind) in quoted {
+            let Some((i, execution)) = executed
+                .iter()
+                .enumerate()
+                .find_map(|(i, executed)| Some((i, executed.execution_of(&quoted)?)))
+            else {
+                continue;
+            };
+            executed.remove(i);
+            let Some(bps) = slippage_bps(&quoted, &execution) else {
+                continue;
+            };
+            metrics::settlement_slippage(kind, bps);
+            inner.samples.push_back((
+                now,
+                Sample {
+                    pool: quoted.pool,
+                    kind,
+                    bps,
+                },
+            ));
+        }
+    }
+
+    /// Returns the realized slippage of all pools with swaps settled within
+    /// the window.
+    pub fn report(&self) -> Report {
+        let inner = self.lock(Instant::now());
+        let mut pools = HashMap::<(eth::H160, &'static str), Vec<f64>>::new();
+        let mut kinds = BTreeMap::<&'static str, Vec<f64>>::new();
+        for (_, sample) in &inner.samples {
+            pools
+                .entry((sample.pool, sample.kind))
+                .or_default()
+                .push(sample.bps);
+            kinds.entry(sample.kind).or_default().push(sample.bps);
+        }
+        Report {
+            pools: pools
+                .into_iter()
+                .map(|((pool, kind), bps)| (pool, kind, Summary::new(bps)))
+                .collect(),
+            kinds: kinds
+                .into_iter()
+                .map(|(kind, bps)| (kind, Summary::new(bps)))
+                .collect(),
+        }
+    }
+
+    /// Locks the statistics, dropping the samples that fell out of the window.
+    fn lock(&self, now: Instant) -> std::sync::MutexGuard<'_, Inner> {
+        let mut inner = self.inner.lock().unwrap();
+        while inner
+            .samples
+            .front()
+            .is_some_and(|(at, _)| now.saturating_duration_since(*at) > self.window)
+        {
+            inner.samples.pop_front();
+        }
+        inner
+    }
+}
+
+/// Computes by how many basis points the price the swap executed at is worse
+/// than the price it was quoted at.
+fn slippage_bps(quoted: &Swap, executed: &Swap) -> Option<f64> {
+    let [quoted_in, quoted_out, executed_in, executed_out] = [
+        quoted.input.amount,
+        quoted.output.amount,
+        executed.input.amount,
+        executed.output.amount,
+    ]
+    .map(|amount| amount.to_f64_lossy());
+    if quoted_in == 0. || quoted_out == 0. || executed_in == 0. {
+        return None;
+    }
+    let quoted_price = quoted_out / quoted_in;
+    let executed_price = executed_out / executed_in;
+    Some((1. - executed_price / quoted_price) * 10_000.)
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::domain::liquidity};
+
+    fn asset(token: u64, amount: u64) -> eth::Asset {
+        eth::Asset {
+            token: eth::TokenAddress(eth::H160::from_low_u64_be(token)),
+            amount: amount.into(),
+        }
+    }
+
+    fn solution(id: u64, pool: u64, input: eth::Asset, output: eth::Asset) -> solution::Solution {
+        solution::Solution {
+            id: solution::Id(id),
+            interactions: vec![solution::Interaction::Liquidity(Box::new(
+                solution::LiquidityInteraction {
+                    liquidity: liquidity::Liquidity {
+                        id: liquidity::Id("0".to_owned()),
+                        address: eth::H160::from_low_u64_be(pool),
+                        balancer_pool_id: None,
+                        gas: eth::Gas(0.into()),
+                        state: liquidity::State::Erc4626(liquidity::erc4626::Edge {
+                            vault: eth::TokenAddress(eth::H160::from_low_u64_be(pool)),
+                            asset: input.token,
+                        }),
+                    },
+                    input,
+                    output,
+                    internalize: false,
+                },
+            ))],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn compares_executed_to_quoted_prices() {
+        let tracker = Tracker::new(Duration::from_secs(60));
+        tracker.record_solutions(1, &[solution(0, 10, asset(2, 1000), asset(1, 2000))]);
+        tracker.record_solutions(2, &[solution(0, 10, asset(1, 1000), asset(2, 500))]);
+
+        // The first swap executes 1% worse than quoted, logged with the
+        // amounts of the sorted tokens.
+        tracker.record_settlement(
+            1,
+            &[0],
+            &[Executed::Sorted {
+                pool: eth::H160::from_low_u64_be(10),
+                amounts_in: [0.into(), 1000.into()],
+                amounts_out: [1980.into(), 0.into()],
+            }],
+        );
+        // The second one executes 1% better, and logs of other pools are
+        // ignored.
+        tracker.record_settlement(
+            2,
+            &[0],
+            &[
+                Executed::Tokens(Swap {
+                    pool: eth::H160::from_low_u64_be(11),
+                    input: asset(1, 1000),
+                    output: asset(2, 1000),
+                }),
+                Executed::Tokens(Swap {
+                    pool: eth::H160::from_low_u64_be(10),
+                    input: asset(1, 1000),
+                    output: asset(2, 505),
+                }),
+            ],
+        );
+        // Settlements of unknown solutions are ignored.
+        tracker.record_settlement(
+            3,
+            &[0],
+            &[Executed::Tokens(Swap {
+                pool: eth::H160::from_low_u64_be(10),
+                input: asset(1, 1000),
+                output: asset(2, 1),
+            })],
+        );
+
+        let report = tracker.report();
+        assert_eq!(report.pools.len(), 1);
+        let summary = &report.kinds["erc4626"];
+        assert_eq!(summary.swaps, 2);
+        assert!(summary.mean_bps.abs() < 1e-9);
+        assert!((summary.max_bps - 100.).abs() < 1e-9);
+    }
+}
@@ -103,6 +103,29 @@ impl Solution {
 
         self
     }
+
+    /// Returns the flashloans needed for settling the solution, i.e. the
+    /// flashloan hints of the traded orders.
+    pub fn flashloans(&self) -> impl Iterator<Item = (order::Uid, &order::FlashloanHint)> {
+        self.trades.iter().filter_map(|trade| match trade {
+            Trade::Fulfillment(fulfillment) => {
+                let order = fulfillment.order();
+                Some((order.uid, order.flashloan_hint.as_ref()?))
+            }
+            Trade::Jit(_) => None,
+        })
+    }
+
+    /// Marks all interactions to be executed instead of settled from the
+    /// Settlement contract buffers.
+    pub fn clear_internalizations(&mut self) {
+        for interaction in &mut self.interactions {
+            match interaction {
+                Interaction::Liquidity(interaction) => interaction.internalize = false,
+                Interaction::Custom(interaction) => interaction.internalize = false,
+            }
+        }
+    }
 }
 
 /// A solution for a settling a single order.
@@ -123,8 +146,13 @@ pub struct Single {
 
 impl Single {
     /// Creates a full solution for a single order solution given gas and sell
-    /// token prices.
-    pub fn into_solution(self, fee: eth::SellTokenAmount) -> Option<Solution> {
+    /// token prices, keeping the specified share of the price improvement over
+    /// the order's limit price.
+    pub fn into_solution(
+        self,
+        fee: eth::SellTokenAmount,
+        surplus_share: SurplusShare,
+    ) -> Option<Solution> {
         let Self {
             order,
             input,
@@ -168,6 +196,7 @@ impl Single {
                 (sell, buy)
             }
         };
+        let (sell, buy) = surplus_share.apply(&order, sell, buy)?;
 
         // Check order's limit price is satisfied accounting for solver
         // specified fees.
@@ -201,6 +230,70 @@ impl Single {
     }
 }
 
+/// The share of the price improvement over an order's limit price that the
+/// solver keeps as a margin, in basis points.
+///
+/// The margin is encoded in the clearing prices: sell orders receive fewer buy
+/// tokens and buy orders pay more sell tokens than the route yields, with the
+/// difference remaining in the settlement contract. The kept amount is always
+/// rounded down, so the order's limit price is never violated.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SurplusShare(u32);
+
+impl SurplusShare {
+    const BPS_BASE: u32 = 10_000;
+
+    /// Creates a new surplus share. Returns `None` if the share exceeds
+    /// 100%.
+    pub fn new(bps: u32) -> Option<Self> {
+        (bps <= Self::BPS_BASE).then_some(Self(bps))
+    }
+
+    pub fn bps(&self) -> u32 {
+        self.0
+    }
+
+    /// Applies the surplus share to the total executed sell and buy amounts
+    /// of an order. Executions that don't improve on the limit price are left
+    /// as is.
+    fn apply(&self, order: &order::Order, sell: U256, buy: U256) -> Option<(U256, U256)> {
+        if self.0 == 0 {
+            return Some((sell, buy));
+        }
+
+        let share = |surplus: U256| {
+            surplus
+                .checked_mul(self.0.into())
+                .map(|amount| amount / Self::BPS_BASE)
+        };
+        match order.side {
+            order::Side::Sell => {
+                // The minimum buy amount for the executed sell amount is
+                // rounded up, in favour of the user.
+                let limit = order
+                    .buy
+                    .amount
+                    .checked_mul(sell)?
+                    .checked_add(order.sell.amount.checked_sub(1.into())?)?
+                    .checked_div(order.sell.amount)?;
+                let kept = share(buy.saturating_sub(limit))?;
+                Some((sell, buy - kept))
+            }
+            order::Side::Buy => {
+                // The maximum sell amount for the executed buy amount is
+                // rounded down, in favour of the user.
+                let limit = order
+                    .sell
+                    .amount
+                    .checked_mul(buy)?
+                    .checked_div(order.buy.amount)?;
+                let kept = share(limit.saturating_sub(sell))?;
+                Some((sell.checked_add(kept)?, buy))
+            }
+        }
+    }
+}
+
 /// A set of uniform clearing prices. They are represented as a mapping of token
 /// addresses to price in an arbitrarily denominated price.
 #[derive(Debug, Default)]
@@ -382,3 +475,100 @@ pub const SETTLEMENT: u64 = 7365;
 /// Value was computed by taking 52 percentile median of `transfer()` costs
 /// of the 90% most traded tokens by volume in the month of Oct. 2021.
 pub const ERC20_TRANSFER: u64 = 27_513;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order(side: order::Side, sell: u64, buy: u64) -> order::Order {
+        order::Order {
+            uid: order::Uid([1; 56]),
+            sell: eth::Asset {
+                token: eth::TokenAddress(eth::H160::from_low_u64_be(1)),
+                amount: sell.into(),
+            },
+            buy: eth::Asset {
+                token: eth::TokenAddress(eth::H160::from_low_u64_be(2)),
+                amount: buy.into(),
+            },
+            side,
+            signature: Some(order::Signature::PreSign),
+            ..Default::default()
+        }
+    }
+
+    fn apply(bps: u32, order: &order::Order, sell: u64, buy: u64) -> (U256, U256) {
+        SurplusShare::new(bps)
+            .unwrap()
+            .apply(order, sell.into(), buy.into())
+            .unwrap()
+    }
+
+    #[test]
+    fn rejects_shares_above_100_percent() {
+        assert!(SurplusShare::new(10_000).is_some());
+        assert!(SurplusShare::new(10_001).is_none());
+    }
+
+    #[test]
+    fn sell_orders_receive_fewer_buy_tokens() {
+        let order = order(order::Side::Sell, 1000, 1000);
+        assert_eq!(apply(0, &order, 1000, 1100), (1000.into(), 1100.into()));
+        assert_eq!(apply(5000, &order, 1000, 1100), (1000.into(), 1050.into()));
+        assert_eq!(
+            apply(10_000, &order, 1000, 1100),
+            (1000.into(), 1000.into())
+        );
+        // Executions that don't improve on the limit price are left as is.
+        assert_eq!(apply(5000, &order, 1000, 1000), (1000.into(), 1000.into()));
+    }
+
+    #[test]
+    fn buy_orders_pay_more_sell_tokens() {
+        let order = order(order::Side::Buy, 1000, 1000);
+        assert_eq!(apply(5000, &order, 900, 1000), (950.into(), 1000.into()));
+        assert_eq!(apply(10_000, &order, 900, 1000), (1000.into(), 1000.into()));
+        assert_eq!(apply(5000, &order, 1000, 1000), (1000.into(), 1000.into()));
+    }
+
+    #[test]
+    fn rounds_in_favour_of_the_user() {
+        // The minimum buy amount of 4/3 is rounded up to 2 and the kept share
+        // of 1.5 is rounded down to 1.
+        let order = order(order::Side::Sell, 3, 2);
+        assert_eq!(apply(5000, &order, 2, 5), (2.into(), 4.into()));
+        // The whole surplus can be kept without violating the limit price.
+        assert_eq!(apply(10_000, &order, 2, 5), (2.into(), 2.into()));
+
+        // The maximum sell amount of 10/3 is rounded down to 3 and the kept
+        // share of 1.5 is rounded down to 1.
+        let order = self::order(order::Side::Buy, 10, 3);
+        assert_eq!(apply(7500, &order, 1, 1), (2.into(), 1.into()));
+        assert_eq!(apply(10_000, &order, 1, 1), (3.into(), 1.into()));
+    }
+
+    #[test]
+    fn encodes_kept_surplus_in_clearing_prices() {
+        let order = order(order::Side::Sell, 1000, 1000);
+        let (sell_token, buy_token) = (order.sell.token, order.buy.token);
+        let solution = Single {
+            order,
+            input: eth::Asset {
+                token: sell_token,
+                amount: 1000.into(),
+            },
+            output: eth::Asset {
+                token: buy_token,
+                amount: 1100.into(),
+            },
+            interactions: Vec::new(),
+            gas: eth::Gas(0.into()),
+            wrappers: Vec::new(),
+        }
+        .into_solution(Default::default(), SurplusShare::new(2000).unwrap())
+        .unwrap();
+
+        assert_eq!(solution.prices.0[&sell_token], 1080.into());
+        assert_eq!(solution.prices.0[&buy_token], 1000.into());
+    }
+}
@@ -0,0 +1,1863 @@
+//! "Baseline" solver implementation.
+//!
+//! The baseline solver is a simple solver implementation that finds the best
+//! path of at most length `max_hops + 1` over a set of on-chain liquidity. It
+//! **does not** try to split large orders into multiple parts and route them
+//! over separate paths.
+
+use {
+    super::solution::Solution,
+    crate::{
+        boundary,
+        domain::{
+            auction,
+            bad_tokens,
+            base_tokens,
+            batch_route,
+            bidding,
+            diagnostics,
+            eth,
+            events,
+            gas_budget,
+            inventory,
+            liquidity,
+            lp,
+            merge,
+            order::{self, Order},
+            order_book,
+            price_guard,
+            quote_matrix,
+            self_trade,
+            slippage,
+            solution,
+            stats,
+            validation,
+        },
+        infra::{heap, metrics},
+    },
+    contracts::alloy::InstanceExt,
+    ethereum_types::U256,
+    ethrpc::alloy::conversions::IntoAlloy,
+    futures::StreamExt,
+    itertools::Itertools,
+    reqwest::Url,
+    std::{
+        cmp,
+        collections::{HashMap, HashSet},
+        sync::{
+            Arc,
+            RwLock,
+            Weak,
+            atomic::{self, AtomicUsize},
+        },
+        time::{Duration, Instant},
+    },
+    subtle::ConstantTimeEq,
+    tracing::Instrument,
+};
+
+pub struct Solver(Arc<Inner>);
+
+/// The result of solving a quote auction, which is shared by the identical
+/// quote auctions batched with it.
+pub type QuoteResult = Result<solvers_dto::solution::Solutions, &'static str>;
+
+/// The outcome of solving an auction.
+pub struct Solved {
+    pub solutions: Vec<solution::Solution>,
+    /// Why the orders went unsolved, if there are no solutions.
+    pub unsolved: Option<Vec<diagnostics::Unsolved>>,
+    /// Whether the deadline cut solving short, i.e. some orders may have gone
+    /// unsolved for lack of time.
+    pub deadline_exceeded: bool,
+}
+
+/// The amount of time we aim the solver to finish before the final deadline is
+/// reached.
+const DEADLINE_SLACK: chrono::Duration = chrono::Duration::milliseconds(500);
+
+/// How often the node gets polled for new blocks to refresh the quote matrix
+/// on.
+const BLOCK_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+pub struct Config {
+    pub chain_id: u64,
+    pub weth: eth::WethAddress,
+    pub base_tokens: Vec<eth::TokenAddress>,
+    pub max_hops: usize,
+    pub max_partial_attempts: usize,
+    pub surplus_share: solution::SurplusShare,
+    pub solution_gas_offset: eth::SignedGas,
+    pub max_solution_gas: Option<eth::Gas>,
+    pub max_concurrent_orders: usize,
+    pub merge_solutions: bool,
+    pub internalize_interactions: bool,
+    pub native_token_price_estimation_amount: eth::U256,
+    pub uni_v3_node_url: Option<Url>,
+    pub erc4626_node_url: Option<Url>,
+    pub liquidity_client_config: Option<crate::infra::config::LiquidityConfig>,
+    pub artifact_sinks: Vec<crate::infra::artifacts::SinkConfig>,
+    pub redact_saved_files: bool,
+    pub vault_address: Option<eth::Address>,
+    pub batch_router_address: Option<eth::Address>,
+    pub erc4626_buffers: Vec<eth::TokenAddress>,
+    pub node_url: Option<Url>,
+    pub quote: Option<QuoteConfig>,
+    pub quote_batching_window: Option<Duration>,
+    pub stats: StatsConfig,
+    pub routing_api_token: Option<String>,
+    pub order_validation: Option<OrderValidationConfig>,
+    pub token_denylist: Option<crate::infra::denylist::Denylist>,
+    pub bad_token_detection: Option<bad_tokens::Config>,
+    pub slippage_tracking: Option<SlippageTrackingConfig>,
+    pub price_guard: Option<price_guard::Config>,
+    pub lp_intents: Option<lp::Config>,
+    pub strategies: Option<crate::infra::strategies::Strategies>,
+    pub price_comparison: Option<crate::infra::price_comparison::Config>,
+    pub order_book: Option<crate::infra::order_book::OrderBook>,
+    pub math_eval: bool,
+    pub heap_debug: bool,
+    /// The directory of the saved artifacts to serve the `/explorer` UI
+    /// for, if enabled.
+    pub explorer: Option<std::path::PathBuf>,
+    pub inventory: Option<inventory::Config>,
+    pub rival_aware_bidding: Option<bidding::Config>,
+    pub auto_base_tokens: Option<base_tokens::Config>,
+}
+
+/// Configuration of the order validation performed before routing.
+pub struct OrderValidationConfig {
+    /// Whether to check the sell token balances and allowances of order
+    /// owners. Requires `node_url` to be configured.
+    pub check_balances: bool,
+}
+
+/// Configuration of the lightweight path used for solving quote auctions.
+pub struct QuoteConfig {
+    /// The maximum number of liquidity sources to consider per token pair.
+    pub max_liquidity_per_pair: usize,
+    /// The maximum amount of time to spend solving a quote.
+    pub time_budget: Duration,
+    /// The token pairs quoted from the routes of a price matrix refreshed
+    /// on every block instead of searching paths. Requires `node_url` to be
+    /// configured.
+    pub matrix_pairs: Vec<(eth::TokenAddress, eth::TokenAddress)>,
+}
+
+/// Configuration of the per token pair statistics.
+pub struct StatsConfig {
+    /// The rolling time window the statistics cover.
+    pub window: Duration,
+    /// The address of this solver in the solver competition.
+    pub solver_address: Option<eth::Address>,
+}
+
+/// Configuration of the tracking of realized slippage of settled swaps.
+pub struct SlippageTrackingConfig {
+    /// The rolling time window the statistics cover.
+    pub window: Duration,
+    /// The node to fetch the receipts of settlement transactions from.
+    pub node_url: Url,
+}
+
+/// The routing parameters of the solver. These can be updated at runtime, with
+/// changes taking effect for subsequent auctions.
+#[derive(Clone, Debug)]
+pub struct Routing {
+    /// Set of tokens to additionally consider as intermediary hops when
+    /// path-finding. This allows paths of the kind `TOKEN1 -> WETH -> TOKEN2`
+    /// to be considered.
+    pub base_tokens: HashSet<eth::TokenAddress>,
+
+    /// Maximum number of hops that can be considered in a trading path. A hop
+    /// is an intermediary token within a trading path. For example:
+    /// - A value of 0 indicates that only a direct trade is allowed: `A -> B`
+    /// - A value of 1 indicates that a single intermediary token can appear
+    ///   within a trading path: `A -> B -> C`
+    /// - A value of 2 indicates: `A -> B -> C -> D`
+    /// - etc.
+    pub max_hops: usize,
+
+    /// The maximum number of attempts to solve a partially fillable order.
+    /// Basically we continuously halve the amount to execute until we find a
+    /// valid solution or exceed this count.
+    pub max_partial_attempts: usize,
+
+    /// The protocols to fetch liquidity from when using the liquidity client.
+    pub protocols: Vec<String>,
+}
+
+impl Routing {
+    /// The maximum number of base tokens that can be configured, in addition
+    /// to the automatically selected ones.
+    pub const MAX_BASE_TOKENS: usize = 20;
+    /// The maximum number of hops. The number of candidate paths grows
+    /// exponentially with the hops, making path finding infeasible beyond it.
+    pub const MAX_HOPS: usize = 2;
+}
+
+struct Inner {
+    chain_id: u64,
+    weth: eth::WethAddress,
+
+    /// The routing parameters used for new auctions.
+    routing: RwLock<Routing>,
+
+    /// Token required for updating the routing parameters through the API.
+    /// Updates are disabled if this is not set.
+    routing_api_token: Option<String>,
+
+    /// The share of the price improvement over an order's limit price that
+    /// is kept as solver margin.
+    surplus_share: solution::SurplusShare,
+
+    /// Units of gas that get added to the gas estimate for executing a
+    /// computed trade route to arrive at a gas estimate for a whole settlement.
+    solution_gas_offset: eth::SignedGas,
+
+    /// The maximum amount of gas a solution may use. Auctions can specify a
+    /// lower limit.
+    max_solution_gas: Option<eth::Gas>,
+
+    /// The maximum number of orders of an auction that get solved
+    /// concurrently.
+    max_concurrent_orders: usize,
+
+    /// Whether to combine solutions for independent orders into joint
+    /// solutions.
+    merge_solutions: bool,
+
+    /// Whether interactions may get internalized using the settlement
+    /// contract buffers.
+    internalize_interactions: bool,
+
+    /// The amount of the native token to use to estimate native price of a
+    /// token
+    native_token_price_estimation_amount: eth::U256,
+
+    /// If provided, the solver can rely on Uniswap V3 LPs
+    uni_v3_quoter_v2: Option<Arc<contracts::alloy::UniswapV3QuoterV2::Instance>>,
+    /// If provided, the solver can rely on Uniswap V4 pools
+    uni_v4_quoter: Option<Arc<contracts::alloy::UniswapV4Quoter::Instance>>,
+    /// If provided, ERC4626 baseline quoting will be enabled using this Web3.
+    /// If not provided but `uni_v3_quoter_v2` is, its Web3 will be reused.
+    erc4626_web3: Option<shared::ethrpc::Web3>,
+
+    /// Optional liquidity client for fetching liquidity from external API
+    liquidity_client: Option<crate::infra::liquidity_client::LiquidityClient>,
+
+    /// Optional sinks to save the auction artifacts to
+    artifacts: Option<crate::infra::artifacts::Artifacts>,
+
+    /// Whether trader data gets redacted from the saved files
+    redact_saved_files: bool,
+
+    /// If provided, boosted routes wrapping or unwrapping ERC4626 tokens
+    /// through Vault buffers get encoded as a single batch router swap.
+    batch_router: Option<batch_route::BatchRouter>,
+
+    /// Optional solution verifier for on-chain quote verification
+    verifier: Option<crate::infra::solution_verifier::SolutionVerifier>,
+
+    /// If provided, quote auctions are solved on a lightweight path with a
+    /// reduced liquidity set and a strict time budget.
+    quote: Option<QuoteConfig>,
+
+    /// If provided, quotes for its token pairs are priced at the cached routes
+    /// of the matrix, which get refreshed on every block.
+    quote_matrix: Option<quote_matrix::Matrix>,
+
+    /// If provided, identical quote auctions arriving within its window are
+    /// solved once.
+    quote_batches: Option<crate::util::batch::Batcher<String, QuoteResult>>,
+
+    /// Rolling-window statistics per token pair.
+    stats: stats::PairStats,
+
+    /// The address of this solver in the solver competition, used for
+    /// attributing competition results.
+    solver_address: Option<eth::Address>,
+
+    /// If provided, orders that are guaranteed to fail settlement are skipped
+    /// before routing.
+    order_validation: Option<OrderValidation>,
+
+    /// Orders trading tokens on this list are dropped before solving.
+    token_denylist: Option<crate::infra::denylist::Denylist>,
+
+    /// Quarantines tokens that repeatedly make settlements fail.
+    bad_tokens: Option<bad_tokens::Detector>,
+
+    /// If provided, the prices settled swaps executed at are compared to the
+    /// prices they were quoted at.
+    slippage: Option<Arc<SlippageTracking>>,
+
+    /// Checks the clearing prices of solutions against oracle prices.
+    price_guard: Option<price_guard::Guard>,
+
+    /// If provided, orders trading pool tokens for one of the pool's tokens
+    /// get settled by joining or exiting the pool.
+    lp: Option<lp::Provider>,
+
+    /// Whether the `/math/eval` debug endpoint is served.
+    math_eval: bool,
+
+    /// The bus the lifecycle events of handled auctions get published on.
+    events: events::Bus,
+
+    /// Whether the `/debug/heap` endpoint is served.
+    heap_debug: bool,
+
+    /// The directory of the saved artifacts the `/explorer` UI is served
+    /// for, if enabled.
+    explorer: Option<std::path::PathBuf>,
+
+    /// If provided, the settlement contract balances are tracked and reserves
+    /// of them are kept out of internalization.
+    inventory: Option<Arc<inventory::Inventory>>,
+
+    /// If provided, the surplus share of single order solutions for
+    /// competition auctions is calibrated on the rival scores of past
+    /// competitions.
+    bidding: Option<bidding::Calibration>,
+
+    /// The configured base tokens, or the ones set through the routing API,
+    /// which are always routed through.
+    static_base_tokens: RwLock<HashSet<eth::TokenAddress>>,
+
+    /// If provided, the most connected tokens of the auction liquidity are
+    /// periodically selected as additional base tokens.
+    base_token_selector: Option<base_tokens::Selector>,
+
+    /// External strategy endpoints the auction gets forwarded to, whose
+    /// solutions are proposed alongside the local ones.
+    strategies: Option<crate::infra::strategies::Strategies>,
+
+    /// If provided, a sample of the quotes is compared against external price
+    /// APIs.
+    comparator: Option<crate::infra::price_comparison::Comparator>,
+
+    /// If provided, the resting orders of the order book get matched against
+    /// the auction's orders as JIT trades.
+    order_book: Option<crate::infra::order_book::OrderBook>,
+}
+
+struct SlippageTracking {
+    tracker: slippage::Tracker,
+    receipts: crate::infra::receipts::Receipts,
+}
+
+struct OrderValidation {
+    /// Balance fetcher used to check that order owners can pay for their
+    /// orders. Balances are not checked if this is not set.
+    balances: Option<crate::infra::balances::Balances>,
+}
+
+impl Solver {
+    /// Creates a new baseline solver for the specified configuration.
+    pub async fn new(config: Config) -> Self {
+        let (uni_v3_quoter_v2, uni_v4_quoter) = match config.uni_v3_node_url {
+            Some(ref url) => {
+                let web3 = ethrpc::web3(Default::default(), Default::default(), url, "baseline");
+                let uni_v3_quoter_v2 =
+                    contracts::alloy::UniswapV3QuoterV2::Instance::deployed(&web3.alloy)
+                        .await
+                        .map(Arc::new)
+                        .inspect_err(|err| {
+                            tracing::warn!(?err, "Failed to load UniswapV3QuoterV2 contract");
+                        })
+                        .ok();
+                let uni_v4_quoter =
+                    contracts::alloy::UniswapV4Quoter::Instance::deployed(&web3.alloy)
+                        .await
+                        .map(Arc::new)
+                        .inspect_err(|err| {
+                            tracing::warn!(?err, "Failed to load UniswapV4Quoter contract");
+                        })
+                        .ok();
+                (uni_v3_quoter_v2, uni_v4_quoter)
+            }
+            None => (None, None),
+        };
+
+        // Configure ERC4626 Web3 from dedicated URL if present; else reuse Uniswap V3
+        // Web3 if present
+        let erc4626_web3 = match (config.erc4626_node_url, &config.uni_v3_node_url) {
+            (Some(url), _) => Some(ethrpc::web3(
+                Default::default(),
+                Default::default(),
+                &url,
+                "erc4626",
+            )),
+            (None, Some(url)) => Some(ethrpc::web3(
+                Default::default(),
+                Default::default(),
+                url,
+                "erc4626",
+            )),
+            (None, None) => None,
+        };
+
+        // Create liquidity client if configured
+        let liquidity_client = config
+            .liquidity_client_config
+            .as_ref()
+            .map(crate::infra::liquidity_client::LiquidityClient::new);
+
+        let order_validation = match config.order_validation {
+            Some(validation) => {
+                let balances = match (validation.check_balances, &config.node_url) {
+                    (true, Some(node_url)) => {
+                        match crate::infra::balances::Balances::new(node_url, config.vault_address)
+                            .await
+                        {
+                            Ok(balances) => Some(balances),
+                            Err(err) => {
+                                tracing::warn!(?err, "order balances won't be checked");
+                                None
+                            }
+                        }
+                    }
+                    (true, None) => {
+                        tracing::warn!("order balances won't be checked without a node URL");
+                        None
+                    }
+                    (false, _) => None,
+                };
+                Some(OrderValidation { balances })
+            }
+            None => None,
+        };
+
+        let batch_router = match config.batch_router_address {
+            Some(address) if !config.erc4626_buffers.is_empty() => Some(
+                batch_route::BatchRouter::new(address, config.erc4626_buffers),
+            ),
+            _ => None,
+        };
+
+        // Create solution verifier if vault and batch router addresses are provided
+        let matrix_node_url = config
+            .quote
+            .as_ref()
+            .filter(|quote| !quote.matrix_pairs.is_empty())
+            .and_then(|_| match &config.node_url {
+                Some(node_url) => Some(node_url.clone()),
+                None => {
+                    tracing::warn!("quote matrix won't be refreshed without a node URL");
+                    None
+                }
+            });
+
+        let verifier = match (
+            config.vault_address,
+            config.batch_router_address,
+            config.node_url,
+        ) {
+            (Some(vault_addr), Some(batch_router_addr), Some(ref node_url)) => {
+                let web3 =
+                    ethrpc::web3(Default::default(), Default::default(), node_url, "verifier");
+                let vault = contracts::alloy::BalancerV2Vault::Instance::new(
+                    vault_addr.0.into_alloy(),
+                    web3.alloy.clone(),
+                );
+                let batch_router = contracts::alloy::BalancerV3BatchRouter::Instance::new(
+                    batch_router_addr.0.into_alloy(),
+                    web3.alloy,
+                );
+                Some(crate::infra::solution_verifier::SolutionVerifier::new(
+                    vault,
+                    batch_router,
+                ))
+            }
+            _ => None,
+        };
+
+        let static_base_tokens = config.base_tokens.into_iter().collect::<HashSet<_>>();
+        let base_token_selector = match config.auto_base_tokens {
+            Some(auto) => Some(base_tokens::Selector::new(auto, config.chain_id).await),
+            None => None,
+        };
+        let routing = Routing {
+            base_tokens: static_base_tokens
+                .iter()
+                .copied()
+                .chain(
+                    base_token_selector
+                        .as_ref()
+                        .and_then(|selector| selector.selection())
+                        .unwrap_or_default(),
+                )
+                .collect(),
+            max_hops: config.max_hops,
+            max_partial_attempts: config.max_partial_attempts,
+            protocols: config
+                .liquidity_client_config
+                .as_ref()
+                .map(|lc_config| lc_config.protocols.clone())
+                .unwrap_or_default(),
+        };
+
+        let inner = Arc::new(Inner {
+            chain_id: config.chain_id,
+            weth: config.weth,
+            routing: RwLock::new(routing),
+            routing_api_token: config.routing_api_token,
+            surplus_share: config.surplus_share,
+            solution_gas_offset: config.solution_gas_offset,
+            max_solution_gas: config.max_solution_gas,
+            max_concurrent_orders: config.max_concurrent_orders,
+            merge_solutions: config.merge_solutions,
+            internalize_interactions: config.internalize_interactions,
+            native_token_price_estimation_amount: config.native_token_price_estimation_amount,
+            uni_v3_quoter_v2,
+            uni_v4_quoter,
+            erc4626_web3,
+            liquidity_client,
+            artifacts: crate::infra::artifacts::Artifacts::from_config(config.artifact_sinks).await,
+            redact_saved_files: config.redact_saved_files,
+            batch_router,
+            verifier,
+            quote_matrix: config
+                .quote
+                .as_ref()
+                .filter(|quote| !quote.matrix_pairs.is_empty())
+                .map(|quote| quote_matrix::Matrix::new(quote.matrix_pairs.iter().copied())),
+            quote: config.quote,
+            quote_batches: config
+                .quote_batching_window
+                .map(crate::util::batch::Batcher::new),
+            stats: stats::PairStats::new(config.stats.window),
+            solver_address: config.stats.solver_address,
+            order_validation,
+            token_denylist: config.token_denylist,
+            bad_tokens: config.bad_token_detection.map(bad_tokens::Detector::new),
+            slippage: config.slippage_tracking.map(|tracking| {
+                Arc::new(SlippageTracking {
+                    tracker: slippage::Tracker::new(tracking.window),
+                    receipts: crate::infra::receipts::Receipts::new(&tracking.node_url),
+                })
+            }),
+            price_guard: config.price_guard.map(price_guard::Guard::new),
+            lp: config.lp_intents.map(lp::Provider::new),
+            strategies: config.strategies,
+            comparator: config.price_comparison.map(|comparison| {
+                crate::infra::price_comparison::Comparator::new(config.chain_id, comparison)
+            }),
+            order_book: config.order_book,
+            math_eval: config.math_eval,
+            events: events::Bus::new(),
+            heap_debug: config.heap_debug,
+            explorer: config.explorer,
+            inventory: config.inventory.map(inventory::Inventory::new),
+            bidding: config.rival_aware_bidding.map(bidding::Calibration::new),
+            static_base_tokens: RwLock::new(static_base_tokens),
+            base_token_selector,
+        });
+        if let Some(node_url) = matrix_node_url {
+            tokio::spawn(refresh_quote_matrix(Arc::downgrade(&inner), node_url));
+        }
+        Self(inner)
+    }
+
+    /// Returns a reference to the liquidity client if configured
+    pub fn liquidity_client(&self) -> Option<&crate::infra::liquidity_client::LiquidityClient> {
+        self.0.liquidity_client.as_ref()
+    }
+
+    /// Returns the base tokens configured for this solver
+    pub fn base_tokens(&self) -> HashSet<eth::TokenAddress> {
+        self.routing().base_tokens
+    }
+
+    /// Returns the protocols configured for liquidity fetching
+    pub fn protocols(&self) -> Option<Vec<String>> {
+        self.0
+            .liquidity_client
+            .as_ref()
+            .map(|_| self.routing().protocols)
+    }
+
+    /// Returns the automatically selected base tokens, if automatic selection
+    /// is enabled and a selection was made.
+    pub fn selected_base_tokens(&self) -> Option<Vec<eth::TokenAddress>> {
+        self.0.base_token_selector.as_ref()?.selection()
+    }
+
+    /// Returns the currently active routing parameters.
+    pub fn routing(&self) -> Routing {
+        self.0.routing.read().unwrap().clone()
+    }
+
+    /// Updates the routing parameters used for subsequent auctions, returning
+    /// the new parameters.
+    pub fn update_routing(&self, update: impl FnOnce(&mut Routing)) -> Routing {
+        let mut routing = self.0.routing.write().unwrap();
+        update(&mut routing);
+        tracing::info!(routing = ?*routing, "updated routing configuration");
+        routing.clone()
+    }
+
+    /// Replaces the base tokens that are always routed through. The
+    /// automatically selected base tokens stay in use until the next
+    /// selection, which then gets combined with these tokens.
+    pub fn set_base_tokens(&self, tokens: HashSet<eth::TokenAddress>) -> Routing {
+        self.update_routing(|routing| {
+            routing.base_tokens = tokens
+                .iter()
+                .copied()
+                .chain(self.selected_base_tokens().unwrap_or_default())
+                .collect();
+            *self.0.static_base_tokens.write().unwrap() = tokens;
+        })
+    }
+
+    /// Returns whether the specified token authorizes updating the routing
+    /// parameters. The token is compared in constant time, so response times
+    /// don't reveal how much of it matches.
+    pub fn authorizes_routing_update(&self, token: &str) -> bool {
+        self.0
+            .routing_api_token
+            .as_deref()
+            .is_some_and(|expected| bool::from(expected.as_bytes().ct_eq(token.as_bytes())))
+    }
+
+    /// Returns the sinks to save the auction artifacts to if configured
+    pub fn artifacts(&self) -> Option<&crate::infra::artifacts::Artifacts> {
+        self.0.artifacts.as_ref()
+    }
+
+    /// Returns whether trader data gets redacted from the saved auction and
+    /// solution files
+    pub fn redact_saved_files(&self) -> bool {
+        self.0.redact_saved_files
+    }
+
+    /// Returns the chain ID for this solver
+    pub fn chain_id(&self) -> u64 {
+        self.0.chain_id
+    }
+
+    /// Returns the CoW API base URL for this chain
+    pub fn cow_api_base_url(&self) -> &'static str {
+        match self.0.chain_id {
+            1 => "https://api.cow.fi/mainnet",
+            100 => "https://api.cow.fi/xdai",
+            42161 => "https://api.cow.fi/arbitrum_one",
+            8453 => "https://api.cow.fi/base",
+            43114 => "https://api.cow.fi/avalanche",
+            137 => "https://api.cow.fi/polygon",
+            _ => {
+                tracing::warn!(chain_id = self.0.chain_id, "Unknown chain ID for CoW API");
+                "https://api.cow.fi/mainnet" // Fallback
+            }
+        }
+    }
+
+    /// Returns the external strategy endpoints if configured
+    pub fn strategies(&self) -> Option<&crate::infra::strategies::Strategies> {
+        self.0.strategies.as_ref()
+    }
+
+    /// Returns the comparator of quotes against external price APIs if
+    /// configured
+    pub fn comparator(&self) -> Option<&crate::infra::price_comparison::Comparator> {
+        self.0.comparator.as_ref()
+    }
+
+    /// Returns a reference to the solution verifier if configured
+    pub fn verifier(&self) -> Option<&crate::infra::solution_verifier::SolutionVerifier> {
+        self.0.verifier.as_ref()
+    }
+
+    /// Returns the per token pair statistics of the solver.
+    pub fn stats(&self) -> &stats::PairStats {
+        &self.0.stats
+    }
+
+    /// Returns the rival-aware bidding calibration if configured.
+    pub fn bidding(&self) -> Option<&bidding::Calibration> {
+        self.0.bidding.as_ref()
+    }
+
+    /// Returns the realized slippage tracker of settled swaps if configured.
+    pub fn slippage(&self) -> Option<&slippage::Tracker> {
+        self.0.slippage.as_ref().map(|slippage| &slippage.tracker)
+    }
+
+    /// Returns the address of this solver in the solver competition if
+    /// configured
+    pub fn solver_address(&self) -> Option<eth::Address> {
+        self.0.solver_address
+    }
+
+    /// Returns the bus the lifecycle events of handled auctions get published
+    /// on.
+    pub fn events(&self) -> &events::Bus {
+        &self.0.events
+    }
+
+    /// Returns whether the `/math/eval` debug endpoint is served.
+    pub fn math_eval(&self) -> bool {
+        self.0.math_eval
+    }
+
+    /// Returns whether the `/debug/heap` endpoint is served.
+    pub fn heap_debug(&self) -> bool {
+        self.0.heap_debug
+    }
+
+    /// Returns the directory of the saved artifacts if the `/explorer` UI is
+    /// served.
+    pub fn explorer(&self) -> Option<&std::path::Path> {
+        self.0.explorer.as_deref()
+    }
+
+    /// Computes the swap through the single liquidity source with the same
+    /// math used for routing auctions. Sell requests get evaluated for their
+    /// sell amount and buy requests for their buy amount, ignoring the other
+    /// amount. Returns `None` if the liquidity can't be used for the swap or
+    /// is quoted on-chain, as the result wouldn't only depend on the request.
+    pub async fn evaluate<'a>(
+        &self,
+        liquidity: &'a liquidity::Liquidity,
+        request: Request,
+    ) -> Option<Segment<'a>> {
+        if liquidity.state.is_quoted_on_chain() {
+            return None;
+        }
+        let request = match request.side {
+            order::Side::Sell => Request {
+                buy: eth::Asset {
+                    amount: eth::U256::zero(),
+                    ..request.buy
+                },
+                ..request
+            },
+            order::Side::Buy => Request {
+                sell: eth::Asset {
+                    amount: eth::U256::MAX,
+                    ..request.sell
+                },
+                ..request
+            },
+        };
+        // Without base tokens or hops, the only candidate route is the direct
+        // swap through the liquidity.
+        let boundary_solver = boundary::baseline::Solver::new(
+            &self.0.weth,
+            &Default::default(),
+            std::slice::from_ref(liquidity),
+            self.0.uni_v3_quoter_v2.clone(),
+            self.0.uni_v4_quoter.clone(),
+            self.0.erc4626_web3.as_ref(),
+        );
+        let route = boundary_solver.route(request, 0).await?;
+        route.segments.into_iter().next()
+    }
+
+    /// Returns whether quote auctions get solved on the lightweight quote path.
+    pub fn lightweight_quotes(&self) -> bool {
+        self.0.quote.is_some()
+    }
+
+    /// Returns the batches identical quote auctions get coalesced into if
+    /// configured.
+    pub fn quote_batches(&self) -> Option<&crate::util::batch::Batcher<String, QuoteResult>> {
+        self.0.quote_batches.as_ref()
+    }
+
+    /// Returns whether a quote for the specified token pairs gets solved over
+    /// the cached routes of the quote matrix, so no liquidity needs to be
+    /// fetched for it.
+    pub fn quotes_from_matrix(
+        &self,
+        pairs: impl IntoIterator<Item = (eth::TokenAddress, eth::TokenAddress)>,
+    ) -> bool {
+        self.0
+            .quote_matrix
+            .as_ref()
+            .is_some_and(|matrix| matrix.covers(pairs))
+    }
+
+    /// Removes the orders that are guaranteed to fail settlement from the
+    /// auction, returning the skipped orders. Does nothing if order validation
+    /// is not enabled.
+    pub async fn validate_orders(
+        &self,
+        auction: &mut auction::Auction,
+    ) -> Vec<validation::Skipped> {
+        match &self.0.order_validation {
+            Some(order_validation) => {
+                validation::validate(auction, order_validation.balances.as_ref()).await
+            }
+            None => Vec::new(),
+        }
+    }
+
+    /// Solves the specified auction, returning a vector of all possible
+    /// solutions. The `external` solutions, e.g. of external strategies, are
+    /// awaited alongside the local solving and get ranked together with the
+    /// local solutions. External solutions not available in time are dropped.
+    ///
+    /// If no solutions are found, the reasons the orders went unsolved are
+    /// returned as well.
+    pub async fn solve(
+        &self,
+        mut auction: auction::Auction,
+        external: impl Future<Output = Vec<solution::Solution>>,
+    ) -> Solved {
+        let uids = auction
+            .orders
+            .iter()
+            .map(|order| order.uid)
+            .collect::<Vec<_>>();
+        if let Some(denylist) = &self.0.token_denylist {
+            denylist.filter(&mut auction);
+        }
+        if let Some(bad_tokens) = &self.0.bad_tokens {
+            bad_tokens.filter(&mut auction);
+        }
+        auction.liquidity.retain(|liquidity| {
+            let valid = liquidity.state.has_valid_fee();
+            if !valid {
+                tracing::debug!(id = ?liquidity.id, "skipping liquidity with invalid fee");
+                metrics::invalid_liquidity_fee(liquidity.state.kind());
+            }
+            valid
+        });
+        if let Some(inventory) = &self.0.inventory {
+            auction.tokens = inventory.observe(&auction.tokens);
+        }
+        let denied = {
+            let remaining = auction
+                .orders
+                .iter()
+                .map(|order| order.uid)
+                .collect::<HashSet<_>>();
+            uids.into_iter()
+                .filter(|uid| !remaining.contains(uid))
+                .collect()
+        };
+        let trusted = auction
+            .tokens
+            .0
+            .iter()
+            .filter(|(_, token)| token.trusted)
+            .map(|(address, _)| *address)
+            .collect::<HashSet<_>>();
+        let auction_id = auction.id;
+        let order_count = auction.orders.len();
+        let quote = match auction.id {
+            auction::Id::Quote => self.0.quote.as_ref(),
+            auction::Id::Solve(_) => None,
+        };
+        let started = Instant::now();
+        let mut matrix_hops = None;
+        match quote {
+            Some(config) => {
+                match self
+                    .0
+                    .quote_matrix
+                    .as_ref()
+                    .and_then(|matrix| matrix.liquidity(&auction.orders))
+                {
+                    Some((liquidity, hops)) => {
+                        auction.liquidity = liquidity;
+                        matrix_hops = Some(hops);
+                        metrics::matrix_quote();
+                    }
+                    None => {
+                        auction.liquidity = quote_liquidity(
+                            std::mem::take(&mut auction.liquidity),
+                            &auction.tokens,
+                            config.max_liquidity_per_pair,
+                        );
+                    }
+                }
+                metrics::quote(&auction);
+            }
+            None => metrics::solve(&auction),
+        }
+        self.0.stats.record_auction(&auction);
+        if let Some(bidding) = &self.0.bidding {
+            bidding.record_auction(&auction);
+        }
+        if let (Some(selector), auction::Id::Solve(_)) = (&self.0.base_token_selector, auction.id)
+            && let Some(selection) = selector.update(&auction)
+        {
+            self.update_routing(|routing| {
+                routing.base_tokens = self
+                    .0
+                    .static_base_tokens
+                    .read()
+                    .unwrap()
+                    .iter()
+                    .copied()
+                    .chain(selection)
+                    .collect();
+            });
+        }
+        let mut routing = self.routing();
+        if let Some(hops) = matrix_hops {
+            // Cached routes only hop through their own intermediate tokens.
+            routing.base_tokens = hops;
+        }
+        if let (Some(matrix), auction::Id::Solve(_)) = (&self.0.quote_matrix, auction.id) {
+            matrix.cache(quote_matrix::Snapshot {
+                liquidity: auction.liquidity.clone(),
+                tokens: auction.tokens.clone(),
+            });
+        }
+        let resting = self
+            .0
+            .order_book
+            .as_ref()
+            .map(|book| order_book::Resting::new(&book.orders(), &auction))
+            .unwrap_or_default();
+        auction.liquidity.extend(resting.liquidity());
+        let diagnostics =
+            diagnostics::Context::new(&auction, denied, &routing.base_tokens, routing.max_hops);
+        let deadline = auction.deadline.clone();
+        let gas_budget = gas_budget::Budget::new(self.0.max_solution_gas, &auction);
+        let tokens = (self.0.merge_solutions || self.0.price_guard.is_some())
+            .then(|| auction.tokens.clone())
+            .unwrap_or_else(|| auction::Tokens(Default::default()));
+        // Make sure to push the CPU-heavy code to a separate thread in order to
+        // not lock up the [`tokio`] runtime and cause it to slow down handling
+        // the real async things. For larger settlements, this can block in the
+        // 100s of ms.
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let mut remaining = auction
+            .deadline
+            .clone()
+            .reduce(DEADLINE_SLACK)
+            .remaining()
+            .unwrap_or_default();
+        if let Some(config) = quote {
+            remaining = remaining.min(config.time_budget);
+        }
+
+        let inner = self.0.clone();
+        let span = tracing::Span::current();
+        let background_work = async move {
+            let solve = inner.solve(auction, routing, sender).instrument(span);
+            heap::track(heap::Subsystem::SolutionBuilder, solve).await;
+        };
+
+        let mut handle = tokio::spawn(background_work);
+
+        let (local, external) = tokio::join!(
+            tokio::time::timeout(remaining, &mut handle),
+            tokio::time::timeout(remaining, external),
+        );
+        if local.is_err() {
+            tracing::debug!("reached timeout while solving orders");
+            if quote.is_some() {
+                metrics::quote_timeout();
+            }
+            // Abort the background task to prevent memory leaks
+            handle.abort();
+        }
+
+        let mut solutions = vec![];
+        while let Ok(solution) = receiver.try_recv() {
+            solutions.push(solution);
+        }
+        // Orders get solved concurrently, so restore the auction's order.
+        solutions.sort_by_key(|solution| solution.id.0);
+        if !resting.is_empty() {
+            solutions = solutions
+                .into_iter()
+                .filter_map(|solution| {
+                    let id = solution.id;
+                    let settled = resting.settle(solution);
+                    if settled.is_none() {
+                        tracing::debug!(?id, "dropping solution not settling resting orders");
+                    }
+                    settled
+                })
+                .collect();
+        }
+        match external {
+            // Local solutions are identified by their order's index, so
+            // external solutions get ids past those.
+            Ok(external) => solutions.extend(
+                external
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, solution)| solution.with_id(solution::Id((order_count + i) as u64))),
+            ),
+            Err(_) => tracing::debug!("reached timeout while awaiting external solutions"),
+        }
+        if self.0.merge_solutions {
+            let amount_out = async |pool: &liquidity::Liquidity,
+                                    input: eth::Asset,
+                                    output: eth::TokenAddress| {
+                boundary::baseline::amount_out(
+                    pool,
+                    input,
+                    output,
+                    self.0.uni_v3_quoter_v2.clone(),
+                    self.0.uni_v4_quoter.clone(),
+                    self.0.erc4626_web3.as_ref(),
+                )
+                .await
+            };
+            solutions = merge::merge(
+                solutions,
+                self.0.solution_gas_offset,
+                gas_budget.as_ref(),
+                &tokens,
+                amount_out,
+            )
+            .await;
+        }
+        // Merged solutions get checked too, along with external solutions
+        // whose gas isn't bounded by the routes computed here.
+        if let Some(budget) = &gas_budget {
+            budget.trim(&mut solutions);
+        }
+        if !self.0.internalize_interactions {
+            solutions
+                .iter_mut()
+                .for_each(solution::Solution::clear_internalizations);
+        }
+        self_trade::filter(&mut solutions);
+        if let Some(guard) = &self.0.price_guard {
+            solutions = guard.check(solutions, &tokens).await;
+        }
+        match quote {
+            Some(_) => metrics::quoted(started.elapsed(), &solutions),
+            None => metrics::solved(&deadline, &solutions),
+        }
+        if let (Some(bad_tokens), auction::Id::Solve(id)) = (&self.0.bad_tokens, auction_id) {
+            bad_tokens.record_solutions(id, &solutions, &trusted);
+        }
+        if let (Some(slippage), auction::Id::Solve(id)) = (&self.0.slippage, auction_id) {
+            slippage.tracker.record_solutions(id, &solutions);
+        }
+        if let (Some(inventory), auction::Id::Solve(_)) = (&self.0.inventory, auction_id) {
+            inventory.record_solutions(&solutions);
+        }
+        let unsolved = solutions
+            .is_empty()
+            .then(|| diagnostics.diagnose(local.is_err()));
+        Solved {
+            solutions,
+            unsolved,
+            deadline_exceeded: local.is_err(),
+        }
+    }
+
+    /// Records the outcome of settling the specified solutions for detecting
+    /// bad tokens.
+    pub fn record_settlement_outcome(
+        &self,
+        auction: i64,
+        solutions: &[u64],
+        outcome: bad_tokens::Outcome,
+    ) {
+        if let Some(bad_tokens) = &self.0.bad_tokens {
+            bad_tokens.record_outcome(auction, solutions, outcome);
+        }
+    }
+
+    /// Records the realized slippage of the swaps of the specified solutions
+    /// settled by the transaction. The receipt gets fetched in the
+    /// background.
+    pub fn record_settlement(&self, auction: i64, solutions: Vec<u64>, transaction: eth::H256) {
+        let Some(slippage) = self.0.slippage.clone() else {
+            return;
+        };
+        tokio::spawn(async move {
+            match slippage.receipts.swaps(transaction).await {
+                Ok(swaps) => slippage
+                    .tracker
+                    .record_settlement(auction, &solutions, &swaps),
+                Err(err) => {
+                    tracing::warn!(?err, ?transaction, "failed to fetch settlement swaps")
+                }
+            }
+        });
+    }
+}
+
+/// Refreshes the routes of the quote matrix on every new block, for as long as
+/// the solver is alive.
+async fn refresh_quote_matrix(inner: Weak<Inner>, node_url: Url) {
+    let blocks =
+        match ethrpc::block_stream::current_block_stream(node_url, BLOCK_POLL_INTERVAL).await {
+            Ok(blocks) => blocks,
+            Err(err) => {
+                tracing::warn!(?err, "quote matrix won't be refreshed");
+                return;
+            }
+        };
+    let mut blocks = ethrpc::block_stream::into_stream(blocks);
+    while let Some(block) = blocks.next().await {
+        let Some(inner) = inner.upgrade() else {
+            return;
+        };
+        let refresh = inner
+            .refresh_quote_matrix()
+            .instrument(tracing::debug_span!("quote_matrix", block = block.number));
+        heap::track(heap::Subsystem::PoolCache, refresh).await;
+    }
+}
+
+impl Inner {
+    /// Recomputes the routes of the quote matrix from the liquidity of the
+    /// latest solved auction. Routes get computed for a sell amount worth
+    /// `native_token_price_estimation_amount`, so pairs whose sell token has
+    /// no reference price are not served from the matrix.
+    async fn refresh_quote_matrix(&self) {
+        let Some(matrix) = &self.quote_matrix else {
+            return;
+        };
+        let Some(snapshot) = matrix.snapshot() else {
+            return;
+        };
+        let (liquidity, tokens) = (&snapshot.liquidity, &snapshot.tokens);
+        let routing = self.routing.read().unwrap().clone();
+        let boundary_solver = boundary::baseline::Solver::new(
+            &self.weth,
+            &routing.base_tokens,
+            liquidity,
+            self.uni_v3_quoter_v2.clone(),
+            self.uni_v4_quoter.clone(),
+            self.erc4626_web3.as_ref(),
+        );
+        let routes = futures::future::join_all(matrix.pairs().map(|(sell, buy)| {
+            let (boundary_solver, routing) = (&boundary_solver, &routing);
+            async move {
+                let amount = match tokens.reference_price(&sell) {
+                    Some(price) => {
+                        price.ether_value(eth::Ether(self.native_token_price_estimation_amount))?
+                    }
+                    None if sell == self.weth.0.into() => self.native_token_price_estimation_amount,
+                    None => return None,
+                };
+                let request = Request {
+                    sell: eth::Asset {
+                        token: sell,
+                        amount,
+                    },
+                    buy: eth::Asset {
+                        token: buy,
+                        amount: eth::U256::zero(),
+                    },
+                    side: order::Side::Sell,
+                    wrappers: Vec::new(),
+                };
+                let route = boundary_solver.route(request, routing.max_hops).await?;
+                let route = quote_matrix::Route {
+                    liquidity: route
+                        .segments
+                        .iter()
+                        .map(|segment| segment.liquidity.clone())
+                        .collect(),
+                    path: std::iter::once(sell)
+                        .chain(route.segments.iter().map(|segment| segment.output.token))
+                        .collect(),
+                    amounts: std::iter::once(route.input().amount)
+                        .chain(route.segments.iter().map(|segment| segment.output.amount))
+                        .collect(),
+                };
+                Some(((sell, buy), route))
+            }
+        }))
+        .await
+        .into_iter()
+        .flatten()
+        .collect::<HashMap<_, _>>();
+        tracing::debug!(routes = routes.len(), "refreshed quote matrix");
+        matrix.update(routes);
+    }
+
+    /// Returns the route of a quote priced at the cached route of the quote
+    /// matrix, or `None` on a miss. The cached pools are looked up in the
+    /// specified liquidity.
+    fn matrix_route<'a>(
+        &self,
+        liquidity: &'a [liquidity::Liquidity],
+        request: &Request,
+    ) -> Option<Route<'a>> {
+        if !request.wrappers.is_empty() {
+            return None;
+        }
+        let amount = match request.side {
+            order::Side::Sell => request.sell.amount,
+            order::Side::Buy => request.buy.amount,
+        };
+        let hops = self.quote_matrix.as_ref()?.quote(
+            (request.sell.token, request.buy.token),
+            request.side,
+            amount,
+        )?;
+        let segments = hops
+            .into_iter()
+            .map(|hop| {
+                let liquidity = liquidity
+                    .iter()
+                    .find(|liquidity| liquidity.id == hop.liquidity)?;
+                Some(Segment {
+                    liquidity,
+                    input: hop.input,
+                    output: hop.output,
+                    gas: liquidity.gas,
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Route::new(segments)
+    }
+
+    async fn solve(
+        self: Arc<Self>,
+        auction: auction::Auction,
+        routing: Routing,
+        sender: tokio::sync::mpsc::UnboundedSender<solution::Solution>,
+    ) {
+        // Orders are routed independently of each other against the same
+        // liquidity, so they get solved in parallel by a pool of tasks. Each
+        // task builds its own liquidity graph once and then solves the orders
+        // it takes from the shared queue.
+        let workers = self.max_concurrent_orders.max(1).min(auction.orders.len());
+        let queue = Arc::new(Queue::new(auction.orders.len()));
+        let auction = Arc::new(auction);
+        let routing = Arc::new(routing);
+        run_workers(workers, || {
+            let inner = self.clone();
+            let (auction, routing, queue, sender) = (
+                auction.clone(),
+                routing.clone(),
+                queue.clone(),
+                sender.clone(),
+            );
+            async move {
+                inner
+                    .solve_orders(&auction, &routing, &queue, &sender)
+                    .await
+            }
+        })
+        .await;
+    }
+
+    /// Solves the orders taken from the queue one after the other.
+    async fn solve_orders(
+        &self,
+        auction: &auction::Auction,
+        routing: &Routing,
+        queue: &Queue,
+        sender: &tokio::sync::mpsc::UnboundedSender<solution::Solution>,
+    ) {
+        let boundary_solver = boundary::baseline::Solver::new(
+            &self.weth,
+            &routing.base_tokens,
+            &auction.liquidity,
+            self.uni_v3_quoter_v2.clone(),
+            self.uni_v4_quoter.clone(),
+            self.erc4626_web3.as_ref(),
+        );
+        let gas_budget = gas_budget::Budget::new(self.max_solution_gas, auction);
+
+        let solve_order = async |i: usize, order: &Order| {
+            let sell_token = order.sell.token;
+            let sell_token_price = match auction.tokens.reference_price(&sell_token) {
+                Some(price) => price,
+                None if sell_token == self.weth.0.into() => {
+                    // Early return if the sell token is native token
+                    auction::Price(eth::Ether(eth::U256::exp10(18)))
+                }
+                None => {
+                    // Estimate the price of the sell token in the native token
+                    let native_price_request = self.native_price_request(&order);
+                    match boundary_solver
+                        .route(native_price_request, routing.max_hops)
+                        .await
+                    {
+                        Some(route) => {
+                            // how many units of buy_token are bought for one unit of sell_token
+                            // (buy_amount / sell_amount).
+                            let price = self.native_token_price_estimation_amount.to_f64_lossy()
+                                / route.input().amount.to_f64_lossy();
+                            let Some(price) = to_normalized_price(price) else {
+                                return;
+                            };
+
+                            auction::Price(eth::Ether(price))
+                        }
+                        _ => {
+                            // This is to allow quotes to be generated for tokens for which the sell
+                            // token price is not available, so we default to fee=0
+                            auction::Price(eth::Ether(eth::U256::MAX))
+                        }
+                    }
+                }
+            };
+
+            let compute_solution = async |solver: &boundary::baseline::Solver<'_>,
+                                          request: Request|
+                   -> Option<Solution> {
+                let wrappers = request.wrappers.clone();
+                let route_request = async |request: Request| {
+                    if let auction::Id::Quote = auction.id
+                        && let Some(route) = self.matrix_route(&auction.liquidity, &request)
+                    {
+                        return Some(route);
+                    }
+                    match (auction.id, request.side) {
+                        // Exact-out quotes are priced at the minimal sell
+                        // amount buying the requested amount, which takes a
+                        // few more swap simulations per path.
+                        (auction::Id::Quote, order::Side::Buy) => {
+                            solver.route_exact_out(request, routing.max_hops).await
+                        }
+                        _ => solver.route(request, routing.max_hops).await,
+                    }
+                };
+                let (sell, buy, side) = (request.sell, request.buy, request.side);
+                let route = route_request(request).await?;
+
+                // Parts of the order the on-chain liquidity trades at a worse
+                // price than the inventory get filled from the inventory, and
+                // only the remainder gets routed.
+                let fill = self.inventory.as_ref().and_then(|inventory| {
+                    let fill = inventory.fill(
+                        side,
+                        sell,
+                        buy,
+                        (route.input(), route.output()),
+                        &auction.tokens,
+                    )?;
+                    Some((inventory, fill))
+                });
+                let (route, fill) = match fill {
+                    Some((inventory, fill)) => {
+                        match remainder(sell, buy, side, &fill, wrappers.clone()) {
+                            Some(remainder) => match route_request(remainder).await {
+                                Some(rest) => (Some(rest), Some((inventory, fill))),
+                                // Keep the original route if the remainder
+                                // can't be routed on its own.
+                                None => (Some(route), None),
+                            },
+                            None => (None, Some((inventory, fill))),
+                        }
+                    }
+                    None => (Some(route), None),
+                };
+                if let Some((_, fill)) = &fill {
+                    tracing::debug!(order =% order.uid, ?fill, "filling order from inventory");
+                }
+
+                let interactions: Vec<_> = route
+                    .iter()
+                    .flat_map(|route| &route.segments)
+                    .map(|segment| {
+                        solution::Interaction::Liquidity(Box::new(solution::LiquidityInteraction {
+                            liquidity: segment.liquidity.clone(),
+                            input: segment.input,
+                            output: segment.output,
+                            // TODO does the baseline solver know about this optimization?
+                            internalize: false,
+                        }))
+                    })
+                    .collect();
+                let mut interactions = match &self.batch_router {
+                    Some(batch_router) => batch_router.encode(interactions),
+                    None => interactions,
+                };
+                let (mut input, mut output) = match &route {
+                    Some(route) => (route.input(), route.output()),
+                    None => (
+                        eth::Asset {
+                            token: sell.token,
+                            amount: U256::zero(),
+                        },
+                        eth::Asset {
+                            token: buy.token,
+                            amount: U256::zero(),
+                        },
+                    ),
+                };
+                let mut gas = route.as_ref().map(Route::gas).unwrap_or_default();
+                if let Some((inventory, fill)) = &fill {
+                    interactions.extend(inventory.interactions(fill));
+                    input.amount = input.amount.checked_add(fill.input.amount)?;
+                    output.amount = output.amount.checked_add(fill.output.amount)?;
+                    gas = eth::Gas(gas.0.saturating_add(inventory.fill_gas().0));
+                }
+
+                // The baseline solver generates a path with swapping
+                // for exact output token amounts. This leads to
+                // potential rounding errors for buy orders, where we
+                // can buy slightly more than intended. Fix this by
+                // capping the output amount to the order's buy amount
+                // for buy orders.
+                if let order::Side::Buy = order.side {
+                    output.amount = cmp::min(output.amount, order.buy.amount);
+                }
+
+                let gas = gas + self.solution_gas_offset;
+                if let Some(budget) = &gas_budget
+                    && !budget.fits(gas)
+                {
+                    tracing::debug!(order =% order.uid, ?gas, "route exceeds gas budget");
+                    return None;
+                }
+                let fee = sell_token_price
+                    .ether_value(eth::Ether(gas.0.checked_mul(auction.gas_price.0.0)?))?
+                    .into();
+
+                let single = solution::Single {
+                    order: order.clone(),
+                    input,
+                    output,
+                    interactions,
+                    gas,
+                    wrappers,
+                };
+                let surplus_share = self.surplus_share(&auction, &single);
+                let tokens =
+                    fill.and_then(|(inventory, fill)| inventory.after_fill(&fill, &auction.tokens));
+                Some(
+                    single
+                        .into_solution(fee, surplus_share)?
+                        .with_id(solution::Id(i as u64))
+                        .with_buffers_internalizations(tokens.as_ref().unwrap_or(&auction.tokens)),
+                )
+            };
+
+            if let Some(lp) = &self.lp
+                && let Some(intent) = lp::Intent::detect(order, &auction.liquidity)
+            {
+                let solution = async {
+                    let single = lp.solve(order, &intent, self.solution_gas_offset).await?;
+                    let fee = sell_token_price
+                        .ether_value(eth::Ether(single.gas.0.checked_mul(auction.gas_price.0.0)?))?
+                        .into();
+                    let surplus_share = self.surplus_share(&auction, &single);
+                    Some(
+                        single
+                            .into_solution(fee, surplus_share)?
+                            .with_id(solution::Id(i as u64))
+                            .with_buffers_internalizations(&auction.tokens),
+                    )
+                };
+                match solution.await {
+                    Some(solution) => {
+                        if sender.send(solution).is_err() {
+                            tracing::debug!("deadline hit, receiver dropped");
+                        }
+                    }
+                    None => tracing::debug!(order =% order.uid, "failed to solve pool intent"),
+                }
+                return;
+            }
+
+            let route_order = async |solver: &boundary::baseline::Solver<'_>| {
+                for request in self.requests_for_order(order, routing.max_partial_attempts) {
+                    tracing::trace!(order =% order.uid, ?request, "finding route");
+                    if let Some(solution) = compute_solution(solver, request).await {
+                        return Some(solution);
+                    }
+                }
+                None
+            };
+
+            // Pinned orders are routed over the pinned liquidity first, and
+            // only fall back to all liquidity if no such route exists.
+            let pinned = order.route_pin.as_ref().map(|pin| {
+                auction
+                    .liquidity
+                    .iter()
+                    .filter(|liquidity| pin.allows(liquidity))
+                    .cloned()
+                    .collect::<Vec<_>>()
+            });
+            let solution = match &pinned {
+                Some(pinned) => {
+                    let pinned_solver = boundary::baseline::Solver::new(
+                        &self.weth,
+                        &routing.base_tokens,
+                        pinned,
+                        self.uni_v3_quoter_v2.clone(),
+                        self.uni_v4_quoter.clone(),
+                        self.erc4626_web3.as_ref(),
+                    );
+                    match route_order(&pinned_solver).await {
+                        Some(solution) => Some(solution),
+                        None => {
+                            tracing::info!(
+                                order =% order.uid,
+                                pin = ?order.route_pin,
+                                liquidity = pinned.len(),
+                                "no route over the pinned liquidity; routing over all liquidity"
+                            );
+                            metrics::route_pin_unsatisfied();
+                            route_order(&boundary_solver).await
+                        }
+                    }
+                }
+                None => route_order(&boundary_solver).await,
+            };
+            if let Some(solution) = solution {
+                self.stats
+                    .record_solution(auction.id, &auction.tokens, &solution);
+                if sender.send(solution).is_err() {
+                    tracing::debug!("deadline hit, receiver dropped");
+                }
+            }
+        };
+        while let Some(i) = queue.next() {
+            solve_order(i, &auction.orders[i]).await;
+        }
+    }
+
+    /// The share of the surplus of the single order solution to keep, which
+    /// is calibrated on past competitions for competition auctions if
+    /// enabled and the configured share otherwise.
+    fn surplus_share(
+        &self,
+        auction: &auction::Auction,
+        single: &solution::Single,
+    ) -> solution::SurplusShare {
+        let calibrated = match (&self.bidding, auction.id) {
+            (Some(bidding), auction::Id::Solve(_)) => {
+                bidding.surplus_share(&auction.tokens, single)
+            }
+            _ => None,
+        };
+        match calibrated {
+            Some(share) => {
+                tracing::debug!(
+                    order =% single.order.uid,
+                    bps = share.bps(),
+                    "calibrated surplus share"
+                );
+                share
+            }
+            None => self.surplus_share,
+        }
+    }
+
+    fn requests_for_order(
+        &self,
+        order: &Order,
+        max_partial_attempts: usize,
+    ) -> impl Iterator<Item = Request> + use<> {
+        let order::Order {
+            sell,
+            buy,
+            side,
+            wrappers,
+            ..
+        } = order.clone();
+
+        let n = if order.partially_fillable {
+            max_partial_attempts
+        } else {
+            1
+        };
+
+        (0..n)
+            .map(move |i| {
+                let divisor = U256::one() << i;
+                Request {
+                    sell: eth::Asset {
+                        token: sell.token,
+                        amount: sell.amount / divisor,
+                    },
+                    buy: eth::Asset {
+                        token: buy.token,
+                        amount: buy.amount / divisor,
+                    },
+                    side,
+                    wrappers: wrappers.clone(),
+                }
+            })
+            .filter(|r| !r.sell.amount.is_zero() && !r.buy.amount.is_zero())
+    }
+
+    fn native_price_request(&self, order: &Order) -> Request {
+        let sell = eth::Asset {
+            token: order.sell.token,
+            // Note that we intentionally do not use [`eth::U256::max_value()`]
+            // as an order with this would cause overflows with the smart
+            // contract, so buy orders requiring excessively large sell amounts
+            // would not work anyway. Instead we use `2 ** 144`, the rationale
+            // being that Uniswap V2 pool reserves are 112-bit integers. Noting
+            // that `256 - 112 = 144`, this means that we can use it to trade a full
+            // `type(uint112).max` without overflowing a `uint256` on the smart
+            // contract level. Requiring to trade more than `type(uint112).max`
+            // is unlikely and would not work with Uniswap V2 anyway.
+            amount: eth::U256::one() << 144,
+        };
+
+        let buy = eth::Asset {
+            token: self.weth.0.into(),
+            amount: self.native_token_price_estimation_amount,
+        };
+
+        Request {
+            sell,
+            buy,
+            side: order::Side::Buy,
+            wrappers: order.wrappers.clone(),
+        }
+    }
+}
+
+/// Hands out the indices of an auction's orders to the tasks solving them.
+struct Queue {
+    next: AtomicUsize,
+    len: usize,
+}
+
+impl Queue {
+    fn new(len: usize) -> Self {
+        Self {
+            next: AtomicUsize::new(0),
+            len,
+        }
+    }
+
+    /// Returns the index of the next order to solve, if any are left.
+    fn next(&self) -> Option<usize> {
+        let i = self.next.fetch_add(1, atomic::Ordering::Relaxed);
+        (i < self.len).then_some(i)
+    }
+}
+
+/// Runs the futures created by `worker` on `count` spawned tasks and waits for
+/// all of them to finish. The tasks get aborted when this future is dropped,
+/// and panics of the tasks are propagated.
+async fn run_workers<F>(count: usize, worker: impl Fn() -> F)
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    let mut workers = tokio::task::JoinSet::new();
+    for _ in 0..count {
+        workers.spawn(worker().instrument(tracing::Span::current()));
+    }
+    while let Some(result) = workers.join_next().await {
+        if let Err(err) = result
+            && err.is_panic()
+        {
+            std::panic::resume_unwind(err.into_panic());
+        }
+    }
+}
+
+/// Reduces the liquidity to the `max_per_pair` deepest liquidity sources for
+/// each token pair. Depth is measured as the native token value of the
+/// liquidity's reserves, so liquidity without known reserves or reference
+/// prices is ranked last.
+fn quote_liquidity(
+    liquidity: Vec<liquidity::Liquidity>,
+    tokens: &auction::Tokens,
+    max_per_pair: usize,
+) -> Vec<liquidity::Liquidity> {
+    let depths = liquidity
+        .iter()
+        .map(|liquidity| {
+            liquidity
+                .reserves()
+                .into_iter()
+                .filter_map(|asset| {
+                    tokens
+                        .reference_price(&asset.token)?
+                        .native_value(asset.amount)
+                })
+                .fold(U256::zero(), |acc, value| acc.saturating_add(value.0))
+        })
+        .collect::<Vec<_>>();
+
+    let mut by_pair = HashMap::<liquidity::TokenPair, Vec<usize>>::new();
+    for (index, liquidity) in liquidity.iter().enumerate() {
+        for (a, b) in liquidity.tokens().into_iter().tuple_combinations() {
+            if let Some(pair) = liquidity::TokenPair::new(a, b) {
+                by_pair.entry(pair).or_default().push(index);
+            }
+        }
+    }
+
+    let mut keep = vec![false; liquidity.len()];
+    for indices in by_pair.values_mut() {
+        indices.sort_by_key(|&index| cmp::Reverse(depths[index]));
+        for &index in indices.iter().take(max_per_pair) {
+            keep[index] = true;
+        }
+    }
+
+    liquidity
+        .into_iter()
+        .zip(keep)
+        .filter_map(|(liquidity, keep)| keep.then_some(liquidity))
+        .collect()
+}
+
+fn to_normalized_price(price: f64) -> Option<U256> {
+    let uint_max = 2.0_f64.powi(256);
+
+    let price_in_eth = 1e18 * price;
+    if price_in_eth.is_normal() && price_in_eth >= 1. && price_in_eth < uint_max {
+        Some(U256::from_f64_lossy(price_in_eth))
+    } else {
+        None
+    }
+}
+
+/// The request routing the part of a request selling `sell` for `buy` left
+/// after the inventory fill, keeping the limit price of the request. Returns
+/// `None` if the fill covers the request entirely.
+fn remainder(
+    sell: eth::Asset,
+    buy: eth::Asset,
+    side: order::Side,
+    fill: &inventory::Fill,
+    wrappers: Vec<order::WrapperCall>,
+) -> Option<Request> {
+    // The remaining amounts are at most the requested amounts, so the
+    // conversions can't overflow.
+    let scale = |amount: U256, numerator: U256, denominator: U256| -> U256 {
+        (amount.full_mul(numerator) / denominator)
+            .try_into()
+            .unwrap_or_default()
+    };
+    let (sell_amount, buy_amount) = match side {
+        order::Side::Sell => {
+            let sell_amount = sell.amount.saturating_sub(fill.input.amount);
+            (sell_amount, scale(buy.amount, sell_amount, sell.amount))
+        }
+        order::Side::Buy => {
+            let buy_amount = buy.amount.saturating_sub(fill.output.amount);
+            (scale(sell.amount, buy_amount, buy.amount), buy_amount)
+        }
+    };
+    if sell_amount.is_zero() || buy_amount.is_zero() {
+        return None;
+    }
+    Some(Request {
+        sell: eth::Asset {
+            token: sell.token,
+            amount: sell_amount,
+        },
+        buy: eth::Asset {
+            token: buy.token,
+            amount: buy_amount,
+        },
+        side,
+        wrappers,
+    })
+}
+
+/// A baseline routing request.
+#[derive(Debug)]
+pub struct Request {
+    pub sell: eth::Asset,
+    pub buy: eth::Asset,
+    pub side: order::Side,
+    pub wrappers: Vec<order::WrapperCall>,
+}
+
+/// A trading route.
+#[derive(Debug)]
+pub struct Route<'a> {
+    segments: Vec<Segment<'a>>,
+}
+
+/// A segment in a trading route.
+#[derive(Debug)]
+pub struct Segment<'a> {
+    pub liquidity: &'a liquidity::Liquidity,
+    // TODO: There is no type-level guarantee here that both `input.token` and
+    // `output.token` are valid for the liquidity in this segment. This is
+    // unfortunate because this type leaks out of this module (currently into
+    // the `boundary::baseline` module) but should no longer need to be `pub`
+    // once the `boundary::baseline` module gets refactored into the domain
+    // logic, so I think it is fine for now.
+    pub input: eth::Asset,
+    pub output: eth::Asset,
+    pub gas: eth::Gas,
+}
+
+impl<'a> Route<'a> {
+    pub fn new(segments: Vec<Segment<'a>>) -> Option<Self> {
+        if segments.is_empty() {
+            return None;
+        }
+        Some(Self { segments })
+    }
+
+    fn input(&self) -> eth::Asset {
+        self.segments[0].input
+    }
+
+    fn output(&self) -> eth::Asset {
+        self.segments
+            .last()
+            .expect("route has at least one segment by construction")
+            .output
+    }
+
+    fn gas(&self) -> eth::Gas {
+        eth::Gas(self.segments.iter().fold(U256::zero(), |acc, segment| {
+            acc.saturating_add(segment.gas.0)
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn solves_orders_in_parallel() {
+        // Every order blocks its thread until all orders are being solved,
+        // which only happens if they are solved on separate threads.
+        let queue = Arc::new(Queue::new(4));
+        let started = Arc::new(AtomicUsize::new(0));
+        let solved = Arc::new(std::sync::Mutex::new(Vec::new()));
+        run_workers(4, || {
+            let (queue, started, solved) = (queue.clone(), started.clone(), solved.clone());
+            async move {
+                while let Some(i) = queue.next() {
+                    started.fetch_add(1, atomic::Ordering::SeqCst);
+                    let deadline = Instant::now() + Duration::from_secs(5);
+                    while started.load(atomic::Ordering::SeqCst) < 4 && Instant::now() < deadline {
+                        std::thread::sleep(Duration::from_millis(1));
+                    }
+                    let parallel = started.load(atomic::Ordering::SeqCst) == 4;
+                    solved.lock().unwrap().push((i, parallel));
+                }
+            }
+        })
+        .await;
+
+        let mut solved = solved.lock().unwrap().clone();
+        solved.sort();
+        assert_eq!(solved, [(0, true), (1, true), (2, true), (3, true)]);
+    }
+
+    #[tokio::test]
+    async fn hands_out_every_order_once() {
+        let queue = Arc::new(Queue::new(10));
+        let solved = Arc::new(std::sync::Mutex::new(Vec::new()));
+        run_workers(3, || {
+            let (queue, solved) = (queue.clone(), solved.clone());
+            async move {
+                while let Some(i) = queue.next() {
+                    solved.lock().unwrap().push(i);
+                    tokio::task::yield_now().await;
+                }
+            }
+        })
+        .await;
+
+        let mut solved = solved.lock().unwrap().clone();
+        solved.sort();
+        assert_eq!(solved, (0..10).collect::<Vec<_>>());
+        assert_eq!(queue.next(), None);
+    }
+}
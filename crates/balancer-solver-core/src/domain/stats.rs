@@ -0,0 +1,455 @@
+//! Rolling-window solver statistics per token pair.
+//!
+//! The statistics are kept in memory and only cover competition auctions, as
+//! quotes never make it to the solver competition.
+
+use {
+    crate::domain::{auction, liquidity, order, solution},
+    ethereum_types::U256,
+    std::{
+        collections::{BTreeMap, HashMap, HashSet, VecDeque},
+        sync::Mutex,
+        time::{Duration, Instant},
+    },
+};
+
+/// Statistics of the solver per token pair over a rolling time window.
+pub struct PairStats {
+    window: Duration,
+    inner: Mutex<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    /// The token pairs traded by recent auctions, used for attributing
+    /// competition results to token pairs.
+    auctions: VecDeque<(Instant, i64, Vec<liquidity::TokenPair>)>,
+    events: VecDeque<(Instant, liquidity::TokenPair, Event)>,
+}
+
+enum Event {
+    Auction,
+    Solution {
+        price_impact: Option<f64>,
+        pool_types: Vec<&'static str>,
+    },
+    Competition {
+        won: bool,
+    },
+}
+
+/// A summary of the solver statistics for a single token pair.
+#[derive(Debug)]
+pub struct Summary {
+    pub pair: liquidity::TokenPair,
+    /// The number of auctions with orders trading the token pair.
+    pub auctions: usize,
+    /// The number of solutions produced for orders trading the token pair.
+    pub solutions: usize,
+    /// The number of auctions trading the token pair with known competition
+    /// results.
+    pub competitions: usize,
+    /// The number of those competitions that the solver won.
+    pub wins: usize,
+    /// The median price impact of the produced solutions, as a fraction of
+    /// the native value of the sold tokens.
+    pub median_price_impact: Option<f64>,
+    /// The number of times each kind of liquidity was used in solutions.
+    pub pool_types: BTreeMap<&'static str, usize>,
+}
+
+impl Summary {
+    fn new(pair: liquidity::TokenPair) -> Self {
+        Self {
+            pair,
+            auctions: 0,
+            solutions: 0,
+            competitions: 0,
+            wins: 0,
+            median_price_impact: None,
+            pool_types: Default::default(),
+        }
+    }
+
+    /// The share of competitions that the solver won, if it participated in
+    /// any.
+    pub fn win_rate(&self) -> Option<f64> {
+        (self.competitions > 0).then(|| self.wins as f64 / self.competitions as f64)
+    }
+}
+
+impl PairStats {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            inner: Default::default(),
+        }
+    }
+
+    /// The time window the statistics cover.
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// Records that an auction was received.
+    pub fn record_auction(&self, auction: &auction::Auction) {
+        let auction::Id::Solve(id) = auction.id else {
+            return;
+        };
+        let pairs = auction
+            .orders
+            .iter()
+            .filter_map(|order| liquidity::TokenPair::new(order.sell.token, order.buy.token))
+            .collect::<HashSet<_>>();
+
+        let now = Instant::now();
+        let mut inner = self.lock(now);
+        for pair in &pairs {
+            inner.events.push_back((now, *pair, Event::Auction));
+        }
+        inner
+            .auctions
+            .push_back((now, id, pairs.into_iter().collect()));
+    }
+
+    /// Records a solution that was produced for an auction.
+    pub fn record_solution(
+        &self,
+        id: auction::Id,
+        tokens: &auction::Tokens,
+        solution: &solution::Solution,
+    ) {
+        if let auction::Id::Quote = id {
+            return;
+        }
+        let pool_types = solution
+            .interactions
+            .iter()
+            .filter_map(|interaction| match interaction {
+                solution::Interaction::Liquidity(interaction) => {
+                    Some(interaction.liquidity.state.kind())
+                }
+                solution::Interaction::Custom(_) => None,
+            })
+            .collect::<Vec<_>>();
+
+        let now = Instant::now();
+        let mut inner = self.lock(now);
+        for trade in &solution.trades {
+            let solution::Trade::Fulfillment(fulfillment) = trade else {
+                continue;
+            };
+            let order = fulfillment.order();
+            let Some(pair) = liquidity::TokenPair::new(order.sell.token, order.buy.token) else {
+                continue;
+            };
+            inner.events.push_back((
+                now,
+                pair,
+                Event::Solution {
+                    price_impact: price_impact(tokens, solution, order),
+                    pool_types: pool_types.clone(),
+                },
+            ));
+        }
+    }
+
+    /// Records the result of the solver competition for an auction.
+    pub fn record_competition(&self, auction: i64, won: bool) {
+        let now = Instant::now();
+        let mut inner = self.lock(now);
+        let Some(pairs) = inner
+            .auctions
+            .iter()
+            .find(|(_, id, _)| *id == auction)
+            .map(|(_, _, pairs)| pairs.clone())
+        else {
+            return;
+        };
+        for pair in pairs {
+            inner
+                .events
+                .push_back((now, pair, Event::Competition { won }));
+        }
+    }
+
+    /// Returns the statistics of all token pairs seen within the window.
+    pub fn summaries(&self) -> Vec<Summary> {
+        let inner = self.lock(Instant::now());
+
+        let mut summaries = HashMap::<liquidity::TokenPair, (Summary, Vec<f64>)>::new();
+        for (_, pair, event) in &inner.events {
+            let (summary, price_impacts) = summaries
+                .entry(*pair)
+                .or_insert_with(|| (Summary::new(*pair), Vec::new()));
+            match event {
+                Event::Auction => summary.auctions += 1,
+                Event::Solution {
+                    price_impact,
+                    pool_types,
+                } => {
+                    summary.solutions += 1;
+                    price_impacts.extend(*price_impact);
+                    for pool_type in pool_types {
+                        *summary.pool_types.entry(*pool_type).or_default() += 1;
+                    }
+                }
+                Event::Competition { won } => {
+                    summary.competitions += 1;
+                    summary.wins += usize::from(*won);
+                }
+            }
+        }
+
+        summaries
+            .into_values()
+            .map(|(mut summary, mut price_impacts)| {
+                price_impacts.sort_by(f64::total_cmp);
+                summary.median_price_impact = median(&price_impacts);
+                summary
+            })
+            .collect()
+    }
+
+    /// Locks the statistics, dropping everything that fell out of the window.
+    fn lock(&self, now: Instant) -> std::sync::MutexGuard<'_, Inner> {
+        let mut inner = self.inner.lock().unwrap();
+        let expired = |at: &Instant| now.saturating_duration_since(*at) > self.window;
+        while inner.events.front().is_some_and(|(at, ..)| expired(at)) {
+            inner.events.pop_front();
+        }
+        while inner.auctions.front().is_some_and(|(at, ..)| expired(at)) {
+            inner.auctions.pop_front();
+        }
+        inner
+    }
+}
+
+/// Computes the price impact of the solution for the specified order, that is
+/// the share of the native value of the sold tokens that is lost when
+/// comparing to the native value of the bought tokens at the auction's
+/// reference prices.
+fn price_impact(
+    tokens: &auction::Tokens,
+    solution: &solution::Solution,
+    order: &order::Order,
+) -> Option<f64> {
+    // Uniform clearing prices for a single order map each token to the
+    // executed amount of the other token.
+    let sold = *solution.prices.0.get(&order.buy.token)?;
+    let bought = *solution.prices.0.get(&order.sell.token)?;
+
+    let sold_value = tokens
+        .reference_price(&order.sell.token)?
+        .native_value(sold)?;
+    let bought_value = tokens
+        .reference_price(&order.buy.token)?
+        .native_value(bought)?;
+    if sold_value.0 == U256::zero() {
+        return None;
+    }
+    Some(1. - bought_value.0.to_f64_lossy() / sold_value.0.to_f64_lossy())
+}
+
+fn median(sorted: &[f64]) -> Option<f64> {
+    let mid = sorted.len() / 2;
+    match sorted.len() {
+        0 => None,
+        len if len % 2 == 0 => Some((sorted[mid - 1] + sorted[mid]) / 2.),
+        _ => Some(sorted[mid]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::domain::eth};
+
+    fn token(token: u64) -> eth::TokenAddress {
+        eth::TokenAddress(eth::H160::from_low_u64_be(token))
+    }
+
+    fn pair(a: u64, b: u64) -> liquidity::TokenPair {
+        liquidity::TokenPair::new(token(a), token(b)).unwrap()
+    }
+
+    fn order(uid: u8, sell: u64, buy: u64) -> order::Order {
+        order::Order {
+            uid: order::Uid([uid; 56]),
+            sell: eth::Asset {
+                token: token(sell),
+                amount: 1000.into(),
+            },
+            buy: eth::Asset {
+                token: token(buy),
+                amount: 1.into(),
+            },
+            ..Default::default()
+        }
+    }
+
+    fn auction(id: auction::Id, orders: Vec<order::Order>) -> auction::Auction {
+        auction::Auction {
+            id,
+            tokens: auction::Tokens(Default::default()),
+            orders,
+            liquidity: Vec::new(),
+            gas_price: auction::GasPrice(eth::Ether(0.into())),
+            deadline: auction::Deadline(chrono::Utc::now()),
+            gas_limit: None,
+        }
+    }
+
+    /// Token 1 is worth 1 ETH and token 2 is worth 2 ETH.
+    fn tokens() -> auction::Tokens {
+        let token = |price: u64| auction::Token {
+            decimals: Some(18),
+            symbol: None,
+            reference_price: Some(auction::Price(eth::Ether(eth::U256::exp10(18) * price))),
+            available_balance: 0.into(),
+            trusted: true,
+        };
+        auction::Tokens([(self::token(1), token(1)), (self::token(2), token(2))].into())
+    }
+
+    /// A solution selling 1000 of token 1 for `bought` of token 2 through
+    /// liquidity of the specified kinds.
+    fn solution(bought: u64, states: Vec<liquidity::State>) -> solution::Solution {
+        let order = order(1, 1, 2);
+        solution::Solution {
+            prices: solution::ClearingPrices(
+                [(token(1), bought.into()), (token(2), 1000.into())].into(),
+            ),
+            trades: vec![solution::Trade::Fulfillment(
+                solution::Fulfillment::fill(order).unwrap(),
+            )],
+            interactions: states
+                .into_iter()
+                .map(|state| {
+                    solution::Interaction::Liquidity(Box::new(solution::LiquidityInteraction {
+                        liquidity: liquidity::Liquidity {
+                            id: liquidity::Id("0".to_owned()),
+                            address: Default::default(),
+                            balancer_pool_id: None,
+                            gas: eth::Gas(0.into()),
+                            state,
+                        },
+                        input: eth::Asset {
+                            token: token(1),
+                            amount: 1000.into(),
+                        },
+                        output: eth::Asset {
+                            token: token(2),
+                            amount: bought.into(),
+                        },
+                        internalize: false,
+                    }))
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn erc4626() -> liquidity::State {
+        liquidity::State::Erc4626(liquidity::erc4626::Edge {
+            vault: token(2),
+            asset: token(1),
+        })
+    }
+
+    fn limit_order() -> liquidity::State {
+        liquidity::State::LimitOrder(liquidity::limit_order::LimitOrder {
+            maker: eth::Asset {
+                token: token(2),
+                amount: 1000.into(),
+            },
+            taker: eth::Asset {
+                token: token(1),
+                amount: 1000.into(),
+            },
+            fee: liquidity::limit_order::TakerAmount(0.into()),
+        })
+    }
+
+    #[test]
+    fn aggregates_events_per_token_pair() {
+        let stats = PairStats::new(Duration::from_secs(60));
+
+        // Orders trading the same pair in either direction count once per
+        // auction, and quotes are ignored.
+        stats.record_auction(&auction(
+            auction::Id::Solve(1),
+            vec![order(1, 1, 2), order(2, 2, 1), order(3, 2, 3)],
+        ));
+        stats.record_auction(&auction(auction::Id::Solve(2), vec![order(1, 1, 2)]));
+        stats.record_auction(&auction(auction::Id::Quote, vec![order(1, 1, 2)]));
+
+        // Selling 1000 of token 1, worth 1000 wei, for 490 and 485 of token 2,
+        // worth 980 and 970 wei, loses 2% and 3% of the value.
+        stats.record_solution(
+            auction::Id::Solve(1),
+            &tokens(),
+            &solution(490, vec![erc4626(), limit_order()]),
+        );
+        stats.record_solution(
+            auction::Id::Solve(2),
+            &tokens(),
+            &solution(485, vec![erc4626()]),
+        );
+        stats.record_solution(auction::Id::Quote, &tokens(), &solution(1, vec![erc4626()]));
+
+        // Competitions of unknown auctions are ignored.
+        stats.record_competition(1, true);
+        stats.record_competition(2, false);
+        stats.record_competition(3, true);
+
+        let mut summaries = stats.summaries();
+        summaries.sort_by_key(|summary| summary.pair.get());
+        let [first, second] = summaries.as_slice() else {
+            panic!("unexpected summaries {summaries:?}");
+        };
+
+        assert_eq!(first.pair, pair(1, 2));
+        assert_eq!(first.auctions, 2);
+        assert_eq!(first.solutions, 2);
+        assert_eq!(first.competitions, 2);
+        assert_eq!(first.wins, 1);
+        assert_eq!(first.win_rate(), Some(0.5));
+        assert!((first.median_price_impact.unwrap() - 0.025).abs() < 1e-9);
+        assert_eq!(
+            first.pool_types,
+            BTreeMap::from([("erc4626", 2), ("limitOrder", 1)])
+        );
+
+        assert_eq!(second.pair, pair(2, 3));
+        assert_eq!(second.auctions, 1);
+        assert_eq!(second.solutions, 0);
+        assert_eq!(second.competitions, 1);
+        assert_eq!(second.wins, 1);
+        assert_eq!(second.win_rate(), Some(1.));
+        assert_eq!(second.median_price_impact, None);
+        assert!(second.pool_types.is_empty());
+    }
+
+    #[test]
+    fn drops_events_outside_of_the_window() {
+        let window = Duration::from_secs(60);
+        let stats = PairStats::new(window);
+        stats.record_auction(&auction(auction::Id::Solve(1), vec![order(1, 1, 2)]));
+        assert_eq!(stats.summaries().len(), 1);
+
+        drop(stats.lock(Instant::now() + window * 2));
+        assert!(stats.summaries().is_empty());
+
+        // The auction is forgotten as well, so its competition is ignored.
+        stats.record_competition(1, true);
+        assert!(stats.summaries().is_empty());
+    }
+
+    #[test]
+    fn computes_medians() {
+        assert_eq!(median(&[]), None);
+        assert_eq!(median(&[1.]), Some(1.));
+        assert_eq!(median(&[1., 2.]), Some(1.5));
+        assert_eq!(median(&[1., 2., 4.]), Some(2.));
+    }
+}
@@ -0,0 +1,170 @@
+//! Solver-side validation of auction orders.
+//!
+//! Orders that are guaranteed to fail settlement are skipped before routing,
+//! so that no time is wasted on them and the solver does not propose solutions
+//! that would revert.
+
+use {
+    crate::{
+        domain::{auction, eth, order},
+        infra::balances::Balances,
+    },
+    ethereum_types::H256,
+};
+
+/// An order that was skipped by the validation.
+#[derive(Debug, Clone, Copy)]
+pub struct Skipped {
+    pub order: order::Uid,
+    pub reason: Reason,
+}
+
+/// The reason for skipping an order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reason {
+    /// The order expires before it can be settled.
+    Expired,
+    /// The order signature can't be valid for its signing scheme.
+    InvalidSignature,
+    /// The owner doesn't have enough sell token balance or allowance.
+    InsufficientBalance,
+}
+
+/// Removes all orders from the auction that are guaranteed to fail settlement,
+/// returning the skipped orders. Balances are only checked if a balance
+/// fetcher is provided.
+pub async fn validate(auction: &mut auction::Auction, balances: Option<&Balances>) -> Vec<Skipped> {
+    // Quote orders are not signed by anyone and are never settled.
+    if let auction::Id::Quote = auction.id {
+        return Vec::new();
+    }
+
+    let settle_after = auction.deadline.0.timestamp();
+    let mut reasons = auction
+        .orders
+        .iter()
+        .map(|order| check(order, settle_after).err())
+        .collect::<Vec<_>>();
+
+    if let Some(balances) = balances {
+        let (indices, orders): (Vec<_>, Vec<_>) = auction
+            .orders
+            .iter()
+            .enumerate()
+            .filter(|(i, order)| reasons[*i].is_none() && order.flashloan_hint.is_none())
+            .unzip();
+        let available = balances.available(&orders).await;
+        for ((index, order), available) in indices.into_iter().zip(orders).zip(available) {
+            if let Some(available) = available
+                && available < required_balance(order)
+            {
+                reasons[index] = Some(Reason::InsufficientBalance);
+            }
+        }
+    }
+
+    let mut skipped = Vec::new();
+    let mut reasons = reasons.into_iter();
+    auction
+        .orders
+        .retain(|order| match reasons.next().flatten() {
+            Some(reason) => {
+                tracing::debug!(uid = %order.uid, ?reason, "skipping order");
+                skipped.push(Skipped {
+                    order: order.uid,
+                    reason,
+                });
+                false
+            }
+            None => true,
+        });
+    skipped
+}
+
+/// Checks the parts of an order that don't depend on on-chain state.
+fn check(order: &order::Order, settle_after: i64) -> Result<(), Reason> {
+    if i64::from(order.valid_to) < settle_after {
+        return Err(Reason::Expired);
+    }
+    let plausible = match &order.signature {
+        Some(order::Signature::Eip712(signature) | order::Signature::EthSign(signature)) => {
+            matches!(signature.v, 27 | 28)
+                && signature.r != H256::zero()
+                && signature.s != H256::zero()
+        }
+        Some(order::Signature::Eip1271(_) | order::Signature::PreSign) => true,
+        None => false,
+    };
+    if !plausible {
+        return Err(Reason::InvalidSignature);
+    }
+    Ok(())
+}
+
+/// The minimum sell token balance an owner needs for the order to be
+/// settleable at all. Partially fillable orders and buy orders can be settled
+/// with less than the full sell amount.
+fn required_balance(order: &order::Order) -> eth::U256 {
+    if order.partially_fillable || order.side == order::Side::Buy {
+        eth::U256::one()
+    } else {
+        order.sell.amount
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::domain::order::EcdsaSignature};
+
+    fn order(valid_to: u32, signature: Option<order::Signature>) -> order::Order {
+        order::Order {
+            uid: order::Uid([1; 56]),
+            sell: eth::Asset {
+                token: eth::TokenAddress(eth::H160::from_low_u64_be(1)),
+                amount: 100.into(),
+            },
+            buy: eth::Asset {
+                token: eth::TokenAddress(eth::H160::from_low_u64_be(2)),
+                amount: 100.into(),
+            },
+            valid_to,
+            signature,
+            ..Default::default()
+        }
+    }
+
+    fn ecdsa(v: u8) -> EcdsaSignature {
+        EcdsaSignature {
+            r: H256::repeat_byte(1),
+            s: H256::repeat_byte(2),
+            v,
+        }
+    }
+
+    #[test]
+    fn checks_validity_window() {
+        let signature = Some(order::Signature::PreSign);
+        assert_eq!(check(&order(1000, signature.clone()), 1000), Ok(()));
+        assert_eq!(check(&order(999, signature), 1000), Err(Reason::Expired));
+    }
+
+    #[test]
+    fn checks_signature_plausibility() {
+        let check = |signature| check(&order(u32::MAX, signature), 0);
+        assert_eq!(check(Some(order::Signature::Eip712(ecdsa(27)))), Ok(()));
+        assert_eq!(check(Some(order::Signature::EthSign(ecdsa(28)))), Ok(()));
+        assert_eq!(
+            check(Some(order::Signature::Eip712(ecdsa(0)))),
+            Err(Reason::InvalidSignature)
+        );
+        assert_eq!(
+            check(Some(order::Signature::EthSign(EcdsaSignature {
+                v: 27,
+                ..Default::default()
+            }))),
+            Err(Reason::InvalidSignature)
+        );
+        assert_eq!(check(None), Err(Reason::InvalidSignature));
+        assert_eq!(check(Some(order::Signature::Eip1271(vec![]))), Ok(()));
+    }
+}
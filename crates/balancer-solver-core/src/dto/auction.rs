@@ -1,8 +1,11 @@
 use {
     crate::{
-        api::routes::Error,
         domain::{auction, eth, liquidity, order},
-        infra::liquidity_client::{LiquidityClient, LiquidityRequest},
+        dto::Error,
+        infra::{
+            artifacts,
+            liquidity_client::{BlockTag, LiquidityClient, LiquidityRequest},
+        },
         util::conv,
     },
     bigdecimal::{FromPrimitive, ToPrimitive},
@@ -76,16 +79,29 @@ fn extract_token_pairs_from_auction(
     result.into_iter().collect()
 }
 
+/// Deserializes the auction of a solve request. Request bodies that don't
+/// describe an auction are rejected like auctions that fail to convert, with
+/// the reason logged.
+pub fn from_json(body: &[u8]) -> Result<Auction, Error> {
+    serde_json::from_slice(body).map_err(|err| {
+        tracing::warn!(?err, "malformed auction");
+        Error::from("malformed auction")
+    })
+}
+
 /// Converts a data transfer object into its domain object representation.
 /// If liquidity_client is provided and auction has empty liquidity, fetches
 /// independently.
+/// The request deadline, if any, bounds fetching liquidity and replaces the
+/// auction deadline if it is earlier.
 /// Returns the auction and optionally the fetched liquidity response.
 pub async fn into_domain(
     auction: Auction,
     liquidity_client: Option<&LiquidityClient>,
     base_tokens: Option<&[eth::H160]>,
     protocols: Option<&[String]>,
-    save_directory: Option<&std::path::Path>,
+    artifacts: Option<&artifacts::Artifacts>,
+    deadline: Option<chrono::DateTime<chrono::Utc>>,
 ) -> Result<
     (
         auction::Auction,
@@ -162,6 +178,36 @@ pub async fn into_domain(
                         data: w.data.clone(),
                     })
                     .collect(),
+                route_pin: order.route_pin.clone().map(|pin| match pin {
+                    RoutePin::Protocol(kind) => order::RoutePin::Protocol(kind),
+                    RoutePin::Liquidity(id) => order::RoutePin::Liquidity(liquidity::Id(id)),
+                }),
+                owner: eth::Address(order.owner),
+                valid_to: order.valid_to,
+                signature: match order.signing_scheme {
+                    SigningScheme::Eip712 => order::EcdsaSignature::from_bytes(&order.signature)
+                        .map(order::Signature::Eip712),
+                    SigningScheme::EthSign => order::EcdsaSignature::from_bytes(&order.signature)
+                        .map(order::Signature::EthSign),
+                    SigningScheme::Eip1271 => {
+                        Some(order::Signature::Eip1271(order.signature.clone()))
+                    }
+                    SigningScheme::PreSign => Some(order::Signature::PreSign),
+                },
+                sell_token_source: match order.sell_token_source {
+                    SellTokenSource::Erc20 => order::SellTokenSource::Erc20,
+                    SellTokenSource::External => order::SellTokenSource::External,
+                    SellTokenSource::Internal => order::SellTokenSource::Internal,
+                },
+                pre_interactions: order
+                    .pre_interactions
+                    .iter()
+                    .map(|interaction| eth::Interaction {
+                        target: eth::Address(interaction.target),
+                        value: eth::Ether(interaction.value),
+                        calldata: interaction.call_data.clone(),
+                    })
+                    .collect(),
             })
             .collect(),
         liquidity: {
@@ -176,30 +222,17 @@ pub async fn into_domain(
                     "Auction has empty liquidity - fetching from liquidity-driver API"
                 );
 
-                // Use the auction deadline to estimate a reasonable block number
-                // This is approximate but better than 0
-                let estimated_block_number = match auction.deadline.timestamp() {
-                    ts if ts > 0 => {
-                        // Rough estimate: ~12 seconds per block on Ethereum
-                        let current_time = chrono::Utc::now().timestamp();
-                        let blocks_in_future = (ts - current_time).max(0) / 12;
-                        // Add current estimated block (rough estimate)
-                        18_000_000u64 + blocks_in_future as u64
-                    }
-                    _ => 18_000_000u64, // Fallback to reasonable mainnet block number
-                };
-
                 let request = LiquidityRequest {
                     auction_id: auction.id.unwrap_or(0) as u64,
                     tokens: auction.tokens.keys().copied().collect(),
                     token_pairs,
-                    block_number: estimated_block_number,
+                    block_number: BlockTag::Latest,
                     protocols: protocols.map(|p| p.to_vec()).unwrap_or_else(|| {
                         vec!["balancer_v2".to_string(), "uniswap_v2".to_string()]
                     }),
                 };
 
-                match client.fetch_liquidity(request).await {
+                match client.fetch_liquidity(request, deadline).await {
                     Ok(response) => {
                         tracing::info!(
                             auction_id = auction.id,
@@ -207,14 +240,14 @@ pub async fn into_domain(
                             "Successfully fetched liquidity from API"
                         );
 
-                        // Save liquidity to JSON if save_directory is provided
-                        if let Some(save_dir) = save_directory {
+                        // Save liquidity if artifact sinks are configured
+                        if let Some(artifacts) = artifacts {
                             let liquidity_json = serde_json::to_value(&response).ok();
-                            let save_dir = save_dir.to_path_buf();
+                            let artifacts = artifacts.clone();
                             let auction_id = auction.id;
                             tokio::spawn(async move {
                                 if let Some(liquidity) = liquidity_json {
-                                    save_liquidity_json(liquidity, auction_id, &save_dir).await;
+                                    save_liquidity_json(liquidity, auction_id, &artifacts).await;
                                 }
                             });
                         }
@@ -250,14 +283,19 @@ pub async fn into_domain(
             }
         },
         gas_price: auction::GasPrice(eth::Ether(auction.effective_gas_price)),
-        deadline: auction::Deadline(auction.deadline),
+        deadline: auction::Deadline(
+            deadline.map_or(auction.deadline, |deadline| deadline.min(auction.deadline)),
+        ),
+        gas_limit: auction.gas_limit.map(eth::Gas),
     };
 
     Ok((auction_domain, fetched_liquidity_response))
 }
 
 /// Helper function to convert DTO liquidity to domain liquidity
-fn convert_dto_liquidity_to_domain(liquidity: &Liquidity) -> Result<liquidity::Liquidity, Error> {
+pub fn convert_dto_liquidity_to_domain(
+    liquidity: &Liquidity,
+) -> Result<liquidity::Liquidity, Error> {
     match liquidity {
         Liquidity::ConstantProduct(liquidity) => constant_product_pool::to_domain(liquidity),
         Liquidity::WeightedProduct(liquidity) => weighted_product_pool::to_domain(liquidity),
@@ -265,6 +303,9 @@ fn convert_dto_liquidity_to_domain(liquidity: &Liquidity) -> Result<liquidity::L
         Liquidity::ConcentratedLiquidity(liquidity) => {
             concentrated_liquidity_pool::to_domain(liquidity)
         }
+        Liquidity::UniswapV4(liquidity) => uniswap_v4_pool::to_domain(liquidity),
+        Liquidity::MaverickV2(liquidity) => maverick_v2_pool::to_domain(liquidity),
+        Liquidity::Curve(liquidity) => curve_pool::to_domain(liquidity),
         Liquidity::GyroE(liquidity) => gyro_e_pool::to_domain(liquidity),
         Liquidity::Gyro2CLP(liquidity) => gyro_2clp_pool::to_domain(liquidity),
         Liquidity::Gyro3CLP(liquidity) => gyro_3clp_pool::to_domain(liquidity),
@@ -276,71 +317,32 @@ fn convert_dto_liquidity_to_domain(liquidity: &Liquidity) -> Result<liquidity::L
     }
 }
 
-/// Saves fetched liquidity data to a JSON file in the configured directory.
-/// This function runs in a background task and logs errors without failing the
+/// Saves fetched liquidity data to the configured artifact sinks. This
+/// function runs in a background task and logs errors without failing the
 /// request.
 async fn save_liquidity_json(
     liquidity: serde_json::Value,
     auction_id: Option<i64>,
-    save_dir: &std::path::Path,
+    artifacts: &artifacts::Artifacts,
 ) {
-    use tokio::fs;
-
-    // Determine filename based on auction ID
-    let base_filename = match auction_id {
-        Some(id) => id.to_string(),
-        None => {
-            // Use timestamp for quote auctions
-            let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S_%3f");
-            format!("quote_{}", timestamp)
-        }
-    };
-
-    let liquidity_file_path = save_dir.join(format!("{}_liquidity.json", base_filename));
-
-    // Create directory if it doesn't exist
-    if let Err(err) = fs::create_dir_all(save_dir).await {
-        tracing::warn!(
-            ?err,
-            directory = ?save_dir,
-            "Failed to create liquidity save directory"
-        );
-        return;
-    }
-
-    // Serialize liquidity to pretty JSON
-    let liquidity_json = match serde_json::to_string_pretty(&liquidity) {
-        Ok(content) => content,
-        Err(err) => {
-            tracing::warn!(?err, "Failed to serialize liquidity to JSON");
-            return;
-        }
-    };
-
     let liquidity_count = liquidity
         .get("liquidity")
         .and_then(|l| l.as_array())
         .map(|a| a.len())
         .unwrap_or(0);
 
-    // Write liquidity file
-    match fs::write(&liquidity_file_path, liquidity_json).await {
-        Ok(_) => {
-            tracing::info!(
-                liquidity_file = ?liquidity_file_path,
-                auction_id = ?auction_id,
-                liquidity_count,
-                "💾 Saved fetched liquidity to JSON file"
-            );
-        }
-        Err(err) => {
-            tracing::warn!(
-                ?err,
-                file_path = ?liquidity_file_path,
-                "Failed to write liquidity JSON file"
-            );
-        }
-    }
+    artifacts
+        .save(artifacts::Artifact {
+            auction: &artifacts::auction_name(auction_id),
+            kind: artifacts::Kind::Liquidity,
+            content: &liquidity,
+        })
+        .await;
+    tracing::info!(
+        auction_id = ?auction_id,
+        liquidity_count,
+        "💾 Saved fetched liquidity"
+    );
 }
 
 mod erc4626 {
@@ -349,6 +351,7 @@ mod erc4626 {
         Ok(liquidity::Liquidity {
             id: liquidity::Id(edge.id.clone()),
             address: edge.vault,
+            balancer_pool_id: None,
             gas: eth::Gas(edge.gas_estimate),
             state: liquidity::State::Erc4626(liquidity::erc4626::Edge {
                 asset: eth::TokenAddress(edge.asset),
@@ -379,6 +382,7 @@ mod constant_product_pool {
         Ok(liquidity::Liquidity {
             id: liquidity::Id(pool.id.clone()),
             address: pool.address,
+            balancer_pool_id: None,
             gas: eth::Gas(pool.gas_estimate),
             state: liquidity::State::ConstantProduct(liquidity::constant_product::Pool {
                 reserves,
@@ -417,6 +421,7 @@ mod weighted_product_pool {
         Ok(liquidity::Liquidity {
             id: liquidity::Id(pool.id.clone()),
             address: pool.address,
+            balancer_pool_id: pool.balancer_pool_id,
             gas: eth::Gas(pool.gas_estimate),
             state: liquidity::State::WeightedProduct(liquidity::weighted_product::Pool {
                 reserves,
@@ -456,12 +461,14 @@ mod stable_pool {
         Ok(liquidity::Liquidity {
             id: liquidity::Id(pool.id.clone()),
             address: pool.address,
+            balancer_pool_id: pool.balancer_pool_id,
             gas: eth::Gas(pool.gas_estimate),
             state: liquidity::State::Stable(liquidity::stable::Pool {
                 reserves,
                 amplification_parameter: conv::decimal_to_rational(&pool.amplification_parameter)
                     .ok_or("invalid amplification parameter")?,
                 fee: conv::decimal_to_rational(&pool.fee).ok_or("invalid stable pool fee")?,
+                actual_supply: pool.actual_supply,
             }),
         })
     }
@@ -493,6 +500,7 @@ mod concentrated_liquidity_pool {
         Ok(liquidity::Liquidity {
             id: liquidity::Id(pool.id.clone()),
             address: pool.address,
+            balancer_pool_id: None,
             gas: eth::Gas(pool.gas_estimate),
             state: liquidity::State::Concentrated(liquidity::concentrated::Pool {
                 tokens,
@@ -506,6 +514,113 @@ mod concentrated_liquidity_pool {
     }
 }
 
+mod uniswap_v4_pool {
+    use super::*;
+
+    pub fn to_domain(pool: &UniswapV4Pool) -> Result<liquidity::Liquidity, Error> {
+        let (a, b) = pool
+            .tokens
+            .iter()
+            .copied()
+            .map(eth::TokenAddress)
+            .collect_tuple()
+            .ok_or("invalid number of Uniswap V4 pool tokens")?;
+        let tokens =
+            liquidity::TokenPair::new(a, b).ok_or("duplicate Uniswap V4 pool token address")?;
+
+        Ok(liquidity::Liquidity {
+            id: liquidity::Id(pool.id.clone()),
+            address: pool.address,
+            balancer_pool_id: None,
+            gas: eth::Gas(pool.gas_estimate),
+            state: liquidity::State::UniswapV4(liquidity::uniswap_v4::Pool {
+                tokens,
+                fee: pool.fee,
+                tick_spacing: pool.tick_spacing,
+                hooks: pool.hooks,
+            }),
+        })
+    }
+}
+
+mod maverick_v2_pool {
+    use super::*;
+
+    pub fn to_domain(pool: &MaverickV2Pool) -> Result<liquidity::Liquidity, Error> {
+        let (a, b) = pool
+            .tokens
+            .iter()
+            .copied()
+            .map(eth::TokenAddress)
+            .collect_tuple()
+            .ok_or("invalid number of Maverick V2 pool tokens")?;
+        let tokens =
+            liquidity::TokenPair::new(a, b).ok_or("duplicate Maverick V2 pool token address")?;
+        // The swap direction depends on which token is token A.
+        if tokens.get().0 != a {
+            return Err("unsorted Maverick V2 pool tokens".into());
+        }
+
+        Ok(liquidity::Liquidity {
+            id: liquidity::Id(pool.id.clone()),
+            address: pool.address,
+            balancer_pool_id: None,
+            gas: eth::Gas(pool.gas_estimate),
+            state: liquidity::State::MaverickV2(liquidity::maverick_v2::Pool {
+                tokens,
+                tick_spacing: pool.tick_spacing,
+                active_tick: pool.active_tick,
+                ticks: pool
+                    .ticks
+                    .iter()
+                    .map(|(tick, reserves)| {
+                        (
+                            *tick,
+                            liquidity::maverick_v2::TickReserves {
+                                a: reserves.reserve_a,
+                                b: reserves.reserve_b,
+                            },
+                        )
+                    })
+                    .collect(),
+                fee_a_in: conv::decimal_to_rational(&pool.fee_a_in)
+                    .ok_or("invalid Maverick V2 fee")?,
+                fee_b_in: conv::decimal_to_rational(&pool.fee_b_in)
+                    .ok_or("invalid Maverick V2 fee")?,
+            }),
+        })
+    }
+}
+
+mod curve_pool {
+    use super::*;
+
+    pub fn to_domain(pool: &CurvePool) -> Result<liquidity::Liquidity, Error> {
+        let coins = pool
+            .tokens
+            .iter()
+            .map(|coin| liquidity::curve::Coin {
+                asset: eth::Asset {
+                    token: eth::TokenAddress(coin.address),
+                    amount: coin.balance,
+                },
+                rate: coin.rate,
+            })
+            .collect();
+
+        Ok(liquidity::Liquidity {
+            id: liquidity::Id(pool.id.clone()),
+            address: pool.address,
+            balancer_pool_id: None,
+            gas: eth::Gas(pool.gas_estimate),
+            state: liquidity::State::Curve(
+                liquidity::curve::Pool::new(coins, pool.amplification, pool.fee, pool.base_pool)
+                    .ok_or("duplicate Curve pool token address")?,
+            ),
+        })
+    }
+}
+
 mod foreign_limit_order {
     use super::*;
 
@@ -513,6 +628,7 @@ mod foreign_limit_order {
         liquidity::Liquidity {
             id: liquidity::Id(order.id.clone()),
             address: order.address,
+            balancer_pool_id: None,
             gas: eth::Gas(order.gas_estimate),
             state: liquidity::State::LimitOrder(liquidity::limit_order::LimitOrder {
                 maker: eth::Asset {
@@ -556,6 +672,7 @@ mod gyro_e_pool {
         Ok(liquidity::Liquidity {
             id: liquidity::Id(pool.id.clone()),
             address: pool.address,
+            balancer_pool_id: pool.balancer_pool_id,
             gas: eth::Gas(pool.gas_estimate),
             state: liquidity::State::GyroE(Box::new(liquidity::gyro_e::Pool {
                 reserves,
@@ -621,6 +738,7 @@ mod gyro_2clp_pool {
         Ok(liquidity::Liquidity {
             id: liquidity::Id(pool.id.clone()),
             address: pool.address,
+            balancer_pool_id: pool.balancer_pool_id,
             gas: eth::Gas(pool.gas_estimate),
             state: liquidity::State::Gyro2CLP(liquidity::gyro_2clp::Pool {
                 reserves,
@@ -664,6 +782,7 @@ mod gyro_3clp_pool {
         Ok(liquidity::Liquidity {
             id: liquidity::Id(pool.id.clone()),
             address: pool.address,
+            balancer_pool_id: pool.balancer_pool_id,
             gas: eth::Gas(pool.gas_estimate),
             state: liquidity::State::Gyro3CLP(liquidity::gyro_3clp::Pool {
                 reserves: liquidity::gyro_3clp::Reserves::new(reserves)
@@ -706,6 +825,7 @@ mod reclamm_pool {
         Ok(liquidity::Liquidity {
             id: liquidity::Id(pool.id.clone()),
             address: pool.address,
+            balancer_pool_id: None,
             gas: eth::Gas(pool.gas_estimate),
             state: liquidity::State::BalancerV3ReClamm(liquidity::reclamm::Pool {
                 reserves,
@@ -730,6 +850,7 @@ mod reclamm_pool {
                 .ok_or("invalid end_fourth_root_price_ratio")?,
                 price_ratio_update_start_time: pool.price_ratio_update_start_time,
                 price_ratio_update_end_time: pool.price_ratio_update_end_time,
+                current_timestamp: pool.current_timestamp,
             }),
         })
     }
@@ -770,12 +891,14 @@ mod stable_surge_pool {
         Ok(liquidity::Liquidity {
             id: liquidity::Id(pool.id.clone()),
             address: pool.address,
+            balancer_pool_id: pool.balancer_pool_id,
             gas: eth::Gas(pool.gas_estimate),
             state: liquidity::State::Stable(liquidity::stable::Pool {
                 reserves,
                 amplification_parameter: conv::decimal_to_rational(&pool.amplification_parameter)
                     .ok_or("invalid amplification parameter")?,
                 fee: conv::decimal_to_rational(&pool.fee).ok_or("invalid stable surge pool fee")?,
+                actual_supply: None,
             }),
         })
     }
@@ -808,6 +931,7 @@ mod quant_amm_pool {
         Ok(liquidity::Liquidity {
             id: liquidity::Id(pool.id.clone()),
             address: pool.address,
+            balancer_pool_id: pool.balancer_pool_id,
             gas: eth::Gas(pool.gas_estimate),
             state: liquidity::State::QuantAmm(liquidity::quantamm::Pool {
                 reserves,
@@ -817,6 +941,14 @@ mod quant_amm_pool {
                 },
                 max_trade_size_ratio: conv::decimal_to_rational(&pool.max_trade_size_ratio)
                     .ok_or("invalid max_trade_size_ratio")?,
+                strategy: pool
+                    .strategy
+                    .as_ref()
+                    .map(|strategy| liquidity::quantamm::Strategy {
+                        rule: strategy.rule.clone(),
+                        oracles: strategy.oracles.clone(),
+                        update_interval: strategy.update_interval,
+                    }),
                 first_four_weights_and_multipliers: pool
                     .first_four_weights_and_multipliers
                     .iter()
@@ -839,7 +971,12 @@ mod quant_amm_pool {
     }
 }
 
-/// Creates an enhanced solutions JSON with full liquidity details embedded
+/// Creates an enhanced solutions JSON with full liquidity details embedded.
+///
+/// Liquidity interactions also get the gas estimate of their liquidity
+/// attached as `estimatedGas`, and each solution the sum of these estimates as
+/// `interactionsGas`, so that the gas of a solution can be attributed to the
+/// pools it routes through.
 pub fn create_enhanced_solutions(
     solutions: &solvers_dto::solution::Solutions,
     liquidity_response: &crate::infra::liquidity_client::LiquidityResponse,
@@ -859,6 +996,7 @@ pub fn create_enhanced_solutions(
     // Enhance each solution's interactions
     if let Some(solutions_array) = solutions_json["solutions"].as_array_mut() {
         for solution in solutions_array {
+            let mut interactions_gas = eth::U256::zero();
             if let Some(interactions) = solution["interactions"].as_array_mut() {
                 for interaction in interactions {
                     if interaction["kind"] == "liquidity" {
@@ -867,11 +1005,16 @@ pub fn create_enhanced_solutions(
                                 // Embed full liquidity details
                                 interaction["liquidityDetails"] =
                                     serde_json::to_value(liquidity_details).unwrap();
+
+                                let gas = extract_liquidity_gas(liquidity_details);
+                                interaction["estimatedGas"] = gas.to_string().into();
+                                interactions_gas = interactions_gas.saturating_add(gas);
                             }
                         }
                     }
                 }
             }
+            solution["interactionsGas"] = interactions_gas.to_string().into();
         }
     }
 
@@ -885,6 +1028,9 @@ fn extract_liquidity_id(liq: &solvers_dto::auction::Liquidity) -> String {
         solvers_dto::auction::Liquidity::WeightedProduct(p) => p.id.clone(),
         solvers_dto::auction::Liquidity::Stable(p) => p.id.clone(),
         solvers_dto::auction::Liquidity::ConcentratedLiquidity(p) => p.id.clone(),
+        solvers_dto::auction::Liquidity::UniswapV4(p) => p.id.clone(),
+        solvers_dto::auction::Liquidity::MaverickV2(p) => p.id.clone(),
+        solvers_dto::auction::Liquidity::Curve(p) => p.id.clone(),
         solvers_dto::auction::Liquidity::GyroE(p) => p.id.clone(),
         solvers_dto::auction::Liquidity::Gyro2CLP(p) => p.id.clone(),
         solvers_dto::auction::Liquidity::Gyro3CLP(p) => p.id.clone(),
@@ -895,3 +1041,355 @@ fn extract_liquidity_id(liq: &solvers_dto::auction::Liquidity) -> String {
         solvers_dto::auction::Liquidity::StableSurge(p) => p.id.clone(),
     }
 }
+
+fn extract_liquidity_gas(liq: &solvers_dto::auction::Liquidity) -> eth::U256 {
+    // The gas estimate the liquidity source reports for swapping through it
+    match liq {
+        solvers_dto::auction::Liquidity::ConstantProduct(p) => p.gas_estimate,
+        solvers_dto::auction::Liquidity::WeightedProduct(p) => p.gas_estimate,
+        solvers_dto::auction::Liquidity::Stable(p) => p.gas_estimate,
+        solvers_dto::auction::Liquidity::ConcentratedLiquidity(p) => p.gas_estimate,
+        solvers_dto::auction::Liquidity::UniswapV4(p) => p.gas_estimate,
+        solvers_dto::auction::Liquidity::MaverickV2(p) => p.gas_estimate,
+        solvers_dto::auction::Liquidity::Curve(p) => p.gas_estimate,
+        solvers_dto::auction::Liquidity::GyroE(p) => p.gas_estimate,
+        solvers_dto::auction::Liquidity::Gyro2CLP(p) => p.gas_estimate,
+        solvers_dto::auction::Liquidity::Gyro3CLP(p) => p.gas_estimate,
+        solvers_dto::auction::Liquidity::LimitOrder(p) => p.gas_estimate,
+        solvers_dto::auction::Liquidity::Erc4626(p) => p.gas_estimate,
+        solvers_dto::auction::Liquidity::ReClamm(p) => p.gas_estimate,
+        solvers_dto::auction::Liquidity::QuantAmm(p) => p.gas_estimate,
+        solvers_dto::auction::Liquidity::StableSurge(p) => p.gas_estimate,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        proptest::prelude::*,
+        serde_json::{Value, json},
+    };
+
+    /// An auction covering most of the DTO, which the fuzz tests mutate.
+    fn auction() -> Value {
+        let weth = "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2";
+        let cow = "0xdef1ca1fb7fbcdc777520aa7f396b4e015f497ab";
+        json!({
+            "id": "1",
+            "tokens": {
+                weth: {
+                    "decimals": 18,
+                    "symbol": "WETH",
+                    "referencePrice": "1000000000000000000",
+                    "availableBalance": "1412206645170290748",
+                    "trusted": true
+                },
+                cow: {
+                    "decimals": null,
+                    "symbol": null,
+                    "referencePrice": null,
+                    "availableBalance": "0",
+                    "trusted": false
+                }
+            },
+            "orders": [{
+                "uid": format!("0x{}", "2a".repeat(56)),
+                "sellToken": weth,
+                "buyToken": cow,
+                "sellAmount": "133700000000000000",
+                "fullSellAmount": "133700000000000000",
+                "buyAmount": "6000000000000000000000",
+                "fullBuyAmount": "6000000000000000000000",
+                "feePolicies": [
+                    { "surplus": { "factor": 0.5, "maxVolumeFactor": 0.01 } },
+                    {
+                        "priceImprovement": {
+                            "factor": 0.5,
+                            "maxVolumeFactor": 0.01,
+                            "quote": {
+                                "sellAmount": "133700000000000000",
+                                "buyAmount": "6000000000000000000000",
+                                "fee": "1000"
+                            }
+                        }
+                    },
+                    { "volume": { "factor": 0.001 } }
+                ],
+                "validTo": 0,
+                "kind": "sell",
+                "owner": "0x5b1e2c2762667331bc91648052f646d1b0d35984",
+                "partiallyFillable": false,
+                "preInteractions": [{
+                    "target": weth,
+                    "value": "0",
+                    "callData": "0x01020304"
+                }],
+                "postInteractions": [],
+                "sellTokenSource": "erc20",
+                "buyTokenDestination": "erc20",
+                "class": "limit",
+                "appData": format!("0x{}", "00".repeat(32)),
+                "signingScheme": "eip712",
+                "signature": format!("0x{}", "11".repeat(65))
+            }],
+            "liquidity": [
+                {
+                    "kind": "constantProduct",
+                    "id": "0",
+                    "address": "0x97b744df0b59d93a866304f97431d8efad29a08d",
+                    "router": "0x7a250d5630b4cf539739df2c5dacb4c659f2488d",
+                    "gasEstimate": "110000",
+                    "tokens": {
+                        weth: { "balance": "3828187314911751990" },
+                        cow: { "balance": "179617892578796375604692" }
+                    },
+                    "fee": "0.003"
+                },
+                {
+                    "kind": "weightedProduct",
+                    "id": "1",
+                    "address": "0x92762b42a06dcdddc5b7362cfb01e631c4d44b40",
+                    "balancerPoolId": format!("0x{}", "5c".repeat(32)),
+                    "gasEstimate": "88892",
+                    "tokens": {
+                        weth: {
+                            "balance": "11260752191375725565253",
+                            "scalingFactor": "1",
+                            "weight": "0.5",
+                            "rate": "1"
+                        },
+                        cow: {
+                            "balance": "18764168403990393422000071",
+                            "scalingFactor": "1",
+                            "weight": "0.5",
+                            "rate": "1"
+                        }
+                    },
+                    "fee": "0.005",
+                    "version": "v3Plus"
+                },
+                {
+                    "kind": "stable",
+                    "id": "2",
+                    "address": "0x06df3b2bbb68adc8b0e302443692037ed9f91b42",
+                    "gasEstimate": "183520",
+                    "tokens": {
+                        weth: {
+                            "balance": "505781036390938593206504",
+                            "scalingFactor": "1",
+                            "rate": "1.0"
+                        },
+                        cow: {
+                            "balance": "554894862074",
+                            "scalingFactor": "1000000000000",
+                            "rate": "1.0"
+                        }
+                    },
+                    "amplificationParameter": "5000.0",
+                    "fee": "0.0001",
+                    "actualSupply": "1000000000000000000000"
+                },
+                {
+                    "kind": "concentratedLiquidity",
+                    "id": "3",
+                    "address": "0x88e6a0c2ddd26feeb64f039a2c41296fcb3f5640",
+                    "router": "0xe592427a0aece92de3edee1f18e0157c05861564",
+                    "gasEstimate": "110000",
+                    "tokens": [weth, cow],
+                    "sqrtPrice": "79228162514264337593543950336",
+                    "liquidity": "1000000000000",
+                    "tick": 0,
+                    "liquidityNet": { "-60": "1000000000000", "60": "-1000000000000" },
+                    "fee": "0.003"
+                },
+                {
+                    "kind": "limitOrder",
+                    "id": "4",
+                    "address": "0xdef1c0ded9bec7f1a1670819833240f027b25eff",
+                    "gasEstimate": "66358",
+                    "hash": format!("0x{}", "ff".repeat(32)),
+                    "makerToken": weth,
+                    "takerToken": cow,
+                    "makerAmount": "1000000000000000000",
+                    "takerAmount": "6000000000000000000000",
+                    "takerTokenFeeAmount": "0"
+                },
+                {
+                    "kind": "erc4626",
+                    "id": "5",
+                    "gasEstimate": "90000",
+                    "vault": "0x83f20f44975d03b1b09e64809b757c47f942beea",
+                    "asset": cow
+                },
+                {
+                    "kind": "curve",
+                    "id": "6",
+                    "address": "0xbebc44782c7db0a1a60cb6fe97d0b483032ff1c7",
+                    "gasEstimate": "130000",
+                    "tokens": [
+                        {
+                            "address": weth,
+                            "balance": "505781036390938593206504",
+                            "rate": "1000000000000000000"
+                        },
+                        {
+                            "address": cow,
+                            "balance": "554894862074",
+                            "rate": "1000000000000000000000000000000"
+                        }
+                    ],
+                    "amplification": "200000",
+                    "fee": "1000000"
+                }
+            ],
+            "effectiveGasPrice": "15000000000",
+            "deadline": "2106-01-01T00:00:00.000Z",
+            "surplusCapturingJitOrderOwners": [],
+            "gasLimit": "30000000"
+        })
+    }
+
+    fn solve(body: &[u8]) -> Result<auction::Auction, Error> {
+        let auction = from_json(body)?;
+        let (auction, _) =
+            futures::executor::block_on(into_domain(auction, None, None, None, None, None))?;
+        Ok(auction)
+    }
+
+    #[test]
+    fn converts_reference_auction() {
+        let auction = solve(auction().to_string().as_bytes()).unwrap();
+        assert_eq!(auction.orders.len(), 1);
+        assert_eq!(auction.liquidity.len(), 7);
+    }
+
+    #[test]
+    fn rejects_truncated_auctions() {
+        let body = auction().to_string();
+        for len in 0..body.len() {
+            assert!(from_json(&body.as_bytes()[..len]).is_err());
+        }
+    }
+
+    #[test]
+    fn unknown_fields_are_only_rejected_in_strict_mode() {
+        let mut auction = auction();
+        auction["orders"][0]["unknownField"] = json!(true);
+        assert_eq!(
+            from_json(auction.to_string().as_bytes()).is_err(),
+            cfg!(feature = "strict-dto")
+        );
+    }
+
+    /// A structural mutation of a value of a JSON document.
+    #[derive(Clone, Debug)]
+    enum Mutation {
+        Replace(Value),
+        /// Removes the value from its object or array.
+        Remove,
+        /// Adds a field, which usually isn't part of the DTOs, to an object.
+        Insert(String, Value),
+        /// Repeats the elements of an array.
+        Duplicate,
+    }
+
+    /// The number of values of the document.
+    fn size(value: &Value) -> usize {
+        1 + match value {
+            Value::Object(object) => object.values().map(size).sum(),
+            Value::Array(array) => array.iter().map(size).sum(),
+            _ => 0,
+        }
+    }
+
+    /// Applies the mutation to the value with the index `target` in a
+    /// depth-first traversal of the document. Returns whether the mutation got
+    /// applied.
+    fn apply(value: &mut Value, target: &mut usize, mutation: &Mutation) -> bool {
+        if *target == 0 {
+            match (mutation, &mut *value) {
+                (Mutation::Remove, _) => return false,
+                (Mutation::Replace(replacement), value) => *value = replacement.clone(),
+                (Mutation::Insert(key, field), Value::Object(object)) => {
+                    object.insert(key.clone(), field.clone());
+                }
+                (Mutation::Duplicate, Value::Array(array)) => array.extend(array.clone()),
+                _ => return false,
+            }
+            return true;
+        }
+
+        *target -= 1;
+        let remove = matches!(mutation, Mutation::Remove);
+        match value {
+            Value::Object(object) => {
+                let keys = object.keys().cloned().collect::<Vec<_>>();
+                for key in keys {
+                    if remove && *target == 0 {
+                        object.remove(&key);
+                        return true;
+                    }
+                    if apply(object.get_mut(&key).unwrap(), target, mutation) {
+                        return true;
+                    }
+                }
+            }
+            Value::Array(array) => {
+                for i in 0..array.len() {
+                    if remove && *target == 0 {
+                        array.remove(i);
+                        return true;
+                    }
+                    if apply(&mut array[i], target, mutation) {
+                        return true;
+                    }
+                }
+            }
+            _ => {}
+        }
+        false
+    }
+
+    fn leaf() -> impl Strategy<Value = Value> {
+        prop_oneof![
+            Just(Value::Null),
+            any::<bool>().prop_map(Value::from),
+            any::<i64>().prop_map(Value::from),
+            any::<f64>().prop_map(Value::from),
+            // Amounts, including ones that overflow 256 bits.
+            "[0-9]{1,80}".prop_map(Value::from),
+            // Addresses, hashes and bytes of any length.
+            "0x[0-9a-f]{0,140}".prop_map(Value::from),
+            // Decimals, including negative ones and ones with exponents.
+            "-?[0-9]{1,4}(\\.[0-9]{1,4})?(e-?[0-9]{1,4})?".prop_map(Value::from),
+            any::<String>().prop_map(Value::from),
+            Just(json!([])),
+            Just(json!({})),
+        ]
+    }
+
+    fn mutation() -> impl Strategy<Value = (prop::sample::Index, Mutation)> {
+        let mutation = prop_oneof![
+            leaf().prop_map(Mutation::Replace),
+            Just(Mutation::Remove),
+            ("[a-zA-Z]{1,12}", leaf()).prop_map(|(key, value)| Mutation::Insert(key, value)),
+            Just(Mutation::Duplicate),
+        ];
+        (any::<prop::sample::Index>(), mutation)
+    }
+
+    proptest! {
+        /// Mutations of a valid auction, which keep the structure of the
+        /// auction mostly intact, get converted or rejected without panicking.
+        #[test]
+        fn handles_mutated_auctions(mutations in prop::collection::vec(mutation(), 1..8)) {
+            let mut auction = auction();
+            for (target, mutation) in mutations {
+                let mut target = target.index(size(&auction));
+                apply(&mut auction, &mut target, &mutation);
+            }
+            let _ = solve(auction.to_string().as_bytes());
+        }
+    }
+}
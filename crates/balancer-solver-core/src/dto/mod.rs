@@ -0,0 +1,17 @@
+use serde::Serialize;
+
+pub mod auction;
+pub mod solution;
+
+pub use solvers_dto::{auction::Auction, solution::Solutions};
+
+#[derive(Debug, Serialize)]
+pub struct Error {
+    pub message: &'static str,
+}
+
+impl From<&'static str> for Error {
+    fn from(message: &'static str) -> Self {
+        Self { message }
+    }
+}
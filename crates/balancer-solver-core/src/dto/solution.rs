@@ -0,0 +1,435 @@
+use {
+    crate::domain::{diagnostics, eth, liquidity, order, solution, validation},
+    solvers_dto::solution::*,
+    std::collections::HashMap,
+};
+
+/// Creates a new solution DTO from its domain object.
+pub fn from_domain(
+    solutions: &[solution::Solution],
+    skipped: &[validation::Skipped],
+    unsolved: Option<&[diagnostics::Unsolved]>,
+    deadline_exceeded: bool,
+) -> super::Solutions {
+    super::Solutions {
+        deadline_exceeded,
+        diagnostics: unsolved.map(|unsolved| Diagnostics {
+            orders: unsolved
+                .iter()
+                .map(|unsolved| UnsolvedOrder {
+                    order: OrderUid(unsolved.order.0),
+                    reason: match unsolved.reason {
+                        diagnostics::Reason::NoLiquidity => UnsolvedReason::NoLiquidity,
+                        diagnostics::Reason::LimitPriceUnreachable => {
+                            UnsolvedReason::LimitPriceUnreachable
+                        }
+                        diagnostics::Reason::DeadlineExhausted => UnsolvedReason::DeadlineExhausted,
+                        diagnostics::Reason::AllPoolsDenied => UnsolvedReason::AllPoolsDenied,
+                    },
+                })
+                .collect(),
+        }),
+        skipped_orders: skipped
+            .iter()
+            .map(|skipped| SkippedOrder {
+                order: OrderUid(skipped.order.0),
+                reason: match skipped.reason {
+                    validation::Reason::Expired => SkipReason::Expired,
+                    validation::Reason::InvalidSignature => SkipReason::InvalidSignature,
+                    validation::Reason::InsufficientBalance => SkipReason::InsufficientBalance,
+                },
+            })
+            .collect(),
+        solutions: solutions
+            .iter()
+            .map(|solution| Solution {
+                id: solution.id.0,
+                prices: solution
+                    .prices
+                    .0
+                    .iter()
+                    .map(|(token, price)| (token.0, *price))
+                    .collect(),
+                trades: solution
+                    .trades
+                    .iter()
+                    .map(|trade| match trade {
+                        solution::Trade::Fulfillment(trade) => Trade::Fulfillment(Fulfillment {
+                            order: OrderUid(trade.order().uid.0),
+                            executed_amount: trade.executed().amount,
+                            fee: trade.surplus_fee().map(|fee| fee.amount),
+                        }),
+                        solution::Trade::Jit(trade) => {
+                            let (signing_scheme, signature) = match &trade.order.signature {
+                                order::Signature::Eip712(signature) => {
+                                    (SigningScheme::Eip712, signature.to_bytes().to_vec())
+                                }
+                                order::Signature::EthSign(signature) => {
+                                    (SigningScheme::EthSign, signature.to_bytes().to_vec())
+                                }
+                                order::Signature::Eip1271(bytes) => {
+                                    (SigningScheme::Eip1271, bytes.clone())
+                                }
+                                order::Signature::PreSign => (SigningScheme::PreSign, vec![]),
+                            };
+
+                            Trade::Jit(JitTrade {
+                                order: JitOrder {
+                                    sell_token: trade.order.sell.token.0,
+                                    sell_amount: trade.order.sell.amount,
+                                    buy_token: trade.order.buy.token.0,
+                                    buy_amount: trade.order.buy.amount,
+                                    partially_fillable: trade.order.partially_fillable,
+                                    receiver: trade.order.receiver,
+                                    valid_to: trade.order.valid_to,
+                                    app_data: trade.order.app_data.0,
+                                    kind: match trade.order.side {
+                                        crate::domain::order::Side::Buy => Kind::Buy,
+                                        crate::domain::order::Side::Sell => Kind::Sell,
+                                    },
+                                    sell_token_balance: SellTokenBalance::Erc20,
+                                    buy_token_balance: BuyTokenBalance::Erc20,
+                                    signing_scheme,
+                                    signature,
+                                },
+                                executed_amount: trade.executed,
+                                fee: Some(trade.fee.0),
+                            })
+                        }
+                    })
+                    .collect(),
+                pre_interactions: interaction_data_from_domain(&solution.pre_interactions),
+                post_interactions: interaction_data_from_domain(&solution.post_interactions),
+                interactions: solution
+                    .interactions
+                    .iter()
+                    .map(|interaction| match interaction {
+                        solution::Interaction::Liquidity(interaction) => {
+                            Interaction::Liquidity(LiquidityInteraction {
+                                id: interaction.liquidity.id.0.clone(),
+                                input_token: interaction.input.token.0,
+                                input_amount: interaction.input.amount,
+                                output_token: interaction.output.token.0,
+                                output_amount: interaction.output.amount,
+                                internalize: interaction.internalize,
+                            })
+                        }
+                        solution::Interaction::Custom(interaction) => {
+                            Interaction::Custom(CustomInteraction {
+                                target: interaction.target,
+                                value: interaction.value.0,
+                                calldata: interaction.calldata.clone(),
+                                internalize: interaction.internalize,
+                                allowances: interaction
+                                    .allowances
+                                    .iter()
+                                    .map(|allowance| Allowance {
+                                        token: allowance.asset.token.0,
+                                        amount: allowance.asset.amount,
+                                        spender: allowance.spender,
+                                    })
+                                    .collect(),
+                                inputs: interaction
+                                    .inputs
+                                    .iter()
+                                    .map(|i| Asset {
+                                        token: i.token.0,
+                                        amount: i.amount,
+                                    })
+                                    .collect(),
+                                outputs: interaction
+                                    .outputs
+                                    .iter()
+                                    .map(|o| Asset {
+                                        token: o.token.0,
+                                        amount: o.amount,
+                                    })
+                                    .collect(),
+                            })
+                        }
+                    })
+                    .collect(),
+                gas: solution.gas.map(|gas| gas.0.as_u64()),
+                flashloans: flashloans_from_domain(solution),
+                wrappers: solution
+                    .wrappers
+                    .iter()
+                    .map(|w| solvers_dto::solution::WrapperCall {
+                        address: w.target.0,
+                        data: w.data.clone(),
+                    })
+                    .collect(),
+            })
+            .collect(),
+    }
+}
+
+fn interaction_data_from_domain(interaction_data: &[eth::Interaction]) -> Vec<Call> {
+    interaction_data
+        .iter()
+        .map(|interaction| Call {
+            target: interaction.target.0,
+            value: interaction.value.0,
+            calldata: interaction.calldata.clone(),
+        })
+        .collect()
+}
+
+/// Converts a solution DTO of an external strategy into its domain object,
+/// resolving the traded orders and liquidity interactions against the
+/// auction. Returns `None` if the solution references unknown orders or
+/// liquidity, or contains JIT trades, which are not supported.
+pub fn into_domain(
+    solution: Solution,
+    orders: &[order::Order],
+    liquidity: &[liquidity::Liquidity],
+) -> Option<solution::Solution> {
+    Some(solution::Solution {
+        id: solution::Id(solution.id),
+        prices: solution::ClearingPrices::new(
+            solution
+                .prices
+                .into_iter()
+                .map(|(token, price)| (eth::TokenAddress(token), price)),
+        ),
+        trades: solution
+            .trades
+            .into_iter()
+            .map(|trade| match trade {
+                Trade::Fulfillment(trade) => {
+                    let order = orders.iter().find(|order| order.uid.0 == trade.order.0)?;
+                    let fee = match trade.fee {
+                        Some(fee) => solution::Fee::Surplus(eth::SellTokenAmount(fee)),
+                        None => solution::Fee::Protocol,
+                    };
+                    solution::Fulfillment::new(order.clone(), trade.executed_amount, fee)
+                        .map(solution::Trade::Fulfillment)
+                }
+                Trade::Jit(_) => None,
+            })
+            .collect::<Option<_>>()?,
+        pre_interactions: interaction_data_into_domain(solution.pre_interactions),
+        interactions: solution
+            .interactions
+            .into_iter()
+            .map(|interaction| match interaction {
+                Interaction::Liquidity(interaction) => {
+                    let liquidity = liquidity
+                        .iter()
+                        .find(|liquidity| liquidity.id.0 == interaction.id)?;
+                    Some(solution::Interaction::Liquidity(Box::new(
+                        solution::LiquidityInteraction {
+                            liquidity: liquidity.clone(),
+                            input: eth::Asset {
+                                token: eth::TokenAddress(interaction.input_token),
+                                amount: interaction.input_amount,
+                            },
+                            output: eth::Asset {
+                                token: eth::TokenAddress(interaction.output_token),
+                                amount: interaction.output_amount,
+                            },
+                            internalize: interaction.internalize,
+                        },
+                    )))
+                }
+                Interaction::Custom(interaction) => {
+                    Some(solution::Interaction::Custom(solution::CustomInteraction {
+                        target: interaction.target,
+                        value: eth::Ether(interaction.value),
+                        calldata: interaction.calldata,
+                        internalize: interaction.internalize,
+                        inputs: interaction
+                            .inputs
+                            .into_iter()
+                            .map(asset_into_domain)
+                            .collect(),
+                        outputs: interaction
+                            .outputs
+                            .into_iter()
+                            .map(asset_into_domain)
+                            .collect(),
+                        allowances: interaction
+                            .allowances
+                            .into_iter()
+                            .map(|allowance| solution::Allowance {
+                                spender: allowance.spender,
+                                asset: eth::Asset {
+                                    token: eth::TokenAddress(allowance.token),
+                                    amount: allowance.amount,
+                                },
+                            })
+                            .collect(),
+                    }))
+                }
+            })
+            .collect::<Option<_>>()?,
+        post_interactions: interaction_data_into_domain(solution.post_interactions),
+        gas: solution.gas.map(|gas| eth::Gas(gas.into())),
+        wrappers: solution
+            .wrappers
+            .into_iter()
+            .map(|wrapper| solution::WrapperCall {
+                target: eth::Address(wrapper.address),
+                data: wrapper.data,
+            })
+            .collect(),
+    })
+}
+
+fn interaction_data_into_domain(interaction_data: Vec<Call>) -> Vec<eth::Interaction> {
+    interaction_data
+        .into_iter()
+        .map(|interaction| eth::Interaction {
+            target: eth::Address(interaction.target),
+            value: eth::Ether(interaction.value),
+            calldata: interaction.calldata,
+        })
+        .collect()
+}
+
+/// Returns the flashloans of the hinted lenders for the traded orders that
+/// need them, or `None` if no order needs one, in which case the driver falls
+/// back to the hints of the auction.
+fn flashloans_from_domain(solution: &solution::Solution) -> Option<HashMap<OrderUid, Flashloan>> {
+    let flashloans = solution
+        .flashloans()
+        .map(|(uid, hint)| {
+            let flashloan = Flashloan {
+                liquidity_provider: hint.liquidity_provider.0,
+                protocol_adapter: hint.protocol_adapter.0,
+                receiver: hint.receiver.0,
+                token: hint.token.0,
+                amount: hint.amount,
+            };
+            (OrderUid(uid.0), flashloan)
+        })
+        .collect::<HashMap<_, _>>();
+    (!flashloans.is_empty()).then_some(flashloans)
+}
+
+fn asset_into_domain(asset: Asset) -> eth::Asset {
+    eth::Asset {
+        token: eth::TokenAddress(asset.token),
+        amount: asset.amount,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order() -> order::Order {
+        order::Order {
+            uid: order::Uid([1; 56]),
+            sell: eth::Asset {
+                token: eth::TokenAddress(eth::H160::repeat_byte(1)),
+                amount: 100.into(),
+            },
+            buy: eth::Asset {
+                token: eth::TokenAddress(eth::H160::repeat_byte(2)),
+                amount: 90.into(),
+            },
+            ..Default::default()
+        }
+    }
+
+    fn external(uid: [u8; 56], interactions: Vec<Interaction>) -> Solution {
+        Solution {
+            id: 7,
+            prices: HashMap::from([
+                (eth::H160::repeat_byte(1), 95.into()),
+                (eth::H160::repeat_byte(2), 100.into()),
+            ]),
+            trades: vec![Trade::Fulfillment(Fulfillment {
+                order: OrderUid(uid),
+                executed_amount: 100.into(),
+                fee: None,
+            })],
+            pre_interactions: Vec::new(),
+            interactions,
+            post_interactions: Vec::new(),
+            gas: Some(100_000),
+            flashloans: None,
+            wrappers: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn converts_external_solutions() {
+        let orders = [order()];
+        let custom = Interaction::Custom(CustomInteraction {
+            internalize: false,
+            target: eth::H160::repeat_byte(0xaa),
+            value: 0.into(),
+            calldata: vec![1, 2, 3],
+            allowances: Vec::new(),
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+        });
+
+        let converted = into_domain(external([1; 56], vec![custom.clone()]), &orders, &[]).unwrap();
+        assert_eq!(converted.id.0, 7);
+        assert_eq!(converted.trades.len(), 1);
+        assert_eq!(converted.interactions.len(), 1);
+        assert_eq!(converted.gas, Some(eth::Gas(100_000.into())));
+
+        // unknown order
+        assert!(into_domain(external([2; 56], vec![custom]), &orders, &[]).is_none());
+
+        // unknown liquidity
+        let liquidity = Interaction::Liquidity(LiquidityInteraction {
+            internalize: false,
+            id: "0".to_owned(),
+            input_token: eth::H160::repeat_byte(1),
+            output_token: eth::H160::repeat_byte(2),
+            input_amount: 100.into(),
+            output_amount: 95.into(),
+        });
+        assert!(into_domain(external([1; 56], vec![liquidity]), &orders, &[]).is_none());
+    }
+
+    #[test]
+    fn emits_flashloans_of_hinted_orders() {
+        let solve = |order: order::Order| {
+            let solution = solution::Single {
+                input: order.sell,
+                output: eth::Asset {
+                    token: order.buy.token,
+                    amount: 95.into(),
+                },
+                order,
+                interactions: Vec::new(),
+                gas: eth::Gas(100_000.into()),
+                wrappers: Vec::new(),
+            }
+            .into_solution(Default::default(), solution::SurplusShare::new(0).unwrap())
+            .unwrap();
+            from_domain(&[solution], &[], None, false)
+                .solutions
+                .pop()
+                .unwrap()
+        };
+
+        assert!(solve(order()).flashloans.is_none());
+
+        let hint = order::FlashloanHint {
+            liquidity_provider: eth::Address(eth::H160::repeat_byte(0xa1)),
+            protocol_adapter: eth::Address(eth::H160::repeat_byte(0xa2)),
+            receiver: eth::Address(eth::H160::repeat_byte(0xa3)),
+            token: eth::TokenAddress(eth::H160::repeat_byte(1)),
+            amount: 100.into(),
+        };
+        let flashloans = solve(order::Order {
+            flashloan_hint: Some(hint),
+            ..order()
+        })
+        .flashloans
+        .unwrap();
+        let flashloan = &flashloans[&OrderUid([1; 56])];
+        assert_eq!(flashloan.liquidity_provider, eth::H160::repeat_byte(0xa1));
+        assert_eq!(flashloan.protocol_adapter, eth::H160::repeat_byte(0xa2));
+        assert_eq!(flashloan.receiver, eth::H160::repeat_byte(0xa3));
+        assert_eq!(flashloan.token, eth::H160::repeat_byte(1));
+        assert_eq!(flashloan.amount, 100.into());
+    }
+}
@@ -0,0 +1,69 @@
+//! Solving auctions in process, without serving the HTTP API.
+//!
+//! This is the hook the stable API at the root of this crate is built on.
+
+use {
+    crate::{domain::solver::Solver, dto, infra::config},
+    ethereum_types::{H160, U256},
+    std::path::Path,
+    url::Url,
+};
+
+/// The settings of a solver engine solving auctions in process. See the
+/// `baseline` command's configuration for their meaning.
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub chain_id: Option<u64>,
+    pub weth: Option<H160>,
+    pub base_tokens: Vec<H160>,
+    pub max_hops: usize,
+    pub max_partial_attempts: usize,
+    pub surplus_share_bps: u32,
+    pub solution_gas_offset: i64,
+    pub max_solution_gas: Option<u64>,
+    pub merge_solutions: bool,
+    pub native_token_price_estimation_amount: U256,
+    pub uni_v3_node_url: Option<Url>,
+    pub erc4626_node_url: Option<Url>,
+}
+
+impl Config {
+    /// Loads the settings from the configuration file at the specified path,
+    /// which has the format of the `baseline` command's configuration.
+    pub async fn load(path: &Path) -> anyhow::Result<Self> {
+        config::load_embedded(path).await
+    }
+}
+
+/// A solver engine solving auctions in process.
+pub struct Engine(Solver);
+
+impl Engine {
+    /// Creates a solver engine with the specified settings. Fails if they are
+    /// invalid.
+    pub async fn new(config: &Config) -> anyhow::Result<Self> {
+        Ok(Self(Solver::new(config::embedded(config)?).await))
+    }
+
+    /// Solves the auction over its liquidity and the additionally specified
+    /// liquidity. Liquidity is never fetched from the liquidity client, and
+    /// neither artifacts get saved nor external strategies get consulted.
+    pub async fn solve(
+        &self,
+        mut auction: dto::Auction,
+        liquidity: Vec<solvers_dto::auction::Liquidity>,
+    ) -> Result<dto::Solutions, &'static str> {
+        auction.liquidity.extend(liquidity);
+        let (mut auction, _) = dto::auction::into_domain(auction, None, None, None, None, None)
+            .await
+            .map_err(|err| err.message)?;
+        let skipped = self.0.validate_orders(&mut auction).await;
+        let solved = self.0.solve(auction, std::future::ready(Vec::new())).await;
+        Ok(dto::solution::from_domain(
+            &solved.solutions,
+            &skipped,
+            solved.unsolved.as_deref(),
+            solved.deadline_exceeded,
+        ))
+    }
+}
@@ -0,0 +1,178 @@
+use {
+    super::{Artifact, ArtifactSink, Kind},
+    anyhow::{Context, Result},
+    serde::Serialize,
+    std::{
+        path::{Path, PathBuf},
+        time::UNIX_EPOCH,
+    },
+};
+
+/// Saves artifacts as pretty printed JSON files named after the artifact,
+/// e.g. `123_auction.json`, in a directory.
+pub struct FileSink {
+    directory: PathBuf,
+}
+
+impl FileSink {
+    pub fn new(directory: PathBuf) -> Self {
+        Self { directory }
+    }
+}
+
+#[async_trait::async_trait]
+impl ArtifactSink for FileSink {
+    fn name(&self) -> &'static str {
+        "file"
+    }
+
+    async fn save(&self, artifact: Artifact<'_>) -> Result<()> {
+        tokio::fs::create_dir_all(&self.directory)
+            .await
+            .with_context(|| format!("creating {:?}", self.directory))?;
+        let path = self.directory.join(format!("{}.json", artifact.name()));
+        let content = serde_json::to_string_pretty(artifact.content)?;
+        tokio::fs::write(&path, content)
+            .await
+            .with_context(|| format!("writing {path:?}"))
+    }
+}
+
+/// An artifact saved to a directory by a [`FileSink`].
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Saved {
+    pub auction: String,
+    pub kind: &'static str,
+    pub size: u64,
+    /// When the artifact was last saved, in seconds since the Unix epoch.
+    pub modified: Option<u64>,
+}
+
+impl FileSink {
+    /// Lists the artifacts saved to the directory, the most recently saved
+    /// first. Other files in the directory are ignored.
+    pub async fn list(directory: &Path) -> Result<Vec<Saved>> {
+        let mut entries = tokio::fs::read_dir(directory)
+            .await
+            .with_context(|| format!("reading {directory:?}"))?;
+        let mut saved = Vec::new();
+        while let Some(entry) = entries.next_entry().await? {
+            let name = entry.file_name();
+            let Some((auction, kind)) = name
+                .to_str()
+                .and_then(|name| name.strip_suffix(".json"))
+                .and_then(parse_name)
+            else {
+                continue;
+            };
+            let metadata = entry.metadata().await?;
+            saved.push(Saved {
+                auction: auction.to_owned(),
+                kind: kind.as_str(),
+                size: metadata.len(),
+                modified: metadata
+                    .modified()
+                    .ok()
+                    .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                    .map(|modified| modified.as_secs()),
+            });
+        }
+        saved.sort_by(|a, b| b.modified.cmp(&a.modified));
+        Ok(saved)
+    }
+
+    /// Reads the artifact with the name, e.g. `123_auction`, saved to the
+    /// directory. Returns `None` if there is no such artifact.
+    pub async fn read(directory: &Path, name: &str) -> Result<Option<serde_json::Value>> {
+        // Only valid artifact names get read, so that no paths can be
+        // smuggled in.
+        if parse_name(name).is_none() {
+            return Ok(None);
+        }
+        let path = directory.join(format!("{name}.json"));
+        let content = match tokio::fs::read(&path).await {
+            Ok(content) => content,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err).with_context(|| format!("reading {path:?}")),
+        };
+        Ok(Some(serde_json::from_slice(&content)?))
+    }
+}
+
+/// Splits an artifact name into the auction and the kind of the artifact.
+fn parse_name(name: &str) -> Option<(&str, Kind)> {
+    let valid = |auction: &str| {
+        !auction.is_empty()
+            && auction
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_')
+    };
+    Kind::ALL.into_iter().find_map(|kind| {
+        let auction = name.strip_suffix(kind.as_str())?.strip_suffix('_')?;
+        valid(auction).then_some((auction, kind))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::infra::artifacts::Kind};
+
+    #[tokio::test]
+    async fn writes_json_files() {
+        let directory = tempfile::tempdir().unwrap();
+        let sink = FileSink::new(directory.path().join("artifacts"));
+        let content = serde_json::json!({ "id": "7" });
+        sink.save(Artifact {
+            auction: "7",
+            kind: Kind::EnhancedSolutions,
+            content: &content,
+        })
+        .await
+        .unwrap();
+
+        let path = directory.path().join("artifacts/7_enhanced_solutions.json");
+        let saved = std::fs::read_to_string(path).unwrap();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&saved).unwrap(),
+            content
+        );
+    }
+
+    #[tokio::test]
+    async fn lists_and_reads_saved_artifacts() {
+        let directory = tempfile::tempdir().unwrap();
+        let sink = FileSink::new(directory.path().to_owned());
+        let content = serde_json::json!({ "id": "7" });
+        for kind in [Kind::Auction, Kind::EnhancedSolutions] {
+            sink.save(Artifact {
+                auction: "quote_20250101",
+                kind,
+                content: &content,
+            })
+            .await
+            .unwrap();
+        }
+        std::fs::write(directory.path().join("notes.txt"), "").unwrap();
+
+        let mut saved = FileSink::list(directory.path())
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|saved| (saved.auction, saved.kind))
+            .collect::<Vec<_>>();
+        saved.sort();
+        assert_eq!(
+            saved,
+            [
+                ("quote_20250101".to_owned(), "auction"),
+                ("quote_20250101".to_owned(), "enhanced_solutions"),
+            ]
+        );
+
+        let read = |name: &'static str| FileSink::read(directory.path(), name);
+        assert_eq!(read("quote_20250101_auction").await.unwrap(), Some(content));
+        assert_eq!(read("8_auction").await.unwrap(), None);
+        assert_eq!(read("../secrets_auction").await.unwrap(), None);
+    }
+}
@@ -0,0 +1,218 @@
+//! Persistence of the artifacts of handled auctions, like the auction itself,
+//! the fetched liquidity and the computed solutions.
+//!
+//! Artifacts get saved to all configured sinks in parallel. A failing sink
+//! only gets logged and counted, so that it neither holds up nor breaks the
+//! other sinks.
+
+mod file;
+mod postgres;
+mod s3;
+
+pub use self::{
+    file::{FileSink, Saved},
+    postgres::PostgresSink,
+    s3::S3Sink,
+};
+use {
+    anyhow::Result,
+    futures::future::join_all,
+    std::{fmt, path::PathBuf, sync::Arc},
+};
+
+/// The kind of an artifact.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    Auction,
+    Liquidity,
+    Solutions,
+    EnhancedSolutions,
+    SolutionVerification,
+    Competition,
+    Competitiveness,
+}
+
+impl Kind {
+    /// All kinds, the ones whose names end in the names of others first.
+    pub const ALL: [Self; 7] = [
+        Self::Auction,
+        Self::Liquidity,
+        Self::EnhancedSolutions,
+        Self::Solutions,
+        Self::SolutionVerification,
+        Self::Competition,
+        Self::Competitiveness,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Auction => "auction",
+            Self::Liquidity => "liquidity",
+            Self::Solutions => "solutions",
+            Self::EnhancedSolutions => "enhanced_solutions",
+            Self::SolutionVerification => "solution_verification",
+            Self::Competition => "competition",
+            Self::Competitiveness => "competitiveness",
+        }
+    }
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An artifact of an auction.
+#[derive(Clone, Copy, Debug)]
+pub struct Artifact<'a> {
+    /// The auction the artifact belongs to, e.g. `123` for auction 123 or
+    /// `quote_<timestamp>` for quotes. Daily reports use their date instead.
+    pub auction: &'a str,
+    pub kind: Kind,
+    pub content: &'a serde_json::Value,
+}
+
+impl Artifact<'_> {
+    /// The name of the artifact, e.g. `123_auction`.
+    pub fn name(&self) -> String {
+        format!("{}_{}", self.auction, self.kind)
+    }
+}
+
+/// A destination artifacts get saved to.
+#[async_trait::async_trait]
+pub trait ArtifactSink: Send + Sync {
+    /// The name of the sink used in logs and metrics.
+    fn name(&self) -> &'static str;
+
+    async fn save(&self, artifact: Artifact<'_>) -> Result<()>;
+}
+
+/// The configuration of a sink.
+#[derive(Clone, Debug)]
+pub enum SinkConfig {
+    File { directory: PathBuf },
+    S3 { bucket: String, prefix: String },
+    Postgres { url: url::Url, table: String },
+}
+
+/// Fans artifacts out to all configured sinks.
+#[derive(Clone)]
+pub struct Artifacts(Arc<[Box<dyn ArtifactSink>]>);
+
+impl Artifacts {
+    /// Returns `None` without any sinks, in which case nothing needs to be
+    /// saved.
+    pub fn new(sinks: Vec<Box<dyn ArtifactSink>>) -> Option<Self> {
+        (!sinks.is_empty()).then(|| Self(sinks.into()))
+    }
+
+    /// Sets up the configured sinks.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a sink can't be set up, e.g. because its credentials are
+    /// invalid.
+    pub async fn from_config(configs: Vec<SinkConfig>) -> Option<Self> {
+        let mut sinks = Vec::<Box<dyn ArtifactSink>>::new();
+        for config in configs {
+            sinks.push(match config {
+                SinkConfig::File { directory } => Box::new(FileSink::new(directory)),
+                SinkConfig::S3 { bucket, prefix } => Box::new(S3Sink::new(bucket, prefix).await),
+                SinkConfig::Postgres { url, table } => Box::new(
+                    PostgresSink::new(&url, &table)
+                        .await
+                        .unwrap_or_else(|err| panic!("failed to set up Postgres sink: {err:?}")),
+                ),
+            });
+        }
+        Self::new(sinks)
+    }
+
+    /// Saves the artifact to all sinks, logging the sinks it failed for.
+    pub async fn save(&self, artifact: Artifact<'_>) {
+        join_all(self.0.iter().map(|sink| async move {
+            match sink.save(artifact).await {
+                Ok(()) => tracing::debug!(
+                    sink = sink.name(),
+                    artifact = artifact.name(),
+                    "saved artifact"
+                ),
+                Err(err) => {
+                    tracing::warn!(
+                        ?err,
+                        sink = sink.name(),
+                        artifact = artifact.name(),
+                        "failed to save artifact"
+                    );
+                    crate::infra::metrics::artifact_sink_failed(sink.name());
+                }
+            }
+        }))
+        .await;
+    }
+}
+
+/// Returns the name to save the artifacts of an auction under, which for
+/// quotes is based on the current time.
+pub fn auction_name(id: Option<i64>) -> String {
+    match id {
+        Some(id) => id.to_string(),
+        None => format!("quote_{}", chrono::Utc::now().format("%Y%m%d_%H%M%S_%3f")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::sync::Mutex};
+
+    #[derive(Default)]
+    struct Recording {
+        failing: bool,
+        saved: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[async_trait::async_trait]
+    impl ArtifactSink for Recording {
+        fn name(&self) -> &'static str {
+            "recording"
+        }
+
+        async fn save(&self, artifact: Artifact<'_>) -> Result<()> {
+            anyhow::ensure!(!self.failing, "sink unavailable");
+            self.saved.lock().unwrap().push(artifact.name());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn failing_sinks_dont_affect_others() {
+        let saved = Arc::new(Mutex::new(Vec::new()));
+        let artifacts = Artifacts::new(vec![
+            Box::new(Recording {
+                failing: true,
+                ..Default::default()
+            }),
+            Box::new(Recording {
+                failing: false,
+                saved: saved.clone(),
+            }),
+        ])
+        .unwrap();
+
+        artifacts
+            .save(Artifact {
+                auction: "42",
+                kind: Kind::Auction,
+                content: &serde_json::json!({}),
+            })
+            .await;
+        assert_eq!(*saved.lock().unwrap(), ["42_auction"]);
+    }
+
+    #[test]
+    fn requires_sinks() {
+        assert!(Artifacts::new(Vec::new()).is_none());
+    }
+}
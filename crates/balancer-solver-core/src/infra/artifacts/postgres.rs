@@ -0,0 +1,64 @@
+use {
+    super::{Artifact, ArtifactSink},
+    anyhow::{Context, Result, ensure},
+    sqlx::PgPool,
+};
+
+/// Inserts artifacts as JSONB rows into a Postgres table, which gets created
+/// if it doesn't exist yet.
+pub struct PostgresSink {
+    pool: PgPool,
+    insert: String,
+}
+
+impl PostgresSink {
+    pub async fn new(url: &url::Url, table: &str) -> Result<Self> {
+        // The table name can't be bound as a parameter, so only allow plain
+        // identifiers.
+        ensure!(
+            !table.is_empty()
+                && table
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.'),
+            "invalid artifacts table name {table:?}"
+        );
+        let pool = PgPool::connect(url.as_str())
+            .await
+            .context("connecting to the artifacts database")?;
+        sqlx::query(&format!(
+            "CREATE TABLE IF NOT EXISTS {table} (
+                auction TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                content JSONB NOT NULL,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+            )"
+        ))
+        .execute(&pool)
+        .await
+        .with_context(|| format!("creating artifacts table {table}"))?;
+
+        Ok(Self {
+            pool,
+            insert: format!(
+                "INSERT INTO {table} (auction, kind, content) VALUES ($1, $2, $3::jsonb)"
+            ),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl ArtifactSink for PostgresSink {
+    fn name(&self) -> &'static str {
+        "postgres"
+    }
+
+    async fn save(&self, artifact: Artifact<'_>) -> Result<()> {
+        sqlx::query(&self.insert)
+            .bind(artifact.auction)
+            .bind(artifact.kind.as_str())
+            .bind(serde_json::to_string(artifact.content)?)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,38 @@
+use {
+    super::{Artifact, ArtifactSink},
+    anyhow::Result,
+};
+
+/// Uploads artifacts as gzipped JSON objects named after the artifact to an
+/// S3 bucket.
+pub struct S3Sink {
+    uploader: ::s3::Uploader,
+}
+
+impl S3Sink {
+    /// Creates the sink, checking that the AWS credentials of the
+    /// environment allow uploads to the bucket.
+    pub async fn new(bucket: String, prefix: String) -> Self {
+        Self {
+            uploader: ::s3::Uploader::new(::s3::Config {
+                bucket,
+                filename_prefix: prefix,
+            })
+            .await,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl ArtifactSink for S3Sink {
+    fn name(&self) -> &'static str {
+        "s3"
+    }
+
+    async fn save(&self, artifact: Artifact<'_>) -> Result<()> {
+        self.uploader
+            .upload(artifact.name(), artifact.content)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,83 @@
+//! Fetching of the sell token balances available to order owners.
+
+use {
+    crate::domain::{eth, order},
+    anyhow::{Context, Result},
+    contracts::alloy::{GPv2Settlement, InstanceExt, support::Balances as BalanceHelper},
+    ethrpc::alloy::conversions::{IntoAlloy, IntoLegacy},
+    model::interaction::InteractionData,
+    shared::{
+        account_balances::{self, BalanceFetching, BalanceSimulator, Query},
+        price_estimation::trade_verifier::balance_overrides::BalanceOverrides,
+    },
+    std::sync::Arc,
+    url::Url,
+};
+
+/// Fetches the balances of order owners that the settlement contract can
+/// actually use, taking both balances and allowances into account.
+#[derive(Clone)]
+pub struct Balances(Arc<dyn BalanceFetching>);
+
+impl Balances {
+    pub async fn new(node_url: &Url, vault: Option<eth::Address>) -> Result<Self> {
+        let web3 = ethrpc::web3(Default::default(), Default::default(), node_url, "balances");
+        let settlement = GPv2Settlement::Instance::deployed(&web3.alloy)
+            .await
+            .context("settlement contract")?;
+        let balance_helper = BalanceHelper::Instance::deployed(&web3.alloy)
+            .await
+            .context("balances helper contract")?;
+        let vault_relayer = settlement
+            .vaultRelayer()
+            .call()
+            .await
+            .context("vault relayer")?;
+        let simulator = BalanceSimulator::new(
+            settlement,
+            balance_helper,
+            vault_relayer.into_legacy(),
+            vault.map(|vault| vault.0),
+            Arc::new(BalanceOverrides::new(web3.clone())),
+        );
+        Ok(Self(account_balances::fetcher(&web3, simulator)))
+    }
+
+    /// Returns the sell token balance available for settling each of the
+    /// specified orders, or `None` if it could not be fetched.
+    pub async fn available(&self, orders: &[&order::Order]) -> Vec<Option<eth::U256>> {
+        let queries = orders
+            .iter()
+            .map(|order| Query {
+                owner: order.owner.0,
+                token: order.sell.token.0,
+                source: match order.sell_token_source {
+                    order::SellTokenSource::Erc20 => model::order::SellTokenSource::Erc20,
+                    order::SellTokenSource::External => model::order::SellTokenSource::External,
+                    order::SellTokenSource::Internal => model::order::SellTokenSource::Internal,
+                },
+                interactions: order
+                    .pre_interactions
+                    .iter()
+                    .map(|interaction| InteractionData {
+                        target: interaction.target.0.into_alloy(),
+                        value: interaction.value.0.into_alloy(),
+                        call_data: interaction.calldata.clone(),
+                    })
+                    .collect(),
+                balance_override: None,
+            })
+            .collect::<Vec<_>>();
+
+        self.0
+            .get_balances(&queries)
+            .await
+            .into_iter()
+            .map(|balance| {
+                balance
+                    .inspect_err(|err| tracing::debug!(?err, "failed to fetch order balance"))
+                    .ok()
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,130 @@
+//! Bundling of the saved artifacts of an auction into a single archive that
+//! can be attached to bug reports.
+
+use {
+    crate::util::redact,
+    anyhow::{Context, Result, ensure},
+    flate2::{Compression, write::GzEncoder},
+    std::{
+        fs,
+        io::Write,
+        path::{Path, PathBuf},
+    },
+};
+
+/// Writes all artifacts saved for the specified auction id in the directory
+/// to a gzipped tar archive, redacting trader data in JSON artifacts if
+/// requested. Returns the archived files.
+pub fn write(directory: &Path, auction: &str, output: &Path, redact: bool) -> Result<Vec<PathBuf>> {
+    let prefix = format!("{auction}_");
+    let mut files = fs::read_dir(directory)
+        .with_context(|| format!("reading {directory:?}"))?
+        .map(|entry| Ok(entry?.path()))
+        .collect::<Result<Vec<_>>>()?;
+    files.retain(|path| {
+        path.is_file()
+            && path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(&prefix))
+    });
+    files.sort();
+    ensure!(
+        !files.is_empty(),
+        "no artifacts of auction {auction} in {directory:?}"
+    );
+
+    let mut archive = GzEncoder::new(
+        fs::File::create(output).with_context(|| format!("creating {output:?}"))?,
+        Compression::default(),
+    );
+    for path in &files {
+        let name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .context("invalid file name")?;
+        let mut content = fs::read(path).with_context(|| format!("reading {path:?}"))?;
+        if redact && name.ends_with(".json") {
+            let mut json =
+                serde_json::from_slice(&content).with_context(|| format!("parsing {path:?}"))?;
+            redact::redact(&mut json);
+            content = serde_json::to_vec_pretty(&json)?;
+        }
+        append(&mut archive, name, &content)?;
+    }
+    // A tar archive ends with two empty blocks.
+    archive.write_all(&[0; 2 * BLOCK])?;
+    archive.finish()?.flush()?;
+    Ok(files)
+}
+
+/// The block size of tar archives.
+const BLOCK: usize = 512;
+
+/// Appends a regular file to a tar archive.
+fn append(archive: &mut impl Write, name: &str, content: &[u8]) -> Result<()> {
+    ensure!(name.len() < 100, "file name {name} too long");
+    let mut header = [0u8; BLOCK];
+    let mut field = |offset: usize, value: &[u8]| {
+        header[offset..offset + value.len()].copy_from_slice(value);
+    };
+    field(0, name.as_bytes());
+    field(100, b"0000644\0");
+    field(108, b"0000000\0");
+    field(116, b"0000000\0");
+    field(124, format!("{:011o}\0", content.len()).as_bytes());
+    field(136, b"00000000000\0");
+    field(156, b"0");
+    field(257, b"ustar\0");
+    field(263, b"00");
+
+    // The checksum is computed with the checksum field filled with spaces.
+    header[148..156].fill(b' ');
+    let checksum = header.iter().map(|byte| u32::from(*byte)).sum::<u32>();
+    header[148..156].copy_from_slice(format!("{checksum:06o}\0 ").as_bytes());
+
+    archive.write_all(&header)?;
+    archive.write_all(content)?;
+    archive.write_all(&vec![
+        0;
+        content.len().next_multiple_of(BLOCK) - content.len()
+    ])?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, flate2::read::GzDecoder, std::io::Read};
+
+    #[test]
+    fn bundles_auction_artifacts() {
+        let directory = tempfile::tempdir().unwrap();
+        let auction = serde_json::json!({ "orders": [{ "signature": "0x1234" }] });
+        fs::write(
+            directory.path().join("42_auction.json"),
+            auction.to_string(),
+        )
+        .unwrap();
+        fs::write(directory.path().join("42_solutions.json"), "{}").unwrap();
+        fs::write(directory.path().join("43_auction.json"), "{}").unwrap();
+        let output = directory.path().join("bundle.tar.gz");
+
+        let files = write(directory.path(), "42", &output, true).unwrap();
+        assert_eq!(files.len(), 2);
+
+        let mut archive = Vec::new();
+        GzDecoder::new(fs::File::open(&output).unwrap())
+            .read_to_end(&mut archive)
+            .unwrap();
+        // two files with a header and a single content block each, followed by
+        // the two end blocks
+        assert_eq!(archive.len(), 6 * BLOCK);
+        assert!(archive.starts_with(b"42_auction.json\0"));
+        let content = String::from_utf8_lossy(&archive[BLOCK..2 * BLOCK]);
+        assert!(content.contains(r#""signature": "0x""#));
+        assert!(!content.contains("0x1234"));
+        assert!(archive[2 * BLOCK..].starts_with(b"42_solutions.json\0"));
+
+        assert!(write(directory.path(), "44", &output, false).is_err());
+    }
+}
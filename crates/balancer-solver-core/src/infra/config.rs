@@ -0,0 +1,1401 @@
+use {
+    crate::{
+        domain::{
+            auction,
+            bad_tokens,
+            base_tokens,
+            bidding,
+            eth,
+            inventory,
+            lp,
+            price_guard,
+            solution::SurplusShare,
+            solver,
+        },
+        embed,
+        infra::{self, contracts},
+        util::serialize,
+    },
+    anyhow::Context,
+    chain::Chain,
+    ethereum_types::H160,
+    ethrpc::alloy::conversions::IntoLegacy,
+    serde::Deserialize,
+    serde_with::serde_as,
+    shared::{price_estimation::gas::SETTLEMENT_OVERHEAD, sources::balancer_rounding},
+    std::{fmt::Debug, path::Path},
+    tokio::fs,
+    url::Url,
+};
+
+#[serde_as]
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct Config {
+    /// Optional chain ID. This is used to automatically determine the address
+    /// of the WETH contract.
+    chain_id: Option<Chain>,
+
+    /// Optional WETH contract address. This can be used to specify a manual
+    /// value **instead** of using the canonical WETH contract for the
+    /// configured chain.
+    weth: Option<H160>,
+
+    /// List of base tokens to use when path finding. This defines the tokens
+    /// that can appear as intermediate "hops" within a trading route. Note that
+    /// WETH is always considered as a base token.
+    base_tokens: Vec<eth::H160>,
+
+    /// The maximum number of hops to consider when finding the optimal trading
+    /// path.
+    max_hops: usize,
+
+    /// The maximum number of pieces to divide partially fillable limit orders
+    /// when trying to solve it against baseline liquidity.
+    max_partial_attempts: usize,
+
+    /// The share of the price improvement over an order's limit price to keep
+    /// as solver margin, in basis points.
+    #[serde(default)]
+    surplus_share_bps: u32,
+
+    /// Units of gas that get added to the gas estimate for executing a
+    /// computed trade route to arrive at a gas estimate for a whole settlement.
+    #[serde(default = "default_gas_offset")]
+    solution_gas_offset: i64,
+
+    /// The maximum amount of gas a solution may use. Routes exceeding it are
+    /// discarded, and solutions only get merged as long as the joint solution
+    /// still fits into it.
+    max_solution_gas: Option<u64>,
+
+    /// The maximum number of orders of an auction that get solved
+    /// concurrently against the auction's liquidity.
+    #[serde(default = "default_max_concurrent_orders")]
+    max_concurrent_orders: usize,
+
+    /// Whether to combine the solutions of orders not sharing any tokens into
+    /// joint solutions. Routes swapping through the same liquidity get
+    /// re-priced sequentially, or kept separate if they no longer cover the
+    /// trades.
+    #[serde(default)]
+    merge_solutions: bool,
+
+    /// Whether to internalize interactions with trusted input tokens whose
+    /// outputs the settlement contract buffers can cover, omitting them from
+    /// the settlement.
+    #[serde(default = "default_internalize_interactions")]
+    internalize_interactions: bool,
+
+    /// The rounding of the Balancer fixed point math. Rounding to the nearest
+    /// value quantifies the cost of rounding like the contracts do, but
+    /// yields amounts that can't be settled, and requires a build with the
+    /// `research-rounding` feature.
+    #[serde(default)]
+    rounding: RoundingConfig,
+
+    /// The amount of the native token to use to estimate native price of a
+    /// token
+    #[serde_as(as = "serialize::U256")]
+    native_token_price_estimation_amount: eth::U256,
+
+    /// If this is configured the solver will also use the Uniswap V3 and V4
+    /// liquidity sources that rely on RPC request.
+    uni_v3_node_url: Option<Url>,
+
+    /// Optional RPC endpoint used for ERC4626 preview_* quoting in baseline
+    /// routing. If unset, we fall back to `uni_v3_node_url` if present. If
+    /// both are unset, ERC4626 baseline routing is disabled.
+    erc4626_node_url: Option<Url>,
+
+    /// Configuration for independent liquidity fetching
+    liquidity: Option<LiquidityConfig>,
+
+    /// Optional directory path to save auction and solution JSON files
+    auction_save_directory: Option<String>,
+
+    /// Whether to redact owner addresses, signatures and app data of orders in
+    /// the saved auction and solution files.
+    #[serde(default)]
+    redact_saved_files: bool,
+
+    /// Sinks to save the auction artifacts to in addition to
+    /// `auction-save-directory`. Artifacts get saved to all sinks in
+    /// parallel and failing sinks don't affect the others.
+    #[serde(default)]
+    artifact_sinks: Vec<ArtifactSinkConfig>,
+
+    /// Balancer V2 Vault address for solution verification
+    vault_address: Option<H160>,
+
+    /// Balancer V3 Batch Router address for solution verification and for
+    /// encoding boosted routes
+    batch_router_address: Option<H160>,
+
+    /// ERC4626 tokens with an initialized Balancer V3 Vault buffer. Routes
+    /// through Balancer V3 pools wrapping or unwrapping them get encoded as a
+    /// single batch router swap using buffer steps. Requires
+    /// `batch-router-address`.
+    #[serde(default)]
+    erc4626_buffers: Vec<H160>,
+
+    /// Node URL for solution verification
+    node_url: Option<Url>,
+
+    /// Enables the lightweight path for quote auctions. Quotes solved on this
+    /// path only consider the deepest liquidity, run on a strict time budget
+    /// and skip persistence and verification.
+    quote: Option<QuoteConfig>,
+
+    /// Coalesces identical quote auctions arriving within this window in
+    /// milliseconds into a single solve, answering all of them with its
+    /// solutions. Quote auctions are identical if they only differ in their
+    /// deadline.
+    quote_batching_window_ms: Option<u64>,
+
+    /// Configuration of the per token pair statistics.
+    #[serde(default)]
+    stats: StatsConfig,
+
+    /// Bearer token authorizing updates of the routing configuration via
+    /// `PATCH /config/routing`. Updates are rejected if this is not set.
+    routing_api_token: Option<String>,
+
+    /// Serves the `/math/eval` debug endpoint, evaluating swaps through a
+    /// single pool for comparing the pool math against other implementations.
+    /// Must not be enabled in production deployments.
+    #[serde(default)]
+    math_eval_endpoint: bool,
+
+    /// Serves the `/debug/heap` endpoint, summarizing the heap usage of the
+    /// process and the allocations per subsystem.
+    #[serde(default)]
+    heap_debug_endpoint: bool,
+
+    /// Serves a web UI on `/explorer` for browsing the artifacts saved to the
+    /// first file artifact sink. Must not be enabled in production
+    /// deployments, as it exposes the saved auctions.
+    #[serde(default)]
+    explorer_endpoint: bool,
+
+    /// Enables skipping orders that are guaranteed to fail settlement before
+    /// routing.
+    order_validation: Option<OrderValidationConfig>,
+
+    /// Tokens to refuse trading, either configured statically or synced from
+    /// a remote unsupported token list.
+    token_denylist: Option<TokenDenylistConfig>,
+
+    /// Enables quarantining tokens that repeatedly make settlements of this
+    /// solver's solutions fail.
+    bad_token_detection: Option<BadTokenDetectionConfig>,
+
+    /// Enables tracking the realized slippage of the swaps of this solver's
+    /// settled solutions against the amounts they were quoted at.
+    slippage_tracking: Option<SlippageTrackingConfig>,
+
+    /// Enables sanity checking the clearing prices of solutions against
+    /// external oracle prices.
+    price_guard: Option<PriceGuardConfig>,
+
+    /// Enables settling orders trading Balancer V2 weighted pool tokens for
+    /// one of the pool's tokens by joining or exiting the pool. Requires
+    /// `vault-address`.
+    lp_intents: Option<LpIntentsConfig>,
+
+    /// External strategy endpoints implementing the solver API. Auctions are
+    /// forwarded to them including the fetched liquidity, and their solutions
+    /// get proposed alongside the local ones.
+    external_strategies: Option<ExternalStrategiesConfig>,
+
+    /// Enables comparing a sample of the quotes against external price APIs
+    /// and saving a daily competitiveness report to the artifact sinks.
+    price_comparison: Option<PriceComparisonConfig>,
+
+    /// Enables keeping reserves of the settlement contract buffers out of
+    /// internalization and reporting how the buffers should be rebalanced.
+    inventory: Option<InventoryConfig>,
+
+    /// Enables periodically selecting the most connected tokens of the
+    /// auction liquidity as additional base tokens.
+    auto_base_tokens: Option<AutoBaseTokensConfig>,
+
+    /// Enables calibrating the surplus share kept from single order
+    /// solutions for competition auctions on the rival scores of past
+    /// competitions, maximizing the expected kept surplus. Requires
+    /// `stats.solver-address`.
+    rival_aware_bidding: Option<RivalAwareBiddingConfig>,
+
+    /// Enables matching the auction's orders against the resting orders of
+    /// the CoW Protocol order book as JIT trades.
+    order_book: Option<OrderBookConfig>,
+
+    /// The listeners to serve the API on. If none are configured, the API is
+    /// served on the `--addr` address.
+    #[serde(default)]
+    listen: Vec<ListenerConfig>,
+}
+
+#[serde_as]
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct ListenerConfig {
+    /// The socket address to listen on, e.g. `127.0.0.1:7872` or `[::]:7872`,
+    /// or the path of a unix domain socket prefixed with `unix:`.
+    #[serde_as(as = "serde_with::DisplayFromStr")]
+    address: shared::listen::Address,
+
+    /// Whether listening on an IPv6 address also accepts IPv4 connections.
+    #[serde(default = "default_dual_stack")]
+    dual_stack: bool,
+
+    /// Terminates TLS on the listener with the certificate in `certificate`
+    /// and its private key in `private-key`, both PEM files. Clients must
+    /// authenticate with a certificate issued by a certificate authority in
+    /// `client-ca` if specified.
+    tls: Option<TlsConfig>,
+}
+
+fn default_dual_stack() -> bool {
+    true
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct TlsConfig {
+    certificate: std::path::PathBuf,
+    private_key: std::path::PathBuf,
+    client_ca: Option<std::path::PathBuf>,
+}
+
+/// Configuration for the liquidity client
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub struct LiquidityConfig {
+    /// URL of the liquidity-driver instance
+    pub driver_url: String,
+
+    /// Request timeout in milliseconds
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+
+    /// Protocols to fetch liquidity from
+    #[serde(default = "default_protocols")]
+    pub protocols: Vec<String>,
+
+    /// PEM file with an additional certificate authority to verify the
+    /// certificate of the liquidity-driver with.
+    pub ca_certificate: Option<std::path::PathBuf>,
+
+    /// Client certificate to authenticate with at liquidity-drivers requiring
+    /// mutual TLS.
+    pub client_certificate: Option<ClientCertificateConfig>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct ClientCertificateConfig {
+    /// PEM file with the certificate chain of the client.
+    pub certificate: std::path::PathBuf,
+
+    /// PEM file with the PKCS#8 encoded private key of the certificate.
+    pub private_key: std::path::PathBuf,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum RoundingConfig {
+    /// Round in the direction the contracts round in.
+    #[default]
+    Strict,
+    /// Round to the nearest value.
+    Nearest,
+}
+
+/// Configuration for the lightweight quote path
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct QuoteConfig {
+    /// The maximum number of liquidity sources to consider per token pair,
+    /// keeping the deepest ones by native token value.
+    #[serde(default = "default_quote_max_liquidity_per_pair")]
+    max_liquidity_per_pair: usize,
+
+    /// The maximum amount of time to spend solving a quote in milliseconds.
+    #[serde(default = "default_quote_time_budget_ms")]
+    time_budget_ms: u64,
+
+    /// Top token pairs to keep a price matrix for. Their best routes get
+    /// refreshed on every block from the liquidity of the latest solved
+    /// auction, and quotes for them are priced at these routes, without
+    /// fetching liquidity or searching paths. Requires `node-url` to be
+    /// configured.
+    #[serde(default)]
+    matrix_pairs: Vec<[H160; 2]>,
+}
+
+/// Configuration for the order validation
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct OrderValidationConfig {
+    /// Whether to check the sell token balances and allowances of order
+    /// owners. Requires `node-url` to be configured.
+    #[serde(default = "default_check_balances")]
+    check_balances: bool,
+}
+
+fn default_check_balances() -> bool {
+    true
+}
+
+fn default_internalize_interactions() -> bool {
+    true
+}
+
+/// Configuration for the token denylist
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct TokenDenylistConfig {
+    /// Tokens that are always denied.
+    #[serde(default)]
+    tokens: Vec<H160>,
+
+    /// URL of an unsupported token list to periodically sync, returning
+    /// either a JSON array of token addresses or a token list.
+    url: Option<Url>,
+
+    /// How often to sync the unsupported token list in seconds.
+    #[serde(default = "default_denylist_sync_interval_secs")]
+    sync_interval_secs: u64,
+}
+
+fn default_denylist_sync_interval_secs() -> u64 {
+    5 * 60
+}
+
+/// Configuration for the bad token detection
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct BadTokenDetectionConfig {
+    /// The ratio of failing settlements at which a token gets quarantined.
+    #[serde(default = "default_bad_token_failure_ratio")]
+    failure_ratio: f64,
+
+    /// The number of settlement outcomes a token needs to be part of before
+    /// it can get quarantined.
+    #[serde(default = "default_bad_token_required_measurements")]
+    required_measurements: u32,
+
+    /// How long a token stays quarantined in seconds.
+    #[serde(default = "default_bad_token_freeze_time_secs")]
+    freeze_time_secs: u64,
+}
+
+fn default_bad_token_failure_ratio() -> f64 {
+    0.9
+}
+
+fn default_bad_token_required_measurements() -> u32 {
+    20
+}
+
+fn default_bad_token_freeze_time_secs() -> u64 {
+    10 * 60
+}
+
+/// Configuration for the realized slippage tracking
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct SlippageTrackingConfig {
+    /// The rolling time window the statistics cover in seconds.
+    #[serde(default = "default_stats_window_secs")]
+    window_secs: u64,
+
+    /// Node URL for fetching the receipts of settlement transactions.
+    /// Defaults to `node-url`.
+    node_url: Option<Url>,
+}
+
+/// Configuration for the oracle price sanity check
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct PriceGuardConfig {
+    /// Node URL for reading the oracle prices. Defaults to `node-url`.
+    node_url: Option<Url>,
+
+    /// The maximum relative deviation of the exchange rate implied by the
+    /// clearing prices from the one implied by the oracle prices.
+    #[serde(default = "default_price_guard_max_deviation")]
+    max_deviation: f64,
+
+    /// Whether to only flag deviating solutions or to reject them.
+    #[serde(default)]
+    action: PriceGuardAction,
+
+    /// The oracle price feeds per token. Pairs with a token without a feed
+    /// are not checked. The wrapped native token needs no feed.
+    #[serde(default)]
+    feeds: Vec<OracleFeedConfig>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "kebab-case")]
+enum PriceGuardAction {
+    #[default]
+    Flag,
+    Reject,
+}
+
+/// The source of the oracle price of a token
+#[derive(Deserialize, Debug)]
+#[serde(
+    tag = "kind",
+    rename_all = "kebab-case",
+    rename_all_fields = "kebab-case",
+    deny_unknown_fields
+)]
+enum OracleFeedConfig {
+    /// A Chainlink aggregator quoting the token price in the native token.
+    Chainlink {
+        token: H160,
+        aggregator: H160,
+        /// The maximum age of the latest round in seconds.
+        #[serde(default = "default_chainlink_max_age_secs")]
+        max_age_secs: u64,
+    },
+    /// A Uniswap V3 pool pairing the token with the wrapped native token.
+    UniswapV3Twap {
+        token: H160,
+        pool: H160,
+        /// The time window to average the pool price over in seconds.
+        #[serde(default = "default_twap_window_secs")]
+        window_secs: u64,
+    },
+}
+
+/// A destination to save auction artifacts to
+#[derive(Deserialize, Debug)]
+#[serde(
+    tag = "kind",
+    rename_all = "kebab-case",
+    rename_all_fields = "kebab-case",
+    deny_unknown_fields
+)]
+enum ArtifactSinkConfig {
+    /// JSON files in a local directory.
+    File { directory: std::path::PathBuf },
+    /// Gzipped JSON objects in an S3 bucket, using the AWS credentials of the
+    /// environment.
+    S3 {
+        bucket: String,
+        /// Prepended to the names of the uploaded objects.
+        #[serde(default)]
+        prefix: String,
+    },
+    /// Rows of a Postgres table, which gets created if it doesn't exist.
+    Postgres {
+        url: Url,
+        #[serde(default = "default_artifacts_table")]
+        table: String,
+    },
+}
+
+fn default_artifacts_table() -> String {
+    "solver_artifacts".to_owned()
+}
+
+/// Configuration for solving pool join and exit intents
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct LpIntentsConfig {
+    /// Node URL for fetching pool token supplies. Defaults to `node-url`.
+    node_url: Option<Url>,
+
+    /// The settlement contract joining and exiting pools. Defaults to the
+    /// canonical deployment on the configured chain.
+    settlement: Option<H160>,
+
+    /// The share of the computed pool or exit token amount that may be lost
+    /// to pool state changes before settlement, in basis points.
+    #[serde(default = "default_lp_slippage_bps")]
+    slippage_bps: u32,
+}
+
+fn default_lp_slippage_bps() -> u32 {
+    50
+}
+
+/// Configuration for forwarding auctions to external strategies
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct ExternalStrategiesConfig {
+    /// The time to wait for the solutions of an external strategy in
+    /// milliseconds. Solutions arriving after the auction deadline are
+    /// dropped regardless.
+    #[serde(default = "default_external_strategies_timeout_ms")]
+    timeout_ms: u64,
+
+    /// The external strategy endpoints.
+    endpoints: Vec<ExternalStrategyConfig>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct ExternalStrategyConfig {
+    /// A name identifying the strategy in logs.
+    name: String,
+
+    /// The URL auctions get posted to.
+    url: Url,
+}
+
+/// Configuration of the comparison of quotes against external price APIs
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct PriceComparisonConfig {
+    /// The share of quotes to compare, between 0 and 1.
+    #[serde(default = "default_price_comparison_sample_rate")]
+    sample_rate: f64,
+
+    /// The time to wait for the price of an API in milliseconds.
+    #[serde(default = "default_price_comparison_timeout_ms")]
+    timeout_ms: u64,
+
+    /// The price APIs to compare against.
+    apis: Vec<PriceApiConfig>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(
+    tag = "kind",
+    rename_all = "kebab-case",
+    rename_all_fields = "kebab-case",
+    deny_unknown_fields
+)]
+enum PriceApiConfig {
+    /// The 1inch swap API, which only quotes sell orders.
+    OneInch {
+        #[serde(default = "default_one_inch_url")]
+        url: Url,
+        api_key: String,
+    },
+    /// The 0x swap API, which only quotes sell orders.
+    ZeroEx {
+        #[serde(default = "default_zero_ex_url")]
+        url: Url,
+        api_key: String,
+    },
+    /// The Paraswap price API.
+    Paraswap {
+        #[serde(default = "default_paraswap_url")]
+        url: Url,
+    },
+}
+
+fn default_price_comparison_sample_rate() -> f64 {
+    0.01
+}
+
+fn default_price_comparison_timeout_ms() -> u64 {
+    2_000
+}
+
+fn default_one_inch_url() -> Url {
+    "https://api.1inch.dev/swap/v6.0/".parse().unwrap()
+}
+
+fn default_zero_ex_url() -> Url {
+    "https://api.0x.org/".parse().unwrap()
+}
+
+fn default_paraswap_url() -> Url {
+    "https://api.paraswap.io/".parse().unwrap()
+}
+
+/// Configuration of the automatic base token selection
+#[serde_as]
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct AutoBaseTokensConfig {
+    /// The number of tokens to select.
+    #[serde(default = "default_auto_base_tokens_count")]
+    count: usize,
+
+    /// How often the selection gets refreshed in seconds.
+    #[serde(default = "default_auto_base_tokens_interval_secs")]
+    interval_secs: u64,
+
+    /// The TVL in wei of the native token a pool needs for counting towards
+    /// the connectedness of its tokens.
+    #[serde_as(as = "serialize::U256")]
+    #[serde(default)]
+    min_pool_tvl: eth::U256,
+
+    /// The file the selection gets persisted to and restored from on startup.
+    path: Option<std::path::PathBuf>,
+}
+
+fn default_auto_base_tokens_count() -> usize {
+    5
+}
+
+fn default_auto_base_tokens_interval_secs() -> u64 {
+    60 * 60
+}
+
+/// Configuration of the rival-aware bidding
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct RivalAwareBiddingConfig {
+    /// The largest share of the surplus that may be kept in basis points.
+    #[serde(default = "default_rival_aware_bidding_max_surplus_share_bps")]
+    max_surplus_share_bps: u32,
+
+    /// The number of rival scores a token pair and order size bucket needs
+    /// before it is calibrated. The configured `surplus-share-bps` is kept
+    /// for buckets with fewer scores.
+    #[serde(default = "default_rival_aware_bidding_min_samples")]
+    min_samples: usize,
+
+    /// The number of most recent rival scores kept per bucket.
+    #[serde(default = "default_rival_aware_bidding_max_samples")]
+    max_samples: usize,
+}
+
+fn default_rival_aware_bidding_max_surplus_share_bps() -> u32 {
+    5_000
+}
+
+fn default_rival_aware_bidding_min_samples() -> usize {
+    20
+}
+
+fn default_rival_aware_bidding_max_samples() -> usize {
+    500
+}
+
+/// Configuration of the settlement contract inventory accounting
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct InventoryConfig {
+    /// How often the buffer flows and rebalancing suggestions get logged in
+    /// seconds.
+    #[serde(default = "default_inventory_report_interval_secs")]
+    report_interval_secs: u64,
+
+    /// The bounds of the buffers per token.
+    #[serde(default)]
+    buffers: Vec<BufferConfig>,
+
+    /// Enables filling orders from the inventory when the on-chain liquidity
+    /// trades at a worse price than the configured reference prices.
+    fills: Option<InventoryFillsConfig>,
+}
+
+/// Configuration of filling orders from the inventory
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct InventoryFillsConfig {
+    /// The account holding the inventory, which must have approved the
+    /// settlement contract to transfer its tokens. Defaults to the settlement
+    /// contract buffers.
+    account: Option<H160>,
+
+    /// The settlement contract pulling the fills from the account. Defaults to
+    /// the canonical deployment on the configured chain.
+    settlement: Option<H160>,
+
+    /// The tokens the inventory trades.
+    tokens: Vec<InventoryTokenConfig>,
+}
+
+#[serde_as]
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct InventoryTokenConfig {
+    token: H160,
+
+    /// The reference price of the token in wei per 10^18 atoms of the token.
+    #[serde_as(as = "serialize::U256")]
+    price: eth::U256,
+
+    /// The maximum amount of the token paid out per fill.
+    #[serde_as(as = "serialize::U256")]
+    max_amount: eth::U256,
+}
+
+#[serde_as]
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct BufferConfig {
+    token: H160,
+
+    /// The balance reserved from internalization. Balances below it should be
+    /// topped up.
+    #[serde_as(as = "serialize::U256")]
+    min: eth::U256,
+
+    /// The balance above which the excess should be withdrawn.
+    #[serde_as(as = "serialize::U256")]
+    max: eth::U256,
+}
+
+fn default_inventory_report_interval_secs() -> u64 {
+    60 * 60
+}
+
+/// Configuration of the resting orders of the order book
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct OrderBookConfig {
+    /// The URL of the order book API, e.g. `https://api.cow.fi/mainnet/`.
+    url: Url,
+
+    /// How often the open orders get synced in seconds.
+    #[serde(default = "default_order_book_sync_interval_secs")]
+    sync_interval_secs: u64,
+}
+
+fn default_order_book_sync_interval_secs() -> u64 {
+    10
+}
+
+fn default_external_strategies_timeout_ms() -> u64 {
+    2_000
+}
+
+fn default_price_guard_max_deviation() -> f64 {
+    0.1
+}
+
+fn default_chainlink_max_age_secs() -> u64 {
+    24 * 60 * 60
+}
+
+fn default_twap_window_secs() -> u64 {
+    30 * 60
+}
+
+/// Configuration for the per token pair statistics
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct StatsConfig {
+    /// The rolling time window the statistics cover in seconds.
+    #[serde(default = "default_stats_window_secs")]
+    window_secs: u64,
+
+    /// The address of this solver in the solver competition. Required for
+    /// computing win rates from the competition data of the CoW API.
+    solver_address: Option<H160>,
+}
+
+impl Default for StatsConfig {
+    fn default() -> Self {
+        Self {
+            window_secs: default_stats_window_secs(),
+            solver_address: None,
+        }
+    }
+}
+
+fn default_stats_window_secs() -> u64 {
+    24 * 60 * 60
+}
+
+fn default_quote_max_liquidity_per_pair() -> usize {
+    5
+}
+
+fn default_quote_time_budget_ms() -> u64 {
+    1000
+}
+
+fn default_timeout_ms() -> u64 {
+    5000
+}
+
+fn default_protocols() -> Vec<String> {
+    vec!["balancer_v2".to_string(), "uniswap_v2".to_string()]
+}
+
+/// Load the driver configuration from a TOML file.
+///
+/// # Panics
+///
+/// This method panics if the config is invalid or on I/O errors.
+pub async fn load(path: &Path) -> solver::Config {
+    let config = parse(path).await;
+    let rounding = match config.rounding {
+        RoundingConfig::Strict => balancer_rounding::Rounding::Strict,
+        RoundingConfig::Nearest => {
+            tracing::warn!("rounding the pool math to the nearest value, solutions may revert");
+            balancer_rounding::Rounding::Nearest
+        }
+    };
+    balancer_rounding::set(rounding)
+        .unwrap_or_else(|err| panic!("invalid configuration: `rounding`: {err}"));
+    let weth = resolve_weth(config.chain_id, config.weth).unwrap_or_else(|err| panic!("{err}"));
+    check_routing(
+        config.base_tokens.len(),
+        config.max_hops,
+        config.max_partial_attempts,
+    )
+    .unwrap_or_else(|err| panic!("{err}"));
+
+    let price_guard = config.price_guard.map(|guard| {
+        let node_url = guard
+            .node_url
+            .or_else(|| config.node_url.clone())
+            .unwrap_or_else(|| {
+                panic!("invalid configuration: `price-guard` requires a node URL to read oracles")
+            });
+        let feeds = guard
+            .feeds
+            .into_iter()
+            .map(|feed| match feed {
+                OracleFeedConfig::Chainlink {
+                    token,
+                    aggregator,
+                    max_age_secs,
+                } => (
+                    eth::TokenAddress(token),
+                    infra::oracle::Feed::Chainlink {
+                        aggregator,
+                        max_age: std::time::Duration::from_secs(max_age_secs),
+                    },
+                ),
+                OracleFeedConfig::UniswapV3Twap {
+                    token,
+                    pool,
+                    window_secs,
+                } => (
+                    eth::TokenAddress(token),
+                    infra::oracle::Feed::UniswapV3Twap {
+                        pool,
+                        window: std::time::Duration::from_secs(window_secs),
+                    },
+                ),
+            })
+            .collect();
+        price_guard::Config {
+            oracle: infra::oracle::Oracle::new(&node_url, weth, feeds),
+            max_deviation: guard.max_deviation,
+            action: match guard.action {
+                PriceGuardAction::Flag => price_guard::Action::Flag,
+                PriceGuardAction::Reject => price_guard::Action::Reject,
+            },
+        }
+    });
+
+    let slippage_tracking = config.slippage_tracking.map(|tracking| {
+        let node_url = tracking
+            .node_url
+            .or_else(|| config.node_url.clone())
+            .unwrap_or_else(|| {
+                panic!(
+                    "invalid configuration: `slippage-tracking` requires a node URL to fetch \
+                     receipts"
+                )
+            });
+        solver::SlippageTrackingConfig {
+            window: std::time::Duration::from_secs(tracking.window_secs),
+            node_url,
+        }
+    });
+
+    let lp_intents = config.lp_intents.map(|lp| {
+        let node_url = lp
+            .node_url
+            .or_else(|| config.node_url.clone())
+            .unwrap_or_else(|| {
+                panic!("invalid configuration: `lp-intents` requires a node URL to read pools")
+            });
+        let vault = config.vault_address.unwrap_or_else(|| {
+            panic!("invalid configuration: `lp-intents` requires `vault-address`")
+        });
+        let settlement = lp
+            .settlement
+            .or_else(|| {
+                config.chain_id.and_then(|chain| {
+                    ::contracts::alloy::GPv2Settlement::deployment_address(&chain.id())
+                        .map(IntoLegacy::into_legacy)
+                })
+            })
+            .unwrap_or_else(|| {
+                panic!("invalid configuration: `lp-intents` requires a settlement address")
+            });
+        lp::Config {
+            vault: eth::Address(vault),
+            settlement: eth::Address(settlement),
+            total_supply: infra::total_supply::TotalSupply::new(&node_url),
+            slippage_bps: lp.slippage_bps,
+        }
+    });
+
+    let rival_aware_bidding = config.rival_aware_bidding.map(|bidding| {
+        assert!(
+            config.stats.solver_address.is_some(),
+            "invalid configuration: `rival-aware-bidding` requires `stats.solver-address`"
+        );
+        bidding::Config {
+            max_surplus_share: SurplusShare::new(bidding.max_surplus_share_bps).unwrap_or_else(
+                || {
+                    panic!(
+                        "invalid configuration: `rival-aware-bidding.max-surplus-share-bps` must \
+                         not exceed 10000"
+                    )
+                },
+            ),
+            min_samples: bidding.min_samples,
+            max_samples: bidding.max_samples,
+        }
+    });
+
+    let explorer = config.explorer_endpoint.then(|| {
+        config
+            .auction_save_directory
+            .as_ref()
+            .map(std::path::PathBuf::from)
+            .or_else(|| {
+                config.artifact_sinks.iter().find_map(|sink| match sink {
+                    ArtifactSinkConfig::File { directory } => Some(directory.clone()),
+                    _ => None,
+                })
+            })
+            .expect("invalid configuration: `explorer-endpoint` requires a file artifact sink")
+    });
+
+    solver::Config {
+        chain_id: config.chain_id.map(|c| c as u64).unwrap_or(1),
+        weth,
+        base_tokens: config
+            .base_tokens
+            .into_iter()
+            .map(eth::TokenAddress)
+            .collect(),
+        max_hops: config.max_hops,
+        max_partial_attempts: config.max_partial_attempts,
+        surplus_share: SurplusShare::new(config.surplus_share_bps).unwrap_or_else(|| {
+            panic!("invalid configuration: `surplus-share-bps` must not exceed 10000")
+        }),
+        solution_gas_offset: config.solution_gas_offset.into(),
+        max_solution_gas: config.max_solution_gas.map(|gas| eth::Gas(gas.into())),
+        max_concurrent_orders: config.max_concurrent_orders,
+        merge_solutions: config.merge_solutions,
+        internalize_interactions: config.internalize_interactions,
+        native_token_price_estimation_amount: config.native_token_price_estimation_amount,
+        uni_v3_node_url: config.uni_v3_node_url,
+        erc4626_node_url: config.erc4626_node_url,
+        liquidity_client_config: config.liquidity,
+        artifact_sinks: config
+            .auction_save_directory
+            .map(|directory| infra::artifacts::SinkConfig::File {
+                directory: directory.into(),
+            })
+            .into_iter()
+            .chain(config.artifact_sinks.into_iter().map(|sink| match sink {
+                ArtifactSinkConfig::File { directory } => {
+                    infra::artifacts::SinkConfig::File { directory }
+                }
+                ArtifactSinkConfig::S3 { bucket, prefix } => {
+                    infra::artifacts::SinkConfig::S3 { bucket, prefix }
+                }
+                ArtifactSinkConfig::Postgres { url, table } => {
+                    infra::artifacts::SinkConfig::Postgres { url, table }
+                }
+            }))
+            .collect(),
+        redact_saved_files: config.redact_saved_files,
+        vault_address: config.vault_address.map(eth::Address),
+        batch_router_address: config.batch_router_address.map(eth::Address),
+        erc4626_buffers: config
+            .erc4626_buffers
+            .into_iter()
+            .map(eth::TokenAddress)
+            .collect(),
+        node_url: config.node_url,
+        quote: config.quote.map(|quote| solver::QuoteConfig {
+            max_liquidity_per_pair: quote.max_liquidity_per_pair,
+            time_budget: std::time::Duration::from_millis(quote.time_budget_ms),
+            matrix_pairs: quote
+                .matrix_pairs
+                .into_iter()
+                .map(|[a, b]| (eth::TokenAddress(a), eth::TokenAddress(b)))
+                .collect(),
+        }),
+        quote_batching_window: config
+            .quote_batching_window_ms
+            .map(std::time::Duration::from_millis),
+        stats: solver::StatsConfig {
+            window: std::time::Duration::from_secs(config.stats.window_secs),
+            solver_address: config.stats.solver_address.map(eth::Address),
+        },
+        routing_api_token: config.routing_api_token,
+        math_eval: config.math_eval_endpoint,
+        heap_debug: config.heap_debug_endpoint,
+        explorer,
+        inventory: config.inventory.map(|inventory| inventory::Config {
+            buffers: inventory
+                .buffers
+                .into_iter()
+                .map(|buffer| {
+                    assert!(
+                        buffer.min <= buffer.max,
+                        "invalid configuration: inventory buffer `min` of {:?} exceeds its `max`",
+                        buffer.token,
+                    );
+                    let bounds = inventory::Bounds {
+                        min: buffer.min,
+                        max: buffer.max,
+                    };
+                    (eth::TokenAddress(buffer.token), bounds)
+                })
+                .collect(),
+            report_interval: std::time::Duration::from_secs(inventory.report_interval_secs),
+            fills: inventory.fills.map(|fills| {
+                let settlement = fills
+                    .settlement
+                    .or_else(|| {
+                        config.chain_id.and_then(|chain| {
+                            ::contracts::alloy::GPv2Settlement::deployment_address(&chain.id())
+                                .map(IntoLegacy::into_legacy)
+                        })
+                    })
+                    .unwrap_or_else(|| {
+                        panic!(
+                            "invalid configuration: inventory fills require a settlement address"
+                        )
+                    });
+                inventory::Fills {
+                    account: fills.account.map(eth::Address),
+                    settlement: eth::Address(settlement),
+                    tokens: fills
+                        .tokens
+                        .into_iter()
+                        .map(|token| {
+                            assert!(
+                                !token.price.is_zero(),
+                                "invalid configuration: inventory price of {:?} is zero",
+                                token.token,
+                            );
+                            let fill = inventory::FillToken {
+                                price: auction::Price(eth::Ether(token.price)),
+                                max_amount: token.max_amount,
+                            };
+                            (eth::TokenAddress(token.token), fill)
+                        })
+                        .collect(),
+                }
+            }),
+        }),
+        auto_base_tokens: config.auto_base_tokens.map(|auto| base_tokens::Config {
+            count: auto.count,
+            interval: std::time::Duration::from_secs(auto.interval_secs),
+            min_pool_tvl: eth::Ether(auto.min_pool_tvl),
+            path: auto.path,
+        }),
+        rival_aware_bidding,
+        token_denylist: config.token_denylist.map(|denylist| {
+            infra::denylist::Denylist::new(
+                denylist.tokens.into_iter().map(eth::TokenAddress),
+                denylist.url.map(|url| infra::denylist::Remote {
+                    url,
+                    interval: std::time::Duration::from_secs(denylist.sync_interval_secs),
+                }),
+            )
+        }),
+        bad_token_detection: config
+            .bad_token_detection
+            .map(|detection| bad_tokens::Config {
+                failure_ratio: detection.failure_ratio,
+                required_measurements: detection.required_measurements,
+                freeze_time: std::time::Duration::from_secs(detection.freeze_time_secs),
+            }),
+        slippage_tracking,
+        order_validation: config
+            .order_validation
+            .map(|validation| solver::OrderValidationConfig {
+                check_balances: validation.check_balances,
+            }),
+        price_guard,
+        lp_intents,
+        strategies: config.external_strategies.map(|strategies| {
+            infra::strategies::Strategies::new(
+                strategies
+                    .endpoints
+                    .into_iter()
+                    .map(|endpoint| infra::strategies::Strategy {
+                        name: endpoint.name,
+                        url: endpoint.url,
+                    })
+                    .collect(),
+                std::time::Duration::from_millis(strategies.timeout_ms),
+            )
+        }),
+        price_comparison: config.price_comparison.map(|comparison| {
+            assert!(
+                (0.0..=1.0).contains(&comparison.sample_rate),
+                "invalid configuration: `price-comparison.sample-rate` must be between 0 and 1"
+            );
+            infra::price_comparison::Config {
+                apis: comparison
+                    .apis
+                    .into_iter()
+                    .map(|api| match api {
+                        PriceApiConfig::OneInch { url, api_key } => {
+                            infra::price_comparison::Api::OneInch { url, api_key }
+                        }
+                        PriceApiConfig::ZeroEx { url, api_key } => {
+                            infra::price_comparison::Api::ZeroEx { url, api_key }
+                        }
+                        PriceApiConfig::Paraswap { url } => {
+                            infra::price_comparison::Api::Paraswap { url }
+                        }
+                    })
+                    .collect(),
+                sample_rate: comparison.sample_rate,
+                timeout: std::time::Duration::from_millis(comparison.timeout_ms),
+            }
+        }),
+        order_book: config.order_book.map(|book| {
+            infra::order_book::OrderBook::new(
+                book.url,
+                std::time::Duration::from_secs(book.sync_interval_secs),
+            )
+        }),
+    }
+}
+
+/// Loads the settings of an embedded solver engine from a TOML file in the
+/// format of the driver configuration. Settings of features that aren't
+/// available when embedding the engine are ignored.
+///
+/// Unlike [`load`], this returns invalid configurations and I/O errors
+/// instead of panicking.
+pub async fn load_embedded(path: &Path) -> anyhow::Result<embed::Config> {
+    let config = try_parse(path).await?;
+    Ok(embed::Config {
+        chain_id: config.chain_id.map(|chain| chain.id()),
+        weth: config.weth,
+        base_tokens: config.base_tokens,
+        max_hops: config.max_hops,
+        max_partial_attempts: config.max_partial_attempts,
+        surplus_share_bps: config.surplus_share_bps,
+        solution_gas_offset: config.solution_gas_offset,
+        max_solution_gas: config.max_solution_gas,
+        merge_solutions: config.merge_solutions,
+        native_token_price_estimation_amount: config.native_token_price_estimation_amount,
+        uni_v3_node_url: config.uni_v3_node_url,
+        erc4626_node_url: config.erc4626_node_url,
+    })
+}
+
+/// Resolves the settings of an embedded solver engine into the solver
+/// configuration. All features besides routing are disabled, as they are
+/// either served by the API or run in the background.
+pub fn embedded(config: &embed::Config) -> anyhow::Result<solver::Config> {
+    let chain_id = config
+        .chain_id
+        .map(|chain_id| {
+            Chain::try_from(chain_id)
+                .ok()
+                .with_context(|| format!("invalid configuration: unsupported chain {chain_id}"))
+        })
+        .transpose()?;
+    check_routing(
+        config.base_tokens.len(),
+        config.max_hops,
+        config.max_partial_attempts,
+    )?;
+    Ok(solver::Config {
+        chain_id: config.chain_id.unwrap_or(1),
+        weth: resolve_weth(chain_id, config.weth)?,
+        base_tokens: config
+            .base_tokens
+            .iter()
+            .copied()
+            .map(eth::TokenAddress)
+            .collect(),
+        max_hops: config.max_hops,
+        max_partial_attempts: config.max_partial_attempts,
+        surplus_share: SurplusShare::new(config.surplus_share_bps)
+            .context("invalid configuration: `surplus-share-bps` must not exceed 10000")?,
+        solution_gas_offset: config.solution_gas_offset.into(),
+        max_solution_gas: config.max_solution_gas.map(|gas| eth::Gas(gas.into())),
+        max_concurrent_orders: default_max_concurrent_orders(),
+        merge_solutions: config.merge_solutions,
+        internalize_interactions: default_internalize_interactions(),
+        native_token_price_estimation_amount: config.native_token_price_estimation_amount,
+        uni_v3_node_url: config.uni_v3_node_url.clone(),
+        erc4626_node_url: config.erc4626_node_url.clone(),
+        liquidity_client_config: None,
+        artifact_sinks: Vec::new(),
+        redact_saved_files: false,
+        vault_address: None,
+        batch_router_address: None,
+        erc4626_buffers: Vec::new(),
+        node_url: None,
+        quote: None,
+        quote_batching_window: None,
+        stats: solver::StatsConfig {
+            window: std::time::Duration::from_secs(default_stats_window_secs()),
+            solver_address: None,
+        },
+        routing_api_token: None,
+        order_validation: None,
+        token_denylist: None,
+        bad_token_detection: None,
+        slippage_tracking: None,
+        price_guard: None,
+        lp_intents: None,
+        strategies: None,
+        price_comparison: None,
+        order_book: None,
+        math_eval: false,
+        heap_debug: false,
+        explorer: None,
+        inventory: None,
+        rival_aware_bidding: None,
+        auto_base_tokens: None,
+    })
+}
+
+/// Returns the WETH address, either the canonical one of the chain or the
+/// configured one.
+fn resolve_weth(chain_id: Option<Chain>, weth: Option<H160>) -> anyhow::Result<eth::WethAddress> {
+    match (chain_id, weth) {
+        (Some(chain_id), None) => Ok(contracts::Contracts::for_chain(chain_id).weth),
+        (None, Some(weth)) => Ok(eth::WethAddress(weth)),
+        (Some(_), Some(_)) => anyhow::bail!(
+            "invalid configuration: cannot specify both `chain-id` and `weth` configuration \
+             options"
+        ),
+        (None, None) => anyhow::bail!(
+            "invalid configuration: must specify either `chain-id` or `weth` configuration options"
+        ),
+    }
+}
+
+/// Checks that the routing parameters are within the bounds path finding stays
+/// feasible in. Updates through the routing API are held to the same bounds.
+fn check_routing(
+    base_tokens: usize,
+    max_hops: usize,
+    max_partial_attempts: usize,
+) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        max_hops <= solver::Routing::MAX_HOPS,
+        "invalid configuration: `max-hops` must not exceed {}",
+        solver::Routing::MAX_HOPS,
+    );
+    anyhow::ensure!(
+        base_tokens <= solver::Routing::MAX_BASE_TOKENS,
+        "invalid configuration: `base-tokens` must not have more than {} tokens",
+        solver::Routing::MAX_BASE_TOKENS,
+    );
+    anyhow::ensure!(
+        max_partial_attempts > 0,
+        "invalid configuration: `max-partial-attempts` must be at least 1"
+    );
+    Ok(())
+}
+
+/// Validates the configuration file like [`load`] does without starting the
+/// solver, returning the effective configuration with defaults applied and
+/// contract addresses resolved for the configured chain. Secrets are
+/// redacted.
+///
+/// # Panics
+///
+/// This method panics if the config is invalid or on I/O errors.
+pub async fn check(path: &Path) -> String {
+    let resolved = load(path).await;
+    let mut config = parse(path).await;
+    if config.routing_api_token.is_some() {
+        config.routing_api_token = Some("<redacted>".to_owned());
+    }
+    for api in config
+        .price_comparison
+        .iter_mut()
+        .flat_map(|comparison| &mut comparison.apis)
+    {
+        if let PriceApiConfig::OneInch { api_key, .. } | PriceApiConfig::ZeroEx { api_key, .. } =
+            api
+        {
+            *api_key = "<redacted>".to_owned();
+        }
+    }
+    for sink in &mut config.artifact_sinks {
+        if let ArtifactSinkConfig::Postgres { url, .. } = sink
+            && url.password().is_some()
+        {
+            let _ = url.set_password(Some("<redacted>"));
+        }
+    }
+    format!(
+        "{config:#?}\n\nresolved contracts:\n  weth: {:?}\n  lp-intents settlement: {:?}",
+        resolved.weth.0,
+        resolved.lp_intents.map(|lp| lp.settlement.0),
+    )
+}
+
+/// Loads the listeners the API is served on from the TOML file at the
+/// specified path.
+pub async fn listeners(path: &Path) -> Vec<shared::listen::Listener> {
+    parse(path)
+        .await
+        .listen
+        .into_iter()
+        .map(|listener| shared::listen::Listener {
+            address: listener.address,
+            dual_stack: listener.dual_stack,
+            tls: listener.tls.map(|tls| shared::listen::Tls {
+                certificate: tls.certificate,
+                private_key: tls.private_key,
+                client_ca: tls.client_ca,
+            }),
+        })
+        .collect()
+}
+
+async fn parse(path: &Path) -> Config {
+    try_parse(path)
+        .await
+        .unwrap_or_else(|err| panic!("{err:#}"))
+}
+
+async fn try_parse(path: &Path) -> anyhow::Result<Config> {
+    let data = fs::read_to_string(path)
+        .await
+        .with_context(|| format!("I/O error while reading {path:?}"))?;
+    // Not printing detailed error because it could potentially leak secrets.
+    or_log(toml::de::from_str::<Config>(&data), &path)
+}
+
+/// Returns the result or an error describing the `TOML` parsing error.
+fn or_log<T, E, P>(result: Result<T, E>, path: &P) -> anyhow::Result<T>
+where
+    E: Debug,
+    P: Debug,
+{
+    result.map_err(|err| {
+        if std::env::var("TOML_TRACE_ERROR").is_ok_and(|v| v == "1") {
+            anyhow::anyhow!("failed to parse TOML config at {path:?}: {err:#?}")
+        } else {
+            anyhow::anyhow!(
+                "failed to parse TOML config at: {path:?}. Set TOML_TRACE_ERROR=1 to print \
+                 parsing error but this may leak secrets."
+            )
+        }
+    })
+}
+
+/// Returns minimum gas used for settling a single order.
+/// (not accounting for the cost of additional interactions)
+fn default_gas_offset() -> i64 {
+    SETTLEMENT_OVERHEAD.try_into().unwrap()
+}
+
+fn default_max_concurrent_orders() -> usize {
+    8
+}
@@ -0,0 +1,152 @@
+//! A denylist of tokens the solver refuses to trade.
+//!
+//! The list combines statically configured tokens with an unsupported token
+//! list that is periodically pulled from a remote source (such as a list
+//! maintained from CoW's bad token detection). Orders trading denied tokens
+//! are dropped when auctions are ingested.
+
+use {
+    crate::{
+        domain::{auction, eth},
+        infra::metrics,
+    },
+    reqwest::{Client, Url},
+    serde::Deserialize,
+    std::{
+        collections::HashSet,
+        sync::{Arc, RwLock},
+        time::Duration,
+    },
+};
+
+/// A remote list of unsupported tokens to keep in sync with.
+pub struct Remote {
+    pub url: Url,
+    pub interval: Duration,
+}
+
+#[derive(Clone)]
+pub struct Denylist {
+    local: Arc<HashSet<eth::TokenAddress>>,
+    remote: Arc<RwLock<HashSet<eth::TokenAddress>>>,
+}
+
+impl Denylist {
+    /// Creates a new denylist, spawning a background task syncing the remote
+    /// list if one is specified.
+    pub fn new(local: impl IntoIterator<Item = eth::TokenAddress>, remote: Option<Remote>) -> Self {
+        let denylist = Self {
+            local: Arc::new(local.into_iter().collect()),
+            remote: Default::default(),
+        };
+        if let Some(remote) = remote {
+            tokio::spawn(sync(Arc::downgrade(&denylist.remote), remote));
+        }
+        denylist
+    }
+
+    pub fn contains(&self, token: &eth::TokenAddress) -> bool {
+        self.local.contains(token) || self.remote.read().unwrap().contains(token)
+    }
+
+    /// Removes all orders trading denied tokens from the auction.
+    pub fn filter(&self, auction: &mut auction::Auction) {
+        auction.orders.retain(|order| {
+            let denied = [order.sell.token, order.buy.token]
+                .into_iter()
+                .filter(|token| self.contains(token))
+                .collect::<Vec<_>>();
+            for token in &denied {
+                tracing::debug!(uid = %order.uid, ?token, "filtering order trading denied token");
+                metrics::denied_token_order(token);
+            }
+            denied.is_empty()
+        });
+    }
+}
+
+/// The formats supported for remote token lists: either a plain array of
+/// token addresses or a token list with an address per entry.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TokenList {
+    Addresses(Vec<eth::H160>),
+    TokenList { tokens: Vec<Entry> },
+}
+
+#[derive(Deserialize)]
+struct Entry {
+    address: eth::H160,
+}
+
+impl TokenList {
+    fn into_tokens(self) -> HashSet<eth::TokenAddress> {
+        match self {
+            Self::Addresses(addresses) => addresses.into_iter().map(eth::TokenAddress).collect(),
+            Self::TokenList { tokens } => tokens
+                .into_iter()
+                .map(|entry| eth::TokenAddress(entry.address))
+                .collect(),
+        }
+    }
+}
+
+/// Periodically replaces the remote tokens with the latest remote list until
+/// the denylist is dropped. The previous list is kept if fetching fails.
+async fn sync(tokens: std::sync::Weak<RwLock<HashSet<eth::TokenAddress>>>, remote: Remote) {
+    let client = Client::new();
+    loop {
+        match fetch(&client, &remote.url).await {
+            Ok(list) => {
+                let Some(tokens) = tokens.upgrade() else {
+                    return;
+                };
+                tracing::debug!(count = list.len(), "synced unsupported token list");
+                *tokens.write().unwrap() = list;
+            }
+            Err(err) => tracing::warn!(?err, url = %remote.url, "failed to sync token denylist"),
+        }
+        tokio::time::sleep(remote.interval).await;
+        if tokens.strong_count() == 0 {
+            return;
+        }
+    }
+}
+
+async fn fetch(client: &Client, url: &Url) -> reqwest::Result<HashSet<eth::TokenAddress>> {
+    let list = client
+        .get(url.clone())
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<TokenList>()
+        .await?;
+    Ok(list.into_tokens())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_token_lists() {
+        let token = eth::TokenAddress(eth::H160::repeat_byte(0x11));
+        let addresses: TokenList =
+            serde_json::from_str(r#"["0x1111111111111111111111111111111111111111"]"#).unwrap();
+        assert_eq!(addresses.into_tokens(), HashSet::from([token]));
+
+        let token_list: TokenList = serde_json::from_str(
+            r#"{
+                "name": "Unsupported Tokens",
+                "tokens": [
+                    {
+                        "address": "0x1111111111111111111111111111111111111111",
+                        "symbol": "BAD"
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(token_list.into_tokens(), HashSet::from([token]));
+    }
+}
@@ -0,0 +1,223 @@
+//! Heap statistics of the solver engine.
+//!
+//! mimalloc is the global allocator, so its process statistics describe the
+//! heap of the solver engine. On top of that, the allocator attributes the
+//! allocations made while polling futures tracked with [`track`] to a
+//! subsystem, so that the allocation volume of e.g. the pool cache can be told
+//! apart from the one of building solutions when planning capacity.
+
+use {
+    serde::Serialize,
+    std::{
+        alloc::{GlobalAlloc, Layout},
+        cell::Cell,
+        future::Future,
+        sync::atomic::{AtomicU64, Ordering},
+    },
+};
+
+/// The global allocator: mimalloc, counting the allocations of subsystems.
+pub struct Allocator;
+
+unsafe impl GlobalAlloc for Allocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        record(layout.size());
+        unsafe { mimalloc::MiMalloc.alloc(layout) }
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        record(layout.size());
+        unsafe { mimalloc::MiMalloc.alloc_zeroed(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { mimalloc::MiMalloc.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        record(new_size.saturating_sub(layout.size()));
+        unsafe { mimalloc::MiMalloc.realloc(ptr, layout, new_size) }
+    }
+}
+
+/// A part of the solver engine allocations get attributed to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Subsystem {
+    /// Fetching and converting auction liquidity and caching the routes of
+    /// the quote matrix.
+    PoolCache,
+    /// Path search and building solutions.
+    SolutionBuilder,
+}
+
+impl Subsystem {
+    pub const ALL: [Subsystem; 2] = [Subsystem::PoolCache, Subsystem::SolutionBuilder];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Subsystem::PoolCache => "pool_cache",
+            Subsystem::SolutionBuilder => "solution_builder",
+        }
+    }
+}
+
+thread_local! {
+    static CURRENT: Cell<Option<Subsystem>> = const { Cell::new(None) };
+}
+
+struct Counter {
+    allocations: AtomicU64,
+    bytes: AtomicU64,
+}
+
+static COUNTERS: [Counter; Subsystem::ALL.len()] = [const {
+    Counter {
+        allocations: AtomicU64::new(0),
+        bytes: AtomicU64::new(0),
+    }
+}; Subsystem::ALL.len()];
+
+fn record(bytes: usize) {
+    // The thread local is gone while the thread shuts down.
+    let Ok(Some(subsystem)) = CURRENT.try_with(Cell::get) else {
+        return;
+    };
+    let counter = &COUNTERS[subsystem as usize];
+    counter.allocations.fetch_add(1, Ordering::Relaxed);
+    counter.bytes.fetch_add(bytes as u64, Ordering::Relaxed);
+}
+
+/// Runs the function attributing its allocations to the subsystem.
+pub fn scope<T>(subsystem: Subsystem, f: impl FnOnce() -> T) -> T {
+    struct Reset(Option<Subsystem>);
+
+    impl Drop for Reset {
+        fn drop(&mut self) {
+            CURRENT.set(self.0);
+        }
+    }
+
+    let _reset = Reset(CURRENT.replace(Some(subsystem)));
+    f()
+}
+
+/// Awaits the future attributing the allocations made while polling it to
+/// the subsystem. Tasks the future spawns are not tracked.
+pub async fn track<F: Future>(subsystem: Subsystem, future: F) -> F::Output {
+    let mut future = std::pin::pin!(future);
+    std::future::poll_fn(|cx| scope(subsystem, || future.as_mut().poll(cx))).await
+}
+
+/// A snapshot of the heap statistics.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Stats {
+    pub process: Process,
+    pub subsystems: Vec<Allocations>,
+}
+
+/// The memory usage of the process as seen by mimalloc.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Process {
+    pub current_rss: u64,
+    pub peak_rss: u64,
+    pub current_commit: u64,
+    pub peak_commit: u64,
+    pub page_faults: u64,
+}
+
+/// The allocations attributed to a subsystem since the start of the process.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Allocations {
+    pub subsystem: &'static str,
+    pub allocations: u64,
+    pub allocated_bytes: u64,
+}
+
+/// Returns the current heap statistics.
+pub fn stats() -> Stats {
+    let mut info = [0usize; 8];
+    let [
+        elapsed,
+        user,
+        system,
+        current_rss,
+        peak_rss,
+        current_commit,
+        peak_commit,
+        faults,
+    ] = &mut info;
+    // SAFETY: mimalloc only writes the statistics to the passed pointers.
+    unsafe {
+        libmimalloc_sys::mi_process_info(
+            elapsed,
+            user,
+            system,
+            current_rss,
+            peak_rss,
+            current_commit,
+            peak_commit,
+            faults,
+        );
+    }
+    let [
+        ..,
+        current_rss,
+        peak_rss,
+        current_commit,
+        peak_commit,
+        page_faults,
+    ] = info.map(|value| value as u64);
+    Stats {
+        process: Process {
+            current_rss,
+            peak_rss,
+            current_commit,
+            peak_commit,
+            page_faults,
+        },
+        subsystems: Subsystem::ALL
+            .iter()
+            .map(|subsystem| {
+                let counter = &COUNTERS[*subsystem as usize];
+                Allocations {
+                    subsystem: subsystem.name(),
+                    allocations: counter.allocations.load(Ordering::Relaxed),
+                    allocated_bytes: counter.bytes.load(Ordering::Relaxed),
+                }
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scopes_nest() {
+        let current = || CURRENT.with(Cell::get);
+        scope(Subsystem::PoolCache, || {
+            assert_eq!(current(), Some(Subsystem::PoolCache));
+            scope(Subsystem::SolutionBuilder, || {
+                assert_eq!(current(), Some(Subsystem::SolutionBuilder));
+            });
+            assert_eq!(current(), Some(Subsystem::PoolCache));
+        });
+        assert_eq!(current(), None);
+    }
+
+    #[tokio::test]
+    async fn tracks_polls_of_futures() {
+        let current = || CURRENT.with(Cell::get);
+        let subsystem = track(Subsystem::SolutionBuilder, async {
+            tokio::task::yield_now().await;
+            current()
+        })
+        .await;
+        assert_eq!(subsystem, Some(Subsystem::SolutionBuilder));
+        assert_eq!(current(), None);
+    }
+}
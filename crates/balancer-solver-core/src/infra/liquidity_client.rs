@@ -0,0 +1,175 @@
+use {
+    crate::{domain::eth, infra::config::LiquidityConfig},
+    anyhow::Context,
+    reqwest::Client,
+    serde::{Deserialize, Serialize},
+    std::time::Duration,
+    tracing,
+};
+
+/// HTTP client for fetching liquidity data from the liquidity-driver API
+#[derive(Clone)]
+pub struct LiquidityClient {
+    client: Client,
+    base_url: String,
+    timeout: Duration,
+}
+
+impl LiquidityClient {
+    /// Creates a client for the configured liquidity-driver.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the configured certificates can't be read.
+    pub fn new(config: &LiquidityConfig) -> Self {
+        Self {
+            client: client(config).unwrap_or_else(|err| {
+                panic!("invalid TLS configuration of the liquidity client: {err:#}")
+            }),
+            base_url: config.driver_url.clone(),
+            timeout: Duration::from_millis(config.timeout_ms),
+        }
+    }
+
+    /// Fetch liquidity data for the specified token pairs and protocols. The
+    /// deadline, if any, bounds the request and is forwarded to the
+    /// liquidity-driver.
+    pub async fn fetch_liquidity(
+        &self,
+        request: LiquidityRequest,
+        deadline: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<LiquidityResponse, LiquidityClientError> {
+        tracing::debug!(
+            auction_id = request.auction_id,
+            pairs_count = request.token_pairs.len(),
+            "Fetching liquidity from driver API"
+        );
+
+        let mut timeout = self.timeout;
+        let mut builder = self
+            .client
+            .post(&format!("{}/api/v1/liquidity", self.base_url))
+            .json(&request);
+        if let Some(deadline) = deadline {
+            let remaining = (deadline - chrono::Utc::now())
+                .to_std()
+                .map_err(|_| LiquidityClientError::DeadlineExceeded)?;
+            timeout = timeout.min(remaining);
+            builder = builder.header(
+                shared::request_deadline::X_DEADLINE,
+                deadline.to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            );
+        }
+        let response = builder.timeout(timeout).send().await.map_err(|err| {
+            if err.is_timeout() && timeout < self.timeout {
+                LiquidityClientError::DeadlineExceeded
+            } else {
+                LiquidityClientError::Http(err)
+            }
+        })?;
+
+        if !response.status().is_success() {
+            return Err(LiquidityClientError::HttpStatus(response.status()));
+        }
+
+        let api_response: ApiLiquidityResponse =
+            response.json().await.map_err(LiquidityClientError::Json)?;
+
+        tracing::debug!(
+            auction_id = request.auction_id,
+            liquidity_count = api_response.result.liquidity.len(),
+            "Successfully fetched liquidity from driver API"
+        );
+
+        Ok(api_response.result)
+    }
+}
+
+/// Builds the HTTP client, trusting the configured certificate authorities and
+/// authenticating with the configured client certificate.
+fn client(config: &LiquidityConfig) -> anyhow::Result<Client> {
+    let read = |path: &std::path::Path| {
+        std::fs::read(path).with_context(|| format!("failed to read {path:?}"))
+    };
+    let mut builder = Client::builder();
+    if let Some(path) = &config.ca_certificate {
+        builder = builder.add_root_certificate(reqwest::Certificate::from_pem(&read(path)?)?);
+    }
+    if let Some(identity) = &config.client_certificate {
+        builder = builder.identity(reqwest::Identity::from_pkcs8_pem(
+            &read(&identity.certificate)?,
+            &read(&identity.private_key)?,
+        )?);
+    }
+    Ok(builder.build()?)
+}
+
+/// Request payload for the liquidity-driver API
+#[derive(Debug, Serialize)]
+pub struct LiquidityRequest {
+    pub auction_id: u64,
+    pub tokens: Vec<eth::H160>,
+    pub token_pairs: Vec<(eth::H160, eth::H160)>,
+    pub block_number: BlockTag,
+    pub protocols: Vec<String>,
+}
+
+/// The block to fetch the liquidity at. The liquidity-driver also accepts
+/// specific block numbers, but solving always wants the latest state.
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BlockTag {
+    Latest,
+}
+
+/// Response from the liquidity-driver API
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LiquidityResponse {
+    pub auction_id: u64,
+    pub liquidity: Vec<solvers_dto::auction::Liquidity>,
+    pub block_number: u64,
+    pub timestamp: u64,
+    /// Whether the liquidity-driver hit the request deadline before fetching
+    /// all liquidity.
+    #[serde(default)]
+    pub deadline_exceeded: bool,
+}
+
+/// Wrapper response from the API
+#[derive(Debug, Deserialize)]
+struct ApiLiquidityResponse {
+    result: LiquidityResponse,
+}
+
+#[derive(Debug)]
+pub enum LiquidityClientError {
+    Http(reqwest::Error),
+    HttpStatus(reqwest::StatusCode),
+    Json(reqwest::Error),
+    DeadlineExceeded,
+}
+
+impl std::fmt::Display for LiquidityClientError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LiquidityClientError::Http(e) => write!(f, "HTTP request failed: {}", e),
+            LiquidityClientError::HttpStatus(status) => {
+                write!(f, "HTTP request returned status: {}", status)
+            }
+            LiquidityClientError::Json(e) => write!(f, "Failed to parse JSON response: {}", e),
+            LiquidityClientError::DeadlineExceeded => write!(f, "Request deadline exceeded"),
+        }
+    }
+}
+
+impl std::error::Error for LiquidityClientError {}
+
+impl From<reqwest::Error> for LiquidityClientError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_decode() {
+            LiquidityClientError::Json(e)
+        } else {
+            LiquidityClientError::Http(e)
+        }
+    }
+}
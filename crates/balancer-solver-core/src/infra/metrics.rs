@@ -0,0 +1,273 @@
+use crate::{
+    domain::{auction, eth, price_guard, self_trade, solution},
+    infra::heap,
+};
+
+/// Metrics for the solver engine.
+#[derive(Debug, Clone, prometheus_metric_storage::MetricStorage)]
+#[metric(subsystem = "solver_engine")]
+struct Metrics {
+    /// The amount of time this solver engine has for solving.
+    #[metric(buckets(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15))]
+    time_limit: prometheus::Histogram,
+
+    /// The amount of time this solver engine has left when it finished solving.
+    #[metric(buckets(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15))]
+    remaining_time: prometheus::Histogram,
+
+    /// Errors that occurred during solving.
+    #[metric(labels("reason"))]
+    solve_errors: prometheus::IntCounterVec,
+
+    /// The number of solutions that were found.
+    solutions: prometheus::IntCounter,
+
+    /// The amount of time spent computing quotes on the lightweight quote
+    /// path.
+    #[metric(buckets(0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1, 2, 5))]
+    quote_time: prometheus::Histogram,
+
+    /// The number of quotes that ran out of their time budget.
+    quote_timeouts: prometheus::IntCounter,
+
+    /// The number of pieces of liquidity considered for quotes.
+    #[metric(buckets(0, 10, 25, 50, 100, 250, 500, 1000))]
+    quote_liquidity: prometheus::Histogram,
+
+    /// The number of quotes that were found.
+    quotes: prometheus::IntCounter,
+
+    /// The number of quotes answered from a batch of identical quotes
+    /// instead of being solved.
+    quote_batch_joins: prometheus::IntCounter,
+
+    /// The number of quotes solved over the cached routes of the quote
+    /// matrix.
+    matrix_quotes: prometheus::IntCounter,
+
+    /// The number of orders filtered out for trading a denied token.
+    #[metric(labels("token"))]
+    denied_token_orders: prometheus::IntCounterVec,
+
+    /// The number of pieces of liquidity skipped for charging swap fees of
+    /// 100% or more.
+    #[metric(labels("kind"))]
+    invalid_liquidity_fees: prometheus::IntCounterVec,
+
+    /// The number of orders pinned to liquidity that no route over the
+    /// pinned liquidity was found for.
+    route_pins_unsatisfied: prometheus::IntCounter,
+
+    /// The number of times a token got quarantined for failing settlements.
+    bad_tokens_detected: prometheus::IntCounter,
+
+    /// The number of solutions whose clearing prices deviate from the oracle
+    /// prices beyond the configured threshold.
+    #[metric(labels("action"))]
+    oracle_price_deviations: prometheus::IntCounterVec,
+
+    /// The number of solutions rejected for trading an account against
+    /// itself.
+    #[metric(labels("kind"))]
+    self_trades: prometheus::IntCounterVec,
+
+    /// The resident set size of the process in bytes, as seen by mimalloc.
+    #[metric(labels("kind"))]
+    heap_rss_bytes: prometheus::IntGaugeVec,
+
+    /// The memory committed by mimalloc in bytes.
+    #[metric(labels("kind"))]
+    heap_committed_bytes: prometheus::IntGaugeVec,
+
+    /// The number of page faults of the process.
+    heap_page_faults: prometheus::IntGauge,
+
+    /// The number of allocations made by a subsystem.
+    #[metric(labels("subsystem"))]
+    heap_allocations: prometheus::IntGaugeVec,
+
+    /// The number of bytes allocated by a subsystem.
+    #[metric(labels("subsystem"))]
+    heap_allocated_bytes: prometheus::IntGaugeVec,
+
+    /// The number of artifacts that failed to be saved to a sink.
+    #[metric(labels("sink"))]
+    artifact_sink_failures: prometheus::IntCounterVec,
+
+    /// The number of quotes compared against an external price API by
+    /// outcome.
+    #[metric(labels("api", "outcome"))]
+    price_comparisons: prometheus::IntCounterVec,
+
+    /// By how many basis points quotes are better or worse than the ones of
+    /// an external price API.
+    #[metric(labels("api", "outcome"), buckets(1, 5, 10, 20, 50, 100, 500))]
+    price_comparison_bps: prometheus::HistogramVec,
+
+    /// By how many basis points the prices settled swaps executed at are
+    /// better or worse than the prices they were quoted at, by kind of pool.
+    #[metric(labels("kind", "outcome"), buckets(1, 5, 10, 20, 50, 100, 500))]
+    settlement_slippage_bps: prometheus::HistogramVec,
+}
+
+/// Setup the metrics registry.
+pub fn init() {
+    observe::metrics::setup_registry_reentrant(Some("solver-engine".to_owned()), None);
+}
+
+pub fn solve(auction: &auction::Auction) {
+    get().time_limit.observe(
+        auction
+            .deadline
+            .remaining()
+            .unwrap_or_default()
+            .as_secs_f64(),
+    );
+}
+
+pub fn solved(deadline: &auction::Deadline, solutions: &[solution::Solution]) {
+    get()
+        .remaining_time
+        .observe(deadline.remaining().unwrap_or_default().as_secs_f64());
+    get().solutions.inc_by(solutions.len() as u64);
+}
+
+pub fn quote(auction: &auction::Auction) {
+    get()
+        .quote_liquidity
+        .observe(auction.liquidity.len() as f64);
+}
+
+pub fn quote_batched() {
+    get().quote_batch_joins.inc();
+}
+
+pub fn quote_timeout() {
+    get().quote_timeouts.inc();
+}
+
+pub fn matrix_quote() {
+    get().matrix_quotes.inc();
+}
+
+pub fn quoted(elapsed: std::time::Duration, solutions: &[solution::Solution]) {
+    observe::openmetrics::observe(&get().quote_time, elapsed.as_secs_f64());
+    get().quotes.inc_by(solutions.len() as u64);
+}
+
+pub fn denied_token_order(token: &eth::TokenAddress) {
+    get()
+        .denied_token_orders
+        .with_label_values(&[&format!("{:?}", token.0)])
+        .inc();
+}
+
+pub fn invalid_liquidity_fee(kind: &str) {
+    get()
+        .invalid_liquidity_fees
+        .with_label_values(&[kind])
+        .inc();
+}
+
+pub fn route_pin_unsatisfied() {
+    get().route_pins_unsatisfied.inc();
+}
+
+pub fn bad_token_detected() {
+    get().bad_tokens_detected.inc();
+}
+
+pub fn oracle_price_deviation(action: price_guard::Action) {
+    let action = match action {
+        price_guard::Action::Flag => "flag",
+        price_guard::Action::Reject => "reject",
+    };
+    get()
+        .oracle_price_deviations
+        .with_label_values(&[action])
+        .inc();
+}
+
+pub fn self_trade(violation: self_trade::Violation) {
+    get()
+        .self_trades
+        .with_label_values(&[violation.kind()])
+        .inc();
+}
+
+pub fn heap(stats: &heap::Stats) {
+    let metrics = get();
+    let gauge = |value: u64| i64::try_from(value).unwrap_or(i64::MAX);
+    let process = &stats.process;
+    for (kind, value) in [("current", process.current_rss), ("peak", process.peak_rss)] {
+        metrics
+            .heap_rss_bytes
+            .with_label_values(&[kind])
+            .set(gauge(value));
+    }
+    for (kind, value) in [
+        ("current", process.current_commit),
+        ("peak", process.peak_commit),
+    ] {
+        metrics
+            .heap_committed_bytes
+            .with_label_values(&[kind])
+            .set(gauge(value));
+    }
+    metrics.heap_page_faults.set(gauge(process.page_faults));
+    for allocations in &stats.subsystems {
+        metrics
+            .heap_allocations
+            .with_label_values(&[allocations.subsystem])
+            .set(gauge(allocations.allocations));
+        metrics
+            .heap_allocated_bytes
+            .with_label_values(&[allocations.subsystem])
+            .set(gauge(allocations.allocated_bytes));
+    }
+}
+
+pub fn artifact_sink_failed(sink: &str) {
+    get()
+        .artifact_sink_failures
+        .with_label_values(&[sink])
+        .inc();
+}
+
+pub fn price_comparison(api: &str, bps: Option<f64>) {
+    let metrics = get();
+    let outcome = match bps {
+        None => "failed",
+        Some(bps) if bps > 0. => "better",
+        Some(bps) if bps < 0. => "worse",
+        Some(_) => "equal",
+    };
+    metrics
+        .price_comparisons
+        .with_label_values(&[api, outcome])
+        .inc();
+    if let Some(bps) = bps {
+        metrics
+            .price_comparison_bps
+            .with_label_values(&[api, outcome])
+            .observe(bps.abs());
+    }
+}
+
+pub fn settlement_slippage(kind: &str, bps: f64) {
+    let outcome = match bps {
+        bps if bps > 0. => "worse",
+        bps if bps < 0. => "better",
+        _ => "equal",
+    };
+    get()
+        .settlement_slippage_bps
+        .with_label_values(&[kind, outcome])
+        .observe(bps.abs());
+}
+
+/// Get the metrics instance.
+fn get() -> &'static Metrics {
+    Metrics::instance(observe::metrics::get_storage_registry())
+        .expect("unexpected error getting metrics instance")
+}
@@ -0,0 +1,16 @@
+pub mod artifacts;
+pub mod balances;
+pub mod bundle;
+pub mod config;
+pub mod contracts;
+pub mod denylist;
+pub mod heap;
+pub mod liquidity_client;
+pub mod metrics;
+pub mod oracle;
+pub mod order_book;
+pub mod price_comparison;
+pub mod receipts;
+pub mod solution_verifier;
+pub mod total_supply;
+pub mod strategies;
@@ -0,0 +1,138 @@
+//! External price oracles used as a reference for sanity checking solutions.
+//!
+//! Prices are read from Chainlink feeds quoted in the native token or from
+//! the time weighted average price of Uniswap V3 pools pairing a token with
+//! the wrapped native token. Both are independent of the liquidity the solver
+//! routes through, so they are not affected by bugs in the pool math or by
+//! manipulated pool state.
+
+use {
+    crate::domain::eth,
+    anyhow::{Context, Result, ensure},
+    contracts::alloy::{ChainlinkAggregatorV3, UniswapV3Pool},
+    ethrpc::alloy::conversions::{IntoAlloy, IntoLegacy},
+    std::{
+        collections::HashMap,
+        time::{Duration, SystemTime, UNIX_EPOCH},
+    },
+    url::Url,
+};
+
+/// The source of the oracle price of a token.
+#[derive(Clone, Debug)]
+pub enum Feed {
+    /// A Chainlink aggregator quoting the price of the token in the native
+    /// token. Rounds older than `max_age` are considered stale.
+    Chainlink {
+        aggregator: eth::H160,
+        max_age: Duration,
+    },
+    /// A Uniswap V3 pool pairing the token with the wrapped native token,
+    /// averaged over `window`.
+    UniswapV3Twap { pool: eth::H160, window: Duration },
+}
+
+pub struct Oracle {
+    web3: ethrpc::Web3,
+    weth: eth::WethAddress,
+    feeds: HashMap<eth::TokenAddress, Feed>,
+}
+
+impl Oracle {
+    pub fn new(
+        node_url: &Url,
+        weth: eth::WethAddress,
+        feeds: HashMap<eth::TokenAddress, Feed>,
+    ) -> Self {
+        Self {
+            web3: ethrpc::web3(Default::default(), Default::default(), node_url, "oracle"),
+            weth,
+            feeds,
+        }
+    }
+
+    /// Returns the price of one token atom in atoms of the native token, or
+    /// `None` if the token has no feed or the feed could not be read. The
+    /// token decimals are required for Chainlink feeds, which quote prices of
+    /// whole tokens.
+    pub async fn native_price(
+        &self,
+        token: eth::TokenAddress,
+        decimals: Option<u8>,
+    ) -> Option<f64> {
+        if token == self.weth.0.into() {
+            return Some(1.);
+        }
+        let feed = self.feeds.get(&token)?;
+        let price = match feed {
+            Feed::Chainlink {
+                aggregator,
+                max_age,
+            } => self.chainlink(*aggregator, *max_age, decimals?).await,
+            Feed::UniswapV3Twap { pool, window } => self.twap(token, *pool, *window).await,
+        };
+        price
+            .inspect_err(|err| tracing::debug!(?err, ?token, ?feed, "failed to read oracle price"))
+            .ok()
+    }
+
+    async fn chainlink(
+        &self,
+        aggregator: eth::H160,
+        max_age: Duration,
+        decimals: u8,
+    ) -> Result<f64> {
+        let aggregator =
+            ChainlinkAggregatorV3::Instance::new(aggregator.into_alloy(), self.web3.alloy.clone());
+        let feed_decimals = aggregator.decimals().call().await.context("decimals")?;
+        let round = aggregator
+            .latestRoundData()
+            .call()
+            .await
+            .context("latest round")?;
+
+        let updated_at = u64::try_from(round.updatedAt).context("invalid update time")?;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        ensure!(
+            now.saturating_sub(updated_at) <= max_age.as_secs(),
+            "stale round updated at {updated_at}"
+        );
+        ensure!(round.answer.is_positive(), "non-positive answer");
+
+        // The answer is the native token value of one whole token with the
+        // feed's decimals.
+        let answer = round.answer.into_raw().into_legacy().to_f64_lossy();
+        Ok(answer * 10f64.powi(18 - i32::from(feed_decimals) - i32::from(decimals)))
+    }
+
+    async fn twap(
+        &self,
+        token: eth::TokenAddress,
+        pool: eth::H160,
+        window: Duration,
+    ) -> Result<f64> {
+        let pool = UniswapV3Pool::Instance::new(pool.into_alloy(), self.web3.alloy.clone());
+        let token0 = pool.token0().call().await.context("token0")?.into_legacy();
+        let token1 = pool.token1().call().await.context("token1")?.into_legacy();
+        let window = u32::try_from(window.as_secs()).context("window too large")?;
+        ensure!(window > 0, "empty window");
+        let observations = pool
+            .observe(vec![window, 0])
+            .call()
+            .await
+            .context("observe")?;
+        let [start, end] = observations.tickCumulatives[..] else {
+            anyhow::bail!("unexpected number of observations");
+        };
+
+        // The price of token0 in token1 atoms is `1.0001^tick`.
+        let ticks = end.as_i64() - start.as_i64();
+        let tick = ticks.div_euclid(i64::from(window));
+        let price = 1.0001_f64.powf(tick as f64);
+        match (token0, token1) {
+            (token0, token1) if token0 == token.0 && token1 == self.weth.0 => Ok(price),
+            (token0, token1) if token0 == self.weth.0 && token1 == token.0 => Ok(1. / price),
+            _ => anyhow::bail!("pool does not pair the token with the native token"),
+        }
+    }
+}
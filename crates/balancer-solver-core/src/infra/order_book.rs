@@ -0,0 +1,219 @@
+//! Resting orders pulled from the CoW Protocol order book API.
+//!
+//! The open orders get periodically synced from the order book's current
+//! auction. Only the orders that can be settled as JIT trades are kept: ECDSA
+//! signed, partially fillable sell orders without hooks that trade ERC20
+//! balances. Orders with other signing schemes can't be recovered to their
+//! owner from the order data alone, and the hooks of an order would not be
+//! executed when it gets settled as a JIT trade.
+
+use {
+    crate::{
+        domain::{eth, order, order_book},
+        util::serialize,
+    },
+    model::{
+        order::{BUY_ETH_ADDRESS, BuyTokenDestination, OrderClass, OrderKind, OrderUid},
+        signature::Signature,
+    },
+    reqwest::{Client, Url},
+    serde::Deserialize,
+    serde_with::serde_as,
+    std::{
+        sync::{Arc, RwLock, Weak},
+        time::Duration,
+    },
+};
+
+#[derive(Clone)]
+pub struct OrderBook(Arc<RwLock<Arc<Vec<order_book::Order>>>>);
+
+impl OrderBook {
+    /// Creates a new order book, spawning a background task syncing the open
+    /// orders from the order book API at the specified URL.
+    pub fn new(url: Url, interval: Duration) -> Self {
+        let orders = Self(Default::default());
+        tokio::spawn(sync(Arc::downgrade(&orders.0), url, interval));
+        orders
+    }
+
+    /// Returns the latest synced resting orders.
+    pub fn orders(&self) -> Arc<Vec<order_book::Order>> {
+        self.0.read().unwrap().clone()
+    }
+}
+
+/// Periodically replaces the resting orders with the latest open orders until
+/// the order book is dropped. The previous orders are kept if fetching fails.
+async fn sync(orders: Weak<RwLock<Arc<Vec<order_book::Order>>>>, url: Url, interval: Duration) {
+    let client = Client::new();
+    let url = shared::url::join(&url, "api/v1/auction");
+    loop {
+        match fetch(&client, &url).await {
+            Ok(latest) => {
+                let Some(orders) = orders.upgrade() else {
+                    return;
+                };
+                tracing::debug!(count = latest.len(), "synced resting orders");
+                *orders.write().unwrap() = Arc::new(latest);
+            }
+            Err(err) => tracing::warn!(?err, %url, "failed to sync resting orders"),
+        }
+        tokio::time::sleep(interval).await;
+        if orders.strong_count() == 0 {
+            return;
+        }
+    }
+}
+
+async fn fetch(client: &Client, url: &Url) -> reqwest::Result<Vec<order_book::Order>> {
+    let auction = client
+        .get(url.clone())
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<Auction>()
+        .await?;
+    Ok(auction.into_orders())
+}
+
+#[derive(Deserialize)]
+struct Auction {
+    orders: Vec<Order>,
+}
+
+impl Auction {
+    fn into_orders(self) -> Vec<order_book::Order> {
+        self.orders
+            .into_iter()
+            .filter_map(Order::into_domain)
+            .collect()
+    }
+}
+
+#[serde_as]
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Order {
+    uid: OrderUid,
+    owner: eth::H160,
+    receiver: Option<eth::H160>,
+    sell_token: eth::H160,
+    buy_token: eth::H160,
+    #[serde_as(as = "serialize::U256")]
+    sell_amount: eth::U256,
+    #[serde_as(as = "serialize::U256")]
+    buy_amount: eth::U256,
+    #[serde_as(as = "serialize::U256")]
+    executed: eth::U256,
+    valid_to: u32,
+    kind: OrderKind,
+    partially_fillable: bool,
+    #[serde(default)]
+    pre_interactions: Vec<serde_json::Value>,
+    #[serde(default)]
+    post_interactions: Vec<serde_json::Value>,
+    sell_token_balance: model::order::SellTokenSource,
+    buy_token_balance: BuyTokenDestination,
+    #[serde(flatten)]
+    class: OrderClass,
+    app_data: eth::H256,
+    #[serde(flatten)]
+    signature: Signature,
+}
+
+impl Order {
+    /// Converts the order into a resting order if it can be settled as a JIT
+    /// trade.
+    fn into_domain(self) -> Option<order_book::Order> {
+        let signature = match self.signature {
+            Signature::Eip712(signature) => {
+                order::Signature::Eip712(order::EcdsaSignature::from_bytes(&signature.to_bytes())?)
+            }
+            Signature::EthSign(signature) => {
+                order::Signature::EthSign(order::EcdsaSignature::from_bytes(&signature.to_bytes())?)
+            }
+            Signature::Eip1271(_) | Signature::PreSign => return None,
+        };
+        let eligible = self.kind == OrderKind::Sell
+            && self.partially_fillable
+            && self.pre_interactions.is_empty()
+            && self.post_interactions.is_empty()
+            && self.sell_token_balance == model::order::SellTokenSource::Erc20
+            && self.buy_token_balance == BuyTokenDestination::Erc20
+            && self.buy_token != BUY_ETH_ADDRESS;
+        eligible.then(|| order_book::Order {
+            uid: order::Uid(self.uid.0),
+            owner: eth::Address(self.owner),
+            receiver: eth::Address(self.receiver.unwrap_or_default()),
+            sell: eth::Asset {
+                token: eth::TokenAddress(self.sell_token),
+                amount: self.sell_amount,
+            },
+            buy: eth::Asset {
+                token: eth::TokenAddress(self.buy_token),
+                amount: self.buy_amount,
+            },
+            executed: self.executed,
+            class: match self.class {
+                OrderClass::Market => order::Class::Market,
+                OrderClass::Limit | OrderClass::Liquidity => order::Class::Limit,
+            },
+            valid_to: self.valid_to,
+            app_data: order::AppData(self.app_data.0),
+            signature,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, serde_json::json};
+
+    fn order(kind: &str, signing_scheme: &str) -> serde_json::Value {
+        json!({
+            "uid": format!("0x{}", "11".repeat(56)),
+            "owner": "0x7777777777777777777777777777777777777777",
+            "receiver": null,
+            "sellToken": "0x2222222222222222222222222222222222222222",
+            "buyToken": "0x1111111111111111111111111111111111111111",
+            "sellAmount": "1000",
+            "buyAmount": "500",
+            "protocolFees": [],
+            "created": 0,
+            "validTo": 4294967295u32,
+            "kind": kind,
+            "partiallyFillable": true,
+            "executed": "600",
+            "preInteractions": [],
+            "postInteractions": [],
+            "sellTokenBalance": "erc20",
+            "buyTokenBalance": "erc20",
+            "class": "limit",
+            "appData": format!("0x{}", "00".repeat(32)),
+            "signingScheme": signing_scheme,
+            "signature": format!("0x{}", "01".repeat(65)),
+        })
+    }
+
+    #[test]
+    fn keeps_orders_settleable_as_jit_trades() {
+        let auction: Auction = serde_json::from_value(json!({
+            "id": 1,
+            "block": 1,
+            "orders": [
+                order("sell", "eip712"),
+                order("buy", "eip712"),
+                order("sell", "eip1271"),
+            ],
+            "prices": {},
+        }))
+        .unwrap();
+
+        let orders = auction.into_orders();
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].executed, 600.into());
+        assert_eq!(orders[0].receiver, eth::Address::default());
+        assert!(matches!(orders[0].signature, order::Signature::Eip712(_)));
+    }
+}
@@ -0,0 +1,424 @@
+//! Comparison of the computed quotes against external price APIs.
+//!
+//! A sample of the quotes gets requested from the 1inch, 0x and Paraswap
+//! price APIs as well, recording by how many basis points our route is better
+//! or worse. The results are aggregated into a daily competitiveness report,
+//! which gets handed out once the first quote of the next day is compared.
+
+use {
+    crate::{
+        domain::{eth, order, solution},
+        util::serialize,
+    },
+    anyhow::{Context, Result, anyhow},
+    reqwest::Client,
+    serde::{Deserialize, Serialize},
+    serde_with::serde_as,
+    std::{collections::BTreeMap, sync::Mutex, time::Duration},
+    url::Url,
+};
+
+/// An external price API to compare quotes against.
+#[derive(Clone, Debug)]
+pub enum Api {
+    OneInch { url: Url, api_key: String },
+    ZeroEx { url: Url, api_key: String },
+    Paraswap { url: Url },
+}
+
+impl Api {
+    /// The name of the API used in logs, metrics and reports.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::OneInch { .. } => "1inch",
+            Self::ZeroEx { .. } => "0x",
+            Self::Paraswap { .. } => "paraswap",
+        }
+    }
+}
+
+pub struct Config {
+    pub apis: Vec<Api>,
+    /// The share of quotes to compare, between 0 and 1.
+    pub sample_rate: f64,
+    /// The time to wait for the price of an API.
+    pub timeout: Duration,
+}
+
+/// A quote computed by this solver.
+#[derive(Clone, Copy, Debug)]
+pub struct Quote {
+    pub side: order::Side,
+    pub sell: eth::Asset,
+    pub buy: eth::Asset,
+    pub sell_decimals: Option<u8>,
+    pub buy_decimals: Option<u8>,
+}
+
+impl Quote {
+    /// Returns the quote for the first order traded by the solution at its
+    /// clearing prices.
+    pub fn from_solution(
+        solution: &solution::Solution,
+        decimals: impl Fn(eth::TokenAddress) -> Option<u8>,
+    ) -> Option<Self> {
+        let fulfillment = solution.trades.iter().find_map(|trade| match trade {
+            solution::Trade::Fulfillment(fulfillment) => Some(fulfillment),
+            solution::Trade::Jit(_) => None,
+        })?;
+        let order = fulfillment.order();
+        let price = |token| solution.prices.0.get(&token).copied();
+        let (sell_price, buy_price) = (price(order.sell.token)?, price(order.buy.token)?);
+        let executed = fulfillment.executed().amount;
+        let (sell, buy) = match order.side {
+            order::Side::Sell => (
+                executed,
+                executed.checked_mul(sell_price)?.checked_div(buy_price)?,
+            ),
+            order::Side::Buy => (
+                executed.checked_mul(buy_price)?.checked_div(sell_price)?,
+                executed,
+            ),
+        };
+        Some(Self {
+            side: order.side,
+            sell: eth::Asset {
+                token: order.sell.token,
+                amount: sell,
+            },
+            buy: eth::Asset {
+                token: order.buy.token,
+                amount: buy,
+            },
+            sell_decimals: decimals(order.sell.token),
+            buy_decimals: decimals(order.buy.token),
+        })
+    }
+
+    /// The amount the external APIs get quoted, i.e. the fixed side of the
+    /// order.
+    fn fixed(&self) -> eth::U256 {
+        match self.side {
+            order::Side::Sell => self.sell.amount,
+            order::Side::Buy => self.buy.amount,
+        }
+    }
+
+    /// How much better this quote is than the external one quoting `amount`
+    /// for the variable side, in basis points. Negative if this quote is
+    /// worse.
+    fn advantage_bps(&self, amount: eth::U256) -> Option<f64> {
+        let external = amount.to_f64_lossy();
+        if external == 0. {
+            return None;
+        }
+        let difference = match self.side {
+            order::Side::Sell => self.buy.amount.to_f64_lossy() - external,
+            order::Side::Buy => external - self.sell.amount.to_f64_lossy(),
+        };
+        Some(difference / external * 10_000.)
+    }
+}
+
+/// The daily competitiveness report.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Report {
+    pub date: chrono::NaiveDate,
+    pub apis: BTreeMap<&'static str, Summary>,
+}
+
+impl Report {
+    fn new(date: chrono::NaiveDate) -> Self {
+        Self {
+            date,
+            apis: Default::default(),
+        }
+    }
+}
+
+/// How the quotes compared against a single API.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Summary {
+    /// The number of quotes the API returned a price for.
+    pub samples: u64,
+    pub better: u64,
+    pub worse: u64,
+    /// The number of quotes the API failed to return a price for.
+    pub failures: u64,
+    /// The average advantage of our quotes in basis points.
+    pub average_bps: f64,
+    pub best_bps: Option<f64>,
+    pub worst_bps: Option<f64>,
+}
+
+impl Summary {
+    fn record(&mut self, bps: f64) {
+        self.average_bps =
+            (self.average_bps * self.samples as f64 + bps) / (self.samples + 1) as f64;
+        self.samples += 1;
+        if bps > 0. {
+            self.better += 1;
+        } else if bps < 0. {
+            self.worse += 1;
+        }
+        self.best_bps = Some(self.best_bps.map_or(bps, |best| best.max(bps)));
+        self.worst_bps = Some(self.worst_bps.map_or(bps, |worst| worst.min(bps)));
+    }
+}
+
+pub struct Comparator {
+    client: Client,
+    chain_id: u64,
+    config: Config,
+    report: Mutex<Report>,
+}
+
+impl Comparator {
+    pub fn new(chain_id: u64, config: Config) -> Self {
+        Self {
+            client: Client::new(),
+            chain_id,
+            config,
+            report: Mutex::new(Report::new(chrono::Utc::now().date_naive())),
+        }
+    }
+
+    /// Whether a quote should be compared.
+    pub fn sample(&self) -> bool {
+        rand::random::<f64>() < self.config.sample_rate
+    }
+
+    /// Compares the quote against all APIs, recording the results in the
+    /// report of the current day. Returns the report of the previous day once
+    /// the day is over.
+    pub async fn compare(&self, quote: Quote) -> Option<Report> {
+        let results = futures::future::join_all(self.config.apis.iter().map(async |api| {
+            let amount = tokio::time::timeout(self.config.timeout, self.price(api, &quote))
+                .await
+                .unwrap_or_else(|_| Err(anyhow!("timeout")));
+            let bps = amount.and_then(|amount| {
+                quote
+                    .advantage_bps(amount)
+                    .context("external price of zero")
+            });
+            match &bps {
+                Ok(bps) => {
+                    tracing::debug!(api = api.name(), bps, ?quote, "compared quote");
+                    crate::infra::metrics::price_comparison(api.name(), Some(*bps));
+                }
+                Err(err) => {
+                    tracing::debug!(?err, api = api.name(), ?quote, "external price failed");
+                    crate::infra::metrics::price_comparison(api.name(), None);
+                }
+            }
+            (api.name(), bps.ok())
+        }))
+        .await;
+        self.record(chrono::Utc::now().date_naive(), results)
+    }
+
+    fn record(
+        &self,
+        date: chrono::NaiveDate,
+        results: impl IntoIterator<Item = (&'static str, Option<f64>)>,
+    ) -> Option<Report> {
+        let mut report = self.report.lock().unwrap();
+        let finished =
+            (report.date != date).then(|| std::mem::replace(&mut *report, Report::new(date)));
+        for (api, bps) in results {
+            let summary = report.apis.entry(api).or_default();
+            match bps {
+                Some(bps) => summary.record(bps),
+                None => summary.failures += 1,
+            }
+        }
+        finished
+    }
+
+    /// Returns the amount the API quotes for the variable side of the quote.
+    async fn price(&self, api: &Api, quote: &Quote) -> Result<eth::U256> {
+        let (sell, buy, amount) = (
+            quote.sell.token.0,
+            quote.buy.token.0,
+            quote.fixed().to_string(),
+        );
+        match api {
+            Api::OneInch { url, api_key } => {
+                anyhow::ensure!(quote.side == order::Side::Sell, "buy orders unsupported");
+                let response: OneInchQuote = self
+                    .client
+                    .get(url.join(&format!("{}/quote", self.chain_id))?)
+                    .bearer_auth(api_key)
+                    .query(&[
+                        ("src", format!("{sell:?}")),
+                        ("dst", format!("{buy:?}")),
+                        ("amount", amount),
+                    ])
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .json()
+                    .await?;
+                Ok(response.dst_amount)
+            }
+            Api::ZeroEx { url, api_key } => {
+                anyhow::ensure!(quote.side == order::Side::Sell, "buy orders unsupported");
+                let response: ZeroExPrice = self
+                    .client
+                    .get(url.join("swap/allowance-holder/price")?)
+                    .header("0x-api-key", api_key)
+                    .header("0x-version", "v2")
+                    .query(&[
+                        ("chainId", self.chain_id.to_string()),
+                        ("sellToken", format!("{sell:?}")),
+                        ("buyToken", format!("{buy:?}")),
+                        ("sellAmount", amount),
+                    ])
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .json()
+                    .await?;
+                Ok(response.buy_amount)
+            }
+            Api::Paraswap { url } => {
+                let (Some(sell_decimals), Some(buy_decimals)) =
+                    (quote.sell_decimals, quote.buy_decimals)
+                else {
+                    return Err(anyhow!("missing token decimals"));
+                };
+                let side = match quote.side {
+                    order::Side::Sell => "SELL",
+                    order::Side::Buy => "BUY",
+                };
+                let response: ParaswapPrices = self
+                    .client
+                    .get(url.join("prices")?)
+                    .query(&[
+                        ("network", self.chain_id.to_string()),
+                        ("srcToken", format!("{sell:?}")),
+                        ("destToken", format!("{buy:?}")),
+                        ("srcDecimals", sell_decimals.to_string()),
+                        ("destDecimals", buy_decimals.to_string()),
+                        ("amount", amount),
+                        ("side", side.to_owned()),
+                        ("version", "6.2".to_owned()),
+                    ])
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .json()
+                    .await?;
+                Ok(match quote.side {
+                    order::Side::Sell => response.price_route.dest_amount,
+                    order::Side::Buy => response.price_route.src_amount,
+                })
+            }
+        }
+    }
+}
+
+#[serde_as]
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OneInchQuote {
+    #[serde_as(as = "serialize::U256")]
+    dst_amount: eth::U256,
+}
+
+#[serde_as]
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ZeroExPrice {
+    #[serde_as(as = "serialize::U256")]
+    buy_amount: eth::U256,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ParaswapPrices {
+    price_route: ParaswapPriceRoute,
+}
+
+#[serde_as]
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ParaswapPriceRoute {
+    #[serde_as(as = "serialize::U256")]
+    src_amount: eth::U256,
+    #[serde_as(as = "serialize::U256")]
+    dest_amount: eth::U256,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quote(side: order::Side, sell: u64, buy: u64) -> Quote {
+        Quote {
+            side,
+            sell: eth::Asset {
+                token: eth::TokenAddress(eth::H160::from_low_u64_be(1)),
+                amount: sell.into(),
+            },
+            buy: eth::Asset {
+                token: eth::TokenAddress(eth::H160::from_low_u64_be(2)),
+                amount: buy.into(),
+            },
+            sell_decimals: Some(18),
+            buy_decimals: Some(6),
+        }
+    }
+
+    #[test]
+    fn computes_advantage() {
+        // Receiving more for a sell order is better.
+        let sell = quote(order::Side::Sell, 1_000, 1_010);
+        assert_eq!(sell.advantage_bps(1_000.into()), Some(100.));
+        assert_eq!(sell.advantage_bps(1_010.into()), Some(0.));
+        // Paying more for a buy order is worse.
+        let buy = quote(order::Side::Buy, 1_010, 1_000);
+        assert_eq!(buy.advantage_bps(1_000.into()), Some(-100.));
+        assert_eq!(buy.advantage_bps(0.into()), None);
+    }
+
+    #[test]
+    fn rolls_reports_over_daily() {
+        let day = |day| chrono::NaiveDate::from_ymd_opt(2026, 1, day).unwrap();
+        let comparator = Comparator {
+            client: Client::new(),
+            chain_id: 1,
+            config: Config {
+                apis: Vec::new(),
+                sample_rate: 1.,
+                timeout: Duration::from_secs(1),
+            },
+            report: Mutex::new(Report::new(day(1))),
+        };
+
+        assert_eq!(
+            comparator.record(day(1), [("0x", Some(10.)), ("paraswap", None)]),
+            None
+        );
+        assert_eq!(comparator.record(day(1), [("0x", Some(-20.))]), None);
+        let report = comparator.record(day(2), [("0x", Some(5.))]).unwrap();
+
+        assert_eq!(report.date, day(1));
+        assert_eq!(
+            report.apis["0x"],
+            Summary {
+                samples: 2,
+                better: 1,
+                worse: 1,
+                failures: 0,
+                average_bps: -5.,
+                best_bps: Some(10.),
+                worst_bps: Some(-20.),
+            }
+        );
+        assert_eq!(report.apis["paraswap"].failures, 1);
+        assert_eq!(comparator.report.lock().unwrap().apis["0x"].samples, 1);
+    }
+}
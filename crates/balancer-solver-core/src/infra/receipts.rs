@@ -0,0 +1,109 @@
+//! Decoding of the pool swaps executed by settlement transactions from their
+//! receipts.
+
+use {
+    crate::domain::{eth, slippage},
+    alloy::{primitives::I256, providers::Provider, rpc::types::Log, sol_types::SolEvent},
+    anyhow::{Context, Result},
+    contracts::alloy::{
+        BalancerV2Vault::BalancerV2Vault,
+        IUniswapLikePair::IUniswapLikePair,
+        UniswapV3Pool::UniswapV3Pool,
+    },
+    ethrpc::alloy::conversions::{IntoAlloy, IntoLegacy},
+    url::Url,
+};
+
+mod balancer_v3 {
+    // There are no alloy bindings for the Balancer V3 vault.
+    alloy::sol! {
+        event Swap(
+            address indexed pool,
+            address indexed tokenIn,
+            address indexed tokenOut,
+            uint256 amountIn,
+            uint256 amountOut,
+            uint256 swapFeePercentage,
+            uint256 swapFeeAmount
+        );
+    }
+}
+
+pub struct Receipts {
+    web3: ethrpc::Web3,
+}
+
+impl Receipts {
+    pub fn new(node_url: &Url) -> Self {
+        Self {
+            web3: ethrpc::web3(Default::default(), Default::default(), node_url, "receipts"),
+        }
+    }
+
+    /// Returns the pool swaps logged by the transaction.
+    pub async fn swaps(&self, transaction: eth::H256) -> Result<Vec<slippage::Executed>> {
+        let receipt = self
+            .web3
+            .alloy
+            .get_transaction_receipt(transaction.into_alloy())
+            .await
+            .context("fetch receipt")?
+            .context("missing receipt")?;
+        Ok(receipt.inner.logs().iter().filter_map(decode).collect())
+    }
+}
+
+/// Decodes the log if it is the swap event of a supported kind of pool.
+fn decode(log: &Log) -> Option<slippage::Executed> {
+    let topic = *log.topic0()?;
+    let asset = |token: alloy::primitives::Address, amount: alloy::primitives::U256| eth::Asset {
+        token: eth::TokenAddress(token.into_legacy()),
+        amount: amount.into_legacy(),
+    };
+
+    if topic == BalancerV2Vault::Swap::SIGNATURE_HASH {
+        let swap = BalancerV2Vault::Swap::decode_log_data(log.data()).ok()?;
+        // Balancer V2 pool IDs start with the address of the pool.
+        return Some(slippage::Executed::Tokens(slippage::Swap {
+            pool: eth::H160::from_slice(&swap.poolId[..20]),
+            input: asset(swap.tokenIn, swap.amountIn),
+            output: asset(swap.tokenOut, swap.amountOut),
+        }));
+    }
+    if topic == balancer_v3::Swap::SIGNATURE_HASH {
+        let swap = balancer_v3::Swap::decode_log_data(log.data()).ok()?;
+        return Some(slippage::Executed::Tokens(slippage::Swap {
+            pool: swap.pool.into_legacy(),
+            input: asset(swap.tokenIn, swap.amountIn),
+            output: asset(swap.tokenOut, swap.amountOut),
+        }));
+    }
+    if topic == IUniswapLikePair::Swap::SIGNATURE_HASH {
+        let swap = IUniswapLikePair::Swap::decode_log_data(log.data()).ok()?;
+        return Some(slippage::Executed::Sorted {
+            pool: log.address().into_legacy(),
+            amounts_in: [swap.amount0In.into_legacy(), swap.amount1In.into_legacy()],
+            amounts_out: [swap.amount0Out.into_legacy(), swap.amount1Out.into_legacy()],
+        });
+    }
+    if topic == UniswapV3Pool::Swap::SIGNATURE_HASH {
+        let swap = UniswapV3Pool::Swap::decode_log_data(log.data()).ok()?;
+        // The amounts are the balance changes of the pool.
+        let split = |amount: I256| {
+            let abs = amount.unsigned_abs().into_legacy();
+            if amount.is_negative() {
+                (eth::U256::zero(), abs)
+            } else {
+                (abs, eth::U256::zero())
+            }
+        };
+        let (amount0_in, amount0_out) = split(swap.amount0);
+        let (amount1_in, amount1_out) = split(swap.amount1);
+        return Some(slippage::Executed::Sorted {
+            pool: log.address().into_legacy(),
+            amounts_in: [amount0_in, amount1_in],
+            amounts_out: [amount0_out, amount1_out],
+        });
+    }
+    None
+}
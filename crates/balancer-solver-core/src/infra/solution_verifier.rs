@@ -0,0 +1,1266 @@
+use {
+    alloy::{eips::BlockId, primitives},
+    contracts::alloy::{
+        BalancerV2Vault::{self, IVault},
+        BalancerV3BatchRouter::{
+            self,
+            IBatchRouter::{SwapPathExactAmountIn, SwapPathStep},
+        },
+    },
+    ethcontract::{Address, H160, U256},
+    ethrpc::alloy::conversions::{IntoAlloy, IntoLegacy},
+    serde::{Deserialize, Serialize},
+    std::collections::HashMap,
+};
+
+/// Differences between the clearing prices and the executed swaps up to this
+/// many basis points are rounding and not reported.
+const PRICE_TOLERANCE_BPS: i64 = 1;
+
+/// Clearing prices may be worse than the executed swaps by this many basis
+/// points, e.g. to charge fees, before the discrepancy gets reported as a
+/// warning.
+const PRICE_WARNING_BPS: i64 = 100;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VerificationResult {
+    pub solution_index: usize,
+    pub swaps: Vec<SwapVerification>,
+    /// Verification of the routes executing the trades of the solution, for
+    /// routes with multiple hops. Merged solutions have a route per trade.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub routes: Vec<RouteVerification>,
+    pub total_gas_estimate: Option<u64>,
+    pub verification_timestamp: u64,
+    /// The block the quotes were made at. `None` for quotes against the
+    /// latest block.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_number: Option<u64>,
+    /// Trades whose clearing prices are inconsistent with the swaps executing
+    /// them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub price_discrepancies: Vec<PriceDiscrepancy>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SwapVerification {
+    pub interaction_index: usize,
+    pub pool_id: String,
+    pub pool_version: PoolVersion,
+    pub token_in: Address,
+    pub token_out: Address,
+    pub amount_in: String,
+    pub expected_amount_out: String,
+    pub quoted_amount_out: Option<String>,
+    pub difference_bps: Option<i64>,
+    pub quote_error: Option<String>,
+    pub contract_call: Option<ContractCallDetails>,
+}
+
+/// Verification of a multi-hop route, quoted as a single query path mirroring
+/// the settlement route. This detects errors in how the hops are composed,
+/// which per-swap verification can't catch.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RouteVerification {
+    pub interaction_indices: Vec<usize>,
+    pub token_in: Address,
+    pub token_out: Address,
+    pub amount_in: String,
+    pub expected_amount_out: String,
+    pub quoted_amount_out: Option<String>,
+    pub difference_bps: Option<i64>,
+    /// Hops whose input doesn't match the output of the previous hop, and
+    /// routes that don't end in the buy token of their trade.
+    pub composition_errors: Vec<String>,
+    pub quote_error: Option<String>,
+    pub contract_call: Option<ContractCallDetails>,
+}
+
+/// A trade whose clearing prices don't match the execution price implied by
+/// the liquidity interactions of the solution.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PriceDiscrepancy {
+    pub order: String,
+    pub severity: Severity,
+    pub sell_token: H160,
+    pub buy_token: H160,
+    /// The amount of sell token swapped by the interactions.
+    pub swapped_sell_amount: String,
+    /// The amount of buy token the interactions swap it for.
+    pub swapped_buy_amount: String,
+    /// The amount of buy token the swapped sell amount is worth at the
+    /// clearing prices.
+    pub clearing_buy_amount: Option<String>,
+    /// How much more the clearing prices pay out than the swaps produce.
+    pub difference_bps: Option<i64>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The clearing prices are noticeably worse than the executed swaps.
+    Warning,
+    /// The clearing prices pay out more than the executed swaps produce, so
+    /// the settlement can't be funded.
+    Error,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ContractCallDetails {
+    pub contract_address: String,
+    pub contract_name: String,
+    pub function_name: String,
+    pub calldata: String,
+    pub decoded_params: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum PoolVersion {
+    V2,
+    V3,
+}
+
+#[derive(Clone)]
+pub struct SolutionVerifier {
+    vault: BalancerV2Vault::Instance,
+    batch_router: BalancerV3BatchRouter::Instance,
+}
+
+impl SolutionVerifier {
+    pub fn new(
+        vault: BalancerV2Vault::Instance,
+        batch_router: BalancerV3BatchRouter::Instance,
+    ) -> Self {
+        Self {
+            vault,
+            batch_router,
+        }
+    }
+
+    /// Detect pool version by ID length
+    fn detect_pool_version(pool_id: &str) -> PoolVersion {
+        // V2 pool IDs are 66 chars (0x + 64 hex chars)
+        // V3 pool IDs are 42 chars (same as address: 0x + 40 hex chars)
+        if pool_id.len() > 42 {
+            PoolVersion::V2
+        } else {
+            PoolVersion::V3
+        }
+    }
+
+    /// Verify a single solution (accepts JSON to support enhanced solutions).
+    ///
+    /// Quotes are made at the specified block, so that solutions are verified
+    /// against the state they were computed for and results are reproducible.
+    /// This requires an archive node for blocks that aren't recent. Without a
+    /// block, quotes are made against the latest block.
+    ///
+    /// The sell and buy tokens of the auction orders, keyed by order UID, are
+    /// needed to check the clearing prices of fulfillments.
+    pub async fn verify_solution(
+        &self,
+        solution: &serde_json::Value,
+        solution_index: usize,
+        block_number: Option<u64>,
+        order_tokens: &HashMap<String, (H160, H160)>,
+    ) -> VerificationResult {
+        let block = block_number.map_or(BlockId::latest(), BlockId::number);
+        let mut swaps = Vec::new();
+        let mut hops = Vec::new();
+
+        if let Some(interactions) = solution["interactions"].as_array() {
+            for (idx, interaction) in interactions.iter().enumerate() {
+                if interaction["kind"] == "liquidity" {
+                    let verification = self.verify_swap(interaction, idx, block).await;
+                    swaps.push(verification);
+                    hops.push(Hop::from_interaction(interaction, idx));
+                }
+            }
+        }
+
+        let trades = solution["trades"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|trade| trade_tokens(trade, order_tokens))
+            .map(|(_, tokens)| tokens)
+            .collect::<Vec<_>>();
+        let mut routes = Vec::new();
+        for route in routes_of(&hops, &trades) {
+            let composition_errors = composition_errors(&route);
+            if route.hops.len() > 1 || !composition_errors.is_empty() {
+                routes.push(
+                    self.verify_route(&route.hops, composition_errors, block)
+                        .await,
+                );
+            }
+        }
+
+        VerificationResult {
+            solution_index,
+            swaps,
+            routes,
+            total_gas_estimate: None,
+            verification_timestamp: chrono::Utc::now().timestamp() as u64,
+            block_number,
+            price_discrepancies: price_discrepancies(solution, order_tokens),
+        }
+    }
+
+    /// Verify a single swap interaction (accepts JSON to support enhanced
+    /// solutions)
+    async fn verify_swap(
+        &self,
+        interaction: &serde_json::Value,
+        interaction_index: usize,
+        block: BlockId,
+    ) -> SwapVerification {
+        // Extract basic fields
+        let pool_id = interaction["id"].as_str().unwrap_or("unknown");
+        let input_token_str = interaction["inputToken"].as_str().unwrap_or("");
+        let output_token_str = interaction["outputToken"].as_str().unwrap_or("");
+        let input_amount_str = interaction["inputAmount"].as_str().unwrap_or("0");
+        let output_amount_str = interaction["outputAmount"].as_str().unwrap_or("0");
+
+        // Parse token addresses
+        let input_token: Address = input_token_str.parse().unwrap_or_default();
+        let output_token: Address = output_token_str.parse().unwrap_or_default();
+        let input_amount = U256::from_dec_str(input_amount_str).unwrap_or_default();
+        let output_amount = U256::from_dec_str(output_amount_str).unwrap_or_default();
+
+        // Try to extract liquidityDetails (enhanced solutions)
+        let pool_details = interaction.get("liquidityDetails");
+
+        // Extract pool address and Balancer pool ID from liquidityDetails if available
+        let (pool_address_opt, balancer_pool_id_opt) = if let Some(details) = pool_details {
+            (
+                details["address"].as_str(),
+                details["balancerPoolId"].as_str(),
+            )
+        } else {
+            (None, None)
+        };
+
+        // Determine pool version:
+        // - If balancerPoolId is None/null, it's a V3 pool (V3 pools don't have
+        //   V2-style pool IDs)
+        // - If balancerPoolId exists, detect version by ID length
+        let pool_version = if balancer_pool_id_opt.is_none() {
+            PoolVersion::V3
+        } else {
+            Self::detect_pool_version(balancer_pool_id_opt.unwrap_or(pool_id))
+        };
+
+        // Quote using appropriate method with enhanced data
+        let quoted_amount = match pool_version {
+            PoolVersion::V2 => {
+                if let Some(pool_id_hex) = balancer_pool_id_opt {
+                    self.quote_v2_swap(
+                        pool_id_hex,
+                        H160::from(input_token.0),
+                        H160::from(output_token.0),
+                        input_amount,
+                        block,
+                    )
+                    .await
+                } else {
+                    Err("Missing balancerPoolId for V2 pool in liquidityDetails".into())
+                }
+            }
+            PoolVersion::V3 => {
+                if let Some(address) = pool_address_opt {
+                    self.quote_v3_swap(
+                        address,
+                        H160::from(input_token.0),
+                        H160::from(output_token.0),
+                        input_amount,
+                        block,
+                    )
+                    .await
+                } else {
+                    Err("Missing pool address for V3 pool in liquidityDetails".into())
+                }
+            }
+        };
+
+        let (quoted_amount_out, difference_bps, quote_error, contract_call) = match quoted_amount {
+            Ok((quote, call_details)) => {
+                let diff = calculate_difference_bps(&output_amount, &quote);
+                (Some(quote), diff, None, Some(call_details))
+            }
+            Err(e) => {
+                // For V3 calls, we still want to save the call details even on error
+                // so the user can see what was actually attempted
+                let error_call_details = if pool_version == PoolVersion::V3 {
+                    if let Some(address) = pool_address_opt {
+                        Some(create_v3_call_details(
+                            &self.batch_router,
+                            address,
+                            &input_token,
+                            &output_token,
+                            input_amount,
+                        ))
+                    } else {
+                        None
+                    }
+                } else {
+                    None
+                };
+                (None, None, Some(e.to_string()), error_call_details)
+            }
+        };
+
+        SwapVerification {
+            interaction_index,
+            pool_id: pool_id.to_string(),
+            pool_version,
+            token_in: input_token,
+            token_out: output_token,
+            amount_in: input_amount.to_string(),
+            expected_amount_out: output_amount.to_string(),
+            quoted_amount_out,
+            difference_bps,
+            quote_error,
+            contract_call,
+        }
+    }
+
+    /// Verify a multi-hop route by quoting all hops in a single query path,
+    /// the same way the settlement executes them.
+    async fn verify_route(
+        &self,
+        hops: &[&Hop<'_>],
+        composition_errors: Vec<String>,
+        block: BlockId,
+    ) -> RouteVerification {
+        let first = &hops[0];
+        let last = &hops[hops.len() - 1];
+
+        let quoted_amount = match route_pool_version(hops) {
+            Some(PoolVersion::V2) => match hops
+                .iter()
+                .map(|hop| {
+                    hop.balancer_pool_id
+                        .map(|pool_id| (pool_id, hop.token_in, hop.token_out))
+                })
+                .collect::<Option<Vec<_>>>()
+            {
+                Some(steps) => self.quote_v2_path(&steps, first.amount_in, block).await,
+                None => Err("Missing balancerPoolId for V2 pool in liquidityDetails".into()),
+            },
+            Some(PoolVersion::V3) => match hops
+                .iter()
+                .map(|hop| hop.pool_address.map(|address| (address, hop.token_out)))
+                .collect::<Option<Vec<_>>>()
+            {
+                Some(steps) => {
+                    self.quote_v3_path(first.token_in, &steps, first.amount_in, block)
+                        .await
+                }
+                None => Err("Missing pool address for V3 pool in liquidityDetails".into()),
+            },
+            None => Err("Route mixes Balancer V2 and V3 pools".into()),
+        };
+
+        let (quoted_amount_out, difference_bps, quote_error, contract_call) = match quoted_amount {
+            Ok((quote, call_details)) => {
+                let diff = calculate_difference_bps(&last.amount_out, &quote);
+                (Some(quote), diff, None, Some(call_details))
+            }
+            Err(e) => (None, None, Some(e.to_string()), None),
+        };
+
+        RouteVerification {
+            interaction_indices: hops.iter().map(|hop| hop.interaction_index).collect(),
+            token_in: first.token_in,
+            token_out: last.token_out,
+            amount_in: first.amount_in.to_string(),
+            expected_amount_out: last.amount_out.to_string(),
+            quoted_amount_out,
+            difference_bps,
+            composition_errors,
+            quote_error,
+            contract_call,
+        }
+    }
+
+    /// Quote V2 swap via Vault.queryBatchSwap
+    /// This uses a static call (eth_call) to query the expected output amount.
+    async fn quote_v2_swap(
+        &self,
+        balancer_pool_id: &str,
+        input_token: H160,
+        output_token: H160,
+        input_amount: U256,
+        block: BlockId,
+    ) -> Result<(String, ContractCallDetails), Box<dyn std::error::Error>> {
+        self.quote_v2_path(
+            &[(balancer_pool_id, input_token, output_token)],
+            input_amount,
+            block,
+        )
+        .await
+    }
+
+    /// Quote a multi-hop V2 path via Vault.queryBatchSwap. Each step after the
+    /// first one swaps the full output of the previous step, which the Vault
+    /// does for steps with a zero amount.
+    async fn quote_v2_path(
+        &self,
+        steps: &[(&str, H160, H160)],
+        input_amount: U256,
+        block: BlockId,
+    ) -> Result<(String, ContractCallDetails), Box<dyn std::error::Error>> {
+        // Build assets array using alloy types, with each token appearing once
+        let mut assets = Vec::new();
+        let mut asset_index = |token: H160| match assets.iter().position(|asset| *asset == token) {
+            Some(index) => index,
+            None => {
+                assets.push(token);
+                assets.len() - 1
+            }
+        };
+
+        let mut swap_steps = Vec::new();
+        let mut decoded_swaps = Vec::new();
+        let mut output_index = None;
+        for (i, (balancer_pool_id, input_token, output_token)) in steps.iter().enumerate() {
+            // Parse pool ID (it's a hex string starting with 0x)
+            let pool_id_bytes = if balancer_pool_id.starts_with("0x") {
+                const_hex::decode(&balancer_pool_id[2..])?
+            } else {
+                const_hex::decode(balancer_pool_id)?
+            };
+
+            if pool_id_bytes.len() != 32 {
+                return Err(format!("Invalid V2 pool ID length: {}", pool_id_bytes.len()).into());
+            }
+
+            let mut pool_id = [0u8; 32];
+            pool_id.copy_from_slice(&pool_id_bytes);
+
+            let asset_in_index = asset_index(*input_token);
+            let asset_out_index = asset_index(*output_token);
+            output_index = Some(asset_out_index);
+            let amount = if i == 0 { input_amount } else { U256::zero() };
+
+            // Create BatchSwapStep using alloy types
+            swap_steps.push(IVault::BatchSwapStep {
+                poolId: primitives::FixedBytes::from(pool_id),
+                assetInIndex: primitives::U256::from(asset_in_index),
+                assetOutIndex: primitives::U256::from(asset_out_index),
+                amount: amount.into_alloy(),
+                userData: primitives::Bytes::new(),
+            });
+            decoded_swaps.push(serde_json::json!({
+                "poolId": balancer_pool_id,
+                "assetInIndex": asset_in_index,
+                "assetOutIndex": asset_out_index,
+                "amount": amount.to_string(),
+                "userData": "0x"
+            }));
+        }
+        let Some(output_index) = output_index else {
+            return Err("Empty V2 swap path".into());
+        };
+
+        // Create FundManagement struct
+        let funds = IVault::FundManagement {
+            sender: primitives::Address::ZERO,
+            fromInternalBalance: false,
+            recipient: primitives::Address::ZERO,
+            toInternalBalance: false,
+        };
+
+        // Build the call - .call() automatically makes it a static call (eth_call)
+        let call_builder = self
+            .vault
+            .queryBatchSwap(
+                0u8, // SwapKind.GIVEN_IN
+                swap_steps,
+                assets.iter().map(|asset| asset.into_alloy()).collect(),
+                funds,
+            )
+            .block(block);
+
+        // Capture contract call details for debugging
+        let calldata = format!("0x{}", const_hex::encode(call_builder.calldata()));
+
+        let decoded_params = serde_json::json!({
+            "kind": "GIVEN_IN (0)",
+            "swaps": decoded_swaps,
+            "assets": assets
+                .iter()
+                .map(|asset| format!("{:?}", asset))
+                .collect::<Vec<_>>(),
+            "funds": {
+                "sender": "0x0000000000000000000000000000000000000000",
+                "fromInternalBalance": false,
+                "recipient": "0x0000000000000000000000000000000000000000",
+                "toInternalBalance": false
+            }
+        });
+
+        let call_details = ContractCallDetails {
+            contract_address: format!("{:?}", self.vault.address().into_legacy()),
+            contract_name: "BalancerV2Vault".to_string(),
+            function_name: "queryBatchSwap".to_string(),
+            calldata,
+            decoded_params,
+        };
+
+        // Execute the static call
+        let result = call_builder.call().await;
+
+        match result {
+            Ok(deltas) => {
+                // Parse output: the delta of the final output token represents its
+                // net token flow. In Balancer V2:
+                //   - Positive delta = tokens going INTO vault (user sends)
+                //   - Negative delta = tokens coming OUT of vault (user receives)
+                // For the output token in a swap, we expect a NEGATIVE delta
+                if deltas.len() != assets.len() {
+                    return Err("Invalid deltas returned from queryBatchSwap".into());
+                }
+
+                let delta_out = deltas[output_index];
+
+                // Check if the signed value is negative
+                let amount_out = if delta_out.is_negative() {
+                    // Negative means tokens out - convert to unsigned by negating
+                    // For Signed types, we need to negate and convert to unsigned
+                    let abs_value = delta_out.abs();
+                    primitives::U256::from_limbs(abs_value.into_limbs())
+                } else {
+                    // Positive means tokens in, which is wrong for output token
+                    return Err("Expected negative output delta (tokens out of vault)".into());
+                };
+
+                Ok((amount_out.to_string(), call_details))
+            }
+            Err(e) => {
+                // Return the error - call details will be saved separately in the JSON
+                Err(format!("Query failed: {:?}", e).into())
+            }
+        }
+    }
+
+    /// Quote V3 swap via Batch Router.querySwapExactIn
+    /// This uses a static call (eth_call) to query the expected output amount.
+    async fn quote_v3_swap(
+        &self,
+        pool_address_str: &str,
+        input_token: H160,
+        output_token: H160,
+        input_amount: U256,
+        block: BlockId,
+    ) -> Result<(String, ContractCallDetails), Box<dyn std::error::Error>> {
+        self.quote_v3_path(
+            input_token,
+            &[(pool_address_str, output_token)],
+            input_amount,
+            block,
+        )
+        .await
+    }
+
+    /// Quote a multi-hop V3 path via Batch Router.querySwapExactIn, with one
+    /// path step per hop of the route.
+    async fn quote_v3_path(
+        &self,
+        input_token: H160,
+        steps: &[(&str, H160)],
+        input_amount: U256,
+        block: BlockId,
+    ) -> Result<(String, ContractCallDetails), Box<dyn std::error::Error>> {
+        // Build SwapPathExactAmountIn using alloy types
+        let path = SwapPathExactAmountIn {
+            tokenIn: input_token.into_alloy(),
+            steps: steps
+                .iter()
+                .map(|(pool_address_str, output_token)| {
+                    // Parse pool address from string
+                    let pool_address: H160 = pool_address_str.parse()?;
+                    Ok::<_, Box<dyn std::error::Error>>(SwapPathStep {
+                        pool: pool_address.into_alloy(),
+                        tokenOut: output_token.into_alloy(),
+                        isBuffer: false,
+                    })
+                })
+                .collect::<Result<_, Box<dyn std::error::Error>>>()?,
+            exactAmountIn: input_amount.into_alloy(),
+            minAmountOut: primitives::U256::ZERO,
+        };
+
+        // Build the call - .call() automatically makes it a static call (eth_call)
+        let call_builder = self
+            .batch_router
+            .querySwapExactIn(
+                vec![path.clone()],
+                *self.batch_router.address(), // sender (required for pools with hooks)
+                primitives::Bytes::new(),     // empty userData
+            )
+            .block(block);
+
+        // Capture contract call details for debugging
+        let calldata = format!("0x{}", const_hex::encode(call_builder.calldata()));
+
+        let decoded_params = serde_json::json!({
+            "paths": [{
+                "tokenIn": format!("{:?}", input_token),
+                "steps": steps
+                    .iter()
+                    .map(|(pool_address_str, output_token)| serde_json::json!({
+                        "pool": pool_address_str,
+                        "tokenOut": format!("{:?}", output_token),
+                        "isBuffer": false
+                    }))
+                    .collect::<Vec<_>>(),
+                "exactAmountIn": input_amount.to_string(),
+                "minAmountOut": "0"
+            }],
+            "sender": format!("{:?}", self.batch_router.address().into_legacy()),
+            "userData": "0x"
+        });
+
+        let call_details = ContractCallDetails {
+            contract_address: format!("{:?}", self.batch_router.address().into_legacy()),
+            contract_name: "BalancerV3BatchRouter".to_string(),
+            function_name: "querySwapExactIn".to_string(),
+            calldata,
+            decoded_params,
+        };
+
+        // Execute the static call
+        let result = call_builder.call().await;
+
+        match result {
+            Ok(return_data) => {
+                let path_amounts_out = return_data.pathAmountsOut;
+                // Get the first path's output amount
+                if path_amounts_out.is_empty() {
+                    return Err("No output amounts returned from querySwapExactIn".into());
+                }
+                Ok((path_amounts_out[0].to_string(), call_details))
+            }
+            Err(e) => {
+                // Return the error - call details will be saved separately in the JSON
+                Err(format!("Query failed: {:?}", e).into())
+            }
+        }
+    }
+}
+
+/// A hop of a settlement route, as described by a liquidity interaction of
+/// an (enhanced) solution.
+struct Hop<'a> {
+    interaction_index: usize,
+    pool_version: PoolVersion,
+    pool_address: Option<&'a str>,
+    balancer_pool_id: Option<&'a str>,
+    token_in: H160,
+    token_out: H160,
+    amount_in: U256,
+    amount_out: U256,
+}
+
+impl<'a> Hop<'a> {
+    fn from_interaction(interaction: &'a serde_json::Value, interaction_index: usize) -> Self {
+        let details = interaction.get("liquidityDetails");
+        let pool_address = details.and_then(|details| details["address"].as_str());
+        let balancer_pool_id = details.and_then(|details| details["balancerPoolId"].as_str());
+        let pool_version = match balancer_pool_id {
+            Some(pool_id) => SolutionVerifier::detect_pool_version(pool_id),
+            None => PoolVersion::V3,
+        };
+        let token = |field: &str| {
+            interaction[field]
+                .as_str()
+                .and_then(|token| token.parse().ok())
+                .unwrap_or_default()
+        };
+        let amount = |field: &str| {
+            interaction[field]
+                .as_str()
+                .and_then(|amount| U256::from_dec_str(amount).ok())
+                .unwrap_or_default()
+        };
+
+        Self {
+            interaction_index,
+            pool_version,
+            pool_address,
+            balancer_pool_id,
+            token_in: token("inputToken"),
+            token_out: token("outputToken"),
+            amount_in: amount("inputAmount"),
+            amount_out: amount("outputAmount"),
+        }
+    }
+}
+
+/// The hops executing a trade of a solution, in the order they are executed.
+struct Route<'h, 'a> {
+    hops: Vec<&'h Hop<'a>>,
+    /// The buy token of the trade, if the route belongs to a known trade.
+    buy_token: Option<H160>,
+    /// The index of the trade the route executes.
+    trade: Option<usize>,
+}
+
+/// Splits the hops of a solution into the routes executing its trades.
+///
+/// Merged solutions execute their trades through disjoint routes, which can't
+/// be quoted as a single path. The route of a trade starts at a hop selling
+/// its sell token and continues with the hops selling what the previous hop
+/// bought, until the buy token is reached. The remaining hops, e.g. of trades
+/// with unknown tokens, are chained the same way without a buy token.
+fn routes_of<'h, 'a>(hops: &'h [Hop<'a>], trades: &[(H160, H160)]) -> Vec<Route<'h, 'a>> {
+    let mut used = vec![false; hops.len()];
+    let mut routes = Vec::new();
+    for (trade, &(sell_token, buy_token)) in trades.iter().enumerate() {
+        let route = chain(hops, &mut used, sell_token, Some(buy_token));
+        if !route.is_empty() {
+            routes.push(Route {
+                hops: route,
+                buy_token: Some(buy_token),
+                trade: Some(trade),
+            });
+        }
+    }
+    while let Some(start) = used.iter().position(|used| !used) {
+        routes.push(Route {
+            hops: chain(hops, &mut used, hops[start].token_in, None),
+            buy_token: None,
+            trade: None,
+        });
+    }
+    routes
+}
+
+/// Chains the unused hops starting at the first one selling `token`, each hop
+/// selling what the previous one bought, until `buy_token` is bought. Hops
+/// buying `buy_token` are preferred, so that routes sharing an intermediate
+/// token don't take each other's last hop.
+fn chain<'h, 'a>(
+    hops: &'h [Hop<'a>],
+    used: &mut [bool],
+    mut token: H160,
+    buy_token: Option<H160>,
+) -> Vec<&'h Hop<'a>> {
+    let mut route = Vec::new();
+    while Some(token) != buy_token {
+        let mut candidates = (0..hops.len()).filter(|&i| !used[i] && hops[i].token_in == token);
+        let Some(next) = candidates
+            .clone()
+            .find(|&i| Some(hops[i].token_out) == buy_token)
+            .or_else(|| candidates.next())
+        else {
+            break;
+        };
+        used[next] = true;
+        token = hops[next].token_out;
+        route.push(&hops[next]);
+    }
+    route
+}
+
+/// Returns the pool version shared by all hops of a route, or `None` if the
+/// route mixes pool versions and can't be quoted as a single path.
+fn route_pool_version(hops: &[&Hop]) -> Option<PoolVersion> {
+    let version = hops.first()?.pool_version;
+    hops.iter()
+        .all(|hop| hop.pool_version == version)
+        .then_some(version)
+}
+
+/// Checks that each hop of a route consumes exactly what the previous hop
+/// produced, and that the route ends in the buy token of its trade.
+fn composition_errors(route: &Route) -> Vec<String> {
+    let last = route.hops.last();
+    let end_error = route
+        .buy_token
+        .zip(last)
+        .filter(|(buy_token, last)| last.token_out != *buy_token)
+        .map(|(buy_token, last)| {
+            format!(
+                "interaction {} buys {:?} but the trade buys {:?}",
+                last.interaction_index, last.token_out, buy_token,
+            )
+        });
+    route
+        .hops
+        .windows(2)
+        .flat_map(|pair| {
+            let (previous, hop) = (&pair[0], &pair[1]);
+            let token_error = (hop.token_in != previous.token_out).then(|| {
+                format!(
+                    "interaction {} sells {:?} but interaction {} buys {:?}",
+                    hop.interaction_index,
+                    hop.token_in,
+                    previous.interaction_index,
+                    previous.token_out,
+                )
+            });
+            let amount_error = (hop.amount_in != previous.amount_out).then(|| {
+                format!(
+                    "interaction {} sells {} but interaction {} buys {}",
+                    hop.interaction_index,
+                    hop.amount_in,
+                    previous.interaction_index,
+                    previous.amount_out,
+                )
+            });
+            token_error.into_iter().chain(amount_error)
+        })
+        .chain(end_error)
+        .collect()
+}
+
+/// Recomputes the execution price of each trade from the liquidity
+/// interactions and compares it to the clearing prices of the solution.
+///
+/// The hops are attributed to the trades with [`routes_of`], so that the
+/// routes of merged solutions are valued separately. The sell amount going
+/// into a trade's route is valued at the clearing prices and compared against
+/// the buy amount coming out of it. Routes that don't end in the trade's buy
+/// token are left to the composition checks.
+fn price_discrepancies(
+    solution: &serde_json::Value,
+    order_tokens: &HashMap<String, (H160, H160)>,
+) -> Vec<PriceDiscrepancy> {
+    let trades = solution["trades"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|trade| trade_tokens(trade, order_tokens))
+        .collect::<Vec<_>>();
+    let hops = solution["interactions"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .enumerate()
+        .filter(|(_, interaction)| interaction["kind"] == "liquidity")
+        .map(|(idx, interaction)| Hop::from_interaction(interaction, idx))
+        .collect::<Vec<_>>();
+    let price = |token: H160| {
+        solution["prices"][format!("{token:?}")]
+            .as_str()
+            .and_then(|price| U256::from_dec_str(price).ok())
+            .filter(|price| !price.is_zero())
+    };
+
+    let tokens = trades.iter().map(|(_, tokens)| *tokens).collect::<Vec<_>>();
+    let routes = routes_of(&hops, &tokens);
+
+    trades
+        .iter()
+        .enumerate()
+        .filter_map(|(index, (uid, (sell_token, buy_token)))| {
+            let (sell_token, buy_token) = (*sell_token, *buy_token);
+            // Trades without a route are not executed against liquidity,
+            // e.g. because they are matched with another trade.
+            let route = routes.iter().find(|route| route.trade == Some(index))?;
+            let (first, last) = (route.hops.first()?, route.hops.last()?);
+            if last.token_out != buy_token {
+                return None;
+            }
+            let (swapped_sell_amount, swapped_buy_amount) = (first.amount_in, last.amount_out);
+            if swapped_sell_amount.is_zero() || swapped_buy_amount.is_zero() {
+                return None;
+            }
+
+            let discrepancy = |severity,
+                               clearing_buy_amount: Option<U256>,
+                               difference_bps,
+                               message| PriceDiscrepancy {
+                order: uid.clone(),
+                severity,
+                sell_token,
+                buy_token,
+                swapped_sell_amount: swapped_sell_amount.to_string(),
+                swapped_buy_amount: swapped_buy_amount.to_string(),
+                clearing_buy_amount: clearing_buy_amount.map(|amount| amount.to_string()),
+                difference_bps,
+                message,
+            };
+
+            let (Some(sell_price), Some(buy_price)) = (price(sell_token), price(buy_token)) else {
+                return Some(discrepancy(
+                    Severity::Error,
+                    None,
+                    None,
+                    "missing clearing price of a traded token".to_owned(),
+                ));
+            };
+            let Some(clearing_buy_amount) = swapped_sell_amount
+                .checked_mul(sell_price)
+                .map(|value| value / buy_price)
+            else {
+                return Some(discrepancy(
+                    Severity::Error,
+                    None,
+                    None,
+                    "clearing buy amount overflows".to_owned(),
+                ));
+            };
+            let difference_bps =
+                calculate_difference_bps(&swapped_buy_amount, &clearing_buy_amount.to_string())?;
+            let (severity, message) = if difference_bps > PRICE_TOLERANCE_BPS {
+                (
+                    Severity::Error,
+                    "clearing prices pay out more than the swaps produce",
+                )
+            } else if difference_bps < -PRICE_WARNING_BPS {
+                (
+                    Severity::Warning,
+                    "clearing prices are considerably worse than the swaps",
+                )
+            } else {
+                return None;
+            };
+            Some(discrepancy(
+                severity,
+                Some(clearing_buy_amount),
+                Some(difference_bps),
+                message.to_owned(),
+            ))
+        })
+        .collect()
+}
+
+/// Returns the order UID, or "jit" for JIT orders, and the sell and buy
+/// tokens of a trade. `None` for trades of unknown orders.
+fn trade_tokens(
+    trade: &serde_json::Value,
+    order_tokens: &HashMap<String, (H160, H160)>,
+) -> Option<(String, (H160, H160))> {
+    let order = &trade["order"];
+    match trade["kind"].as_str()? {
+        "fulfillment" => {
+            let uid = order.as_str()?.to_lowercase();
+            let tokens = *order_tokens.get(&uid)?;
+            Some((uid, tokens))
+        }
+        "jit" => {
+            let token = |field: &str| order[field].as_str()?.parse().ok();
+            Some(("jit".to_owned(), (token("sellToken")?, token("buyToken")?)))
+        }
+        _ => None,
+    }
+}
+
+fn create_v3_call_details(
+    batch_router: &BalancerV3BatchRouter::Instance,
+    pool_address: &str,
+    input_token: &Address,
+    output_token: &Address,
+    input_amount: U256,
+) -> ContractCallDetails {
+    let decoded_params = serde_json::json!({
+        "paths": [{
+            "tokenIn": format!("{:?}", H160::from(input_token.0)),
+            "steps": [{
+                "pool": pool_address,
+                "tokenOut": format!("{:?}", H160::from(output_token.0)),
+                "isBuffer": false
+            }],
+            "exactAmountIn": input_amount.to_string(),
+            "minAmountOut": "0"
+        }],
+        "sender": format!("{:?}", batch_router.address().into_legacy()),
+        "userData": "0x"
+    });
+
+    ContractCallDetails {
+        contract_address: format!("{:?}", batch_router.address().into_legacy()),
+        contract_name: "BalancerV3BatchRouter".to_string(),
+        function_name: "querySwapExactIn".to_string(),
+        calldata: "(error - call details captured without execution)".to_string(),
+        decoded_params,
+    }
+}
+
+fn calculate_difference_bps(expected: &U256, actual: &str) -> Option<i64> {
+    // Parse actual amount
+    let actual_u256 = U256::from_dec_str(actual).ok()?;
+
+    // Calculate difference in basis points
+    // diff_bps = ((actual - expected) / expected) * 10000
+    if *expected == U256::zero() {
+        return None;
+    }
+
+    // Saturate, as wildly wrong amounts would overflow the basis points.
+    let bps = |delta: U256| {
+        ((delta * 10000u64) / *expected)
+            .min(U256::from(i64::MAX as u64))
+            .as_u64() as i64
+    };
+    let diff = if actual_u256 > *expected {
+        bps(actual_u256 - *expected)
+    } else {
+        -bps(*expected - actual_u256)
+    };
+
+    Some(diff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hop(
+        index: usize,
+        token_in: u64,
+        token_out: u64,
+        amount_in: u64,
+        amount_out: u64,
+    ) -> Hop<'static> {
+        Hop {
+            interaction_index: index,
+            pool_version: PoolVersion::V3,
+            pool_address: None,
+            balancer_pool_id: None,
+            token_in: H160::from_low_u64_be(token_in),
+            token_out: H160::from_low_u64_be(token_out),
+            amount_in: amount_in.into(),
+            amount_out: amount_out.into(),
+        }
+    }
+
+    fn route<'h, 'a>(hops: &'h [Hop<'a>], buy_token: Option<u64>) -> Route<'h, 'a> {
+        Route {
+            hops: hops.iter().collect(),
+            buy_token: buy_token.map(H160::from_low_u64_be),
+            trade: None,
+        }
+    }
+
+    #[test]
+    fn detects_composition_errors() {
+        let hops = [hop(0, 1, 2, 100, 50), hop(1, 2, 3, 50, 25)];
+        assert!(composition_errors(&route(&hops, Some(3))).is_empty());
+        assert!(composition_errors(&route(&hops, None)).is_empty());
+        assert_eq!(
+            composition_errors(&route(&hops, Some(4))),
+            vec![format!(
+                "interaction 1 buys {:?} but the trade buys {:?}",
+                H160::from_low_u64_be(3),
+                H160::from_low_u64_be(4),
+            )]
+        );
+
+        let hops = [hop(0, 1, 2, 100, 50), hop(1, 4, 3, 50, 25)];
+        assert_eq!(
+            composition_errors(&route(&hops, None)),
+            vec![format!(
+                "interaction 1 sells {:?} but interaction 0 buys {:?}",
+                H160::from_low_u64_be(4),
+                H160::from_low_u64_be(2),
+            )]
+        );
+
+        let hops = [hop(0, 1, 2, 100, 50), hop(1, 4, 3, 40, 25)];
+        assert_eq!(composition_errors(&route(&hops, Some(3))).len(), 2);
+        let hops = [hop(0, 1, 2, 100, 50), hop(1, 2, 3, 40, 25)];
+        assert_eq!(
+            composition_errors(&route(&hops, Some(3))),
+            vec!["interaction 1 sells 40 but interaction 0 buys 50".to_owned()]
+        );
+    }
+
+    #[test]
+    fn splits_hops_into_routes_per_trade() {
+        let indices = |routes: Vec<Route>| {
+            routes
+                .iter()
+                .map(|route| {
+                    let indices = route
+                        .hops
+                        .iter()
+                        .map(|hop| hop.interaction_index)
+                        .collect::<Vec<_>>();
+                    (indices, route.buy_token.map(|token| token.to_low_u64_be()))
+                })
+                .collect::<Vec<_>>()
+        };
+        let trade = |sell: u64, buy: u64| (H160::from_low_u64_be(sell), H160::from_low_u64_be(buy));
+
+        // A merged solution with the routes 1 -> 2 -> 3 and 4 -> 2 -> 5,
+        // whose interactions are interleaved.
+        let hops = [
+            hop(0, 1, 2, 100, 50),
+            hop(1, 4, 2, 10, 5),
+            hop(2, 2, 3, 50, 25),
+            hop(3, 2, 5, 5, 2),
+        ];
+        assert_eq!(
+            indices(routes_of(&hops, &[trade(1, 3), trade(4, 5)])),
+            vec![(vec![0, 2], Some(3)), (vec![1, 3], Some(5))]
+        );
+        // Routes sharing an intermediate token continue with the hop buying
+        // the trade's buy token, regardless of the order of the trades.
+        assert_eq!(
+            indices(routes_of(&hops, &[trade(4, 5), trade(1, 3)])),
+            vec![(vec![1, 3], Some(5)), (vec![0, 2], Some(3))]
+        );
+
+        let hops = [
+            hop(0, 1, 2, 100, 50),
+            hop(1, 2, 3, 50, 25),
+            hop(2, 4, 2, 10, 5),
+            hop(3, 2, 5, 5, 2),
+        ];
+        assert_eq!(
+            indices(routes_of(&hops, &[trade(1, 3), trade(4, 5)])),
+            vec![(vec![0, 1], Some(3)), (vec![2, 3], Some(5))]
+        );
+
+        // Hops of unknown trades are chained without a buy token.
+        assert_eq!(
+            indices(routes_of(&hops, &[trade(4, 5)])),
+            vec![(vec![2, 3], Some(5)), (vec![0, 1], None)]
+        );
+        assert_eq!(
+            indices(routes_of(&hops, &[])),
+            vec![(vec![0, 1], None), (vec![2, 3], None)]
+        );
+
+        // Routes that don't reach the buy token keep the hops they chained.
+        assert_eq!(
+            indices(routes_of(&hops[..1], &[trade(1, 3)])),
+            vec![(vec![0], Some(3))]
+        );
+    }
+
+    #[test]
+    fn reports_clearing_prices_inconsistent_with_swaps() {
+        let uid = format!("0x{}", "01".repeat(56));
+        let (sell, buy) = (H160::from_low_u64_be(1), H160::from_low_u64_be(2));
+        let order_tokens = HashMap::from([(uid.clone(), (sell, buy))]);
+        // Swaps 1000 sell token for 2000 buy token.
+        let solution = |sell_price: &str, buy_price: &str| {
+            serde_json::json!({
+                "prices": {
+                    format!("{sell:?}"): sell_price,
+                    format!("{buy:?}"): buy_price,
+                },
+                "trades": [{
+                    "kind": "fulfillment",
+                    "order": uid,
+                    "executedAmount": "1000",
+                }],
+                "interactions": [{
+                    "kind": "liquidity",
+                    "inputToken": format!("{sell:?}"),
+                    "outputToken": format!("{buy:?}"),
+                    "inputAmount": "1000",
+                    "outputAmount": "2000",
+                }],
+            })
+        };
+        let severities = |solution: serde_json::Value| {
+            price_discrepancies(&solution, &order_tokens)
+                .into_iter()
+                .map(|discrepancy| discrepancy.severity)
+                .collect::<Vec<_>>()
+        };
+
+        assert!(severities(solution("2000", "1000")).is_empty());
+        // Within the fees the clearing prices may charge.
+        assert!(severities(solution("1990", "1000")).is_empty());
+        assert_eq!(severities(solution("2100", "1000")), vec![Severity::Error]);
+        assert_eq!(
+            severities(solution("1500", "1000")),
+            vec![Severity::Warning]
+        );
+        assert_eq!(severities(solution("2000", "0")), vec![Severity::Error]);
+        // Trades of unknown orders can't be checked.
+        assert!(price_discrepancies(&solution("2100", "1000"), &HashMap::new()).is_empty());
+    }
+
+    #[test]
+    fn attributes_swaps_to_trades_of_merged_solutions() {
+        let uids = [
+            format!("0x{}", "01".repeat(56)),
+            format!("0x{}", "02".repeat(56)),
+        ];
+        let token = |token: u64| format!("{:?}", H160::from_low_u64_be(token));
+        let order_tokens = HashMap::from([
+            (
+                uids[0].clone(),
+                (H160::from_low_u64_be(1), H160::from_low_u64_be(3)),
+            ),
+            (
+                uids[1].clone(),
+                (H160::from_low_u64_be(4), H160::from_low_u64_be(3)),
+            ),
+        ]);
+        let interaction = |input: u64, output: u64, input_amount: &str, output_amount: &str| {
+            serde_json::json!({
+                "kind": "liquidity",
+                "inputToken": token(input),
+                "outputToken": token(output),
+                "inputAmount": input_amount,
+                "outputAmount": output_amount,
+            })
+        };
+        // Swaps 1000 of token 1 for 2000 of token 3 through token 2, and 100
+        // of token 4 for 50 of token 3 directly.
+        let solution = |price_4: &str| {
+            serde_json::json!({
+                "prices": {
+                    token(1): "2000",
+                    token(3): "1000",
+                    token(4): price_4,
+                },
+                "trades": [
+                    { "kind": "fulfillment", "order": uids[0], "executedAmount": "1000" },
+                    { "kind": "fulfillment", "order": uids[1], "executedAmount": "100" },
+                ],
+                "interactions": [
+                    interaction(1, 2, "1000", "500"),
+                    interaction(4, 3, "100", "50"),
+                    interaction(2, 3, "500", "2000"),
+                ],
+            })
+        };
+        let discrepancies = |solution: serde_json::Value| {
+            price_discrepancies(&solution, &order_tokens)
+                .into_iter()
+                .map(|discrepancy| {
+                    (
+                        discrepancy.order,
+                        discrepancy.severity,
+                        discrepancy.swapped_sell_amount,
+                        discrepancy.swapped_buy_amount,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert!(discrepancies(solution("500")).is_empty());
+        assert_eq!(
+            discrepancies(solution("600")),
+            vec![(
+                uids[1].clone(),
+                Severity::Error,
+                "100".to_owned(),
+                "50".to_owned()
+            )]
+        );
+    }
+
+    #[test]
+    fn requires_single_pool_version_per_route() {
+        let mut hops = vec![hop(0, 1, 2, 100, 50), hop(1, 2, 3, 50, 25)];
+        assert_eq!(
+            route_pool_version(&hops.iter().collect::<Vec<_>>()),
+            Some(PoolVersion::V3)
+        );
+        hops[1].pool_version = PoolVersion::V2;
+        assert_eq!(route_pool_version(&hops.iter().collect::<Vec<_>>()), None);
+    }
+}
@@ -0,0 +1,77 @@
+//! External strategy endpoints.
+//!
+//! External strategies implement the same `/solve` API as this solver. They
+//! receive the auction including any independently fetched liquidity, and
+//! their solutions are proposed alongside the locally computed ones.
+
+use {
+    anyhow::{Context, Result},
+    reqwest::Client,
+    solvers_dto::solution::Solutions,
+    std::time::Duration,
+    url::Url,
+};
+
+/// An external strategy endpoint.
+#[derive(Clone, Debug)]
+pub struct Strategy {
+    pub name: String,
+    pub url: Url,
+}
+
+pub struct Strategies {
+    client: Client,
+    strategies: Vec<Strategy>,
+    timeout: Duration,
+}
+
+impl Strategies {
+    pub fn new(strategies: Vec<Strategy>, timeout: Duration) -> Self {
+        Self {
+            client: Client::new(),
+            strategies,
+            timeout,
+        }
+    }
+
+    /// Forwards the auction to all strategies concurrently, returning the
+    /// solutions of the strategies that responded within the timeout. Failing
+    /// strategies are logged and skipped.
+    pub async fn solve(&self, auction: &serde_json::Value) -> Vec<(&Strategy, Solutions)> {
+        futures::future::join_all(self.strategies.iter().map(async |strategy| {
+            match self.request(strategy, auction).await {
+                Ok(solutions) => {
+                    tracing::debug!(
+                        strategy = %strategy.name,
+                        solutions = solutions.solutions.len(),
+                        "received external solutions"
+                    );
+                    Some((strategy, solutions))
+                }
+                Err(err) => {
+                    tracing::warn!(?err, strategy = %strategy.name, "external strategy failed");
+                    None
+                }
+            }
+        }))
+        .await
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    async fn request(&self, strategy: &Strategy, auction: &serde_json::Value) -> Result<Solutions> {
+        self.client
+            .post(strategy.url.clone())
+            .json(auction)
+            .timeout(self.timeout)
+            .send()
+            .await
+            .context("request")?
+            .error_for_status()
+            .context("status")?
+            .json()
+            .await
+            .context("response")
+    }
+}
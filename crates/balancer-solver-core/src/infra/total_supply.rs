@@ -0,0 +1,34 @@
+//! Fetching of the total supply of pool tokens.
+
+use {
+    crate::domain::eth,
+    contracts::alloy::ERC20,
+    ethrpc::alloy::conversions::{IntoAlloy, IntoLegacy},
+    url::Url,
+};
+
+#[derive(Clone)]
+pub struct TotalSupply(ethrpc::Web3);
+
+impl TotalSupply {
+    pub fn new(node_url: &Url) -> Self {
+        Self(ethrpc::web3(
+            Default::default(),
+            Default::default(),
+            node_url,
+            "total-supply",
+        ))
+    }
+
+    /// Returns the total supply of the specified token, or `None` if it could
+    /// not be fetched.
+    pub async fn fetch(&self, token: eth::TokenAddress) -> Option<eth::U256> {
+        ERC20::Instance::new(token.0.into_alloy(), self.0.alloy.clone())
+            .totalSupply()
+            .call()
+            .await
+            .inspect_err(|err| tracing::debug!(?err, ?token, "failed to fetch total supply"))
+            .ok()
+            .map(IntoLegacy::into_legacy)
+    }
+}
@@ -0,0 +1,238 @@
+//! The Balancer solver engine as a library.
+//!
+//! Solves auctions in process without the HTTP API, so that other Rust
+//! services, e.g. market making bots, can embed the routing engine directly.
+//! Auctions and solutions are the data transfer objects of the solver engine
+//! API, which makes embedding the solver and calling it over HTTP
+//! interchangeable. The API of this crate is kept stable across changes of
+//! the solver engine's internals.
+//!
+//! The solver engine's internals are exported for the `balancer-solver` crate,
+//! which serves them over HTTP. They aren't part of the stable API.
+
+// TODO remove this once the crate stabilizes a bit.
+#![allow(dead_code)]
+#![recursion_limit = "256"]
+
+#[doc(hidden)]
+pub mod boundary;
+#[doc(hidden)]
+pub mod domain;
+#[doc(hidden)]
+pub mod dto;
+#[doc(hidden)]
+pub mod embed;
+#[doc(hidden)]
+pub mod infra;
+#[doc(hidden)]
+pub mod util;
+
+use std::path::Path;
+
+pub use {
+    ethereum_types::{H160, U256},
+    solvers_dto::{
+        auction::{Auction, Liquidity},
+        solution::Solutions,
+    },
+    url::Url,
+};
+
+/// The configuration of the solver engine. The settings have the meaning of
+/// the corresponding settings of the solver engine's `baseline`
+/// configuration.
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// The chain to solve auctions for. Determines the canonical WETH
+    /// contract unless `weth` is specified.
+    pub chain_id: Option<u64>,
+    /// The WETH contract, for chains without a canonical one.
+    pub weth: Option<H160>,
+    /// The tokens that can appear as intermediate hops of routes, besides
+    /// WETH.
+    pub base_tokens: Vec<H160>,
+    /// The maximum number of intermediate hops of routes.
+    pub max_hops: usize,
+    /// The maximum number of pieces partially fillable orders are divided
+    /// into.
+    pub max_partial_attempts: usize,
+    /// The share of the price improvement over an order's limit price to keep
+    /// as solver margin, in basis points.
+    pub surplus_share_bps: u32,
+    /// The gas added to the gas estimate of routes for the settlement
+    /// overhead.
+    pub solution_gas_offset: i64,
+    /// The maximum amount of gas a solution may use.
+    pub max_solution_gas: Option<u64>,
+    /// Whether to combine the solutions of orders not sharing any tokens.
+    pub merge_solutions: bool,
+    /// The amount of the native token to estimate native token prices with.
+    pub native_token_price_estimation_amount: U256,
+    /// The node to quote Uniswap V3 and V4 liquidity with.
+    pub uni_v3_node_url: Option<Url>,
+    /// The node to quote ERC4626 vaults with. Defaults to `uni_v3_node_url`.
+    pub erc4626_node_url: Option<Url>,
+}
+
+impl Config {
+    /// Reads the configuration from the TOML file at the specified path, which
+    /// has the format of the solver engine's `baseline` configuration.
+    /// Settings of features that aren't available in process are ignored.
+    pub async fn from_file(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let config = embed::Config::load(path.as_ref())
+            .await
+            .map_err(|err| Error::Config(format!("{err:#}")))?;
+        Ok(Self {
+            chain_id: config.chain_id,
+            weth: config.weth,
+            base_tokens: config.base_tokens,
+            max_hops: config.max_hops,
+            max_partial_attempts: config.max_partial_attempts,
+            surplus_share_bps: config.surplus_share_bps,
+            solution_gas_offset: config.solution_gas_offset,
+            max_solution_gas: config.max_solution_gas,
+            merge_solutions: config.merge_solutions,
+            native_token_price_estimation_amount: config.native_token_price_estimation_amount,
+            uni_v3_node_url: config.uni_v3_node_url,
+            erc4626_node_url: config.erc4626_node_url,
+        })
+    }
+
+    fn to_engine(&self) -> embed::Config {
+        embed::Config {
+            chain_id: self.chain_id,
+            weth: self.weth,
+            base_tokens: self.base_tokens.clone(),
+            max_hops: self.max_hops,
+            max_partial_attempts: self.max_partial_attempts,
+            surplus_share_bps: self.surplus_share_bps,
+            solution_gas_offset: self.solution_gas_offset,
+            max_solution_gas: self.max_solution_gas,
+            merge_solutions: self.merge_solutions,
+            native_token_price_estimation_amount: self.native_token_price_estimation_amount,
+            uni_v3_node_url: self.uni_v3_node_url.clone(),
+            erc4626_node_url: self.erc4626_node_url.clone(),
+        }
+    }
+}
+
+/// A solver engine. Creating the solver sets up the configured background
+/// tasks and node connections, so it should be created once and reused for
+/// solving auctions.
+pub struct Solver(embed::Engine);
+
+impl Solver {
+    /// Creates a solver engine. Fails if the configuration is invalid.
+    pub async fn new(config: &Config) -> Result<Self, Error> {
+        embed::Engine::new(&config.to_engine())
+            .await
+            .map(Self)
+            .map_err(|err| Error::Config(format!("{err:#}")))
+    }
+
+    /// Solves the auction over its liquidity and the specified liquidity.
+    pub async fn solve(
+        &self,
+        auction: Auction,
+        liquidity: Vec<Liquidity>,
+    ) -> Result<Solutions, Error> {
+        self.0
+            .solve(auction, liquidity)
+            .await
+            .map_err(Error::Auction)
+    }
+}
+
+/// Solves a single auction with a solver created for it. Prefer [`Solver`]
+/// for solving more than one auction.
+pub async fn solve(
+    auction: Auction,
+    liquidity: Vec<Liquidity>,
+    config: &Config,
+) -> Result<Solutions, Error> {
+    Solver::new(config).await?.solve(auction, liquidity).await
+}
+
+/// Errors creating a solver or solving an auction.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The solver could not be created because its configuration is invalid.
+    #[error("invalid configuration: {0}")]
+    Config(String),
+    /// The auction could not be solved because it is invalid.
+    #[error("invalid auction: {0}")]
+    Auction(&'static str),
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, serde_json::json};
+
+    #[tokio::test]
+    async fn solves_auctions_in_process() {
+        let auction = serde_json::from_value(json!({
+            "id": "1",
+            "tokens": {},
+            "orders": [],
+            "liquidity": [],
+            "effectiveGasPrice": "15000000000",
+            "deadline": chrono::Utc::now() + chrono::Duration::seconds(10),
+            "surplusCapturingJitOrderOwners": [],
+        }))
+        .unwrap();
+        let config = Config::from_file(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../balancer-solver/config/example.baseline.toml"
+        ))
+        .await
+        .unwrap();
+
+        let solutions = solve(auction, Vec::new(), &config).await.unwrap();
+        assert!(solutions.solutions.is_empty());
+    }
+
+    #[tokio::test]
+    async fn rejects_invalid_configurations() {
+        let config = Config {
+            chain_id: Some(1),
+            weth: None,
+            base_tokens: Vec::new(),
+            max_hops: 0,
+            max_partial_attempts: 5,
+            surplus_share_bps: 0,
+            solution_gas_offset: 106_391,
+            max_solution_gas: None,
+            merge_solutions: false,
+            native_token_price_estimation_amount: U256::exp10(17),
+            uni_v3_node_url: None,
+            erc4626_node_url: None,
+        };
+        assert!(Solver::new(&config).await.is_ok());
+
+        for config in [
+            Config {
+                chain_id: None,
+                ..config.clone()
+            },
+            Config {
+                weth: Some(H160::repeat_byte(1)),
+                ..config.clone()
+            },
+            Config {
+                chain_id: Some(0),
+                ..config.clone()
+            },
+            Config {
+                surplus_share_bps: 10_001,
+                ..config.clone()
+            },
+        ] {
+            assert!(matches!(Solver::new(&config).await, Err(Error::Config(_))));
+        }
+
+        assert!(matches!(
+            Config::from_file("does-not-exist.toml").await,
+            Err(Error::Config(_))
+        ));
+    }
+}
@@ -0,0 +1,107 @@
+//! Coalescing of identical requests arriving in bursts.
+
+use {
+    futures::{
+        FutureExt,
+        future::{BoxFuture, Shared},
+    },
+    std::{
+        collections::HashMap,
+        future::Future,
+        hash::Hash,
+        sync::Mutex,
+        time::{Duration, Instant},
+    },
+};
+
+/// Batches identical requests arriving within a time window, computing their
+/// result once and handing it out to all of them.
+///
+/// A batch opens with the first request for a key and starts computing its
+/// result right away. Identical requests arriving within the window join the
+/// batch, regardless of whether its result is still being computed.
+pub struct Batcher<K, V: Clone> {
+    window: Duration,
+    batches: Mutex<HashMap<K, Batch<V>>>,
+}
+
+struct Batch<V: Clone> {
+    opened: Instant,
+    result: Shared<BoxFuture<'static, V>>,
+}
+
+impl<K, V> Batcher<K, V>
+where
+    K: Eq + Hash,
+    V: Clone + Send + Sync + 'static,
+{
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            batches: Default::default(),
+        }
+    }
+
+    /// Returns the result of the open batch for the key, or opens a new batch
+    /// computing its result with `compute`. Also returns whether an open batch
+    /// was joined.
+    pub async fn run<F>(&self, key: K, compute: impl FnOnce() -> F) -> (V, bool)
+    where
+        F: Future<Output = V> + Send + 'static,
+    {
+        let (result, joined) = {
+            let mut batches = self.batches.lock().unwrap();
+            batches.retain(|_, batch| batch.opened.elapsed() < self.window);
+            match batches.get(&key) {
+                Some(batch) => (batch.result.clone(), true),
+                None => {
+                    let result = compute().boxed().shared();
+                    let batch = Batch {
+                        opened: Instant::now(),
+                        result: result.clone(),
+                    };
+                    batches.insert(key, batch);
+                    (result, false)
+                }
+            }
+        };
+        (result.await, joined)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        std::sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    #[tokio::test]
+    async fn coalesces_identical_requests_within_window() {
+        let window = Duration::from_millis(50);
+        let batcher = Batcher::new(window);
+        let computed = AtomicUsize::new(0);
+        let request = |key: &'static str| {
+            batcher.run(key, || {
+                computed.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                    key.len()
+                }
+            })
+        };
+
+        let (a, b, c) = tokio::join!(request("a"), request("a"), request("bb"));
+        assert_eq!((a, b, c), ((1, false), (1, true), (2, false)));
+        assert_eq!(computed.load(Ordering::SeqCst), 2);
+
+        // Completed batches are still joined within the window...
+        assert_eq!(request("a").await, (1, true));
+        assert_eq!(computed.load(Ordering::SeqCst), 2);
+
+        // ...but not afterwards.
+        tokio::time::sleep(window).await;
+        assert_eq!(request("a").await, (1, false));
+        assert_eq!(computed.load(Ordering::SeqCst), 3);
+    }
+}
@@ -1,5 +1,7 @@
+pub mod batch;
 pub mod bytes;
 pub mod conv;
 pub mod fmt;
 pub mod math;
+pub mod redact;
 pub mod serialize;
@@ -0,0 +1,109 @@
+//! Redaction of saved auction and solution files.
+//!
+//! Redacted files can be shared outside of the team for debugging, since they
+//! no longer identify the traders. Amounts, tokens, liquidity and interactions
+//! are preserved, so the redacted auctions can still be solved and the
+//! solutions replayed.
+
+use serde_json::Value;
+
+/// Fields holding addresses of traders.
+const ADDRESSES: &[&str] = &["owner", "receiver"];
+/// Fields holding order signatures.
+const SIGNATURES: &[&str] = &["signature"];
+/// Fields holding order app data, which may reference the trader or the
+/// frontend they used.
+const APP_DATA: &[&str] = &["appData", "fullAppData"];
+
+/// The length of an order uid in bytes, consisting of the order digest, the
+/// owner address and the validity timestamp.
+const UID_LENGTH: usize = 56;
+/// The range of the owner address within an order uid.
+const UID_OWNER: std::ops::Range<usize> = 32..52;
+
+/// Redacts owner addresses, signatures and app data of all orders in the
+/// JSON document in place. Owner addresses are also zeroed in order uids, so
+/// uids stay consistent between auction and solution files.
+pub fn redact(value: &mut Value) {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object.iter_mut() {
+                let key = key.as_str();
+                if ADDRESSES.contains(&key) && value.is_string() {
+                    *value = Value::String(format!("0x{}", "00".repeat(20)));
+                } else if SIGNATURES.contains(&key) && value.is_string() {
+                    *value = Value::String("0x".to_owned());
+                } else if APP_DATA.contains(&key) && value.is_string() {
+                    *value = Value::String(format!("0x{}", "00".repeat(32)));
+                } else {
+                    redact(value);
+                }
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(redact),
+        Value::String(string) => {
+            if let Some(uid) = redact_uid(string) {
+                *string = uid;
+            }
+        }
+        Value::Null | Value::Bool(_) | Value::Number(_) => {}
+    }
+}
+
+/// Returns the order uid with the owner address zeroed, or `None` if the
+/// string is not an order uid.
+fn redact_uid(string: &str) -> Option<String> {
+    let mut bytes = const_hex::decode(string.strip_prefix("0x")?).ok()?;
+    if bytes.len() != UID_LENGTH {
+        return None;
+    }
+    bytes[UID_OWNER].fill(0);
+    Some(const_hex::encode_prefixed(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, serde_json::json};
+
+    #[test]
+    fn redacts_trader_data() {
+        let uid = format!("0x{}{}{}", "11".repeat(32), "22".repeat(20), "33".repeat(4));
+        let redacted_uid = format!("0x{}{}{}", "11".repeat(32), "00".repeat(20), "33".repeat(4));
+        let mut auction = json!({
+            "orders": [{
+                "uid": uid,
+                "owner": format!("0x{}", "22".repeat(20)),
+                "receiver": format!("0x{}", "44".repeat(20)),
+                "sellAmount": "1000",
+                "signature": "0x1234",
+                "appData": format!("0x{}", "55".repeat(32)),
+            }],
+            "liquidity": [{ "address": format!("0x{}", "66".repeat(20)) }],
+        });
+        let mut solutions = json!({
+            "solutions": [{ "trades": [{ "order": uid, "executedAmount": "1000" }] }],
+        });
+
+        redact(&mut auction);
+        redact(&mut solutions);
+
+        assert_eq!(
+            auction,
+            json!({
+                "orders": [{
+                    "uid": redacted_uid,
+                    "owner": format!("0x{}", "00".repeat(20)),
+                    "receiver": format!("0x{}", "00".repeat(20)),
+                    "sellAmount": "1000",
+                    "signature": "0x",
+                    "appData": format!("0x{}", "00".repeat(32)),
+                }],
+                "liquidity": [{ "address": format!("0x{}", "66".repeat(20)) }],
+            })
+        );
+        assert_eq!(
+            solutions["solutions"][0]["trades"][0]["order"],
+            json!(redacted_uid)
+        );
+    }
+}
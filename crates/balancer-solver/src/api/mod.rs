@@ -3,16 +3,17 @@
 use {
     crate::domain::solver::Solver,
     observe::distributed_tracing::tracing_axum::{make_span, record_trace_id},
+    shared::listen,
     std::{future::Future, net::SocketAddr, sync::Arc},
     tokio::sync::oneshot,
 };
 
-mod routes;
+pub(crate) mod routes;
 
 const REQUEST_BODY_LIMIT: usize = 10 * 1024 * 1024;
 
 pub struct Api {
-    pub addr: SocketAddr,
+    pub listeners: Vec<listen::Listener>,
     pub solver: Solver,
 }
 
@@ -22,7 +23,7 @@ impl Api {
         bind: Option<oneshot::Sender<SocketAddr>>,
         shutdown: impl Future<Output = ()> + Send + 'static,
     ) -> Result<(), hyper::Error> {
-        let app = axum::Router::new()
+        let mut app = axum::Router::new()
             .layer(tower::ServiceBuilder::new().layer(
                 tower_http::limit::RequestBodyLimitLayer::new(REQUEST_BODY_LIMIT),
             ))
@@ -30,6 +31,35 @@ impl Api {
             .route("/healthz", axum::routing::get(routes::healthz))
             .route("/solve", axum::routing::post(routes::solve))
             .route("/notify", axum::routing::post(routes::notify))
+            .route("/stats/pairs", axum::routing::get(routes::stats_pairs))
+            .route(
+                "/stats/slippage",
+                axum::routing::get(routes::stats_slippage),
+            )
+            .route("/events", axum::routing::get(routes::events))
+            .route(
+                "/config/routing",
+                axum::routing::get(routes::get_routing).patch(routes::patch_routing),
+            );
+        if self.solver.math_eval() {
+            app = app.route("/math/eval", axum::routing::post(routes::math_eval));
+        }
+        if self.solver.heap_debug() {
+            app = app.route("/debug/heap", axum::routing::get(routes::heap));
+        }
+        if self.solver.explorer().is_some() {
+            app = app
+                .route("/explorer", axum::routing::get(routes::explorer))
+                .route(
+                    "/explorer/artifacts",
+                    axum::routing::get(routes::explorer_artifacts),
+                )
+                .route(
+                    "/explorer/artifacts/:name",
+                    axum::routing::get(routes::explorer_artifact),
+                );
+        }
+        let app = app
             .layer(
                 tower::ServiceBuilder::new()
                     .layer(tower_http::trace::TraceLayer::new_for_http().make_span_with(make_span))
@@ -39,11 +69,17 @@ impl Api {
             // axum's default body limit needs to be disabled to not have the default limit on top of our custom limit
             .layer(axum::extract::DefaultBodyLimit::disable());
 
-        let server = axum::Server::bind(&self.addr).serve(app.into_make_service());
-        if let Some(bind) = bind {
-            let _ = bind.send(server.local_addr());
+        let incoming = listen::bind(&self.listeners)
+            .unwrap_or_else(|err| panic!("failed to bind the API listeners: {err:#}"));
+        if let Some(bind) = bind
+            && let Some(addr) = incoming.local_addrs.first()
+        {
+            let _ = bind.send(*addr);
         }
 
-        server.with_graceful_shutdown(shutdown).await
+        axum::Server::builder(incoming)
+            .serve(app.into_make_service())
+            .with_graceful_shutdown(shutdown)
+            .await
     }
 }
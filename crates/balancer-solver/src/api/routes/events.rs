@@ -0,0 +1,138 @@
+use {
+    crate::domain::{auction, events, solver::Solver},
+    axum::{
+        extract::ws::{Message, WebSocket, WebSocketUpgrade},
+        response::IntoResponse,
+    },
+    serde::Serialize,
+    std::sync::Arc,
+    tokio::sync::broadcast::{self, error::RecvError},
+};
+
+/// Streams the lifecycle events of handled auctions to the websocket client
+/// as JSON text messages.
+pub async fn events(
+    state: axum::extract::State<Arc<Solver>>,
+    ws: WebSocketUpgrade,
+) -> impl IntoResponse {
+    let events = state.events().subscribe();
+    ws.on_upgrade(|socket| stream(socket, events))
+}
+
+async fn stream(mut socket: WebSocket, mut events: broadcast::Receiver<events::Entry>) {
+    loop {
+        let entry = match events.recv().await {
+            Ok(entry) => entry,
+            Err(RecvError::Lagged(missed)) => {
+                tracing::debug!(missed, "events subscriber lagging behind");
+                continue;
+            }
+            Err(RecvError::Closed) => break,
+        };
+        let message = match serde_json::to_string(&Entry::from(entry)) {
+            Ok(message) => message,
+            Err(err) => {
+                tracing::warn!(?err, "failed to serialize event");
+                continue;
+            }
+        };
+        if socket.send(Message::Text(message)).await.is_err() {
+            // The client disconnected.
+            break;
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Entry {
+    time: chrono::DateTime<chrono::Utc>,
+    #[serde(flatten)]
+    event: Event,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+enum Event {
+    #[serde(rename_all = "camelCase")]
+    AuctionReceived {
+        auction_id: String,
+        orders: usize,
+        tokens: usize,
+    },
+    #[serde(rename_all = "camelCase")]
+    LiquidityFetched {
+        auction_id: String,
+        liquidity: usize,
+        deadline_exceeded: bool,
+    },
+    #[serde(rename_all = "camelCase")]
+    SolutionsComputed {
+        auction_id: String,
+        solutions: usize,
+    },
+    #[serde(rename_all = "camelCase")]
+    SolutionsReturned {
+        auction_id: String,
+        solutions: usize,
+        elapsed_ms: u128,
+    },
+    #[serde(rename_all = "camelCase")]
+    VerificationFinished {
+        auction_id: String,
+        solutions: usize,
+        quote_errors: usize,
+    },
+}
+
+impl From<events::Entry> for Entry {
+    fn from(entry: events::Entry) -> Self {
+        let id = |auction: auction::Id| auction.to_string();
+        let event = match entry.event {
+            events::Event::AuctionReceived {
+                auction,
+                orders,
+                tokens,
+            } => Event::AuctionReceived {
+                auction_id: id(auction),
+                orders,
+                tokens,
+            },
+            events::Event::LiquidityFetched {
+                auction,
+                liquidity,
+                deadline_exceeded,
+            } => Event::LiquidityFetched {
+                auction_id: id(auction),
+                liquidity,
+                deadline_exceeded,
+            },
+            events::Event::SolutionsComputed { auction, solutions } => Event::SolutionsComputed {
+                auction_id: id(auction),
+                solutions,
+            },
+            events::Event::SolutionsReturned {
+                auction,
+                solutions,
+                elapsed,
+            } => Event::SolutionsReturned {
+                auction_id: id(auction),
+                solutions,
+                elapsed_ms: elapsed.as_millis(),
+            },
+            events::Event::VerificationFinished {
+                auction,
+                solutions,
+                quote_errors,
+            } => Event::VerificationFinished {
+                auction_id: id(auction),
+                solutions,
+                quote_errors,
+            },
+        };
+        Self {
+            time: entry.time,
+            event,
+        }
+    }
+}
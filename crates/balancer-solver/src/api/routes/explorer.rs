@@ -0,0 +1,75 @@
+//! A minimal web UI for browsing the artifacts saved to the file sink, e.g.
+//! `http://localhost:7872/explorer`. It lists the saved artifacts by auction,
+//! renders the routes of the saved solutions and links addresses and
+//! transactions to the block explorer of the chain.
+
+use {
+    crate::{domain::solver::Solver, infra::artifacts::FileSink},
+    axum::{
+        http::StatusCode,
+        response::{Html, IntoResponse},
+    },
+    serde::Serialize,
+    std::sync::Arc,
+};
+
+const INDEX: &str = include_str!("explorer.html");
+
+pub async fn index() -> Html<&'static str> {
+    Html(INDEX)
+}
+
+pub async fn artifacts(state: axum::extract::State<Arc<Solver>>) -> axum::response::Response {
+    let Some(directory) = state.explorer() else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    match FileSink::list(directory).await {
+        Ok(artifacts) => axum::response::Json(Artifacts {
+            block_explorer: block_explorer(state.chain_id()),
+            artifacts,
+        })
+        .into_response(),
+        Err(err) => {
+            tracing::warn!(?err, "failed to list saved artifacts");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+pub async fn artifact(
+    state: axum::extract::State<Arc<Solver>>,
+    axum::extract::Path(name): axum::extract::Path<String>,
+) -> axum::response::Response {
+    let Some(directory) = state.explorer() else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    match FileSink::read(directory, &name).await {
+        Ok(Some(content)) => axum::response::Json(content).into_response(),
+        Ok(None) => StatusCode::NOT_FOUND.into_response(),
+        Err(err) => {
+            tracing::warn!(?err, name, "failed to read saved artifact");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Artifacts {
+    block_explorer: Option<&'static str>,
+    artifacts: Vec<crate::infra::artifacts::Saved>,
+}
+
+/// The block explorer addresses and transactions get linked to.
+fn block_explorer(chain_id: u64) -> Option<&'static str> {
+    match chain_id {
+        1 => Some("https://etherscan.io"),
+        100 => Some("https://gnosisscan.io"),
+        137 => Some("https://polygonscan.com"),
+        8453 => Some("https://basescan.org"),
+        42161 => Some("https://arbiscan.io"),
+        43114 => Some("https://snowtrace.io"),
+        11155111 => Some("https://sepolia.etherscan.io"),
+        _ => None,
+    }
+}
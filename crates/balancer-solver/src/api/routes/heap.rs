@@ -0,0 +1,7 @@
+use crate::infra::heap;
+
+/// Summarizes the heap usage of the process and the allocations of the
+/// subsystems of the solver engine.
+pub async fn heap() -> axum::response::Json<heap::Stats> {
+    axum::response::Json(heap::stats())
+}
@@ -0,0 +1,101 @@
+use {
+    super::Response,
+    crate::{
+        domain::{eth, order, solver},
+        dto,
+        util::serialize,
+    },
+    axum::http::StatusCode,
+    serde::{Deserialize, Serialize},
+    serde_with::{DisplayFromStr, serde_as},
+    std::sync::Arc,
+};
+
+/// Evaluates a swap through a single pool with the solver's pool math. The
+/// computation only depends on the request, so the results can be compared
+/// against other implementations of the pool math.
+pub async fn eval(
+    state: axum::extract::State<Arc<solver::Solver>>,
+    axum::extract::Json(request): axum::extract::Json<EvalRequest>,
+) -> (StatusCode, axum::response::Json<Response<EvalResponse>>) {
+    let liquidity = match dto::auction::convert_dto_liquidity_to_domain(&request.pool) {
+        Ok(liquidity) => liquidity,
+        Err(err) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                axum::response::Json(Response::Err(err)),
+            );
+        }
+    };
+    if liquidity.state.is_quoted_on_chain() {
+        return (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            axum::response::Json(Response::Err(
+                "swaps through liquidity quoted on-chain can't be evaluated".into(),
+            )),
+        );
+    }
+    let asset = |token| eth::Asset {
+        token: eth::TokenAddress(token),
+        amount: request.amount,
+    };
+    let request = solver::Request {
+        sell: asset(request.token_in),
+        buy: asset(request.token_out),
+        side: match request.kind {
+            SwapKind::GivenIn => order::Side::Sell,
+            SwapKind::GivenOut => order::Side::Buy,
+        },
+        wrappers: Vec::new(),
+    };
+
+    match state.evaluate(&liquidity, request).await {
+        Some(segment) => (
+            StatusCode::OK,
+            axum::response::Json(Response::Ok(EvalResponse {
+                amount_in: segment.input.amount,
+                amount_out: segment.output.amount,
+                gas: segment.gas.0,
+            })),
+        ),
+        None => (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            axum::response::Json(Response::Err("swap can't be computed for the pool".into())),
+        ),
+    }
+}
+
+/// Whether the amount of a swap is the amount going in or coming out of the
+/// pool.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum SwapKind {
+    GivenIn,
+    GivenOut,
+}
+
+/// A swap through a pool given in the auction liquidity format.
+#[serde_as]
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct EvalRequest {
+    pool: solvers_dto::auction::Liquidity,
+    token_in: eth::H160,
+    token_out: eth::H160,
+    kind: SwapKind,
+    #[serde_as(as = "serialize::U256")]
+    amount: eth::U256,
+}
+
+/// The computed amounts of a swap as decimal strings.
+#[serde_as]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EvalResponse {
+    #[serde_as(as = "DisplayFromStr")]
+    amount_in: eth::U256,
+    #[serde_as(as = "DisplayFromStr")]
+    amount_out: eth::U256,
+    #[serde_as(as = "DisplayFromStr")]
+    gas: eth::U256,
+}
@@ -1,4 +1,11 @@
-pub async fn metrics() -> String {
+use crate::infra::{heap, metrics};
+
+pub async fn metrics(headers: axum::http::HeaderMap) -> impl axum::response::IntoResponse {
+    metrics::heap(&heap::stats());
     let registry = observe::metrics::get_registry();
-    observe::metrics::encode(registry)
+    let accept = headers
+        .get(axum::http::header::ACCEPT)
+        .and_then(|accept| accept.to_str().ok());
+    let (content_type, metrics) = observe::metrics::encode_accepted(registry, accept);
+    ([(axum::http::header::CONTENT_TYPE, content_type)], metrics)
 }
@@ -1,11 +1,28 @@
-use serde::Serialize;
+use {crate::dto::Error, serde::Serialize};
 
+mod events;
+mod explorer;
 mod healthz;
+mod heap;
+mod math;
 mod metrics;
 mod notify;
+mod routing;
 mod solve;
+mod stats;
 
-pub(super) use {healthz::healthz, metrics::metrics, notify::notify, solve::solve};
+pub(super) use {
+    events::events,
+    explorer::{artifact as explorer_artifact, artifacts as explorer_artifacts, index as explorer},
+    healthz::healthz,
+    heap::heap,
+    math::eval as math_eval,
+    metrics::metrics,
+    notify::notify,
+    routing::{get as get_routing, patch as patch_routing},
+    solve::solve,
+    stats::{pairs as stats_pairs, slippage as stats_slippage},
+};
 
 #[derive(Debug, Serialize)]
 #[serde(untagged)]
@@ -13,14 +30,3 @@ pub enum Response<T> {
     Ok(T),
     Err(Error),
 }
-
-#[derive(Debug, Serialize)]
-pub struct Error {
-    pub message: &'static str,
-}
-
-impl From<&'static str> for Error {
-    fn from(message: &'static str) -> Self {
-        Self { message }
-    }
-}
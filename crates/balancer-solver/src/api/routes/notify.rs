@@ -1,10 +1,42 @@
 use {
-    axum::{Json, http::StatusCode, response::IntoResponse},
-    solvers_dto::notification::Notification,
+    crate::domain::{bad_tokens::Outcome, solver::Solver},
+    axum::{Json, extract::State, http::StatusCode, response::IntoResponse},
+    solvers_dto::notification::{Kind, Notification, SolutionId},
+    std::sync::Arc,
     tracing::debug,
 };
 
-pub async fn notify(Json(notification): Json<Notification>) -> impl IntoResponse {
+pub async fn notify(
+    State(state): State<Arc<Solver>>,
+    Json(notification): Json<Notification>,
+) -> impl IntoResponse {
     debug!(?notification, "received notification");
+
+    let outcome = match notification.kind {
+        // Simulations that succeeded at least once likely failed because of
+        // changing on-chain state rather than the tokens involved.
+        Kind::SimulationFailed {
+            succeeded_once: false,
+            ..
+        }
+        | Kind::Revert { .. } => Some(Outcome::Failure),
+        Kind::Success { .. } => Some(Outcome::Success),
+        _ => None,
+    };
+    let (Some(auction), Some(solution)) = (notification.auction_id, notification.solution_id)
+    else {
+        return StatusCode::OK;
+    };
+    let solutions = match solution {
+        SolutionId::Single(id) => vec![id],
+        SolutionId::Merged(ids) => ids,
+    };
+    if let Some(outcome) = outcome {
+        state.record_settlement_outcome(auction, &solutions, outcome);
+    }
+    if let Kind::Success { transaction } = notification.kind {
+        state.record_settlement(auction, solutions, transaction);
+    }
+
     StatusCode::OK
 }
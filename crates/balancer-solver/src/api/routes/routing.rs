@@ -0,0 +1,125 @@
+use {
+    super::Response,
+    crate::domain::{
+        eth,
+        solver::{self, Solver},
+    },
+    axum::http::{HeaderMap, StatusCode, header::AUTHORIZATION},
+    serde::{Deserialize, Serialize},
+    std::sync::Arc,
+};
+
+pub async fn get(state: axum::extract::State<Arc<Solver>>) -> axum::response::Json<Routing> {
+    axum::response::Json(Routing::new(&state))
+}
+
+pub async fn patch(
+    state: axum::extract::State<Arc<Solver>>,
+    headers: HeaderMap,
+    axum::extract::Json(update): axum::extract::Json<RoutingUpdate>,
+) -> (StatusCode, axum::response::Json<Response<Routing>>) {
+    let token = headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    if !token.is_some_and(|token| state.authorizes_routing_update(token)) {
+        return (
+            StatusCode::UNAUTHORIZED,
+            axum::response::Json(Response::Err("unauthorized routing update".into())),
+        );
+    }
+    if let Err(err) = validate(&update) {
+        return (
+            StatusCode::BAD_REQUEST,
+            axum::response::Json(Response::Err(err.into())),
+        );
+    }
+
+    if let Some(base_tokens) = update.base_tokens {
+        state.set_base_tokens(base_tokens.into_iter().map(eth::TokenAddress).collect());
+    }
+    state.update_routing(|routing| {
+        if let Some(max_hops) = update.max_hops {
+            routing.max_hops = max_hops;
+        }
+        if let Some(max_partial_attempts) = update.max_partial_attempts {
+            routing.max_partial_attempts = max_partial_attempts;
+        }
+        if let Some(protocols) = update.protocols {
+            routing.protocols = protocols;
+        }
+    });
+    (
+        StatusCode::OK,
+        axum::response::Json(Response::Ok(Routing::new(&state))),
+    )
+}
+
+/// Checks the update against the bounds the configuration is loaded with.
+fn validate(update: &RoutingUpdate) -> Result<(), &'static str> {
+    if update
+        .base_tokens
+        .as_ref()
+        .is_some_and(|tokens| tokens.len() > solver::Routing::MAX_BASE_TOKENS)
+    {
+        return Err("base tokens must not have more than 20 tokens");
+    }
+    if update
+        .max_hops
+        .is_some_and(|hops| hops > solver::Routing::MAX_HOPS)
+    {
+        return Err("max hops must not exceed 2");
+    }
+    if update.max_partial_attempts == Some(0) {
+        return Err("max partial attempts must be at least 1");
+    }
+    Ok(())
+}
+
+/// The active routing configuration of the solver.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Routing {
+    base_tokens: Vec<eth::H160>,
+    /// The base tokens selected by liquidity depth, if automatic selection is
+    /// enabled. These are part of `base_tokens` until the next selection.
+    selected_base_tokens: Option<Vec<eth::H160>>,
+    max_hops: usize,
+    /// The maximum number of parts partially fillable orders get split into.
+    max_partial_attempts: usize,
+    /// The protocols liquidity gets fetched from, if the solver fetches its
+    /// own liquidity.
+    protocols: Option<Vec<String>>,
+}
+
+impl Routing {
+    fn new(solver: &Solver) -> Self {
+        let routing = solver.routing();
+        let mut base_tokens = routing
+            .base_tokens
+            .into_iter()
+            .map(|token| token.0)
+            .collect::<Vec<_>>();
+        base_tokens.sort();
+        Self {
+            base_tokens,
+            selected_base_tokens: solver
+                .selected_base_tokens()
+                .map(|tokens| tokens.into_iter().map(|token| token.0).collect()),
+            max_hops: routing.max_hops,
+            max_partial_attempts: routing.max_partial_attempts,
+            protocols: solver.protocols(),
+        }
+    }
+}
+
+/// A partial update of the routing configuration. Omitted fields are left
+/// unchanged.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct RoutingUpdate {
+    base_tokens: Option<Vec<eth::H160>>,
+    max_hops: Option<usize>,
+    max_partial_attempts: Option<usize>,
+    protocols: Option<Vec<String>>,
+}
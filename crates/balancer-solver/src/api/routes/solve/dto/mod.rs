@@ -1,4 +0,0 @@
-pub mod auction;
-pub mod solution;
-
-pub use solvers_dto::{auction::Auction, solution::Solutions};
@@ -1,25 +1,120 @@
-use {super::Response, tracing::Instrument};
-
-mod dto;
-
-use {crate::domain::solver::Solver, std::sync::Arc};
+use {
+    super::Response,
+    crate::{
+        domain::{events, solver::Solver},
+        dto,
+        infra::{artifacts, heap, price_comparison},
+    },
+    std::{collections::HashMap, sync::Arc, time::Instant},
+    tracing::Instrument,
+};
 
 pub async fn solve(
     state: axum::extract::State<Arc<Solver>>,
     headers: axum::http::HeaderMap,
-    axum::extract::Json(auction): axum::extract::Json<dto::Auction>,
+    body: axum::body::Bytes,
 ) -> (
     axum::http::StatusCode,
     axum::response::Json<Response<dto::Solutions>>,
 ) {
-    let handle_request = async {
-        // 🔍 LOG RAW REQUEST DATA FROM COW PROTOCOL
-        tracing::info!(
-            auction_id = ?auction.id,
-            orders_count = auction.orders.len(),
-            "🎯 RECEIVED SOLVE REQUEST FROM COW PROTOCOL"
-        );
+    // The body gets deserialized by hand rather than by the JSON extractor, so
+    // that malformed auctions get the same error responses as invalid ones.
+    let auction = match dto::auction::from_json(&body) {
+        Ok(auction) => auction,
+        Err(err) => {
+            return (
+                axum::http::StatusCode::BAD_REQUEST,
+                axum::response::Json(Response::Err(err)),
+            );
+        }
+    };
+
+    let handle_request = |state, headers, auction| {
+        solve_auction(state, headers, auction).instrument(tracing::info_span!("/solve"))
+    };
 
+    // Identical quote auctions arriving in bursts get solved once, answering
+    // all of them with the same solutions.
+    let batch = state
+        .quote_batches()
+        .filter(|_| auction.id.is_none())
+        .and_then(|batches| Some((batches, batch_key(&auction)?)));
+    let result = match batch {
+        Some((batches, key)) => {
+            let solver = Arc::clone(&state.0);
+            let (result, joined) = batches
+                .run(key, || async move {
+                    handle_request(solver, headers, auction)
+                        .await
+                        .map_err(|err| err.message)
+                })
+                .await;
+            if joined {
+                tracing::debug!("answered quote from a batch of identical quotes");
+                crate::infra::metrics::quote_batched();
+            }
+            result.map_err(super::Error::from)
+        }
+        None => handle_request(Arc::clone(&state.0), headers, auction).await,
+    };
+
+    match result {
+        Ok(solutions) => (
+            axum::http::StatusCode::OK,
+            axum::response::Json(Response::Ok(solutions)),
+        ),
+        Err(err) => (
+            axum::http::StatusCode::BAD_REQUEST,
+            axum::response::Json(Response::Err(err)),
+        ),
+    }
+}
+
+/// The key identifying quote auctions that can be answered with the same
+/// solutions: the whole auction apart from its deadline. Maps of JSON values
+/// are sorted by key, so the key doesn't depend on the order of the tokens.
+fn batch_key(auction: &dto::Auction) -> Option<String> {
+    let mut auction = serde_json::to_value(auction).ok()?;
+    let fields = auction.as_object_mut()?;
+    fields.remove("id");
+    fields.remove("deadline");
+    serde_json::to_string(&auction).ok()
+}
+
+/// Solves the auction, returning the solutions or why the auction is
+/// invalid.
+async fn solve_auction(
+    state: Arc<Solver>,
+    headers: axum::http::HeaderMap,
+    auction: dto::Auction,
+) -> Result<dto::Solutions, super::Error> {
+    let started = Instant::now();
+    state.events().publish(events::Event::AuctionReceived {
+        auction: auction.id.map_or(
+            crate::domain::auction::Id::Quote,
+            crate::domain::auction::Id::Solve,
+        ),
+        orders: auction.orders.len(),
+        tokens: auction.tokens.len(),
+    });
+
+    // 🔍 LOG RAW REQUEST DATA FROM COW PROTOCOL
+    tracing::info!(
+        auction_id = ?auction.id,
+        orders_count = auction.orders.len(),
+        "🎯 RECEIVED SOLVE REQUEST FROM COW PROTOCOL"
+    );
+
+    // Quotes on the lightweight path skip the detailed request logging as
+    // well as all persistence and verification of the results.
+    let lightweight_quote = auction.id.is_none() && state.lightweight_quotes();
+    let artifacts = if lightweight_quote {
+        None
+    } else {
+        state.artifacts()
+    };
+
+    if !lightweight_quote {
         // Log request headers to identify source
         let user_agent = headers
             .get("user-agent")
@@ -66,54 +161,151 @@ pub async fn solve(
                 "🔍 Large auction - not logging full JSON to avoid spam"
             );
         }
-        let liquidity_client = state.liquidity_client();
-
-        // Get base tokens and protocols from solver configuration if available
-        let base_tokens = {
-            let tokens: Vec<_> = state.base_tokens().iter().map(|t| t.0).collect();
-            if tokens.is_empty() {
-                None
-            } else {
-                Some(tokens)
-            }
-        };
-        let protocols = state.protocols();
+    }
 
-        // Serialize auction DTO for potential saving later (before consuming it)
-        let auction_json = serde_json::to_value(&auction).ok();
+    // Quotes for pairs of the quote matrix get solved over its cached
+    // routes, so fetching liquidity for them can be skipped.
+    let liquidity_client = state.liquidity_client().filter(|_| {
+        !(lightweight_quote
+            && state.quotes_from_matrix(auction.orders.iter().map(|order| {
+                (
+                    crate::domain::eth::TokenAddress(order.sell_token),
+                    crate::domain::eth::TokenAddress(order.buy_token),
+                )
+            })))
+    });
+
+    // Get base tokens and protocols from solver configuration if available
+    let base_tokens = {
+        let tokens: Vec<_> = state.base_tokens().iter().map(|t| t.0).collect();
+        if tokens.is_empty() {
+            None
+        } else {
+            Some(tokens)
+        }
+    };
+    let protocols = state.protocols();
+
+    // Serialize auction DTO for potential saving later (before consuming it)
+    let auction_json = artifacts.and_then(|_| serde_json::to_value(&auction).ok());
+    // Lightweight quotes are not forwarded to external strategies.
+    let mut forwarded_json = state
+        .strategies()
+        .filter(|_| !lightweight_quote)
+        .and_then(|_| serde_json::to_value(&auction).ok());
+
+    // Clients may bound how long they wait for the response, in which case
+    // the work gets cut short and partial results are returned in time.
+    let request_deadline = {
+        let header = |name| headers.get(name).and_then(|value| value.to_str().ok());
+        shared::request_deadline::parse(
+            header(shared::request_deadline::X_DEADLINE),
+            header(shared::request_deadline::GRPC_TIMEOUT),
+            chrono::Utc::now(),
+        )
+    };
 
-        let (auction, fetched_liquidity) = match dto::auction::into_domain(
+    let into_domain = heap::track(
+        heap::Subsystem::PoolCache,
+        dto::auction::into_domain(
             auction,
             liquidity_client,
             base_tokens.as_deref(),
             protocols.as_deref(),
-            state.auction_save_directory(),
-        )
-        .await
-        {
-            Ok(value) => value,
-            Err(err) => {
-                tracing::warn!(?err, "invalid auction");
-                return (
-                    axum::http::StatusCode::BAD_REQUEST,
-                    axum::response::Json(Response::Err(err)),
-                );
-            }
-        };
+            artifacts,
+            request_deadline,
+        ),
+    );
+    let (mut auction, fetched_liquidity) = match into_domain.await {
+        Ok(value) => value,
+        Err(err) => {
+            tracing::warn!(?err, "invalid auction");
+            return Err(err);
+        }
+    };
 
-        let auction_id = auction.id;
-        let solutions = state
-            .solve(auction)
-            .instrument(tracing::info_span!("auction", id = %auction_id))
-            .await;
+    // External strategies get the auction including the fetched liquidity.
+    if let (Some(forwarded), Some(response)) = (&mut forwarded_json, &fetched_liquidity)
+        && let Ok(liquidity) = serde_json::to_value(&response.liquidity)
+    {
+        forwarded["liquidity"] = liquidity;
+    }
 
+    let liquidity_deadline_exceeded = fetched_liquidity
+        .as_ref()
+        .is_some_and(|response| response.deadline_exceeded)
+        || request_deadline.is_some_and(|deadline| deadline <= chrono::Utc::now());
+    state.events().publish(events::Event::LiquidityFetched {
+        auction: auction.id,
+        liquidity: auction.liquidity.len(),
+        deadline_exceeded: liquidity_deadline_exceeded,
+    });
+
+    let skipped = state.validate_orders(&mut auction).await;
+    if !skipped.is_empty() {
         tracing::info!(
-            auction_id = %auction_id,
-            solutions_count = solutions.len(),
-            "🔄 COMPUTED SOLUTIONS FOR COW PROTOCOL"
+            skipped_count = skipped.len(),
+            skipped = ?skipped,
+            "⏭️ SKIPPED ORDERS FAILING VALIDATION"
         );
+    }
+
+    let auction_id = auction.id;
+    // The verifier checks the clearing prices of the traded orders.
+    let order_tokens: Arc<HashMap<_, _>> = Arc::new(
+        auction
+            .orders
+            .iter()
+            .map(|order| {
+                (
+                    order.uid.to_string(),
+                    (order.sell.token.0, order.buy.token.0),
+                )
+            })
+            .collect(),
+    );
+    // A sample of the quotes gets compared against external price APIs,
+    // which need the token decimals.
+    let compared_decimals = state
+        .comparator()
+        .filter(|comparator| {
+            matches!(auction_id, crate::domain::auction::Id::Quote) && comparator.sample()
+        })
+        .map(|_| {
+            auction
+                .tokens
+                .0
+                .iter()
+                .map(|(token, info)| (*token, info.decimals))
+                .collect::<HashMap<_, _>>()
+        });
+    let external = external_solutions(
+        state.strategies(),
+        forwarded_json,
+        &auction.orders,
+        &auction.liquidity,
+    );
+    let solved = state
+        .solve(auction, external)
+        .instrument(tracing::info_span!("auction", id = %auction_id))
+        .await;
+
+    let (solutions, unsolved) = (solved.solutions, solved.unsolved);
+    state.events().publish(events::Event::SolutionsComputed {
+        auction: auction_id,
+        solutions: solutions.len(),
+    });
+    let deadline_exceeded = liquidity_deadline_exceeded || solved.deadline_exceeded;
 
-        // Log each solution summary
+    tracing::info!(
+        auction_id = %auction_id,
+        solutions_count = solutions.len(),
+        deadline_exceeded,
+        "🔄 COMPUTED SOLUTIONS FOR COW PROTOCOL"
+    );
+
+    // Log each solution summary
+    if !lightweight_quote {
         for (i, solution) in solutions.iter().enumerate() {
             tracing::info!(
                 solution_index = i,
@@ -123,294 +315,273 @@ pub async fn solve(
                 "💡 SOLUTION SUMMARY"
             );
         }
+    }
 
-        let solutions_dto = dto::solution::from_domain(&solutions);
+    if let Some(decimals) = compared_decimals
+        && let Some(quote) = solutions.first().and_then(|solution| {
+            price_comparison::Quote::from_solution(solution, |token| {
+                decimals.get(&token).copied().flatten()
+            })
+        })
+    {
+        let solver = Arc::clone(&state);
+        tokio::spawn(async move {
+            let Some(comparator) = solver.comparator() else {
+                return;
+            };
+            if let Some(report) = comparator.compare(quote).await {
+                save_competitiveness_report(&report, solver.artifacts()).await;
+            }
+        });
+    }
 
-        tracing::info!(
-            auction_id = %auction_id,
-            returning_solutions = solutions_dto.solutions.len(),
-            "✅ SENDING RESPONSE TO COW PROTOCOL"
-        );
+    if let Some(unsolved) = &unsolved {
+        tracing::info!(auction_id = %auction_id, ?unsolved, "auction unsolved");
+    }
+    let solutions_dto =
+        dto::solution::from_domain(&solutions, &skipped, unsolved.as_deref(), deadline_exceeded);
 
-        // Save auction and solutions to JSON if configured (non-blocking)
-        if let (Some(save_dir), Some(auction_json)) = (state.auction_save_directory(), auction_json)
-        {
-            let solutions_json = serde_json::to_value(&solutions_dto).ok();
-            let save_dir = save_dir.to_path_buf();
-            let save_dir_for_competition = save_dir.clone();
-            let save_dir_for_enhanced = save_dir.clone();
-            let save_dir_for_verify = save_dir.clone();
+    tracing::info!(
+        auction_id = %auction_id,
+        returning_solutions = solutions_dto.solutions.len(),
+        "✅ SENDING RESPONSE TO COW PROTOCOL"
+    );
 
-            tokio::spawn(async move {
-                if let Some(solutions) = solutions_json {
-                    save_auction_and_solutions(auction_json, solutions, &save_dir).await;
+    // Fetch the competition data in the background, for saving it, for
+    // attributing the competition result to the solver statistics and for
+    // calibrating the rival-aware bidding.
+    let solver_address = state.solver_address();
+    if let crate::domain::auction::Id::Solve(id) = auction_id
+        && (artifacts.is_some() || solver_address.is_some())
+    {
+        let solver = Arc::clone(&state);
+        let artifacts = artifacts.cloned();
+        tokio::spawn(async move {
+            let Some(competition) = fetch_competition_data(id, solver.cow_api_base_url()).await
+            else {
+                return;
+            };
+            if let Some(artifacts) = artifacts {
+                save_competition_data(id, &competition, &artifacts).await;
+            }
+            if let Some(address) = solver_address
+                && let Some(won) = competition_winner(&competition, address)
+            {
+                solver.stats().record_competition(id, won);
+            }
+            if let (Some(address), Some(bidding)) = (solver_address, solver.bidding()) {
+                bidding.record_competition(id, &rival_scores(&competition, address));
+            }
+        });
+    }
+
+    // Save auction and solutions to JSON if configured (non-blocking)
+    if let (Some(artifacts), Some(mut auction_json)) = (artifacts, auction_json) {
+        let solutions_json = serde_json::to_value(&solutions_dto).ok();
+        let artifacts_for_solutions = artifacts.clone();
+        let artifacts_for_enhanced = artifacts.clone();
+        let artifacts_for_verify = artifacts.clone();
+        let redact = state.redact_saved_files();
+
+        tokio::spawn(async move {
+            if let Some(mut solutions) = solutions_json {
+                if redact {
+                    crate::util::redact::redact(&mut auction_json);
+                    crate::util::redact::redact(&mut solutions);
                 }
-            });
+                save_auction_and_solutions(auction_json, solutions, &artifacts_for_solutions).await;
+            }
+        });
 
-            // Spawn background task to fetch competition data
-            let cow_api_url = state.cow_api_base_url();
-            tokio::spawn(async move {
-                fetch_and_save_competition_data(auction_id, cow_api_url, &save_dir_for_competition)
-                    .await;
-            });
+        // Spawn background task to create enhanced solutions if liquidity was fetched
+        // If verifier is also configured, verify using the enhanced solutions
+        let bus = state.events().clone();
+        if let Some(liq_response) = fetched_liquidity {
+            let verifier_opt = state.verifier().cloned();
+            let solutions_json_for_enhanced = serde_json::to_value(&solutions_dto).ok();
+            let order_tokens = Arc::clone(&order_tokens);
 
-            // Spawn background task to create enhanced solutions if liquidity was fetched
-            // If verifier is also configured, verify using the enhanced solutions
-            if let Some(liq_response) = fetched_liquidity {
-                let verifier_opt = state.verifier().cloned();
-                let solutions_json_for_enhanced = serde_json::to_value(&solutions_dto).ok();
-
-                tokio::spawn(async move {
-                    if let Some(solutions_json) = solutions_json_for_enhanced {
-                        // Deserialize back to Solutions for the function
-                        if let Ok(solutions_for_enhance) =
-                            serde_json::from_value::<dto::Solutions>(solutions_json)
-                        {
-                            // Create enhanced solutions with liquidityDetails
-                            let enhanced = dto::auction::create_enhanced_solutions(
-                                &solutions_for_enhance,
-                                &liq_response,
-                            );
+            tokio::spawn(async move {
+                if let Some(solutions_json) = solutions_json_for_enhanced {
+                    // Deserialize back to Solutions for the function
+                    if let Ok(solutions_for_enhance) =
+                        serde_json::from_value::<dto::Solutions>(solutions_json)
+                    {
+                        // Create enhanced solutions with liquidityDetails
+                        let enhanced = dto::auction::create_enhanced_solutions(
+                            &solutions_for_enhance,
+                            &liq_response,
+                        );
+
+                        // Save enhanced solutions file
+                        let mut saved = enhanced.clone();
+                        if redact {
+                            crate::util::redact::redact(&mut saved);
+                        }
+                        save_enhanced_solutions_json(saved, auction_id, &artifacts_for_enhanced)
+                            .await;
 
-                            // Save enhanced solutions file
-                            save_enhanced_solutions_json(
-                                enhanced.clone(),
+                        // Verify using enhanced solutions if verifier is configured,
+                        // at the block the liquidity was fetched at
+                        if let Some(verifier) = verifier_opt {
+                            verify_and_save_solutions(
+                                enhanced,
+                                verifier,
                                 auction_id,
-                                &save_dir_for_enhanced,
+                                Some(liq_response.block_number),
+                                order_tokens,
+                                &artifacts_for_verify,
+                                &bus,
                             )
                             .await;
-
-                            // Verify using enhanced solutions if verifier is configured
-                            if let Some(verifier) = verifier_opt {
-                                verify_and_save_solutions(
-                                    enhanced,
-                                    verifier,
-                                    auction_id,
-                                    &save_dir_for_verify,
-                                )
-                                .await;
-                            }
                         }
                     }
-                });
-            } else if let Some(verifier) = state.verifier() {
-                // No liquidity fetched, but verifier configured - use basic solutions
-                let solutions_json_for_verify = serde_json::to_value(&solutions_dto).ok();
-                let verifier = verifier.clone();
-
-                tokio::spawn(async move {
-                    if let Some(solutions_json) = solutions_json_for_verify {
-                        verify_and_save_solutions(
-                            solutions_json,
-                            verifier,
-                            auction_id,
-                            &save_dir_for_verify,
-                        )
-                        .await;
-                    }
-                });
-            }
+                }
+            });
+        } else if let Some(verifier) = state.verifier() {
+            // No liquidity fetched, but verifier configured - use basic solutions
+            let solutions_json_for_verify = serde_json::to_value(&solutions_dto).ok();
+            let verifier = verifier.clone();
+
+            tokio::spawn(async move {
+                if let Some(solutions_json) = solutions_json_for_verify {
+                    verify_and_save_solutions(
+                        solutions_json,
+                        verifier,
+                        auction_id,
+                        None,
+                        order_tokens,
+                        &artifacts_for_verify,
+                        &bus,
+                    )
+                    .await;
+                }
+            });
         }
+    }
 
-        (
-            axum::http::StatusCode::OK,
-            axum::response::Json(Response::Ok(solutions_dto)),
-        )
-    };
+    state.events().publish(events::Event::SolutionsReturned {
+        auction: auction_id,
+        solutions: solutions_dto.solutions.len(),
+        elapsed: started.elapsed(),
+    });
+    Ok(solutions_dto)
+}
 
-    handle_request
-        .instrument(tracing::info_span!("/solve"))
-        .await
+/// Forwards the auction to the external strategies and converts their
+/// solutions to domain solutions. Solutions that can't be converted, e.g.
+/// because they trade orders skipped by this solver, are dropped.
+fn external_solutions<'a>(
+    strategies: Option<&'a crate::infra::strategies::Strategies>,
+    auction: Option<serde_json::Value>,
+    orders: &[crate::domain::order::Order],
+    liquidity: &[crate::domain::liquidity::Liquidity],
+) -> impl Future<Output = Vec<crate::domain::solution::Solution>> + use<'a> {
+    let context = auction
+        .as_ref()
+        .map(|_| (orders.to_vec(), liquidity.to_vec()));
+    async move {
+        let (Some(strategies), Some(auction), Some((orders, liquidity))) =
+            (strategies, auction, context)
+        else {
+            return Vec::new();
+        };
+        let (orders, liquidity) = (&orders, &liquidity);
+        strategies
+            .solve(&auction)
+            .await
+            .into_iter()
+            .flat_map(|(strategy, solutions)| {
+                solutions.solutions.into_iter().filter_map(move |solution| {
+                    let id = solution.id;
+                    let solution = dto::solution::into_domain(solution, orders, liquidity);
+                    if solution.is_none() {
+                        tracing::debug!(
+                            strategy = %strategy.name,
+                            id,
+                            "dropping unsupported external solution"
+                        );
+                    }
+                    solution
+                })
+            })
+            .collect()
+    }
 }
 
-/// Saves auction and solutions to separate JSON files in the configured
-/// directory. This function runs in a background task and logs errors without
-/// failing the request.
+/// Saves auction and solutions to the configured artifact sinks. This
+/// function runs in a background task and logs errors without failing the
+/// request.
 async fn save_auction_and_solutions(
     auction: serde_json::Value,
     solutions: serde_json::Value,
-    save_dir: &std::path::Path,
+    artifacts: &artifacts::Artifacts,
 ) {
-    use tokio::fs;
-
-    // Determine base filename based on auction ID
-    let base_filename = match auction.get("id").and_then(|v| v.as_str()) {
-        Some(id) => id.to_string(),
-        None => {
-            // Use timestamp for quote auctions
-            let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S_%3f");
-            format!("quote_{}", timestamp)
-        }
-    };
-
-    let auction_file_path = save_dir.join(format!("{}_auction.json", base_filename));
-    let solutions_file_path = save_dir.join(format!("{}_solutions.json", base_filename));
-
-    // Create directory if it doesn't exist
-    if let Err(err) = fs::create_dir_all(save_dir).await {
-        tracing::warn!(
-            ?err,
-            directory = ?save_dir,
-            "Failed to create auction save directory"
-        );
-        return;
-    }
-
-    // Serialize auction to pretty JSON
-    let auction_json = match serde_json::to_string_pretty(&auction) {
-        Ok(content) => content,
-        Err(err) => {
-            tracing::warn!(?err, "Failed to serialize auction to JSON");
-            return;
-        }
-    };
-
-    // Serialize solutions to pretty JSON
-    let solutions_json = match serde_json::to_string_pretty(&solutions) {
-        Ok(content) => content,
-        Err(err) => {
-            tracing::warn!(?err, "Failed to serialize solutions to JSON");
-            return;
-        }
-    };
-
+    let name = artifacts::auction_name(
+        auction
+            .get("id")
+            .and_then(|id| id.as_str())
+            .and_then(|id| id.parse().ok()),
+    );
     let solutions_count = solutions
         .get("solutions")
         .and_then(|s| s.as_array())
         .map(|a| a.len())
         .unwrap_or(0);
 
-    // Write auction file
-    let auction_write_result = fs::write(&auction_file_path, auction_json).await;
-
-    // Write solutions file
-    let solutions_write_result = fs::write(&solutions_file_path, solutions_json).await;
-
-    // Log results
-    match (auction_write_result, solutions_write_result) {
-        (Ok(_), Ok(_)) => {
-            tracing::info!(
-                auction_file = ?auction_file_path,
-                solutions_file = ?solutions_file_path,
-                auction_id = ?auction.get("id"),
-                solutions_count,
-                "💾 Saved auction and solutions to separate JSON files"
-            );
-        }
-        (Err(err), _) => {
-            tracing::warn!(
-                ?err,
-                file_path = ?auction_file_path,
-                "Failed to write auction JSON file"
-            );
-        }
-        (_, Err(err)) => {
-            tracing::warn!(
-                ?err,
-                file_path = ?solutions_file_path,
-                "Failed to write solutions JSON file"
-            );
-        }
-    }
+    futures::join!(
+        artifacts.save(artifacts::Artifact {
+            auction: &name,
+            kind: artifacts::Kind::Auction,
+            content: &auction,
+        }),
+        artifacts.save(artifacts::Artifact {
+            auction: &name,
+            kind: artifacts::Kind::Solutions,
+            content: &solutions,
+        }),
+    );
+    tracing::info!(
+        auction_id = ?auction.get("id"),
+        solutions_count,
+        "💾 Saved auction and solutions"
+    );
 }
 
-/// Fetches competition data from the CoW API and saves it to a JSON file.
-/// This function waits 60 seconds before attempting to fetch, then retries up
-/// to 10 times.
-async fn fetch_and_save_competition_data(
-    auction_id: crate::domain::auction::Id,
+/// Fetches competition data from the CoW API. This function waits 60 seconds
+/// before attempting to fetch, then retries up to 10 times.
+async fn fetch_competition_data(
+    auction_id: i64,
     cow_api_base_url: &str,
-    save_dir: &std::path::Path,
-) {
-    use tokio::{
-        fs,
-        time::{Duration, sleep},
-    };
-
-    // Extract the numeric auction ID
-    let auction_id_num = match auction_id {
-        crate::domain::auction::Id::Solve(id) => id,
-        crate::domain::auction::Id::Quote => {
-            tracing::debug!("Skipping competition data fetch for quote auction");
-            return;
-        }
-    };
+) -> Option<serde_json::Value> {
+    use tokio::time::{Duration, sleep};
 
     // Wait 60 seconds for the competition to settle
     tracing::info!(
-        auction_id = auction_id_num,
+        auction_id,
         "Waiting 60 seconds before fetching competition data"
     );
     sleep(Duration::from_secs(60)).await;
 
     let url = format!(
         "{}/api/v2/solver_competition/{}",
-        cow_api_base_url, auction_id_num
+        cow_api_base_url, auction_id
     );
     let client = reqwest::Client::new();
 
     // Retry up to 10 times with 10 second delays between attempts
     for attempt in 1..=10 {
-        tracing::debug!(
-            auction_id = auction_id_num,
-            attempt,
-            "Fetching competition data"
-        );
+        tracing::debug!(auction_id, attempt, "Fetching competition data");
 
         match client.get(&url).send().await {
             Ok(response) => {
                 if response.status().is_success() {
                     match response.json::<serde_json::Value>().await {
-                        Ok(competition_data) => {
-                            // Save to file
-                            let filename = format!("{}_competition.json", auction_id_num);
-                            let file_path = save_dir.join(filename);
-
-                            // Create directory if needed
-                            if let Err(err) = fs::create_dir_all(save_dir).await {
-                                tracing::warn!(
-                                    ?err,
-                                    directory = ?save_dir,
-                                    "Failed to create competition data directory"
-                                );
-                                return;
-                            }
-
-                            // Serialize to pretty JSON
-                            let json_string = match serde_json::to_string_pretty(&competition_data)
-                            {
-                                Ok(s) => s,
-                                Err(err) => {
-                                    tracing::warn!(?err, "Failed to serialize competition data");
-                                    return;
-                                }
-                            };
-
-                            // Write to file
-                            match fs::write(&file_path, json_string).await {
-                                Ok(_) => {
-                                    tracing::info!(
-                                        auction_id = auction_id_num,
-                                        file_path = ?file_path,
-                                        attempt,
-                                        "💾 Successfully saved competition data"
-                                    );
-                                    return; // Success!
-                                }
-                                Err(err) => {
-                                    tracing::warn!(
-                                        ?err,
-                                        file_path = ?file_path,
-                                        "Failed to write competition data file"
-                                    );
-                                    return;
-                                }
-                            }
-                        }
+                        Ok(competition_data) => return Some(competition_data),
                         Err(err) => {
                             tracing::warn!(
                                 ?err,
-                                auction_id = auction_id_num,
+                                auction_id,
                                 attempt,
                                 "Failed to parse competition data JSON"
                             );
@@ -418,13 +589,13 @@ async fn fetch_and_save_competition_data(
                     }
                 } else if response.status().as_u16() == 404 {
                     tracing::debug!(
-                        auction_id = auction_id_num,
+                        auction_id,
                         attempt,
                         "Competition data not yet available (404), will retry"
                     );
                 } else {
                     tracing::warn!(
-                        auction_id = auction_id_num,
+                        auction_id,
                         status = response.status().as_u16(),
                         attempt,
                         "Unexpected HTTP status when fetching competition data"
@@ -434,7 +605,7 @@ async fn fetch_and_save_competition_data(
             Err(err) => {
                 tracing::warn!(
                     ?err,
-                    auction_id = auction_id_num,
+                    auction_id,
                     attempt,
                     "HTTP request failed when fetching competition data"
                 );
@@ -448,21 +619,142 @@ async fn fetch_and_save_competition_data(
     }
 
     tracing::warn!(
-        auction_id = auction_id_num,
+        auction_id,
         "Failed to fetch competition data after 10 attempts"
     );
+    None
+}
+
+/// Saves competition data to the configured artifact sinks.
+async fn save_competition_data(
+    auction_id: i64,
+    competition_data: &serde_json::Value,
+    artifacts: &artifacts::Artifacts,
+) {
+    artifacts
+        .save(artifacts::Artifact {
+            auction: &auction_id.to_string(),
+            kind: artifacts::Kind::Competition,
+            content: competition_data,
+        })
+        .await;
+    tracing::info!(auction_id, "💾 Saved competition data");
+}
+
+/// Logs the finished daily competitiveness report and saves it to the
+/// configured artifact sinks, named after its date.
+async fn save_competitiveness_report(
+    report: &price_comparison::Report,
+    artifacts: Option<&artifacts::Artifacts>,
+) {
+    tracing::info!(?report, "competitiveness against external price APIs");
+    let (Some(artifacts), Ok(content)) = (artifacts, serde_json::to_value(report)) else {
+        return;
+    };
+    artifacts
+        .save(artifacts::Artifact {
+            auction: &report.date.to_string(),
+            kind: artifacts::Kind::Competitiveness,
+            content: &content,
+        })
+        .await;
+}
+
+/// Determines from the competition data whether the solver with the specified
+/// address won the competition. Returns `None` if the competition data does
+/// not contain any solutions.
+fn competition_winner(
+    competition_data: &serde_json::Value,
+    solver_address: crate::domain::eth::Address,
+) -> Option<bool> {
+    let solutions = competition_data.get("solutions")?.as_array()?;
+    if solutions.is_empty() {
+        return None;
+    }
+    Some(solutions.iter().any(|solution| {
+        let is_solver = solution
+            .get("solverAddress")
+            .and_then(|address| address.as_str())
+            .and_then(|address| address.parse::<crate::domain::eth::H160>().ok())
+            == Some(solver_address.0);
+        let is_winner = solution
+            .get("isWinner")
+            .and_then(|winner| winner.as_bool())
+            .unwrap_or_else(|| {
+                solution.get("ranking").and_then(|ranking| ranking.as_u64()) == Some(1)
+            });
+        is_solver && is_winner
+    }))
+}
+
+/// Extracts the best score of the rival solutions for every order traded by
+/// a solution in the competition data. Orders that only the solver with the
+/// specified address traded get a score of zero. Rival solutions settling
+/// multiple orders are skipped, as their scores can't be attributed to the
+/// individual orders.
+fn rival_scores(
+    competition_data: &serde_json::Value,
+    solver_address: crate::domain::eth::Address,
+) -> HashMap<crate::domain::order::Uid, crate::domain::eth::U256> {
+    let mut scores = HashMap::new();
+    let mut unattributable = std::collections::HashSet::new();
+    let solutions = competition_data
+        .get("solutions")
+        .and_then(|solutions| solutions.as_array())
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    for solution in solutions {
+        let orders = solution
+            .get("orders")
+            .and_then(|orders| orders.as_array())
+            .map(|orders| {
+                orders
+                    .iter()
+                    .filter_map(|order| order.get("id")?.as_str())
+                    .filter_map(|id| const_hex::decode_to_array(id).ok())
+                    .map(crate::domain::order::Uid)
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        let is_solver = solution
+            .get("solverAddress")
+            .and_then(|address| address.as_str())
+            .and_then(|address| address.parse::<crate::domain::eth::H160>().ok())
+            == Some(solver_address.0);
+        let score = solution
+            .get("score")
+            .and_then(|score| score.as_str())
+            .and_then(|score| crate::domain::eth::U256::from_dec_str(score).ok());
+        match (is_solver, score, orders.as_slice()) {
+            (true, ..) => {
+                for uid in orders {
+                    scores.entry(uid).or_default();
+                }
+            }
+            (false, Some(score), [uid]) => {
+                let best = scores.entry(*uid).or_default();
+                *best = (*best).max(score);
+            }
+            _ => unattributable.extend(orders),
+        }
+    }
+    scores.retain(|uid, _| !unattributable.contains(uid));
+    scores
 }
 
 /// Verifies solutions against on-chain Balancer contracts and saves results
 /// Accepts JSON solutions (possibly enhanced with liquidityDetails)
+/// Quotes are pinned to the auction block if known, and use the latest block
+/// otherwise.
 async fn verify_and_save_solutions(
     solutions_json: serde_json::Value,
     verifier: crate::infra::solution_verifier::SolutionVerifier,
     auction_id: crate::domain::auction::Id,
-    save_dir: &std::path::Path,
+    block_number: Option<u64>,
+    order_tokens: Arc<HashMap<String, (crate::domain::eth::H160, crate::domain::eth::H160)>>,
+    artifacts: &artifacts::Artifacts,
+    bus: &events::Bus,
 ) {
-    use tokio::fs;
-
     let auction_id_num = match auction_id {
         crate::domain::auction::Id::Solve(id) => id,
         crate::domain::auction::Id::Quote => {
@@ -482,6 +774,7 @@ async fn verify_and_save_solutions(
 
     tracing::info!(
         auction_id = auction_id_num,
+        ?block_number,
         solutions_count = solutions_array.len(),
         has_liquidity_details = solutions_array
             .get(0)
@@ -497,8 +790,11 @@ async fn verify_and_save_solutions(
     for (idx, solution) in solutions_array.iter().enumerate() {
         let verifier_clone = verifier.clone();
         let solution = solution.clone();
+        let order_tokens = Arc::clone(&order_tokens);
         verification_futures.push(tokio::spawn(async move {
-            verifier_clone.verify_solution(&solution, idx).await
+            verifier_clone
+                .verify_solution(&solution, idx, block_number, &order_tokens)
+                .await
         }));
     }
 
@@ -507,47 +803,47 @@ async fn verify_and_save_solutions(
         .into_iter()
         .filter_map(|r| r.ok())
         .collect();
+    bus.publish(events::Event::VerificationFinished {
+        auction: auction_id,
+        solutions: results.len(),
+        quote_errors: results
+            .iter()
+            .flat_map(|result| {
+                let swaps = result.swaps.iter().map(|swap| &swap.quote_error);
+                swaps.chain(result.routes.iter().map(|route| &route.quote_error))
+            })
+            .filter(|error| error.is_some())
+            .count(),
+    });
 
     // Save results
-    let filename = format!("{}_solution_verification.json", auction_id_num);
-    let file_path = save_dir.join(filename);
-
-    if let Err(err) = fs::create_dir_all(save_dir).await {
-        tracing::warn!(?err, "Failed to create verification directory");
-        return;
-    }
-
-    let json_string = match serde_json::to_string_pretty(&results) {
-        Ok(s) => s,
+    let results_json = match serde_json::to_value(&results) {
+        Ok(json) => json,
         Err(err) => {
             tracing::warn!(?err, "Failed to serialize verification results");
             return;
         }
     };
-
-    match fs::write(&file_path, json_string).await {
-        Ok(_) => {
-            tracing::info!(
-                auction_id = auction_id_num,
-                file_path = ?file_path,
-                solutions_verified = results.len(),
-                "💾 Saved solution verification results"
-            );
-        }
-        Err(err) => {
-            tracing::warn!(?err, "Failed to write verification file");
-        }
-    }
+    artifacts
+        .save(artifacts::Artifact {
+            auction: &auction_id_num.to_string(),
+            kind: artifacts::Kind::SolutionVerification,
+            content: &results_json,
+        })
+        .await;
+    tracing::info!(
+        auction_id = auction_id_num,
+        solutions_verified = results.len(),
+        "💾 Saved solution verification results"
+    );
 }
 
-/// Saves enhanced solutions (already created) to a JSON file
+/// Saves enhanced solutions (already created) to the configured artifact sinks
 async fn save_enhanced_solutions_json(
     enhanced: serde_json::Value,
     auction_id: crate::domain::auction::Id,
-    save_dir: &std::path::Path,
+    artifacts: &artifacts::Artifacts,
 ) {
-    use tokio::fs;
-
     let auction_id_num = match auction_id {
         crate::domain::auction::Id::Solve(id) => id,
         crate::domain::auction::Id::Quote => {
@@ -556,35 +852,67 @@ async fn save_enhanced_solutions_json(
         }
     };
 
-    let filename = format!("{}_enhanced_solutions.json", auction_id_num);
-    let file_path = save_dir.join(filename);
+    artifacts
+        .save(artifacts::Artifact {
+            auction: &auction_id_num.to_string(),
+            kind: artifacts::Kind::EnhancedSolutions,
+            content: &enhanced,
+        })
+        .await;
+    tracing::info!(
+        auction_id = auction_id_num,
+        "💾 Saved enhanced solutions with liquidity details"
+    );
+}
 
-    // Create directory if needed
-    if let Err(err) = fs::create_dir_all(save_dir).await {
-        tracing::warn!(?err, directory = ?save_dir, "Failed to create directory");
-        return;
+#[cfg(test)]
+mod tests {
+    use {super::*, serde_json::json};
+
+    fn auction(deadline: &str, gas_price: &str, tokens: &[&str]) -> dto::Auction {
+        serde_json::from_value(json!({
+            "id": null,
+            "tokens": tokens
+                .iter()
+                .map(|token| {
+                    (
+                        token.to_string(),
+                        json!({
+                            "decimals": 18,
+                            "symbol": null,
+                            "referencePrice": "1000000000000000000",
+                            "availableBalance": "0",
+                            "trusted": false
+                        }),
+                    )
+                })
+                .collect::<serde_json::Map<_, _>>(),
+            "orders": [],
+            "liquidity": [],
+            "effectiveGasPrice": gas_price,
+            "deadline": deadline,
+            "surplusCapturingJitOrderOwners": []
+        }))
+        .unwrap()
     }
 
-    // Serialize to pretty JSON
-    let json_string = match serde_json::to_string_pretty(&enhanced) {
-        Ok(s) => s,
-        Err(err) => {
-            tracing::warn!(?err, "Failed to serialize enhanced solutions");
-            return;
-        }
-    };
+    #[test]
+    fn batches_auctions_differing_only_in_deadline() {
+        let a = "0x0000000000000000000000000000000000000001";
+        let b = "0x0000000000000000000000000000000000000002";
+        let key = batch_key(&auction("2106-01-01T00:00:00Z", "1", &[a, b])).unwrap();
 
-    // Write to file
-    match fs::write(&file_path, json_string).await {
-        Ok(_) => {
-            tracing::info!(
-                auction_id = auction_id_num,
-                file_path = ?file_path,
-                "💾 Saved enhanced solutions with liquidity details"
-            );
-        }
-        Err(err) => {
-            tracing::warn!(?err, file_path = ?file_path, "Failed to write enhanced solutions");
-        }
+        assert_eq!(
+            batch_key(&auction("2106-01-01T00:00:01Z", "1", &[b, a])).unwrap(),
+            key
+        );
+        assert_ne!(
+            batch_key(&auction("2106-01-01T00:00:00Z", "2", &[a, b])).unwrap(),
+            key
+        );
+        assert_ne!(
+            batch_key(&auction("2106-01-01T00:00:00Z", "1", &[a])).unwrap(),
+            key
+        );
     }
 }
@@ -0,0 +1,120 @@
+use {
+    crate::domain::{eth, slippage, solver::Solver},
+    axum::{http::StatusCode, response::IntoResponse},
+    serde::Serialize,
+    std::{collections::BTreeMap, sync::Arc},
+};
+
+pub async fn pairs(state: axum::extract::State<Arc<Solver>>) -> axum::response::Json<PairStats> {
+    let stats = state.stats();
+    let mut pairs = stats
+        .summaries()
+        .into_iter()
+        .map(|summary| {
+            let (token_a, token_b) = summary.pair.get();
+            Pair {
+                token_a: token_a.0,
+                token_b: token_b.0,
+                auctions: summary.auctions,
+                solutions: summary.solutions,
+                competitions: summary.competitions,
+                wins: summary.wins,
+                win_rate: summary.win_rate(),
+                median_price_impact: summary.median_price_impact,
+                pool_types: summary.pool_types,
+            }
+        })
+        .collect::<Vec<_>>();
+    pairs.sort_by(|a, b| b.auctions.cmp(&a.auctions));
+
+    axum::response::Json(PairStats {
+        window_seconds: stats.window().as_secs(),
+        pairs,
+    })
+}
+
+pub async fn slippage(state: axum::extract::State<Arc<Solver>>) -> axum::response::Response {
+    let Some(tracker) = state.slippage() else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let report = tracker.report();
+    let mut pools = report
+        .pools
+        .into_iter()
+        .map(|(pool, kind, summary)| PoolSlippage {
+            pool,
+            kind,
+            slippage: summary.into(),
+        })
+        .collect::<Vec<_>>();
+    pools.sort_by(|a, b| b.slippage.swaps.cmp(&a.slippage.swaps));
+
+    axum::response::Json(SlippageStats {
+        window_seconds: tracker.window().as_secs(),
+        kinds: report
+            .kinds
+            .into_iter()
+            .map(|(kind, summary)| (kind, summary.into()))
+            .collect(),
+        pools,
+    })
+    .into_response()
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PairStats {
+    window_seconds: u64,
+    pairs: Vec<Pair>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Pair {
+    token_a: eth::H160,
+    token_b: eth::H160,
+    auctions: usize,
+    solutions: usize,
+    competitions: usize,
+    wins: usize,
+    win_rate: Option<f64>,
+    median_price_impact: Option<f64>,
+    pool_types: BTreeMap<&'static str, usize>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SlippageStats {
+    window_seconds: u64,
+    kinds: BTreeMap<&'static str, Slippage>,
+    pools: Vec<PoolSlippage>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PoolSlippage {
+    pool: eth::H160,
+    kind: &'static str,
+    #[serde(flatten)]
+    slippage: Slippage,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Slippage {
+    swaps: usize,
+    mean_bps: f64,
+    median_bps: f64,
+    max_bps: f64,
+}
+
+impl From<slippage::Summary> for Slippage {
+    fn from(summary: slippage::Summary) -> Self {
+        Self {
+            swaps: summary.swaps,
+            mean_bps: summary.mean_bps,
+            median_bps: summary.median_bps,
+            max_bps: summary.max_bps,
+        }
+    }
+}
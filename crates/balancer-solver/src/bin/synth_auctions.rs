@@ -0,0 +1,436 @@
+//! Generates synthetic auctions from pool state for load testing the solve
+//! path and fuzzing the DTO layer.
+//!
+//! The pool state is read from a liquidity file saved by the solver engine
+//! (or any JSON holding a `liquidity` array of pools, like an auction). Each
+//! order trades between two tokens of a random pool and sells a fraction of
+//! the pool's reserve of the sell token, so order sizes follow the depth of
+//! the indexed pools. Limit prices are derived from the reserve ratio with
+//! some random slippage.
+//!
+//! ```text
+//! cargo run -p balancer-solver --bin synth-auctions -- \
+//!     --liquidity 123_liquidity.json --count 100 --solver-url http://localhost:7872
+//! ```
+
+use {
+    anyhow::{Context, Result},
+    clap::Parser,
+    ethereum_types::{H160, U256, U512},
+    rand::{Rng, SeedableRng, rngs::StdRng, seq::SliceRandom},
+    serde_json::Value,
+    solvers_dto::auction::{
+        Auction,
+        BuyTokenDestination,
+        Class,
+        Kind,
+        Liquidity,
+        Order,
+        SellTokenSource,
+        SigningScheme,
+        Token,
+    },
+    std::{
+        collections::{BTreeMap, HashMap},
+        path::PathBuf,
+        time::{Duration, Instant},
+    },
+    url::Url,
+};
+
+#[derive(Debug, Parser)]
+struct Args {
+    /// JSON file with the pools to generate auctions from: a saved liquidity
+    /// response, an auction or a plain array of liquidity.
+    #[clap(long, env)]
+    liquidity: PathBuf,
+
+    /// The number of auctions to generate.
+    #[clap(long, env, default_value = "10")]
+    count: usize,
+
+    /// The number of orders per auction.
+    #[clap(long, env, default_value = "5")]
+    orders: usize,
+
+    /// The smallest fraction of the sell token reserve of a pool an order
+    /// sells.
+    #[clap(long, env, default_value = "0.0001")]
+    min_depth_fraction: f64,
+
+    /// The largest fraction of the sell token reserve of a pool an order
+    /// sells. Order sizes are sampled log-uniformly in between.
+    #[clap(long, env, default_value = "0.05")]
+    max_depth_fraction: f64,
+
+    /// The share of buy orders.
+    #[clap(long, env, default_value = "0.2")]
+    buy_order_share: f64,
+
+    /// The probability of an auction being corrupted to fuzz the DTO layer
+    /// of the solver engine, which should reject it instead of failing.
+    #[clap(long, env, default_value = "0")]
+    malformed_share: f64,
+
+    /// Seed of the random number generator, for reproducible auctions.
+    #[clap(long, env)]
+    seed: Option<u64>,
+
+    /// Directory to write the auctions to. Auctions are printed to stdout,
+    /// one per line, if neither an output directory nor a solver URL is set.
+    #[clap(long, env)]
+    output: Option<PathBuf>,
+
+    /// Base URL of a solver engine to post the auctions to.
+    #[clap(long, env)]
+    solver_url: Option<Url>,
+
+    /// The time the solver engine gets to solve an auction.
+    #[clap(long, env, default_value = "5s", value_parser = humantime::parse_duration)]
+    time_limit: Duration,
+
+    /// The log filter.
+    #[clap(long, env, default_value = "warn,synth_auctions=info")]
+    log: String,
+}
+
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+    observe::tracing::initialize(&observe::Config::new(&args.log, None, false, None));
+    if let Err(err) = run(args).await {
+        tracing::error!(?err, "failed to generate auctions");
+        std::process::exit(1);
+    }
+}
+
+async fn run(args: Args) -> Result<()> {
+    let liquidity = std::fs::read(&args.liquidity).context("failed to read liquidity file")?;
+    let pools = pools(serde_json::from_slice(&liquidity)?)?;
+    anyhow::ensure!(!pools.is_empty(), "no pools with two or more funded tokens");
+    tracing::info!(pools = pools.len(), "loaded pools");
+
+    let mut rng = match args.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    let client = reqwest::Client::new();
+    let mut stats = BTreeMap::<String, Vec<Duration>>::new();
+
+    for index in 0..args.count {
+        let auction = generate(&args, &pools, index, &mut rng);
+        let mut auction = serde_json::to_value(&auction)?;
+        let malformed = rng.gen_bool(args.malformed_share);
+        if malformed {
+            corrupt(&mut auction, &mut rng);
+        }
+
+        if let Some(dir) = &args.output {
+            std::fs::create_dir_all(dir)?;
+            let path = dir.join(format!("{index}_auction.json"));
+            std::fs::write(path, serde_json::to_string_pretty(&auction)?)?;
+        }
+        match &args.solver_url {
+            Some(url) => {
+                let start = Instant::now();
+                let outcome = match client
+                    .post(url.join("solve")?)
+                    .timeout(args.time_limit * 2)
+                    .json(&auction)
+                    .send()
+                    .await
+                {
+                    Ok(response) => response.status().to_string(),
+                    Err(err) if err.is_timeout() => "timeout".to_string(),
+                    Err(err) => {
+                        tracing::warn!(?err, index, "failed to post auction");
+                        "error".to_string()
+                    }
+                };
+                let elapsed = start.elapsed();
+                if malformed && outcome.starts_with('5') {
+                    tracing::warn!(index, %outcome, "malformed auction caused a server error");
+                }
+                tracing::debug!(index, malformed, %outcome, ?elapsed, "posted auction");
+                stats.entry(outcome).or_default().push(elapsed);
+            }
+            None if args.output.is_none() => println!("{auction}"),
+            None => (),
+        }
+    }
+
+    for (outcome, mut latencies) in stats {
+        latencies.sort();
+        let percentile = |p: usize| latencies[(latencies.len() - 1) * p / 100];
+        tracing::info!(
+            %outcome,
+            count = latencies.len(),
+            p50 = ?percentile(50),
+            p90 = ?percentile(90),
+            max = ?percentile(100),
+            "solve responses"
+        );
+    }
+    Ok(())
+}
+
+/// A pool with the balances of its tokens.
+#[derive(Debug)]
+struct Pool {
+    reserves: Vec<(H160, U256)>,
+}
+
+/// Returns the pools of the liquidity with at least two funded tokens.
+fn pools(file: Value) -> Result<Vec<Pool>> {
+    let liquidity = match file {
+        Value::Array(_) => file,
+        mut file => file
+            .get_mut("liquidity")
+            .map(Value::take)
+            .context("file has no liquidity")?,
+    };
+    let liquidity: Vec<Liquidity> = serde_json::from_value(liquidity)?;
+
+    macro_rules! reserves {
+        ($pool:expr) => {
+            $pool
+                .tokens
+                .iter()
+                .map(|(token, reserve)| (*token, reserve.balance))
+                .collect::<Vec<_>>()
+        };
+    }
+
+    Ok(liquidity
+        .iter()
+        .filter_map(|liquidity| {
+            let mut reserves = match liquidity {
+                Liquidity::ConstantProduct(pool) => reserves!(pool),
+                Liquidity::WeightedProduct(pool) => reserves!(pool),
+                Liquidity::Stable(pool) => reserves!(pool),
+                Liquidity::StableSurge(pool) => reserves!(pool),
+                Liquidity::GyroE(pool) => reserves!(pool),
+                Liquidity::Gyro2CLP(pool) => reserves!(pool),
+                Liquidity::Gyro3CLP(pool) => reserves!(pool),
+                Liquidity::ReClamm(pool) => reserves!(pool),
+                Liquidity::QuantAmm(pool) => reserves!(pool),
+                Liquidity::Curve(pool) => pool
+                    .tokens
+                    .iter()
+                    .map(|coin| (coin.address, coin.balance))
+                    .collect(),
+                // These don't expose the depth of their liquidity per token.
+                Liquidity::ConcentratedLiquidity(_)
+                | Liquidity::UniswapV4(_)
+                | Liquidity::MaverickV2(_)
+                | Liquidity::LimitOrder(_)
+                | Liquidity::Erc4626(_) => return None,
+            };
+            reserves.retain(|(_, balance)| !balance.is_zero());
+            // Sorted for reproducible auctions, pool tokens are hash maps.
+            reserves.sort();
+            (reserves.len() >= 2).then_some(Pool { reserves })
+        })
+        .collect())
+}
+
+/// Generates a random auction trading through the pools.
+fn generate(args: &Args, pools: &[Pool], index: usize, rng: &mut StdRng) -> Auction {
+    let mut tokens = HashMap::new();
+    let orders = (0..args.orders)
+        .filter_map(|_| {
+            let pool = pools.choose(rng)?;
+            let mut pair = pool.reserves.choose_multiple(rng, 2).collect::<Vec<_>>();
+            pair.shuffle(rng);
+            let [&(sell_token, sell_reserve), &(buy_token, buy_reserve)] = pair[..] else {
+                return None;
+            };
+
+            let fraction = rng
+                .gen_range(args.min_depth_fraction.ln()..=args.max_depth_fraction.ln())
+                .exp();
+            let slippage = rng.gen_range(0.001..0.05);
+            let (kind, sell_amount, buy_amount) = if rng.gen_bool(args.buy_order_share) {
+                let buy_amount = scale(buy_reserve, fraction)?;
+                let sell_amount = scale(
+                    multiply_ratio(buy_amount, sell_reserve, buy_reserve)?,
+                    1. + slippage,
+                )?;
+                (Kind::Buy, sell_amount, buy_amount)
+            } else {
+                let sell_amount = scale(sell_reserve, fraction)?;
+                let buy_amount = scale(
+                    multiply_ratio(sell_amount, buy_reserve, sell_reserve)?,
+                    1. - slippage,
+                )?;
+                (Kind::Sell, sell_amount, buy_amount)
+            };
+            if sell_amount.is_zero() || buy_amount.is_zero() {
+                return None;
+            }
+
+            let owner = H160(rng.r#gen());
+            tokens
+                .entry(sell_token)
+                .or_insert_with(Token::default)
+                .available_balance += sell_amount;
+            tokens.entry(buy_token).or_insert_with(Token::default);
+
+            let mut uid = [0; 56];
+            rng.fill(&mut uid[..]);
+            Some(Order {
+                uid,
+                sell_token,
+                buy_token,
+                sell_amount,
+                full_sell_amount: sell_amount,
+                buy_amount,
+                full_buy_amount: buy_amount,
+                fee_policies: None,
+                valid_to: u32::MAX,
+                kind,
+                receiver: None,
+                owner,
+                partially_fillable: false,
+                pre_interactions: Vec::new(),
+                post_interactions: Vec::new(),
+                sell_token_source: SellTokenSource::Erc20,
+                buy_token_destination: BuyTokenDestination::Erc20,
+                class: Class::Market,
+                app_data: Default::default(),
+                flashloan_hint: None,
+                wrappers: Vec::new(),
+                route_pin: None,
+                signing_scheme: SigningScheme::Eip712,
+                signature: vec![0; 65],
+            })
+        })
+        .collect();
+
+    Auction {
+        id: i64::try_from(index).ok(),
+        tokens,
+        orders,
+        liquidity: Vec::new(),
+        effective_gas_price: U256::exp10(10),
+        deadline: chrono::Utc::now()
+            + chrono::Duration::from_std(args.time_limit).unwrap_or_default(),
+        surplus_capturing_jit_order_owners: Vec::new(),
+        gas_limit: None,
+    }
+}
+
+/// Corrupts a serialized auction in one of the ways a buggy or hostile client
+/// could.
+fn corrupt(auction: &mut Value, rng: &mut StdRng) {
+    let invalid_field = match rng.gen_range(0..5) {
+        0 => Some(("sellAmount", "-1")),
+        1 => Some(("buyToken", "0x1234")),
+        2 => Some(("kind", "swap")),
+        _ => None,
+    };
+    if let Some((field, value)) = invalid_field
+        && let Some(order) = auction["orders"]
+            .as_array_mut()
+            .and_then(|orders| orders.choose_mut(rng))
+    {
+        order[field] = value.into();
+    } else if rng.gen_bool(0.5) {
+        auction["deadline"] = "tomorrow".into();
+    } else if let Some(object) = auction.as_object_mut() {
+        let keys = object.keys().cloned().collect::<Vec<_>>();
+        if let Some(key) = keys.choose(rng) {
+            object.remove(key);
+        }
+    }
+}
+
+/// Scales an amount by a factor, returning `None` on overflow.
+fn scale(amount: U256, factor: f64) -> Option<U256> {
+    const PRECISION: u64 = 1_000_000_000;
+    multiply_ratio(
+        amount,
+        U256::from((factor * PRECISION as f64) as u64),
+        U256::from(PRECISION),
+    )
+}
+
+/// Computes `amount * numerator / denominator`, returning `None` on overflow.
+fn multiply_ratio(amount: U256, numerator: U256, denominator: U256) -> Option<U256> {
+    if denominator.is_zero() {
+        return None;
+    }
+    (amount.full_mul(numerator) / U512::from(denominator))
+        .try_into()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args() -> Args {
+        Args::parse_from(["synth-auctions", "--liquidity", "liquidity.json"])
+    }
+
+    #[test]
+    fn generates_orders_within_pool_depth() {
+        let pools = pools(serde_json::json!({
+            "liquidity": [{
+                "kind": "constantProduct",
+                "id": "0",
+                "address": "0x0000000000000000000000000000000000000001",
+                "router": "0x0000000000000000000000000000000000000002",
+                "gasEstimate": "110000",
+                "tokens": {
+                    "0x000000000000000000000000000000000000000a": { "balance": "1000000000" },
+                    "0x000000000000000000000000000000000000000b": { "balance": "4000000000" },
+                    "0x000000000000000000000000000000000000000c": { "balance": "0" },
+                },
+                "fee": "0.003",
+            }]
+        }))
+        .unwrap();
+        assert_eq!(pools.len(), 1);
+        assert_eq!(pools[0].reserves.len(), 2);
+
+        let args = args();
+        let mut rng = StdRng::seed_from_u64(42);
+        for index in 0..10 {
+            let auction = generate(&args, &pools, index, &mut rng);
+            assert_eq!(auction.orders.len(), args.orders);
+            for order in &auction.orders {
+                let sell_reserve = pools[0]
+                    .reserves
+                    .iter()
+                    .find(|(token, _)| *token == order.sell_token)
+                    .unwrap()
+                    .1;
+                assert_ne!(order.sell_token, order.buy_token);
+                assert!(order.sell_amount <= sell_reserve / 10);
+                assert!(!order.buy_amount.is_zero());
+            }
+        }
+    }
+
+    #[test]
+    fn corrupted_auctions_fail_to_parse() {
+        let pools = vec![Pool {
+            reserves: vec![
+                (H160::from_low_u64_be(1), U256::exp10(24)),
+                (H160::from_low_u64_be(2), U256::exp10(24)),
+            ],
+        }];
+        let args = args();
+        let mut rng = StdRng::seed_from_u64(1);
+        for index in 0..20 {
+            let mut auction =
+                serde_json::to_value(generate(&args, &pools, index, &mut rng)).unwrap();
+            corrupt(&mut auction, &mut rng);
+            // Removing the optional auction ID is the only harmless corruption.
+            if serde_json::from_value::<Auction>(auction.clone()).is_ok() {
+                assert!(auction.get("id").is_none());
+            }
+        }
+    }
+}
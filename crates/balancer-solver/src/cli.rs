@@ -0,0 +1,71 @@
+//! CLI arguments for the `solvers` binary.
+
+use {
+    clap::{Parser, Subcommand},
+    std::{net::SocketAddr, path::PathBuf},
+};
+
+/// Run a solver engine
+#[derive(Parser, Debug)]
+#[command(version)]
+pub struct Args {
+    /// The log filter.
+    #[arg(
+        long,
+        env,
+        default_value = "warn,solvers=debug,shared=debug,model=debug,solver=debug"
+    )]
+    pub log: String,
+
+    /// Whether to use JSON format for the logs.
+    #[clap(long, env, default_value = "false")]
+    pub use_json_logs: bool,
+
+    /// The format of the logs, one of `pretty`, `json`, `json-gcp` and
+    /// `json-datadog`. Takes precedence over `--use-json-logs`.
+    #[clap(long, env)]
+    pub log_format: Option<observe::LogFormat>,
+
+    /// The socket address to bind to.
+    #[arg(long, env, default_value = "127.0.0.1:7872")]
+    pub addr: SocketAddr,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+/// The solver engine to run. The config field is a path to the solver
+/// configuration file. This file should be in TOML format.
+#[derive(Subcommand, Debug)]
+#[clap(rename_all = "lowercase")]
+pub enum Command {
+    /// solve individual orders exclusively via provided onchain liquidity
+    Baseline {
+        #[clap(long, env)]
+        config: PathBuf,
+    },
+    /// validate a configuration file and print the effective configuration
+    /// without starting the solver
+    #[command(name = "check-config")]
+    CheckConfig {
+        /// The path to the solver configuration file.
+        config: PathBuf,
+    },
+    /// bundle the saved artifacts of an auction into a `.tar.gz` archive
+    Bundle {
+        /// The directory the auction artifacts were saved to.
+        #[clap(long, env)]
+        directory: PathBuf,
+        /// The id of the auction, or the base name of saved quote artifacts.
+        #[clap(long)]
+        auction: String,
+        /// The archive to write. Defaults to `<auction>_bundle.tar.gz` in the
+        /// working directory.
+        #[clap(long)]
+        output: Option<PathBuf>,
+        /// Whether to redact owner addresses, signatures and app data of
+        /// orders, so the bundle can be shared externally.
+        #[clap(long)]
+        redact: bool,
+    },
+}
@@ -1,9 +0,0 @@
-//! Core solver engine logic.
-
-pub mod auction;
-pub mod eth;
-pub mod liquidity;
-pub mod notification;
-pub mod order;
-pub mod solution;
-pub mod solver;
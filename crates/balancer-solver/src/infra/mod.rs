@@ -1,6 +0,0 @@
-pub mod cli;
-pub mod config;
-pub mod contracts;
-pub mod liquidity_client;
-pub mod metrics;
-pub mod solution_verifier;
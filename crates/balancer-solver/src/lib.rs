@@ -3,12 +3,13 @@
 #![recursion_limit = "256"]
 
 mod api;
-mod boundary;
-mod domain;
-mod infra;
+mod cli;
 mod run;
 #[cfg(test)]
 mod tests;
-mod util;
 
-pub use self::run::{run, start};
+use balancer_solver_core::{domain, dto, infra, util};
+pub use {
+    balancer_solver_core::infra::heap::Allocator,
+    run::{run, start},
+};
@@ -1,5 +1,5 @@
 #[global_allocator]
-static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
+static GLOBAL: balancer_solver::Allocator = balancer_solver::Allocator;
 
 #[tokio::main]
 async fn main() {
@@ -2,8 +2,9 @@
 use tokio::signal::unix::{self, SignalKind};
 use {
     crate::{
+        cli,
         domain::solver,
-        infra::{cli, config},
+        infra::{self, config},
     },
     clap::Parser,
     std::net::SocketAddr,
@@ -31,21 +32,52 @@ async fn run_with(args: cli::Args, bind: Option<oneshot::Sender<SocketAddr>>) {
         args.use_json_logs,
         None,
     );
+    let obs_config = match args.log_format {
+        Some(log_format) => obs_config.with_log_format(log_format),
+        None => obs_config,
+    };
     observe::tracing::initialize_reentrant(&obs_config);
 
     let commit_hash = option_env!("VERGEN_GIT_SHA").unwrap_or("COMMIT_INFO_NOT_FOUND");
 
     tracing::info!(%commit_hash, "running solver engine with {args:#?}");
 
-    let solver = match args.command {
-        cli::Command::Baseline { config } => {
-            let config = config::load(&config).await;
-            solver::Solver::new(config).await
+    let (solver, listeners) = match args.command {
+        cli::Command::Baseline { config: path } => {
+            let config = config::load(&path).await;
+            (
+                solver::Solver::new(config).await,
+                config::listeners(&path).await,
+            )
+        }
+        cli::Command::CheckConfig { config } => {
+            println!("{}", config::check(&config).await);
+            return;
+        }
+        cli::Command::Bundle {
+            directory,
+            auction,
+            output,
+            redact,
+        } => {
+            let output = output.unwrap_or_else(|| format!("{auction}_bundle.tar.gz").into());
+            match infra::bundle::write(&directory, &auction, &output, redact) {
+                Ok(files) => println!("bundled {} artifacts into {output:?}", files.len()),
+                Err(err) => {
+                    eprintln!("failed to bundle artifacts of auction {auction}: {err:?}");
+                    std::process::exit(1);
+                }
+            }
+            return;
         }
     };
 
     crate::api::Api {
-        addr: args.addr,
+        listeners: if listeners.is_empty() {
+            vec![shared::listen::Listener::tcp(args.addr)]
+        } else {
+            listeners
+        },
         solver,
     }
     .serve(bind, shutdown_signal())
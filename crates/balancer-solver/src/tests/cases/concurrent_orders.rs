@@ -0,0 +1,214 @@
+//! Test case that verifies that orders solved concurrently by separate tasks
+//! get merged into a joint solution.
+
+use {crate::tests, serde_json::json};
+
+#[tokio::test]
+async fn test() {
+    let engine = tests::SolverEngine::new(
+        "baseline",
+        tests::Config::String(
+            r#"
+                chain-id = "1"
+                base-tokens = []
+                max-hops = 0
+                max-partial-attempts = 1
+                native-token-price-estimation-amount = "100000000000000000"
+                max-concurrent-orders = 2
+                merge-solutions = true
+            "#
+            .to_owned(),
+        ),
+    )
+    .await;
+
+    let order = |uid: &str, sell: &str, buy: &str, sell_amount: &str, buy_amount: &str| {
+        json!({
+            "uid": uid,
+            "sellToken": sell,
+            "buyToken": buy,
+            "sellAmount": sell_amount,
+            "fullSellAmount": sell_amount,
+            "buyAmount": buy_amount,
+            "fullBuyAmount": buy_amount,
+            "feePolicies": [],
+            "validTo": 0,
+            "kind": "sell",
+            "owner": "0x5b1e2c2762667331bc91648052f646d1b0d35984",
+            "partiallyFillable": false,
+            "preInteractions": [],
+            "postInteractions": [],
+            "sellTokenSource": "erc20",
+            "buyTokenDestination": "erc20",
+            "class": "market",
+            "appData": "0x6000000000000000000000000000000000000000000000000000000000000007",
+            "signingScheme": "presign",
+            "signature": "0x",
+        })
+    };
+    let weth_cow = "0x2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a\
+                    2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a\
+                    2a2a2a2a";
+    let dai_usdc = "0x0101010101010101010101010101010101010101010101010101010101010101\
+                    0101010101010101010101010101010101010101\
+                    01010101";
+
+    let solution = engine
+        .solve(json!({
+            "id": "1",
+            "tokens": {
+                "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2": {
+                    "decimals": 18,
+                    "symbol": "WETH",
+                    "referencePrice": "1000000000000000000",
+                    "availableBalance": "0",
+                    "trusted": true
+                },
+                "0xdef1ca1fb7fbcdc777520aa7f396b4e015f497ab": {
+                    "decimals": 18,
+                    "symbol": "COW",
+                    "referencePrice": "53125132573502",
+                    "availableBalance": "0",
+                    "trusted": true
+                },
+                "0x6b175474e89094c44da98b954eedeac495271d0f": {
+                    "decimals": 18,
+                    "symbol": "DAI",
+                    "referencePrice": "597423824203645",
+                    "availableBalance": "0",
+                    "trusted": true
+                },
+                "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48": {
+                    "decimals": 6,
+                    "symbol": "USDC",
+                    "referencePrice": "597647838715990684620292096",
+                    "availableBalance": "0",
+                    "trusted": true
+                },
+            },
+            "orders": [
+                order(
+                    weth_cow,
+                    "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+                    "0xdef1ca1fb7fbcdc777520aa7f396b4e015f497ab",
+                    "133700000000000000",
+                    "6000000000000000000000",
+                ),
+                order(
+                    dai_usdc,
+                    "0x6b175474e89094c44da98b954eedeac495271d0f",
+                    "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+                    "10000000000000000000",
+                    "9500000",
+                ),
+            ],
+            "liquidity": [
+                {
+                    "kind": "constantProduct",
+                    "tokens": {
+                        "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2": {
+                            "balance": "3828187314911751990"
+                        },
+                        "0xdef1ca1fb7fbcdc777520aa7f396b4e015f497ab": {
+                            "balance": "179617892578796375604692"
+                        }
+                    },
+                    "fee": "0.003",
+                    "id": "0",
+                    "address": "0x97b744df0b59d93a866304f97431d8efad29a08d",
+                    "router": "0x7a250d5630b4cf539739df2c5dacb4c659f2488d",
+                    "gasEstimate": "110000"
+                },
+                {
+                    "kind": "stable",
+                    "tokens": {
+                        "0x6b175474e89094c44da98b954eedeac495271d0f": {
+                            "balance": "505781036390938593206504",
+                            "scalingFactor": "1",
+                            "rate": "1.0",
+                        },
+                        "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48": {
+                            "balance": "554894862074",
+                            "scalingFactor": "1000000000000",
+                            "rate": "1.0",
+                        },
+                        "0xdac17f958d2ee523a2206206994597c13d831ec7": {
+                            "balance": "1585576741011",
+                            "scalingFactor": "1000000000000",
+                            "rate": "1.0",
+                        },
+                    },
+                    "fee": "0.0001",
+                    "amplificationParameter": "5000.0",
+                    "id": "1",
+                    "address": "0x06df3b2bbb68adc8b0e302443692037ed9f91b42",
+                    "balancerPoolId": "0x5c78d05b8ecf97507d1cf70646082c54faa4da950000000000000000000005ca",
+                    "gasEstimate": "183520",
+                },
+            ],
+            "effectiveGasPrice": "15000000000",
+            "deadline": "2106-01-01T00:00:00.000Z",
+            "surplusCapturingJitOrderOwners": []
+        }))
+        .await;
+
+    // The orders trade disjoint tokens, so their solutions get merged. The
+    // order of the trades and interactions depends on the surplus of the
+    // solutions, so they get compared sorted.
+    let solutions = solution["solutions"].as_array().unwrap();
+    assert_eq!(solutions.len(), 1);
+    let solution = &solutions[0];
+    assert_eq!(
+        solution["prices"],
+        json!({
+            "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2": "6043910341261930467761",
+            "0xdef1ca1fb7fbcdc777520aa7f396b4e015f497ab": "133700000000000000",
+            "0x6b175474e89094c44da98b954eedeac495271d0f": "9999475",
+            "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48": "10000000000000000000",
+        }),
+    );
+
+    let sorted = |values: &serde_json::Value, key: &str| {
+        let mut values = values.as_array().unwrap().clone();
+        values.sort_by_key(|value| value[key].as_str().unwrap().to_owned());
+        serde_json::Value::Array(values)
+    };
+    assert_eq!(
+        sorted(&solution["trades"], "order"),
+        json!([
+            {
+                "kind": "fulfillment",
+                "order": dai_usdc,
+                "executedAmount": "10000000000000000000"
+            },
+            {
+                "kind": "fulfillment",
+                "order": weth_cow,
+                "executedAmount": "133700000000000000"
+            },
+        ]),
+    );
+    assert_eq!(
+        sorted(&solution["interactions"], "id"),
+        json!([
+            {
+                "kind": "liquidity",
+                "internalize": false,
+                "id": "0",
+                "inputToken": "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+                "outputToken": "0xdef1ca1fb7fbcdc777520aa7f396b4e015f497ab",
+                "inputAmount": "133700000000000000",
+                "outputAmount": "6043910341261930467761"
+            },
+            {
+                "kind": "liquidity",
+                "internalize": false,
+                "id": "1",
+                "inputToken": "0x6b175474e89094c44da98b954eedeac495271d0f",
+                "outputToken": "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+                "inputAmount": "10000000000000000000",
+                "outputAmount": "9999475"
+            },
+        ]),
+    );
+}
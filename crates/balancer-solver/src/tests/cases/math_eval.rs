@@ -0,0 +1,258 @@
+//! Test cases that verify that the `/math/eval` endpoint computes swaps
+//! through a single pool with the same amounts the baseline solver routes
+//! auctions with, and rejects liquidity quoted on-chain.
+
+use {crate::tests, serde_json::json};
+
+async fn engine() -> tests::SolverEngine {
+    tests::SolverEngine::new(
+        "baseline",
+        tests::Config::String(
+            r#"
+                chain-id = "1"
+                base-tokens = []
+                max-hops = 0
+                max-partial-attempts = 1
+                native-token-price-estimation-amount = "100000000000000000"
+                math-eval-endpoint = true
+            "#
+            .to_owned(),
+        ),
+    )
+    .await
+}
+
+fn weighted_pool(
+    (token_a, balance_a): (&str, &str),
+    (token_b, balance_b): (&str, &str),
+    address: &str,
+) -> serde_json::Value {
+    json!({
+        "kind": "weightedProduct",
+        "tokens": {
+            token_a: {
+                "balance": balance_a,
+                "scalingFactor": "1",
+                "weight": "0.5",
+                "rate": "1000000000000000000",
+            },
+            token_b: {
+                "balance": balance_b,
+                "scalingFactor": "1",
+                "weight": "0.5",
+                "rate": "1000000000000000000",
+            }
+        },
+        "fee": "0.005",
+        "id": "0",
+        "address": address,
+        "balancerPoolId": "0x5c78d05b8ecf97507d1cf70646082c54faa4da950000000000000000000005ca",
+        "gasEstimate": "88892",
+        "version": "v0",
+    })
+}
+
+fn stable_pool() -> serde_json::Value {
+    json!({
+        "kind": "stable",
+        "tokens": {
+            "0x6b175474e89094c44da98b954eedeac495271d0f": {
+                "balance": "505781036390938593206504",
+                "scalingFactor": "1",
+                "rate": "1.0",
+            },
+            "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48": {
+                "balance": "554894862074",
+                "scalingFactor": "1000000000000",
+                "rate": "1.0",
+            },
+            "0xdac17f958d2ee523a2206206994597c13d831ec7": {
+                "balance": "1585576741011",
+                "scalingFactor": "1000000000000",
+                "rate": "1.0",
+            }
+        },
+        "fee": "0.0001",
+        "amplificationParameter": "5000.0",
+        "id": "0",
+        "address": "0x06df3b2bbb68adc8b0e302443692037ed9f91b42",
+        "balancerPoolId": "0x5c78d05b8ecf97507d1cf70646082c54faa4da950000000000000000000005ca",
+        "gasEstimate": "183520",
+    })
+}
+
+#[tokio::test]
+async fn weighted() {
+    let engine = engine().await;
+
+    // Matches the GNO to COW swap of the `bal_liquidity::weighted` auction.
+    let (status, response) = engine
+        .post(
+            "math/eval",
+            json!({
+                "pool": weighted_pool(
+                    (
+                        "0x6810e776880c02933d47db1b9fc05908e5386b96",
+                        "11260752191375725565253",
+                    ),
+                    (
+                        "0xdef1ca1fb7fbcdc777520aa7f396b4e015f497ab",
+                        "18764168403990393422000071",
+                    ),
+                    "0x92762b42a06dcdddc5b7362cfb01e631c4d44b40",
+                ),
+                "tokenIn": "0x6810e776880c02933d47db1b9fc05908e5386b96",
+                "tokenOut": "0xdef1ca1fb7fbcdc777520aa7f396b4e015f497ab",
+                "kind": "givenIn",
+                "amount": "1000000000000000000",
+            }),
+        )
+        .await;
+    assert_eq!(status, reqwest::StatusCode::OK);
+    assert_eq!(
+        response,
+        json!({
+            "amountIn": "1000000000000000000",
+            "amountOut": "1657855325872947866705",
+            "gas": "88892",
+        }),
+    );
+
+    // Matches the xCOW to xGNO hop of the `buy_order_rounding::balancer_weighted`
+    // auction.
+    let (status, response) = engine
+        .post(
+            "math/eval",
+            json!({
+                "pool": weighted_pool(
+                    (
+                        "0x177127622c4a00f3d409b75571e12cb3c8973d3c",
+                        "1963528800698237927834721",
+                    ),
+                    (
+                        "0x9c58bacc331c9aa871afd802db6379a98e80cedb",
+                        "1152796145430714835825",
+                    ),
+                    "0x21d4c792ea7e38e0d0819c2011a2b1cb7252bd99",
+                ),
+                "tokenIn": "0x177127622c4a00f3d409b75571e12cb3c8973d3c",
+                "tokenOut": "0x9c58bacc331c9aa871afd802db6379a98e80cedb",
+                "kind": "givenOut",
+                "amount": "9056454904360584",
+            }),
+        )
+        .await;
+    assert_eq!(status, reqwest::StatusCode::OK);
+    assert_eq!(
+        response,
+        json!({
+            "amountIn": "15503270361052085989",
+            "amountOut": "9056454904360584",
+            "gas": "88892",
+        }),
+    );
+}
+
+#[tokio::test]
+async fn stable() {
+    let engine = engine().await;
+
+    // Matches the DAI to USDC swaps of the `bal_liquidity::stable` auction.
+    let (status, response) = engine
+        .post(
+            "math/eval",
+            json!({
+                "pool": stable_pool(),
+                "tokenIn": "0x6b175474e89094c44da98b954eedeac495271d0f",
+                "tokenOut": "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+                "kind": "givenIn",
+                "amount": "10000000000000000000",
+            }),
+        )
+        .await;
+    assert_eq!(status, reqwest::StatusCode::OK);
+    assert_eq!(
+        response,
+        json!({
+            "amountIn": "10000000000000000000",
+            "amountOut": "9999475",
+            "gas": "183520",
+        }),
+    );
+
+    let (status, response) = engine
+        .post(
+            "math/eval",
+            json!({
+                "pool": stable_pool(),
+                "tokenIn": "0x6b175474e89094c44da98b954eedeac495271d0f",
+                "tokenOut": "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+                "kind": "givenOut",
+                "amount": "10000000",
+            }),
+        )
+        .await;
+    assert_eq!(status, reqwest::StatusCode::OK);
+    assert_eq!(
+        response,
+        json!({
+            "amountIn": "10000524328839166557",
+            "amountOut": "10000000",
+            "gas": "183520",
+        }),
+    );
+}
+
+#[tokio::test]
+async fn rejects_liquidity_quoted_on_chain() {
+    let engine = engine().await;
+
+    for pool in [
+        json!({
+            "kind": "concentratedLiquidity",
+            "id": "0",
+            "address": "0x88e6a0c2ddd26feeb64f039a2c41296fcb3f5640",
+            "router": "0xe592427a0aece92de3edee1f18e0157c05861564",
+            "gasEstimate": "110000",
+            "tokens": [
+                "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+                "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+            ],
+            "sqrtPrice": "1839233343405233130305228455591405",
+            "liquidity": "19137312436640928016",
+            "tick": 200000,
+            "liquidityNet": {
+                "199980": "19137312436640928016",
+                "200040": "-19137312436640928016",
+            },
+            "fee": "0.0005",
+        }),
+        json!({
+            "kind": "erc4626",
+            "id": "0",
+            "gasEstimate": "90000",
+            "vault": "0x83f20f44975d03b1b09e64809b757c47f942beea",
+            "asset": "0x6b175474e89094c44da98b954eedeac495271d0f",
+        }),
+    ] {
+        let (status, response) = engine
+            .post(
+                "math/eval",
+                json!({
+                    "pool": pool,
+                    "tokenIn": "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+                    "tokenOut": "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+                    "kind": "givenIn",
+                    "amount": "1000000",
+                }),
+            )
+            .await;
+        assert_eq!(status, reqwest::StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(
+            response,
+            json!({
+                "message": "swaps through liquidity quoted on-chain can't be evaluated",
+            }),
+        );
+    }
+}
@@ -2,8 +2,14 @@
 
 mod bal_liquidity;
 mod buy_order_rounding;
+mod concurrent_orders;
+mod diagnostics;
 mod direct_swap;
 mod gyro_e_pool_test;
 mod internalization;
 mod limit_order_quoting;
+mod math_eval;
 mod partial_fill;
+mod route_pin;
+mod routing;
+mod stats;
@@ -0,0 +1,106 @@
+//! Test case that verifies that the baseline solver routes orders pinned to
+//! specific liquidity over that liquidity, and falls back to routing over all
+//! liquidity when the pinned liquidity can't route the order.
+
+use {crate::tests, serde_json::json};
+
+fn auction(route_pin: serde_json::Value) -> serde_json::Value {
+    let pool = |id: &str, fee: &str| {
+        json!({
+            "kind": "constantProduct",
+            "tokens": {
+                "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2": {
+                    "balance": "3828187314911751990"
+                },
+                "0xDEf1CA1fb7FBcDC777520aa7f396b4E015F497aB": {
+                    "balance": "179617892578796375604692"
+                }
+            },
+            "fee": fee,
+            "id": id,
+            "address": "0x97b744df0b59d93A866304f97431D8EfAd29a08d",
+            "router": "0x7a250d5630b4cf539739df2c5dacb4c659f2488d",
+            "gasEstimate": "110000"
+        })
+    };
+
+    json!({
+        "id": "1",
+        "tokens": {
+            "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2": {
+                "decimals": 18,
+                "symbol": "WETH",
+                "referencePrice": "1000000000000000000",
+                "availableBalance": "1412206645170290748",
+                "trusted": true
+            },
+            "0xDEf1CA1fb7FBcDC777520aa7f396b4E015F497aB": {
+                "decimals": 18,
+                "symbol": "COW",
+                "referencePrice": "53125132573502",
+                "availableBalance": "740264138483556450389",
+                "trusted": true
+            }
+        },
+        "orders": [
+            {
+                "uid": "0x2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a\
+                          2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a\
+                          2a2a2a2a",
+                "sellToken": "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2",
+                "buyToken": "0xDEf1CA1fb7FBcDC777520aa7f396b4E015F497aB",
+                "sellAmount": "133700000000000000",
+                "fullSellAmount": "133700000000000000",
+                "buyAmount": "6000000000000000000000",
+                "fullBuyAmount": "6000000000000000000000",
+                "feePolicies": [],
+                "validTo": 0,
+                "kind": "sell",
+                "owner": "0x5b1e2c2762667331bc91648052f646d1b0d35984",
+                "partiallyFillable": false,
+                "preInteractions": [],
+                "postInteractions": [],
+                "sellTokenSource": "erc20",
+                "buyTokenDestination": "erc20",
+                "class": "market",
+                "appData": "0x6000000000000000000000000000000000000000000000000000000000000007",
+                "routePin": route_pin,
+                "signingScheme": "presign",
+                "signature": "0x",
+            }
+        ],
+        // The pool with the lower fee yields the better route.
+        "liquidity": [pool("0", "0.003"), pool("1", "0.005")],
+        "effectiveGasPrice": "15000000000",
+        "deadline": "2106-01-01T00:00:00.000Z",
+        "surplusCapturingJitOrderOwners": []
+    })
+}
+
+fn routed_liquidity(solution: &serde_json::Value) -> &serde_json::Value {
+    &solution["solutions"][0]["interactions"][0]["id"]
+}
+
+#[tokio::test]
+async fn test() {
+    let engine = tests::SolverEngine::new(
+        "baseline",
+        tests::Config::File("config/example.baseline.toml".into()),
+    )
+    .await;
+
+    let solution = engine.solve(auction(serde_json::Value::Null)).await;
+    assert_eq!(routed_liquidity(&solution), "0");
+
+    let solution = engine.solve(auction(json!({ "liquidity": "1" }))).await;
+    assert_eq!(routed_liquidity(&solution), "1");
+
+    let solution = engine
+        .solve(auction(json!({ "protocol": "constantProduct" })))
+        .await;
+    assert_eq!(routed_liquidity(&solution), "0");
+
+    // No stable pools can route the order, so the pin is not respected.
+    let solution = engine.solve(auction(json!({ "protocol": "stable" }))).await;
+    assert_eq!(routed_liquidity(&solution), "0");
+}
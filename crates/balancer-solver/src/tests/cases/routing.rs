@@ -0,0 +1,144 @@
+//! Test cases that verify that the routing configuration can be updated
+//! through the routing API.
+
+use {crate::tests, serde_json::json};
+
+fn auction() -> serde_json::Value {
+    json!({
+        "id": "1",
+        "tokens": {
+            "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2": {
+                "decimals": 18,
+                "symbol": "WETH",
+                "referencePrice": "1000000000000000000",
+                "availableBalance": "1412206645170290748",
+                "trusted": true
+            },
+            "0xDEf1CA1fb7FBcDC777520aa7f396b4E015F497aB": {
+                "decimals": 18,
+                "symbol": "COW",
+                "referencePrice": "53125132573502",
+                "availableBalance": "740264138483556450389",
+                "trusted": true
+            }
+        },
+        "orders": [],
+        "liquidity": [
+            {
+                "kind": "constantProduct",
+                "tokens": {
+                    "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2": {
+                        "balance": "3828187314911751990"
+                    },
+                    "0xDEf1CA1fb7FBcDC777520aa7f396b4E015F497aB": {
+                        "balance": "179617892578796375604692"
+                    }
+                },
+                "fee": "0.003",
+                "id": "0",
+                "address": "0x97b744df0b59d93A866304f97431D8EfAd29a08d",
+                "router": "0x7a250d5630b4cf539739df2c5dacb4c659f2488d",
+                "gasEstimate": "110000"
+            }
+        ],
+        "effectiveGasPrice": "15000000000",
+        "deadline": "2106-01-01T00:00:00.000Z",
+        "surplusCapturingJitOrderOwners": []
+    })
+}
+
+#[tokio::test]
+async fn base_tokens_survive_selection() {
+    let engine = tests::SolverEngine::new(
+        "baseline",
+        tests::Config::String(
+            r#"
+                chain-id = "1"
+                base-tokens = []
+                max-hops = 1
+                max-partial-attempts = 1
+                native-token-price-estimation-amount = "100000000000000000"
+                routing-api-token = "secret"
+
+                [auto-base-tokens]
+                interval-secs = 0
+            "#
+            .to_owned(),
+        ),
+    )
+    .await;
+
+    let (status, _) = engine
+        .patch(
+            "config/routing",
+            "secret",
+            json!({
+                "baseTokens": ["0x0101010101010101010101010101010101010101"],
+            }),
+        )
+        .await;
+    assert_eq!(status, reqwest::StatusCode::OK);
+
+    // Solving an auction refreshes the selection, which gets combined with
+    // the base tokens set through the API.
+    engine.solve(auction()).await;
+
+    let routing = engine.get("config/routing").await;
+    assert_eq!(
+        routing["baseTokens"],
+        json!([
+            "0x0101010101010101010101010101010101010101",
+            "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+            "0xdef1ca1fb7fbcdc777520aa7f396b4e015f497ab",
+        ]),
+    );
+    assert_eq!(
+        routing["selectedBaseTokens"],
+        json!([
+            "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+            "0xdef1ca1fb7fbcdc777520aa7f396b4e015f497ab",
+        ]),
+    );
+}
+
+#[tokio::test]
+async fn rejects_updates_out_of_bounds() {
+    let engine = tests::SolverEngine::new(
+        "baseline",
+        tests::Config::String(
+            r#"
+                chain-id = "1"
+                base-tokens = []
+                max-hops = 1
+                max-partial-attempts = 1
+                native-token-price-estimation-amount = "100000000000000000"
+                routing-api-token = "secret"
+            "#
+            .to_owned(),
+        ),
+    )
+    .await;
+
+    let too_many_base_tokens = (1..=21).map(|i| format!("0x{i:040x}")).collect::<Vec<_>>();
+    for (update, message) in [
+        (
+            json!({ "baseTokens": too_many_base_tokens }),
+            "base tokens must not have more than 20 tokens",
+        ),
+        (json!({ "maxHops": 3 }), "max hops must not exceed 2"),
+        (
+            json!({ "maxPartialAttempts": 0 }),
+            "max partial attempts must be at least 1",
+        ),
+    ] {
+        let (status, response) = engine.patch("config/routing", "secret", update).await;
+        assert_eq!(status, reqwest::StatusCode::BAD_REQUEST);
+        assert_eq!(response, json!({ "message": message }));
+    }
+
+    let (status, response) = engine
+        .patch("config/routing", "secret", json!({ "maxHops": 2 }))
+        .await;
+    assert_eq!(status, reqwest::StatusCode::OK);
+    assert_eq!(response["maxHops"], 2);
+}
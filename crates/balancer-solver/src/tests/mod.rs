@@ -81,6 +81,47 @@ impl SolverEngine {
 
         response.json().await.unwrap()
     }
+
+    /// Gets the JSON response of the path.
+    pub async fn get(&self, path: &str) -> serde_json::Value {
+        let url = shared::url::join(&self.url, path);
+        let response = reqwest::get(url).await.unwrap();
+        assert!(response.status().is_success(), "HTTP {}", response.status());
+        response.json().await.unwrap()
+    }
+
+    /// Posts a raw JSON request to the path, returning the status and the JSON
+    /// response without checking for success.
+    pub async fn post(
+        &self,
+        path: &str,
+        body: serde_json::Value,
+    ) -> (reqwest::StatusCode, serde_json::Value) {
+        let client = reqwest::Client::new();
+        let url = shared::url::join(&self.url, path);
+        let response = client.post(url).json(&body).send().await.unwrap();
+        (response.status(), response.json().await.unwrap())
+    }
+
+    /// Patches the path with a raw JSON request authorized by the bearer
+    /// token, returning the status and the JSON response.
+    pub async fn patch(
+        &self,
+        path: &str,
+        token: &str,
+        body: serde_json::Value,
+    ) -> (reqwest::StatusCode, serde_json::Value) {
+        let client = reqwest::Client::new();
+        let url = shared::url::join(&self.url, path);
+        let response = client
+            .patch(url)
+            .bearer_auth(token)
+            .json(&body)
+            .send()
+            .await
+            .unwrap();
+        (response.status(), response.json().await.unwrap())
+    }
 }
 
 impl Drop for SolverEngine {
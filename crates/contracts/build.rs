@@ -469,6 +469,7 @@ fn main() {
                 },
             )
     });
+    generate_contract("BalancerV3ReClammPoolFactory");
     generate_contract_with_config("BalancerV3ReClammPoolFactoryV2", |builder| {
         builder
             .add_network(
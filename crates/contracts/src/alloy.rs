@@ -41,6 +41,26 @@ crate::bindings!(
     }
 );
 
+crate::bindings!(ChainlinkAggregatorV3);
+
+crate::bindings!(
+    Multicall3,
+    // <https://github.com/mds1/multicall3#deployments>
+    crate::deployments! {
+        MAINNET => address!("0xcA11bde05977b3631167028862bE3a173976CA11"),
+        GNOSIS => address!("0xcA11bde05977b3631167028862bE3a173976CA11"),
+        SEPOLIA => address!("0xcA11bde05977b3631167028862bE3a173976CA11"),
+        ARBITRUM_ONE => address!("0xcA11bde05977b3631167028862bE3a173976CA11"),
+        BASE => address!("0xcA11bde05977b3631167028862bE3a173976CA11"),
+        AVALANCHE => address!("0xcA11bde05977b3631167028862bE3a173976CA11"),
+        BNB => address!("0xcA11bde05977b3631167028862bE3a173976CA11"),
+        OPTIMISM => address!("0xcA11bde05977b3631167028862bE3a173976CA11"),
+        POLYGON => address!("0xcA11bde05977b3631167028862bE3a173976CA11"),
+        LINEA => address!("0xcA11bde05977b3631167028862bE3a173976CA11"),
+        // Deployed at a different address on Lens
+    }
+);
+
 crate::bindings!(ERC20Mintable);
 
 crate::bindings!(GnosisSafe);
@@ -376,6 +396,7 @@ crate::bindings!(
         // Not available on Lens
     }
 );
+crate::bindings!(BalancerV3Vault);
 crate::bindings!(
     BalancerV3BatchRouter,
     crate::deployments! {
@@ -553,6 +574,38 @@ crate::bindings!(
         // Not available on Gnosis Chain
     }
 );
+crate::bindings!(
+    UniswapV4PoolManager,
+    crate::deployments! {
+        // <https://docs.uniswap.org/contracts/v4/deployments>
+        MAINNET => (address!("0x000000000004444c5dc75cB358380D2e3dE08A90"), 21688329),
+    }
+);
+crate::bindings!(
+    UniswapV4Quoter,
+    crate::deployments! {
+        // <https://docs.uniswap.org/contracts/v4/deployments>
+        MAINNET => address!("0x52f0e24d1c21c8a0cb1e5a5dd6198556bd9e1203"),
+    }
+);
+crate::bindings!(
+    UniswapV4UniversalRouter,
+    crate::deployments! {
+        // <https://docs.uniswap.org/contracts/v4/deployments>
+        MAINNET => address!("0x66a9893cc07d91d95644aedd05d03f95e1dba8af"),
+    }
+);
+crate::bindings!(
+    CurveRegistry,
+    crate::deployments! {
+        // <https://docs.curve.fi/references/deployed-contracts/#curve-registry>
+        MAINNET => address!("0x90E00ACe148ca3b23Ac1bC8C240C2a7Dd9c2d7f5"),
+    }
+);
+crate::bindings!(CurveStableSwapPool);
+crate::bindings!(MaverickV2Factory);
+crate::bindings!(MaverickV2Pool);
+crate::bindings!(MaverickV2Router);
 crate::bindings!(
     IUniswapV3Factory,
     crate::deployments! {
@@ -377,6 +377,42 @@ fn run() -> Result<()> {
         .manual(
             "ChainalysisOracle",
             "Chainalysis does not publish its code",
+        )
+        .manual(
+            "ChainlinkAggregatorV3",
+            "Only the AggregatorV3Interface ABI of price feeds is needed",
+        )
+        .manual(
+            "UniswapV4PoolManager",
+            "Only the Initialize event of the PoolManager is needed",
+        )
+        .manual(
+            "UniswapV4Quoter",
+            "Only the single pool quotes of the V4Quoter are needed",
+        )
+        .manual(
+            "UniswapV4UniversalRouter",
+            "Only the execute function of the UniversalRouter is needed",
+        )
+        .manual(
+            "CurveRegistry",
+            "Curve publishes Vyper sources only, the pool getters of the registry are needed",
+        )
+        .manual(
+            "CurveStableSwapPool",
+            "Curve publishes Vyper sources only, StableSwap pools differ in their ABIs",
+        )
+        .manual(
+            "MaverickV2Factory",
+            "Only the PoolCreated event of the factory is needed",
+        )
+        .manual(
+            "MaverickV2Pool",
+            "Only the state and tick getters of the pools are needed",
+        )
+        .manual(
+            "MaverickV2Router",
+            "Only the single pool swaps of the router are needed",
         );
     
     Ok(())
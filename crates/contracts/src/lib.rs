@@ -64,6 +64,7 @@ include_contracts! {
     BalancerV3Gyro2CLPPool;
     BalancerV3Gyro2CLPPoolFactory;
     BalancerV3ReClammPool;
+    BalancerV3ReClammPoolFactory;
     BalancerV3ReClammPoolFactoryV2;
     BalancerV3QuantAMMWeightedPoolFactory;
     BalancerV3QuantAMMWeightedPool;
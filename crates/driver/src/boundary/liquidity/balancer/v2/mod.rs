@@ -224,6 +224,8 @@ async fn init_liquidity(
         .into_iter()
         .flatten()
         .collect(),
+        multicall: None,
+        rate_providers: None,
     };
     let token_info_fetcher = Arc::new(CachedTokenInfoFetcher::new(Arc::new(TokenInfoFetcher {
         web3: web3.clone(),
@@ -240,6 +242,7 @@ async fn init_liquidity(
             web3.clone(),
             &contracts,
             config.pool_deny_list.clone(),
+            Default::default(),
             chain_to_gql_chain(&eth.chain()),
         )
         .await
@@ -262,6 +262,8 @@ async fn init_liquidity(
         .into_iter()
         .flatten()
         .collect(),
+        multicall: None,
+        rate_providers: None,
     };
     let token_info_fetcher = Arc::new(CachedTokenInfoFetcher::new(Arc::new(TokenInfoFetcher {
         web3: web3.clone(),
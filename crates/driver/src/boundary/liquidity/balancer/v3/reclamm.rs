@@ -40,6 +40,9 @@ pub fn to_domain(id: liquidity::Id, pool: BalancerV3ReClammOrder) -> Result<liqu
             )?,
             fee: balancer::v3::Fee::from_raw(pool.fee.as_uint256()),
             version: match pool.version {
+                shared::sources::balancer_v3::pools::reclamm::Version::V1 => {
+                    balancer::v3::reclamm::Version::V1
+                }
                 shared::sources::balancer_v3::pools::reclamm::Version::V2 => {
                     balancer::v3::reclamm::Version::V2
                 }
@@ -60,6 +63,7 @@ pub fn to_domain(id: liquidity::Id, pool: BalancerV3ReClammOrder) -> Result<liqu
             )?,
             price_ratio_update_start_time: pool.price_ratio_update_start_time,
             price_ratio_update_end_time: pool.price_ratio_update_end_time,
+            current_timestamp: pool.current_timestamp,
         }),
     })
 }
@@ -197,6 +197,11 @@ impl Fetcher {
                     Liquidity::LimitOrder(pool) => zeroex::to_domain(id, pool),
                     Liquidity::Concentrated(pool) => uniswap::v3::to_domain(id, pool),
                     Liquidity::Erc4626(order) => erc4626::to_domain(id, *order),
+                    // Uniswap V4, Maverick V2 and Curve pools are only collected
+                    // by the liquidity-driver.
+                    Liquidity::UniswapV4(_) | Liquidity::MaverickV2(_) | Liquidity::Curve(_) => {
+                        Err(anyhow::anyhow!("unsupported liquidity"))
+                    }
                 }
                 // Ignore "bad" liquidity - this allows the driver to continue
                 // solving with the other good stuff.
@@ -313,10 +313,11 @@ pub fn solver_response(
             tracing::warn!(%endpoint, ?err, "failed to receive response from solver")
         }
     }
-    metrics::get()
-        .used_solve_time
-        .with_label_values(&[solver])
-        .observe(compute_time.as_secs_f64());
+    // Slow solves are linked to their traces.
+    observe::openmetrics::observe(
+        &metrics::get().used_solve_time.with_label_values(&[solver]),
+        compute_time.as_secs_f64(),
+    );
 }
 
 /// Observe the result of mempool transaction execution.
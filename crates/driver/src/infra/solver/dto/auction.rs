@@ -175,6 +175,7 @@ pub fn new(
                         .flatten()
                         .cloned()
                         .collect(),
+                    route_pin: None,
                     signature: order.signature.data.clone().into(),
                     signing_scheme: match order.signature.scheme {
                         Scheme::Eip712 => solvers_dto::auction::SigningScheme::Eip712,
@@ -263,6 +264,7 @@ pub fn new(
                                 ),
                             ),
                             fee: fee_to_decimal(pool.fee),
+                            actual_supply: None,
                         })
                     }
                     liquidity::Kind::BalancerV3Stable(pool) => {
@@ -292,6 +294,7 @@ pub fn new(
                                 ),
                             ),
                             fee: fee_to_decimal_v3(pool.fee),
+                            actual_supply: None,
                         })
                     }
                     liquidity::Kind::BalancerV2Weighted(pool) => {
@@ -606,6 +609,7 @@ pub fn new(
                                 ),
                                 price_ratio_update_start_time: pool.price_ratio_update_start_time,
                                 price_ratio_update_end_time: pool.price_ratio_update_end_time,
+                                current_timestamp: pool.current_timestamp,
                             },
                         )
                     }
@@ -654,6 +658,7 @@ pub fn new(
                                 last_update_time: pool.last_update_time,
                                 last_interop_time: pool.last_interop_time,
                                 current_timestamp: pool.current_timestamp,
+                                strategy: None,
                             },
                         )
                     }
@@ -765,6 +770,7 @@ pub fn new(
             .cloned()
             .map(Into::into)
             .collect::<Vec<_>>(),
+        gas_limit: None,
     }
 }
 
@@ -1,2 +1,246 @@
 pub mod v2;
 pub mod v3;
+
+use {
+    crate::domain::{eth, liquidity},
+    anyhow::{Context, Result, ensure},
+    serde::{Deserialize, Serialize},
+    shared::sources::{balancer_v2, balancer_v3},
+    std::{collections::HashSet, path::Path, sync::Mutex},
+};
+
+/// A portable snapshot of the Balancer pools an instance indexes, in the
+/// format of the Balancer API.
+///
+/// Importing the bundle exported by one instance on startup makes another
+/// instance index the same pool set without querying the Balancer API, which
+/// makes it easy to stand up staging instances mirroring production.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolBundle {
+    pub chain_id: u64,
+    #[serde(default)]
+    pub balancer_v2: Option<balancer_v2::RegisteredPools>,
+    #[serde(default)]
+    pub balancer_v3: Option<balancer_v3::RegisteredPools>,
+}
+
+impl PoolBundle {
+    /// Reads a bundle from a JSON file.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path).with_context(|| format!("reading {path:?}"))?;
+        serde_json::from_str(&content).with_context(|| format!("parsing {path:?}"))
+    }
+}
+
+/// A pool the Balancer registries got initialized with.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecordedPool {
+    pub id: String,
+    pub address: eth::H160,
+    pub version: Version,
+    pub tokens: Vec<eth::H160>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Version {
+    V2,
+    V3,
+}
+
+/// The state of a single Balancer pool, for inspecting why it does or doesn't
+/// get used.
+#[derive(Debug)]
+pub struct PoolInspection {
+    pub pool: RecordedPool,
+    /// The block the state of the pool was fetched at.
+    pub block: u64,
+    /// Whether the pool is paused, or `None` if this couldn't be fetched.
+    pub paused: Option<bool>,
+    /// The liquidity of the pool, or `None` if the pool isn't part of the
+    /// fetched liquidity, e.g. because it is paused or in recovery mode.
+    pub liquidity: Option<liquidity::Liquidity>,
+}
+
+/// Keeps track of the pools the Balancer registries get initialized with.
+#[derive(Debug)]
+pub struct Registry {
+    imported: Option<PoolBundle>,
+    registered: Mutex<PoolBundle>,
+}
+
+impl Registry {
+    /// Creates a registry for the specified chain, initializing the pools
+    /// from the imported bundle instead of the Balancer API if there is one.
+    pub fn new(chain_id: u64, imported: Option<PoolBundle>) -> Result<Self> {
+        if let Some(bundle) = &imported {
+            ensure!(
+                bundle.chain_id == chain_id,
+                "pool bundle is for chain {} instead of {chain_id}",
+                bundle.chain_id
+            );
+        }
+        Ok(Self {
+            imported,
+            registered: Mutex::new(PoolBundle {
+                chain_id,
+                ..Default::default()
+            }),
+        })
+    }
+
+    /// Returns the imported Balancer V2 pools.
+    pub fn imported_v2(&self) -> Option<&balancer_v2::RegisteredPools> {
+        self.imported.as_ref()?.balancer_v2.as_ref()
+    }
+
+    /// Returns the imported Balancer V3 pools.
+    pub fn imported_v3(&self) -> Option<&balancer_v3::RegisteredPools> {
+        self.imported.as_ref()?.balancer_v3.as_ref()
+    }
+
+    /// Records the Balancer V2 pools a registry got initialized with,
+    /// replacing the pools recorded by earlier initializations.
+    pub fn record_v2(&self, pools: &balancer_v2::RegisteredPools) {
+        let mut registered = self.registered.lock().unwrap();
+        let recorded = registered
+            .balancer_v2
+            .get_or_insert_with(|| balancer_v2::RegisteredPools::empty(pools.fetched_block_number));
+        recorded.fetched_block_number = recorded
+            .fetched_block_number
+            .min(pools.fetched_block_number);
+        let ids = pools
+            .pools
+            .iter()
+            .map(|pool| &pool.id)
+            .collect::<HashSet<_>>();
+        recorded.pools.retain(|pool| !ids.contains(&pool.id));
+        recorded.pools.extend(pools.pools.iter().cloned());
+    }
+
+    /// Records the Balancer V3 pools a registry got initialized with,
+    /// replacing the pools recorded by earlier initializations.
+    pub fn record_v3(&self, pools: &balancer_v3::RegisteredPools) {
+        let mut registered = self.registered.lock().unwrap();
+        let recorded = registered
+            .balancer_v3
+            .get_or_insert_with(|| balancer_v3::RegisteredPools::empty(pools.fetched_block_number));
+        recorded.fetched_block_number = recorded
+            .fetched_block_number
+            .min(pools.fetched_block_number);
+        let ids = pools
+            .pools
+            .iter()
+            .map(|pool| &pool.id)
+            .collect::<HashSet<_>>();
+        recorded.pools.retain(|pool| !ids.contains(&pool.id));
+        recorded.pools.extend(pools.pools.iter().cloned());
+    }
+
+    /// Returns the recorded pool with the specified pool ID or address.
+    pub fn find(&self, id: &str) -> Option<RecordedPool> {
+        let registered = self.registered.lock().unwrap();
+        let matches = |pool_id: &str, address: &eth::H160| {
+            pool_id.eq_ignore_ascii_case(id) || format!("{address:#x}").eq_ignore_ascii_case(id)
+        };
+        let v2 = registered
+            .balancer_v2
+            .iter()
+            .flat_map(|pools| &pools.pools)
+            .find(|pool| matches(&pool.id, &pool.address))
+            .map(|pool| RecordedPool {
+                id: pool.id.clone(),
+                address: pool.address,
+                version: Version::V2,
+                tokens: pool.pool_tokens.iter().map(|t| t.address).collect(),
+            });
+        v2.or_else(|| {
+            registered
+                .balancer_v3
+                .iter()
+                .flat_map(|pools| &pools.pools)
+                .find(|pool| matches(&pool.id, &pool.address))
+                .map(|pool| RecordedPool {
+                    id: pool.id.clone(),
+                    address: pool.address,
+                    version: Version::V3,
+                    tokens: pool.pool_tokens.iter().map(|t| t.address).collect(),
+                })
+        })
+    }
+
+    /// Exports the recorded pools. These are all pools the API or imported
+    /// bundle returned, including the ones pruned from the registries.
+    pub fn export(&self) -> PoolBundle {
+        self.registered.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, serde_json::json};
+
+    fn pools(ids: &[&str]) -> balancer_v2::RegisteredPools {
+        serde_json::from_value(json!({
+            "fetchedBlockNumber": 0,
+            "pools": ids.iter().map(|id| json!({
+                "id": id,
+                "address": "0x2222222222222222222222222222222222222222",
+                "type": "WEIGHTED",
+                "protocolVersion": 2,
+                "factory": "0x5555555555555555555555555555555555555555",
+                "chain": "MAINNET",
+                "poolTokens": [],
+                "dynamicData": { "swapEnabled": true },
+                "createTime": 0,
+            })).collect::<Vec<_>>(),
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn reinitialized_pools_replace_recorded_ones() {
+        let registry = Registry::new(1, None).unwrap();
+        registry.record_v2(&pools(&["0x01", "0x02"]));
+        registry.record_v2(&pools(&["0x02", "0x03"]));
+
+        let exported = registry.export();
+        let ids = exported
+            .balancer_v2
+            .unwrap()
+            .pools
+            .into_iter()
+            .map(|pool| pool.id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, ["0x01", "0x02", "0x03"]);
+        assert!(exported.balancer_v3.is_none());
+    }
+
+    #[test]
+    fn finds_recorded_pools_by_id_and_address() {
+        let registry = Registry::new(1, None).unwrap();
+        registry.record_v2(&pools(&["0x01"]));
+
+        let pool = RecordedPool {
+            id: "0x01".to_owned(),
+            address: eth::H160([0x22; 20]),
+            version: Version::V2,
+            tokens: vec![],
+        };
+        assert_eq!(registry.find("0x01"), Some(pool.clone()));
+        assert_eq!(
+            registry.find("0x2222222222222222222222222222222222222222"),
+            Some(pool)
+        );
+        assert_eq!(registry.find("0x02"), None);
+    }
+
+    #[test]
+    fn rejects_bundles_of_other_chains() {
+        let bundle = PoolBundle {
+            chain_id: 100,
+            ..Default::default()
+        };
+        assert!(Registry::new(1, Some(bundle)).is_err());
+    }
+}
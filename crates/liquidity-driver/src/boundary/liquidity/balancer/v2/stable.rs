@@ -43,6 +43,7 @@ pub fn to_domain(id: liquidity::Id, pool: StablePoolOrder) -> Result<liquidity::
                 pool.amplification_parameter.precision(),
             )?,
             fee: balancer::v2::Fee::from_raw(pool.fee.as_uint256()),
+            actual_supply: pool.actual_supply,
         }),
     })
 }
@@ -19,21 +19,35 @@ use {
         BalancerV3StableSurgePoolFactoryV2,
         BalancerV3Vault,
         BalancerV3WeightedPoolFactory,
-        alloy::GPv2Settlement,
+        alloy::{GPv2Settlement, InstanceExt, Multicall3},
     },
     ethrpc::{
         alloy::conversions::{IntoAlloy, IntoLegacy},
         block_stream::{BlockRetrieving, CurrentBlockWatcher},
     },
     shared::{
+        event_handling::Backfill,
         http_solver::model::TokenAmount,
-        sources::balancer_v3::{
-            BalancerFactoryKind,
-            BalancerPoolFetcher,
-            GqlChain,
-            pool_fetching::BalancerContracts,
+        multicall::{self, Multicall},
+        sources::{
+            balancer_snapshots::SnapshotStore,
+            balancer_v3::{
+                BalancerApiClient,
+                BalancerFactoryKind,
+                BalancerPoolFetcher,
+                GqlChain,
+                PoolInitializing,
+                RegisteredPools,
+                pool_fetching::BalancerContracts,
+                swap::gas,
+            },
+            rate_provider_cache::RateProviderCache,
+        },
+        token_info::{
+            CachedTokenInfoFetcher,
+            DecimalsOverridingTokenInfoFetcher,
+            TokenInfoFetcher,
         },
-        token_info::{CachedTokenInfoFetcher, TokenInfoFetcher},
     },
     solver::{
         interactions::allowances::Allowances,
@@ -109,11 +123,81 @@ fn to_interaction(
     }
 }
 
+/// Records the gas measured for a swap through the liquidity with the gas
+/// cost model of the Balancer V3 pools, if it is a Balancer V3 pool.
+pub fn observe_gas(liquidity: &liquidity::Liquidity, gas: eth::Gas) {
+    let kind = match liquidity.kind {
+        liquidity::Kind::BalancerV3Weighted(_) => gas::PoolKind::Weighted,
+        liquidity::Kind::BalancerV3Stable(_) => gas::PoolKind::Stable,
+        liquidity::Kind::BalancerV3StableSurge(_) => gas::PoolKind::StableSurge,
+        liquidity::Kind::BalancerV3Gyro2CLP(_) => gas::PoolKind::Gyro2CLP,
+        liquidity::Kind::BalancerV3GyroE(_) => gas::PoolKind::GyroE,
+        liquidity::Kind::BalancerV3ReClamm(_) => gas::PoolKind::ReClamm,
+        liquidity::Kind::BalancerV3QuantAmm(_) => gas::PoolKind::QuantAmm,
+        _ => return,
+    };
+    if let Ok(gas) = usize::try_from(gas.0) {
+        gas::observe(kind, gas);
+    }
+}
+
+/// Initializes the pools from the imported bundle if there is one and from
+/// the Balancer V3 API otherwise, recording them for exporting.
+struct PoolInitializer {
+    api: BalancerApiClient,
+    registry: Arc<super::Registry>,
+}
+
+impl PoolInitializer {
+    /// Adds the pools created since the newest pool of the bundle, so that
+    /// only the pools the bundle is missing get fetched from the API.
+    async fn resync(&self, pools: &mut RegisteredPools) {
+        let Some(create_time) = pools.latest_create_time() else {
+            return;
+        };
+        match self.api.get_pools_created_since(create_time).await {
+            Ok(created) => {
+                let added = pools.extend_new(created.pools);
+                tracing::info!(added, "resynced Balancer V3 pools created since the bundle");
+            }
+            Err(err) => tracing::warn!(?err, "failed to resync Balancer V3 pools"),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl PoolInitializing for PoolInitializer {
+    async fn initialize_pools(&self) -> Result<RegisteredPools> {
+        let pools = match self.registry.imported_v3() {
+            Some(pools) => {
+                tracing::info!(
+                    pools = pools.pools.len(),
+                    "initializing Balancer V3 pools from bundle"
+                );
+                let mut pools = pools.clone();
+                self.resync(&mut pools).await;
+                pools
+            }
+            None => self.api.initialize_pools().await?,
+        };
+        self.registry.record_v3(&pools);
+        Ok(pools)
+    }
+
+    fn restored(&self, pools: &RegisteredPools) {
+        self.registry.record_v3(pools);
+    }
+}
+
 pub fn collector(
     eth: &Ethereum,
     block_stream: CurrentBlockWatcher,
     block_retriever: Arc<dyn BlockRetrieving>,
     config: &infra::liquidity::config::BalancerV3,
+    registry: Arc<super::Registry>,
+    snapshots: Option<Arc<SnapshotStore>>,
+    backfill: Backfill,
+    rate_providers: Option<RateProviderCache>,
 ) -> Box<dyn LiquidityCollecting> {
     let eth = Arc::new(eth.with_metric_label("balancerV3".into()));
     let reinit_interval = config.reinit_interval;
@@ -123,7 +207,22 @@ pub fn collector(
         let block_stream = block_stream.clone();
         let block_retriever = block_retriever.clone();
         let config = config.clone();
-        async move { init_liquidity(&eth, &block_stream, block_retriever.clone(), &config).await }
+        let registry = registry.clone();
+        let snapshots = snapshots.clone();
+        let rate_providers = rate_providers.clone();
+        async move {
+            init_liquidity(
+                &eth,
+                &block_stream,
+                block_retriever.clone(),
+                &config,
+                registry,
+                snapshots,
+                backfill,
+                rate_providers,
+            )
+            .await
+        }
     };
     const TEN_MINUTES: std::time::Duration = std::time::Duration::from_secs(10 * 60);
     Box::new(BackgroundInitLiquiditySource::new(
@@ -139,6 +238,10 @@ async fn init_liquidity(
     block_stream: &CurrentBlockWatcher,
     block_retriever: Arc<dyn BlockRetrieving>,
     config: &infra::liquidity::config::BalancerV3,
+    registry: Arc<super::Registry>,
+    snapshots: Option<Arc<SnapshotStore>>,
+    backfill: Backfill,
+    rate_providers: Option<RateProviderCache>,
 ) -> Result<impl LiquidityCollecting + use<>> {
     let web3 = eth.web3().clone();
 
@@ -246,6 +349,18 @@ async fn init_liquidity(
                     )
                 })
                 .collect::<Vec<_>>(),
+            config
+                .reclamm_v1
+                .iter()
+                .map(|&factory| {
+                    (
+                        BalancerFactoryKind::ReClammV1,
+                        contracts::BalancerV3ReClammPoolFactory::at(&web3, factory.into())
+                            .raw_instance()
+                            .clone(),
+                    )
+                })
+                .collect::<Vec<_>>(),
             config
                 .quantamm
                 .iter()
@@ -262,23 +377,46 @@ async fn init_liquidity(
         .into_iter()
         .flatten()
         .collect(),
+        multicall: match config.multicall_batch_size {
+            Some(max_batch_size) => Some(Multicall::new(
+                Multicall3::Instance::deployed(&web3.alloy)
+                    .await
+                    .context("Cannot retrieve Multicall3")?,
+                multicall::Config {
+                    max_batch_size: max_batch_size.get(),
+                    ..Default::default()
+                },
+            )),
+            None => None,
+        },
+        rate_providers,
     };
-    let token_info_fetcher = Arc::new(CachedTokenInfoFetcher::new(Arc::new(TokenInfoFetcher {
-        web3: web3.clone(),
-    })));
+    let token_info_fetcher = Arc::new(DecimalsOverridingTokenInfoFetcher::new(
+        Arc::new(CachedTokenInfoFetcher::new(Arc::new(TokenInfoFetcher {
+            web3: web3.clone(),
+        }))),
+        config.token_decimals.clone(),
+    ));
 
     let balancer_pool_fetcher = Arc::new(
-        BalancerPoolFetcher::new(
-            &config.graph_url,
+        BalancerPoolFetcher::with_initializer(
+            PoolInitializer {
+                api: BalancerApiClient::from_subgraph_url(
+                    &config.graph_url,
+                    boundary::liquidity::http_client(),
+                    chain_to_gql_chain(&eth.chain()),
+                )?,
+                registry,
+            },
             block_retriever.clone(),
             token_info_fetcher.clone(),
             boundary::liquidity::cache_config(),
             block_stream.clone(),
-            boundary::liquidity::http_client(),
             web3.clone(),
             &contracts,
             config.pool_deny_list.clone(),
-            chain_to_gql_chain(&eth.chain()),
+            snapshots,
+            backfill,
         )
         .await
         .context("failed to create Balancer V3 pool fetcher")?,
@@ -46,6 +46,11 @@ pub fn to_domain(id: liquidity::Id, pool: BalancerV3QuantAmmOrder) -> Result<liq
             max_trade_size_ratio: balancer::v3::ScalingFactor::from_raw(
                 pool.max_trade_size_ratio.as_uint256(),
             )?,
+            strategy: balancer::v3::quantamm::Strategy {
+                rule: pool.strategy.rule,
+                oracles: pool.strategy.oracles,
+                update_interval: pool.strategy.update_interval,
+            },
             first_four_weights_and_multipliers: pool.first_four_weights_and_multipliers,
             second_four_weights_and_multipliers: pool.second_four_weights_and_multipliers,
             last_update_time: pool.last_update_time,
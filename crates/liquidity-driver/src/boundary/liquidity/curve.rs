@@ -0,0 +1,135 @@
+use {
+    crate::{
+        boundary::Result,
+        domain::{
+            eth,
+            liquidity::{
+                self,
+                curve::{Coin, Pool},
+            },
+        },
+        infra::{self, blockchain::Ethereum},
+    },
+    anyhow::Context,
+    contracts::alloy::CurveRegistry,
+    ethrpc::alloy::conversions::{IntoAlloy, IntoLegacy},
+    shared::{
+        http_solver::model::TokenAmount,
+        interaction::Interaction,
+        maintenance::{Maintaining, ServiceMaintenance},
+        sources::curve::{self, pool_fetching::CurvePoolFetcher},
+    },
+    solver::{
+        interactions::allowances::Allowances,
+        liquidity::{
+            CurvePool,
+            curve::{CurveLiquidity, CurveSettlementHandler},
+        },
+        liquidity_collector::{
+            BackgroundInitLiquiditySource,
+            LiquidityCollecting,
+            MaintainedLiquiditySource,
+        },
+    },
+    std::sync::{Arc, Mutex},
+};
+
+pub fn to_domain(id: liquidity::Id, pool: CurvePool) -> Result<liquidity::Liquidity> {
+    let pool = pool.pool;
+    anyhow::ensure!(
+        pool.coins.len() == pool.balances.len() && pool.coins.len() == pool.rates.len(),
+        "Curve pool coins should have balances and rates",
+    );
+
+    Ok(liquidity::Liquidity {
+        id,
+        gas: eth::Gas(curve::Pool::POOL_SWAP_GAS_COST.into()),
+        kind: liquidity::Kind::Curve(Pool {
+            address: pool.address.into(),
+            coins: pool
+                .coins
+                .iter()
+                .zip(&pool.balances)
+                .zip(&pool.rates)
+                .map(|((token, balance), rate)| Coin {
+                    token: (*token).into(),
+                    balance: *balance,
+                    rate: *rate,
+                })
+                .collect(),
+            amplification: pool.amplification,
+            fee: pool.fee,
+            base_pool: pool.base_pool.map(Into::into),
+        }),
+    })
+}
+
+pub fn to_interaction(
+    pool: &liquidity::curve::Pool,
+    input: &liquidity::MaxInput,
+    output: &liquidity::ExactOutput,
+) -> Result<eth::Interaction> {
+    let handler = CurveSettlementHandler::new(
+        pool.address.0.into_alloy(),
+        pool.coins.iter().map(|coin| coin.token.into()).collect(),
+        Mutex::new(Allowances::empty(pool.address.0)),
+    );
+
+    let (_, interaction) = handler.settle(
+        TokenAmount::new(input.0.token.into(), input.0.amount),
+        TokenAmount::new(output.0.token.into(), output.0.amount),
+    )?;
+
+    let encoded = interaction.encode();
+    Ok(eth::Interaction {
+        target: eth::Address(encoded.0.into_legacy()),
+        value: eth::Ether(encoded.1.into_legacy()),
+        call_data: crate::util::Bytes(encoded.2.0.to_vec()),
+    })
+}
+
+pub fn collector(
+    eth: &Ethereum,
+    config: &infra::liquidity::config::Curve,
+) -> Box<dyn LiquidityCollecting> {
+    let eth = Arc::new(eth.with_metric_label("curve".into()));
+    let config = Arc::new(Clone::clone(config));
+    let reinit_interval = config.reinit_interval;
+    let init = move || {
+        let eth = eth.clone();
+        let config = config.clone();
+        async move { init_liquidity(&eth, &config).await }
+    };
+    const TEN_MINUTES: std::time::Duration = std::time::Duration::from_secs(10 * 60);
+    Box::new(BackgroundInitLiquiditySource::new(
+        "curve",
+        init,
+        TEN_MINUTES,
+        reinit_interval,
+    )) as Box<_>
+}
+
+async fn init_liquidity(
+    eth: &Ethereum,
+    config: &infra::liquidity::config::Curve,
+) -> anyhow::Result<impl LiquidityCollecting + use<>> {
+    let web3 = eth.web3().clone();
+
+    let registry = CurveRegistry::Instance::new(config.registry.0.into_alloy(), web3.alloy.clone());
+    let pool_fetcher = Arc::new(CurvePoolFetcher::new(registry));
+
+    // List the pools of the registry before the (re)initialized source gets
+    // swapped in.
+    let maintenance = ServiceMaintenance::new(vec![pool_fetcher.clone()]);
+    maintenance
+        .run_maintenance()
+        .await
+        .context("failed to list Curve pools")?;
+    let update_task =
+        tokio::task::spawn(maintenance.run_maintenance_on_new_block(eth.current_block().clone()));
+
+    Ok(MaintainedLiquiditySource::new(
+        CurveLiquidity::new(*eth.contracts().settlement().address(), web3, pool_fetcher),
+        vec![update_task.abort_handle()],
+    ))
+}
@@ -0,0 +1,170 @@
+use {
+    crate::{
+        boundary::Result,
+        domain::{
+            eth,
+            liquidity::{
+                self,
+                maverick_v2::{Fee, Pool, Tick, TickReserves},
+            },
+        },
+        infra::{self, blockchain::Ethereum},
+    },
+    anyhow::Context,
+    contracts::alloy::MaverickV2Factory,
+    ethrpc::{
+        alloy::conversions::{IntoAlloy, IntoLegacy},
+        block_stream::BlockRetrieving,
+    },
+    shared::{
+        http_solver::model::TokenAmount,
+        interaction::Interaction,
+        maintenance::{Maintaining, ServiceMaintenance},
+        sources::maverick_v2::{self, pool_fetching::MaverickV2PoolFetcher},
+    },
+    solver::{
+        interactions::allowances::Allowances,
+        liquidity::{
+            MaverickV2Pool,
+            maverick_v2::{MaverickV2Liquidity, MaverickV2SettlementHandler},
+        },
+        liquidity_collector::{
+            BackgroundInitLiquiditySource,
+            LiquidityCollecting,
+            MaintainedLiquiditySource,
+        },
+    },
+    std::sync::{Arc, Mutex},
+};
+
+pub fn to_domain(id: liquidity::Id, pool: MaverickV2Pool) -> Result<liquidity::Liquidity> {
+    let handler = pool
+        .settlement_handling
+        .as_any()
+        .downcast_ref::<MaverickV2SettlementHandler>()
+        .expect("downcast maverick v2 settlement handler");
+
+    // The token pair is sorted, so token A has to be the lower address for the
+    // swap direction to be encoded correctly.
+    let tokens = liquidity::TokenPair::try_new(pool.pool.token_a.into(), pool.pool.token_b.into())?;
+    anyhow::ensure!(
+        tokens.get().0 == eth::TokenAddress::from(pool.pool.token_a),
+        "Maverick V2 pool tokens should be sorted",
+    );
+
+    Ok(liquidity::Liquidity {
+        id,
+        gas: eth::Gas(maverick_v2::Pool::POOL_SWAP_GAS_COST.into()),
+        kind: liquidity::Kind::MaverickV2(Pool {
+            router: handler.inner.router.into_legacy().into(),
+            address: pool.pool.address.into(),
+            tokens,
+            tick_spacing: pool.pool.tick_spacing,
+            active_tick: Tick(pool.pool.active_tick),
+            ticks: pool
+                .pool
+                .ticks
+                .iter()
+                .map(|(tick, reserves)| {
+                    (
+                        Tick(*tick),
+                        TickReserves {
+                            a: reserves.reserve_a,
+                            b: reserves.reserve_b,
+                        },
+                    )
+                })
+                .collect(),
+            fee_a_in: Fee(pool.pool.fee_a_in),
+            fee_b_in: Fee(pool.pool.fee_b_in),
+        }),
+    })
+}
+
+pub fn to_interaction(
+    pool: &liquidity::maverick_v2::Pool,
+    input: &liquidity::MaxInput,
+    output: &liquidity::ExactOutput,
+    receiver: &eth::Address,
+) -> Result<eth::Interaction> {
+    let (token_a, token_b) = pool.tokens.get();
+    let handler = MaverickV2SettlementHandler::new(
+        pool.router.0.into_alloy(),
+        receiver.0.into_alloy(),
+        Mutex::new(Allowances::empty(receiver.0)),
+        pool.address.0.into_alloy(),
+        token_a.into(),
+        token_b.into(),
+    );
+
+    let (_, interaction) = handler.settle(
+        TokenAmount::new(input.0.token.into(), input.0.amount),
+        TokenAmount::new(output.0.token.into(), output.0.amount),
+    )?;
+
+    let encoded = interaction.encode();
+    Ok(eth::Interaction {
+        target: eth::Address(encoded.0.into_legacy()),
+        value: eth::Ether(encoded.1.into_legacy()),
+        call_data: crate::util::Bytes(encoded.2.0.to_vec()),
+    })
+}
+
+pub fn collector(
+    eth: &Ethereum,
+    block_retriever: Arc<dyn BlockRetrieving>,
+    config: &infra::liquidity::config::MaverickV2,
+) -> Box<dyn LiquidityCollecting> {
+    let eth = Arc::new(eth.with_metric_label("maverickV2".into()));
+    let config = Arc::new(Clone::clone(config));
+    let reinit_interval = config.reinit_interval;
+    let init = move || {
+        let eth = eth.clone();
+        let block_retriever = block_retriever.clone();
+        let config = config.clone();
+        async move { init_liquidity(&eth, block_retriever.clone(), &config).await }
+    };
+    const TEN_MINUTES: std::time::Duration = std::time::Duration::from_secs(10 * 60);
+    Box::new(BackgroundInitLiquiditySource::new(
+        "maverick-v2",
+        init,
+        TEN_MINUTES,
+        reinit_interval,
+    )) as Box<_>
+}
+
+async fn init_liquidity(
+    eth: &Ethereum,
+    block_retriever: Arc<dyn BlockRetrieving>,
+    config: &infra::liquidity::config::MaverickV2,
+) -> anyhow::Result<impl LiquidityCollecting + use<>> {
+    let web3 = eth.web3().clone();
+
+    let factory =
+        MaverickV2Factory::Instance::new(config.factory.0.into_alloy(), web3.alloy.clone());
+    let pool_fetcher = Arc::new(MaverickV2PoolFetcher::new(
+        &factory,
+        config.deployment_block,
+        block_retriever,
+    ));
+
+    // Index the pools up to the chain head before the (re)initialized source
+    // gets swapped in.
+    let maintenance = ServiceMaintenance::new(vec![pool_fetcher.clone()]);
+    maintenance
+        .run_maintenance()
+        .await
+        .context("failed to sync MaverickV2 liquidity")?;
+    let update_task =
+        tokio::task::spawn(maintenance.run_maintenance_on_new_block(eth.current_block().clone()));
+
+    Ok(MaintainedLiquiditySource::new(
+        MaverickV2Liquidity::new(
+            config.router.0.into_alloy(),
+            *eth.contracts().settlement().address(),
+            web3,
+            pool_fetcher,
+        ),
+        vec![update_task.abort_handle()],
+    ))
+}
@@ -3,7 +3,7 @@ use {
         domain::{eth, liquidity},
         infra::{self, blockchain::Ethereum},
     },
-    anyhow::Result,
+    anyhow::{Context, Result, anyhow},
     ethrpc::{
         alloy::conversions::{IntoAlloy, IntoLegacy},
         block_stream::CurrentBlockWatcher,
@@ -14,6 +14,11 @@ use {
         baseline_solver::BaseTokens,
         http_client::HttpClientFactory,
         recent_block_cache::{self, CacheConfig},
+        sources::{
+            balancer_snapshots::SnapshotStore,
+            balancer_v3::swap::gas,
+            rate_provider_cache::RateProviderCache,
+        },
     },
     solver::{
         liquidity::Liquidity,
@@ -28,7 +33,10 @@ use {
 };
 
 pub mod balancer;
+pub mod curve;
 pub mod erc4626;
+pub mod maverick_v2;
+pub mod routing;
 pub mod swapr;
 pub mod uniswap;
 pub mod zeroex;
@@ -54,6 +62,9 @@ pub struct Fetcher {
     blocks: CurrentBlockWatcher,
     inner: LiquidityCollector,
     swapr_routers: HashSet<eth::ContractAddress>,
+    balancer_pools: Arc<balancer::Registry>,
+    balancer_v3_vaults: Vec<eth::ContractAddress>,
+    web3: shared::ethrpc::Web3,
 }
 
 impl Fetcher {
@@ -79,19 +90,60 @@ impl Fetcher {
         )
         .await?;
 
+        let balancer_pools = Arc::new(balancer::Registry::new(
+            eth.chain().id(),
+            config
+                .pool_bundle
+                .as_deref()
+                .map(balancer::PoolBundle::load)
+                .transpose()?,
+        )?);
+        let pool_snapshots = config
+            .pool_snapshots
+            .as_deref()
+            .map(SnapshotStore::open)
+            .transpose()?
+            .map(Arc::new);
+
+        let event_backfill = config.event_backfill;
+        let rate_providers = config.rate_provider_cache.map(|config| {
+            let eth = eth.with_metric_label("balancerRateProviders".into());
+            let cache = RateProviderCache::new(Arc::new(eth.web3().clone()), config);
+            cache.spawn_refresh_task(block_stream.clone());
+            cache
+        });
         let bal_v2: Vec<_> = config
             .balancer_v2
             .iter()
             .map(|config| {
-                balancer::v2::collector(eth, block_stream.clone(), block_retriever.clone(), config)
+                balancer::v2::collector(
+                    eth,
+                    block_stream.clone(),
+                    block_retriever.clone(),
+                    config,
+                    balancer_pools.clone(),
+                    pool_snapshots.clone(),
+                    event_backfill,
+                    rate_providers.clone(),
+                )
             })
             .collect();
 
+        gas::configure(gas::GasCostModel::new(config.balancer_v3_gas_costs.clone()));
         let bal_v3: Vec<_> = config
             .balancer_v3
             .iter()
             .map(|config| {
-                balancer::v3::collector(eth, block_stream.clone(), block_retriever.clone(), config)
+                balancer::v3::collector(
+                    eth,
+                    block_stream.clone(),
+                    block_retriever.clone(),
+                    config,
+                    balancer_pools.clone(),
+                    pool_snapshots.clone(),
+                    event_backfill,
+                    rate_providers.clone(),
+                )
             })
             .collect();
 
@@ -101,6 +153,24 @@ impl Fetcher {
             .map(|config| uniswap::v3::collector(eth, block_retriever.clone(), config))
             .collect();
 
+        let uni_v4: Vec<_> = config
+            .uniswap_v4
+            .iter()
+            .map(|config| uniswap::v4::collector(eth, block_retriever.clone(), config))
+            .collect();
+
+        let maverick_v2: Vec<_> = config
+            .maverick_v2
+            .iter()
+            .map(|config| maverick_v2::collector(eth, block_retriever.clone(), config))
+            .collect();
+
+        let curve: Vec<_> = config
+            .curve
+            .iter()
+            .map(|config| curve::collector(eth, config))
+            .collect();
+
         let zeroex: Vec<_> = future::try_join_all(
             config
                 .zeroex
@@ -133,6 +203,9 @@ impl Fetcher {
                     bal_v2,
                     bal_v3,
                     uni_v3,
+                    uni_v4,
+                    maverick_v2,
+                    curve,
                     zeroex,
                     erc4626_sources,
                 ]
@@ -142,9 +215,88 @@ impl Fetcher {
                 base_tokens: Arc::new(base_tokens),
             },
             swapr_routers,
+            balancer_pools,
+            balancer_v3_vaults: config
+                .balancer_v3
+                .iter()
+                .map(|config| config.vault)
+                .collect(),
+            web3: eth.web3().clone(),
         })
     }
 
+    /// Exports the Balancer pools the registries got initialized with.
+    pub fn balancer_pools(&self) -> balancer::PoolBundle {
+        self.balancer_pools.export()
+    }
+
+    /// Fetches the latest state of the Balancer pool with the specified pool
+    /// ID or address. Returns `None` if the registries don't know the pool.
+    pub async fn fetch_pool(&self, id: &str) -> Result<Option<balancer::PoolInspection>> {
+        let Some(pool) = self.balancer_pools.find(id) else {
+            return Ok(None);
+        };
+        let tokens = &pool.tokens;
+        let pairs = tokens
+            .iter()
+            .enumerate()
+            .flat_map(|(i, a)| tokens[i + 1..].iter().map(move |b| (*a, *b)))
+            .filter_map(|(a, b)| liquidity::TokenPair::try_new(a.into(), b.into()).ok())
+            .collect();
+        let block = self.blocks.borrow().number;
+        let (liquidity, paused) = future::join(
+            self.fetch(&pairs, infra::liquidity::AtBlock::Number(block)),
+            self.fetch_paused(&pool, block),
+        )
+        .await;
+        let liquidity = liquidity?
+            .into_iter()
+            .find(|liquidity| liquidity.address() == Some(pool.address));
+        Ok(Some(balancer::PoolInspection {
+            pool,
+            block,
+            paused,
+            liquidity,
+        }))
+    }
+
+    /// Fetches whether the Balancer pool is paused at the specified block.
+    /// Returns `None` if the paused state couldn't be fetched.
+    async fn fetch_paused(&self, pool: &balancer::RecordedPool, block: u64) -> Option<bool> {
+        let paused = match pool.version {
+            balancer::Version::V2 => contracts::alloy::BalancerV2BasePool::Instance::new(
+                pool.address.into_alloy(),
+                self.web3.alloy.clone(),
+            )
+            .getPausedState()
+            .block(block.into())
+            .call()
+            .await
+            .map(|state| state.paused)
+            .map_err(anyhow::Error::from),
+            // The registries don't remember which vault a V3 pool belongs to,
+            // so ask the vaults until one of them knows the pool.
+            balancer::Version::V3 => {
+                let mut paused = Err(anyhow!("no Balancer V3 vault configured"));
+                for vault in &self.balancer_v3_vaults {
+                    paused = contracts::BalancerV3Vault::at(&self.web3, vault.0)
+                        .is_pool_paused(pool.address)
+                        .block(ethcontract::BlockId::Number(block.into()))
+                        .call()
+                        .await
+                        .map_err(anyhow::Error::from);
+                    if paused.is_ok() {
+                        break;
+                    }
+                }
+                paused
+            }
+        };
+        paused
+            .inspect_err(|err| tracing::debug!(?err, id = %pool.id, "failed to fetch paused state"))
+            .ok()
+    }
+
     /// Fetches liquidity for the specified auction.
     pub async fn fetch(
         &self,
@@ -166,44 +318,95 @@ impl Fetcher {
                 let block_number = self.blocks.borrow().number;
                 recent_block_cache::Block::Number(block_number)
             }
+            infra::liquidity::AtBlock::Number(block_number) => {
+                recent_block_cache::Block::Number(block_number)
+            }
         };
         let liquidity = self.inner.get_liquidity(pairs, block).await?;
 
         let liquidity = liquidity
             .into_iter()
             .enumerate()
-            .filter_map(|(index, liquidity)| {
-                let id = liquidity::Id(index);
-                match liquidity {
-                    Liquidity::ConstantProduct(pool) => {
-                        if self.swapr_routers.contains(&uniswap::v2::router(&pool)) {
-                            swapr::to_domain(id, pool)
-                        } else {
-                            uniswap::v2::to_domain(id, pool)
-                        }
-                    }
-                    Liquidity::BalancerWeighted(pool) => balancer::v2::weighted::to_domain(id, pool),
-                    Liquidity::BalancerV3Weighted(pool) => balancer::v3::weighted::to_domain(id, pool),
-                    Liquidity::BalancerStable(pool) => balancer::v2::stable::to_domain(id, pool),
-                    Liquidity::BalancerV3Stable(pool) => balancer::v3::stable::to_domain(id, pool),
-                    Liquidity::BalancerGyroE(pool) => balancer::v2::gyro_e::to_domain(id, pool),
-                    Liquidity::BalancerGyro2CLP(pool) => balancer::v2::gyro_2clp::to_domain(id, pool),
-                    Liquidity::BalancerGyro3CLP(pool) => balancer::v2::gyro_3clp::to_domain(id, pool),
-                    Liquidity::BalancerV3GyroE(pool) => balancer::v3::gyro_e::to_domain(id, pool),
-                    Liquidity::BalancerV3Gyro2CLP(pool) => balancer::v3::gyro_2clp::to_domain(id, pool),
-                    Liquidity::BalancerV3ReClamm(pool) => balancer::v3::reclamm::to_domain(id, pool),
-                    Liquidity::BalancerV3QuantAmm(pool) => balancer::v3::quantamm::to_domain(id, pool),
-                    Liquidity::BalancerV3StableSurge(pool) => balancer::v3::stable_surge::to_domain(id, pool),
-                    Liquidity::LimitOrder(pool) => zeroex::to_domain(id, pool),
-                    Liquidity::Concentrated(pool) => uniswap::v3::to_domain(id, pool),
-                    Liquidity::Erc4626(order) => erc4626::to_domain(id, *order),
-                }
-                // Ignore "bad" liquidity - this allows the driver to continue
-                // solving with the other good stuff.
-                .ok()
+            // Ignore "bad" liquidity - this allows the driver to continue
+            // solving with the other good stuff.
+            .filter_map(|(index, liquidity)| self.to_domain(liquidity::Id(index), liquidity).ok())
+            .collect::<Vec<_>>();
+
+        // Different collectors can index the same pools, so collapse those to
+        // avoid sending duplicates to the solvers.
+        let total = liquidity.len();
+        let liquidity = liquidity::deduplicate(liquidity);
+        if liquidity.len() < total {
+            tracing::debug!(
+                removed = total - liquidity.len(),
+                "removed duplicate liquidity"
+            );
+        }
+        Ok(liquidity)
+    }
+
+    /// Enumerates the routes for selling the asset for the buy token over the
+    /// recently cached liquidity, with up to `max_hops` intermediate base
+    /// tokens.
+    pub async fn routes(
+        &self,
+        sell: eth::Asset,
+        buy: eth::TokenAddress,
+        max_hops: usize,
+    ) -> Result<Vec<routing::Route>> {
+        let pair = TokenPair::new(sell.token.0.0.into_alloy(), buy.0.0.into_alloy())
+            .context("sell and buy tokens are equal")?;
+        let liquidity = self
+            .inner
+            .get_liquidity([pair].into(), recent_block_cache::Block::Recent)
+            .await?;
+        let edges = liquidity
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, boundary)| {
+                let liquidity = self
+                    .to_domain(liquidity::Id(index), boundary.clone())
+                    .ok()?;
+                routing::Edge::new(&self.web3, boundary, liquidity)
             })
             .collect();
-        Ok(liquidity)
+        Ok(routing::routes(&self.inner.base_tokens, edges, sell, buy, max_hops).await)
+    }
+
+    fn to_domain(&self, id: liquidity::Id, liquidity: Liquidity) -> Result<liquidity::Liquidity> {
+        match liquidity {
+            Liquidity::ConstantProduct(pool) => {
+                // Constant product pools with custom fees behave exactly like
+                // Swapr pools.
+                if self.swapr_routers.contains(&uniswap::v2::router(&pool))
+                    || !uniswap::v2::has_constant_fee(&pool)
+                {
+                    swapr::to_domain(id, pool)
+                } else {
+                    uniswap::v2::to_domain(id, pool)
+                }
+            }
+            Liquidity::BalancerWeighted(pool) => balancer::v2::weighted::to_domain(id, pool),
+            Liquidity::BalancerV3Weighted(pool) => balancer::v3::weighted::to_domain(id, pool),
+            Liquidity::BalancerStable(pool) => balancer::v2::stable::to_domain(id, pool),
+            Liquidity::BalancerV3Stable(pool) => balancer::v3::stable::to_domain(id, pool),
+            Liquidity::BalancerGyroE(pool) => balancer::v2::gyro_e::to_domain(id, pool),
+            Liquidity::BalancerGyro2CLP(pool) => balancer::v2::gyro_2clp::to_domain(id, pool),
+            Liquidity::BalancerGyro3CLP(pool) => balancer::v2::gyro_3clp::to_domain(id, pool),
+            Liquidity::BalancerV3GyroE(pool) => balancer::v3::gyro_e::to_domain(id, pool),
+            Liquidity::BalancerV3Gyro2CLP(pool) => balancer::v3::gyro_2clp::to_domain(id, pool),
+            Liquidity::BalancerV3ReClamm(pool) => balancer::v3::reclamm::to_domain(id, pool),
+            Liquidity::BalancerV3QuantAmm(pool) => balancer::v3::quantamm::to_domain(id, pool),
+            Liquidity::BalancerV3StableSurge(pool) => {
+                balancer::v3::stable_surge::to_domain(id, pool)
+            }
+            Liquidity::LimitOrder(pool) => zeroex::to_domain(id, pool),
+            Liquidity::Concentrated(pool) => uniswap::v3::to_domain(id, pool),
+            Liquidity::UniswapV4(pool) => uniswap::v4::to_domain(id, pool),
+            Liquidity::MaverickV2(pool) => maverick_v2::to_domain(id, pool),
+            Liquidity::Curve(pool) => curve::to_domain(id, pool),
+            Liquidity::Erc4626(order) => erc4626::to_domain(id, *order),
+        }
     }
 }
 
@@ -0,0 +1,383 @@
+//! Enumeration of the swap routes between two tokens over the fetched
+//! liquidity, using the [`shared`] baseline path finding.
+
+use {
+    crate::domain::{eth, liquidity},
+    ethrpc::alloy::conversions::{IntoAlloy, IntoLegacy},
+    model::TokenPair,
+    shared::{
+        baseline_solver::{self, BaseTokens, BaselineSolvable},
+        ethrpc::Web3,
+        sources::{
+            balancer_v2,
+            balancer_v3,
+            curve,
+            erc4626::{Erc4626Edge, registry::VaultMeta},
+            maverick_v2,
+        },
+    },
+    solver::liquidity::{
+        BalancerV3StablePoolOrder,
+        BalancerV3WeightedProductOrder,
+        ConstantProductOrder,
+        Liquidity,
+        StablePoolOrder,
+        WeightedProductOrder,
+    },
+    std::collections::HashMap,
+};
+
+/// A route for swapping the sell asset for the buy token.
+#[derive(Debug)]
+pub struct Route {
+    /// The swaps along the route, each swapping the output of the previous
+    /// one.
+    pub hops: Vec<Hop>,
+}
+
+impl Route {
+    /// The buy tokens the route is expected to yield.
+    pub fn output(&self) -> eth::Asset {
+        self.hops
+            .last()
+            .expect("routes have at least one hop")
+            .output
+    }
+
+    /// The gas of all the swaps along the route.
+    pub fn gas(&self) -> eth::Gas {
+        eth::Gas(self.hops.iter().map(|hop| hop.gas.0).sum())
+    }
+}
+
+/// A single swap of a route.
+#[derive(Debug)]
+pub struct Hop {
+    pub liquidity: liquidity::Liquidity,
+    pub input: eth::Asset,
+    pub output: eth::Asset,
+    pub gas: eth::Gas,
+}
+
+/// Fetched liquidity that can be priced offchain, and therefore routed
+/// through.
+#[derive(Clone)]
+pub struct Edge {
+    liquidity: liquidity::Liquidity,
+    pool: Pool,
+}
+
+#[derive(Clone)]
+enum Pool {
+    UniswapV2(ConstantProductOrder),
+    BalancerV2Weighted(WeightedProductOrder),
+    BalancerV2Stable(StablePoolOrder),
+    BalancerV3Weighted(BalancerV3WeightedProductOrder),
+    BalancerV3Stable(BalancerV3StablePoolOrder),
+    MaverickV2(maverick_v2::Pool),
+    Curve(curve::Pool),
+    Erc4626(Erc4626Edge),
+}
+
+impl Edge {
+    /// Returns the edge for the fetched liquidity and its domain
+    /// representation, or `None` for the kinds of liquidity that can't be
+    /// routed through, such as limit orders or pools that require onchain
+    /// quoting.
+    pub fn new(web3: &Web3, boundary: Liquidity, liquidity: liquidity::Liquidity) -> Option<Self> {
+        let pool = match boundary {
+            Liquidity::ConstantProduct(pool) => Pool::UniswapV2(pool),
+            Liquidity::BalancerWeighted(pool) => Pool::BalancerV2Weighted(pool),
+            Liquidity::BalancerStable(pool) => Pool::BalancerV2Stable(pool),
+            Liquidity::BalancerV3Weighted(pool) => Pool::BalancerV3Weighted(pool),
+            Liquidity::BalancerV3Stable(pool) => Pool::BalancerV3Stable(pool),
+            Liquidity::MaverickV2(pool) => Pool::MaverickV2(pool.pool),
+            Liquidity::Curve(pool) => Pool::Curve(pool.pool),
+            Liquidity::Erc4626(order) => {
+                let (vault, asset) = match (&order.wrap, &order.unwrap) {
+                    (Some(wrap), _) => (wrap.vault.address(), wrap.underlying.address()),
+                    (None, Some(unwrap)) => {
+                        let vault = unwrap.vault.address();
+                        let asset = order.tokens.other(&vault.into_alloy())?;
+                        (vault, asset.into_legacy())
+                    }
+                    (None, None) => return None,
+                };
+                // Routes are only priced for exact input amounts, which the
+                // epsilon does not apply to.
+                let meta = VaultMeta {
+                    vault,
+                    asset,
+                    epsilon_bps: 0,
+                };
+                Pool::Erc4626(Erc4626Edge::new(web3, &meta))
+            }
+            _ => return None,
+        };
+        Some(Self { liquidity, pool })
+    }
+
+    /// The token pairs that can be swapped through the edge.
+    fn pairs(&self) -> Vec<TokenPair> {
+        let tokens = match &self.pool {
+            Pool::UniswapV2(pool) => return vec![pool.tokens],
+            Pool::Curve(pool) => return pool.token_pairs(),
+            Pool::BalancerV2Weighted(pool) => pool.reserves.keys().copied().collect(),
+            Pool::BalancerV2Stable(pool) => pool.reserves.keys().copied().collect(),
+            Pool::BalancerV3Weighted(pool) => pool.reserves.keys().copied().collect(),
+            Pool::BalancerV3Stable(pool) => pool.reserves.keys().copied().collect(),
+            Pool::MaverickV2(pool) => vec![pool.token_a, pool.token_b],
+            Pool::Erc4626(edge) => vec![edge.vault, edge.asset],
+        };
+        tokens
+            .iter()
+            .enumerate()
+            .flat_map(|(i, a)| tokens[i + 1..].iter().map(move |b| (*a, *b)))
+            .filter_map(|(a, b)| TokenPair::new(a.into_alloy(), b.into_alloy()))
+            .collect()
+    }
+}
+
+impl BaselineSolvable for Edge {
+    async fn get_amount_out(
+        &self,
+        out_token: eth::H160,
+        input: (eth::U256, eth::H160),
+    ) -> Option<eth::U256> {
+        match &self.pool {
+            Pool::UniswapV2(pool) => pool.get_amount_out(out_token, input).await,
+            Pool::BalancerV2Weighted(pool) => pool.get_amount_out(out_token, input).await,
+            Pool::BalancerV2Stable(pool) => v2_stable(pool).get_amount_out(out_token, input).await,
+            Pool::BalancerV3Weighted(pool) => {
+                v3_weighted(pool).get_amount_out(out_token, input).await
+            }
+            Pool::BalancerV3Stable(pool) => v3_stable(pool).get_amount_out(out_token, input).await,
+            Pool::MaverickV2(pool) => pool.get_amount_out(out_token, input).await,
+            Pool::Curve(pool) => pool.get_amount_out(out_token, input).await,
+            Pool::Erc4626(edge) => edge.get_amount_out(out_token, input).await,
+        }
+    }
+
+    async fn get_amount_in(
+        &self,
+        in_token: eth::H160,
+        output: (eth::U256, eth::H160),
+    ) -> Option<eth::U256> {
+        match &self.pool {
+            Pool::UniswapV2(pool) => pool.get_amount_in(in_token, output).await,
+            Pool::BalancerV2Weighted(pool) => pool.get_amount_in(in_token, output).await,
+            Pool::BalancerV2Stable(pool) => v2_stable(pool).get_amount_in(in_token, output).await,
+            Pool::BalancerV3Weighted(pool) => {
+                v3_weighted(pool).get_amount_in(in_token, output).await
+            }
+            Pool::BalancerV3Stable(pool) => v3_stable(pool).get_amount_in(in_token, output).await,
+            Pool::MaverickV2(pool) => pool.get_amount_in(in_token, output).await,
+            Pool::Curve(pool) => pool.get_amount_in(in_token, output).await,
+            Pool::Erc4626(edge) => edge.get_amount_in(in_token, output).await,
+        }
+    }
+
+    async fn gas_cost(&self) -> usize {
+        match &self.pool {
+            Pool::UniswapV2(pool) => pool.gas_cost().await,
+            Pool::BalancerV2Weighted(pool) => pool.gas_cost().await,
+            Pool::BalancerV2Stable(pool) => v2_stable(pool).gas_cost().await,
+            Pool::BalancerV3Weighted(pool) => v3_weighted(pool).gas_cost().await,
+            Pool::BalancerV3Stable(pool) => v3_stable(pool).gas_cost().await,
+            Pool::MaverickV2(pool) => pool.gas_cost().await,
+            Pool::Curve(pool) => pool.gas_cost().await,
+            Pool::Erc4626(edge) => edge.gas_cost().await,
+        }
+    }
+}
+
+fn v2_stable(pool: &StablePoolOrder) -> balancer_v2::swap::StablePoolRef<'_> {
+    balancer_v2::swap::StablePoolRef {
+        address: pool.address,
+        reserves: &pool.reserves,
+        swap_fee: pool.fee,
+        amplification_parameter: pool.amplification_parameter,
+        actual_supply: pool.actual_supply,
+    }
+}
+
+fn v3_weighted(pool: &BalancerV3WeightedProductOrder) -> balancer_v3::swap::WeightedPoolRef<'_> {
+    balancer_v3::swap::WeightedPoolRef {
+        reserves: &pool.reserves,
+        swap_fee: pool.fee,
+        version: pool.version,
+    }
+}
+
+fn v3_stable(pool: &BalancerV3StablePoolOrder) -> balancer_v3::swap::StablePoolRef<'_> {
+    balancer_v3::swap::StablePoolRef {
+        address: pool.address,
+        reserves: &pool.reserves,
+        swap_fee: pool.fee,
+        amplification_parameter: pool.amplification_parameter,
+    }
+}
+
+/// Enumerates the routes for selling the asset for the buy token with up to
+/// `max_hops` intermediate base tokens. For every candidate path of tokens,
+/// the edge yielding the most is picked for each hop. The routes are sorted
+/// by the buy amount they yield, the best route first.
+pub async fn routes(
+    base_tokens: &BaseTokens,
+    edges: Vec<Edge>,
+    sell: eth::Asset,
+    buy: eth::TokenAddress,
+    max_hops: usize,
+) -> Vec<Route> {
+    let mut graph = HashMap::<TokenPair, Vec<Edge>>::new();
+    for edge in edges {
+        for pair in edge.pairs() {
+            graph.entry(pair).or_default().push(edge.clone());
+        }
+    }
+
+    let candidates = base_tokens.path_candidates_with_hops(sell.token.0.0, buy.0.0, max_hops);
+    let routes = candidates.iter().map(|path| async {
+        let estimate = baseline_solver::estimate_buy_amount(sell.amount.0, path, &graph).await?;
+        let mut hops = Vec::new();
+        let mut input = sell;
+        for (edge, token) in estimate.path.iter().zip(path.iter().skip(1)) {
+            let amount = edge
+                .get_amount_out(*token, (input.amount.0, input.token.0.0))
+                .await?;
+            let output = eth::Asset {
+                token: (*token).into(),
+                amount: amount.into(),
+            };
+            hops.push(Hop {
+                liquidity: edge.liquidity.clone(),
+                input,
+                output,
+                gas: eth::Gas(edge.gas_cost().await.into()),
+            });
+            input = output;
+        }
+        Some(Route { hops })
+    });
+
+    let mut routes = futures::future::join_all(routes)
+        .await
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    routes.sort_by_key(|route| std::cmp::Reverse(route.output().amount.0));
+    routes
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::boundary::liquidity::uniswap,
+        solver::{interactions::allowances::Allowances, liquidity::uniswap_v2},
+        std::sync::{Arc, Mutex},
+    };
+
+    fn token(token: u64) -> eth::H160 {
+        eth::H160::from_low_u64_be(token)
+    }
+
+    /// A Uniswap V2 edge between two tokens with the specified reserves.
+    fn edge(id: usize, (a, reserve_a): (u64, u128), (b, reserve_b): (u64, u128)) -> Edge {
+        let (tokens, reserves) = if token(a) < token(b) {
+            ((a, b), (reserve_a, reserve_b))
+        } else {
+            ((b, a), (reserve_b, reserve_a))
+        };
+        let pool = ConstantProductOrder {
+            address: eth::H160::from_low_u64_be(id as u64 + 100),
+            tokens: TokenPair::new(token(tokens.0).into_alloy(), token(tokens.1).into_alloy())
+                .unwrap(),
+            reserves,
+            fee: num::rational::Ratio::new(3, 1000),
+            settlement_handling: Arc::new(uniswap_v2::Inner::new(
+                token(99).into_alloy(),
+                token(98).into_alloy(),
+                Mutex::new(Allowances::empty(token(99))),
+            )),
+        };
+        Edge {
+            liquidity: uniswap::v2::to_domain(liquidity::Id(id), pool.clone()).unwrap(),
+            pool: Pool::UniswapV2(pool),
+        }
+    }
+
+    /// Routes selling token 1 for token 2, with token 4 as the native token
+    /// and token 3 as an additional base token.
+    async fn enumerate(max_hops: usize) -> Vec<Route> {
+        let edges = vec![
+            edge(0, (1, 1_000_000), (2, 1_000_000)),
+            // A shallower pool for the same pair that is never picked.
+            edge(1, (1, 500_000), (2, 500_000)),
+            edge(2, (1, 1_000_000), (4, 4_000_000)),
+            edge(3, (4, 4_000_000), (2, 1_100_000)),
+            edge(4, (4, 4_000_000), (3, 4_000_000)),
+            edge(5, (3, 4_000_000), (2, 1_200_000)),
+        ];
+        super::routes(
+            &BaseTokens::new(token(4), &[token(3)]),
+            edges,
+            eth::Asset {
+                token: token(1).into(),
+                amount: 10_000.into(),
+            },
+            token(2).into(),
+            max_hops,
+        )
+        .await
+    }
+
+    /// The liquidity, input and output amounts, and gas of each hop.
+    fn hops(route: &Route) -> Vec<(usize, u64, u64, u64)> {
+        route
+            .hops
+            .iter()
+            .map(|hop| {
+                (
+                    hop.liquidity.id.0,
+                    hop.input.amount.0.as_u64(),
+                    hop.output.amount.0.as_u64(),
+                    hop.gas.0.as_u64(),
+                )
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn limits_routes_to_max_hops() {
+        let direct = vec![(0, 10_000, 9_871, 60_000)];
+        let via_native = vec![(2, 10_000, 39_486, 60_000), (3, 39_486, 10_720, 60_000)];
+        let via_base = vec![
+            (2, 10_000, 39_486, 60_000),
+            (4, 39_486, 38_983, 60_000),
+            (5, 38_983, 11_547, 60_000),
+        ];
+
+        let routes = enumerate(0).await;
+        assert_eq!(
+            routes.iter().map(hops).collect::<Vec<_>>(),
+            [direct.clone()]
+        );
+
+        let routes = enumerate(1).await;
+        assert_eq!(
+            routes.iter().map(hops).collect::<Vec<_>>(),
+            [via_native.clone(), direct.clone()]
+        );
+
+        // Paths without liquidity for every hop, like the one through token 3
+        // and then token 4, are dropped.
+        let routes = enumerate(2).await;
+        assert_eq!(
+            routes.iter().map(hops).collect::<Vec<_>>(),
+            [via_base, via_native, direct]
+        );
+        assert_eq!(routes[0].output().amount.0, 11_547.into());
+        assert_eq!(routes[0].gas().0, 180_000.into());
+    }
+}
@@ -46,7 +46,7 @@ pub async fn collector(
         blocks,
         &infra::liquidity::config::UniswapV2 {
             router: config.router,
-            pool_code: config.pool_code,
+            pools: infra::liquidity::config::UniswapV2Pools::Derived(config.pool_code),
             missing_pool_cache_time: config.missing_pool_cache_time,
         },
         |web3, pair_provider| SwaprPoolReader(DefaultPoolReader::new(web3, pair_provider)),
@@ -5,7 +5,7 @@ use {
             eth,
             liquidity::{self, uniswap},
         },
-        infra::{self, blockchain::Ethereum},
+        infra::{self, blockchain::Ethereum, liquidity::config::UniswapV2Pools},
     },
     async_trait::async_trait,
     contracts::alloy::IUniswapLikeRouter,
@@ -20,6 +20,7 @@ use {
             pair_provider::PairProvider,
             pool_cache::PoolCache,
             pool_fetching::{DefaultPoolReader, PoolFetcher, PoolReading},
+            pool_list::{ListedPoolReader, PoolList},
         },
     },
     solver::{
@@ -41,10 +42,7 @@ use {
 const GAS_PER_SWAP: u64 = 90_171;
 
 pub fn to_domain(id: liquidity::Id, pool: ConstantProductOrder) -> Result<liquidity::Liquidity> {
-    assert!(
-        *pool.fee.numer() == 3 && *pool.fee.denom() == 1000,
-        "uniswap pools have constant fees",
-    );
+    assert!(has_constant_fee(&pool), "uniswap pools have constant fees");
 
     Ok(liquidity::Liquidity {
         id,
@@ -53,6 +51,12 @@ pub fn to_domain(id: liquidity::Id, pool: ConstantProductOrder) -> Result<liquid
     })
 }
 
+/// Returns whether the pool charges the constant Uniswap V2 fee of 0.3%. Pools
+/// from pool lists may belong to forks charging different fees.
+pub fn has_constant_fee(pool: &ConstantProductOrder) -> bool {
+    *pool.fee.numer() == 3 && *pool.fee.denom() == 1000
+}
+
 pub fn router(pool: &ConstantProductOrder) -> eth::ContractAddress {
     let address = pool
         .settlement_handling
@@ -140,14 +144,27 @@ where
         IUniswapLikeRouter::Instance::new(config.router.0.into_alloy(), eth.web3().alloy.clone());
     let settlement = eth.contracts().settlement().clone();
     let pool_fetcher = {
-        let factory = router.factory().call().await?;
-        let pair_provider = PairProvider {
-            factory: factory.into_legacy(),
-            init_code_digest: config.pool_code.into(),
+        let factory = router.factory().call().await?.into_legacy();
+        let pool_reader: Box<dyn PoolReading> = match &config.pools {
+            UniswapV2Pools::Derived(pool_code) => {
+                let pair_provider = PairProvider {
+                    factory,
+                    init_code_digest: (*pool_code).into(),
+                };
+                Box::new(reader(eth.web3().clone(), pair_provider))
+            }
+            UniswapV2Pools::Listed(path) => {
+                let pools = PoolList::load(path)?.validate(eth.web3(), factory).await?;
+                tracing::info!(?path, pools = pools.len(), "loaded Uniswap V2 pool list");
+                Box::new(ListedPoolReader {
+                    pools: Arc::new(pools),
+                    web3: eth.web3().clone(),
+                })
+            }
         };
 
         let pool_fetcher = PoolFetcher::new(
-            reader(eth.web3().clone(), pair_provider),
+            pool_reader,
             eth.web3().clone(),
             config.missing_pool_cache_time,
         );
@@ -18,7 +18,7 @@ use {
     shared::{
         http_solver::model::TokenAmount,
         interaction::Interaction,
-        maintenance::ServiceMaintenance,
+        maintenance::{Maintaining, ServiceMaintenance},
         sources::uniswap_v3::pool_fetching::UniswapV3PoolFetcher,
     },
     solver::{
@@ -27,7 +27,11 @@ use {
             ConcentratedLiquidity,
             uniswap_v3::{self, UniswapV3Liquidity, UniswapV3SettlementHandler},
         },
-        liquidity_collector::{BackgroundInitLiquiditySource, LiquidityCollecting},
+        liquidity_collector::{
+            BackgroundInitLiquiditySource,
+            LiquidityCollecting,
+            MaintainedLiquiditySource,
+        },
     },
     std::{
         collections::BTreeMap,
@@ -144,14 +148,23 @@ async fn init_liquidity(
         .context("failed to initialise UniswapV3 liquidity")?,
     );
 
-    let update_task = ServiceMaintenance::new(vec![pool_fetcher.clone()])
-        .run_maintenance_on_new_block(eth.current_block().clone());
-    tokio::task::spawn(update_task);
+    // Catch up with the chain head before the (re)initialized source gets
+    // swapped in, so it never serves liquidity that is missing recent events.
+    let maintenance = ServiceMaintenance::new(vec![pool_fetcher.clone()]);
+    maintenance
+        .run_maintenance()
+        .await
+        .context("failed to sync UniswapV3 liquidity")?;
+    let update_task =
+        tokio::task::spawn(maintenance.run_maintenance_on_new_block(eth.current_block().clone()));
 
-    Ok(UniswapV3Liquidity::new(
-        config.router.0.into_alloy(),
-        *eth.contracts().settlement().address(),
-        web3,
-        pool_fetcher,
+    Ok(MaintainedLiquiditySource::new(
+        UniswapV3Liquidity::new(
+            config.router.0.into_alloy(),
+            *eth.contracts().settlement().address(),
+            web3,
+            pool_fetcher,
+        ),
+        vec![update_task.abort_handle()],
     ))
 }
@@ -0,0 +1,163 @@
+use {
+    crate::{
+        boundary::{self, Result},
+        domain::{
+            eth,
+            liquidity::{
+                self,
+                uniswap::v4::{Fee, Pool},
+            },
+        },
+        infra::{self, blockchain::Ethereum},
+    },
+    anyhow::Context,
+    contracts::alloy::UniswapV4PoolManager,
+    ethrpc::{
+        alloy::conversions::{IntoAlloy, IntoLegacy},
+        block_stream::BlockRetrieving,
+    },
+    shared::{
+        http_solver::model::TokenAmount,
+        interaction::Interaction,
+        maintenance::{Maintaining, ServiceMaintenance},
+        sources::uniswap_v4::{self, PoolKey, pool_fetching::UniswapV4PoolFetcher},
+    },
+    solver::{
+        interactions::allowances::Allowances,
+        liquidity::{
+            UniswapV4Pool,
+            uniswap_v4::{UniswapV4Liquidity, UniswapV4SettlementHandler},
+        },
+        liquidity_collector::{
+            BackgroundInitLiquiditySource,
+            LiquidityCollecting,
+            MaintainedLiquiditySource,
+        },
+    },
+    std::sync::{Arc, Mutex},
+};
+
+pub fn to_domain(id: liquidity::Id, pool: UniswapV4Pool) -> Result<liquidity::Liquidity> {
+    let handler = pool
+        .settlement_handling
+        .as_any()
+        .downcast_ref::<UniswapV4SettlementHandler>()
+        .expect("downcast uniswap v4 settlement handler");
+
+    Ok(liquidity::Liquidity {
+        id,
+        gas: eth::Gas(uniswap_v4::Pool::POOL_SWAP_GAS_COST.into()),
+        kind: liquidity::Kind::UniswapV4(Pool {
+            pool_manager: handler.inner.pool_manager.into_legacy().into(),
+            router: handler.inner.router.into_legacy().into(),
+            permit2: handler.inner.permit2.into_legacy().into(),
+            tokens: liquidity::TokenPair::try_new(
+                pool.key.currency0.into(),
+                pool.key.currency1.into(),
+            )?,
+            fee: Fee(pool.key.fee),
+            tick_spacing: pool.key.tick_spacing,
+            hooks: pool.key.hooks.into(),
+        }),
+    })
+}
+
+/// Encodes the Permit2 approval of the router and the swap. The router sends
+/// the output tokens to its caller, i.e. the settlement contract.
+pub fn to_interactions(
+    pool: &liquidity::uniswap::v4::Pool,
+    input: &liquidity::MaxInput,
+    output: &liquidity::ExactOutput,
+    receiver: &eth::Address,
+) -> Result<Vec<eth::Interaction>> {
+    let (currency0, currency1) = pool.tokens.get();
+    let handler = UniswapV4SettlementHandler::new(
+        pool.pool_manager.0.into_alloy(),
+        pool.router.0.into_alloy(),
+        pool.permit2.0.into_alloy(),
+        Mutex::new(Allowances::empty(receiver.0)),
+        PoolKey {
+            currency0: currency0.into(),
+            currency1: currency1.into(),
+            fee: pool.fee.0,
+            tick_spacing: pool.tick_spacing,
+            hooks: pool.hooks.into(),
+        },
+    );
+
+    let (_, permit, swap) = handler.settle(
+        TokenAmount::new(input.0.token.into(), input.0.amount),
+        TokenAmount::new(output.0.token.into(), output.0.amount),
+    )?;
+
+    Ok([permit.encode(), swap.encode()]
+        .into_iter()
+        .map(|encoded| eth::Interaction {
+            target: eth::Address(encoded.0.into_legacy()),
+            value: eth::Ether(encoded.1.into_legacy()),
+            call_data: crate::util::Bytes(encoded.2.0.to_vec()),
+        })
+        .collect())
+}
+
+pub fn collector(
+    eth: &Ethereum,
+    block_retriever: Arc<dyn BlockRetrieving>,
+    config: &infra::liquidity::config::UniswapV4,
+) -> Box<dyn LiquidityCollecting> {
+    let eth = Arc::new(eth.with_metric_label("uniswapV4".into()));
+    let config = Arc::new(Clone::clone(config));
+    let reinit_interval = config.reinit_interval;
+    let init = move || {
+        let eth = eth.clone();
+        let block_retriever = block_retriever.clone();
+        let config = config.clone();
+        async move { init_liquidity(&eth, block_retriever.clone(), &config).await }
+    };
+    const TEN_MINUTES: std::time::Duration = std::time::Duration::from_secs(10 * 60);
+    Box::new(BackgroundInitLiquiditySource::new(
+        "uniswap-v4",
+        init,
+        TEN_MINUTES,
+        reinit_interval,
+    )) as Box<_>
+}
+
+async fn init_liquidity(
+    eth: &Ethereum,
+    block_retriever: Arc<dyn BlockRetrieving>,
+    config: &infra::liquidity::config::UniswapV4,
+) -> anyhow::Result<impl LiquidityCollecting + use<>> {
+    let web3 = eth.web3().clone();
+
+    let pool_manager =
+        UniswapV4PoolManager::Instance::new(config.pool_manager.0.into_alloy(), web3.alloy.clone());
+    let pool_fetcher = Arc::new(UniswapV4PoolFetcher::new(
+        &pool_manager,
+        config.deployment_block,
+        block_retriever,
+        config.allowed_hooks.clone(),
+    ));
+
+    // Index the pools up to the chain head before the (re)initialized source
+    // gets swapped in.
+    let maintenance = ServiceMaintenance::new(vec![pool_fetcher.clone()]);
+    maintenance
+        .run_maintenance()
+        .await
+        .context("failed to sync UniswapV4 liquidity")?;
+    let update_task =
+        tokio::task::spawn(maintenance.run_maintenance_on_new_block(eth.current_block().clone()));
+
+    Ok(MaintainedLiquiditySource::new(
+        UniswapV4Liquidity::new(
+            config.pool_manager.0.into_alloy(),
+            config.router.0.into_alloy(),
+            config.permit2.0.into_alloy(),
+            *eth.contracts().settlement().address(),
+            web3,
+            pool_fetcher,
+        ),
+        vec![update_task.abort_handle()],
+    ))
+}
@@ -42,6 +42,7 @@ pub mod auction;
 pub mod bad_tokens;
 pub mod order;
 mod pre_processing;
+pub mod scheduler;
 pub mod solution;
 pub mod sorting;
 
@@ -71,6 +72,8 @@ pub struct Competition {
     fetcher: Arc<pre_processing::DataAggregator>,
     settle_queue: mpsc::Sender<SettleRequest>,
     order_sorting_strategies: Vec<Arc<dyn sorting::SortingStrategy>>,
+    /// Bounds how many auctions get solved concurrently, if configured.
+    scheduler: Option<scheduler::Scheduler>,
 }
 
 impl Competition {
@@ -87,6 +90,9 @@ impl Competition {
         order_sorting_strategies: Vec<Arc<dyn sorting::SortingStrategy>>,
     ) -> Arc<Self> {
         let (settle_sender, settle_receiver) = mpsc::channel(solver.settle_queue_size());
+        let scheduler = solver
+            .max_concurrent_auctions()
+            .map(scheduler::Scheduler::new);
 
         let competition = Arc::new(Self {
             solver,
@@ -100,6 +106,7 @@ impl Competition {
             bad_tokens,
             fetcher,
             order_sorting_strategies,
+            scheduler,
         });
 
         let competition_clone = Arc::clone(&competition);
@@ -188,6 +195,24 @@ impl Competition {
 
         let auction = &auction;
 
+        // Wait for our turn if more auctions are being solved than allowed.
+        // The permit is held until the auction is solved.
+        let _permit = match &self.scheduler {
+            Some(scheduler) => {
+                let remaining = auction
+                    .deadline(self.solver.timeouts())
+                    .solvers()
+                    .remaining()?;
+                let priority = scheduler::expected_surplus(auction);
+                Some(
+                    tokio::time::timeout(remaining, scheduler.acquire(priority))
+                        .await
+                        .map_err(|_| DeadlineExceeded)?,
+                )
+            }
+            None => None,
+        };
+
         // Fetch the solutions from the solver.
         let solutions = self
             .solver
@@ -0,0 +1,183 @@
+//! Prioritization of auctions competing for the solver engine.
+//!
+//! In regular operation the driver solves one auction at a time, but when
+//! auctions get replayed or solved in batches they can arrive faster than the
+//! solver engine solves them. Solving all of them at once spreads the compute
+//! thin, so the scheduler bounds how many auctions get solved concurrently
+//! and lets the queued auction with the highest expected surplus go next.
+
+use {
+    super::Auction,
+    crate::domain::eth,
+    std::{
+        cmp::Reverse,
+        collections::BinaryHeap,
+        num::NonZeroUsize,
+        sync::{Arc, Mutex},
+    },
+    tokio::sync::oneshot,
+};
+
+/// Bounds the number of concurrently solved auctions.
+#[derive(Debug)]
+pub struct Scheduler(Arc<Mutex<State>>);
+
+#[derive(Debug)]
+struct State {
+    limit: usize,
+    running: usize,
+    queue: BinaryHeap<Queued>,
+    /// Increases with every queued auction, so that auctions with the same
+    /// expected surplus get solved in the order they arrived.
+    sequence: u64,
+}
+
+#[derive(Debug)]
+struct Queued {
+    priority: (eth::U256, Reverse<u64>),
+    sender: oneshot::Sender<Permit>,
+}
+
+impl PartialEq for Queued {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority
+    }
+}
+
+impl Eq for Queued {}
+
+impl PartialOrd for Queued {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Queued {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.priority.cmp(&other.priority)
+    }
+}
+
+impl Scheduler {
+    pub fn new(limit: NonZeroUsize) -> Self {
+        Self(Arc::new(Mutex::new(State {
+            limit: limit.get(),
+            running: 0,
+            queue: Default::default(),
+            sequence: 0,
+        })))
+    }
+
+    /// Waits until the auction can be solved. Auctions with a higher
+    /// `priority` get to be solved first. The auction may be solved until the
+    /// returned permit is dropped.
+    pub async fn acquire(&self, priority: eth::U256) -> Permit {
+        let receiver = {
+            let mut state = self.0.lock().unwrap();
+            if state.running < state.limit {
+                state.running += 1;
+                return Permit(self.0.clone());
+            }
+            let (sender, receiver) = oneshot::channel();
+            state.sequence += 1;
+            let sequence = state.sequence;
+            state.queue.push(Queued {
+                priority: (priority, Reverse(sequence)),
+                sender,
+            });
+            tracing::debug!(queued = state.queue.len(), "auction queued for solving");
+            receiver
+        };
+        // Queued senders are only ever dropped by handing over a permit.
+        receiver.await.expect("queued auctions get a permit")
+    }
+}
+
+/// Allows solving an auction. Dropping the permit hands it over to the
+/// queued auction with the highest priority.
+#[derive(Debug)]
+pub struct Permit(Arc<Mutex<State>>);
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        let next = {
+            let mut state = self.0.lock().unwrap();
+            let next = state.queue.pop();
+            if next.is_none() {
+                state.running -= 1;
+            }
+            next
+        };
+        if let Some(queued) = next {
+            // If the queued auction is no longer waiting, e.g. because its
+            // request was cancelled, the returned permit gets dropped as well
+            // and is handed over to the next auction.
+            let _ = queued.sender.send(Permit(self.0.clone()));
+        }
+    }
+}
+
+/// Estimates the surplus of an auction in wei as the sum of how much more
+/// the orders sell than they buy at the native prices. This is the size of
+/// the orders times the spread between their limit and market prices, so
+/// auctions with large orders with generous limit prices come first.
+pub fn expected_surplus(auction: &Auction) -> eth::U256 {
+    let prices = auction.native_prices();
+    let value = |asset: &eth::Asset| {
+        let price = prices.get(&asset.token)?;
+        let value = asset.amount.0.full_mul(price.0.0) / eth::U512::exp10(18);
+        eth::U256::try_from(value).ok()
+    };
+    auction
+        .orders()
+        .iter()
+        .filter_map(|order| Some(value(&order.sell)?.saturating_sub(value(&order.buy)?)))
+        .fold(eth::U256::zero(), eth::U256::saturating_add)
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::time::Duration};
+
+    #[tokio::test]
+    async fn queued_auctions_get_solved_by_priority() {
+        let scheduler = Arc::new(Scheduler::new(NonZeroUsize::new(1).unwrap()));
+        let running = scheduler.acquire(0.into()).await;
+
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        for priority in [1, 3, 2] {
+            let scheduler = scheduler.clone();
+            let sender = sender.clone();
+            tokio::spawn(async move {
+                let _permit = scheduler.acquire(priority.into()).await;
+                sender.send(priority).unwrap();
+            });
+        }
+        // Wait for all auctions to be queued.
+        while scheduler.0.lock().unwrap().queue.len() < 3 {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+
+        drop(running);
+        let mut solved = Vec::new();
+        for _ in 0..3 {
+            solved.push(receiver.recv().await.unwrap());
+        }
+        assert_eq!(solved, [3, 2, 1]);
+        assert_eq!(scheduler.0.lock().unwrap().running, 0);
+    }
+
+    #[tokio::test]
+    async fn cancelled_auctions_pass_their_turn_on() {
+        let scheduler = Scheduler::new(NonZeroUsize::new(1).unwrap());
+        let running = scheduler.acquire(0.into()).await;
+
+        // The auction stops waiting after being queued.
+        let cancelled = tokio::time::timeout(Duration::from_millis(1), scheduler.acquire(1.into()));
+        assert!(cancelled.await.is_err());
+
+        drop(running);
+        let _permit = scheduler.acquire(0.into()).await;
+        assert_eq!(scheduler.0.lock().unwrap().running, 1);
+    }
+}
@@ -193,18 +193,22 @@ pub fn tx(
             continue;
         }
 
-        interactions.push(match interaction {
-            competition::solution::Interaction::Custom(interaction) => eth::Interaction {
-                value: interaction.value,
-                target: interaction.target.into(),
-                call_data: interaction.call_data.clone(),
-            },
-            competition::solution::Interaction::Liquidity(liquidity) => liquidity_interaction(
-                liquidity,
-                &slippage,
-                contracts.settlement().address().into_legacy(),
-            )?,
-        })
+        match interaction {
+            competition::solution::Interaction::Custom(interaction) => {
+                interactions.push(eth::Interaction {
+                    value: interaction.value,
+                    target: interaction.target.into(),
+                    call_data: interaction.call_data.clone(),
+                })
+            }
+            competition::solution::Interaction::Liquidity(liquidity) => {
+                interactions.extend(liquidity_interaction(
+                    liquidity,
+                    &slippage,
+                    contracts.settlement().address().into_legacy(),
+                )?)
+            }
+        }
     }
 
     // Encode WETH unwrap
@@ -346,23 +350,46 @@ fn encode_wrapper_data(wrappers: &[super::WrapperCall]) -> Vec<u8> {
     wrapper_data
 }
 
+/// Encodes the interactions executing a swap through liquidity. Most liquidity
+/// needs a single interaction, Uniswap V4 pools need a Permit2 approval of the
+/// router before the swap.
 pub fn liquidity_interaction(
     liquidity: &Liquidity,
     slippage: &slippage::Parameters,
     settlement_contract: H160,
-) -> Result<eth::Interaction, Error> {
+) -> Result<Vec<eth::Interaction>, Error> {
     let (input, output) = slippage.apply_to(&slippage::Interaction {
         input: liquidity.input,
         output: liquidity.output,
     })?;
 
-    match liquidity.liquidity.kind.clone() {
+    swap_interactions(&liquidity.liquidity, input, output, settlement_contract)
+        .ok_or_else(|| Error::InvalidInteractionExecution(Box::new(liquidity.clone())))
+}
+
+/// Encodes the interactions swapping at most the input for exactly the output
+/// through the liquidity, with the settlement contract receiving the output.
+/// Returns `None` if the swap is invalid for the liquidity.
+pub fn swap_interactions(
+    liquidity: &liquidity::Liquidity,
+    input: liquidity::MaxInput,
+    output: liquidity::ExactOutput,
+    settlement_contract: H160,
+) -> Option<Vec<eth::Interaction>> {
+    match liquidity.kind.clone() {
+        liquidity::Kind::UniswapV4(pool) => {
+            return pool.swap(&input, &output, &settlement_contract.into()).ok();
+        }
         liquidity::Kind::UniswapV2(pool) => {
             pool.swap(&input, &output, &settlement_contract.into()).ok()
         }
         liquidity::Kind::UniswapV3(pool) => {
             pool.swap(&input, &output, &settlement_contract.into()).ok()
         }
+        liquidity::Kind::MaverickV2(pool) => {
+            pool.swap(&input, &output, &settlement_contract.into()).ok()
+        }
+        liquidity::Kind::Curve(pool) => pool.swap(&input, &output).ok(),
         liquidity::Kind::BalancerV2Stable(pool) => {
             pool.swap(&input, &output, &settlement_contract.into()).ok()
         }
@@ -427,9 +454,7 @@ pub fn liquidity_interaction(
             }
         }
     }
-    .ok_or(Error::InvalidInteractionExecution(Box::new(
-        liquidity.clone(),
-    )))
+    .map(|interaction| vec![interaction])
 }
 
 pub fn approve(allowance: &Allowance) -> eth::Interaction {
@@ -616,7 +641,7 @@ mod test {
         // to build full Solution here.
 
         // Encode to interactions list
-        let interaction = liquidity_interaction(
+        let interactions = liquidity_interaction(
             &InteractionLiquidity {
                 liquidity: liquidity.clone(),
                 input: eth::Asset {
@@ -656,6 +681,7 @@ mod test {
         assert_eq!(allowances.len(), 1);
         assert_eq!(allowances[0].0.amount, alloy::primitives::U256::from(100));
         // Ensure interaction is a mint (selector 0x94bf804d)
-        assert_eq!(&interaction.call_data.0[0..4], &hex!("94bf804d"));
+        assert_eq!(interactions.len(), 1);
+        assert_eq!(&interactions[0].call_data.0[0..4], &hex!("94bf804d"));
     }
 }
@@ -42,6 +42,11 @@ impl Interaction {
                 let address = match &interaction.liquidity.kind {
                     liquidity::Kind::UniswapV2(pool) => pool.router.into(),
                     liquidity::Kind::UniswapV3(pool) => pool.router.into(),
+                    liquidity::Kind::MaverickV2(pool) => pool.router.into(),
+                    // The pools pull their input tokens themselves.
+                    liquidity::Kind::Curve(pool) => pool.address.into(),
+                    // The router pulls the input tokens through Permit2.
+                    liquidity::Kind::UniswapV4(pool) => pool.permit2.into(),
                     liquidity::Kind::BalancerV2Stable(pool) => pool.vault.into(),
                     liquidity::Kind::BalancerV3Stable(pool) => pool.batch_router.into(),
                     liquidity::Kind::BalancerV3StableSurge(pool) => pool.batch_router.into(),
@@ -282,18 +282,19 @@ impl Solution {
         let settlement_contract = &eth.contracts().settlement();
         let allowances =
             try_join_all(self.allowances(internalization).map(|required| async move {
-                eth.erc20(required.0.token)
+                let token = eth.erc20(required.0.token);
+                token
                     .allowance(
                         settlement_contract.address().into_legacy().into(),
                         required.0.spender,
                     )
                     .await
-                    .map(|existing| (required, existing))
+                    .map(|existing| (required, existing, token.quirks()))
             }))
             .await?;
         let approvals = allowances
             .into_iter()
-            .filter_map(|(required, existing)| required.approval(&existing));
+            .flat_map(|(required, existing, quirks)| required.approvals(&existing, quirks));
         Ok(approvals)
     }
 
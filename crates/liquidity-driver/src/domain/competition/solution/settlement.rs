@@ -1,6 +1,7 @@
 use {
     super::{Error, Solution, encoding, trade::ClearingPrices},
     crate::{
+        boundary,
         domain::{
             competition::{
                 self,
@@ -169,6 +170,9 @@ impl Settlement {
             simulator,
         )
         .await?;
+        if simulator.simulates_gas() {
+            Self::observe_liquidity_gas(&solution, gas);
+        }
         let price = eth.gas_price(None).await?;
         let gas = Gas::new(gas, eth.block_gas_limit(), price)?;
 
@@ -233,6 +237,31 @@ impl Settlement {
         Ok((access_list, gas?))
     }
 
+    /// Refines the gas cost model of the liquidity with the simulated gas of a
+    /// settlement swapping through a single piece of liquidity. The difference
+    /// between the simulated gas and the solver's estimate is attributed to
+    /// the swap, on top of the gas estimated for the liquidity.
+    fn observe_liquidity_gas(solution: &Solution, simulated: eth::Gas) {
+        let Some(estimate) = solution.gas() else {
+            return;
+        };
+        let liquidity = solution
+            .interactions()
+            .iter()
+            .filter_map(|interaction| match interaction {
+                Interaction::Liquidity(interaction) => Some(&interaction.liquidity),
+                Interaction::Custom(_) => None,
+            })
+            .collect::<Vec<_>>();
+        let [liquidity] = liquidity[..] else {
+            return;
+        };
+        let Some(gas) = (simulated.0 + liquidity.gas.0).checked_sub(estimate.0) else {
+            return;
+        };
+        boundary::liquidity::balancer::v3::observe_gas(liquidity, eth::Gas(gas));
+    }
+
     /// The calldata for this settlement.
     pub fn transaction(&self, internalization: Internalization) -> &eth::Tx {
         match internalization {
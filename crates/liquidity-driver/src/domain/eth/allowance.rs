@@ -36,6 +36,34 @@ impl Required {
             Some(Approval(self.0))
         }
     }
+
+    /// Returns the approvals needed for this allowance, taking the quirks of
+    /// the token into account. Tokens guarding against the approval race
+    /// only allow approving a non-zero amount if the allowance was 0 to begin
+    /// with, so a non-zero existing allowance gets reset first.
+    pub fn approvals(&self, existing: &Existing, quirks: Quirks) -> Vec<Approval> {
+        match self.approval(existing) {
+            Some(approval) if quirks.approval_race && !existing.0.amount.is_zero() => {
+                vec![approval.revoke(), approval]
+            }
+            Some(approval) => vec![approval],
+            None => vec![],
+        }
+    }
+}
+
+/// Deviations of a token from EIP-20 that affect how interactions with it
+/// need to be encoded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Quirks {
+    /// The token reverts when changing a non-zero allowance to another
+    /// non-zero amount, e.g. Tether USD.
+    pub approval_race: bool,
+    /// The `transfer`, `transferFrom` and `approve` methods of the token don't
+    /// return a boolean, e.g. Tether USD. Settlement interactions don't decode
+    /// return data, but contracts calling the token through the standard
+    /// interface revert.
+    pub no_return_value: bool,
 }
 
 /// An approval which needs to be made with an approve() call, see
@@ -61,3 +89,43 @@ impl Approval {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::domain::eth};
+
+    fn allowance(amount: u64) -> Allowance {
+        Allowance {
+            token: eth::H160([1; 20]).into(),
+            spender: eth::H160([2; 20]).into(),
+            amount: U256::from(amount),
+        }
+    }
+
+    #[test]
+    fn resets_allowances_of_approval_race_tokens() {
+        let quirks = Quirks {
+            approval_race: true,
+            ..Default::default()
+        };
+        let amounts = |existing| {
+            Required(allowance(100))
+                .approvals(&Existing(allowance(existing)), quirks)
+                .into_iter()
+                .map(|approval| approval.0.amount)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(amounts(0), [U256::from(100)]);
+        assert_eq!(amounts(50), [U256::ZERO, U256::from(100)]);
+        assert!(amounts(100).is_empty());
+    }
+
+    #[test]
+    fn approves_standard_tokens_directly() {
+        let approvals =
+            Required(allowance(100)).approvals(&Existing(allowance(50)), Quirks::default());
+        assert_eq!(approvals.len(), 1);
+        assert_eq!(approvals[0].0.amount, U256::from(100));
+    }
+}
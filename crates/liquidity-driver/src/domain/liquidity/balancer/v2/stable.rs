@@ -21,6 +21,10 @@ pub struct Pool {
     pub reserves: Reserves,
     pub amplification_parameter: AmplificationParameter,
     pub fee: Fee,
+    /// The BPT supply of composable stable pools, net of the BPT held by the
+    /// vault. Composable stable pools hold their own BPT, which can be swapped
+    /// like any of the pool's tokens.
+    pub actual_supply: Option<eth::U256>,
 }
 
 impl Pool {
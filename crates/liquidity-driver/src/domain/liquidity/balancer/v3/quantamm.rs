@@ -21,6 +21,7 @@ pub struct Pool {
     pub version: Version,
     // QuantAMM-specific parameters for weight interpolation
     pub max_trade_size_ratio: ScalingFactor,
+    pub strategy: Strategy,
     pub first_four_weights_and_multipliers: Vec<ethcontract::I256>,
     pub second_four_weights_and_multipliers: Vec<ethcontract::I256>,
     pub last_update_time: u64,
@@ -102,3 +103,15 @@ pub struct Reserve {
 pub enum Version {
     V1,
 }
+
+/// Strategy metadata of a QuantAMM pool, read from the pool details when it
+/// gets indexed. It does not affect the swap math.
+#[derive(Clone, Debug, Default)]
+pub struct Strategy {
+    /// The weight update rule, if the pool describes it.
+    pub rule: Option<String>,
+    /// The oracles feeding the update rule.
+    pub oracles: Vec<String>,
+    /// The minimum number of seconds between weight updates.
+    pub update_interval: u64,
+}
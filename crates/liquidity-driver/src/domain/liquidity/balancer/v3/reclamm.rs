@@ -24,6 +24,7 @@ pub struct Pool {
     pub end_fourth_root_price_ratio: ScalingFactor,
     pub price_ratio_update_start_time: u64,
     pub price_ratio_update_end_time: u64,
+    pub current_timestamp: u64,
 }
 
 impl Pool {
@@ -94,5 +95,6 @@ pub struct Reserve {
 
 #[derive(Clone, Copy, Debug)]
 pub enum Version {
+    V1,
     V2,
 }
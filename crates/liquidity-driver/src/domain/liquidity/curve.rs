@@ -0,0 +1,70 @@
+use {
+    crate::{
+        boundary,
+        domain::{
+            eth,
+            liquidity::{self, InvalidSwap},
+        },
+    },
+    derive_more::Debug,
+};
+
+/// A Curve StableSwap pool [^1], including metapools pairing tokens with the
+/// LP token of a base pool.
+///
+/// The pool swaps exact input amounts, so swapping through it spends all of
+/// the maximum input and receives at least the output.
+///
+/// [^1]: <https://docs.curve.fi/stableswap-exchange/stableswap/pools/plain_pools>
+#[derive(Clone, Debug)]
+pub struct Pool {
+    pub address: eth::ContractAddress,
+    /// The coins of the pool in the order of their on-chain indices.
+    pub coins: Vec<Coin>,
+    /// The amplification parameter multiplied by its precision of 100.
+    pub amplification: eth::U256,
+    /// The swap fee relative to `10^10`.
+    pub fee: eth::U256,
+    /// The base pool of metapools.
+    pub base_pool: Option<eth::ContractAddress>,
+}
+
+/// A coin of a Curve pool.
+#[derive(Clone, Copy, Debug)]
+pub struct Coin {
+    pub token: eth::TokenAddress,
+    pub balance: eth::U256,
+    /// The rate normalizing the balance to 18 decimals, scaled by the exchange
+    /// rate of coins that accrue value.
+    pub rate: eth::U256,
+}
+
+impl Pool {
+    /// The tokens traded by the pool. Native ETH can't be traded by the
+    /// settlement contract, so it is skipped.
+    pub fn tokens(&self) -> impl Iterator<Item = eth::TokenAddress> + '_ {
+        self.coins
+            .iter()
+            .map(|coin| coin.token)
+            .filter(|token| *token != eth::ETH_TOKEN)
+    }
+
+    /// Encodes a pool swap as an interaction. Returns `Err` if the swap
+    /// parameters are invalid for the pool, specifically if the input and
+    /// output tokens aren't distinct coins of the pool.
+    pub fn swap(
+        &self,
+        input: &liquidity::MaxInput,
+        output: &liquidity::ExactOutput,
+    ) -> Result<eth::Interaction, InvalidSwap> {
+        let tokens_match = input.0.token != output.0.token
+            && self.tokens().any(|token| token == input.0.token)
+            && self.tokens().any(|token| token == output.0.token);
+
+        if !tokens_match {
+            return Err(InvalidSwap);
+        }
+
+        boundary::liquidity::curve::to_interaction(self, input, output).map_err(|_| InvalidSwap)
+    }
+}
@@ -0,0 +1,70 @@
+use {
+    crate::{
+        boundary,
+        domain::{
+            eth,
+            liquidity::{self, InvalidSwap},
+        },
+    },
+    derive_more::Debug,
+    std::collections::BTreeMap,
+};
+
+/// A Maverick V2 pool, whose liquidity sits in bins aggregated per tick.
+///
+/// The tokens of the pair are the tokens A and B of the pool in that order,
+/// as Maverick V2 pools require token A to be the lower address.
+///
+/// [^1]: <https://docs.mav.xyz/technical-reference/maverick-v2>
+#[derive(Clone, Debug)]
+pub struct Pool {
+    /// The router executing the swaps.
+    pub router: eth::ContractAddress,
+    pub address: eth::ContractAddress,
+    pub tokens: liquidity::TokenPair,
+    pub tick_spacing: u32,
+    pub active_tick: Tick,
+    /// The reserves of the ticks around the active tick.
+    #[debug(ignore)]
+    pub ticks: BTreeMap<Tick, TickReserves>,
+    pub fee_a_in: Fee,
+    pub fee_b_in: Fee,
+}
+
+/// An index to a tick of a Maverick V2 pool, the price of its lower bound
+/// being `1.0001^(tick * tick_spacing)` token A per token B.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Tick(pub i32);
+
+/// The reserves of the bins at a tick.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct TickReserves {
+    pub a: eth::U256,
+    pub b: eth::U256,
+}
+
+/// A swap fee as an 18 decimal fixed point fraction of the input amount.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Fee(pub eth::U256);
+
+impl Pool {
+    /// Encodes a pool swap as an interaction. Returns `Err` if the swap
+    /// parameters are invalid for the pool, specifically if the input and
+    /// output tokens don't correspond to the pool's token pair.
+    pub fn swap(
+        &self,
+        input: &liquidity::MaxInput,
+        output: &liquidity::ExactOutput,
+        receiver: &eth::Address,
+    ) -> Result<eth::Interaction, InvalidSwap> {
+        let tokens_match = (input.0.token == self.tokens.0 && output.0.token == self.tokens.1)
+            || (input.0.token == self.tokens.1 && output.0.token == self.tokens.0);
+
+        if !tokens_match {
+            return Err(InvalidSwap);
+        }
+
+        boundary::liquidity::maverick_v2::to_interaction(self, input, output, receiver)
+            .map_err(|_| InvalidSwap)
+    }
+}
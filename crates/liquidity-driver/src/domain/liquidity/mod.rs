@@ -4,11 +4,13 @@
 use {
     crate::domain::eth,
     derive_more::{From, Into},
-    std::cmp::Ordering,
+    std::{cmp::Ordering, collections::HashMap},
 };
 
 pub mod balancer;
+pub mod curve;
 pub mod erc4626;
+pub mod maverick_v2;
 pub mod swapr;
 pub mod uniswap;
 pub mod zeroex;
@@ -22,6 +24,107 @@ pub struct Liquidity {
     pub kind: Kind,
 }
 
+impl Liquidity {
+    /// The address of the on-chain pool backing this liquidity. Returns `None`
+    /// for liquidity that isn't a pool, such as limit orders or ERC4626
+    /// wrapping edges.
+    pub fn address(&self) -> Option<eth::H160> {
+        match &self.kind {
+            Kind::UniswapV2(pool) => Some(pool.address.0),
+            Kind::UniswapV3(pool) => Some(pool.address.0),
+            Kind::BalancerV2Stable(pool) => Some(pool.id.address().0),
+            Kind::BalancerV3Stable(pool) => Some(pool.id.0),
+            Kind::BalancerV3StableSurge(pool) => Some(pool.id.0),
+            Kind::BalancerV2Weighted(pool) => Some(pool.id.address().0),
+            Kind::BalancerV3Weighted(pool) => Some(pool.id.0),
+            Kind::BalancerV2GyroE(pool) => Some(pool.id.address().0),
+            Kind::BalancerV2Gyro2CLP(pool) => Some(pool.id.address().0),
+            Kind::BalancerV2Gyro3CLP(pool) => Some(pool.id.address().0),
+            Kind::BalancerV3GyroE(pool) => Some(pool.id.0),
+            Kind::BalancerV3Gyro2CLP(pool) => Some(pool.id.0),
+            Kind::BalancerV3ReClamm(pool) => Some(pool.id.0),
+            Kind::BalancerV3QuantAmm(pool) => Some(pool.id.0),
+            Kind::Swapr(pool) => Some(pool.base.address.0),
+            Kind::MaverickV2(pool) => Some(pool.address.0),
+            Kind::Curve(pool) => Some(pool.address.0),
+            // Uniswap V4 pools all live in the PoolManager.
+            Kind::UniswapV4(_) | Kind::ZeroEx(_) | Kind::Erc4626(_) => None,
+        }
+    }
+
+    /// The tokens that can be swapped through this liquidity.
+    pub fn tokens(&self) -> Vec<eth::TokenAddress> {
+        match &self.kind {
+            Kind::UniswapV2(pool) => pool.reserves.iter().map(|asset| asset.token).collect(),
+            Kind::Swapr(pool) => pool.base.reserves.iter().map(|asset| asset.token).collect(),
+            Kind::UniswapV3(uniswap::v3::Pool { tokens, .. })
+            | Kind::UniswapV4(uniswap::v4::Pool { tokens, .. })
+            | Kind::MaverickV2(maverick_v2::Pool { tokens, .. }) => {
+                let (a, b) = tokens.get();
+                vec![a, b]
+            }
+            Kind::BalancerV2Stable(pool) => pool.reserves.tokens().collect(),
+            Kind::BalancerV3Stable(pool) => pool.reserves.tokens().collect(),
+            Kind::BalancerV3StableSurge(pool) => pool.reserves.tokens().collect(),
+            Kind::BalancerV2Weighted(pool) => pool.reserves.tokens().collect(),
+            Kind::BalancerV3Weighted(pool) => pool.reserves.tokens().collect(),
+            Kind::BalancerV2GyroE(pool) => pool.reserves.tokens().collect(),
+            Kind::BalancerV2Gyro2CLP(pool) => pool.reserves.tokens().collect(),
+            Kind::BalancerV2Gyro3CLP(pool) => pool.reserves.tokens().collect(),
+            Kind::BalancerV3GyroE(pool) => pool.reserves.tokens().collect(),
+            Kind::BalancerV3Gyro2CLP(pool) => pool.reserves.tokens().collect(),
+            Kind::BalancerV3ReClamm(pool) => pool.reserves.tokens().collect(),
+            Kind::BalancerV3QuantAmm(pool) => pool.reserves.tokens().collect(),
+            Kind::Curve(pool) => pool.tokens().collect(),
+            Kind::ZeroEx(limit_order) => vec![
+                limit_order.order.taker_token.into(),
+                limit_order.order.maker_token.into(),
+            ],
+            Kind::Erc4626(edge) => vec![edge.tokens.0, edge.tokens.1],
+        }
+    }
+
+    /// The timestamp of the on-chain state this liquidity was built from, for
+    /// the kinds of liquidity that track it.
+    fn state_timestamp(&self) -> Option<u64> {
+        match &self.kind {
+            Kind::BalancerV3ReClamm(pool) => Some(pool.current_timestamp),
+            Kind::BalancerV3QuantAmm(pool) => Some(pool.current_timestamp),
+            _ => None,
+        }
+    }
+}
+
+/// Collapses liquidity that refers to the same pool, i.e. has the same kind
+/// and address. This happens when multiple collectors index the same pools.
+///
+/// When duplicates carry state timestamps, the freshest one is kept; otherwise
+/// the first occurrence wins. The relative order of the remaining liquidity is
+/// preserved.
+pub fn deduplicate(liquidity: Vec<Liquidity>) -> Vec<Liquidity> {
+    let mut deduplicated = Vec::<Liquidity>::with_capacity(liquidity.len());
+    let mut seen = HashMap::<(&'static str, eth::H160), usize>::new();
+    for item in liquidity {
+        let Some(address) = item.address() else {
+            deduplicated.push(item);
+            continue;
+        };
+        let key = (<&'static str>::from(&item.kind), address);
+        match seen.get(&key) {
+            Some(&index) => {
+                if item.state_timestamp() > deduplicated[index].state_timestamp() {
+                    deduplicated[index] = item;
+                }
+            }
+            None => {
+                seen.insert(key, deduplicated.len());
+                deduplicated.push(item);
+            }
+        }
+    }
+    deduplicated
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, From, Into)]
 pub struct Id(pub usize);
 
@@ -48,6 +151,7 @@ pub struct ExactOutput(pub eth::Asset);
 pub enum Kind {
     UniswapV2(uniswap::v2::Pool),
     UniswapV3(uniswap::v3::Pool),
+    UniswapV4(uniswap::v4::Pool),
     BalancerV2Stable(balancer::v2::stable::Pool),
     BalancerV3Stable(balancer::v3::stable::Pool),
     BalancerV3StableSurge(balancer::v3::stable_surge::Pool),
@@ -61,6 +165,8 @@ pub enum Kind {
     BalancerV3ReClamm(balancer::v3::reclamm::Pool),
     BalancerV3QuantAmm(balancer::v3::quantamm::Pool),
     Swapr(swapr::Pool),
+    MaverickV2(maverick_v2::Pool),
+    Curve(curve::Pool),
     ZeroEx(zeroex::LimitOrder),
     Erc4626(erc4626::Edge),
 }
@@ -70,6 +176,7 @@ impl From<&Kind> for &'static str {
         match *val {
             Kind::UniswapV2(_) => "UniswapV2",
             Kind::UniswapV3(_) => "UniswapV3",
+            Kind::UniswapV4(_) => "UniswapV4",
             Kind::BalancerV2Stable(_) => "BalancerV2Stable",
             Kind::BalancerV3Stable(_) => "BalancerV3Stable",
             Kind::BalancerV3StableSurge(_) => "BalancerV3StableSurge",
@@ -83,6 +190,8 @@ impl From<&Kind> for &'static str {
             Kind::BalancerV3ReClamm(_) => "BalancerV3ReClamm",
             Kind::BalancerV3QuantAmm(_) => "BalancerV3QuantAmm",
             Kind::Swapr(_) => "Swapr",
+            Kind::MaverickV2(_) => "MaverickV2",
+            Kind::Curve(_) => "Curve",
             Kind::ZeroEx(_) => "ZeroExLimitOrder",
             Kind::Erc4626(_) => "Erc4626",
         }
@@ -117,3 +226,39 @@ pub struct InvalidTokenPair;
 #[derive(Debug, thiserror::Error)]
 #[error("swap parameters do not match pool")]
 pub struct InvalidSwap;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uniswap_v2(id: usize, address: u64, reserve: u128) -> Liquidity {
+        let asset = |token: u64, amount: u128| eth::Asset {
+            token: eth::H160::from_low_u64_be(token).into(),
+            amount: amount.into(),
+        };
+        Liquidity {
+            id: Id(id),
+            gas: 0.into(),
+            kind: Kind::UniswapV2(uniswap::v2::Pool {
+                address: eth::H160::from_low_u64_be(address).into(),
+                router: eth::H160::from_low_u64_be(0xdead).into(),
+                reserves: uniswap::v2::Reserves::try_new(asset(1, reserve), asset(2, reserve))
+                    .unwrap(),
+            }),
+        }
+    }
+
+    #[test]
+    fn deduplicates_by_kind_and_address() {
+        let liquidity = deduplicate(vec![
+            uniswap_v2(0, 1, 100),
+            uniswap_v2(1, 2, 100),
+            uniswap_v2(2, 1, 200),
+        ]);
+
+        assert_eq!(
+            liquidity.iter().map(|l| l.id.0).collect::<Vec<_>>(),
+            vec![0, 1]
+        );
+    }
+}
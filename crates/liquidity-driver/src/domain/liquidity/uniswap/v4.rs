@@ -0,0 +1,54 @@
+use crate::{
+    boundary,
+    domain::{
+        eth,
+        liquidity::{self, InvalidSwap},
+    },
+};
+
+/// A Uniswap V4 pool of the PoolManager singleton.
+///
+/// The state of the pool isn't tracked, solvers quote the pool on-chain
+/// instead, which also accounts for the effects of its hooks.
+///
+/// [^1]: <https://docs.uniswap.org/contracts/v4/overview>
+#[derive(Clone, Debug)]
+pub struct Pool {
+    /// The PoolManager holding the pool.
+    pub pool_manager: eth::ContractAddress,
+    /// The UniversalRouter executing the swaps.
+    pub router: eth::ContractAddress,
+    /// The Permit2 contract through which the router pulls the input tokens.
+    pub permit2: eth::ContractAddress,
+    pub tokens: liquidity::TokenPair,
+    pub fee: Fee,
+    pub tick_spacing: i32,
+    pub hooks: eth::ContractAddress,
+}
+
+/// The LP fee of a pool in hundredths of a basis point, or the dynamic fee
+/// flag for pools whose fee is set by their hooks.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Fee(pub u32);
+
+impl Pool {
+    /// Encodes a pool swap as the Permit2 approval of the router followed by
+    /// the swap. Returns `Err` if the input and output tokens don't
+    /// correspond to the pool's token pair.
+    pub fn swap(
+        &self,
+        input: &liquidity::MaxInput,
+        output: &liquidity::ExactOutput,
+        receiver: &eth::Address,
+    ) -> Result<Vec<eth::Interaction>, InvalidSwap> {
+        let tokens_match = (input.0.token == self.tokens.0 && output.0.token == self.tokens.1)
+            || (input.0.token == self.tokens.1 && output.0.token == self.tokens.0);
+
+        if !tokens_match {
+            return Err(InvalidSwap);
+        }
+
+        boundary::liquidity::uniswap::v4::to_interactions(self, input, output, receiver)
+            .map_err(|_| InvalidSwap)
+    }
+}
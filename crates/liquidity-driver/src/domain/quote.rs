@@ -34,10 +34,34 @@ pub struct Quote {
     /// Which `tx.origin` is required to make the quote simulation pass.
     pub tx_origin: Option<eth::Address>,
     pub jit_orders: Vec<solution::trade::Jit>,
+    /// Whether the deadline cut fetching liquidity short, so the quote was
+    /// computed without liquidity from the liquidity sources.
+    pub deadline_exceeded: bool,
+    /// The quoted amounts signed by the solver's operator, if the solver
+    /// attests its quotes.
+    pub attestation: Option<solver::attestation::Attestation>,
+    /// The liquidity the quote is routed through, or `None` if the solution
+    /// also relies on interactions or JIT orders that aren't backed by
+    /// liquidity of the driver.
+    pub route: Option<Route>,
 }
 
 impl Quote {
     fn try_new(eth: &Ethereum, solution: competition::Solution) -> Result<Self, Error> {
+        let has_jit_orders = solution
+            .trades()
+            .iter()
+            .any(|trade| matches!(trade, solution::Trade::Jit(_)));
+        let route = solution
+            .interactions()
+            .iter()
+            .map(|interaction| match interaction {
+                solution::Interaction::Liquidity(liquidity) => Some(liquidity.liquidity.clone()),
+                solution::Interaction::Custom(_) => None,
+            })
+            .collect::<Option<Vec<_>>>()
+            .filter(|_| solution.pre_interactions().is_empty() && !has_jit_orders)
+            .map(Route);
         Ok(Self {
             clearing_prices: solution
                 .clearing_prices()
@@ -66,10 +90,38 @@ impl Quote {
                     _ => None,
                 })
                 .collect(),
+            deadline_exceeded: false,
+            attestation: None,
+            route,
         })
     }
 }
 
+/// The liquidity a quote is routed through.
+#[derive(Clone)]
+pub struct Route(pub Vec<liquidity::Liquidity>);
+
+impl std::fmt::Debug for Route {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // The state of the liquidity is too verbose for logging quotes.
+        f.debug_list()
+            .entries(self.0.iter().map(liquidity::Liquidity::address))
+            .finish()
+    }
+}
+
+/// The simulated execution of a quoted order.
+#[derive(Debug)]
+pub struct Simulation {
+    pub quote: Quote,
+    /// The sold asset at the clearing prices of the quote.
+    pub sell: eth::Asset,
+    /// The bought asset at the clearing prices of the quote.
+    pub buy: eth::Asset,
+    /// The swap as executed by the settlement contract.
+    pub executed: blockchain::SimulatedSwap,
+}
+
 /// An order which needs to be quoted.
 #[derive(Debug)]
 pub struct Order {
@@ -91,11 +143,22 @@ impl Order {
         liquidity: &infra::liquidity::Fetcher,
         tokens: &infra::tokens::Fetcher,
     ) -> Result<Quote, Error> {
-        let liquidity = match solver.liquidity() {
+        let (liquidity, deadline_exceeded) = match solver.liquidity() {
             solver::Liquidity::Fetch => {
-                liquidity
-                    .fetch(&self.liquidity_pairs(), infra::liquidity::AtBlock::Recent)
-                    .await
+                // Leave the solver at least half of the time until the deadline.
+                let budget = (self.deadline - Utc::now()).to_std().unwrap_or_default() / 2;
+                let fetch =
+                    liquidity.fetch(&self.liquidity_pairs(), infra::liquidity::AtBlock::Recent);
+                match tokio::time::timeout(budget, fetch).await {
+                    Ok(liquidity) => (liquidity, false),
+                    Err(_) => {
+                        tracing::warn!(
+                            deadline = ?self.deadline,
+                            "quote deadline exceeded fetching liquidity"
+                        );
+                        (Default::default(), true)
+                    }
+                }
             }
             solver::Liquidity::Skip => Default::default(),
         };
@@ -104,7 +167,7 @@ impl Order {
             .fake_auction(eth, tokens, solver.quote_using_limit_orders())
             .await?;
         let solutions = solver.solve(&auction, &liquidity).await?;
-        Quote::try_new(
+        let quote = Quote::try_new(
             eth,
             // TODO(#1468): choose the best solution in the future, but for now just pick the
             // first solution
@@ -112,7 +175,98 @@ impl Order {
                 .into_iter()
                 .find(|solution| !solution.is_empty(auction.surplus_capturing_jit_order_owners()))
                 .ok_or(QuotingFailed::NoSolutions)?,
-        )
+        )?;
+        let attestation = solver
+            .quote_attestor()
+            .and_then(|attestor| self.attest(attestor, eth, &quote.clearing_prices));
+        Ok(Quote {
+            deadline_exceeded,
+            attestation,
+            ..quote
+        })
+    }
+
+    /// Quotes this order and simulates the interactions of the quote as part
+    /// of a settlement. The order is hypothetical, so instead of trading it,
+    /// the settlement contract gets funded with the sell amount.
+    pub async fn simulate(
+        &self,
+        eth: &Ethereum,
+        solver: &Solver,
+        liquidity: &infra::liquidity::Fetcher,
+        tokens: &infra::tokens::Fetcher,
+    ) -> Result<Simulation, SimulationError> {
+        let quote = self.quote(eth, solver, liquidity, tokens).await?;
+        if !quote.jit_orders.is_empty() {
+            return Err(SimulationError::JitOrders);
+        }
+        let (sell, buy) = self
+            .traded(&quote.clearing_prices)
+            .ok_or(SimulationError::MissingClearingPrices)?;
+        let executed = eth
+            .simulate_swap(
+                quote.tx_origin.unwrap_or(quote.solver),
+                sell,
+                buy.token,
+                &quote.clearing_prices,
+                &quote.pre_interactions,
+                &quote.interactions,
+            )
+            .await?
+            .ok_or(SimulationError::UnsupportedSellToken)?;
+        Ok(Simulation {
+            quote,
+            sell,
+            buy,
+            executed,
+        })
+    }
+
+    /// Attests the amounts traded at the clearing prices of the quote. Returns
+    /// `None` if the amounts can't be computed from the clearing prices.
+    fn attest(
+        &self,
+        attestor: &solver::attestation::Attestor,
+        eth: &Ethereum,
+        prices: &HashMap<eth::H160, eth::U256>,
+    ) -> Option<solver::attestation::Attestation> {
+        let (sell, buy) = self.traded(prices)?;
+        let domain = solver::attestation::domain_separator(
+            eth.chain().id(),
+            eth.contracts().settlement().address().into_legacy(),
+        );
+        Some(attestor.attest(&domain, sell, buy, eth.current_block().borrow().number))
+    }
+
+    /// The assets traded at the clearing prices. Returns `None` if the amounts
+    /// can't be computed from the clearing prices.
+    fn traded(&self, prices: &HashMap<eth::H160, eth::U256>) -> Option<(eth::Asset, eth::Asset)> {
+        let sell_price = *prices.get(&self.tokens.sell.into())?;
+        let buy_price = *prices.get(&self.tokens.buy.into())?;
+        let amount: eth::U256 = self.amount.into();
+        let (sell, buy) = match self.side {
+            order::Side::Sell => (
+                amount,
+                amount.checked_mul(sell_price)?.checked_div(buy_price)?,
+            ),
+            order::Side::Buy => (
+                amount
+                    .checked_mul(buy_price)?
+                    .checked_add(sell_price.checked_sub(1.into())?)?
+                    .checked_div(sell_price)?,
+                amount,
+            ),
+        };
+        Some((
+            eth::Asset {
+                token: self.tokens.sell,
+                amount: sell.into(),
+            },
+            eth::Asset {
+                token: self.tokens.buy,
+                amount: buy.into(),
+            },
+        ))
     }
 
     async fn fake_auction(
@@ -287,11 +441,11 @@ mod encode {
         };
 
         let encoded = match interaction {
-            solution::Interaction::Custom(interaction) => eth::Interaction {
+            solution::Interaction::Custom(interaction) => vec![eth::Interaction {
                 value: interaction.value,
                 target: interaction.target.0.into(),
                 call_data: interaction.call_data.clone(),
-            },
+            }],
             solution::Interaction::Liquidity(liquidity) => {
                 solution::encoding::liquidity_interaction(liquidity, &slippage, settlement)?
             }
@@ -316,7 +470,7 @@ mod encode {
                     solution::encoding::approve(&approval.max().0),
                 ]
             })
-            .chain(std::iter::once(encoded))
+            .chain(encoded)
             .collect())
     }
 }
@@ -350,6 +504,21 @@ pub enum QuotingFailed {
     NoSolutions,
 }
 
+#[derive(Debug, thiserror::Error)]
+pub enum SimulationError {
+    #[error(transparent)]
+    Quote(#[from] Error),
+    #[error("quotes with JIT orders can't be simulated")]
+    JitOrders,
+    #[error("missing clearing price of a traded token")]
+    MissingClearingPrices,
+    #[error("the sell token balance can't be overridden")]
+    UnsupportedSellToken,
+    /// Includes reverts of the simulated settlement.
+    #[error("blockchain error: {0:?}")]
+    Blockchain(#[from] blockchain::Error),
+}
+
 #[derive(Debug, thiserror::Error)]
 #[error("the quoted tokens are the same")]
 pub struct SameTokens;
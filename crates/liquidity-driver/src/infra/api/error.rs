@@ -23,6 +23,8 @@ enum Kind {
     FailedToSubmit,
     NoValidOrders,
     MalformedRequest,
+    InvalidBlock,
+    SimulationFailed,
 }
 
 #[derive(Debug, Serialize)]
@@ -57,6 +59,8 @@ impl From<Kind> for (hyper::StatusCode, axum::Json<Error>) {
             Kind::TooManyPendingSettlements => "Settlement queue is full",
             Kind::NoValidOrders => "No valid orders found in the auction",
             Kind::MalformedRequest => "Could not parse the request",
+            Kind::InvalidBlock => "The requested block is ahead of the latest block",
+            Kind::SimulationFailed => "The order could not be simulated",
         };
         (
             hyper::StatusCode::BAD_REQUEST,
@@ -82,6 +86,20 @@ impl From<quote::Error> for (hyper::StatusCode, axum::Json<Error>) {
     }
 }
 
+impl From<quote::SimulationError> for (hyper::StatusCode, axum::Json<Error>) {
+    fn from(value: quote::SimulationError) -> Self {
+        let error = match value {
+            quote::SimulationError::Quote(err) => return err.into(),
+            quote::SimulationError::MissingClearingPrices => Kind::QuotingFailed,
+            quote::SimulationError::JitOrders => Kind::SimulationFailed,
+            quote::SimulationError::UnsupportedSellToken => Kind::SimulationFailed,
+            quote::SimulationError::Blockchain(err) if err.is_revert() => Kind::SimulationFailed,
+            quote::SimulationError::Blockchain(_) => Kind::Unknown,
+        };
+        error.into()
+    }
+}
+
 impl From<competition::Error> for (hyper::StatusCode, axum::Json<Error>) {
     fn from(value: competition::Error) -> Self {
         let error = match value {
@@ -116,6 +134,17 @@ impl From<api::routes::AuctionError> for (hyper::StatusCode, axum::Json<Error>)
     }
 }
 
+impl From<api::routes::LiquidityError> for (hyper::StatusCode, axum::Json<Error>) {
+    fn from(value: api::routes::LiquidityError) -> Self {
+        tracing::warn!(error = ?value, "Liquidity API error");
+        let error = match value {
+            api::routes::LiquidityError::InvalidTokenPair => Kind::InvalidTokens,
+            api::routes::LiquidityError::FutureBlock => Kind::InvalidBlock,
+        };
+        error.into()
+    }
+}
+
 impl From<api::routes::OrderError> for (hyper::StatusCode, axum::Json<Error>) {
     fn from(value: api::routes::OrderError) -> Self {
         let error = match value {
@@ -19,7 +19,7 @@ use {
     error::Error,
     futures::Future,
     observe::distributed_tracing::tracing_axum::{make_span, record_trace_id},
-    shared::account_balances,
+    shared::{account_balances, listen},
     std::{net::SocketAddr, sync::Arc},
     tokio::sync::oneshot,
 };
@@ -27,6 +27,8 @@ use {
 mod error;
 pub mod routes;
 
+pub use routes::LiquidityFixtures;
+
 const REQUEST_BODY_LIMIT: usize = 10 * 1024 * 1024;
 
 pub struct Api {
@@ -36,7 +38,7 @@ pub struct Api {
     pub simulator: Simulator,
     pub eth: Ethereum,
     pub mempools: Mempools,
-    pub addr: SocketAddr,
+    pub listeners: Vec<listen::Listener>,
     pub bad_token_detector: bad_tokens::simulation::Detector,
     /// If this channel is specified, the bound address will be sent to it. This
     /// allows the driver to bind to 0.0.0.0:0 during testing.
@@ -80,6 +82,15 @@ impl Api {
         let eth = axum::Router::new();
         app = app.merge(routes::gasprice(eth).with_state(self.eth.clone()));
 
+        // Add the endpoints exporting the Balancer pools for seeding other
+        // instances, inspecting the state of single pools and enumerating the
+        // routes over the cached liquidity.
+        let liquidity = axum::Router::new();
+        let liquidity = routes::balancer_pools(liquidity);
+        let liquidity = routes::pools(liquidity);
+        let liquidity = routes::swap_routes(liquidity);
+        app = app.merge(liquidity.with_state(self.liquidity.clone()));
+
         // Multiplex each solver as part of the API. Multiple solvers are multiplexed
         // on the same driver so only one liquidity collector collects the liquidity
         // for all of them. This is important because liquidity collection is
@@ -89,6 +100,7 @@ impl Api {
             let router = axum::Router::new();
             let router = routes::info(router);
             let router = routes::quote(router);
+            let router = routes::simulate_order(router);
             let router = routes::liquidity(router);
             let router = routes::solve(router);
             let router = routes::reveal(router);
@@ -112,6 +124,19 @@ impl Api {
                 ));
             }
 
+            // Attested quotes are signed for the exact quoted amounts, so they
+            // can't be served for other amounts of the same bucket.
+            let quote_cache = solver
+                .quote_cache()
+                .filter(|_| solver.quote_attestor().is_none())
+                .map(|config| {
+                    routes::QuoteCache::new(
+                        config,
+                        self.liquidity.clone(),
+                        self.eth.current_block().clone(),
+                    )
+                });
+
             let router = router.with_state(State(Arc::new(Inner {
                 eth: self.eth.clone(),
                 solver: solver.clone(),
@@ -128,6 +153,7 @@ impl Api {
                 ),
                 liquidity: self.liquidity.clone(),
                 tokens: tokens.clone(),
+                quote_cache,
             })));
             let path = format!("/{name}");
             infra::observe::mounting_solver(&name, &path);
@@ -144,12 +170,17 @@ impl Api {
             );
 
         // Start the server.
-        let server = axum::Server::bind(&self.addr).serve(app.into_make_service());
-        tracing::info!(port = server.local_addr().port(), "serving driver");
+        let incoming = listen::bind(&self.listeners)
+            .unwrap_or_else(|err| panic!("failed to bind the API listeners: {err:#}"));
+        tracing::info!(addrs = ?incoming.local_addrs, "serving driver");
         if let Some(addr_sender) = self.addr_sender {
-            addr_sender.send(server.local_addr()).unwrap();
+            let addr = *incoming.local_addrs.first().expect("no TCP listener");
+            addr_sender.send(addr).unwrap();
         }
-        server.with_graceful_shutdown(shutdown).await
+        axum::Server::builder(incoming)
+            .serve(app.into_make_service())
+            .with_graceful_shutdown(shutdown)
+            .await
     }
 
     fn build_order_sorting_strategies(
@@ -179,6 +210,44 @@ impl Api {
     }
 }
 
+/// A liquidity-driver serving recorded liquidity, see
+/// [`LiquidityFixtures`].
+pub struct MockApi {
+    pub fixtures: LiquidityFixtures,
+    pub addr: SocketAddr,
+    /// If this channel is specified, the bound address will be sent to it.
+    pub addr_sender: Option<oneshot::Sender<SocketAddr>>,
+}
+
+impl MockApi {
+    pub async fn serve(
+        self,
+        shutdown: impl Future<Output = ()> + Send + 'static,
+    ) -> Result<(), hyper::Error> {
+        let app = routes::healthz(axum::Router::new());
+        let app = routes::mock_liquidity(app, Arc::new(self.fixtures))
+            .layer(axum::extract::DefaultBodyLimit::disable())
+            .layer(tower_http::limit::RequestBodyLimitLayer::new(
+                REQUEST_BODY_LIMIT,
+            ))
+            .layer(
+                tower::ServiceBuilder::new()
+                    .layer(tower_http::trace::TraceLayer::new_for_http().make_span_with(make_span))
+                    .map_request(record_trace_id),
+            );
+
+        let server = axum::Server::bind(&self.addr).serve(app.into_make_service());
+        tracing::info!(
+            port = server.local_addr().port(),
+            "serving mock liquidity driver"
+        );
+        if let Some(addr_sender) = self.addr_sender {
+            addr_sender.send(server.local_addr()).unwrap();
+        }
+        server.with_graceful_shutdown(shutdown).await
+    }
+}
+
 #[derive(Clone)]
 struct State(Arc<Inner>);
 
@@ -202,6 +271,10 @@ impl State {
     fn tokens(&self) -> &tokens::Fetcher {
         &self.0.tokens
     }
+
+    fn quote_cache(&self) -> Option<&routes::QuoteCache> {
+        self.0.quote_cache.as_ref()
+    }
 }
 
 struct Inner {
@@ -210,4 +283,15 @@ struct Inner {
     competition: Arc<domain::Competition>,
     liquidity: liquidity::Fetcher,
     tokens: tokens::Fetcher,
+    quote_cache: Option<routes::QuoteCache>,
+}
+
+/// Returns the deadline the client attached to the request, if any.
+fn request_deadline(headers: &axum::http::HeaderMap) -> Option<chrono::DateTime<chrono::Utc>> {
+    let header = |name| headers.get(name).and_then(|value| value.to_str().ok());
+    shared::request_deadline::parse(
+        header(shared::request_deadline::X_DEADLINE),
+        header(shared::request_deadline::GRPC_TIMEOUT),
+        chrono::Utc::now(),
+    )
 }
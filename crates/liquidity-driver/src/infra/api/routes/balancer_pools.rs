@@ -0,0 +1,16 @@
+use {
+    crate::{boundary::liquidity::balancer::PoolBundle, infra::liquidity},
+    axum::Json,
+};
+
+/// Exposes the Balancer pools the registries got initialized with as a bundle
+/// that other instances can import on startup, see the `pool-bundle` option.
+pub(in crate::infra::api) fn balancer_pools(
+    app: axum::Router<liquidity::Fetcher>,
+) -> axum::Router<liquidity::Fetcher> {
+    app.route("/balancer-pools", axum::routing::get(route))
+}
+
+async fn route(liquidity: axum::extract::State<liquidity::Fetcher>) -> Json<PoolBundle> {
+    Json(liquidity.balancer_pools())
+}
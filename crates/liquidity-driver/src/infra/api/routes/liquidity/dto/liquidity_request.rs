@@ -1,5 +1,19 @@
 use {crate::domain::eth, serde::Deserialize};
 
+/// Query parameters of the liquidity route.
+#[derive(Debug, Default, Deserialize)]
+pub struct LiquidityQuery {
+    /// Whether to include the liquidity that was skipped because it could not
+    /// be converted, along with the reasons, in the response.
+    #[serde(default)]
+    pub include_errors: bool,
+
+    /// Whether to include calldata templates for swapping through the
+    /// liquidity in the response.
+    #[serde(default)]
+    pub include_swap_templates: bool,
+}
+
 /// Request for fetching liquidity data for specific token pairs
 #[derive(Debug, Deserialize)]
 pub struct LiquidityRequest {
@@ -13,10 +27,53 @@ pub struct LiquidityRequest {
     /// These pairs will be automatically expanded with base token routing
     pub token_pairs: Vec<(eth::H160, eth::H160)>,
 
-    /// Block number for ensuring data freshness and consistency  
-    pub block_number: u64,
+    /// The block to fetch the liquidity at, either a block number or one of
+    /// `"latest"` and `"finalized"`
+    pub block_number: BlockSpec,
 
     /// List of protocols to fetch liquidity from
     /// e.g., ["balancer_v2", "uniswap_v2", "uniswap_v3"]
     pub protocols: Vec<String>,
 }
+
+/// The block liquidity is requested at.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq)]
+#[serde(untagged)]
+pub enum BlockSpec {
+    Number(u64),
+    Tag(BlockTag),
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum BlockTag {
+    Latest,
+    Finalized,
+}
+
+impl BlockSpec {
+    /// The requested block number, if a specific block was requested.
+    pub fn number(&self) -> Option<u64> {
+        match self {
+            Self::Number(number) => Some(*number),
+            Self::Tag(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserializes_block_spec() {
+        for (json, spec) in [
+            ("42", BlockSpec::Number(42)),
+            (r#""latest""#, BlockSpec::Tag(BlockTag::Latest)),
+            (r#""finalized""#, BlockSpec::Tag(BlockTag::Finalized)),
+        ] {
+            assert_eq!(serde_json::from_str::<BlockSpec>(json).unwrap(), spec);
+        }
+        assert!(serde_json::from_str::<BlockSpec>(r#""pending""#).is_err());
+    }
+}
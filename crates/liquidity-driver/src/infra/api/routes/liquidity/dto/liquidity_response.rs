@@ -1,4 +1,9 @@
-use {serde::Serialize, solvers_dto};
+use {
+    crate::{domain::eth, util::serialize},
+    serde::Serialize,
+    serde_with::serde_as,
+    solvers_dto,
+};
 
 /// Response containing liquidity data for the requested token pairs
 #[derive(Debug, Serialize)]
@@ -15,6 +20,56 @@ pub struct LiquidityResponse {
 
     /// Timestamp when this data was generated (Unix timestamp)
     pub timestamp: u64,
+
+    /// Whether the request deadline passed before all liquidity was fetched,
+    /// in which case the liquidity is incomplete
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub deadline_exceeded: bool,
+
+    /// The liquidity that was skipped because it could not be converted.
+    /// Only included when requested with `include_errors=true`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub skipped: Vec<SkippedLiquidity>,
+
+    /// Calldata templates for swapping through the liquidity. Only included
+    /// when requested with `include_swap_templates=true`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub swap_templates: Vec<SwapTemplate>,
+}
+
+/// Liquidity that was skipped because it could not be converted
+#[derive(Debug, Serialize)]
+pub struct SkippedLiquidity {
+    pub id: String,
+    pub kind: &'static str,
+    pub reason: &'static str,
+    pub message: String,
+}
+
+/// The calls swapping at most a maximum input amount for an exact output
+/// amount through the liquidity with the specified ID, with the settlement
+/// contract receiving the output.
+#[derive(Debug, Serialize)]
+pub struct SwapTemplate {
+    pub id: String,
+    pub token_in: eth::H160,
+    pub token_out: eth::H160,
+    pub calls: Vec<CallTemplate>,
+}
+
+/// A call of a swap template. The amounts of a swap get written as 32-byte
+/// big-endian words into the calldata at the specified byte offsets.
+#[serde_as]
+#[derive(Debug, Serialize)]
+pub struct CallTemplate {
+    pub target: eth::H160,
+    #[serde_as(as = "serialize::Hex")]
+    pub selector: [u8; 4],
+    /// The calldata with zeroes in place of the amounts.
+    #[serde_as(as = "serialize::Hex")]
+    pub call_data: Vec<u8>,
+    pub max_amount_in_offsets: Vec<usize>,
+    pub amount_out_offsets: Vec<usize>,
 }
 
 /// Response wrapper used by the API infrastructure
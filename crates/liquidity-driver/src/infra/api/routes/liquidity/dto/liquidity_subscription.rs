@@ -0,0 +1,30 @@
+use {
+    crate::domain::eth,
+    serde::{Deserialize, Serialize},
+    solvers_dto,
+};
+
+/// Request subscribing to the liquidity of token pairs
+#[derive(Debug, Deserialize)]
+pub struct LiquiditySubscription {
+    /// The trading pairs to stream the liquidity of
+    pub token_pairs: Vec<(eth::H160, eth::H160)>,
+}
+
+/// The changes to the liquidity of the subscribed token pairs at a new block.
+/// The first delta of a subscription contains all of its liquidity.
+#[derive(Debug, Serialize)]
+pub struct LiquidityDelta {
+    /// Block number the liquidity was fetched at
+    pub block_number: u64,
+
+    /// Timestamp when this data was generated (Unix timestamp)
+    pub timestamp: u64,
+
+    /// The liquidity that is new or whose state changed since the previous
+    /// delta
+    pub updated: Vec<solvers_dto::auction::Liquidity>,
+
+    /// The IDs of the liquidity that is no longer available
+    pub removed: Vec<String>,
+}
@@ -1,4 +1,5 @@
 pub mod liquidity_request;
 pub mod liquidity_response;
+pub mod liquidity_subscription;
 
-pub use {liquidity_request::*, liquidity_response::*};
+pub use {liquidity_request::*, liquidity_response::*, liquidity_subscription::*};
@@ -0,0 +1,251 @@
+//! Liquidity route serving recorded responses instead of fetching liquidity,
+//! so that solvers can be developed and tested without RPC or subgraph
+//! access.
+//!
+//! The fixtures directory contains one recorded `/api/v1/liquidity` response
+//! body per token pair, named after the pair, e.g. `<token a>-<token b>.json`
+//! (in any order). A request gets served the liquidity recorded for all of its
+//! token pairs.
+
+use {
+    super::{ApiLiquidityResponse, LiquidityRequest, LiquidityResponse},
+    crate::domain::{eth, liquidity},
+    anyhow::{Context, Result},
+    serde::Deserialize,
+    std::{
+        collections::{HashMap, HashSet},
+        path::Path,
+        sync::Arc,
+    },
+};
+
+/// Register the mock liquidity route with the router, both at the root and
+/// below a solver name like the route of the actual driver.
+pub(in crate::infra::api) fn liquidity(
+    router: axum::Router<()>,
+    fixtures: Arc<Fixtures>,
+) -> axum::Router<()> {
+    let route = axum::routing::post(route).with_state(fixtures);
+    router
+        .route("/api/v1/liquidity", route.clone())
+        .route("/:solver/api/v1/liquidity", route)
+}
+
+async fn route(
+    fixtures: axum::extract::State<Arc<Fixtures>>,
+    req: axum::Json<LiquidityRequest>,
+) -> axum::Json<ApiLiquidityResponse> {
+    let request = req.0;
+    let pairs = request
+        .token_pairs
+        .into_iter()
+        .filter_map(|(a, b)| liquidity::TokenPair::try_new(a.into(), b.into()).ok())
+        .collect::<HashSet<_>>();
+    let (liquidity, block_number) = fixtures.lookup(&pairs);
+    tracing::debug!(
+        auction_id = request.auction_id,
+        pairs = pairs.len(),
+        liquidity = liquidity.len(),
+        "serving recorded liquidity"
+    );
+
+    axum::Json(ApiLiquidityResponse {
+        result: LiquidityResponse {
+            auction_id: request.auction_id,
+            liquidity,
+            block_number: block_number
+                .or(request.block_number.number())
+                .unwrap_or_default(),
+            timestamp: chrono::Utc::now().timestamp() as u64,
+            deadline_exceeded: false,
+            skipped: Vec::new(),
+            swap_templates: Vec::new(),
+        },
+    })
+}
+
+/// The recorded liquidity, keyed by token pair.
+#[derive(Debug, Default)]
+pub struct Fixtures(HashMap<liquidity::TokenPair, Recording>);
+
+#[derive(Debug)]
+struct Recording {
+    block_number: u64,
+    /// The recorded liquidity, kept as JSON as the DTOs can't be cloned.
+    liquidity: Vec<serde_json::Value>,
+}
+
+/// The parts of a recorded response body the mock needs.
+#[derive(Deserialize)]
+struct RecordedResponse {
+    result: RecordedResult,
+}
+
+#[derive(Deserialize)]
+struct RecordedResult {
+    block_number: u64,
+    liquidity: Vec<serde_json::Value>,
+}
+
+impl Fixtures {
+    /// Loads the recordings of the fixtures directory. Fails on files that
+    /// are not named after a token pair or don't contain valid liquidity, so
+    /// that broken fixtures get noticed right away.
+    pub fn load(dir: &Path) -> Result<Self> {
+        let mut fixtures = HashMap::new();
+        for entry in std::fs::read_dir(dir).with_context(|| format!("reading {dir:?}"))? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|extension| extension != "json") {
+                continue;
+            }
+            let pair = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(parse_pair)
+                .with_context(|| format!("{path:?} is not named after a token pair"))?;
+            let file =
+                std::fs::read_to_string(&path).with_context(|| format!("reading {path:?}"))?;
+            let recording = parse_recording(&file).with_context(|| format!("parsing {path:?}"))?;
+            fixtures.insert(pair, recording);
+        }
+        tracing::info!(pairs = fixtures.len(), ?dir, "loaded liquidity fixtures");
+        Ok(Self(fixtures))
+    }
+
+    /// Returns the liquidity recorded for the token pairs, without
+    /// duplicates, and the latest block it was recorded at. Pairs without a
+    /// recording have no liquidity.
+    fn lookup(
+        &self,
+        pairs: &HashSet<liquidity::TokenPair>,
+    ) -> (Vec<solvers_dto::auction::Liquidity>, Option<u64>) {
+        let mut ids = HashSet::new();
+        let mut liquidity = Vec::new();
+        let mut block_number = None;
+        for pair in pairs {
+            let Some(recording) = self.0.get(pair) else {
+                tracing::debug!(?pair, "no liquidity recorded for token pair");
+                continue;
+            };
+            block_number = block_number.max(Some(recording.block_number));
+            for value in &recording.liquidity {
+                // Pools with more than two tokens are recorded for each of
+                // their pairs.
+                if let Some(id) = value.get("id").and_then(|id| id.as_str())
+                    && !ids.insert(id.to_owned())
+                {
+                    continue;
+                }
+                liquidity.push(
+                    serde_json::from_value(value.clone())
+                        .expect("recorded liquidity gets validated at load time"),
+                );
+            }
+        }
+        (liquidity, block_number)
+    }
+}
+
+fn parse_pair(stem: &str) -> Option<liquidity::TokenPair> {
+    let (a, b) = stem.split_once('-')?;
+    let token = |address: &str| {
+        address
+            .parse::<eth::H160>()
+            .ok()
+            .map(eth::TokenAddress::from)
+    };
+    liquidity::TokenPair::try_new(token(a)?, token(b)?).ok()
+}
+
+fn parse_recording(file: &str) -> Result<Recording> {
+    let RecordedResponse { result } = serde_json::from_str(file)?;
+    for value in &result.liquidity {
+        serde_json::from_value::<solvers_dto::auction::Liquidity>(value.clone())?;
+    }
+    Ok(Recording {
+        block_number: result.block_number,
+        liquidity: result.liquidity,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, serde_json::json};
+
+    const WETH: &str = "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2";
+    const DAI: &str = "0x6b175474e89094c44da98b954eedeac495271d0f";
+    const USDC: &str = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48";
+
+    fn pool(id: &str, tokens: &[&str]) -> serde_json::Value {
+        json!({
+            "kind": "constantProduct",
+            "id": id,
+            "address": "0x0000000000000000000000000000000000000001",
+            "router": "0x0000000000000000000000000000000000000002",
+            "gasEstimate": "110000",
+            "tokens": tokens
+                .iter()
+                .map(|token| (token.to_string(), json!({ "balance": "1000" })))
+                .collect::<serde_json::Map<_, _>>(),
+            "fee": "0.003",
+        })
+    }
+
+    fn recording(block_number: u64, liquidity: Vec<serde_json::Value>) -> String {
+        json!({
+            "result": {
+                "auction_id": 1,
+                "liquidity": liquidity,
+                "block_number": block_number,
+                "timestamp": 0,
+            }
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn serves_recorded_liquidity_of_requested_pairs() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join(format!("{WETH}-{DAI}.json")),
+            recording(
+                10,
+                vec![pool("0", &[WETH, DAI]), pool("1", &[WETH, DAI, USDC])],
+            ),
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join(format!("{USDC}-{WETH}.json")),
+            recording(12, vec![pool("1", &[WETH, DAI, USDC])]),
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("README.md"), "ignored").unwrap();
+
+        let fixtures = Fixtures::load(dir.path()).unwrap();
+        let pair = |a: &str, b: &str| parse_pair(&format!("{a}-{b}")).unwrap();
+
+        let (liquidity, block_number) =
+            fixtures.lookup(&HashSet::from([pair(DAI, WETH), pair(WETH, USDC)]));
+        assert_eq!(liquidity.len(), 2);
+        assert_eq!(block_number, Some(12));
+
+        let (liquidity, block_number) = fixtures.lookup(&HashSet::from([pair(DAI, USDC)]));
+        assert!(liquidity.is_empty());
+        assert_eq!(block_number, None);
+    }
+
+    #[test]
+    fn rejects_invalid_fixtures() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("weth-dai.json"), recording(10, vec![])).unwrap();
+        assert!(Fixtures::load(dir.path()).is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join(format!("{WETH}-{DAI}.json")),
+            recording(10, vec![json!({ "kind": "unknown" })]),
+        )
+        .unwrap();
+        assert!(Fixtures::load(dir.path()).is_err());
+    }
+}
@@ -2,7 +2,7 @@ use {
     crate::{
         domain::{eth, liquidity},
         infra::{
-            api::{State, error},
+            api::{State, error, request_deadline},
             liquidity::fetcher::AtBlock,
             observe,
         },
@@ -14,17 +14,27 @@ use {
 };
 
 mod dto;
+pub(in crate::infra::api) mod mock;
+mod subscribe;
+mod templates;
 
 pub use dto::*;
 
-/// Register the liquidity route with the router
+/// Register the liquidity routes with the router
 pub(in crate::infra::api) fn liquidity(router: axum::Router<State>) -> axum::Router<State> {
-    router.route("/api/v1/liquidity", axum::routing::post(route))
+    router
+        .route("/api/v1/liquidity", axum::routing::post(route))
+        .route(
+            "/api/v1/liquidity/subscribe",
+            axum::routing::post(subscribe::route),
+        )
 }
 
 /// Main handler for the /api/v1/liquidity endpoint
 async fn route(
     state: axum::extract::State<State>,
+    headers: axum::http::HeaderMap,
+    query: axum::extract::Query<LiquidityQuery>,
     req: axum::Json<LiquidityRequest>,
 ) -> Result<axum::Json<ApiLiquidityResponse>, (hyper::StatusCode, axum::Json<error::Error>)> {
     let auction_id = req.auction_id; // Extract before moving req
@@ -42,31 +52,84 @@ async fn route(
 
         observe::fetching_liquidity();
 
-        // Fetch liquidity using the existing liquidity fetcher
-        let domain_liquidity = state.liquidity().fetch(&pairs, AtBlock::Latest).await;
+        // Fetch liquidity using the existing liquidity fetcher. Fetching stops
+        // at the deadline of the request, if any, so the client gets a flagged
+        // empty response instead of one arriving after it gave up. The
+        // requested block is resolved to a block number first, which is
+        // reported back, so that clients can reason about the staleness of the
+        // liquidity and reproduce their results against the state it was
+        // fetched at.
+        let latest = state.eth().current_block().borrow().number;
+        let block_number = match request.block_number {
+            BlockSpec::Number(number) if number > latest => Err(LiquidityError::FutureBlock)?,
+            BlockSpec::Number(number) => number,
+            BlockSpec::Tag(BlockTag::Latest) => latest,
+            BlockSpec::Tag(BlockTag::Finalized) => state.eth().finalized_block().await?.0,
+        };
+        let fetch = state
+            .liquidity()
+            .fetch(&pairs, AtBlock::Number(block_number));
+        let (domain_liquidity, deadline_exceeded) = match request_deadline(&headers) {
+            Some(deadline) => {
+                let timeout = (deadline - chrono::Utc::now()).to_std().unwrap_or_default();
+                match tokio::time::timeout(timeout, fetch).await {
+                    Ok(liquidity) => (liquidity, false),
+                    Err(_) => {
+                        tracing::warn!(?deadline, "request deadline exceeded fetching liquidity");
+                        (Vec::new(), true)
+                    }
+                }
+            }
+            None => (fetch.await, false),
+        };
 
         observe::fetched_liquidity(&domain_liquidity);
 
-        // Convert domain liquidity to solvers-dto format
-        let liquidity_dto = domain_liquidity
-            .into_iter()
-            .filter_map(|liq| match convert_domain_to_dto(liq) {
-                Ok(dto) => Some(dto),
-                Err(e) => {
-                    tracing::warn!(
-                        liquidity_id = ?e,
-                        "Failed to convert domain liquidity to DTO, skipping"
-                    );
-                    None
+        // Derive the swap templates before the liquidity gets consumed by the
+        // conversion
+        let swap_templates = if query.include_swap_templates {
+            let settlement = state.eth().contracts().settlement().address().into_legacy();
+            domain_liquidity
+                .iter()
+                .flat_map(|liquidity| templates::derive(liquidity, settlement))
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        // Convert domain liquidity to solvers-dto format, skipping the pools
+        // that can't be converted
+        let mut liquidity_dto = Vec::new();
+        let mut skipped = Vec::new();
+        for liq in domain_liquidity {
+            let id = liq.id;
+            let kind = (&liq.kind).into();
+            match convert_domain_to_dto(liq) {
+                Ok(dto) => liquidity_dto.push(dto),
+                Err(err) => {
+                    observe::liquidity_conversion_failed(id, kind, &err);
+                    skipped.push(SkippedLiquidity {
+                        id: id.0.to_string(),
+                        kind,
+                        reason: err.reason(),
+                        message: err.to_string(),
+                    });
                 }
-            })
-            .collect();
+            }
+        }
 
         let response = LiquidityResponse {
             auction_id: request.auction_id,
             liquidity: liquidity_dto,
-            block_number: request.block_number,
+            block_number,
             timestamp: chrono::Utc::now().timestamp() as u64,
+            deadline_exceeded,
+            skipped: if query.include_errors {
+                skipped
+            } else {
+                Vec::new()
+            },
+            swap_templates,
         };
 
         Ok(axum::Json(ApiLiquidityResponse { result: response }))
@@ -81,9 +144,10 @@ async fn route(
 }
 
 /// Convert domain liquidity types to solvers_dto types
-fn convert_domain_to_dto(
+pub(in crate::infra::api) fn convert_domain_to_dto(
     liquidity: liquidity::Liquidity,
-) -> Result<solvers_dto::auction::Liquidity, LiquidityError> {
+) -> Result<solvers_dto::auction::Liquidity, ConversionError> {
+    let kind = (&liquidity.kind).into();
     match liquidity.kind {
         liquidity::Kind::UniswapV2(pool) => Ok(solvers_dto::auction::Liquidity::ConstantProduct(
             solvers_dto::auction::ConstantProductPool {
@@ -126,6 +190,63 @@ fn convert_domain_to_dto(
                 },
             ))
         }
+        liquidity::Kind::UniswapV4(pool) => Ok(solvers_dto::auction::Liquidity::UniswapV4(
+            solvers_dto::auction::UniswapV4Pool {
+                id: liquidity.id.0.to_string(),
+                address: pool.pool_manager.0,
+                router: pool.router.into(),
+                gas_estimate: liquidity.gas.0,
+                tokens: vec![pool.tokens.get().0.into(), pool.tokens.get().1.into()],
+                fee: pool.fee.0,
+                tick_spacing: pool.tick_spacing,
+                hooks: pool.hooks.into(),
+            },
+        )),
+        liquidity::Kind::MaverickV2(pool) => Ok(solvers_dto::auction::Liquidity::MaverickV2(
+            solvers_dto::auction::MaverickV2Pool {
+                id: liquidity.id.0.to_string(),
+                address: pool.address.0,
+                router: pool.router.into(),
+                gas_estimate: liquidity.gas.0,
+                tokens: vec![pool.tokens.get().0.into(), pool.tokens.get().1.into()],
+                tick_spacing: pool.tick_spacing,
+                active_tick: pool.active_tick.0,
+                ticks: pool
+                    .ticks
+                    .iter()
+                    .map(|(tick, reserves)| {
+                        (
+                            tick.0,
+                            solvers_dto::auction::MaverickV2Tick {
+                                reserve_a: reserves.a,
+                                reserve_b: reserves.b,
+                            },
+                        )
+                    })
+                    .collect(),
+                fee_a_in: bigdecimal::BigDecimal::new(pool.fee_a_in.0.to_big_int(), 18),
+                fee_b_in: bigdecimal::BigDecimal::new(pool.fee_b_in.0.to_big_int(), 18),
+            },
+        )),
+        liquidity::Kind::Curve(pool) => Ok(solvers_dto::auction::Liquidity::Curve(
+            solvers_dto::auction::CurvePool {
+                id: liquidity.id.0.to_string(),
+                address: pool.address.0,
+                gas_estimate: liquidity.gas.0,
+                tokens: pool
+                    .coins
+                    .iter()
+                    .map(|coin| solvers_dto::auction::CurveCoin {
+                        address: coin.token.into(),
+                        balance: coin.balance,
+                        rate: coin.rate,
+                    })
+                    .collect(),
+                amplification: pool.amplification,
+                fee: pool.fee,
+                base_pool: pool.base_pool.map(|pool| pool.0),
+            },
+        )),
 
         liquidity::Kind::BalancerV2Weighted(pool) => {
             Ok(solvers_dto::auction::Liquidity::WeightedProduct(
@@ -220,6 +341,7 @@ fn convert_domain_to_dto(
                     pool.amplification_parameter.precision().to_big_int(),
                 )),
                 fee: fee_to_decimal(pool.fee),
+                actual_supply: pool.actual_supply,
             },
         )),
 
@@ -248,6 +370,7 @@ fn convert_domain_to_dto(
                     pool.amplification_parameter.precision().to_big_int(),
                 )),
                 fee: fee_to_decimal_v3(pool.fee),
+                actual_supply: None,
             },
         )),
 
@@ -463,6 +586,15 @@ fn convert_domain_to_dto(
             },
         )),
 
+        liquidity::Kind::BalancerV3ReClamm(pool)
+            if pool.last_virtual_balances.len() != pool.reserves.len() =>
+        {
+            Err(ConversionError::MismatchedVirtualBalances {
+                kind,
+                reserves: pool.reserves.len(),
+                virtual_balances: pool.last_virtual_balances.len(),
+            })
+        }
         liquidity::Kind::BalancerV3ReClamm(pool) => Ok(solvers_dto::auction::Liquidity::ReClamm(
             solvers_dto::auction::ReClammPool {
                 id: liquidity.id.0.to_string(),
@@ -499,6 +631,7 @@ fn convert_domain_to_dto(
                 ),
                 price_ratio_update_start_time: pool.price_ratio_update_start_time,
                 price_ratio_update_end_time: pool.price_ratio_update_end_time,
+                current_timestamp: pool.current_timestamp,
             },
         )),
 
@@ -542,6 +675,11 @@ fn convert_domain_to_dto(
                 last_update_time: pool.last_update_time,
                 last_interop_time: pool.last_interop_time,
                 current_timestamp: pool.current_timestamp,
+                strategy: Some(solvers_dto::auction::QuantAmmStrategy {
+                    rule: pool.strategy.rule,
+                    oracles: pool.strategy.oracles,
+                    update_interval: pool.strategy.update_interval,
+                }),
             },
         )),
 
@@ -592,13 +730,7 @@ fn convert_domain_to_dto(
         )),
 
         #[allow(unreachable_patterns)]
-        _ => {
-            tracing::warn!(
-                "Unsupported pool type for liquidity conversion: {:?}",
-                std::mem::discriminant(&liquidity.kind)
-            );
-            Err(LiquidityError::UnsupportedPoolType)
-        }
+        _ => Err(ConversionError::UnsupportedPoolType(kind)),
     }
 }
 
@@ -606,8 +738,31 @@ fn convert_domain_to_dto(
 pub enum LiquidityError {
     #[error("Invalid token pair")]
     InvalidTokenPair,
-    #[error("Unsupported pool type")]
-    UnsupportedPoolType,
+    #[error("Requested block is ahead of the latest block")]
+    FutureBlock,
+}
+
+/// Why liquidity could not be converted to its DTO.
+#[derive(Debug, thiserror::Error)]
+pub enum ConversionError {
+    #[error("unsupported pool type {0}")]
+    UnsupportedPoolType(&'static str),
+    #[error("{kind} pool has {virtual_balances} virtual balances for {reserves} reserves")]
+    MismatchedVirtualBalances {
+        kind: &'static str,
+        reserves: usize,
+        virtual_balances: usize,
+    },
+}
+
+impl ConversionError {
+    /// The reason of the error, used as metrics label and in API responses.
+    pub fn reason(&self) -> &'static str {
+        match self {
+            Self::UnsupportedPoolType(_) => "UnsupportedPoolType",
+            Self::MismatchedVirtualBalances { .. } => "MismatchedVirtualBalances",
+        }
+    }
 }
 
 fn fee_to_decimal(fee: liquidity::balancer::v2::Fee) -> bigdecimal::BigDecimal {
@@ -711,24 +866,3 @@ fn i256_to_decimal(i256: ethcontract::I256) -> bigdecimal::BigDecimal {
         .expect("valid I256 should parse to BigInt");
     bigdecimal::BigDecimal::new(big_int, 18)
 }
-
-impl From<LiquidityError> for (hyper::StatusCode, axum::Json<error::Error>) {
-    fn from(error: LiquidityError) -> Self {
-        tracing::warn!(?error, "Liquidity API error");
-
-        // Map to existing error kinds that are exposed via the error module
-        match error {
-            LiquidityError::InvalidTokenPair => {
-                // Use the existing From implementation for InvalidTokens error kind
-                let auction_error = crate::infra::api::routes::AuctionError::InvalidTokens;
-                auction_error.into()
-            }
-            LiquidityError::UnsupportedPoolType => {
-                // For now, just return the same error as InvalidTokens since they both
-                // result in Bad Request. We can make this more specific later if needed.
-                let auction_error = crate::infra::api::routes::AuctionError::InvalidTokens;
-                auction_error.into()
-            }
-        }
-    }
-}
@@ -0,0 +1,176 @@
+//! Streaming of the liquidity of token pairs as server-sent events, so that
+//! solvers don't have to poll `/api/v1/liquidity` for every auction.
+//!
+//! A subscription fetches the liquidity of its token pairs on every new block
+//! and pushes a `delta` event with the liquidity whose state changed since the
+//! previous block, as well as the IDs of the liquidity that disappeared.
+
+use {
+    super::{LiquidityDelta, LiquidityError, LiquiditySubscription, convert_domain_to_dto},
+    crate::{
+        domain::liquidity,
+        infra::{
+            api::{State, error},
+            liquidity::fetcher::AtBlock,
+            observe,
+        },
+    },
+    axum::response::sse::{Event, KeepAlive, Sse},
+    ethrpc::block_stream,
+    futures::{Stream, StreamExt},
+    std::collections::{HashMap, HashSet},
+    tracing::Instrument,
+};
+
+/// Handler for the /api/v1/liquidity/subscribe endpoint
+pub(super) async fn route(
+    state: axum::extract::State<State>,
+    req: axum::Json<LiquiditySubscription>,
+) -> Result<
+    Sse<impl Stream<Item = Result<Event, axum::Error>>>,
+    (hyper::StatusCode, axum::Json<error::Error>),
+> {
+    let pairs = req
+        .0
+        .token_pairs
+        .into_iter()
+        .map(|(a, b)| liquidity::TokenPair::try_new(a.into(), b.into()))
+        .collect::<Result<HashSet<_>, _>>()
+        .map_err(|_| LiquidityError::InvalidTokenPair)?;
+    tracing::debug!(pairs = pairs.len(), "liquidity subscription");
+
+    let state = state.0;
+    let blocks = block_stream::into_stream(state.eth().current_block().clone());
+    let deltas = futures::stream::unfold(
+        (blocks, Snapshot::default()),
+        move |(mut blocks, mut snapshot)| {
+            let state = state.clone();
+            let pairs = pairs.clone();
+            async move {
+                let block = blocks.next().await?;
+                observe::fetching_liquidity();
+                let fetched = state
+                    .liquidity()
+                    .fetch(&pairs, AtBlock::Number(block.number))
+                    .await;
+                observe::fetched_liquidity(&fetched);
+
+                let (updated, removed) = snapshot.update(convert(fetched));
+                let delta = LiquidityDelta {
+                    block_number: block.number,
+                    timestamp: chrono::Utc::now().timestamp() as u64,
+                    updated,
+                    removed,
+                };
+                Some((
+                    Event::default().event("delta").json_data(&delta),
+                    (blocks, snapshot),
+                ))
+            }
+            .instrument(tracing::info_span!("/api/v1/liquidity/subscribe"))
+        },
+    );
+
+    Ok(Sse::new(deltas).keep_alive(KeepAlive::default()))
+}
+
+/// Converts the liquidity to its DTOs, skipping the liquidity that can't be
+/// converted.
+fn convert(liquidity: Vec<liquidity::Liquidity>) -> Vec<solvers_dto::auction::Liquidity> {
+    liquidity
+        .into_iter()
+        .filter_map(|liquidity| {
+            let id = liquidity.id;
+            let kind = (&liquidity.kind).into();
+            convert_domain_to_dto(liquidity)
+                .inspect_err(|err| observe::liquidity_conversion_failed(id, kind, err))
+                .ok()
+        })
+        .collect()
+}
+
+/// The state of the liquidity last pushed to a subscriber, by liquidity ID.
+#[derive(Default)]
+struct Snapshot(HashMap<String, serde_json::Value>);
+
+impl Snapshot {
+    /// Replaces the snapshot with the liquidity and returns the liquidity
+    /// whose state changed, as well as the IDs of the liquidity that is no
+    /// longer included.
+    fn update(
+        &mut self,
+        liquidity: Vec<solvers_dto::auction::Liquidity>,
+    ) -> (Vec<solvers_dto::auction::Liquidity>, Vec<String>) {
+        let mut previous = std::mem::take(&mut self.0);
+        let mut updated = Vec::new();
+        for liquidity in liquidity {
+            let id = id(&liquidity).to_owned();
+            let Ok(state) = serde_json::to_value(&liquidity) else {
+                continue;
+            };
+            if previous.remove(&id).as_ref() != Some(&state) {
+                updated.push(liquidity);
+            }
+            self.0.insert(id, state);
+        }
+        let mut removed = previous.into_keys().collect::<Vec<_>>();
+        removed.sort();
+        (updated, removed)
+    }
+}
+
+fn id(liquidity: &solvers_dto::auction::Liquidity) -> &str {
+    use solvers_dto::auction::Liquidity;
+    match liquidity {
+        Liquidity::ConstantProduct(pool) => &pool.id,
+        Liquidity::WeightedProduct(pool) => &pool.id,
+        Liquidity::Stable(pool) => &pool.id,
+        Liquidity::ConcentratedLiquidity(pool) => &pool.id,
+        Liquidity::UniswapV4(pool) => &pool.id,
+        Liquidity::MaverickV2(pool) => &pool.id,
+        Liquidity::Curve(pool) => &pool.id,
+        Liquidity::GyroE(pool) => &pool.id,
+        Liquidity::Gyro2CLP(pool) => &pool.id,
+        Liquidity::Gyro3CLP(pool) => &pool.id,
+        Liquidity::ReClamm(pool) => &pool.id,
+        Liquidity::QuantAmm(pool) => &pool.id,
+        Liquidity::StableSurge(pool) => &pool.id,
+        Liquidity::LimitOrder(order) => &order.id,
+        Liquidity::Erc4626(edge) => &edge.id,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, solvers_dto::auction::Erc4626Edge};
+
+    fn edge(id: &str, gas: u64) -> solvers_dto::auction::Liquidity {
+        solvers_dto::auction::Liquidity::Erc4626(Erc4626Edge {
+            id: id.to_owned(),
+            gas_estimate: gas.into(),
+            vault: Default::default(),
+            asset: Default::default(),
+        })
+    }
+
+    fn ids(liquidity: &[solvers_dto::auction::Liquidity]) -> Vec<&str> {
+        liquidity.iter().map(id).collect()
+    }
+
+    #[test]
+    fn pushes_only_changed_liquidity() {
+        let mut snapshot = Snapshot::default();
+
+        let (updated, removed) = snapshot.update(vec![edge("1", 100), edge("2", 100)]);
+        assert_eq!(ids(&updated), ["1", "2"]);
+        assert!(removed.is_empty());
+
+        let (updated, removed) = snapshot.update(vec![edge("1", 100), edge("2", 200)]);
+        assert_eq!(ids(&updated), ["2"]);
+        assert!(removed.is_empty());
+
+        let (updated, removed) = snapshot.update(vec![edge("2", 200), edge("3", 100)]);
+        assert_eq!(ids(&updated), ["3"]);
+        assert_eq!(removed, ["1"]);
+    }
+}
@@ -0,0 +1,128 @@
+//! Calldata templates for swapping through the returned liquidity, so that
+//! thin solvers can build interactions without embedding an encoder for every
+//! protocol.
+//!
+//! A template gets derived by encoding a swap through the liquidity with
+//! sentinel amounts and locating the sentinels in the encoded calldata. Solvers
+//! build their interactions by writing their amounts into the calldata at the
+//! reported offsets.
+
+use {
+    super::{CallTemplate, SwapTemplate},
+    crate::domain::{
+        competition::solution::encoding,
+        eth,
+        liquidity::{self, ExactOutput, MaxInput},
+    },
+    itertools::Itertools,
+};
+
+/// The sentinel encoded as the maximum input amount. The sentinels are small
+/// enough to fit the narrowest amount types of the supported protocols.
+const MAX_AMOUNT_IN: u128 = 0x1111_1111_1111_1111_1111_1111_1111;
+
+/// The sentinel encoded as the exact output amount.
+const AMOUNT_OUT: u128 = 0x2222_2222_2222_2222_2222_2222_2222;
+
+/// Derives the templates for swapping in each direction between the tokens of
+/// the liquidity, with the settlement contract receiving the output. Returns
+/// no templates for the directions that can't be templated.
+pub fn derive(liquidity: &liquidity::Liquidity, settlement: eth::H160) -> Vec<SwapTemplate> {
+    liquidity
+        .tokens()
+        .into_iter()
+        .permutations(2)
+        .filter_map(|tokens| derive_swap(liquidity, tokens[0], tokens[1], settlement))
+        .collect()
+}
+
+fn derive_swap(
+    liquidity: &liquidity::Liquidity,
+    token_in: eth::TokenAddress,
+    token_out: eth::TokenAddress,
+    settlement: eth::H160,
+) -> Option<SwapTemplate> {
+    let interactions = encoding::swap_interactions(
+        liquidity,
+        MaxInput(eth::Asset {
+            token: token_in,
+            amount: eth::U256::from(MAX_AMOUNT_IN).into(),
+        }),
+        ExactOutput(eth::Asset {
+            token: token_out,
+            amount: eth::U256::from(AMOUNT_OUT).into(),
+        }),
+        settlement,
+    )?;
+    let calls = interactions
+        .into_iter()
+        .map(call)
+        .collect::<Option<Vec<_>>>()?;
+    // Without the output amount in the calldata, the template would always
+    // swap the sentinel amount.
+    if calls.iter().all(|call| call.amount_out_offsets.is_empty()) {
+        return None;
+    }
+    Some(SwapTemplate {
+        id: liquidity.id.0.to_string(),
+        token_in: token_in.into(),
+        token_out: token_out.into(),
+        calls,
+    })
+}
+
+/// Turns an encoded interaction into a call template, blanking out the
+/// sentinel amounts. Returns `None` for interactions that can't be templated,
+/// i.e. ones sending ETH.
+fn call(interaction: eth::Interaction) -> Option<CallTemplate> {
+    if !interaction.value.0.is_zero() {
+        return None;
+    }
+    let mut call_data = interaction.call_data.0;
+    let max_amount_in_offsets = blank(&mut call_data, MAX_AMOUNT_IN);
+    let amount_out_offsets = blank(&mut call_data, AMOUNT_OUT);
+    Some(CallTemplate {
+        target: interaction.target.0,
+        selector: call_data.get(..4)?.try_into().ok()?,
+        call_data,
+        max_amount_in_offsets,
+        amount_out_offsets,
+    })
+}
+
+/// Zeroes the ABI words of the calldata holding the sentinel and returns their
+/// byte offsets.
+fn blank(call_data: &mut [u8], sentinel: u128) -> Vec<usize> {
+    let mut word = [0; 32];
+    eth::U256::from(sentinel).to_big_endian(&mut word);
+    let mut offsets = Vec::new();
+    // The arguments are encoded in 32-byte words following the selector.
+    let mut offset = 4;
+    while offset + 32 <= call_data.len() {
+        if call_data[offset..offset + 32] == word {
+            call_data[offset..offset + 32].fill(0);
+            offsets.push(offset);
+        }
+        offset += 32;
+    }
+    offsets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blanks_sentinel_words() {
+        let mut word = [0; 32];
+        eth::U256::from(AMOUNT_OUT).to_big_endian(&mut word);
+        let mut call_data = [[0xab; 4].as_slice(), &[1; 32], &word, &[2; 32], &word].concat();
+
+        assert_eq!(blank(&mut call_data, AMOUNT_OUT), [36, 100]);
+        assert_eq!(
+            call_data,
+            [[0xab; 4].as_slice(), &[1; 32], &[0; 32], &[2; 32], &[0; 32]].concat()
+        );
+        assert!(blank(&mut call_data, MAX_AMOUNT_IN).is_empty());
+    }
+}
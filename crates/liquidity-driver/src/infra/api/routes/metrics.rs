@@ -2,7 +2,11 @@ pub(in crate::infra::api) fn metrics(app: axum::Router<()>) -> axum::Router<()>
     app.route("/metrics", axum::routing::get(route))
 }
 
-async fn route() -> String {
+async fn route(headers: axum::http::HeaderMap) -> impl axum::response::IntoResponse {
     let registry = observe::metrics::get_registry();
-    observe::metrics::encode(registry)
+    let accept = headers
+        .get(axum::http::header::ACCEPT)
+        .and_then(|accept| accept.to_str().ok());
+    let (content_type, metrics) = observe::metrics::encode_accepted(registry, accept);
+    ([(axum::http::header::CONTENT_TYPE, content_type)], metrics)
 }
@@ -1,23 +1,35 @@
+mod balancer_pools;
 mod gasprice;
 mod healthz;
 mod info;
 mod liquidity;
 mod metrics;
 mod notify;
+mod pools;
 mod quote;
 mod reveal;
 mod settle;
+mod simulate_order;
 pub mod solve;
+mod swap_routes;
 
+pub use liquidity::{
+    ConversionError as LiquidityConversionError,
+    mock::Fixtures as LiquidityFixtures,
+};
 pub(super) use {
+    balancer_pools::balancer_pools,
     gasprice::gasprice,
     healthz::healthz,
     info::info,
-    liquidity::liquidity,
+    liquidity::{LiquidityError, liquidity, mock::liquidity as mock_liquidity},
     metrics::metrics,
     notify::notify,
-    quote::{OrderError, quote},
+    pools::pools,
+    quote::{OrderError, QuoteCache, quote},
     reveal::reveal,
     settle::settle,
+    simulate_order::simulate_order,
     solve::{AuctionError, solve},
+    swap_routes::swap_routes,
 };
@@ -0,0 +1,80 @@
+use {
+    super::liquidity::convert_domain_to_dto,
+    crate::{
+        boundary::liquidity::balancer::{PoolInspection, Version},
+        domain::eth,
+        infra::liquidity,
+    },
+    axum::{
+        Json,
+        extract::{Path, State},
+    },
+    hyper::StatusCode,
+    serde::Serialize,
+};
+
+/// Exposes the latest state of a single Balancer pool by its pool ID or
+/// address, in the format the solver engines get it in. This is useful for
+/// debugging quotes routed through a specific pool, or why a pool doesn't get
+/// used at all.
+pub(in crate::infra::api) fn pools(
+    app: axum::Router<liquidity::Fetcher>,
+) -> axum::Router<liquidity::Fetcher> {
+    app.route("/api/v1/pools/:id", axum::routing::get(route))
+}
+
+async fn route(
+    liquidity: State<liquidity::Fetcher>,
+    Path(id): Path<String>,
+) -> Result<Json<Pool>, StatusCode> {
+    let pool = liquidity
+        .fetch_pool(&id)
+        .await
+        .map_err(|err| {
+            tracing::warn!(?err, %id, "failed to fetch pool");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(Pool::new(pool)))
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Pool {
+    id: String,
+    address: eth::H160,
+    version: &'static str,
+    tokens: Vec<eth::H160>,
+    /// The block the state of the pool was fetched at.
+    block_number: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    paused: Option<bool>,
+    /// The state of the pool, or `None` if the pool isn't part of the
+    /// liquidity the solvers get, e.g. because it is paused or in recovery
+    /// mode.
+    state: Option<solvers_dto::auction::Liquidity>,
+}
+
+impl Pool {
+    fn new(inspection: PoolInspection) -> Self {
+        let state = inspection.liquidity.and_then(|liquidity| {
+            convert_domain_to_dto(liquidity)
+                .inspect_err(|err| {
+                    tracing::warn!(?err, id = %inspection.pool.id, "failed to convert pool");
+                })
+                .ok()
+        });
+        Self {
+            id: inspection.pool.id,
+            address: inspection.pool.address,
+            version: match inspection.pool.version {
+                Version::V2 => "v2",
+                Version::V3 => "v3",
+            },
+            tokens: inspection.pool.tokens,
+            block_number: inspection.block,
+            paused: inspection.paused,
+            state,
+        }
+    }
+}
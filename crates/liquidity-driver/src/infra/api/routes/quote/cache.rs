@@ -0,0 +1,344 @@
+//! Caching of quotes, so that repeated quotes of the same order get answered
+//! without asking the solver again.
+//!
+//! Quotes are cached by their token pair, side and amount bucket together with
+//! the state of the liquidity they are routed through. Quotes containing
+//! calldata or signed amounts for the quoted amount are only served for that
+//! exact amount, while others get served to every amount of the bucket. The
+//! liquidity of the
+//! cached token pairs gets fetched on every new block, and quotes routed
+//! through liquidity whose state changed or that disappeared get evicted, so
+//! that a cached quote is only served as long as the solver would be quoting
+//! against the same pool states.
+
+use {
+    super::dto,
+    crate::{
+        domain::{competition::order, eth, liquidity, quote},
+        infra::{
+            self,
+            api::routes::liquidity::convert_domain_to_dto,
+            liquidity::fetcher::AtBlock,
+            solver,
+        },
+    },
+    ethrpc::block_stream::{self, CurrentBlockWatcher},
+    futures::StreamExt,
+    std::{
+        collections::{HashMap, HashSet},
+        sync::{Arc, Mutex, Weak},
+        time::Instant,
+    },
+    tracing::Instrument,
+};
+
+/// Cache of the quotes of a solver.
+#[derive(Clone)]
+pub struct QuoteCache(Arc<Inner>);
+
+struct Inner {
+    config: solver::QuoteCache,
+    entries: Mutex<HashMap<Key, Entry>>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+struct Key {
+    tokens: quote::Tokens,
+    side: order::Side,
+    amount: eth::U256,
+}
+
+struct Entry {
+    quote: dto::Quote,
+    /// The exact amount the quote was computed for.
+    amount: eth::U256,
+    route: Route,
+    created: Instant,
+}
+
+/// The state of the liquidity a quote is routed through, by pool address.
+type Route = HashMap<eth::H160, serde_json::Value>;
+
+impl QuoteCache {
+    /// Creates a new quote cache, which gets invalidated with the liquidity
+    /// fetched on every new block.
+    pub fn new(
+        config: solver::QuoteCache,
+        liquidity: infra::liquidity::Fetcher,
+        blocks: CurrentBlockWatcher,
+    ) -> Self {
+        let inner = Arc::new(Inner {
+            config,
+            entries: Default::default(),
+        });
+        spawn_invalidation_task(Arc::downgrade(&inner), liquidity, blocks);
+        Self(inner)
+    }
+
+    /// Returns the cached quote for the order, if any.
+    pub fn get(&self, order: &quote::Order) -> Option<dto::Quote> {
+        let key = self.0.key(order);
+        let entries = self.0.entries.lock().unwrap();
+        entries
+            .get(&key)
+            .filter(|entry| entry.created.elapsed() < self.0.config.max_age)
+            .filter(|entry| {
+                entry.amount == order.amount.into() || !entry.quote.is_amount_specific()
+            })
+            .map(|entry| entry.quote.clone())
+    }
+
+    /// Caches the quote for the order. Quotes whose route isn't fully known to
+    /// the driver can't be invalidated and therefore don't get cached.
+    pub fn insert(&self, order: &quote::Order, route: quote::Route, quote: dto::Quote) {
+        let Some(route) = route
+            .0
+            .into_iter()
+            .map(state)
+            .collect::<Option<Route>>()
+            .filter(|route| !route.is_empty())
+        else {
+            return;
+        };
+
+        let key = self.0.key(order);
+        let mut entries = self.0.entries.lock().unwrap();
+        if entries.len() >= self.0.config.max_entries && !entries.contains_key(&key) {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.created)
+                .map(|(key, _)| *key);
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert(
+            key,
+            Entry {
+                quote,
+                amount: order.amount.into(),
+                route,
+                created: Instant::now(),
+            },
+        );
+    }
+}
+
+impl Inner {
+    fn key(&self, order: &quote::Order) -> Key {
+        Key {
+            tokens: order.tokens,
+            side: order.side,
+            amount: bucket(order.amount.into(), self.config.amount_precision_bits),
+        }
+    }
+
+    /// The token pairs of the cached quotes.
+    fn pairs(&self) -> HashSet<liquidity::TokenPair> {
+        self.entries
+            .lock()
+            .unwrap()
+            .keys()
+            .map(|key| {
+                liquidity::TokenPair::try_new(key.tokens.sell(), key.tokens.buy())
+                    .expect("sell != buy by construction")
+            })
+            .collect()
+    }
+
+    /// Evicts the quotes that are too old or routed through liquidity whose
+    /// state differs from the fetched liquidity. Returns the number of evicted
+    /// quotes.
+    fn invalidate(&self, liquidity: Vec<liquidity::Liquidity>) -> usize {
+        let current = liquidity.into_iter().filter_map(state).collect::<Route>();
+        let mut entries = self.entries.lock().unwrap();
+        let before = entries.len();
+        entries.retain(|_, entry| {
+            entry.created.elapsed() < self.config.max_age
+                && entry
+                    .route
+                    .iter()
+                    .all(|(address, state)| current.get(address) == Some(state))
+        });
+        before - entries.len()
+    }
+}
+
+/// Rounds the amount down to its `precision` most significant bits.
+fn bucket(amount: eth::U256, precision: u32) -> eth::U256 {
+    let shift = amount.bits().saturating_sub(precision as usize);
+    (amount >> shift) << shift
+}
+
+/// The address of the pool backing the liquidity and the state of the
+/// liquidity independent of the ID it got fetched with. Returns `None` for
+/// liquidity that isn't backed by a pool of its own.
+fn state(liquidity: liquidity::Liquidity) -> Option<(eth::H160, serde_json::Value)> {
+    let address = liquidity.address()?;
+    let mut state = serde_json::to_value(convert_domain_to_dto(liquidity).ok()?).ok()?;
+    state.as_object_mut()?.remove("id");
+    Some((address, state))
+}
+
+/// Fetches the liquidity of the token pairs of the cached quotes on every new
+/// block and evicts the quotes whose route changed.
+fn spawn_invalidation_task(
+    inner: Weak<Inner>,
+    liquidity: infra::liquidity::Fetcher,
+    blocks: CurrentBlockWatcher,
+) {
+    tokio::task::spawn(
+        async move {
+            let mut stream = block_stream::into_stream(blocks);
+            while let Some(block) = stream.next().await {
+                let Some(inner) = inner.upgrade() else {
+                    tracing::debug!("quote cache no longer in use; terminate invalidation");
+                    break;
+                };
+                let pairs = inner.pairs();
+                if pairs.is_empty() {
+                    continue;
+                }
+                let fetched = liquidity.fetch(&pairs, AtBlock::Number(block.number)).await;
+                let evicted = inner.invalidate(fetched);
+                tracing::debug!(block = block.number, evicted, "invalidated cached quotes");
+            }
+        }
+        .instrument(tracing::info_span!("quote_cache")),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache() -> QuoteCache {
+        QuoteCache(Arc::new(Inner {
+            config: solver::QuoteCache {
+                max_entries: 2,
+                max_age: std::time::Duration::from_secs(60),
+                amount_precision_bits: 4,
+            },
+            entries: Default::default(),
+        }))
+    }
+
+    fn order(amount: u64) -> quote::Order {
+        quote::Order {
+            tokens: quote::Tokens::try_new(
+                eth::H160::from_low_u64_be(1).into(),
+                eth::H160::from_low_u64_be(2).into(),
+            )
+            .unwrap(),
+            amount: eth::U256::from(amount).into(),
+            side: order::Side::Sell,
+            deadline: Default::default(),
+        }
+    }
+
+    fn pool(address: u64, reserve: u64) -> liquidity::Liquidity {
+        let asset = |token: u64| eth::Asset {
+            token: eth::H160::from_low_u64_be(token).into(),
+            amount: eth::U256::from(reserve).into(),
+        };
+        liquidity::Liquidity {
+            id: liquidity::Id(0),
+            gas: eth::Gas(100_000.into()),
+            kind: liquidity::Kind::UniswapV2(liquidity::uniswap::v2::Pool {
+                address: eth::H160::from_low_u64_be(address).into(),
+                router: eth::H160::from_low_u64_be(10).into(),
+                reserves: liquidity::uniswap::v2::Reserves::try_new(asset(1), asset(2)).unwrap(),
+            }),
+        }
+    }
+
+    fn quote(interactions: usize) -> dto::Quote {
+        dto::Quote::new(quote::Quote {
+            clearing_prices: Default::default(),
+            pre_interactions: Vec::new(),
+            interactions: (0..interactions)
+                .map(|_| eth::Interaction {
+                    target: eth::H160::from_low_u64_be(3).into(),
+                    value: eth::Ether(0.into()),
+                    call_data: Default::default(),
+                })
+                .collect(),
+            solver: Default::default(),
+            gas: None,
+            tx_origin: None,
+            jit_orders: Vec::new(),
+            deadline_exceeded: false,
+            attestation: None,
+            route: None,
+        })
+    }
+
+    #[test]
+    fn serves_quotes_by_amount_bucket() {
+        let cache = cache();
+        cache.insert(
+            &order(0b1011_0000),
+            quote::Route(vec![pool(5, 100)]),
+            quote(0),
+        );
+        // quotes without amount specific data are served to the whole bucket
+        assert!(cache.get(&order(0b1011_0000)).is_some());
+        assert!(cache.get(&order(0b1011_0111)).is_some());
+        assert!(cache.get(&order(0b1100_0000)).is_none());
+
+        cache.insert(
+            &order(0b1100_0000),
+            quote::Route(vec![pool(5, 100)]),
+            quote(1),
+        );
+        // quotes with calldata are only served to the exact amount
+        assert!(cache.get(&order(0b1100_0000)).is_some());
+        assert!(cache.get(&order(0b1100_0001)).is_none());
+    }
+
+    #[test]
+    fn skips_quotes_with_unknown_routes() {
+        let cache = cache();
+        cache.insert(&order(1), quote::Route(Vec::new()), quote(0));
+        assert!(cache.get(&order(1)).is_none());
+    }
+
+    #[test]
+    fn evicts_oldest_quotes_when_full() {
+        let cache = cache();
+        for amount in [1, 2, 3] {
+            cache.insert(&order(amount), quote::Route(vec![pool(5, 100)]), quote(1));
+            // distinguishes the creation times of the entries
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+        assert!(cache.get(&order(1)).is_none());
+        assert!(cache.get(&order(2)).is_some());
+        assert!(cache.get(&order(3)).is_some());
+    }
+
+    #[test]
+    fn invalidates_quotes_of_changed_pools() {
+        let cache = cache();
+        cache.insert(&order(1), quote::Route(vec![pool(5, 100)]), quote(1));
+        cache.insert(&order(2), quote::Route(vec![pool(6, 100)]), quote(1));
+
+        // the first pool is unchanged and the second one changed state
+        let evicted = cache.0.invalidate(vec![pool(5, 100), pool(6, 200)]);
+        assert_eq!(evicted, 1);
+        assert!(cache.get(&order(1)).is_some());
+        assert!(cache.get(&order(2)).is_none());
+
+        // quotes through pools that disappeared get evicted too
+        assert_eq!(cache.0.invalidate(Vec::new()), 1);
+        assert!(cache.get(&order(1)).is_none());
+    }
+
+    #[test]
+    fn buckets_amounts_by_most_significant_bits() {
+        assert_eq!(bucket(0b1011_0111.into(), 4), 0b1011_0000.into());
+        assert_eq!(bucket(0b1011_1111.into(), 4), 0b1011_0000.into());
+        assert_eq!(bucket(0b1011.into(), 4), 0b1011.into());
+        assert_eq!(bucket(0b11.into(), 4), 0b11.into());
+        assert_eq!(bucket(0.into(), 4), 0.into());
+    }
+}
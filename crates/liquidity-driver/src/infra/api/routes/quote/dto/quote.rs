@@ -1,6 +1,7 @@
 use {
     crate::{
         domain::{self, competition::solution::encoding::codec, eth, quote},
+        infra::solver,
         util::serialize,
     },
     model::{
@@ -22,12 +23,23 @@ impl Quote {
             gas: quote.gas.map(|gas| gas.0.as_u64()),
             tx_origin: quote.tx_origin.map(|addr| addr.0),
             jit_orders: quote.jit_orders.into_iter().map(Into::into).collect(),
+            deadline_exceeded: quote.deadline_exceeded,
+            attestation: quote.attestation.map(Into::into),
         }
     }
+
+    /// Whether the quote is only valid for the quoted amount, because it
+    /// contains calldata or signed amounts for it.
+    pub fn is_amount_specific(&self) -> bool {
+        !self.pre_interactions.is_empty()
+            || !self.interactions.is_empty()
+            || !self.jit_orders.is_empty()
+            || self.attestation.is_some()
+    }
 }
 
 #[serde_as]
-#[derive(Debug, Serialize)]
+#[derive(Clone, Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Quote {
     #[serde_as(as = "HashMap<_, serialize::U256>")]
@@ -40,10 +52,46 @@ pub struct Quote {
     #[serde(skip_serializing_if = "Option::is_none")]
     tx_origin: Option<eth::H160>,
     jit_orders: Vec<JitOrder>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    deadline_exceeded: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    attestation: Option<Attestation>,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Attestation {
+    sell_token: eth::H160,
+    buy_token: eth::H160,
+    #[serde_as(as = "serialize::U256")]
+    sell_amount: eth::U256,
+    #[serde_as(as = "serialize::U256")]
+    buy_amount: eth::U256,
+    block_number: u64,
+    expiry: u64,
+    signer: eth::H160,
+    #[serde_as(as = "serialize::Hex")]
+    signature: [u8; 65],
+}
+
+impl From<solver::attestation::Attestation> for Attestation {
+    fn from(attestation: solver::attestation::Attestation) -> Self {
+        Self {
+            sell_token: attestation.sell.token.into(),
+            buy_token: attestation.buy.token.into(),
+            sell_amount: attestation.sell.amount.into(),
+            buy_amount: attestation.buy.amount.into(),
+            block_number: attestation.block,
+            expiry: attestation.expiry,
+            signer: attestation.signer.into(),
+            signature: attestation.signature,
+        }
+    }
 }
 
 #[serde_as]
-#[derive(Debug, Serialize)]
+#[derive(Clone, Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 struct Interaction {
     target: eth::H160,
@@ -64,7 +112,7 @@ impl From<eth::Interaction> for Interaction {
 }
 
 #[serde_as]
-#[derive(Debug, Serialize)]
+#[derive(Clone, Debug, Serialize)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 struct JitOrder {
     buy_token: eth::H160,
@@ -109,7 +157,7 @@ impl From<domain::competition::solution::trade::Jit> for JitOrder {
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Clone, Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 enum Side {
     Sell,
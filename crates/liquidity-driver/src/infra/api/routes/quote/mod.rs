@@ -1,14 +1,18 @@
 use {
     crate::infra::{
-        api::{Error, State},
+        api::{Error, State, request_deadline},
         observe,
     },
     tracing::Instrument,
 };
 
+mod cache;
 mod dto;
 
-pub use dto::OrderError;
+pub use {
+    cache::QuoteCache,
+    dto::{Order, OrderError},
+};
 
 pub(in crate::infra::api) fn quote(router: axum::Router<State>) -> axum::Router<State> {
     router.route("/quote", axum::routing::get(route))
@@ -16,13 +20,24 @@ pub(in crate::infra::api) fn quote(router: axum::Router<State>) -> axum::Router<
 
 async fn route(
     state: axum::extract::State<State>,
+    headers: axum::http::HeaderMap,
     order: axum::extract::Query<dto::Order>,
 ) -> Result<axum::Json<dto::Quote>, (hyper::StatusCode, axum::Json<Error>)> {
     let handle_request = async {
-        let order = order.0.into_domain().inspect_err(|err| {
+        let mut order = order.0.into_domain().inspect_err(|err| {
             observe::invalid_dto(err, "order");
         })?;
+        if let Some(deadline) = request_deadline(&headers) {
+            order.deadline = order.deadline.min(deadline);
+        }
         observe::quoting(&order);
+        if let Some(cache) = state.quote_cache() {
+            let cached = cache.get(&order);
+            observe::quote_cache_lookup(state.solver().name(), &order, cached.is_some());
+            if let Some(quote) = cached {
+                return Ok(axum::response::Json(quote));
+            }
+        }
         let quote = order
             .quote(
                 state.eth(),
@@ -32,7 +47,13 @@ async fn route(
             )
             .await;
         observe::quoted(state.solver().name(), &order, &quote);
-        Ok(axum::response::Json(dto::Quote::new(quote?)))
+        let mut quote = quote?;
+        let route = quote.route.take().filter(|_| !quote.deadline_exceeded);
+        let quote = dto::Quote::new(quote);
+        if let (Some(cache), Some(route)) = (state.quote_cache(), route) {
+            cache.insert(&order, route, quote.clone());
+        }
+        Ok(axum::response::Json(quote))
     };
 
     handle_request
@@ -0,0 +1,3 @@
+mod simulation;
+
+pub use simulation::Simulation;
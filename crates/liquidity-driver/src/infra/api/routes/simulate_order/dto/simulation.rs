@@ -0,0 +1,58 @@
+use {
+    crate::{
+        domain::{eth, quote},
+        util::serialize,
+    },
+    serde::Serialize,
+    serde_with::serde_as,
+    std::collections::HashMap,
+};
+
+impl Simulation {
+    pub fn new(simulation: quote::Simulation) -> Self {
+        Self {
+            sell_token: simulation.sell.token.into(),
+            buy_token: simulation.buy.token.into(),
+            expected: Amounts {
+                sell_amount: simulation.sell.amount.into(),
+                buy_amount: simulation.buy.amount.into(),
+            },
+            executed: Amounts {
+                sell_amount: simulation.executed.sold.into(),
+                buy_amount: simulation.executed.bought.into(),
+            },
+            gas: simulation.executed.gas.0.as_u64(),
+            solver: simulation.quote.solver.0,
+            clearing_prices: simulation.quote.clearing_prices,
+        }
+    }
+}
+
+#[serde_as]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Simulation {
+    sell_token: eth::H160,
+    buy_token: eth::H160,
+    /// The amounts traded at the clearing prices of the quote.
+    expected: Amounts,
+    /// The amounts the settlement contract spent and received in the
+    /// simulation.
+    executed: Amounts,
+    /// The gas used by the simulated settlement, excluding the transfers of
+    /// the order itself.
+    gas: u64,
+    solver: eth::H160,
+    #[serde_as(as = "HashMap<_, serialize::U256>")]
+    clearing_prices: HashMap<eth::H160, eth::U256>,
+}
+
+#[serde_as]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Amounts {
+    #[serde_as(as = "serialize::U256")]
+    sell_amount: eth::U256,
+    #[serde_as(as = "serialize::U256")]
+    buy_amount: eth::U256,
+}
@@ -0,0 +1,47 @@
+mod dto;
+
+use {
+    super::quote,
+    crate::infra::{
+        api::{Error, State, request_deadline},
+        observe,
+    },
+    tracing::Instrument,
+};
+
+/// Quotes a hypothetical order and simulates the execution of the quote, so
+/// that pre-trade analysis tools can learn the amounts an order would trade
+/// and the gas its settlement would use without placing it.
+pub(in crate::infra::api) fn simulate_order(router: axum::Router<State>) -> axum::Router<State> {
+    router.route("/simulate-order", axum::routing::post(route))
+}
+
+async fn route(
+    state: axum::extract::State<State>,
+    headers: axum::http::HeaderMap,
+    order: axum::Json<quote::Order>,
+) -> Result<axum::Json<dto::Simulation>, (hyper::StatusCode, axum::Json<Error>)> {
+    let handle_request = async {
+        let mut order = order.0.into_domain().inspect_err(|err| {
+            observe::invalid_dto(err, "order");
+        })?;
+        if let Some(deadline) = request_deadline(&headers) {
+            order.deadline = order.deadline.min(deadline);
+        }
+        observe::simulating_order(&order);
+        let simulation = order
+            .simulate(
+                state.eth(),
+                state.solver(),
+                state.liquidity(),
+                state.tokens(),
+            )
+            .await;
+        observe::simulated_order(state.solver().name(), &order, &simulation);
+        Ok(axum::Json(dto::Simulation::new(simulation?)))
+    };
+
+    handle_request
+        .instrument(tracing::info_span!("/simulate-order", solver = %state.solver().name()))
+        .await
+}
@@ -31,9 +31,16 @@ impl SolveRequest {
         tokens: &tokens::Fetcher,
         app_data: HashMap<Arc<AppDataHash>, Arc<app_data::ValidatedAppData>>,
     ) -> Result<competition::Auction, Error> {
+        // Only tokens whose metadata isn't fully part of the auction need to be
+        // fetched. Missing fields fall back to the fetched ones individually.
         let token_addresses: Vec<_> = self
             .tokens
             .iter()
+            .filter(|token| {
+                token.decimals.is_none()
+                    || token.symbol.is_none()
+                    || token.available_balance.is_none()
+            })
             .map(|token| token.address.into())
             .collect();
         let token_infos = tokens.get(&token_addresses).await;
@@ -159,11 +166,14 @@ impl SolveRequest {
             self.tokens.into_iter().map(|token| {
                 let info = token_infos.get(&token.address.into());
                 competition::auction::Token {
-                    decimals: info.and_then(|i| i.decimals),
-                    symbol: info.and_then(|i| i.symbol.clone()),
+                    decimals: token.decimals.or(info.and_then(|i| i.decimals)),
+                    symbol: token.symbol.or_else(|| info.and_then(|i| i.symbol.clone())),
                     address: token.address.into(),
                     price: token.price.map(Into::into),
-                    available_balance: info.map(|i| i.balance).unwrap_or(0.into()).into(),
+                    available_balance: token
+                        .available_balance
+                        .or(info.map(|i| i.balance.into()))
+                        .unwrap_or_default(),
                     trusted: token.trusted,
                 }
             }),
@@ -234,6 +244,15 @@ struct Token {
     #[serde_as(as = "Option<serialize::U256>")]
     pub price: Option<eth::U256>,
     pub trusted: bool,
+    #[serde(default)]
+    pub decimals: Option<u8>,
+    #[serde(default)]
+    pub symbol: Option<String>,
+    /// The balance of the token in the settlement contract, if known to the
+    /// auction.
+    #[serde_as(as = "Option<serialize::U256>")]
+    #[serde(default)]
+    pub available_balance: Option<eth::U256>,
 }
 
 #[serde_as]
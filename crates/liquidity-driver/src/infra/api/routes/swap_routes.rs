@@ -0,0 +1,138 @@
+use {
+    super::liquidity::convert_domain_to_dto,
+    crate::{
+        boundary::liquidity::routing,
+        domain::eth,
+        infra::{liquidity, observe},
+        util::serialize,
+    },
+    axum::{
+        Json,
+        extract::{Query, State},
+    },
+    hyper::StatusCode,
+    serde::{Deserialize, Serialize},
+    serde_with::serde_as,
+};
+
+/// The number of intermediate tokens routes have by default.
+const DEFAULT_MAX_HOPS: usize = 2;
+
+/// The most intermediate tokens routes can have. The number of candidate
+/// paths grows exponentially with it.
+const MAX_HOPS: usize = 3;
+
+/// Enumerates the candidate routes for selling an amount of a token for
+/// another over the cached liquidity, so that solvers can reuse the path
+/// finding of the driver instead of reimplementing it.
+pub(in crate::infra::api) fn swap_routes(
+    app: axum::Router<liquidity::Fetcher>,
+) -> axum::Router<liquidity::Fetcher> {
+    app.route("/api/v1/routes", axum::routing::get(route))
+}
+
+async fn route(
+    liquidity: State<liquidity::Fetcher>,
+    Query(query): Query<RoutesQuery>,
+) -> Result<Json<Routes>, StatusCode> {
+    if query.sell_token == query.buy_token || query.max_hops > MAX_HOPS {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let sell = eth::Asset {
+        token: query.sell_token.into(),
+        amount: query.amount.into(),
+    };
+    let routes = liquidity
+        .routes(sell, query.buy_token.into(), query.max_hops)
+        .await
+        .map_err(|err| {
+            tracing::warn!(?err, ?query, "failed to enumerate routes");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    Ok(Json(Routes {
+        routes: routes.into_iter().filter_map(Route::new).collect(),
+    }))
+}
+
+#[serde_as]
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RoutesQuery {
+    sell_token: eth::H160,
+    buy_token: eth::H160,
+    #[serde_as(as = "serialize::U256")]
+    amount: eth::U256,
+    #[serde(default = "default_max_hops")]
+    max_hops: usize,
+}
+
+fn default_max_hops() -> usize {
+    DEFAULT_MAX_HOPS
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Routes {
+    /// The routes, the one yielding the most buy tokens first.
+    routes: Vec<Route>,
+}
+
+#[serde_as]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Route {
+    #[serde_as(as = "serialize::U256")]
+    buy_amount: eth::U256,
+    #[serde_as(as = "serialize::U256")]
+    gas: eth::U256,
+    hops: Vec<Hop>,
+}
+
+impl Route {
+    /// Converts the route, skipping it if any of its liquidity can't be
+    /// converted.
+    fn new(route: routing::Route) -> Option<Self> {
+        Some(Self {
+            buy_amount: route.output().amount.0,
+            gas: route.gas().0,
+            hops: route
+                .hops
+                .into_iter()
+                .map(Hop::new)
+                .collect::<Option<_>>()?,
+        })
+    }
+}
+
+#[serde_as]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Hop {
+    sell_token: eth::H160,
+    #[serde_as(as = "serialize::U256")]
+    sell_amount: eth::U256,
+    buy_token: eth::H160,
+    #[serde_as(as = "serialize::U256")]
+    buy_amount: eth::U256,
+    #[serde_as(as = "serialize::U256")]
+    gas: eth::U256,
+    liquidity: solvers_dto::auction::Liquidity,
+}
+
+impl Hop {
+    fn new(hop: routing::Hop) -> Option<Self> {
+        let id = hop.liquidity.id;
+        let kind = (&hop.liquidity.kind).into();
+        let liquidity = convert_domain_to_dto(hop.liquidity)
+            .inspect_err(|err| observe::liquidity_conversion_failed(id, kind, err))
+            .ok()?;
+        Some(Self {
+            sell_token: hop.input.token.into(),
+            sell_amount: hop.input.amount.0,
+            buy_token: hop.output.token.into(),
+            buy_amount: hop.output.amount.0,
+            gas: hop.gas.0,
+            liquidity,
+        })
+    }
+}
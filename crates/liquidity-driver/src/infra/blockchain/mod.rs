@@ -11,7 +11,7 @@ use {
             BalanceOverriding,
         },
     },
-    std::{fmt, sync::Arc, time::Duration},
+    std::{collections::HashMap, fmt, sync::Arc, time::Duration},
     thiserror::Error,
     tracing::{Level, instrument},
     url::Url,
@@ -20,8 +20,9 @@ use {
 
 pub mod contracts;
 pub mod gas;
+pub mod swap;
 pub mod token;
-pub use self::{contracts::Contracts, gas::GasPriceEstimator};
+pub use self::{contracts::Contracts, gas::GasPriceEstimator, swap::SimulatedSwap};
 
 /// An Ethereum RPC connection.
 pub struct Rpc {
@@ -86,10 +87,12 @@ struct Inner {
     balance_simulator: BalanceSimulator,
     balance_overrider: Arc<dyn BalanceOverriding>,
     tx_gas_limit: U256,
+    token_quirks: HashMap<eth::TokenAddress, eth::allowance::Quirks>,
 }
 
 impl Ethereum {
-    /// Access the Ethereum blockchain through an RPC API.
+    /// Access the Ethereum blockchain through an RPC API. The configured token
+    /// quirks extend and override the well-known quirky tokens of the chain.
     ///
     /// # Panics
     ///
@@ -98,6 +101,7 @@ impl Ethereum {
     pub async fn new(
         rpc: Rpc,
         addresses: contracts::Addresses,
+        token_quirks: HashMap<eth::TokenAddress, eth::allowance::Quirks>,
         gas: Arc<GasPriceEstimator>,
         tx_gas_limit: U256,
         current_block_args: &shared::current_block::Arguments,
@@ -121,6 +125,9 @@ impl Ethereum {
             balance_overrider.clone(),
         );
 
+        let mut known_quirks = token::known_quirks(chain);
+        known_quirks.extend(token_quirks);
+
         Self {
             inner: Arc::new(Inner {
                 current_block: current_block_stream,
@@ -130,6 +137,7 @@ impl Ethereum {
                 balance_simulator,
                 balance_overrider,
                 tx_gas_limit,
+                token_quirks: known_quirks,
             }),
             web3,
         }
@@ -173,6 +181,23 @@ impl Ethereum {
         &self.inner.current_block
     }
 
+    /// Returns the number of the latest finalized block.
+    pub async fn finalized_block(&self) -> Result<eth::BlockNo, Error> {
+        let block = self
+            .web3
+            .eth()
+            .block(web3::types::BlockId::Number(
+                web3::types::BlockNumber::Finalized,
+            ))
+            .await?;
+        block
+            .and_then(|block| block.number)
+            .map(|number| eth::BlockNo(number.as_u64()))
+            .ok_or_else(|| {
+                web3::error::Error::InvalidResponse("missing finalized block".into()).into()
+            })
+    }
+
     /// Create access list used by a transaction.
     #[instrument(skip(self, tx), ret(level = Level::DEBUG))]
     pub async fn create_access_list<T>(&self, tx: T) -> Result<eth::AccessList, Error>
@@ -0,0 +1,145 @@
+use {
+    super::{Error, Ethereum},
+    crate::domain::eth,
+    contracts::alloy::{
+        GPv2Settlement,
+        support::{AnyoneAuthenticator, Solver},
+    },
+    ethcontract::state_overrides::{StateOverride, StateOverrides},
+    ethrpc::alloy::conversions::{IntoAlloy, IntoLegacy},
+    itertools::Itertools,
+    shared::price_estimation::trade_verifier::balance_overrides::BalanceOverrideRequest,
+    std::collections::HashMap,
+};
+
+/// The outcome of a simulated swap through the settlement contract.
+#[derive(Debug)]
+pub struct SimulatedSwap {
+    /// The amount of sell tokens the settlement contract spent.
+    pub sold: eth::TokenAmount,
+    /// The amount of buy tokens the settlement contract received.
+    pub bought: eth::TokenAmount,
+    /// The gas used by the settlement, excluding the transfers of the trade
+    /// itself.
+    pub gas: eth::Gas,
+}
+
+impl Ethereum {
+    /// Simulates a settlement executing the interactions of a swap for a
+    /// hypothetical order. As there is no signed order to trade, the settlement
+    /// contract gets funded with the sell amount through a balance override
+    /// instead, and the swap is measured by the balance changes of the
+    /// settlement contract. The solver gets impersonated with the `Solver`
+    /// support contract and authorized regardless of the allow list.
+    ///
+    /// Returns `None` if the sell token balance can't be overridden.
+    pub async fn simulate_swap(
+        &self,
+        solver: eth::Address,
+        sell: eth::Asset,
+        buy: eth::TokenAddress,
+        clearing_prices: &HashMap<eth::H160, eth::U256>,
+        pre_interactions: &[eth::Interaction],
+        interactions: &[eth::Interaction],
+    ) -> Result<Option<SimulatedSwap>, Error> {
+        let settlement = self.contracts().settlement();
+        let buffer = self
+            .erc20(sell.token)
+            .balance(settlement.address().into_legacy().into())
+            .await?;
+        let Some((token, balance_override)) = self
+            .balance_overrider()
+            .state_override(BalanceOverrideRequest {
+                token: sell.token.into(),
+                holder: settlement.address().into_legacy(),
+                amount: buffer.0.saturating_add(sell.amount.0),
+            })
+            .await
+        else {
+            return Ok(None);
+        };
+        let authenticator = settlement.authenticator().call().await?;
+        let overrides: StateOverrides = [
+            (token, balance_override),
+            (
+                authenticator.into_legacy(),
+                StateOverride {
+                    code: Some(
+                        AnyoneAuthenticator::AnyoneAuthenticator::DEPLOYED_BYTECODE
+                            .clone()
+                            .into_legacy(),
+                    ),
+                    ..Default::default()
+                },
+            ),
+            (
+                solver.0,
+                StateOverride {
+                    code: Some(Solver::Solver::DEPLOYED_BYTECODE.clone().into_legacy()),
+                    // Allow the simulation to proceed even if the solver holds no ETH.
+                    balance: Some(eth::U256::exp10(18)),
+                    ..Default::default()
+                },
+            ),
+        ]
+        .into_iter()
+        .collect();
+
+        let (tokens, prices) = clearing_prices
+            .iter()
+            .sorted_by_key(|(token, _)| **token)
+            .map(|(token, price)| (token.into_alloy(), price.into_alloy()))
+            .unzip();
+        let settle_call = settlement
+            .settle(
+                tokens,
+                prices,
+                vec![],
+                [
+                    pre_interactions.iter().map(interaction).collect(),
+                    interactions.iter().map(interaction).collect(),
+                    vec![],
+                ],
+            )
+            .calldata()
+            .clone();
+
+        let Solver::Solver::swapReturn {
+            gasUsed,
+            queriedBalances,
+        } = Solver::Instance::new(solver.0.into_alloy(), self.web3.alloy.clone())
+            .swap(
+                *settlement.address(),
+                vec![sell.token.0.0.into_alloy(), buy.0.0.into_alloy()],
+                solver.0.into_alloy(),
+                settle_call,
+            )
+            .from(solver.0.into_alloy())
+            .gas(self.inner.tx_gas_limit.as_u64())
+            .call()
+            .overrides(overrides.into_alloy())
+            .await?;
+
+        // The settlement balances of the tokens get stored before and after the
+        // settlement.
+        let [sell_before, buy_before, sell_after, buy_after] = queriedBalances[..] else {
+            return Err(web3::error::Error::Decoder(
+                "unexpected number of queried balances".into(),
+            )
+            .into());
+        };
+        Ok(Some(SimulatedSwap {
+            sold: sell_before.saturating_sub(sell_after).into_legacy().into(),
+            bought: buy_after.saturating_sub(buy_before).into_legacy().into(),
+            gas: eth::Gas(gasUsed.into_legacy()),
+        }))
+    }
+}
+
+fn interaction(interaction: &eth::Interaction) -> GPv2Settlement::GPv2Interaction::Data {
+    GPv2Settlement::GPv2Interaction::Data {
+        target: interaction.target.0.into_alloy(),
+        value: interaction.value.0.into_alloy(),
+        callData: interaction.call_data.0.clone().into(),
+    }
+}
@@ -1,10 +1,13 @@
 use {
     super::{Error, Ethereum},
     crate::domain::eth,
+    chain::Chain,
     ethrpc::alloy::{
         conversions::{IntoAlloy, IntoLegacy},
         errors::ContractErrorExt,
     },
+    hex_literal::hex,
+    std::collections::HashMap,
 };
 
 /// An ERC-20 token.
@@ -12,6 +15,7 @@ use {
 /// https://eips.ethereum.org/EIPS/eip-20
 pub struct Erc20 {
     token: contracts::alloy::ERC20::Instance,
+    quirks: eth::allowance::Quirks,
 }
 
 impl Erc20 {
@@ -21,6 +25,12 @@ impl Erc20 {
                 address.0.0.into_alloy(),
                 eth.web3.alloy.clone(),
             ),
+            quirks: eth
+                .inner
+                .token_quirks
+                .get(&address)
+                .copied()
+                .unwrap_or_default(),
         }
     }
 
@@ -29,6 +39,11 @@ impl Erc20 {
         self.token.address().into_legacy().into()
     }
 
+    /// Returns the deviations of the token from EIP-20.
+    pub fn quirks(&self) -> eth::allowance::Quirks {
+        self.quirks
+    }
+
     /// Fetch the ERC20 allowance for the spender. See the allowance method in
     /// EIP-20.
     ///
@@ -89,3 +104,33 @@ impl Erc20 {
             .map_err(Into::into)
     }
 }
+
+/// Returns the well-known tokens of the chain that deviate from EIP-20 in ways
+/// that affect the encoding of interactions.
+pub(super) fn known_quirks(chain: Chain) -> HashMap<eth::TokenAddress, eth::allowance::Quirks> {
+    let tokens: &[([u8; 20], eth::allowance::Quirks)] = match chain {
+        Chain::Mainnet => &[
+            // Tether USD
+            (
+                hex!("dac17f958d2ee523a2206206994597c13d831ec7"),
+                eth::allowance::Quirks {
+                    approval_race: true,
+                    no_return_value: true,
+                },
+            ),
+            // Kyber Network Crystal (legacy)
+            (
+                hex!("dd974d5c2e2928dea5f71b9825b8b646686bd200"),
+                eth::allowance::Quirks {
+                    approval_race: true,
+                    no_return_value: false,
+                },
+            ),
+        ],
+        _ => &[],
+    };
+    tokens
+        .iter()
+        .map(|(address, quirks)| (eth::H160(*address).into(), *quirks))
+        .collect()
+}
@@ -32,6 +32,11 @@ pub struct Args {
     #[clap(long, env, default_value = "false")]
     pub use_json_logs: bool,
 
+    /// The format of the logs, one of `pretty`, `json`, `json-gcp` and
+    /// `json-datadog`. Takes precedence over `--use-json-logs`.
+    #[clap(long, env)]
+    pub log_format: Option<observe::LogFormat>,
+
     /// The node RPC API endpoint.
     #[clap(long, env)]
     pub ethrpc: Url,
@@ -50,3 +55,37 @@ pub struct Args {
     #[clap(long, env)]
     pub config: PathBuf,
 }
+
+/// Serve recorded liquidity responses instead of fetching liquidity, for
+/// developing and testing solvers without RPC or subgraph access.
+#[derive(Debug, clap::Parser)]
+pub struct MockArgs {
+    /// The directory with the recorded liquidity responses. Each file holds
+    /// the response body for a token pair and is named after it, e.g.
+    /// `<token a>-<token b>.json`.
+    #[clap(long, env)]
+    pub mock: PathBuf,
+
+    /// The address to bind the mock driver to.
+    #[clap(long, env, default_value = "0.0.0.0:11088")]
+    pub addr: SocketAddr,
+
+    /// The log filter.
+    #[clap(long, env, default_value = "warn,liquidity_driver=debug")]
+    pub log: String,
+}
+
+/// Validate a configuration file without starting the driver. Contract
+/// deployments are resolved for the configured chain and the effective
+/// configuration gets printed.
+#[derive(Debug, clap::Parser)]
+#[command(name = "check-config")]
+pub struct CheckConfigArgs {
+    /// Path to the driver configuration file.
+    pub config: PathBuf,
+
+    /// The chain to check the configuration for if the configuration file
+    /// does not specify a `chain-id`.
+    #[clap(long, env)]
+    pub chain_id: Option<u64>,
+}
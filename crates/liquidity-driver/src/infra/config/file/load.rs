@@ -15,10 +15,67 @@ use {
     chain::Chain,
     futures::future::join_all,
     number::conversions::big_decimal_to_big_rational,
+    shared::{
+        event_handling::Backfill,
+        sources::{chain_profile::ChainProfile, rate_provider_cache},
+    },
     std::path::Path,
     tokio::fs,
 };
 
+/// Validates the driver configuration in a TOML file without connecting to a
+/// node, returning the resolved configuration. The chain is read from the
+/// `chain-id` of the file, falling back to the specified chain.
+///
+/// # Panics
+///
+/// This method panics if the config is invalid, if no chain is known, or on
+/// I/O errors.
+pub async fn check(path: &Path, chain: Option<Chain>) -> infra::Config {
+    let data = fs::read_to_string(path)
+        .await
+        .unwrap_or_else(|e| panic!("I/O error while reading {path:?}: {e:?}"));
+    let chain = toml::de::from_str::<toml::Table>(&data)
+        .ok()
+        .and_then(|table| table.get("chain-id")?.as_integer())
+        .map(|id| {
+            u64::try_from(id)
+                .ok()
+                .and_then(|id| Chain::try_from(id).ok())
+                .expect("unsupported chain ID")
+        })
+        .or(chain)
+        .expect("no chain to check the configuration for: set `chain-id` or pass `--chain-id`");
+
+    let config = load(chain, path).await;
+    let graph_urls = config
+        .liquidity
+        .uniswap_v3
+        .iter()
+        .map(|uniswap| &uniswap.graph_url)
+        .chain(
+            config
+                .liquidity
+                .balancer_v2
+                .iter()
+                .map(|balancer| &balancer.graph_url),
+        )
+        .chain(
+            config
+                .liquidity
+                .balancer_v3
+                .iter()
+                .map(|balancer| &balancer.graph_url),
+        );
+    for url in graph_urls {
+        assert!(
+            matches!(url.scheme(), "http" | "https") && url.host().is_some(),
+            "invalid subgraph URL {url}: must be an HTTP(S) URL with a host"
+        );
+    }
+    config
+}
+
 /// Load the driver configuration from a TOML file for the specifed Ethereum
 /// network.
 ///
@@ -49,6 +106,12 @@ pub async fn load(chain: Chain, path: &Path) -> infra::Config {
         chain,
         "The configured chain ID does not match the connected Ethereum node"
     );
+    let profile = ChainProfile::for_chain(chain.id());
+    let balancer_api_url = |graph_url: Option<reqwest::Url>| {
+        graph_url
+            .or_else(|| profile?.balancer_api_url())
+            .expect("no default Balancer API for current network: set `graph-url`")
+    };
     infra::Config {
         solvers: join_all(config.solvers.into_iter().map(|solver_config| async move {
             let account = match solver_config.account {
@@ -142,17 +205,34 @@ pub async fn load(chain: Chain, path: &Path) -> infra::Config {
                     file::AtBlock::Latest => liquidity::AtBlock::Latest,
                     file::AtBlock::Finalized => liquidity::AtBlock::Finalized,
                 },
+                quote_attestor: solver_config.quote_attestation.map(|attestation| {
+                    solver::attestation::Attestor::new(
+                        secp256k1::SecretKey::from_slice(&attestation.private_key.0)
+                            .expect("invalid quote attestation private key"),
+                        attestation.validity,
+                    )
+                }),
+                max_concurrent_auctions: solver_config.max_concurrent_auctions,
+                quote_cache: solver_config
+                    .quote_cache
+                    .map(|quote_cache| solver::QuoteCache {
+                        max_entries: quote_cache.max_entries,
+                        max_age: quote_cache.max_age,
+                        amount_precision_bits: quote_cache.amount_precision_bits,
+                    }),
             }
         }))
         .await,
         liquidity: liquidity::Config {
-            base_tokens: config
-                .liquidity
-                .base_tokens
-                .iter()
-                .copied()
-                .map(eth::TokenAddress::from)
-                .collect(),
+            base_tokens: match &config.liquidity.base_tokens {
+                Some(base_tokens) => base_tokens.clone(),
+                None => profile
+                    .map(|profile| profile.base_tokens.to_vec())
+                    .unwrap_or_default(),
+            }
+            .into_iter()
+            .map(eth::TokenAddress::from)
+            .collect(),
             uniswap_v2: config
                 .liquidity
                 .uniswap_v2
@@ -186,7 +266,16 @@ pub async fn load(chain: Chain, path: &Path) -> infra::Config {
                         missing_pool_cache_time,
                     } => liquidity::config::UniswapV2 {
                         router: router.into(),
-                        pool_code: pool_code.into(),
+                        pools: liquidity::config::UniswapV2Pools::Derived(pool_code.into()),
+                        missing_pool_cache_time,
+                    },
+                    file::UniswapV2Config::PoolList {
+                        router,
+                        pool_list,
+                        missing_pool_cache_time,
+                    } => liquidity::config::UniswapV2 {
+                        router: router.into(),
+                        pools: liquidity::config::UniswapV2Pools::Listed(pool_list),
                         missing_pool_cache_time,
                     },
                 })
@@ -260,6 +349,65 @@ pub async fn load(chain: Chain, path: &Path) -> infra::Config {
                     },
                 })
                 .collect(),
+            uniswap_v4: config
+                .liquidity
+                .uniswap_v4
+                .iter()
+                .cloned()
+                .map(|config| match config {
+                    file::UniswapV4Config::Preset {
+                        preset,
+                        allowed_hooks,
+                        reinit_interval,
+                    } => liquidity::config::UniswapV4 {
+                        allowed_hooks: allowed_hooks.into_iter().collect(),
+                        reinit_interval,
+                        ..match preset {
+                            file::UniswapV4Preset::UniswapV4 => {
+                                liquidity::config::UniswapV4::uniswap_v4(chain)
+                            }
+                        }
+                        .expect("no Uniswap V4 preset for current network")
+                    },
+                    file::UniswapV4Config::Manual {
+                        pool_manager,
+                        deployment_block,
+                        router,
+                        permit2,
+                        allowed_hooks,
+                        reinit_interval,
+                    } => liquidity::config::UniswapV4 {
+                        pool_manager: pool_manager.into(),
+                        deployment_block,
+                        router: router.into(),
+                        permit2: permit2.into(),
+                        allowed_hooks: allowed_hooks.into_iter().collect(),
+                        reinit_interval,
+                    },
+                })
+                .collect(),
+            maverick_v2: config
+                .liquidity
+                .maverick_v2
+                .iter()
+                .cloned()
+                .map(|config| liquidity::config::MaverickV2 {
+                    factory: config.factory.into(),
+                    deployment_block: config.deployment_block,
+                    router: config.router.into(),
+                    reinit_interval: config.reinit_interval,
+                })
+                .collect(),
+            curve: config
+                .liquidity
+                .curve
+                .iter()
+                .cloned()
+                .map(|config| liquidity::config::Curve {
+                    registry: config.registry.into(),
+                    reinit_interval: config.reinit_interval,
+                })
+                .collect(),
             balancer_v2: config
                 .liquidity
                 .balancer_v2
@@ -269,15 +417,24 @@ pub async fn load(chain: Chain, path: &Path) -> infra::Config {
                     file::BalancerV2Config::Preset {
                         preset,
                         pool_deny_list,
+                        min_tvl,
+                        keep_pools,
                         graph_url,
                         reinit_interval,
-                        ..
+                        multicall_batch_size,
                     } => liquidity::config::BalancerV2 {
                         pool_deny_list: pool_deny_list.clone(),
+                        min_tvl,
+                        keep_pools,
                         reinit_interval,
+                        multicall_batch_size,
                         ..match preset {
                             file::BalancerV2Preset::BalancerV2 => {
-                                liquidity::config::BalancerV2::balancer_v2(&graph_url, chain, None)
+                                liquidity::config::BalancerV2::balancer_v2(
+                                    &balancer_api_url(graph_url),
+                                    chain,
+                                    None,
+                                )
                             }
                         }
                         .expect("no Balancer V2 preset for current network")
@@ -295,8 +452,11 @@ pub async fn load(chain: Chain, path: &Path) -> infra::Config {
                             gyro_2clp: manual_config.gyro_2clp.clone(),
                             gyro_3clp: manual_config.gyro_3clp.clone(),
                             pool_deny_list: manual_config.pool_deny_list.clone(),
+                            min_tvl: manual_config.min_tvl,
+                            keep_pools: manual_config.keep_pools.clone(),
                             graph_url: manual_config.graph_url.clone(),
                             reinit_interval: manual_config.reinit_interval,
+                            multicall_batch_size: manual_config.multicall_batch_size,
                         }
                     }
                 })
@@ -312,13 +472,20 @@ pub async fn load(chain: Chain, path: &Path) -> infra::Config {
                         pool_deny_list,
                         graph_url,
                         reinit_interval,
-                        ..
+                        token_decimals,
+                        multicall_batch_size,
                     } => liquidity::config::BalancerV3 {
                         pool_deny_list: pool_deny_list.clone(),
                         reinit_interval,
+                        token_decimals,
+                        multicall_batch_size,
                         ..match preset {
                             file::BalancerV3Preset::BalancerV3 => {
-                                liquidity::config::BalancerV3::balancer_v3(&graph_url, chain, None)
+                                liquidity::config::BalancerV3::balancer_v3(
+                                    &balancer_api_url(graph_url),
+                                    chain,
+                                    None,
+                                )
                             }
                         }
                         .expect("no Balancer V3 preset for current network")
@@ -335,10 +502,13 @@ pub async fn load(chain: Chain, path: &Path) -> infra::Config {
                             gyro_e,
                             gyro_2clp,
                             reclamm,
+                            reclamm_v1,
                             quantamm,
                             pool_deny_list,
                             graph_url,
                             reinit_interval,
+                            token_decimals,
+                            multicall_batch_size,
                         } = manual_config.as_ref();
 
                         liquidity::config::BalancerV3 {
@@ -384,6 +554,11 @@ pub async fn load(chain: Chain, path: &Path) -> infra::Config {
                                 .cloned()
                                 .map(eth::ContractAddress::from)
                                 .collect(),
+                            reclamm_v1: reclamm_v1
+                                .iter()
+                                .cloned()
+                                .map(eth::ContractAddress::from)
+                                .collect(),
                             quantamm: quantamm
                                 .iter()
                                 .cloned()
@@ -392,10 +567,13 @@ pub async fn load(chain: Chain, path: &Path) -> infra::Config {
                             pool_deny_list: pool_deny_list.clone(),
                             graph_url: graph_url.clone(),
                             reinit_interval: *reinit_interval,
+                            token_decimals: token_decimals.clone(),
+                            multicall_batch_size: *multicall_batch_size,
                         }
                     }
                 })
                 .collect(),
+            balancer_v3_gas_costs: config.liquidity.balancer_v3_gas_costs,
             zeroex: config
                 .liquidity
                 .zeroex
@@ -404,6 +582,25 @@ pub async fn load(chain: Chain, path: &Path) -> infra::Config {
                     api_key: config.api_key,
                     http_timeout: config.http_timeout,
                 }),
+            prefetch: config
+                .liquidity
+                .prefetch
+                .map(|config| liquidity::config::Prefetch {
+                    auctions: config.auctions,
+                    max_pairs: config.max_pairs,
+                }),
+            pool_bundle: config.liquidity.pool_bundle,
+            pool_snapshots: config.liquidity.pool_snapshots,
+            event_backfill: Backfill {
+                max_blocks_per_request: config.liquidity.event_backfill.max_blocks_per_request,
+                request_interval: config.liquidity.event_backfill.request_interval,
+            },
+            rate_provider_cache: config.liquidity.rate_provider_cache.map(|config| {
+                rate_provider_cache::Config {
+                    ttl: config.ttl,
+                    max_age_blocks: config.max_age_blocks,
+                }
+            }),
         },
         liquidity_sources_notifier: config.liquidity_sources_notifier.map(|notifier| {
             notify::liquidity_sources::config::Config {
@@ -495,6 +692,19 @@ pub async fn load(chain: Chain, path: &Path) -> infra::Config {
                 .collect(),
             flashloan_router: config.contracts.flashloan_router.map(Into::into),
         },
+        token_quirks: config
+            .token_quirks
+            .into_iter()
+            .map(|quirks| {
+                (
+                    quirks.token.into(),
+                    eth::allowance::Quirks {
+                        approval_race: quirks.approval_race,
+                        no_return_value: quirks.no_return_value,
+                    },
+                )
+            })
+            .collect(),
         disable_access_list_simulation: config.disable_access_list_simulation,
         disable_gas_simulation: config.disable_gas_simulation.map(Into::into),
         gas_estimator: config.gas_estimator,
@@ -502,5 +712,18 @@ pub async fn load(chain: Chain, path: &Path) -> infra::Config {
         simulation_bad_token_max_age: config.simulation_bad_token_max_age,
         app_data_fetching: config.app_data_fetching,
         tx_gas_limit: config.tx_gas_limit,
+        listeners: config
+            .listen
+            .into_iter()
+            .map(|listener| shared::listen::Listener {
+                address: listener.address,
+                dual_stack: listener.dual_stack,
+                tls: listener.tls.map(|tls| shared::listen::Tls {
+                    certificate: tls.certificate,
+                    private_key: tls.private_key,
+                    client_ca: tls.client_ca,
+                }),
+            })
+            .collect(),
     }
 }
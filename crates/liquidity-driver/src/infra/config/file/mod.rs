@@ -1,4 +1,4 @@
-pub use load::load;
+pub use load::{check, load};
 use {
     crate::{domain::eth, infra, util::serialize},
     alloy::primitives::Address,
@@ -6,8 +6,9 @@ use {
     reqwest::Url,
     serde::{Deserialize, Deserializer, Serialize},
     serde_with::serde_as,
+    shared::sources::balancer_v3::swap::gas::PoolKind,
     solver::solver::Arn,
-    std::{collections::HashMap, time::Duration},
+    std::{collections::HashMap, num::NonZeroUsize, path::PathBuf, time::Duration},
 };
 
 mod load;
@@ -45,6 +46,11 @@ struct Config {
     #[serde(default)]
     contracts: ContractsConfig,
 
+    /// Tokens deviating from EIP-20 in ways that affect the encoding of
+    /// interactions, in addition to the well-known ones of the chain.
+    #[serde(default)]
+    token_quirks: Vec<TokenQuirksConfig>,
+
     /// Use Tenderly for transaction simulation.
     tenderly: Option<TenderlyConfig>,
 
@@ -86,6 +92,47 @@ struct Config {
 
     #[serde_as(as = "HexOrDecimalU256")]
     tx_gas_limit: eth::U256,
+
+    /// The listeners to serve the API on. If none are configured, the API is
+    /// served on the `--addr` address.
+    #[serde(default)]
+    listen: Vec<ListenerConfig>,
+}
+
+#[serde_as]
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct ListenerConfig {
+    /// The socket address to listen on, e.g. `0.0.0.0:11088` or `[::]:11088`,
+    /// or the path of a unix domain socket prefixed with `unix:`.
+    #[serde_as(as = "serde_with::DisplayFromStr")]
+    address: shared::listen::Address,
+
+    /// Whether listening on an IPv6 address also accepts IPv4 connections.
+    #[serde(default = "default_dual_stack")]
+    dual_stack: bool,
+
+    /// Terminates TLS on the listener.
+    tls: Option<TlsConfig>,
+}
+
+fn default_dual_stack() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct TlsConfig {
+    /// The PEM file holding the certificate chain of the server.
+    certificate: PathBuf,
+
+    /// The PEM file holding the private key of the certificate.
+    private_key: PathBuf,
+
+    /// Requires clients to authenticate with a certificate issued by one of
+    /// the certificate authorities in this PEM file, e.g. to only serve
+    /// liquidity to the solvers of the deployment.
+    client_ca: Option<PathBuf>,
 }
 
 #[serde_as]
@@ -294,6 +341,76 @@ struct SolverConfig {
     /// before the driver starts dropping new `/solve` requests.
     #[serde(default = "default_settle_queue_size")]
     settle_queue_size: usize,
+
+    /// Signs the quotes of this solver with an operator key, attesting the
+    /// quoted amounts in the quote responses.
+    #[serde(default)]
+    quote_attestation: Option<QuoteAttestation>,
+
+    /// Serves repeated quotes of this solver from a cache until the state of
+    /// a pool they are routed through changes. Quotes of solvers attesting
+    /// their quotes don't get cached.
+    #[serde(default)]
+    quote_cache: Option<QuoteCache>,
+
+    /// The maximum number of auctions solved concurrently. Further auctions,
+    /// e.g. when replaying or solving auctions in batches, get queued and are
+    /// solved in the order of their expected surplus. Unbounded by default.
+    #[serde(default)]
+    max_concurrent_auctions: Option<NonZeroUsize>,
+}
+
+#[serde_as]
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct QuoteAttestation {
+    /// The private key of the operator signing the quotes. Expects a 32-byte
+    /// hex encoded string.
+    private_key: eth::H256,
+
+    /// How long the solver stands by its attested quotes.
+    #[serde(
+        with = "humantime_serde",
+        default = "default_quote_attestation_validity"
+    )]
+    validity: Duration,
+}
+
+fn default_quote_attestation_validity() -> Duration {
+    Duration::from_secs(60)
+}
+
+#[serde_as]
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct QuoteCache {
+    /// The maximum number of cached quotes.
+    #[serde(default = "default_quote_cache_max_entries")]
+    max_entries: usize,
+
+    /// How long quotes get served from the cache at most, even if the state
+    /// of the pools they are routed through doesn't change.
+    #[serde(with = "humantime_serde", default = "default_quote_cache_max_age")]
+    max_age: Duration,
+
+    /// The number of most significant bits of the quoted amount that quotes
+    /// get cached by. Orders with amounts that only differ in the remaining
+    /// bits get the same quote, unless the quote contains calldata or signed
+    /// amounts for the exact quoted amount.
+    #[serde(default = "default_quote_cache_amount_precision_bits")]
+    amount_precision_bits: u32,
+}
+
+fn default_quote_cache_max_entries() -> usize {
+    1000
+}
+
+fn default_quote_cache_max_age() -> Duration {
+    Duration::from_secs(60)
+}
+
+fn default_quote_cache_amount_precision_bits() -> u32 {
+    32
 }
 
 #[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
@@ -386,6 +503,23 @@ struct ContractsConfig {
     flashloan_router: Option<eth::H160>,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct TokenQuirksConfig {
+    /// The address of the token.
+    token: eth::H160,
+
+    /// Whether the token reverts when changing a non-zero allowance to another
+    /// non-zero amount, requiring allowances to be reset to 0 first.
+    #[serde(default)]
+    approval_race: bool,
+
+    /// Whether the `transfer`, `transferFrom` and `approve` methods of the
+    /// token don't return a boolean.
+    #[serde(default)]
+    no_return_value: bool,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "kebab-case", deny_unknown_fields)]
 pub struct CowAmmConfig {
@@ -433,9 +567,10 @@ struct EnsoConfig {
 #[serde(rename_all = "kebab-case", deny_unknown_fields)]
 struct LiquidityConfig {
     /// Additional tokens for which liquidity is always fetched, regardless of
-    /// whether or not the token appears in the auction.
+    /// whether or not the token appears in the auction. Defaults to the base
+    /// tokens of the chain.
     #[serde(default)]
-    base_tokens: Vec<eth::H160>,
+    base_tokens: Option<Vec<eth::H160>>,
 
     /// Liquidity provided by a Uniswap V2 compatible contract.
     #[serde(default)]
@@ -449,6 +584,18 @@ struct LiquidityConfig {
     #[serde(default)]
     uniswap_v3: Vec<UniswapV3Config>,
 
+    /// Liquidity provided by a Uniswap V4 compatible pool manager.
+    #[serde(default)]
+    uniswap_v4: Vec<UniswapV4Config>,
+
+    /// Liquidity provided by a Maverick V2 compatible pool factory.
+    #[serde(default)]
+    maverick_v2: Vec<MaverickV2Config>,
+
+    /// Liquidity provided by the StableSwap pools of a Curve registry.
+    #[serde(default)]
+    curve: Vec<CurveConfig>,
+
     /// Liquidity provided by a Balancer V2 compatible contract.
     #[serde(default)]
     balancer_v2: Vec<BalancerV2Config>,
@@ -457,6 +604,12 @@ struct LiquidityConfig {
     #[serde(default)]
     balancer_v3: Vec<BalancerV3Config>,
 
+    /// The gas used by swaps through the different kinds of Balancer V3 pools
+    /// on the chain, replacing the default costs. They are refined with the
+    /// gas measured for settlements swapping through the pools.
+    #[serde(default)]
+    balancer_v3_gas_costs: HashMap<PoolKind, usize>,
+
     /// Liquidity provided by 0x API.
     #[serde(default)]
     zeroex: Option<ZeroExConfig>,
@@ -465,6 +618,101 @@ struct LiquidityConfig {
     /// requests.
     #[serde(default)]
     fetch_at_block: AtBlock,
+
+    /// Prefetch liquidity for the token pairs of recent auctions on every new
+    /// block.
+    #[serde(default)]
+    prefetch: Option<PrefetchConfig>,
+
+    /// The path of a pool bundle, as exported by the `/balancer-pools`
+    /// endpoint of another instance, to initialize the Balancer pool
+    /// registries with instead of querying the Balancer API.
+    #[serde(default)]
+    pool_bundle: Option<PathBuf>,
+
+    /// The path of an embedded store the Balancer pool registries save
+    /// snapshots of their pools to, and resume from on restarts instead of
+    /// initializing the pools and replaying their creation events again.
+    #[serde(default)]
+    pool_snapshots: Option<PathBuf>,
+
+    /// Limits on fetching the pool creation events of the history, which the
+    /// Balancer pool registries do for many blocks when indexing a newly
+    /// added factory.
+    #[serde(default)]
+    event_backfill: EventBackfillConfig,
+
+    /// Cache the rates of the rate providers of Balancer pools, which are
+    /// otherwise fetched again for every pool state.
+    #[serde(default)]
+    rate_provider_cache: Option<RateProviderCacheConfig>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct PrefetchConfig {
+    /// The number of most recent auctions whose token pairs get prefetched.
+    #[serde(default = "default_prefetch_auctions")]
+    auctions: NonZeroUsize,
+
+    /// The maximum number of token pairs to prefetch per block, bounding the
+    /// RPC load caused by prefetching.
+    #[serde(default = "default_prefetch_max_pairs")]
+    max_pairs: usize,
+}
+
+fn default_prefetch_auctions() -> NonZeroUsize {
+    NonZeroUsize::new(3).unwrap()
+}
+
+fn default_prefetch_max_pairs() -> usize {
+    500
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct EventBackfillConfig {
+    /// The most blocks whose events are fetched with a single request.
+    #[serde(default = "default_backfill_max_blocks_per_request")]
+    max_blocks_per_request: u64,
+
+    /// How long to wait between the requests, to stay within the rate limits
+    /// of the node.
+    #[serde(default, with = "humantime_serde")]
+    request_interval: Duration,
+}
+
+impl Default for EventBackfillConfig {
+    fn default() -> Self {
+        Self {
+            max_blocks_per_request: default_backfill_max_blocks_per_request(),
+            request_interval: Duration::ZERO,
+        }
+    }
+}
+
+fn default_backfill_max_blocks_per_request() -> u64 {
+    500
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct RateProviderCacheConfig {
+    /// How long a fetched rate is served from the cache.
+    #[serde(default = "default_rate_provider_cache_ttl", with = "humantime_serde")]
+    ttl: Duration,
+
+    /// Rates of blocks this many blocks behind the current block get evicted.
+    #[serde(default = "default_rate_provider_cache_max_age_blocks")]
+    max_age_blocks: u64,
+}
+
+fn default_rate_provider_cache_ttl() -> Duration {
+    Duration::from_secs(60)
+}
+
+fn default_rate_provider_cache_max_age_blocks() -> u64 {
+    5
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -487,6 +735,24 @@ enum UniswapV2Config {
         #[serde(with = "humantime_serde")]
         missing_pool_cache_time: Duration,
     },
+
+    #[serde(rename_all = "kebab-case")]
+    PoolList {
+        /// The address of the Uniswap V2 compatible router contract.
+        router: eth::H160,
+
+        /// Path to a JSON list of the exchange's pools, for exchanges where
+        /// pool addresses can't be derived from the token pair. Only listed
+        /// pools that are verified on-chain to belong to the router's factory
+        /// are used.
+        pool_list: PathBuf,
+
+        /// How long liquidity should not be fetched for a token pair that
+        /// didn't return useful liquidity before allowing to fetch it
+        /// again.
+        #[serde(with = "humantime_serde")]
+        missing_pool_cache_time: Duration,
+    },
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -602,6 +868,82 @@ mod uniswap_v3 {
     }
 }
 
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged, deny_unknown_fields)]
+enum UniswapV4Config {
+    #[serde(rename_all = "kebab-case")]
+    Preset {
+        preset: UniswapV4Preset,
+
+        /// Hooks contracts that get called on swaps but are known to be safe
+        /// to route through.
+        #[serde(default)]
+        allowed_hooks: Vec<eth::H160>,
+
+        /// How often the liquidity source should be reinitialized.
+        #[serde(with = "humantime_serde", default = "default_reinit_interval")]
+        reinit_interval: Option<Duration>,
+    },
+
+    #[serde(rename_all = "kebab-case")]
+    Manual {
+        /// Address of the Uniswap V4 PoolManager contract.
+        pool_manager: eth::H160,
+
+        /// The block at which to start indexing the pools of the PoolManager.
+        deployment_block: u64,
+
+        /// Address of the UniversalRouter contract.
+        router: eth::H160,
+
+        /// Address of the Permit2 contract.
+        permit2: eth::H160,
+
+        /// Hooks contracts that get called on swaps but are known to be safe
+        /// to route through.
+        #[serde(default)]
+        allowed_hooks: Vec<eth::H160>,
+
+        /// How often the liquidity source should be reinitialized.
+        #[serde(with = "humantime_serde", default = "default_reinit_interval")]
+        reinit_interval: Option<Duration>,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+enum UniswapV4Preset {
+    UniswapV4,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct MaverickV2Config {
+    /// Address of the Maverick V2 pool factory contract.
+    factory: eth::H160,
+
+    /// The block at which to start indexing the pools of the factory.
+    deployment_block: u64,
+
+    /// Address of the Maverick V2 router contract.
+    router: eth::H160,
+
+    /// How often the liquidity source should be reinitialized.
+    #[serde(with = "humantime_serde", default = "default_reinit_interval")]
+    reinit_interval: Option<Duration>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct CurveConfig {
+    /// Address of the Curve registry contract listing the pools.
+    registry: eth::H160,
+
+    /// How often the liquidity source should be reinitialized.
+    #[serde(with = "humantime_serde", default = "default_reinit_interval")]
+    reinit_interval: Option<Duration>,
+}
+
 #[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 struct ManualBalancerV2Config {
@@ -647,6 +989,15 @@ struct ManualBalancerV2Config {
     #[serde(default)]
     pool_deny_list: Vec<eth::H256>,
 
+    /// The minimum USD value of the balances, as reported by the Balancer
+    /// API, for a pool to be indexed at initialization.
+    #[serde(default)]
+    min_tvl: Option<f64>,
+
+    /// Balancer V2 pools that are indexed regardless of `min-tvl`.
+    #[serde(default)]
+    keep_pools: Vec<eth::H256>,
+
     /// The URL used to connect to balancer v2 subgraph client.
     graph_url: Url,
 
@@ -654,6 +1005,12 @@ struct ManualBalancerV2Config {
     /// access to new pools.
     #[serde(with = "humantime_serde", default = "default_reinit_interval")]
     reinit_interval: Option<Duration>,
+
+    /// The most vault calls to aggregate into a single Multicall3 call when
+    /// fetching the pool balances. Without it, the balances of the pools are
+    /// fetched with separate calls each.
+    #[serde(default)]
+    multicall_batch_size: Option<NonZeroUsize>,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -667,13 +1024,30 @@ enum BalancerV2Config {
         #[serde(default)]
         pool_deny_list: Vec<eth::H256>,
 
-        /// The URL used to connect to balancer v2 subgraph client.
-        graph_url: Url,
+        /// The minimum USD value of the balances, as reported by the Balancer
+        /// API, for a pool to be indexed at initialization.
+        #[serde(default)]
+        min_tvl: Option<f64>,
+
+        /// Balancer V2 pools that are indexed regardless of `min-tvl`.
+        #[serde(default)]
+        keep_pools: Vec<eth::H256>,
+
+        /// The URL used to connect to balancer v2 subgraph client. Defaults
+        /// to the Balancer API.
+        #[serde(default)]
+        graph_url: Option<Url>,
 
         /// How often the liquidity source should be reinitialized to get
         /// access to new pools.
         #[serde(with = "humantime_serde", default = "default_reinit_interval")]
         reinit_interval: Option<Duration>,
+
+        /// The most vault calls to aggregate into a single Multicall3 call
+        /// when fetching the pool balances. Without it, the balances of the
+        /// pools are fetched with separate calls each.
+        #[serde(default)]
+        multicall_batch_size: Option<NonZeroUsize>,
     },
 
     #[serde(rename_all = "kebab-case")]
@@ -729,6 +1103,11 @@ struct ManualBalancerV3Config {
     #[serde(default)]
     reclamm: Vec<eth::H160>,
 
+    /// The ReClamm V1 pool factory contract addresses (only supported on
+    /// Balancer V3).
+    #[serde(default)]
+    reclamm_v1: Vec<eth::H160>,
+
     /// The QuantAMM pool factory contract addresses (only supported on
     /// Balancer V3).
     #[serde(default)]
@@ -745,6 +1124,17 @@ struct ManualBalancerV3Config {
     /// access to new pools.
     #[serde(with = "humantime_serde", default = "default_reinit_interval")]
     reinit_interval: Option<Duration>,
+
+    /// Decimals to use for non-standard tokens instead of the ones reported
+    /// by the token contracts when deriving pool scaling factors.
+    #[serde(default)]
+    token_decimals: HashMap<eth::H160, u8>,
+
+    /// The most vault calls to aggregate into a single Multicall3 call when
+    /// fetching the pool states. Without it, the states of the pools are
+    /// fetched with separate calls each.
+    #[serde(default)]
+    multicall_batch_size: Option<NonZeroUsize>,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -758,13 +1148,26 @@ enum BalancerV3Config {
         #[serde(default)]
         pool_deny_list: Vec<eth::H160>,
 
-        /// The URL used to connect to balancer v3 subgraph client.
-        graph_url: Url,
+        /// The URL used to connect to balancer v3 subgraph client. Defaults
+        /// to the Balancer API.
+        #[serde(default)]
+        graph_url: Option<Url>,
 
         /// How often the liquidity source should be reinitialized to get
         /// access to new pools.
         #[serde(with = "humantime_serde", default = "default_reinit_interval")]
         reinit_interval: Option<Duration>,
+
+        /// Decimals to use for non-standard tokens instead of the ones
+        /// reported by the token contracts when deriving pool scaling factors.
+        #[serde(default)]
+        token_decimals: HashMap<eth::H160, u8>,
+
+        /// The most vault calls to aggregate into a single Multicall3 call
+        /// when fetching the pool states. Without it, the states of the pools
+        /// are fetched with separate calls each.
+        #[serde(default)]
+        multicall_batch_size: Option<NonZeroUsize>,
     },
 
     Manual(Box<ManualBalancerV3Config>),
@@ -11,7 +11,7 @@ use {
             solver,
         },
     },
-    std::time::Duration,
+    std::{collections::HashMap, time::Duration},
 };
 
 pub mod file;
@@ -28,8 +28,11 @@ pub struct Config {
     pub gas_estimator: GasEstimatorType,
     pub mempools: Vec<mempool::Config>,
     pub contracts: blockchain::contracts::Addresses,
+    pub token_quirks: HashMap<eth::TokenAddress, eth::allowance::Quirks>,
     pub order_priority_strategies: Vec<OrderPriorityStrategy>,
     pub simulation_bad_token_max_age: Duration,
     pub app_data_fetching: AppDataFetching,
     pub tx_gas_limit: eth::U256,
+    /// The listeners to serve the API on, replacing the `--addr` address.
+    pub listeners: Vec<shared::listen::Listener>,
 }
@@ -10,15 +10,27 @@ use {
     ethrpc::alloy::conversions::IntoLegacy,
     hex_literal::hex,
     reqwest::Url,
-    shared::sources::uniswap_v2::{
-        BAOSWAP_INIT,
-        HONEYSWAP_INIT,
-        SUSHISWAP_INIT,
-        SWAPR_INIT,
-        TESTNET_UNISWAP_INIT,
-        UNISWAP_INIT,
+    shared::{
+        event_handling::Backfill,
+        sources::{
+            balancer_v3::swap::gas::PoolKind,
+            rate_provider_cache,
+            uniswap_v2::{
+                BAOSWAP_INIT,
+                HONEYSWAP_INIT,
+                SUSHISWAP_INIT,
+                SWAPR_INIT,
+                TESTNET_UNISWAP_INIT,
+                UNISWAP_INIT,
+            },
+        },
+    },
+    std::{
+        collections::{HashMap, HashSet},
+        num::NonZeroUsize,
+        path::PathBuf,
+        time::Duration,
     },
-    std::{collections::HashSet, time::Duration},
 };
 
 /// Configuration options for liquidity fetching.
@@ -40,6 +52,17 @@ pub struct Config {
     /// for.
     pub uniswap_v3: Vec<UniswapV3>,
 
+    /// The collection of Uniswap V4 compatible pool managers to fetch
+    /// liquidity for.
+    pub uniswap_v4: Vec<UniswapV4>,
+
+    /// The collection of Maverick V2 compatible pool factories to fetch
+    /// liquidity for.
+    pub maverick_v2: Vec<MaverickV2>,
+
+    /// The collection of Curve registries to fetch the StableSwap pools of.
+    pub curve: Vec<Curve>,
+
     /// The collection of Balancer V2 compatible exchanges to fetch liquidity
     /// for.
     pub balancer_v2: Vec<BalancerV2>,
@@ -48,18 +71,47 @@ pub struct Config {
     /// for.
     pub balancer_v3: Vec<BalancerV3>,
 
+    /// The gas used by swaps through the different kinds of Balancer V3
+    /// pools, replacing the default costs.
+    pub balancer_v3_gas_costs: HashMap<PoolKind, usize>,
+
     /// 0x liquidity fetcher.
     pub zeroex: Option<ZeroEx>,
+
+    /// Prefetching of liquidity for the token pairs of recent auctions.
+    pub prefetch: Option<Prefetch>,
+
+    /// A pool bundle exported by another instance to initialize the Balancer
+    /// pool registries with instead of querying the Balancer API.
+    pub pool_bundle: Option<PathBuf>,
+
+    /// An embedded store to snapshot the Balancer pool registries to and
+    /// resume them from on restarts.
+    pub pool_snapshots: Option<PathBuf>,
+
+    /// Limits on fetching the pool creation events of the history.
+    pub event_backfill: Backfill,
+
+    /// Caching of the rates of the rate providers of Balancer pools.
+    pub rate_provider_cache: Option<rate_provider_cache::Config>,
 }
 
-/// Uniswap V2 (and Uniswap V2 clone) liquidity fetching options.
+/// Options for prefetching liquidity on new blocks.
 #[derive(Clone, Copy, Debug)]
+pub struct Prefetch {
+    /// The number of most recent auctions whose token pairs get prefetched.
+    pub auctions: NonZeroUsize,
+    /// The maximum number of token pairs to prefetch per block.
+    pub max_pairs: usize,
+}
+
+/// Uniswap V2 (and Uniswap V2 clone) liquidity fetching options.
+#[derive(Clone, Debug)]
 pub struct UniswapV2 {
     /// The address of the Uniswap V2 compatible router contract.
     pub router: eth::ContractAddress,
-    /// The digest of the pool initialization code. This digest is used for
-    /// computing the deterministic pool addresses per token pair.
-    pub pool_code: eth::CodeDigest,
+    /// How the pool addresses of the exchange are determined.
+    pub pools: UniswapV2Pools,
     /// How long liquidity should not be fetched for a token pair that didn't
     /// return useful liquidity before allowing to fetch it again.
     pub missing_pool_cache_time: Duration,
@@ -73,7 +125,7 @@ impl UniswapV2 {
             router: ContractAddress::from(
                 contracts::alloy::UniswapV2Router02::deployment_address(&chain.id())?.into_legacy(),
             ),
-            pool_code: UNISWAP_INIT.into(),
+            pools: UniswapV2Pools::Derived(UNISWAP_INIT.into()),
             missing_pool_cache_time: Duration::from_secs(60 * 60),
         })
     }
@@ -84,7 +136,7 @@ impl UniswapV2 {
             router: ContractAddress::from(
                 contracts::alloy::SushiSwapRouter::deployment_address(&chain.id())?.into_legacy(),
             ),
-            pool_code: SUSHISWAP_INIT.into(),
+            pools: UniswapV2Pools::Derived(SUSHISWAP_INIT.into()),
             missing_pool_cache_time: Duration::from_secs(60 * 60),
         })
     }
@@ -95,7 +147,7 @@ impl UniswapV2 {
             router: ContractAddress::from(
                 contracts::alloy::BaoswapRouter::deployment_address(&chain.id())?.into_legacy(),
             ),
-            pool_code: HONEYSWAP_INIT.into(),
+            pools: UniswapV2Pools::Derived(HONEYSWAP_INIT.into()),
             missing_pool_cache_time: Duration::from_secs(60 * 60),
         })
     }
@@ -106,7 +158,7 @@ impl UniswapV2 {
             router: ContractAddress::from(
                 contracts::alloy::BaoswapRouter::deployment_address(&chain.id())?.into_legacy(),
             ),
-            pool_code: BAOSWAP_INIT.into(),
+            pools: UniswapV2Pools::Derived(BAOSWAP_INIT.into()),
             missing_pool_cache_time: Duration::from_secs(60 * 60),
         })
     }
@@ -122,7 +174,7 @@ impl UniswapV2 {
             router: ContractAddress::from(
                 contracts::alloy::PancakeRouter::deployment_address(&chain.id())?.into_legacy(),
             ),
-            pool_code,
+            pools: UniswapV2Pools::Derived(pool_code),
             missing_pool_cache_time: Duration::from_secs(60 * 60),
         })
     }
@@ -135,12 +187,22 @@ impl UniswapV2 {
                 contracts::alloy::TestnetUniswapV2Router02::deployment_address(&chain.id())?
                     .into_legacy(),
             ),
-            pool_code: TESTNET_UNISWAP_INIT.into(),
+            pools: UniswapV2Pools::Derived(TESTNET_UNISWAP_INIT.into()),
             missing_pool_cache_time: Duration::from_secs(60 * 60),
         })
     }
 }
 
+/// How the pools of a Uniswap V2 compatible exchange are found.
+#[derive(Clone, Debug)]
+pub enum UniswapV2Pools {
+    /// Pool addresses are derived from the token pair using the digest of the
+    /// pool initialization code.
+    Derived(eth::CodeDigest),
+    /// Pools are taken from the JSON pool list at the specified path.
+    Listed(PathBuf),
+}
+
 /// Swapr (Uniswap V2 clone with a twist) liquidity fetching options.
 #[derive(Clone, Copy, Debug)]
 pub struct Swapr {
@@ -214,6 +276,79 @@ impl UniswapV3 {
     }
 }
 
+/// Uniswap V4 liquidity fetching options.
+#[derive(Clone, Debug)]
+pub struct UniswapV4 {
+    /// The address of the Uniswap V4 PoolManager contract.
+    pub pool_manager: eth::ContractAddress,
+
+    /// The block at which to start indexing the pools of the PoolManager.
+    pub deployment_block: u64,
+
+    /// The address of the UniversalRouter contract executing the swaps.
+    pub router: eth::ContractAddress,
+
+    /// The address of the Permit2 contract through which the router pulls
+    /// the input tokens.
+    pub permit2: eth::ContractAddress,
+
+    /// Hooks contracts that get called on swaps but are known to be safe to
+    /// route through. Pools with other hooks that get called on swaps are
+    /// ignored.
+    pub allowed_hooks: HashSet<eth::H160>,
+
+    /// How often the liquidity source should be reinitialized.
+    pub reinit_interval: Option<Duration>,
+}
+
+impl UniswapV4 {
+    /// Returns the liquidity configuration for Uniswap V4.
+    #[expect(clippy::self_named_constructors)]
+    pub fn uniswap_v4(chain: Chain) -> Option<Self> {
+        let chain = chain.id();
+        Some(Self {
+            pool_manager: contracts::alloy::UniswapV4PoolManager::deployment_address(&chain)?
+                .into_legacy()
+                .into(),
+            deployment_block: contracts::alloy::UniswapV4PoolManager::deployment_block(&chain)?,
+            router: contracts::alloy::UniswapV4UniversalRouter::deployment_address(&chain)?
+                .into_legacy()
+                .into(),
+            permit2: contracts::alloy::Permit2::deployment_address(&chain)?
+                .into_legacy()
+                .into(),
+            allowed_hooks: Default::default(),
+            reinit_interval: None,
+        })
+    }
+}
+
+/// Maverick V2 liquidity fetching options.
+#[derive(Clone, Debug)]
+pub struct MaverickV2 {
+    /// The address of the Maverick V2 pool factory contract.
+    pub factory: eth::ContractAddress,
+
+    /// The block at which to start indexing the pools of the factory.
+    pub deployment_block: u64,
+
+    /// The address of the Maverick V2 router contract executing the swaps.
+    pub router: eth::ContractAddress,
+
+    /// How often the liquidity source should be reinitialized.
+    pub reinit_interval: Option<Duration>,
+}
+
+/// Curve liquidity fetching options.
+#[derive(Clone, Debug)]
+pub struct Curve {
+    /// The address of the Curve registry contract listing the pools.
+    pub registry: eth::ContractAddress,
+
+    /// How often the liquidity source should be reinitialized.
+    pub reinit_interval: Option<Duration>,
+}
+
 /// Balancer V2 liquidity fetching options.
 #[derive(Clone, Debug)]
 pub struct BalancerV2 {
@@ -251,12 +386,25 @@ pub struct BalancerV2 {
     /// ignored.
     pub pool_deny_list: Vec<eth::H256>,
 
+    /// The minimum USD value of the balances, as reported by the Balancer
+    /// API, for a pool to be indexed. Mainnet has thousands of dust pools
+    /// that only grow the registry and the cost of refreshing pool state.
+    pub min_tvl: Option<f64>,
+
+    /// Pools that are indexed regardless of `min_tvl`.
+    pub keep_pools: Vec<eth::H256>,
+
     /// The base URL used to connect to balancer v2 subgraph client.
     pub graph_url: Url,
 
     /// How often the liquidty source should be re-initialized to become
     /// aware of new pools.
     pub reinit_interval: Option<Duration>,
+
+    /// The most vault calls aggregated into a single Multicall3 call when
+    /// fetching the pool balances, or `None` to fetch them with separate
+    /// calls.
+    pub multicall_batch_size: Option<NonZeroUsize>,
 }
 
 impl BalancerV2 {
@@ -316,8 +464,11 @@ impl BalancerV2 {
             gyro_2clp: address_for!(chain, [contracts::alloy::BalancerV2Gyro2CLPPoolFactory]),
             gyro_3clp: address_for!(chain, [contracts::alloy::BalancerV2Gyro3CLPPoolFactory]),
             pool_deny_list: Vec::new(),
+            min_tvl: None,
+            keep_pools: Vec::new(),
             graph_url: graph_url.clone(),
             reinit_interval: None,
+            multicall_batch_size: None,
         })
     }
 }
@@ -355,6 +506,9 @@ pub struct BalancerV3 {
     /// ReClamm pool factory addresses.
     pub reclamm: Vec<eth::ContractAddress>,
 
+    /// ReClamm V1 pool factory addresses.
+    pub reclamm_v1: Vec<eth::ContractAddress>,
+
     /// QuantAMM pool factory addresses.
     pub quantamm: Vec<eth::ContractAddress>,
 
@@ -371,6 +525,14 @@ pub struct BalancerV3 {
     /// How often the liquidty source should be re-initialized to become
     /// aware of new pools.
     pub reinit_interval: Option<Duration>,
+
+    /// Decimals overriding the ones reported by non-standard token contracts
+    /// when deriving pool scaling factors.
+    pub token_decimals: HashMap<eth::H160, u8>,
+
+    /// The most vault calls aggregated into a single Multicall3 call when
+    /// fetching the pool states, or `None` to fetch them with separate calls.
+    pub multicall_batch_size: Option<NonZeroUsize>,
 }
 
 impl BalancerV3 {
@@ -416,12 +578,17 @@ impl BalancerV3 {
             reclamm: factory_addresses(
                 &[contracts::BalancerV3ReClammPoolFactoryV2::raw_contract()],
             ),
+            reclamm_v1: factory_addresses(&[
+                contracts::BalancerV3ReClammPoolFactory::raw_contract(),
+            ]),
             quantamm: factory_addresses(&[
                 contracts::BalancerV3QuantAMMWeightedPoolFactory::raw_contract(),
             ]),
             pool_deny_list: Vec::new(),
             graph_url: graph_url.clone(),
             reinit_interval: None,
+            token_decimals: Default::default(),
+            multicall_batch_size: None,
         })
     }
 }
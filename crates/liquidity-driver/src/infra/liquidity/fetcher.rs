@@ -1,16 +1,52 @@
 use {
     crate::{
         boundary,
-        domain::liquidity,
+        domain::{eth, liquidity},
         infra::{self, blockchain::Ethereum, observe},
     },
-    std::{collections::HashSet, sync::Arc},
+    ethrpc::block_stream::{self, CurrentBlockWatcher},
+    futures::StreamExt,
+    std::{
+        collections::{HashSet, VecDeque},
+        sync::{Arc, Mutex, Weak},
+    },
+    tracing::Instrument,
 };
 
 /// Fetch liquidity for auctions to be sent to solver engines.
 #[derive(Clone, Debug)]
 pub struct Fetcher {
     inner: Arc<boundary::liquidity::Fetcher>,
+    recent: Option<Arc<Mutex<RecentPairs>>>,
+}
+
+/// The token pairs of the most recent auctions.
+#[derive(Debug)]
+struct RecentPairs {
+    auctions: VecDeque<HashSet<liquidity::TokenPair>>,
+    config: infra::liquidity::config::Prefetch,
+}
+
+impl RecentPairs {
+    fn record(&mut self, pairs: &HashSet<liquidity::TokenPair>) {
+        if self.auctions.len() == self.config.auctions.get() {
+            self.auctions.pop_front();
+        }
+        self.auctions.push_back(pairs.clone());
+    }
+
+    /// Returns the token pairs to prefetch, preferring the pairs of the most
+    /// recent auctions when exceeding the configured limit.
+    fn pairs(&self) -> HashSet<liquidity::TokenPair> {
+        let mut pairs = HashSet::new();
+        for pair in self.auctions.iter().rev().flatten() {
+            if pairs.len() == self.config.max_pairs {
+                break;
+            }
+            pairs.insert(*pair);
+        }
+        pairs
+    }
 }
 
 /// Specifies at which block liquidity should be fetched.
@@ -31,6 +67,9 @@ pub enum AtBlock {
     /// Useful for chains that can't fetch liquidity on non-finalized
     /// blocks(e.g. Avalanche).
     Finalized,
+    /// Fetches liquidity for the state of the blockchain at the specified
+    /// block, so that the block the liquidity was read at is known exactly.
+    Number(u64),
 }
 
 impl Fetcher {
@@ -38,10 +77,20 @@ impl Fetcher {
     /// configuration.
     pub async fn try_new(eth: &Ethereum, config: &infra::liquidity::Config) -> Result<Self, Error> {
         let eth = eth.with_metric_label("liquidity".into());
-        let inner = boundary::liquidity::Fetcher::try_new(&eth, config).await?;
-        Ok(Self {
-            inner: Arc::new(inner),
-        })
+        let inner = Arc::new(boundary::liquidity::Fetcher::try_new(&eth, config).await?);
+        let recent = config.prefetch.map(|config| {
+            let recent = Arc::new(Mutex::new(RecentPairs {
+                auctions: VecDeque::with_capacity(config.auctions.get()),
+                config,
+            }));
+            spawn_prefetch_task(
+                Arc::downgrade(&inner),
+                recent.clone(),
+                eth.current_block().clone(),
+            );
+            recent
+        });
+        Ok(Self { inner, recent })
     }
 
     /// Fetches all relevant liquidity for the specified token pairs. Handles
@@ -52,6 +101,13 @@ impl Fetcher {
         block: AtBlock,
     ) -> Vec<liquidity::Liquidity> {
         observe::fetching_liquidity();
+        // Quotes are not worth keeping fresh on every block, so only track the
+        // token pairs of auctions.
+        if let Some(recent) = &self.recent
+            && !matches!(block, AtBlock::Recent)
+        {
+            recent.lock().unwrap().record(pairs);
+        }
         match self.inner.fetch(pairs, block).await {
             Ok(liquidity) => {
                 observe::fetched_liquidity(&liquidity);
@@ -63,6 +119,67 @@ impl Fetcher {
             }
         }
     }
+
+    /// Fetches the latest state of the Balancer pool with the specified pool
+    /// ID or address for inspection. Returns `None` if the pool is unknown.
+    pub async fn fetch_pool(
+        &self,
+        id: &str,
+    ) -> Result<Option<boundary::liquidity::balancer::PoolInspection>, Error> {
+        Ok(self.inner.fetch_pool(id).await?)
+    }
+
+    /// Enumerates the routes for selling the asset for the buy token over the
+    /// recently cached liquidity, with up to `max_hops` intermediate base
+    /// tokens. The best route comes first.
+    pub async fn routes(
+        &self,
+        sell: eth::Asset,
+        buy: eth::TokenAddress,
+        max_hops: usize,
+    ) -> Result<Vec<boundary::liquidity::routing::Route>, Error> {
+        Ok(self.inner.routes(sell, buy, max_hops).await?)
+    }
+
+    /// Exports the indexed Balancer pools as a bundle that other instances
+    /// can get initialized with.
+    pub fn balancer_pools(&self) -> boundary::liquidity::balancer::PoolBundle {
+        self.inner.balancer_pools()
+    }
+}
+
+/// Refreshes the liquidity of the token pairs of recent auctions on every new
+/// block. This only warms the liquidity caches so that auctions arriving
+/// later in the block don't have to wait for fetching the state of hot pools.
+fn spawn_prefetch_task(
+    inner: Weak<boundary::liquidity::Fetcher>,
+    recent: Arc<Mutex<RecentPairs>>,
+    blocks: CurrentBlockWatcher,
+) {
+    tokio::task::spawn(
+        async move {
+            let mut stream = block_stream::into_stream(blocks);
+            while let Some(block) = stream.next().await {
+                let Some(inner) = inner.upgrade() else {
+                    tracing::debug!("liquidity fetcher no longer in use; terminate prefetching");
+                    break;
+                };
+                let pairs = recent.lock().unwrap().pairs();
+                if pairs.is_empty() {
+                    continue;
+                }
+                tracing::debug!(
+                    block = block.number,
+                    pairs = pairs.len(),
+                    "prefetching liquidity"
+                );
+                if let Err(err) = inner.fetch(&pairs, AtBlock::Latest).await {
+                    tracing::warn!(?err, "failed to prefetch liquidity");
+                }
+            }
+        }
+        .instrument(tracing::info_span!("liquidity_prefetch")),
+    );
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -70,3 +187,40 @@ pub enum Error {
     #[error("boundary error: {0:?}")]
     Boundary(#[from] boundary::Error),
 }
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::num::NonZeroUsize};
+
+    fn pair(a: u64, b: u64) -> liquidity::TokenPair {
+        liquidity::TokenPair::try_new(
+            eth::H160::from_low_u64_be(a).into(),
+            eth::H160::from_low_u64_be(b).into(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn tracks_pairs_of_recent_auctions() {
+        let mut recent = RecentPairs {
+            auctions: Default::default(),
+            config: infra::liquidity::config::Prefetch {
+                auctions: NonZeroUsize::new(2).unwrap(),
+                max_pairs: 3,
+            },
+        };
+
+        recent.record(&[pair(1, 2)].into());
+        recent.record(&[pair(2, 3), pair(3, 4)].into());
+        assert_eq!(recent.pairs(), [pair(1, 2), pair(2, 3), pair(3, 4)].into());
+
+        // Only the last two auctions are considered.
+        recent.record(&[pair(4, 5)].into());
+        assert_eq!(recent.pairs(), [pair(2, 3), pair(3, 4), pair(4, 5)].into());
+
+        // Pairs of the most recent auctions are preferred when exceeding the
+        // limit.
+        recent.record(&[pair(5, 6), pair(6, 7), pair(7, 8)].into());
+        assert_eq!(recent.pairs(), [pair(5, 6), pair(6, 7), pair(7, 8)].into());
+    }
+}
@@ -18,6 +18,9 @@ pub struct Metrics {
     /// The results of the quoting process.
     #[metric(labels("solver", "result"))]
     pub quotes: prometheus::IntCounterVec,
+    /// Quote cache hits and misses.
+    #[metric(labels("solver", "result"))]
+    pub quote_cache: prometheus::IntCounterVec,
     /// The results of the mempool submission.
     #[metric(labels("mempool", "result"))]
     pub mempool_submission: prometheus::IntCounterVec,
@@ -25,6 +28,9 @@ pub struct Metrics {
     /// atempted and the error detection.
     #[metric(labels("mempool", "result"))]
     pub mempool_submission_results_blocks_passed: prometheus::IntCounterVec,
+    /// Liquidity skipped because it could not be converted to its DTO.
+    #[metric(labels("kind", "reason"))]
+    pub liquidity_conversion_failures: prometheus::IntCounterVec,
     /// How many tokens detected by specific solver and strategy.
     #[metric(labels("solver", "strategy"))]
     pub bad_tokens_detected: prometheus::IntCounterVec,
@@ -16,11 +16,12 @@ use {
                 solution::{self, Settlement},
             },
             eth::{self, Gas},
+            liquidity,
             mempools::{self, SubmissionSuccess},
             quote::{self, Quote},
             time::{Deadline, Remaining},
         },
-        infra::solver,
+        infra::{api::routes::LiquidityConversionError, solver},
         util::http,
     },
     ethrpc::block_stream::BlockInfo,
@@ -59,6 +60,20 @@ pub fn fetched_liquidity(liquidity: &[Liquidity]) {
     tracing::debug!(liquidity = ?grouped, "fetched liquidity sources");
 }
 
+/// Observe that liquidity was skipped because it could not be converted to
+/// its DTO.
+pub fn liquidity_conversion_failed(
+    id: liquidity::Id,
+    kind: &'static str,
+    err: &LiquidityConversionError,
+) {
+    tracing::warn!(?id, kind, ?err, "failed to convert liquidity, skipping");
+    metrics::get()
+        .liquidity_conversion_failures
+        .with_label_values(&[kind, err.reason()])
+        .inc();
+}
+
 /// Observe that fetching liquidity failed.
 pub fn fetching_liquidity_failed(err: &boundary::Error) {
     tracing::warn!(?err, "failed to fetch liquidity");
@@ -313,10 +328,11 @@ pub fn solver_response(
             tracing::warn!(%endpoint, ?err, "failed to receive response from solver")
         }
     }
-    metrics::get()
-        .used_solve_time
-        .with_label_values(&[solver])
-        .observe(compute_time.as_secs_f64());
+    // Slow solves are linked to their traces.
+    observe::openmetrics::observe(
+        &metrics::get().used_solve_time.with_label_values(&[solver]),
+        compute_time.as_secs_f64(),
+    );
 }
 
 /// Observe the result of mempool transaction execution.
@@ -406,6 +422,32 @@ pub fn quoting(order: &quote::Order) {
     tracing::trace!(?order, "quoting");
 }
 
+/// Observe that the simulation of an order is about to start.
+pub fn simulating_order(order: &quote::Order) {
+    tracing::trace!(?order, "simulating order");
+}
+
+/// Observe the result of simulating an order.
+pub fn simulated_order(
+    solver: &solver::Name,
+    order: &quote::Order,
+    result: &Result<quote::Simulation, quote::SimulationError>,
+) {
+    match result {
+        Ok(simulation) => tracing::info!(%solver, ?order, ?simulation, "simulated order"),
+        Err(err) => tracing::warn!(%solver, ?order, ?err, "failed to simulate order"),
+    }
+}
+
+/// Observe the lookup of a quote in the quote cache of a solver.
+pub fn quote_cache_lookup(solver: &solver::Name, order: &quote::Order, hit: bool) {
+    tracing::trace!(?order, hit, "quote cache lookup");
+    metrics::get()
+        .quote_cache
+        .with_label_values(&[solver.as_str(), if hit { "hits" } else { "misses" }])
+        .inc();
+}
+
 fn competition_error(err: &competition::Error) -> &'static str {
     match err {
         competition::Error::SolutionNotAvailable => "SolutionNotAvailable",
@@ -78,6 +78,11 @@ impl Simulator {
         self.disable_gas = Some(fixed_gas);
     }
 
+    /// Whether the gas estimates are simulated rather than fixed.
+    pub fn simulates_gas(&self) -> bool {
+        self.disable_gas.is_none()
+    }
+
     /// Simulate the access list needed by a transaction. If the transaction
     /// already has an access list, the returned access list will be a
     /// superset of the existing one.
@@ -0,0 +1,181 @@
+//! Signed attestations of the quotes of a solver.
+//!
+//! A solver can be configured with an operator key to attest the quotes it
+//! returns. An attestation commits to the quoted pair and amounts, the block
+//! the quote was computed at and until when the solver stands by it, which
+//! allows downstream systems to hold the solver accountable for its quoted
+//! prices. Attestations are EIP-712 signatures in a domain bound to the chain
+//! and its settlement contract, so they can't be replayed on other chains.
+
+use {
+    crate::domain::eth,
+    model::signature::{EcdsaSignature, EcdsaSigningScheme},
+    secp256k1::SecretKey,
+    std::{
+        fmt,
+        sync::LazyLock,
+        time::{Duration, SystemTime, UNIX_EPOCH},
+    },
+    web3::{
+        ethabi::{Token, encode},
+        signing::{self, Key, SecretKeyRef},
+    },
+};
+
+/// Signs quotes with the key of the solver's operator.
+#[derive(Clone)]
+pub struct Attestor {
+    key: SecretKey,
+    validity: Duration,
+}
+
+impl Attestor {
+    /// Creates an attestor signing with the specified key. Attested quotes
+    /// expire after `validity`.
+    pub fn new(key: SecretKey, validity: Duration) -> Self {
+        Self { key, validity }
+    }
+
+    /// The address of the operator key.
+    pub fn signer(&self) -> eth::Address {
+        SecretKeyRef::new(&self.key).address().into()
+    }
+
+    /// Attests that the solver quoted trading `sell` for `buy` at the
+    /// specified block.
+    pub fn attest(
+        &self,
+        domain: &eth::DomainSeparator,
+        sell: eth::Asset,
+        buy: eth::Asset,
+        block: u64,
+    ) -> Attestation {
+        let expiry = (SystemTime::now() + self.validity)
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let mut attestation = Attestation {
+            sell,
+            buy,
+            block,
+            expiry,
+            signer: self.signer(),
+            signature: [0; 65],
+        };
+        attestation.signature = EcdsaSignature::sign(
+            EcdsaSigningScheme::Eip712,
+            &model::DomainSeparator(domain.0),
+            &attestation.hash(),
+            SecretKeyRef::new(&self.key),
+        )
+        .to_bytes();
+        attestation
+    }
+}
+
+impl fmt::Debug for Attestor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Attestor")
+            .field("signer", &self.signer())
+            .field("validity", &self.validity)
+            .finish()
+    }
+}
+
+/// A quote signed by the solver's operator.
+#[derive(Clone, Debug)]
+pub struct Attestation {
+    pub sell: eth::Asset,
+    pub buy: eth::Asset,
+    /// The block the quote was computed at.
+    pub block: u64,
+    /// The Unix timestamp until which the solver stands by the quote.
+    pub expiry: u64,
+    pub signer: eth::Address,
+    /// The EIP-712 signature as `r || s || v`.
+    pub signature: [u8; 65],
+}
+
+impl Attestation {
+    /// The EIP-712 struct hash of the attested quote.
+    fn hash(&self) -> [u8; 32] {
+        static TYPE_HASH: LazyLock<[u8; 32]> = LazyLock::new(|| {
+            signing::keccak256(
+                b"QuoteAttestation(address sellToken,address buyToken,uint256 sellAmount,\
+                  uint256 buyAmount,uint256 blockNumber,uint256 expiry)",
+            )
+        });
+        signing::keccak256(&encode(&[
+            Token::Uint((*TYPE_HASH).into()),
+            Token::Address(self.sell.token.into()),
+            Token::Address(self.buy.token.into()),
+            Token::Uint(self.sell.amount.into()),
+            Token::Uint(self.buy.amount.into()),
+            Token::Uint(self.block.into()),
+            Token::Uint(self.expiry.into()),
+        ]))
+    }
+}
+
+/// The EIP-712 domain of the attestations on the chain with the specified
+/// settlement contract.
+pub fn domain_separator(chain_id: u64, settlement: eth::H160) -> eth::DomainSeparator {
+    static TYPE_HASH: LazyLock<[u8; 32]> = LazyLock::new(|| {
+        signing::keccak256(
+            b"EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)",
+        )
+    });
+    static NAME: LazyLock<[u8; 32]> =
+        LazyLock::new(|| signing::keccak256(b"CoW Protocol Quote Attestation"));
+    static VERSION: LazyLock<[u8; 32]> = LazyLock::new(|| signing::keccak256(b"v1"));
+    eth::DomainSeparator(signing::keccak256(&encode(&[
+        Token::Uint((*TYPE_HASH).into()),
+        Token::Uint((*NAME).into()),
+        Token::Uint((*VERSION).into()),
+        Token::Uint(chain_id.into()),
+        Token::Address(settlement),
+    ])))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn asset(token: u64, amount: u64) -> eth::Asset {
+        eth::Asset {
+            token: eth::H160::from_low_u64_be(token).into(),
+            amount: eth::U256::from(amount).into(),
+        }
+    }
+
+    #[test]
+    fn attestations_recover_to_the_operator() {
+        let attestor = Attestor::new(
+            SecretKey::from_slice(&[1; 32]).unwrap(),
+            Duration::from_secs(30),
+        );
+        let domain = domain_separator(1, eth::H160::from_low_u64_be(9));
+        let attestation = attestor.attest(&domain, asset(1, 1000), asset(2, 2000), 42);
+
+        let recovered = EcdsaSignature::from_bytes(&attestation.signature)
+            .recover(
+                EcdsaSigningScheme::Eip712,
+                &model::DomainSeparator(domain.0),
+                &attestation.hash(),
+            )
+            .unwrap();
+        assert_eq!(eth::Address::from(recovered.signer), attestor.signer());
+        assert_eq!(attestation.signer, attestor.signer());
+
+        // attestations are bound to the chain
+        let other = domain_separator(100, eth::H160::from_low_u64_be(9));
+        let recovered = EcdsaSignature::from_bytes(&attestation.signature)
+            .recover(
+                EcdsaSigningScheme::Eip712,
+                &model::DomainSeparator(other.0),
+                &attestation.hash(),
+            )
+            .unwrap();
+        assert_ne!(eth::Address::from(recovered.signer), attestor.signer());
+    }
+}
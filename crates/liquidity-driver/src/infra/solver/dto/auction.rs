@@ -54,6 +54,9 @@ pub fn new(
         .flat_map(|liquidity| match &liquidity.kind {
             liquidity::Kind::UniswapV2(pool) => pool.reserves.iter().map(|r| r.token).collect(),
             liquidity::Kind::UniswapV3(pool) => vec![pool.tokens.get().0, pool.tokens.get().1],
+            liquidity::Kind::UniswapV4(pool) => vec![pool.tokens.get().0, pool.tokens.get().1],
+            liquidity::Kind::MaverickV2(pool) => vec![pool.tokens.get().0, pool.tokens.get().1],
+            liquidity::Kind::Curve(pool) => pool.tokens().collect(),
             liquidity::Kind::BalancerV2Stable(pool) => pool.reserves.tokens().collect(),
             liquidity::Kind::BalancerV3Stable(pool) => pool.reserves.tokens().collect(),
             liquidity::Kind::BalancerV3StableSurge(pool) => pool.reserves.tokens().collect(),
@@ -175,6 +178,7 @@ pub fn new(
                         .flatten()
                         .cloned()
                         .collect(),
+                    route_pin: None,
                     signature: order.signature.data.clone().into(),
                     signing_scheme: match order.signature.scheme {
                         Scheme::Eip712 => solvers_dto::auction::SigningScheme::Eip712,
@@ -236,6 +240,74 @@ pub fn new(
                             },
                         )
                     }
+                    liquidity::Kind::UniswapV4(pool) => solvers_dto::auction::Liquidity::UniswapV4(
+                        solvers_dto::auction::UniswapV4Pool {
+                            id: liquidity.id.0.to_string(),
+                            address: pool.pool_manager.0,
+                            router: pool.router.into(),
+                            gas_estimate: liquidity.gas.0,
+                            tokens: vec![pool.tokens.get().0.into(), pool.tokens.get().1.into()],
+                            fee: pool.fee.0,
+                            tick_spacing: pool.tick_spacing,
+                            hooks: pool.hooks.into(),
+                        },
+                    ),
+                    liquidity::Kind::MaverickV2(pool) => {
+                        solvers_dto::auction::Liquidity::MaverickV2(
+                            solvers_dto::auction::MaverickV2Pool {
+                                id: liquidity.id.0.to_string(),
+                                address: pool.address.0,
+                                router: pool.router.into(),
+                                gas_estimate: liquidity.gas.0,
+                                tokens: vec![
+                                    pool.tokens.get().0.into(),
+                                    pool.tokens.get().1.into(),
+                                ],
+                                tick_spacing: pool.tick_spacing,
+                                active_tick: pool.active_tick.0,
+                                ticks: pool
+                                    .ticks
+                                    .iter()
+                                    .map(|(tick, reserves)| {
+                                        (
+                                            tick.0,
+                                            solvers_dto::auction::MaverickV2Tick {
+                                                reserve_a: reserves.a,
+                                                reserve_b: reserves.b,
+                                            },
+                                        )
+                                    })
+                                    .collect(),
+                                fee_a_in: bigdecimal::BigDecimal::new(
+                                    pool.fee_a_in.0.to_big_int(),
+                                    18,
+                                ),
+                                fee_b_in: bigdecimal::BigDecimal::new(
+                                    pool.fee_b_in.0.to_big_int(),
+                                    18,
+                                ),
+                            },
+                        )
+                    }
+                    liquidity::Kind::Curve(pool) => {
+                        solvers_dto::auction::Liquidity::Curve(solvers_dto::auction::CurvePool {
+                            id: liquidity.id.0.to_string(),
+                            address: pool.address.0,
+                            gas_estimate: liquidity.gas.0,
+                            tokens: pool
+                                .coins
+                                .iter()
+                                .map(|coin| solvers_dto::auction::CurveCoin {
+                                    address: coin.token.into(),
+                                    balance: coin.balance,
+                                    rate: coin.rate,
+                                })
+                                .collect(),
+                            amplification: pool.amplification,
+                            fee: pool.fee,
+                            base_pool: pool.base_pool.map(|pool| pool.0),
+                        })
+                    }
                     liquidity::Kind::BalancerV2Stable(pool) => {
                         solvers_dto::auction::Liquidity::Stable(solvers_dto::auction::StablePool {
                             id: liquidity.id.0.to_string(),
@@ -263,6 +335,7 @@ pub fn new(
                                 ),
                             ),
                             fee: fee_to_decimal(pool.fee),
+                            actual_supply: pool.actual_supply,
                         })
                     }
                     liquidity::Kind::BalancerV3Stable(pool) => {
@@ -292,6 +365,7 @@ pub fn new(
                                 ),
                             ),
                             fee: fee_to_decimal_v3(pool.fee),
+                            actual_supply: None,
                         })
                     }
                     liquidity::Kind::BalancerV2Weighted(pool) => {
@@ -606,6 +680,7 @@ pub fn new(
                                 ),
                                 price_ratio_update_start_time: pool.price_ratio_update_start_time,
                                 price_ratio_update_end_time: pool.price_ratio_update_end_time,
+                                current_timestamp: pool.current_timestamp,
                             },
                         )
                     }
@@ -654,6 +729,11 @@ pub fn new(
                                 last_update_time: pool.last_update_time,
                                 last_interop_time: pool.last_interop_time,
                                 current_timestamp: pool.current_timestamp,
+                                strategy: Some(solvers_dto::auction::QuantAmmStrategy {
+                                    rule: pool.strategy.rule.clone(),
+                                    oracles: pool.strategy.oracles.clone(),
+                                    update_interval: pool.strategy.update_interval,
+                                }),
                             },
                         )
                     }
@@ -765,6 +845,7 @@ pub fn new(
             .cloned()
             .map(Into::into)
             .collect::<Vec<_>>(),
+        gas_limit: None,
     }
 }
 
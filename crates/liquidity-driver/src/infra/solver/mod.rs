@@ -28,12 +28,14 @@ use {
     reqwest::header::HeaderName,
     std::{
         collections::HashMap,
+        num::NonZeroUsize,
         time::{Duration, Instant},
     },
     thiserror::Error,
     tracing::{Instrument, instrument},
 };
 
+pub mod attestation;
 pub mod dto;
 
 // TODO At some point I should be checking that the names are unique, I don't
@@ -138,6 +140,12 @@ pub struct Config {
     /// Defines at which block the liquidity needs to be fetched on /solve
     /// requests.
     pub fetch_liquidity_at_block: infra::liquidity::AtBlock,
+    /// Signs the quotes of this solver with an operator key.
+    pub quote_attestor: Option<attestation::Attestor>,
+    /// How many auctions may be solved concurrently.
+    pub max_concurrent_auctions: Option<NonZeroUsize>,
+    /// Caching of the quotes of this solver.
+    pub quote_cache: Option<QuoteCache>,
 }
 
 impl Solver {
@@ -228,6 +236,20 @@ impl Solver {
         self.config.fetch_liquidity_at_block.clone()
     }
 
+    /// The attestor signing the quotes of this solver, if configured.
+    pub fn quote_attestor(&self) -> Option<&attestation::Attestor> {
+        self.config.quote_attestor.as_ref()
+    }
+
+    pub fn max_concurrent_auctions(&self) -> Option<NonZeroUsize> {
+        self.config.max_concurrent_auctions
+    }
+
+    /// The configuration of the quote cache of this solver, if enabled.
+    pub fn quote_cache(&self) -> Option<QuoteCache> {
+        self.config.quote_cache
+    }
+
     /// Make a POST request instructing the solver to solve an auction.
     /// Allocates at most `timeout` time for the solving.
     #[instrument(name = "solver_engine", skip_all)]
@@ -436,3 +458,14 @@ pub struct BadTokenDetection {
     pub metrics_strategy_log_only: bool,
     pub metrics_strategy_token_freeze_time: Duration,
 }
+
+#[derive(Debug, Clone, Copy)]
+pub struct QuoteCache {
+    /// The maximum number of cached quotes.
+    pub max_entries: usize,
+    /// How long quotes get served from the cache at most.
+    pub max_age: Duration,
+    /// The number of most significant bits of the quoted amount that quotes
+    /// get cached by.
+    pub amount_precision_bits: u32,
+}
@@ -27,10 +27,50 @@ use {
 /// driver from multiple binaries.
 pub async fn start(args: impl Iterator<Item = String>) {
     observe::panic_hook::install();
+    let args = args.collect::<Vec<_>>();
+    if args.get(1).is_some_and(|command| command == "check-config") {
+        check_config(cli::CheckConfigArgs::parse_from(args.into_iter().skip(1))).await;
+        return;
+    }
+    if args
+        .iter()
+        .any(|arg| arg == "--mock" || arg.starts_with("--mock="))
+    {
+        run_mock(cli::MockArgs::parse_from(args), None).await;
+        return;
+    }
     let args = cli::Args::parse_from(args);
     run_with(args, None).await
 }
 
+/// Serves the recorded liquidity of the fixtures directory without
+/// connecting to a node.
+async fn run_mock(args: cli::MockArgs, addr_sender: Option<oneshot::Sender<SocketAddr>>) {
+    infra::observe::init(observe::Config::new(&args.log, None, false, None));
+
+    let fixtures = infra::api::LiquidityFixtures::load(&args.mock)
+        .unwrap_or_else(|err| panic!("invalid liquidity fixtures: {err:#}"));
+    let serve = infra::api::MockApi {
+        fixtures,
+        addr: args.addr,
+        addr_sender,
+    }
+    .serve(shutdown_signal());
+    if let Err(err) = serve.await {
+        panic!("serve task exited: {err:?}");
+    }
+}
+
+/// Validates the configuration file and prints the effective configuration
+/// without connecting to a node or starting any servers.
+async fn check_config(args: cli::CheckConfigArgs) {
+    let chain = args
+        .chain_id
+        .map(|id| chain::Chain::try_from(id).expect("unsupported chain ID"));
+    let config = config::file::check(&args.config, chain).await;
+    println!("{config:#?}");
+}
+
 /// This function exists to enable running the driver for testing. The
 /// `addr_sender` parameter is used so that the testing framework can get the
 /// address of the server and connect to it. Outside the test suite, the
@@ -47,12 +87,16 @@ pub async fn run(
 /// Run the driver. This function exists to avoid multiple monomorphizations of
 /// the `run` code, which bloats the binaries and increases compile times.
 async fn run_with(args: cli::Args, addr_sender: Option<oneshot::Sender<SocketAddr>>) {
-    infra::observe::init(observe::Config::new(
+    let obs_config = observe::Config::new(
         &args.log,
         args.stderr_threshold,
         args.use_json_logs,
         tracing_config(&args.tracing, "driver".into()),
-    ));
+    );
+    infra::observe::init(match args.log_format {
+        Some(log_format) => obs_config.with_log_format(log_format),
+        None => obs_config,
+    });
 
     let ethrpc = ethrpc(&args).await;
     let web3 = ethrpc.web3().clone();
@@ -92,7 +136,11 @@ async fn run_with(args: cli::Args, addr_sender: Option<oneshot::Sender<SocketAdd
             &eth,
         ),
         eth,
-        addr: args.addr,
+        listeners: if config.listeners.is_empty() {
+            vec![shared::listen::Listener::tcp(args.addr)]
+        } else {
+            config.listeners
+        },
         addr_sender,
     }
     .serve(
@@ -173,6 +221,7 @@ async fn ethereum(
     Ethereum::new(
         ethrpc,
         config.contracts.clone(),
+        config.token_quirks.clone(),
         gas,
         config.tx_gas_limit,
         current_block_args,
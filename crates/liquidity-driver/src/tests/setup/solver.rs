@@ -484,6 +484,7 @@ impl Solver {
                         .into(),
                 ),
             },
+            Default::default(),
             gas,
             45_000_000.into(),
             &shared::current_block::Arguments {
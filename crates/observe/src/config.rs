@@ -1,4 +1,7 @@
-use {core::time::Duration, tracing::Level};
+use {
+    core::{fmt, str::FromStr, time::Duration},
+    tracing::Level,
+};
 
 #[derive(Debug, Clone)]
 pub struct Config {
@@ -7,8 +10,8 @@ pub struct Config {
     pub(crate) env_filter: String,
     /// Minimum level threshold for stderr output
     pub(crate) stderr_threshold: Option<Level>,
-    /// Output log events as JSON
-    pub(crate) use_json_format: bool,
+    /// The format log events get written in
+    pub(crate) log_format: LogFormat,
     /// Tracing config
     pub(crate) tracing: Option<TracingConfig>,
 }
@@ -23,14 +26,23 @@ impl Config {
         Self {
             env_filter: env_filter.into(),
             stderr_threshold,
-            use_json_format,
+            log_format: if use_json_format {
+                LogFormat::Json
+            } else {
+                LogFormat::Pretty
+            },
             tracing: tracing_config,
         }
     }
 
     /// Create an ObserveConfig with JSON format enabled
     pub fn with_json_format(mut self) -> Self {
-        self.use_json_format = true;
+        self.log_format = LogFormat::Json;
+        self
+    }
+
+    pub fn with_log_format(mut self, log_format: LogFormat) -> Self {
+        self.log_format = log_format;
         self
     }
 
@@ -55,12 +67,55 @@ impl Default for Config {
         Self {
             env_filter: "info".to_string(),
             stderr_threshold: None,
-            use_json_format: false,
+            log_format: LogFormat::Pretty,
             tracing: None,
         }
     }
 }
 
+/// The format log events get written in.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum LogFormat {
+    /// Human-readable log lines.
+    #[default]
+    Pretty,
+    /// JSON objects with the event fields nested under `fields`.
+    Json,
+    /// JSON objects following the structured logging conventions of Google
+    /// Cloud Logging, i.e. with `severity`, `message` and trace keys.
+    JsonGcp,
+    /// JSON objects following the conventions of Datadog log ingestion, i.e.
+    /// with `status`, `message` and `dd.trace_id` keys.
+    JsonDatadog,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pretty" => Ok(Self::Pretty),
+            "json" => Ok(Self::Json),
+            "json-gcp" => Ok(Self::JsonGcp),
+            "json-datadog" => Ok(Self::JsonDatadog),
+            _ => Err(format!(
+                "unknown log format {s:?}, expected one of pretty, json, json-gcp, json-datadog"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for LogFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Pretty => "pretty",
+            Self::Json => "json",
+            Self::JsonGcp => "json-gcp",
+            Self::JsonDatadog => "json-datadog",
+        })
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct TracingConfig {
     /// Endpoint to send tracing info to
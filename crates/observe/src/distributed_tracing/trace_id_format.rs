@@ -1,9 +1,16 @@
 use {
     chrono::Utc,
-    opentelemetry::trace::{TraceContextExt, TraceId},
+    opentelemetry::trace::{SpanId, TraceContextExt, TraceId},
     serde::ser::{SerializeMap, Serializer as _},
+    serde_json::{Map, Value},
     std::{fmt, io},
-    tracing::{Event, Span, Subscriber},
+    tracing::{
+        Event,
+        Level,
+        Span,
+        Subscriber,
+        field::{Field, Visit},
+    },
     tracing_opentelemetry::OpenTelemetrySpanExt,
     tracing_serde::{AsSerde, fields::AsMap},
     tracing_subscriber::{
@@ -87,6 +94,192 @@ where
     }
 }
 
+/// A `tracing_subscriber::fmt::FormatEvent` implementation for JSON log
+/// formatting following the structured logging conventions of Google Cloud
+/// Logging, so that the severity, message and trace of log entries get
+/// recognized without post-processing.
+///
+/// The event fields are written at the root level of each log object, which
+/// Cloud Logging stores in the `jsonPayload` of the entries.
+///
+/// ## Example Output
+/// ```json
+/// {
+///   "code": 200,
+///   "time": "2025-07-04T12:58:56.138095625+00:00",
+///   "severity": "INFO",
+///   "message": "finished processing with success",
+///   "target": "warp::filters::trace",
+///   "logging.googleapis.com/trace": "4bf92f3577b34da6a3ce929d0e0e4736",
+///   "logging.googleapis.com/spanId": "00f067aa0ba902b7"
+/// }
+/// ```
+pub struct GcpJsonFormat;
+
+impl<S, N> FormatEvent<S, N> for GcpJsonFormat
+where
+    S: Subscriber + for<'lookup> LookupSpan<'lookup>,
+    N: for<'writer> FormatFields<'writer> + 'static,
+{
+    fn format_event(
+        &self,
+        _ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let meta = event.metadata();
+        let severity = match *meta.level() {
+            Level::ERROR => "ERROR",
+            Level::WARN => "WARNING",
+            Level::INFO => "INFO",
+            _ => "DEBUG",
+        };
+        let mut fields = event_fields(event);
+        let message = fields.remove("message").unwrap_or_default();
+
+        let mut visit = || {
+            let mut serializer = serde_json::Serializer::new(WriteAdapter(&mut writer));
+            let mut serializer = serializer.serialize_map(None)?;
+            // The fields come first so that they can't shadow the keys the log
+            // entries get parsed by.
+            for (key, value) in &fields {
+                serializer.serialize_entry(key, value)?;
+            }
+            serializer.serialize_entry("time", &Utc::now().to_rfc3339())?;
+            serializer.serialize_entry("severity", severity)?;
+            serializer.serialize_entry("message", &message)?;
+            serializer.serialize_entry("target", meta.target())?;
+            if let Some((trace_id, span_id)) = current_trace() {
+                serializer
+                    .serialize_entry("logging.googleapis.com/trace", &trace_id.to_string())?;
+                serializer
+                    .serialize_entry("logging.googleapis.com/spanId", &span_id.to_string())?;
+            }
+            serializer.end()
+        };
+
+        visit().map_err(|_| fmt::Error)?;
+        writeln!(writer)
+    }
+}
+
+/// A `tracing_subscriber::fmt::FormatEvent` implementation for JSON log
+/// formatting following the conventions of the Datadog log ingestion, so that
+/// the status, message and logger of log entries get recognized and the logs
+/// get correlated with the traces without post-processing.
+///
+/// Datadog identifies traces and spans by 64-bit decimal IDs, so the lower 64
+/// bits of the OpenTelemetry IDs are written.
+///
+/// ## Example Output
+/// ```json
+/// {
+///   "code": 200,
+///   "timestamp": "2025-07-04T12:58:56.138095625+00:00",
+///   "status": "info",
+///   "message": "finished processing with success",
+///   "logger.name": "warp::filters::trace",
+///   "dd.trace_id": "11803532876627986230",
+///   "dd.span_id": "67667974448284343"
+/// }
+/// ```
+pub struct DatadogJsonFormat;
+
+impl<S, N> FormatEvent<S, N> for DatadogJsonFormat
+where
+    S: Subscriber + for<'lookup> LookupSpan<'lookup>,
+    N: for<'writer> FormatFields<'writer> + 'static,
+{
+    fn format_event(
+        &self,
+        _ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let meta = event.metadata();
+        let status = match *meta.level() {
+            Level::ERROR => "error",
+            Level::WARN => "warn",
+            Level::INFO => "info",
+            _ => "debug",
+        };
+        let mut fields = event_fields(event);
+        let message = fields.remove("message").unwrap_or_default();
+
+        let mut visit = || {
+            let mut serializer = serde_json::Serializer::new(WriteAdapter(&mut writer));
+            let mut serializer = serializer.serialize_map(None)?;
+            // The fields come first so that they can't shadow the keys the log
+            // entries get parsed by.
+            for (key, value) in &fields {
+                serializer.serialize_entry(key, value)?;
+            }
+            serializer.serialize_entry("timestamp", &Utc::now().to_rfc3339())?;
+            serializer.serialize_entry("status", status)?;
+            serializer.serialize_entry("message", &message)?;
+            serializer.serialize_entry("logger.name", meta.target())?;
+            if let Some((trace_id, span_id)) = current_trace() {
+                let trace_id = trace_id.to_bytes();
+                let trace_id = u64::from_be_bytes(trace_id[8..].try_into().unwrap());
+                let span_id = u64::from_be_bytes(span_id.to_bytes());
+                serializer.serialize_entry("dd.trace_id", &trace_id.to_string())?;
+                serializer.serialize_entry("dd.span_id", &span_id.to_string())?;
+            }
+            serializer.end()
+        };
+
+        visit().map_err(|_| fmt::Error)?;
+        writeln!(writer)
+    }
+}
+
+/// Returns the OpenTelemetry trace and span ID of the current span, if it is
+/// part of a trace.
+fn current_trace() -> Option<(TraceId, SpanId)> {
+    let context = Span::current().context();
+    let span = context.span();
+    let span_context = span.span_context();
+    (span_context.trace_id() != TraceId::INVALID)
+        .then(|| (span_context.trace_id(), span_context.span_id()))
+}
+
+/// Collects the fields of the event into a JSON object.
+fn event_fields(event: &Event<'_>) -> Map<String, Value> {
+    let mut fields = JsonFields::default();
+    event.record(&mut fields);
+    fields.0
+}
+
+#[derive(Default)]
+struct JsonFields(Map<String, Value>);
+
+impl Visit for JsonFields {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().into(), format!("{value:?}").into());
+    }
+}
+
 struct WriteAdapter<'a>(pub(crate) &'a mut dyn std::fmt::Write);
 
 impl<'a> io::Write for WriteAdapter<'a> {
@@ -145,3 +338,64 @@ where
         format_res
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        std::sync::{Arc, Mutex},
+        tracing_subscriber::{Registry, fmt::format::DefaultFields},
+    };
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn log_warning<E>(format: E) -> Value
+    where
+        E: FormatEvent<Registry, DefaultFields> + Send + Sync + 'static,
+    {
+        let buffer = Buffer::default();
+        let writer = buffer.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .event_format(format)
+            .with_writer(move || writer.clone())
+            .finish();
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::warn!(pool = 3, token = "weth", "price moved");
+        });
+        serde_json::from_slice(&buffer.0.lock().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn formats_gcp_log_entries() {
+        let log = log_warning(GcpJsonFormat);
+        assert_eq!(log["severity"], "WARNING");
+        assert_eq!(log["message"], "price moved");
+        assert_eq!(log["pool"], 3);
+        assert_eq!(log["token"], "weth");
+        assert_eq!(log["target"], module_path!());
+        assert!(log.get("logging.googleapis.com/trace").is_none());
+    }
+
+    #[test]
+    fn formats_datadog_log_entries() {
+        let log = log_warning(DatadogJsonFormat);
+        assert_eq!(log["status"], "warn");
+        assert_eq!(log["message"], "price moved");
+        assert_eq!(log["pool"], 3);
+        assert_eq!(log["token"], "weth");
+        assert_eq!(log["logger.name"], module_path!());
+        assert!(log.get("dd.trace_id").is_none());
+    }
+}
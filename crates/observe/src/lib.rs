@@ -5,12 +5,13 @@ pub mod config;
 pub mod distributed_tracing;
 pub mod future;
 pub mod metrics;
+pub mod openmetrics;
 pub mod panic_hook;
 pub mod tracing;
 #[cfg(unix)]
 mod tracing_reload_handler;
 
 pub use {
-    config::{Config, TracingConfig},
+    config::{Config, LogFormat, TracingConfig},
     distributed_tracing::request_id,
 };
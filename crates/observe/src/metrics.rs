@@ -1,4 +1,5 @@
 use {
+    crate::openmetrics,
     prometheus::{
         Encoder,
         core::{AtomicF64, AtomicU64, GenericCounterVec},
@@ -36,9 +37,11 @@ static REGISTRY: OnceLock<prometheus_metric_storage::StorageRegistry> = OnceLock
 /// any call to [`get_registry`]. This function also panics if registry
 /// configuration is invalid.
 pub fn setup_registry(prefix: Option<String>, labels: Option<HashMap<String, String>>) {
+    let naming = naming(&prefix, &labels);
     let registry = prometheus::Registry::new_custom(prefix, labels).unwrap();
     let storage_registry = prometheus_metric_storage::StorageRegistry::new(registry);
     REGISTRY.set(storage_registry).unwrap();
+    openmetrics::NAMING.set(naming).ok();
 }
 
 /// Like [`setup_registry`], but can be called multiple times in a row.
@@ -46,9 +49,25 @@ pub fn setup_registry(prefix: Option<String>, labels: Option<HashMap<String, Str
 ///
 /// Useful for tests.
 pub fn setup_registry_reentrant(prefix: Option<String>, labels: Option<HashMap<String, String>>) {
+    let naming = naming(&prefix, &labels);
     let registry = prometheus::Registry::new_custom(prefix, labels).unwrap();
     let storage_registry = prometheus_metric_storage::StorageRegistry::new(registry);
-    REGISTRY.set(storage_registry).ok();
+    if REGISTRY.set(storage_registry).is_ok() {
+        openmetrics::NAMING.set(naming).ok();
+    }
+}
+
+fn naming(
+    prefix: &Option<String>,
+    labels: &Option<HashMap<String, String>>,
+) -> openmetrics::Naming {
+    openmetrics::Naming {
+        prefix: prefix.clone(),
+        labels: labels
+            .iter()
+            .flat_map(|labels| labels.keys().cloned())
+            .collect(),
+    }
 }
 
 /// Get the global instance of the metrics registry.
@@ -77,6 +96,23 @@ pub fn encode(registry: &prometheus::Registry) -> String {
     String::from_utf8(buffer).unwrap()
 }
 
+/// Encodes the registry in the format the `Accept` header of a scrape asks
+/// for, which is OpenMetrics with exemplars or the Prometheus text format.
+/// Returns the content type along with the encoded metrics.
+pub fn encode_accepted(
+    registry: &prometheus::Registry,
+    accept: Option<&str>,
+) -> (&'static str, String) {
+    if openmetrics::accepted(accept) {
+        (
+            openmetrics::CONTENT_TYPE,
+            openmetrics::encode(&registry.gather()),
+        )
+    } else {
+        (prometheus::TEXT_FORMAT, encode(registry))
+    }
+}
+
 pub const DEFAULT_METRICS_PORT: u16 = 9586;
 
 #[async_trait::async_trait]
@@ -101,7 +137,12 @@ pub fn serve_metrics(
 // `/metrics` route exposing encoded prometheus data to monitoring system
 pub fn handle_metrics() -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
     let registry = get_registry();
-    warp::path("metrics").map(move || encode(registry))
+    warp::path("metrics")
+        .and(warp::header::optional::<String>("accept"))
+        .map(move |accept: Option<String>| {
+            let (content_type, metrics) = encode_accepted(registry, accept.as_deref());
+            warp::reply::with_header(metrics, "content-type", content_type)
+        })
 }
 
 fn handle_liveness_probe(
@@ -0,0 +1,300 @@
+//! Encoding of the metrics in the OpenMetrics text format, with exemplars
+//! linking histogram samples to the traces they were observed in.
+//!
+//! The `prometheus` crate has no notion of exemplars, so the most recent
+//! exemplar of every histogram bucket is kept on the side and appended to the
+//! bucket sample when encoding. A slow sample in the tail of a latency
+//! histogram then links straight to the trace of the request it came from.
+
+use {
+    opentelemetry::trace::{TraceContextExt, TraceId},
+    prometheus::{
+        Histogram,
+        core::Collector,
+        proto::{LabelPair, MetricFamily, MetricType},
+    },
+    std::{
+        collections::{HashMap, HashSet},
+        fmt::Write,
+        sync::{LazyLock, Mutex, OnceLock},
+        time::SystemTime,
+    },
+    tracing::Span,
+    tracing_opentelemetry::OpenTelemetrySpanExt,
+};
+
+/// The content type of metrics encoded in the OpenMetrics text format.
+pub const CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// The prefix and common labels the registry adds to all metrics when
+/// gathering them, which the exemplars are recorded without.
+#[derive(Debug, Default)]
+pub(crate) struct Naming {
+    pub prefix: Option<String>,
+    pub labels: HashSet<String>,
+}
+
+pub(crate) static NAMING: OnceLock<Naming> = OnceLock::new();
+
+/// The most recent exemplars of the histogram buckets.
+static EXEMPLARS: LazyLock<Mutex<HashMap<Bucket, Exemplar>>> = LazyLock::new(Default::default);
+
+/// A histogram bucket, identified by the name and labels of the histogram and
+/// the bits of its upper bound.
+#[derive(Debug, Eq, Hash, PartialEq)]
+struct Bucket {
+    name: String,
+    labels: Vec<(String, String)>,
+    upper_bound: u64,
+}
+
+#[derive(Debug)]
+struct Exemplar {
+    trace_id: TraceId,
+    value: f64,
+    timestamp: f64,
+}
+
+/// Returns whether the `Accept` header of a scrape asks for the OpenMetrics
+/// format, which is what Prometheus does when exemplar storage is enabled.
+pub fn accepted(accept: Option<&str>) -> bool {
+    accept.is_some_and(|accept| accept.contains("application/openmetrics-text"))
+}
+
+/// Observes the value with the histogram, recording the trace of the current
+/// span as the exemplar of the bucket the value falls into.
+pub fn observe(histogram: &Histogram, value: f64) {
+    histogram.observe(value);
+
+    let trace_id = Span::current().context().span().span_context().trace_id();
+    if trace_id == TraceId::INVALID {
+        return;
+    }
+    // The name and labels of the histogram are only available by collecting
+    // it.
+    let Some(family) = histogram.collect().pop() else {
+        return;
+    };
+    let Some(metric) = family.get_metric().first() else {
+        return;
+    };
+    let upper_bound = metric
+        .get_histogram()
+        .get_bucket()
+        .iter()
+        .map(|bucket| bucket.get_upper_bound())
+        .find(|upper_bound| value <= *upper_bound)
+        .unwrap_or(f64::INFINITY);
+    let bucket = Bucket {
+        name: family.get_name().to_owned(),
+        labels: labels(metric.get_label(), &HashSet::new()),
+        upper_bound: upper_bound.to_bits(),
+    };
+    let timestamp = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64();
+    EXEMPLARS.lock().unwrap().insert(
+        bucket,
+        Exemplar {
+            trace_id,
+            value,
+            timestamp,
+        },
+    );
+}
+
+/// Encodes the metric families in the OpenMetrics text format.
+pub fn encode(families: &[MetricFamily]) -> String {
+    let naming = NAMING.get_or_init(Default::default);
+    let exemplars = EXEMPLARS.lock().unwrap();
+    let mut out = String::new();
+    for family in families {
+        let name = family.get_name();
+        // OpenMetrics counter samples are suffixed with `_total`. Counters
+        // named without it are exposed as unknown metrics instead, so that
+        // their series keep their names.
+        let counter = name.strip_suffix("_total");
+        let (kind, family_name) = match family.get_field_type() {
+            MetricType::COUNTER => match counter {
+                Some(name) => ("counter", name),
+                None => ("unknown", name),
+            },
+            MetricType::GAUGE => ("gauge", name),
+            MetricType::SUMMARY => ("summary", name),
+            MetricType::HISTOGRAM => ("histogram", name),
+            MetricType::UNTYPED => ("unknown", name),
+        };
+        if !family.get_help().is_empty() {
+            writeln!(out, "# HELP {family_name} {}", escape(family.get_help())).unwrap();
+        }
+        writeln!(out, "# TYPE {family_name} {kind}").unwrap();
+
+        let unprefixed = match &naming.prefix {
+            Some(prefix) => name
+                .strip_prefix(prefix.as_str())
+                .and_then(|name| name.strip_prefix('_'))
+                .unwrap_or(name),
+            None => name,
+        };
+        for metric in family.get_metric() {
+            let labels = metric.get_label();
+            match family.get_field_type() {
+                MetricType::COUNTER => {
+                    let value = float(metric.get_counter().get_value());
+                    sample(&mut out, name, "", labels, None, &value);
+                }
+                MetricType::GAUGE => {
+                    let value = float(metric.get_gauge().get_value());
+                    sample(&mut out, name, "", labels, None, &value);
+                }
+                MetricType::UNTYPED => {
+                    let value = float(metric.get_untyped().get_value());
+                    sample(&mut out, name, "", labels, None, &value);
+                }
+                MetricType::SUMMARY => {
+                    let summary = metric.get_summary();
+                    for quantile in summary.get_quantile() {
+                        let label = ("quantile", quantile.get_quantile());
+                        let value = float(quantile.get_value());
+                        sample(&mut out, name, "", labels, Some(label), &value);
+                    }
+                    let sum = float(summary.get_sample_sum());
+                    let count = summary.get_sample_count().to_string();
+                    sample(&mut out, name, "_sum", labels, None, &sum);
+                    sample(&mut out, name, "_count", labels, None, &count);
+                }
+                MetricType::HISTOGRAM => {
+                    let histogram = metric.get_histogram();
+                    let count = histogram.get_sample_count();
+                    let mut key = Bucket {
+                        name: unprefixed.to_owned(),
+                        labels: self::labels(labels, &naming.labels),
+                        upper_bound: 0,
+                    };
+                    let buckets = histogram
+                        .get_bucket()
+                        .iter()
+                        .map(|bucket| (bucket.get_upper_bound(), bucket.get_cumulative_count()))
+                        .filter(|(upper_bound, _)| upper_bound.is_finite())
+                        .chain([(f64::INFINITY, count)]);
+                    for (upper_bound, cumulative) in buckets {
+                        let label = ("le", upper_bound);
+                        let value = cumulative.to_string();
+                        sample(&mut out, name, "_bucket", labels, Some(label), &value);
+                        key.upper_bound = upper_bound.to_bits();
+                        if let Some(exemplar) = exemplars.get(&key) {
+                            // Replace the new line of the sample with the
+                            // exemplar.
+                            out.pop();
+                            writeln!(
+                                out,
+                                " # {{trace_id=\"{}\"}} {} {}",
+                                exemplar.trace_id,
+                                float(exemplar.value),
+                                float(exemplar.timestamp),
+                            )
+                            .unwrap();
+                        }
+                    }
+                    let sum = float(histogram.get_sample_sum());
+                    sample(&mut out, name, "_sum", labels, None, &sum);
+                    sample(&mut out, name, "_count", labels, None, &count.to_string());
+                }
+            }
+        }
+    }
+    out.push_str("# EOF\n");
+    out
+}
+
+/// Writes a sample line with the labels of the metric and an optional extra
+/// label, such as the bucket bound of histograms.
+fn sample(
+    out: &mut String,
+    name: &str,
+    suffix: &str,
+    labels: &[LabelPair],
+    extra: Option<(&str, f64)>,
+    value: &str,
+) {
+    let labels = labels
+        .iter()
+        .map(|label| format!("{}=\"{}\"", label.get_name(), escape(label.get_value())))
+        .chain(extra.map(|(name, value)| format!("{name}=\"{}\"", float(value))))
+        .collect::<Vec<_>>();
+    if labels.is_empty() {
+        writeln!(out, "{name}{suffix} {value}").unwrap();
+    } else {
+        writeln!(out, "{name}{suffix}{{{}}} {value}", labels.join(",")).unwrap();
+    }
+}
+
+/// The label pairs sorted by name, without the excluded labels.
+fn labels(labels: &[LabelPair], excluded: &HashSet<String>) -> Vec<(String, String)> {
+    let mut labels = labels
+        .iter()
+        .filter(|label| !excluded.contains(label.get_name()))
+        .map(|label| (label.get_name().to_owned(), label.get_value().to_owned()))
+        .collect::<Vec<_>>();
+    labels.sort();
+    labels
+}
+
+fn float(value: f64) -> String {
+    match value {
+        f64::INFINITY => "+Inf".to_owned(),
+        f64::NEG_INFINITY => "-Inf".to_owned(),
+        value if value.is_nan() => "NaN".to_owned(),
+        value => format!("{value:?}"),
+    }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', r"\\")
+        .replace('"', r#"\""#)
+        .replace('\n', r"\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, prometheus::HistogramOpts};
+
+    #[test]
+    fn encodes_histograms_with_exemplars() {
+        let registry = prometheus::Registry::new();
+        let histogram = Histogram::with_opts(
+            HistogramOpts::new("solve_time", "Time \"spent\" solving.").buckets(vec![1., 2.5]),
+        )
+        .unwrap();
+        registry.register(Box::new(histogram.clone())).unwrap();
+        histogram.observe(0.5);
+        histogram.observe(2.);
+        EXEMPLARS.lock().unwrap().insert(
+            Bucket {
+                name: "solve_time".to_owned(),
+                labels: Vec::new(),
+                upper_bound: 2.5f64.to_bits(),
+            },
+            Exemplar {
+                trace_id: TraceId::from_hex("4bf92f3577b34da6a3ce929d0e0e4736").unwrap(),
+                value: 2.,
+                timestamp: 1700000000.5,
+            },
+        );
+
+        assert_eq!(
+            encode(&registry.gather()),
+            "# HELP solve_time Time \\\"spent\\\" solving.\n\
+             # TYPE solve_time histogram\n\
+             solve_time_bucket{le=\"1.0\"} 1\n\
+             solve_time_bucket{le=\"2.5\"} 2 # {trace_id=\"4bf92f3577b34da6a3ce929d0e0e4736\"} \
+             2.0 1700000000.5\n\
+             solve_time_bucket{le=\"+Inf\"} 2\n\
+             solve_time_sum 2.5\n\
+             solve_time_count 2\n\
+             # EOF\n"
+        );
+    }
+}
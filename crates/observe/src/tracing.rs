@@ -1,9 +1,9 @@
 use {
     crate::{
-        config::Config,
+        config::{Config, LogFormat},
         distributed_tracing::{
             request_id::RequestIdLayer,
-            trace_id_format::{TraceIdFmt, TraceIdJsonFormat},
+            trace_id_format::{DatadogJsonFormat, GcpJsonFormat, TraceIdFmt, TraceIdJsonFormat},
         },
         tracing_reload_handler::spawn_reload_handler,
     },
@@ -74,7 +74,7 @@ fn set_tracing_subscriber(config: &Config) {
     //    work correctly.
 
     macro_rules! fmt_layer {
-        ($env_filter:expr_2021, $stderr_threshold:expr_2021, $use_json_format:expr_2021) => {{
+        ($env_filter:expr_2021, $stderr_threshold:expr_2021, $log_format:expr_2021) => {{
             let stderr_threshold = $stderr_threshold.clone();
             let writer = std::io::stderr
                 .with_filter(move |meta| {
@@ -88,15 +88,24 @@ fn set_tracing_subscriber(config: &Config) {
                 "[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond digits:3]Z"
             ));
 
-            if config.use_json_format {
+            match $log_format {
                 // structured logging
-                tracing_subscriber::fmt::layer()
+                LogFormat::Json => tracing_subscriber::fmt::layer()
                     .event_format(TraceIdJsonFormat)
                     .with_writer(writer)
                     .with_filter($env_filter)
-                    .boxed()
-            } else {
-                tracing_subscriber::fmt::layer()
+                    .boxed(),
+                LogFormat::JsonGcp => tracing_subscriber::fmt::layer()
+                    .event_format(GcpJsonFormat)
+                    .with_writer(writer)
+                    .with_filter($env_filter)
+                    .boxed(),
+                LogFormat::JsonDatadog => tracing_subscriber::fmt::layer()
+                    .event_format(DatadogJsonFormat)
+                    .with_writer(writer)
+                    .with_filter($env_filter)
+                    .boxed(),
+                LogFormat::Pretty => tracing_subscriber::fmt::layer()
                     .with_timer(timer)
                     .with_ansi(atty::is(atty::Stream::Stdout))
                     .map_event_format(|formatter| TraceIdFmt {
@@ -104,7 +113,7 @@ fn set_tracing_subscriber(config: &Config) {
                     })
                     .with_writer(writer)
                     .with_filter($env_filter)
-                    .boxed()
+                    .boxed(),
             }
         }};
     }
@@ -157,7 +166,7 @@ fn set_tracing_subscriber(config: &Config) {
         .with(fmt_layer!(
             env_filter,
             config.stderr_threshold,
-            config.use_json_format
+            config.log_format
         ))
         .with(tracing_layer);
 
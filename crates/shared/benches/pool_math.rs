@@ -0,0 +1,274 @@
+//! Benchmarks for the Balancer V3 pool math kernels.
+//!
+//! Every pool type is benchmarked in both swap directions with parameters
+//! taken from real mainnet pools (or the fixtures used in the unit tests).
+//! Use `just bench-save` to record a local baseline and `just bench-compare`
+//! to check a change against it; the comparison does not depend on CI.
+
+use {
+    criterion::{BenchmarkId, Criterion, criterion_group, criterion_main},
+    ethcontract::{H160, I256, U256},
+    futures::executor::block_on,
+    shared::{
+        baseline_solver::BaselineSolvable,
+        sources::balancer_v3::{
+            pool_fetching::{
+                AmplificationParameter,
+                CommonPoolState,
+                Gyro2CLPPool,
+                GyroEPool,
+                QuantAmmPool,
+                ReClammPool,
+                StablePool,
+                TokenState,
+                WeightedPool,
+                WeightedTokenState,
+            },
+            swap::{
+                fixed_point::Bfp,
+                signed_fixed_point::{FixedPointPrecision, SBfp},
+            },
+        },
+    },
+    std::{collections::BTreeMap, hint::black_box},
+};
+
+/// Fixed "current" timestamp for the time dependent pools so that results do
+/// not depend on when the benchmarks are run.
+const NOW: u64 = 1_750_000_000;
+
+fn bfp(s: &str) -> Bfp {
+    s.parse().unwrap()
+}
+
+fn sbfp(s: &str) -> SBfp {
+    SBfp::from_str_with_precision(s, FixedPointPrecision::Standard18).unwrap()
+}
+
+fn sbfp38(s: &str) -> SBfp {
+    SBfp::from_str_with_precision(s, FixedPointPrecision::Extended38).unwrap()
+}
+
+fn token(i: u64) -> H160 {
+    H160::from_low_u64_be(i)
+}
+
+fn common(swap_fee: &str) -> CommonPoolState {
+    CommonPoolState {
+        id: H160::repeat_byte(0xb3),
+        address: H160::repeat_byte(0xb3),
+        swap_fee: bfp(swap_fee),
+        paused: false,
+    }
+}
+
+fn token_state(balance: u128, scaling_factor: Bfp) -> TokenState {
+    TokenState {
+        balance: balance.into(),
+        scaling_factor,
+        rate: U256::exp10(18),
+    }
+}
+
+fn weighted_pool() -> WeightedPool {
+    let reserves = [
+        (1_850_304_144_768_426_873_445_489_u128, "0.8"),
+        (95_671_347_892_391_047_965_654_u128, "0.2"),
+    ]
+    .into_iter()
+    .enumerate()
+    .map(|(i, (balance, weight))| {
+        (
+            token(i as u64 + 1),
+            WeightedTokenState {
+                common: token_state(balance, Bfp::exp10(0)),
+                weight: bfp(weight),
+            },
+        )
+    })
+    .collect();
+    WeightedPool {
+        common: common("0.002"),
+        reserves,
+        version: Default::default(),
+    }
+}
+
+fn stable_pool(balances: &[(u128, Bfp)]) -> StablePool {
+    let reserves = balances
+        .iter()
+        .enumerate()
+        .map(|(i, (balance, scaling_factor))| {
+            (token(i as u64 + 1), token_state(*balance, *scaling_factor))
+        })
+        .collect();
+    StablePool {
+        common: common("0.0003"),
+        reserves,
+        amplification_parameter: AmplificationParameter::try_new(570_000.into(), 1_000.into())
+            .unwrap(),
+        version: Default::default(),
+    }
+}
+
+fn stable_pool_3() -> StablePool {
+    stable_pool(&[
+        (40_927_687_702_846_622_465_144_342, Bfp::exp10(0)),
+        (59_448_574_675_062, Bfp::exp10(12)),
+        (55_199_308_926_456, Bfp::exp10(12)),
+    ])
+}
+
+fn stable_pool_5() -> StablePool {
+    stable_pool(&[
+        (40_927_687_702_846_622_465_144_342, Bfp::exp10(0)),
+        (59_448_574_675_062, Bfp::exp10(12)),
+        (55_199_308_926_456, Bfp::exp10(12)),
+        (38_112_455_018_334_906_513_004_771, Bfp::exp10(0)),
+        (47_860_920_118_402, Bfp::exp10(12)),
+    ])
+}
+
+fn two_token_reserves(balance_0: u128, balance_1: u128) -> BTreeMap<H160, TokenState> {
+    [
+        (token(1), token_state(balance_0, Bfp::exp10(0))),
+        (token(2), token_state(balance_1, Bfp::exp10(0))),
+    ]
+    .into_iter()
+    .collect()
+}
+
+fn gyro_e_pool() -> GyroEPool {
+    GyroEPool {
+        common: common("0.0001"),
+        reserves: two_token_reserves(
+            1_000_000_000_000_000_000_000_000,
+            1_000_000_000_000_000_000_000_000,
+        ),
+        version: Default::default(),
+        params_alpha: sbfp("0.7"),
+        params_beta: sbfp("1.3"),
+        params_c: sbfp("0.707106781186547524"),
+        params_s: sbfp("0.707106781186547524"),
+        params_lambda: sbfp("1"),
+        tau_alpha_x: sbfp38("-0.17378533390904767196396190604716688"),
+        tau_alpha_y: sbfp38("0.984783558817936807795784134267279"),
+        tau_beta_x: sbfp38("0.1293391840677680520489165354049038"),
+        tau_beta_y: sbfp38("0.9916004111862217323750267714375956"),
+        u: sbfp38("0.1515622589884078618346041354467426"),
+        v: sbfp38("0.9881919850020792689650338303356912"),
+        w: sbfp38("0.003408426184142462285756984496121705"),
+        z: sbfp38("-0.022223074920639809932327072642593141"),
+        d_sq: sbfp38("0.9999999999999999988662409334210612"),
+    }
+}
+
+fn gyro_2clp_pool() -> Gyro2CLPPool {
+    Gyro2CLPPool {
+        common: common("0.0001"),
+        reserves: two_token_reserves(
+            1_000_000_000_000_000_000_000_000,
+            1_000_000_000_000_000_000_000_000,
+        ),
+        version: Default::default(),
+        sqrt_alpha: sbfp("0.998"),
+        sqrt_beta: sbfp("1.002"),
+    }
+}
+
+fn reclamm_pool() -> ReClammPool {
+    ReClammPool {
+        common: common("0.0025"),
+        reserves: two_token_reserves(1_000_000_000_000_000_000_000, 1_000_000_000_000_000_000_000),
+        version: Default::default(),
+        last_virtual_balances: vec![
+            U256::from(5_000_000_000_000_000_000_000_u128),
+            U256::from(5_000_000_000_000_000_000_000_u128),
+        ],
+        daily_price_shift_base: bfp("0.999991977119453220"),
+        last_timestamp: NOW,
+        centeredness_margin: bfp("0.2"),
+        start_fourth_root_price_ratio: bfp("1.1"),
+        end_fourth_root_price_ratio: bfp("1.1"),
+        price_ratio_update_start_time: NOW - 86_400,
+        price_ratio_update_end_time: NOW - 86_400,
+        current_timestamp: NOW,
+    }
+}
+
+fn quantamm_pool() -> QuantAmmPool {
+    let half = I256::from(500_000_000_000_000_000_i128);
+    let multiplier = I256::from(1_000_000_000_i128);
+    QuantAmmPool {
+        common: common("0.003"),
+        reserves: two_token_reserves(250_000_000_000_000_000_000, 750_000_000_000_000_000_000_000),
+        version: Default::default(),
+        max_trade_size_ratio: bfp("0.1"),
+        strategy: Default::default(),
+        // Weights for both tokens followed by their per-second multipliers.
+        first_four_weights_and_multipliers: vec![half, half, multiplier, -multiplier],
+        second_four_weights_and_multipliers: vec![],
+        last_update_time: NOW - 3_600,
+        last_interop_time: NOW + 3_600,
+        current_timestamp: NOW,
+    }
+}
+
+fn bench_pool(
+    c: &mut Criterion,
+    name: &str,
+    pool: &impl BaselineSolvable,
+    (token_in, token_out): (H160, H160),
+    amount: U256,
+) {
+    let mut group = c.benchmark_group(name);
+    group.bench_with_input(
+        BenchmarkId::new("get_amount_out", amount),
+        &amount,
+        |b, amount| {
+            b.iter(|| {
+                block_on(pool.get_amount_out(black_box(token_out), (black_box(*amount), token_in)))
+            })
+        },
+    );
+    group.bench_with_input(
+        BenchmarkId::new("get_amount_in", amount),
+        &amount,
+        |b, amount| {
+            b.iter(|| {
+                block_on(pool.get_amount_in(black_box(token_in), (black_box(*amount), token_out)))
+            })
+        },
+    );
+    group.finish();
+}
+
+fn pool_math(c: &mut Criterion) {
+    let pair = (token(1), token(2));
+    let amount = U256::exp10(21);
+    bench_pool(c, "weighted", &weighted_pool(), pair, amount);
+    // Trade between two of the 6 decimal stable coins, 1000 units each.
+    let stable_pair = (token(2), token(3));
+    let stable_amount = U256::exp10(9);
+    bench_pool(
+        c,
+        "stable_3_tokens",
+        &stable_pool_3(),
+        stable_pair,
+        stable_amount,
+    );
+    bench_pool(
+        c,
+        "stable_5_tokens",
+        &stable_pool_5(),
+        stable_pair,
+        stable_amount,
+    );
+    bench_pool(c, "gyro_e", &gyro_e_pool(), pair, amount);
+    bench_pool(c, "gyro_2clp", &gyro_2clp_pool(), pair, amount);
+    bench_pool(c, "reclamm", &reclamm_pool(), pair, U256::exp10(19));
+    bench_pool(c, "quantamm", &quantamm_pool(), pair, U256::exp10(19));
+}
+
+criterion_group!(benches, pool_math);
+criterion_main!(benches);
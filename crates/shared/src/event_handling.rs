@@ -12,8 +12,9 @@ use {
         block_stream::{BlockNumberHash, BlockRetrieving, RangeInclusive},
     },
     futures::{Stream, StreamExt, TryStreamExt, future},
+    itertools::Itertools,
     primitive_types::H256,
-    std::{pin::Pin, sync::Arc},
+    std::{pin::Pin, sync::Arc, time::Duration},
     tokio::sync::Mutex,
     tracing::Instrument,
     web3::types::Address,
@@ -54,9 +55,30 @@ where
     contract: C,
     store: S,
     last_handled_blocks: Vec<BlockNumberHash>,
+    backfill: Backfill,
     _phantom: std::marker::PhantomData<E>,
 }
 
+/// Limits on fetching the events of the history, which spans many blocks
+/// when indexing from scratch, so that the backfill doesn't exceed the rate
+/// limits of the node.
+#[derive(Clone, Copy, Debug)]
+pub struct Backfill {
+    /// The most blocks whose events are fetched with a single request.
+    pub max_blocks_per_request: u64,
+    /// How long to wait between the requests.
+    pub request_interval: Duration,
+}
+
+impl Default for Backfill {
+    fn default() -> Self {
+        Self {
+            max_blocks_per_request: 500,
+            request_interval: Duration::ZERO,
+        }
+    }
+}
+
 /// `EventStoring` is used by `EventHandler` for the purpose of giving the user
 /// freedom in how, where and which events are stored.
 ///
@@ -142,16 +164,15 @@ where
     async fn get_events_by_block_range(
         &self,
         block_range: &RangeInclusive<u64>,
+        page_size: u64,
     ) -> Result<EventStream<Self::Event>> {
-        const CHUNK_SIZE: usize = 500;
-
         let start = *block_range.start();
         let end = *block_range.end();
         let provider = self.0.provider().clone();
         let base_filter = self.0.filter();
 
         let stream = futures::stream::iter(start..=end)
-            .chunks(CHUNK_SIZE)
+            .chunks(page_size.max(1) as usize)
             .then(move |range| {
                 let provider = provider.clone();
                 let filter = base_filter.clone();
@@ -219,12 +240,13 @@ where
     async fn get_events_by_block_range(
         &self,
         block_range: &RangeInclusive<u64>,
+        page_size: u64,
     ) -> Result<EventStream<Self::Event>> {
         let stream = self
             .get_events()
             .from_block((*block_range.start()).into())
             .to_block((*block_range.end()).into())
-            .block_page_size(500)
+            .block_page_size(page_size)
             .query_paginated()
             .await?
             .map_err(anyhow::Error::from);
@@ -246,9 +268,12 @@ pub trait EventRetrieving {
         block_hash: ethcontract::H256,
     ) -> Result<Vec<Self::Event>>;
 
+    /// Returns the events of the block range, fetched in pages of at most
+    /// `page_size` blocks.
     async fn get_events_by_block_range(
         &self,
         block_range: &RangeInclusive<u64>,
+        page_size: u64,
     ) -> Result<EventStream<Self::Event>>;
 
     fn address(&self) -> Vec<Address>;
@@ -285,10 +310,17 @@ where
                     None => vec![],
                 }
             },
+            backfill: Default::default(),
             _phantom: std::marker::PhantomData,
         }
     }
 
+    /// Limits the requests fetching the events of the history.
+    pub fn with_backfill(mut self, backfill: Backfill) -> Self {
+        self.backfill = backfill;
+        self
+    }
+
     /// Creates a new instance of the event handler that does not index events
     /// appearing in blocks before the specified input date. Note that this
     /// is a different behavior compared to [`Self::new()`]: that function
@@ -462,18 +494,53 @@ where
         Ok(())
     }
 
+    /// Updates the events of the history in windows of at most
+    /// `max_blocks_per_request` blocks. The progress is recorded after every
+    /// window, so that the backfill resumes from the last updated window if
+    /// it fails.
     async fn update_events_from_old_blocks(&mut self, range: RangeInclusive<u64>) -> Result<()> {
-        // first get the blocks needed to update `last_handled_blocks` because if it
-        // fails, it's safer to fail at the beginning of the function before we
-        // update Storage
-        let blocks = self
-            .block_retriever
-            .blocks(RangeInclusive::try_new(
-                range.end().saturating_sub(MAX_REORG_BLOCK_COUNT),
-                *range.end(),
-            )?)
-            .await?;
+        let (start, end) = range.into_inner();
+        let address = self
+            .contract
+            .address()
+            .iter()
+            .map(|address| format!("{address:?}"))
+            .join(",");
+        let window_size = self.backfill.max_blocks_per_request.max(1);
+        let mut window_start = start;
+        loop {
+            let window_end = end.min(window_start.saturating_add(window_size - 1));
+            // first get the blocks needed to update `last_handled_blocks` because if it
+            // fails, it's safer to fail at the beginning of the window before we
+            // update Storage
+            let blocks = self
+                .block_retriever
+                .blocks(RangeInclusive::try_new(
+                    if window_end == end {
+                        end.saturating_sub(MAX_REORG_BLOCK_COUNT)
+                    } else {
+                        window_end
+                    },
+                    window_end,
+                )?)
+                .await?;
+            self.update_events_in_block_range(RangeInclusive::try_new(window_start, window_end)?)
+                .await?;
+            self.update_last_handled_blocks(&blocks);
+
+            let remaining = end - window_end;
+            track_backfill_progress(&address, remaining);
+            if remaining == 0 {
+                return Ok(());
+            }
+            self.store.persist_last_indexed_block(window_end).await?;
+            tracing::info!(window_end, remaining, "backfilling events");
+            tokio::time::sleep(self.backfill.request_interval).await;
+            window_start = window_end + 1;
+        }
+    }
 
+    async fn update_events_in_block_range(&mut self, range: RangeInclusive<u64>) -> Result<()> {
         let events = self
             .past_events_by_block_number_range(&range)
             .await
@@ -527,8 +594,6 @@ where
         if !have_deleted_old_events {
             self.store.replace_events(Vec::new(), range.clone()).await?;
         }
-
-        self.update_last_handled_blocks(&blocks);
         Ok(())
     }
 
@@ -607,7 +672,9 @@ where
         &self,
         block_range: &RangeInclusive<u64>,
     ) -> Result<impl Stream<Item = Result<E>> + use<C, S, E> + Send> {
-        self.contract.get_events_by_block_range(block_range).await
+        self.contract
+            .get_events_by_block_range(block_range, self.backfill.max_blocks_per_request)
+            .await
     }
 
     fn update_last_handled_blocks(&mut self, blocks: &[BlockNumberHash]) {
@@ -758,6 +825,10 @@ struct Metrics {
     /// Tracks how many blocks were replaced/added in each call to EventHandler
     #[metric(labels("range"))]
     block_ranges: prometheus::IntCounterVec,
+
+    /// The number of blocks left to backfill the events of.
+    #[metric(labels("address"))]
+    backfill_blocks_remaining: prometheus::IntGaugeVec,
 }
 
 fn track_block_range(range: &str) {
@@ -768,6 +839,14 @@ fn track_block_range(range: &str) {
         .inc();
 }
 
+fn track_backfill_progress(address: &str, remaining: u64) {
+    Metrics::instance(observe::metrics::get_storage_registry())
+        .expect("unexpected error getting metrics instance")
+        .backfill_blocks_remaining
+        .with_label_values(&[address])
+        .set(remaining.try_into().unwrap_or(i64::MAX));
+}
+
 #[cfg(test)]
 mod tests {
     use {
@@ -935,6 +1014,126 @@ mod tests {
         );
     }
 
+    /// Blocks whose hashes are derived from their numbers.
+    #[derive(Debug)]
+    struct FakeBlocks;
+
+    #[async_trait::async_trait]
+    impl BlockRetrieving for FakeBlocks {
+        async fn current_block(&self) -> Result<ethrpc::block_stream::BlockInfo> {
+            unimplemented!()
+        }
+
+        async fn block(&self, number: u64) -> Result<BlockNumberHash> {
+            Ok((number, H256::from_low_u64_be(number)))
+        }
+
+        async fn blocks(&self, range: RangeInclusive<u64>) -> Result<Vec<BlockNumberHash>> {
+            Ok((*range.start()..=*range.end())
+                .map(|number| (number, H256::from_low_u64_be(number)))
+                .collect())
+        }
+    }
+
+    /// Emits an event with the first block of every requested page and
+    /// records the requested pages.
+    #[derive(Default)]
+    struct PageEvents {
+        pages: std::sync::Mutex<Vec<(u64, u64)>>,
+    }
+
+    #[async_trait::async_trait]
+    impl EventRetrieving for PageEvents {
+        type Event = u64;
+
+        async fn get_events_by_block_hash(&self, _: H256) -> Result<Vec<u64>> {
+            unimplemented!()
+        }
+
+        async fn get_events_by_block_range(
+            &self,
+            block_range: &RangeInclusive<u64>,
+            page_size: u64,
+        ) -> Result<EventStream<u64>> {
+            let pages = (*block_range.start()..=*block_range.end())
+                .step_by(page_size as usize)
+                .map(|start| (start, (start + page_size - 1).min(*block_range.end())))
+                .collect::<Vec<_>>();
+            self.pages.lock().unwrap().extend(pages.iter().copied());
+            Ok(Box::pin(futures::stream::iter(
+                pages.into_iter().map(|(start, _)| Ok(start)),
+            )))
+        }
+
+        fn address(&self) -> Vec<Address> {
+            vec![]
+        }
+    }
+
+    #[derive(Default)]
+    struct BackfillStorage {
+        events: Vec<u64>,
+        persisted: Vec<u64>,
+    }
+
+    #[async_trait::async_trait]
+    impl EventStoring<u64> for BackfillStorage {
+        async fn replace_events(
+            &mut self,
+            events: Vec<u64>,
+            range: RangeInclusive<u64>,
+        ) -> Result<()> {
+            self.events.retain(|block| block < range.start());
+            self.append_events(events).await
+        }
+
+        async fn append_events(&mut self, events: Vec<u64>) -> Result<()> {
+            self.events.extend(events);
+            Ok(())
+        }
+
+        async fn last_event_block(&self) -> Result<u64> {
+            Ok(self.events.last().copied().unwrap_or_default())
+        }
+
+        async fn persist_last_indexed_block(&mut self, last_block: u64) -> Result<()> {
+            self.persisted.push(last_block);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn backfills_history_in_windows() {
+        let mut event_handler = EventHandler::new(
+            Arc::new(FakeBlocks),
+            PageEvents::default(),
+            BackfillStorage::default(),
+            None,
+        )
+        .with_backfill(Backfill {
+            max_blocks_per_request: 100,
+            request_interval: Duration::from_millis(1),
+        });
+
+        event_handler
+            .update_events_from_old_blocks(RangeInclusive::try_new(0, 250).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            *event_handler.contract.pages.lock().unwrap(),
+            vec![(0, 99), (100, 199), (200, 250)]
+        );
+        assert_eq!(event_handler.store().events, vec![0, 100, 200]);
+        // The progress is persisted after every window but the last, which
+        // the caller persists.
+        assert_eq!(event_handler.store().persisted, vec![99, 199]);
+        assert_eq!(
+            event_handler.last_handled_block(),
+            Some((250, H256::from_low_u64_be(250)))
+        );
+    }
+
     #[tokio::test]
     #[ignore]
     async fn past_events_by_block_hashes_test() {
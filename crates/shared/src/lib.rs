@@ -22,12 +22,15 @@ pub mod gas_price_estimation;
 pub mod http_client;
 pub mod http_solver;
 pub mod interaction;
+pub mod listen;
 pub mod maintenance;
+pub mod multicall;
 pub mod order_quoting;
 pub mod order_validation;
 pub mod price_estimation;
 pub mod recent_block_cache;
 pub mod remaining_amounts;
+pub mod request_deadline;
 pub mod request_sharing;
 pub mod signature_validator;
 pub mod sources;
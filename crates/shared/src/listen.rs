@@ -0,0 +1,386 @@
+//! Listeners the HTTP APIs of the services are served on.
+//!
+//! An API can be served on several listeners at once, e.g. on an IPv4 and an
+//! IPv6 address, or on a unix domain socket for sidecars running next to the
+//! service. Each listener can terminate TLS on its own, optionally requiring
+//! clients to authenticate with certificates (mutual TLS) so that deployments
+//! crossing trust boundaries don't need an authenticating proxy in front of
+//! the service. The connections of all listeners get handed to a single
+//! server.
+
+use {
+    anyhow::{Context as _, Result, bail},
+    hyper::server::accept::Accept,
+    std::{
+        convert::Infallible,
+        fmt,
+        fs,
+        io,
+        net::SocketAddr,
+        path::{Path, PathBuf},
+        pin::Pin,
+        str::FromStr,
+        sync::Arc,
+        task::{Context, Poll},
+        time::Duration,
+    },
+    tokio::{
+        io::{AsyncRead, AsyncWrite},
+        net::TcpListener,
+        sync::mpsc,
+    },
+    tokio_rustls::{TlsAcceptor, rustls},
+};
+
+/// How long clients get to complete the TLS handshake.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long to wait before accepting connections again after accepting
+/// failed, e.g. because the process ran out of file descriptors.
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+
+/// The address a listener binds to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Address {
+    Tcp(SocketAddr),
+    /// The path of a unix domain socket.
+    Unix(PathBuf),
+}
+
+impl FromStr for Address {
+    type Err = std::net::AddrParseError;
+
+    /// Parses socket addresses like `0.0.0.0:80` or `[::]:80`, and paths of
+    /// unix domain sockets prefixed with `unix:`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.strip_prefix("unix:") {
+            Some(path) => Ok(Self::Unix(path.into())),
+            None => s.parse().map(Self::Tcp),
+        }
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tcp(addr) => write!(f, "{addr}"),
+            Self::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Listener {
+    pub address: Address,
+    /// Whether a listener on an IPv6 address also accepts IPv4 connections.
+    /// Disabling this allows binding the same port on an IPv4 address with a
+    /// separate listener.
+    pub dual_stack: bool,
+    /// If provided, the listener terminates TLS.
+    pub tls: Option<Tls>,
+}
+
+impl Listener {
+    /// A plain, dual-stack TCP listener.
+    pub fn tcp(addr: SocketAddr) -> Self {
+        Self {
+            address: Address::Tcp(addr),
+            dual_stack: true,
+            tls: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Tls {
+    /// The PEM file holding the certificate chain, starting with the
+    /// certificate of the server.
+    pub certificate: PathBuf,
+    /// The PEM file holding the private key of the certificate.
+    pub private_key: PathBuf,
+    /// If provided, clients must authenticate with a certificate issued by
+    /// one of the certificate authorities in this PEM file.
+    pub client_ca: Option<PathBuf>,
+}
+
+/// A connection accepted by one of the listeners.
+pub trait Io: AsyncRead + AsyncWrite + Send + Unpin + 'static {}
+
+impl<T> Io for T where T: AsyncRead + AsyncWrite + Send + Unpin + 'static {}
+
+/// The connections accepted by all listeners, for serving them with
+/// `hyper::Server::builder`.
+pub struct Incoming {
+    connections: mpsc::UnboundedReceiver<Box<dyn Io>>,
+    /// The addresses the TCP listeners are bound to, in the order they were
+    /// specified.
+    pub local_addrs: Vec<SocketAddr>,
+}
+
+impl Accept for Incoming {
+    type Conn = Box<dyn Io>;
+    type Error = Infallible;
+
+    fn poll_accept(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Conn, Self::Error>>> {
+        self.get_mut()
+            .connections
+            .poll_recv(cx)
+            .map(|connection| connection.map(Ok))
+    }
+}
+
+/// Binds all listeners, spawning a task per listener that accepts its
+/// connections until the returned connections get dropped. Fails if any of
+/// the listeners can't be bound.
+pub fn bind(listeners: &[Listener]) -> Result<Incoming> {
+    let (sender, connections) = mpsc::unbounded_channel();
+    let mut local_addrs = Vec::new();
+    for listener in listeners {
+        let address = &listener.address;
+        let tls = listener
+            .tls
+            .as_ref()
+            .map(acceptor)
+            .transpose()
+            .with_context(|| format!("invalid TLS configuration of listener {address}"))?;
+        match address {
+            Address::Tcp(addr) => {
+                let listener = bind_tcp(*addr, listener.dual_stack)
+                    .with_context(|| format!("failed to bind to {address}"))?;
+                local_addrs.push(listener.local_addr()?);
+                tokio::spawn(accept_tcp(listener, tls, sender.clone()));
+            }
+            Address::Unix(path) => {
+                let listener =
+                    bind_unix(path).with_context(|| format!("failed to bind to {address}"))?;
+                tokio::spawn(accept_unix(listener, tls, sender.clone()));
+            }
+        }
+        tracing::info!(%address, tls = listener.tls.is_some(), "listening");
+    }
+    Ok(Incoming {
+        connections,
+        local_addrs,
+    })
+}
+
+fn bind_tcp(addr: SocketAddr, dual_stack: bool) -> io::Result<TcpListener> {
+    let socket = socket2::Socket::new(
+        socket2::Domain::for_address(addr),
+        socket2::Type::STREAM,
+        Some(socket2::Protocol::TCP),
+    )?;
+    if addr.is_ipv6() {
+        socket.set_only_v6(!dual_stack)?;
+    }
+    socket.set_reuse_address(true)?;
+    socket.bind(&addr.into())?;
+    socket.listen(1024)?;
+    socket.set_nonblocking(true)?;
+    TcpListener::from_std(socket.into())
+}
+
+#[cfg(unix)]
+fn bind_unix(path: &Path) -> io::Result<tokio::net::UnixListener> {
+    use std::os::unix::fs::FileTypeExt;
+
+    // The socket of a previous run is left behind when the service stops and
+    // would make binding fail. Other files at the path are never removed.
+    if fs::symlink_metadata(path).is_ok_and(|metadata| metadata.file_type().is_socket()) {
+        fs::remove_file(path)?;
+    }
+    tokio::net::UnixListener::bind(path)
+}
+
+#[cfg(not(unix))]
+fn bind_unix(_: &Path) -> io::Result<std::convert::Infallible> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "unix domain sockets are not supported on this platform",
+    ))
+}
+
+async fn accept_tcp(
+    listener: TcpListener,
+    tls: Option<TlsAcceptor>,
+    sender: mpsc::UnboundedSender<Box<dyn Io>>,
+) {
+    loop {
+        let accepted = tokio::select! {
+            accepted = listener.accept() => accepted,
+            _ = sender.closed() => return,
+        };
+        match accepted {
+            Ok((stream, _)) => {
+                let _ = stream.set_nodelay(true);
+                forward(stream, tls.as_ref(), &sender);
+            }
+            Err(err) => {
+                tracing::warn!(?err, "failed to accept connection");
+                tokio::time::sleep(ACCEPT_BACKOFF).await;
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+async fn accept_unix(
+    listener: tokio::net::UnixListener,
+    tls: Option<TlsAcceptor>,
+    sender: mpsc::UnboundedSender<Box<dyn Io>>,
+) {
+    loop {
+        let accepted = tokio::select! {
+            accepted = listener.accept() => accepted,
+            _ = sender.closed() => return,
+        };
+        match accepted {
+            Ok((stream, _)) => forward(stream, tls.as_ref(), &sender),
+            Err(err) => {
+                tracing::warn!(?err, "failed to accept connection");
+                tokio::time::sleep(ACCEPT_BACKOFF).await;
+            }
+        }
+    }
+}
+
+#[cfg(not(unix))]
+async fn accept_unix(
+    listener: std::convert::Infallible,
+    _: Option<TlsAcceptor>,
+    _: mpsc::UnboundedSender<Box<dyn Io>>,
+) {
+    match listener {}
+}
+
+/// Hands an accepted connection over to the server. With TLS, the handshake
+/// happens in the background so that slow clients don't hold up accepting
+/// other connections.
+fn forward<S>(stream: S, tls: Option<&TlsAcceptor>, sender: &mpsc::UnboundedSender<Box<dyn Io>>)
+where
+    S: Io,
+{
+    let Some(tls) = tls else {
+        let _ = sender.send(Box::new(stream));
+        return;
+    };
+    let (tls, sender) = (tls.clone(), sender.clone());
+    tokio::spawn(async move {
+        match tokio::time::timeout(HANDSHAKE_TIMEOUT, tls.accept(stream)).await {
+            Ok(Ok(stream)) => {
+                let _ = sender.send(Box::new(stream));
+            }
+            Ok(Err(err)) => tracing::debug!(?err, "TLS handshake failed"),
+            Err(_) => tracing::debug!("TLS handshake timed out"),
+        }
+    });
+}
+
+fn acceptor(tls: &Tls) -> Result<TlsAcceptor> {
+    let certificates = read_pem(&tls.certificate)?
+        .into_iter()
+        .filter_map(|item| match item {
+            rustls_pemfile::Item::X509Certificate(der) => Some(rustls::Certificate(der)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    if certificates.is_empty() {
+        bail!("no certificate in {:?}", tls.certificate);
+    }
+    let key = read_pem(&tls.private_key)?
+        .into_iter()
+        .find_map(|item| match item {
+            rustls_pemfile::Item::RSAKey(der)
+            | rustls_pemfile::Item::PKCS8Key(der)
+            | rustls_pemfile::Item::ECKey(der) => Some(rustls::PrivateKey(der)),
+            _ => None,
+        })
+        .with_context(|| format!("no private key in {:?}", tls.private_key))?;
+
+    let builder = rustls::ServerConfig::builder().with_safe_defaults();
+    let builder = match &tls.client_ca {
+        Some(path) => {
+            let mut roots = rustls::RootCertStore::empty();
+            for item in read_pem(path)? {
+                if let rustls_pemfile::Item::X509Certificate(der) = item {
+                    roots.add(&rustls::Certificate(der))?;
+                }
+            }
+            if roots.is_empty() {
+                bail!("no certificate authority in {path:?}");
+            }
+            builder.with_client_cert_verifier(Arc::new(
+                rustls::server::AllowAnyAuthenticatedClient::new(roots),
+            ))
+        }
+        None => builder.with_no_client_auth(),
+    };
+    let mut config = builder.with_single_cert(certificates, key)?;
+    config.alpn_protocols = vec![b"http/1.1".to_vec()];
+    Ok(TlsAcceptor::from(Arc::new(config)))
+}
+
+fn read_pem(path: &Path) -> Result<Vec<rustls_pemfile::Item>> {
+    let file = fs::File::open(path).with_context(|| format!("failed to open {path:?}"))?;
+    rustls_pemfile::read_all(&mut io::BufReader::new(file))
+        .with_context(|| format!("failed to read PEM file {path:?}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, futures::future::poll_fn};
+
+    #[test]
+    fn parses_addresses() {
+        assert_eq!(
+            "[::]:80".parse::<Address>().unwrap(),
+            Address::Tcp("[::]:80".parse().unwrap())
+        );
+        assert_eq!(
+            "unix:/run/api.sock".parse::<Address>().unwrap(),
+            Address::Unix("/run/api.sock".into())
+        );
+        assert!("localhost".parse::<Address>().is_err());
+    }
+
+    async fn accept(incoming: &mut Incoming) -> Option<Result<Box<dyn Io>, Infallible>> {
+        poll_fn(|cx| Pin::new(&mut *incoming).poll_accept(cx)).await
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn accepts_connections_of_all_listeners() {
+        let path = std::env::temp_dir().join(format!("listen-{}.sock", std::process::id()));
+        let mut incoming = bind(&[
+            Listener::tcp("127.0.0.1:0".parse().unwrap()),
+            Listener {
+                address: Address::Unix(path.clone()),
+                dual_stack: true,
+                tls: None,
+            },
+        ])
+        .unwrap();
+
+        let _tcp = tokio::net::TcpStream::connect(incoming.local_addrs[0])
+            .await
+            .unwrap();
+        assert!(matches!(accept(&mut incoming).await, Some(Ok(_))));
+        let _unix = tokio::net::UnixStream::connect(&path).await.unwrap();
+        assert!(matches!(accept(&mut incoming).await, Some(Ok(_))));
+
+        // Stale sockets get replaced when binding again.
+        drop(incoming);
+        assert!(
+            bind(&[Listener {
+                address: Address::Unix(path.clone()),
+                dual_stack: true,
+                tls: None,
+            }])
+            .is_ok()
+        );
+        let _ = fs::remove_file(path);
+    }
+}
@@ -0,0 +1,272 @@
+//! Batching of contract calls into Multicall3 `aggregate3` calls.
+//!
+//! Fetching the state of many pools takes several `eth_call`s per pool. Even
+//! when the requests get batched by the transport, the node executes each of
+//! them separately. Aggregating the calls on the same block into a Multicall3
+//! call executes them all in a single `eth_call` instead.
+//!
+//! The calls are aggregated in chunks of a configurable size, each executed on
+//! its own so that a failing chunk doesn't fail the others. The calls of a
+//! chunk that reverts as a whole, for example because it runs out of gas, are
+//! retried individually.
+
+use {
+    alloy::{
+        eips::BlockId,
+        primitives::{Address, Bytes},
+        providers::Provider,
+        rpc::types::{TransactionInput, TransactionRequest},
+        sol_types::SolCall,
+    },
+    anyhow::{Context, Result, anyhow},
+    contracts::alloy::Multicall3::{self, Multicall3::Call3},
+    ethrpc::alloy::errors::ContractErrorExt,
+    futures::{
+        channel::{mpsc, oneshot},
+        stream::StreamExt as _,
+    },
+    std::{future::Future, time::Duration},
+    tokio_stream::StreamExt as _,
+};
+
+#[derive(Clone, Copy, Debug)]
+pub struct Config {
+    /// The most calls aggregated into a single Multicall3 call.
+    pub max_batch_size: usize,
+    /// How long to wait for more calls before executing an incomplete batch.
+    pub batch_delay: Duration,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            max_batch_size: 100,
+            batch_delay: Duration::from_millis(5),
+        }
+    }
+}
+
+/// Aggregates contract calls made on the same block into Multicall3 calls.
+#[derive(Clone, Debug)]
+pub struct Multicall {
+    calls: mpsc::UnboundedSender<Call>,
+}
+
+struct Call {
+    block: BlockId,
+    call: Call3,
+    result: oneshot::Sender<Result<Bytes>>,
+}
+
+impl Multicall {
+    /// Creates a batcher executing the calls through the Multicall3 contract
+    /// in a background task.
+    pub fn new(multicall: Multicall3::Instance, config: Config) -> Self {
+        let (calls, receiver) = mpsc::unbounded();
+        tokio::task::spawn(
+            receiver
+                .chunks_timeout(config.max_batch_size.max(1), config.batch_delay)
+                .for_each_concurrent(None, move |calls| execute(multicall.clone(), calls)),
+        );
+        Self { calls }
+    }
+
+    /// Calls the contract at the specified block as part of a batch. The call
+    /// is queued right away, so that the calls made together end up in the
+    /// same batch regardless of when the returned futures get polled.
+    pub fn call<C>(
+        &self,
+        target: Address,
+        call: C,
+        block: BlockId,
+    ) -> impl Future<Output = Result<C::Return>> + Send + 'static
+    where
+        C: SolCall,
+        C::Return: Send,
+    {
+        let (sender, receiver) = oneshot::channel();
+        let queued = self.calls.unbounded_send(Call {
+            block,
+            call: Call3 {
+                target,
+                allowFailure: true,
+                callData: call.abi_encode().into(),
+            },
+            result: sender,
+        });
+        async move {
+            queued.map_err(|_| anyhow!("multicall batching stopped"))?;
+            let data = receiver.await.context("multicall batch dropped")??;
+            C::abi_decode_returns(&data).context("decoding multicall result")
+        }
+    }
+}
+
+/// Executes a batch of calls, with one Multicall3 call per block.
+async fn execute(multicall: Multicall3::Instance, calls: Vec<Call>) {
+    let mut by_block = Vec::<(BlockId, Vec<Call>)>::new();
+    for call in calls {
+        match by_block.iter_mut().find(|(block, _)| *block == call.block) {
+            Some((_, calls)) => calls.push(call),
+            None => by_block.push((call.block, vec![call])),
+        }
+    }
+    futures::future::join_all(
+        by_block
+            .into_iter()
+            .map(|(block, calls)| aggregate(&multicall, block, calls)),
+    )
+    .await;
+}
+
+async fn aggregate(multicall: &Multicall3::Instance, block: BlockId, calls: Vec<Call>) {
+    let (calls, senders): (Vec<_>, Vec<_>) = calls
+        .into_iter()
+        .map(|call| (call.call, call.result))
+        .unzip();
+    let aggregated = multicall.aggregate3(calls.clone()).block(block);
+    let results = match aggregated.call().await {
+        Ok(results) if results.len() == senders.len() => results,
+        Ok(results) => {
+            let err = format!("expected {} results, got {}", senders.len(), results.len());
+            for sender in senders {
+                let _ = sender.send(Err(anyhow!("multicall failed: {err}")));
+            }
+            return;
+        }
+        Err(err) if err.is_contract_error() => {
+            tracing::debug!(?err, calls = calls.len(), "multicall reverted");
+            let calls = calls
+                .into_iter()
+                .zip(senders)
+                .map(|(call, sender)| async move {
+                    let _ = sender.send(call_individually(multicall, call, block).await);
+                });
+            futures::future::join_all(calls).await;
+            return;
+        }
+        Err(err) => {
+            let err = format!("{err:?}");
+            for sender in senders {
+                let _ = sender.send(Err(anyhow!("multicall failed: {err}")));
+            }
+            return;
+        }
+    };
+    for (result, sender) in results.into_iter().zip(senders) {
+        let result = if result.success {
+            Ok(result.returnData)
+        } else {
+            Err(anyhow!("call reverted: {}", result.returnData))
+        };
+        // Ignore callers that stopped awaiting their call.
+        let _ = sender.send(result);
+    }
+}
+
+/// Executes a call of a reverted Multicall3 call on its own, so that only the
+/// calls that revert by themselves fail.
+async fn call_individually(
+    multicall: &Multicall3::Instance,
+    call: Call3,
+    block: BlockId,
+) -> Result<Bytes> {
+    let tx = TransactionRequest::default()
+        .to(call.target)
+        .input(TransactionInput::new(call.callData));
+    multicall
+        .provider()
+        .call(tx)
+        .block(block)
+        .await
+        .context("call reverted")
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        alloy::{
+            eips::BlockNumberOrTag,
+            primitives::U256,
+            providers::ProviderBuilder,
+            rpc::json_rpc::ErrorPayload,
+            transports::mock::Asserter,
+        },
+        contracts::alloy::Multicall3::Multicall3::{
+            Result as CallResult,
+            aggregate3Call,
+            getBlockNumberCall,
+        },
+    };
+
+    fn multicall(asserter: &Asserter) -> Multicall {
+        let provider = ProviderBuilder::new()
+            .connect_mocked_client(asserter.clone())
+            .erased();
+        Multicall::new(
+            Multicall3::Instance::new(Address::repeat_byte(1), provider),
+            Config {
+                max_batch_size: 10,
+                batch_delay: Duration::from_millis(50),
+            },
+        )
+    }
+
+    fn revert() -> ErrorPayload {
+        ErrorPayload {
+            code: 3,
+            message: "execution reverted".into(),
+            data: Some(serde_json::value::to_raw_value("0x").unwrap()),
+        }
+    }
+
+    #[tokio::test]
+    async fn aggregates_calls_on_the_same_block() {
+        let asserter = Asserter::new();
+        let multicall = multicall(&asserter);
+
+        // Both calls are answered by a single `aggregate3` call, one of them
+        // reverting.
+        asserter.push_success(&aggregate3Call::abi_encode_returns(&vec![
+            CallResult {
+                success: true,
+                returnData: getBlockNumberCall::abi_encode_returns(&U256::from(42)).into(),
+            },
+            CallResult {
+                success: false,
+                returnData: Bytes::new(),
+            },
+        ]));
+        let block = BlockId::Number(BlockNumberOrTag::Number(42));
+        let target = Address::repeat_byte(2);
+        let (ok, reverted) = futures::join!(
+            multicall.call(target, getBlockNumberCall {}, block),
+            multicall.call(target, getBlockNumberCall {}, block),
+        );
+        assert_eq!(ok.unwrap(), U256::from(42));
+        assert!(reverted.is_err());
+    }
+
+    #[tokio::test]
+    async fn calls_individually_when_reverting() {
+        let asserter = Asserter::new();
+        let multicall = multicall(&asserter);
+
+        // The `aggregate3` call reverts as a whole, so the calls are retried
+        // on their own, only one of them reverting.
+        asserter.push_failure(revert());
+        asserter.push_success(&Bytes::from(getBlockNumberCall::abi_encode_returns(
+            &U256::from(42),
+        )));
+        asserter.push_failure(revert());
+        let block = BlockId::Number(BlockNumberOrTag::Number(42));
+        let target = Address::repeat_byte(2);
+        let (ok, reverted) = futures::join!(
+            multicall.call(target, getBlockNumberCall {}, block),
+            multicall.call(target, getBlockNumberCall {}, block),
+        );
+        assert_eq!(ok.unwrap(), U256::from(42));
+        assert!(reverted.is_err());
+    }
+}
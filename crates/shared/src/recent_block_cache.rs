@@ -8,10 +8,10 @@
 //! - Automatically updating the cache is decoupled from normal on-chain data
 //!   fetches.
 //!
-//! A result of this is that it is possible that the same uncached entry is
-//! requested multiple times simultaneously and some work is wasted. This is
-//! unlikely to happen in practice and the value is going to be cached the next
-//! time it is needed.
+//! A result of this is that the same uncached entry can be requested multiple
+//! times simultaneously. Concurrent fetches of an entry at the same block get
+//! coalesced into a single request to the node, so that several in-flight
+//! requests needing the same entry don't refresh it multiple times.
 //!
 //! When entries are requested we mark all those entries as recently used which
 //! potentially evicts other entries from the lru cache. Cache misses are
@@ -84,6 +84,16 @@ impl From<Block> for BlockNumber {
     }
 }
 
+impl From<Block> for alloy::eips::BlockId {
+    fn from(val: Block) -> Self {
+        match val {
+            Block::Recent => Self::latest(),
+            Block::Number(number) => Self::number(number),
+            Block::Finalized => Self::finalized(),
+        }
+    }
+}
+
 /// Recent block cache for arbitrary key-value pairs.
 ///
 /// Caches on-chain data for a specific number of blocks and automatically
@@ -142,6 +152,11 @@ struct Metrics {
     /// misses
     #[metric(labels("cache_type"))]
     recent_block_cache_misses: IntCounterVec,
+
+    /// cache misses joining an in-flight fetch of the same entry at the same
+    /// block instead of fetching it again
+    #[metric(labels("cache_type"))]
+    recent_block_cache_coalesced_fetches: IntCounterVec,
 }
 impl<K, V, F> RecentBlockCache<K, V, F>
 where
@@ -265,7 +280,9 @@ where
         let retries = self.maximum_retries;
         let delay = self.delay_between_retries;
         let fetcher = self.fetcher.clone();
+        let mut coalesced = true;
         let fut = self.requests.shared_or_else((key, block), |entry| {
+            coalesced = false;
             let (key, block) = entry.clone();
             async move {
                 for _ in 0..=retries {
@@ -280,6 +297,12 @@ where
             }
             .boxed()
         });
+        if coalesced {
+            self.metrics
+                .recent_block_cache_coalesced_fetches
+                .with_label_values(&[self.metrics_label])
+                .inc();
+        }
         fut.await.context("could not fetch liquidity")
     }
 
@@ -870,4 +893,57 @@ mod tests {
         assert!(cache.mutexed.lock().unwrap().get(key, Some(8)).is_some());
         assert!(cache.mutexed.lock().unwrap().get(key, None).is_some());
     }
+
+    /// Counts the requested keys, yielding once before returning so that
+    /// concurrent fetches overlap.
+    #[derive(Default)]
+    struct CountingFetcher(Arc<Mutex<HashMap<(TestKey, Block), usize>>>);
+
+    #[async_trait::async_trait]
+    impl CacheFetching<TestKey, TestValue> for CountingFetcher {
+        async fn fetch_values(
+            &self,
+            requested: HashSet<TestKey>,
+            block: Block,
+        ) -> Result<Vec<TestValue>> {
+            for key in &requested {
+                *self.0.lock().unwrap().entry((*key, block)).or_default() += 1;
+            }
+            tokio::task::yield_now().await;
+            Ok(requested
+                .into_iter()
+                .map(|key| TestValue::new(key.0, "a"))
+                .collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn coalesces_concurrent_fetches_at_same_block() {
+        let fetcher = CountingFetcher::default();
+        let fetches = fetcher.0.clone();
+        let block_stream = mock_single_block(BlockInfo {
+            number: 10,
+            ..Default::default()
+        });
+        let cache = RecentBlockCache::new(Default::default(), fetcher, block_stream, "")
+            .unwrap()
+            .inner;
+
+        let (a, b, c) = futures::join!(
+            cache.fetch(test_keys(0..2), Block::Number(10)),
+            cache.fetch(test_keys(1..3), Block::Number(10)),
+            cache.fetch(test_keys(1..2), Block::Number(9)),
+        );
+        assert_eq!(a.unwrap().len(), 2);
+        assert_eq!(b.unwrap().len(), 2);
+        assert_eq!(c.unwrap().len(), 1);
+
+        let fetches = fetches.lock().unwrap();
+        assert_eq!(fetches[&(TestKey(0), Block::Number(10))], 1);
+        assert_eq!(fetches[&(TestKey(1), Block::Number(10))], 1);
+        assert_eq!(fetches[&(TestKey(2), Block::Number(10))], 1);
+        // Fetches at different blocks are not coalesced.
+        assert_eq!(fetches[&(TestKey(1), Block::Number(9))], 1);
+        assert_eq!(fetches.len(), 4);
+    }
 }
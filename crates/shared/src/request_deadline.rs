@@ -0,0 +1,98 @@
+//! Deadlines clients attach to their requests.
+//!
+//! A client bounds how long it is willing to wait for a response either with
+//! an absolute `X-Deadline` header holding an RFC 3339 timestamp, or with a
+//! relative gRPC style `grpc-timeout` header like `500m` for 500 milliseconds.
+
+use chrono::{DateTime, Duration, Utc};
+
+/// The header holding the absolute deadline of a request.
+pub const X_DEADLINE: &str = "x-deadline";
+/// The header holding the timeout of a request relative to its arrival.
+pub const GRPC_TIMEOUT: &str = "grpc-timeout";
+
+/// Returns the deadline of a request given the values of its deadline headers.
+/// The earlier deadline wins if both headers are set. Malformed values are
+/// ignored.
+pub fn parse(
+    x_deadline: Option<&str>,
+    grpc_timeout: Option<&str>,
+    now: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    let absolute = x_deadline
+        .and_then(|value| DateTime::parse_from_rfc3339(value.trim()).ok())
+        .map(|deadline| deadline.with_timezone(&Utc));
+    let relative = grpc_timeout
+        .and_then(|value| parse_grpc_timeout(value.trim()))
+        .and_then(|timeout| now.checked_add_signed(timeout));
+    match (absolute, relative) {
+        (Some(absolute), Some(relative)) => Some(absolute.min(relative)),
+        (absolute, relative) => absolute.or(relative),
+    }
+}
+
+/// Parses a `grpc-timeout` value: a positive integer of at most 8 digits
+/// followed by one of the units `H`, `M`, `S`, `m`, `u` or `n`.
+fn parse_grpc_timeout(value: &str) -> Option<Duration> {
+    let (amount, unit) = value.split_at_checked(value.len().checked_sub(1)?)?;
+    if amount.is_empty() || amount.len() > 8 || !amount.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let amount = amount.parse().ok()?;
+    match unit {
+        "H" => Duration::try_hours(amount),
+        "M" => Duration::try_minutes(amount),
+        "S" => Duration::try_seconds(amount),
+        "m" => Duration::try_milliseconds(amount),
+        "u" => Some(Duration::microseconds(amount)),
+        "n" => Some(Duration::nanoseconds(amount)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_deadline_headers() {
+        let now = "2024-01-01T00:00:00Z".parse::<DateTime<Utc>>().unwrap();
+
+        assert_eq!(
+            parse(Some("2024-01-01T00:00:02+00:00"), None, now),
+            Some(now + Duration::seconds(2))
+        );
+        assert_eq!(
+            parse(None, Some("1500m"), now),
+            Some(now + Duration::milliseconds(1500))
+        );
+        assert_eq!(
+            parse(None, Some("2M"), now),
+            Some(now + Duration::minutes(2))
+        );
+        // the earlier deadline wins
+        assert_eq!(
+            parse(Some("2024-01-01T00:00:02Z"), Some("1S"), now),
+            Some(now + Duration::seconds(1))
+        );
+        assert_eq!(
+            parse(Some("2024-01-01T00:00:02Z"), Some("3S"), now),
+            Some(now + Duration::seconds(2))
+        );
+    }
+
+    #[test]
+    fn ignores_malformed_headers() {
+        let now = Utc::now();
+
+        assert_eq!(parse(None, None, now), None);
+        assert_eq!(parse(Some("tomorrow"), None, now), None);
+        for timeout in ["", "S", "10", "10s", "-1S", "123456789S", "1.5S"] {
+            assert_eq!(parse(None, Some(timeout), now), None, "{timeout}");
+        }
+        assert_eq!(
+            parse(Some("tomorrow"), Some("1S"), now),
+            Some(now + Duration::seconds(1))
+        );
+    }
+}
@@ -0,0 +1,137 @@
+//! Querying of the Balancer API shared by the Balancer V2 and V3 clients.
+//!
+//! Chains can have tens of thousands of registered pools, which the API only
+//! returns in pages. Paging with offsets gets slow for large offsets and
+//! skips or repeats pools whose order changes between the page requests, so
+//! the pools are paged through in the order of their creation instead, using
+//! the creation time of the last pool of a page as the cursor of the next one.
+
+use {
+    anyhow::{Result, ensure},
+    std::{collections::HashSet, future::Future, time::Duration},
+};
+
+/// The number of attempts made for every API query.
+const MAX_ATTEMPTS: usize = 5;
+
+/// The delay before retrying a failed query, which doubles with every attempt.
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+
+/// The maximum delay between two attempts of a query.
+const MAX_BACKOFF: Duration = Duration::from_secs(10);
+
+/// A pool returned by the Balancer API.
+pub trait ApiPool {
+    fn id(&self) -> &str;
+
+    /// The creation time of the pool as a UNIX timestamp.
+    fn create_time(&self) -> u64;
+}
+
+/// Fetches all pools created at or after the specified time, or all pools if
+/// no time is specified, page by page.
+///
+/// The pages get fetched for a cursor, and must contain the first `page_size`
+/// pools created at or after the cursor in the order of their creation. As
+/// pools created at the same time as the last pool of a page can be on the
+/// next page, the pages overlap and the pools get deduplicated.
+pub async fn paginate<P, F, Fut>(
+    page_size: usize,
+    created_since: Option<u64>,
+    mut fetch_page: F,
+) -> Result<Vec<P>>
+where
+    P: ApiPool,
+    F: FnMut(Option<u64>) -> Fut,
+    Fut: Future<Output = Result<Vec<P>>>,
+{
+    let mut pools = Vec::new();
+    let mut ids = HashSet::new();
+    let mut cursor = created_since;
+    loop {
+        let page = fetch_page(cursor).await?;
+        let no_more_pages = page.len() < page_size;
+        let last_create_time = page.iter().map(ApiPool::create_time).max();
+        for pool in page {
+            if ids.insert(pool.id().to_owned()) {
+                pools.push(pool);
+            }
+        }
+
+        let Some(last_create_time) = last_create_time.filter(|_| !no_more_pages) else {
+            break;
+        };
+        ensure!(
+            cursor != Some(last_create_time),
+            "more than {page_size} pools created at {last_create_time}"
+        );
+        cursor = Some(last_create_time);
+    }
+    Ok(pools)
+}
+
+/// Performs the query, retrying it with exponential backoff when it fails.
+pub async fn retry_with_backoff<T, F, Fut>(mut query: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut backoff = INITIAL_BACKOFF;
+    let mut attempt = 1;
+    loop {
+        match query().await {
+            Ok(result) => return Ok(result),
+            Err(err) if attempt < MAX_ATTEMPTS => {
+                tracing::warn!(?err, attempt, ?backoff, "retrying Balancer API query");
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+                attempt += 1;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::sync::Mutex};
+
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    struct Pool(&'static str, u64);
+
+    impl ApiPool for Pool {
+        fn id(&self) -> &str {
+            self.0
+        }
+
+        fn create_time(&self) -> u64 {
+            self.1
+        }
+    }
+
+    #[tokio::test]
+    async fn pages_through_pools_by_creation_time() {
+        let all = [
+            Pool("a", 1),
+            Pool("b", 2),
+            Pool("c", 2),
+            Pool("d", 3),
+            Pool("e", 4),
+        ];
+        let cursors = Mutex::new(Vec::new());
+        let fetch_page = |cursor: Option<u64>| {
+            cursors.lock().unwrap().push(cursor);
+            let page: Vec<_> = all
+                .iter()
+                .filter(|pool| pool.1 >= cursor.unwrap_or_default())
+                .take(3)
+                .cloned()
+                .collect();
+            async move { anyhow::Ok(page) }
+        };
+
+        let pools = paginate(3, None, fetch_page).await.unwrap();
+        assert_eq!(pools, all);
+        assert_eq!(*cursors.lock().unwrap(), [None, Some(2), Some(3)]);
+    }
+}
@@ -0,0 +1,163 @@
+//! Caching of the pools of token pairs in the Balancer pool registries.
+//!
+//! Resolving the pools of token pairs requires the lock of the registry's pool
+//! storage, which is also held while indexing newly created pools. The pools
+//! of a token pair only change when pools get indexed or removed though, so
+//! the registries cache them for the current version of their storage and
+//! resolve most token pairs with a lock-free lookup.
+
+use {
+    arc_swap::ArcSwap,
+    model::TokenPair,
+    prometheus::{IntCounter, IntCounterVec},
+    std::{
+        collections::{HashMap, HashSet},
+        hash::Hash,
+        sync::{
+            Arc,
+            atomic::{AtomicU64, Ordering},
+        },
+    },
+};
+
+/// The version of a pool registry's storage. It changes whenever pools get
+/// added to or removed from the storage.
+#[derive(Clone, Debug, Default)]
+pub struct Version(Arc<AtomicU64>);
+
+impl Version {
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Acquire)
+    }
+
+    /// Invalidates the token pairs cached for the previous version.
+    pub fn bump(&self) {
+        self.0.fetch_add(1, Ordering::AcqRel);
+        Metrics::get().balancer_registry_version_changes.inc();
+    }
+}
+
+/// The pools of token pairs cached for a version of a pool registry.
+pub struct PairCache<Id> {
+    version: Version,
+    entries: ArcSwap<Entries<Id>>,
+}
+
+struct Entries<Id> {
+    version: u64,
+    pools: HashMap<TokenPair, Arc<HashSet<Id>>>,
+}
+
+impl<Id> PairCache<Id>
+where
+    Id: Copy + Eq + Hash,
+{
+    pub fn new(version: Version) -> Self {
+        Self {
+            entries: ArcSwap::from_pointee(Entries {
+                version: version.get(),
+                pools: Default::default(),
+            }),
+            version,
+        }
+    }
+
+    /// Returns the pools containing at least one of the token pairs, if all
+    /// token pairs are cached for the current version of the registry.
+    pub fn get(&self, token_pairs: &HashSet<TokenPair>) -> Option<HashSet<Id>> {
+        let entries = self.entries.load();
+        let cached = entries.version == self.version.get()
+            && token_pairs
+                .iter()
+                .all(|pair| entries.pools.contains_key(pair));
+        Metrics::get()
+            .balancer_pair_cache_access
+            .with_label_values(&[if cached { "hits" } else { "misses" }])
+            .inc();
+        if !cached {
+            return None;
+        }
+        Some(
+            token_pairs
+                .iter()
+                .flat_map(|pair| entries.pools[pair].iter().copied())
+                .collect(),
+        )
+    }
+
+    /// Caches the pools of token pairs resolved at the specified version of
+    /// the registry.
+    pub fn insert(&self, version: u64, pools: HashMap<TokenPair, HashSet<Id>>) {
+        let pools: HashMap<_, _> = pools
+            .into_iter()
+            .map(|(pair, ids)| (pair, Arc::new(ids)))
+            .collect();
+        self.entries.rcu(|entries| {
+            if entries.version > version {
+                // The pools were resolved for an outdated version.
+                return entries.clone();
+            }
+            let mut cached = if entries.version == version {
+                entries.pools.clone()
+            } else {
+                HashMap::new()
+            };
+            cached.extend(pools.iter().map(|(pair, ids)| (*pair, ids.clone())));
+            Arc::new(Entries {
+                version,
+                pools: cached,
+            })
+        });
+    }
+}
+
+#[derive(prometheus_metric_storage::MetricStorage)]
+struct Metrics {
+    /// Balancer pool registry token pair cache hits and misses.
+    #[metric(labels("result"))]
+    balancer_pair_cache_access: IntCounterVec,
+
+    /// Number of pool additions and removals invalidating the token pairs
+    /// cached by the Balancer pool registries.
+    balancer_registry_version_changes: IntCounter,
+}
+
+impl Metrics {
+    fn get() -> &'static Self {
+        Metrics::instance(observe::metrics::get_storage_registry()).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, ethcontract::H160, maplit::hashmap};
+
+    fn pair(a: u64, b: u64) -> TokenPair {
+        TokenPair::new(H160::from_low_u64_be(a), H160::from_low_u64_be(b)).unwrap()
+    }
+
+    #[test]
+    fn caches_pairs_until_the_version_changes() {
+        let version = Version::default();
+        let cache = PairCache::<u64>::new(version.clone());
+        let pairs: HashSet<_> = [pair(1, 2), pair(2, 3)].into();
+        assert_eq!(cache.get(&pairs), None);
+
+        cache.insert(
+            version.get(),
+            hashmap! { pair(1, 2) => [10, 11].into(), pair(2, 3) => [11].into() },
+        );
+        assert_eq!(cache.get(&pairs), Some([10, 11].into()));
+        assert_eq!(cache.get(&[pair(2, 3)].into()), Some([11].into()));
+        assert_eq!(cache.get(&[pair(1, 3)].into()), None);
+
+        version.bump();
+        assert_eq!(cache.get(&pairs), None);
+
+        // Pools resolved for an outdated version don't get cached.
+        cache.insert(version.get() - 1, hashmap! { pair(1, 2) => [10].into() });
+        assert_eq!(cache.get(&[pair(1, 2)].into()), None);
+        cache.insert(version.get(), hashmap! { pair(1, 2) => [10].into() });
+        assert_eq!(cache.get(&[pair(1, 2)].into()), Some([10].into()));
+    }
+}
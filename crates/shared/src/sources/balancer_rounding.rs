@@ -0,0 +1,106 @@
+//! Selection of the rounding of the Balancer fixed point math.
+//!
+//! The fixed point modules round like the Balancer contracts, always in the
+//! direction favouring the pool, so that computed amounts match the on-chain
+//! execution. Research builds, compiled with the `research-rounding` feature,
+//! can instead round every operation to the nearest value, which quantifies
+//! how much the rounding conservatism costs in quoted amounts. Without the
+//! feature, the contract rounding is always used and the selection compiles
+//! away.
+
+use {
+    anyhow::{Result, ensure},
+    ethcontract::U256,
+    num::{BigInt, Signed},
+    std::sync::atomic::{AtomicBool, Ordering},
+};
+
+static NEAREST: AtomicBool = AtomicBool::new(false);
+
+/// The rounding of the fixed point operations.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Rounding {
+    /// Round in the direction the contracts round in.
+    #[default]
+    Strict,
+    /// Round to the nearest value, with ties rounding away from zero. Amounts
+    /// computed this way can't be relied on for settling.
+    Nearest,
+}
+
+/// Selects the rounding of the fixed point operations of the whole process.
+/// Fails when selecting nearest rounding in builds without the
+/// `research-rounding` feature.
+pub fn set(rounding: Rounding) -> Result<()> {
+    ensure!(
+        cfg!(feature = "research-rounding") || rounding == Rounding::Strict,
+        "rounding to the nearest value requires the `research-rounding` feature"
+    );
+    NEAREST.store(rounding == Rounding::Nearest, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Returns whether the fixed point operations round to the nearest value.
+#[inline]
+pub fn nearest() -> bool {
+    cfg!(feature = "research-rounding") && NEAREST.load(Ordering::Relaxed)
+}
+
+/// Divides rounding to the nearest integer, with ties rounding up. The divisor
+/// must not be zero.
+pub fn div_nearest(a: U256, b: U256) -> U256 {
+    let quotient = a / b;
+    // Compares the remainder against half the divisor without overflowing.
+    if a % b >= b - b / 2 {
+        quotient + 1
+    } else {
+        quotient
+    }
+}
+
+/// Divides signed integers rounding to the nearest integer, with ties
+/// rounding away from zero. The divisor must not be zero.
+pub fn div_nearest_signed(a: &BigInt, b: &BigInt) -> BigInt {
+    let magnitude = (a.abs() * 2 + b.abs()) / (b.abs() * 2);
+    if a.is_negative() != b.is_negative() {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn divides_to_nearest() {
+        let div = |a: u64, b: u64| div_nearest(a.into(), b.into()).as_u64();
+        assert_eq!(div(14, 10), 1);
+        assert_eq!(div(15, 10), 2);
+        assert_eq!(div(16, 10), 2);
+        assert_eq!(div(4, 3), 1);
+        assert_eq!(div(5, 3), 2);
+        assert_eq!(div_nearest(U256::MAX, U256::MAX), U256::one());
+    }
+
+    #[test]
+    fn divides_signed_to_nearest() {
+        let div = |a: i64, b: i64| div_nearest_signed(&a.into(), &b.into());
+        assert_eq!(div(14, 10), 1.into());
+        assert_eq!(div(15, 10), 2.into());
+        assert_eq!(div(-15, 10), (-2).into());
+        assert_eq!(div(-14, 10), (-1).into());
+        assert_eq!(div(15, -10), (-2).into());
+        assert_eq!(div(-16, -10), 2.into());
+        assert_eq!(div(0, -10), 0.into());
+    }
+
+    #[cfg(not(feature = "research-rounding"))]
+    #[test]
+    fn nearest_rounding_requires_research_builds() {
+        assert!(set(Rounding::Nearest).is_err());
+        assert!(set(Rounding::Strict).is_ok());
+        assert!(!nearest());
+    }
+}
@@ -0,0 +1,211 @@
+//! Snapshots of the Balancer pool registries in an embedded store.
+//!
+//! Initializing the registries from the Balancer API and replaying the pool
+//! creation events since takes minutes on mainnet. The registries instead
+//! snapshot the pools they got initialized with, the pools indexed from
+//! events since and the last indexed block, so that restarts resume indexing
+//! from the snapshot.
+
+use {
+    anyhow::{Context, Result},
+    ethcontract::H160,
+    serde::{Deserialize, Serialize, de::DeserializeOwned},
+    std::{collections::HashSet, path::Path, sync::Arc},
+};
+
+/// The number of blocks between the snapshots of a registry.
+const SNAPSHOT_INTERVAL: u64 = 50;
+
+/// An embedded store of registry snapshots.
+pub struct SnapshotStore {
+    db: sled::Db,
+}
+
+impl SnapshotStore {
+    /// Opens the store at the specified path, creating it if it doesn't
+    /// exist.
+    pub fn open(path: &Path) -> Result<Self> {
+        let db = sled::open(path).with_context(|| format!("opening {path:?}"))?;
+        Ok(Self { db })
+    }
+
+    /// Loads the snapshot stored under the specified key.
+    pub fn load<P>(&self, key: &str) -> Result<Option<Snapshot<P>>>
+    where
+        P: DeserializeOwned,
+    {
+        let Some(registered) = self.get(&registered_key(key))? else {
+            return Ok(None);
+        };
+        let Some(progress) = self.get::<Progress>(&progress_key(key))? else {
+            return Ok(None);
+        };
+        Ok(Some(Snapshot {
+            block: progress.block,
+            registered,
+            created: progress.created,
+            pruned: progress.pruned,
+        }))
+    }
+
+    fn get<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
+        self.db
+            .get(key)?
+            .map(|value| serde_json::from_slice(&value))
+            .transpose()
+            .with_context(|| format!("decoding snapshot {key}"))
+    }
+
+    fn insert<T: Serialize>(&self, key: &str, value: &T) -> Result<()> {
+        self.db.insert(key, serde_json::to_vec(value)?)?;
+        Ok(())
+    }
+}
+
+/// The state of a registry, from which it can resume indexing.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Snapshot<P> {
+    /// The last block whose pool creation events are indexed.
+    pub block: u64,
+    /// The pools the registry got initialized with.
+    pub registered: P,
+    /// The addresses and creation blocks of the pools indexed from events
+    /// that the registry didn't get initialized with.
+    pub created: Vec<(H160, u64)>,
+    /// The addresses of the pools whose creations are not indexed.
+    pub pruned: HashSet<H160>,
+}
+
+/// The part of a snapshot that changes as the registry indexes events. It is
+/// stored separately from the initial pools, which only need storing once.
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Progress {
+    block: u64,
+    created: Vec<(H160, u64)>,
+    #[serde(default)]
+    pruned: HashSet<H160>,
+}
+
+fn registered_key(key: &str) -> String {
+    format!("{key}/registered")
+}
+
+fn progress_key(key: &str) -> String {
+    format!("{key}/progress")
+}
+
+/// Periodically snapshots the pools of a registry.
+pub struct Snapshotter<P> {
+    store: Arc<SnapshotStore>,
+    key: String,
+    registered: P,
+    /// The addresses of the pools the registry got initialized with.
+    addresses: HashSet<H160>,
+    /// The block of the last snapshot, if one was saved.
+    saved: Option<u64>,
+}
+
+impl<P> Snapshotter<P>
+where
+    P: Serialize,
+{
+    /// Creates a snapshotter storing the snapshots of a registry initialized
+    /// with the specified pools under the key.
+    pub fn new(
+        store: Arc<SnapshotStore>,
+        key: String,
+        registered: P,
+        addresses: HashSet<H160>,
+    ) -> Self {
+        Self {
+            store,
+            key,
+            registered,
+            addresses,
+            saved: None,
+        }
+    }
+
+    /// Saves a snapshot at the last indexed block, unless one was saved
+    /// recently. Pools of the registry that it didn't get initialized with
+    /// are recorded as created.
+    pub fn save(
+        &mut self,
+        block: u64,
+        pools: impl Iterator<Item = (H160, u64)>,
+        pruned: &HashSet<H160>,
+    ) -> Result<()> {
+        if self
+            .saved
+            .is_some_and(|saved| block < saved + SNAPSHOT_INTERVAL)
+        {
+            return Ok(());
+        }
+        if self.saved.is_none() {
+            self.store
+                .insert(&registered_key(&self.key), &self.registered)?;
+        }
+        let progress = Progress {
+            block,
+            created: pools
+                .filter(|(address, _)| !self.addresses.contains(address))
+                .collect(),
+            pruned: pruned.clone(),
+        };
+        self.store.insert(&progress_key(&self.key), &progress)?;
+        self.saved = Some(block);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saves_snapshots_periodically() {
+        let store = Arc::new(SnapshotStore {
+            db: sled::Config::new().temporary(true).open().unwrap(),
+        });
+        let initial = H160([1; 20]);
+        let created = H160([2; 20]);
+        let pruned = HashSet::from([H160([3; 20])]);
+        let mut snapshotter = Snapshotter::new(
+            store.clone(),
+            "pools".to_owned(),
+            vec![initial],
+            HashSet::from([initial]),
+        );
+        assert_eq!(store.load::<Vec<H160>>("pools").unwrap(), None);
+
+        snapshotter
+            .save(100, [(initial, 0)].into_iter(), &pruned)
+            .unwrap();
+        snapshotter
+            .save(149, [(initial, 0), (created, 120)].into_iter(), &pruned)
+            .unwrap();
+        assert_eq!(
+            store.load("pools").unwrap(),
+            Some(Snapshot {
+                block: 100,
+                registered: vec![initial],
+                created: vec![],
+                pruned: pruned.clone(),
+            })
+        );
+
+        snapshotter
+            .save(150, [(initial, 0), (created, 120)].into_iter(), &pruned)
+            .unwrap();
+        assert_eq!(
+            store.load("pools").unwrap(),
+            Some(Snapshot {
+                block: 150,
+                registered: vec![initial],
+                created: vec![(created, 120)],
+                pruned,
+            })
+        );
+    }
+}
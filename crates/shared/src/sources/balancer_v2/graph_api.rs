@@ -9,15 +9,22 @@
 //!   from the node
 
 use {
-    super::swap::{fixed_point::Bfp, signed_fixed_point::SBfp},
-    crate::subgraph::SubgraphClient,
+    super::swap::{
+        fixed_point::Bfp,
+        signed_fixed_point::{FixedPointPrecision, SBfp},
+    },
+    crate::{
+        sources::balancer_api::{self, ApiPool},
+        subgraph::SubgraphClient,
+    },
     anyhow::{Context, Result},
+    bigdecimal::BigDecimal,
     ethcontract::{H160, H256},
     reqwest::{Client, Url},
-    serde::{Deserialize, Deserializer, Serialize},
+    serde::{Deserialize, Deserializer, Serialize, Serializer},
     serde_json::json,
     serde_with::{DisplayFromStr, serde_as},
-    std::collections::HashMap,
+    std::collections::{HashMap, HashSet},
 };
 
 const QUERY_PAGE_SIZE: usize = 100;
@@ -46,8 +53,6 @@ where
 
             // Parse valid decimal strings with automatic precision detection
             // (same logic as the original SBfp::Deserialize implementation)
-            use crate::sources::balancer_v2::swap::signed_fixed_point::FixedPointPrecision;
-
             let precision = if s.contains('.')
                 && s.split('.').nth(1).map_or(0, |decimals| decimals.len()) > 30
             {
@@ -82,6 +87,38 @@ where
     }
 }
 
+/// Serializes optional fixed point values as the decimal strings the API
+/// returns, so that exported pool bundles read back the same values.
+fn serialize_optional_sbfp<S>(value: &Option<SBfp>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    value
+        .map(|value| value.to_string_with_precision(FixedPointPrecision::Standard18))
+        .serialize(serializer)
+}
+
+/// Like [`serialize_optional_sbfp`] for the E-CLP parameters the API returns
+/// with 38 decimals.
+fn serialize_optional_extended_sbfp<S>(
+    value: &Option<SBfp>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    value
+        .map(|value| value.to_string_with_precision(FixedPointPrecision::Extended38))
+        .serialize(serializer)
+}
+
+fn serialize_optional_bfp<S>(value: &Option<Bfp>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    value.map(|value| value.to_string()).serialize(serializer)
+}
+
 /// Balancer API v3 client for fetching pool data.
 pub struct BalancerApiClient {
     client: SubgraphClient,
@@ -119,41 +156,44 @@ impl BalancerApiClient {
 
     /// Retrieves all registered pools for the configured chain.
     pub async fn get_registered_pools(&self) -> Result<RegisteredPools> {
+        self.query_pools(None).await
+    }
+
+    /// Retrieves the registered pools created at or after the specified UNIX
+    /// timestamp, for resyncing pools that were initialized before without
+    /// fetching all pools again.
+    pub async fn get_pools_created_since(&self, create_time: u64) -> Result<RegisteredPools> {
+        self.query_pools(Some(create_time)).await
+    }
+
+    async fn query_pools(&self, created_since: Option<u64>) -> Result<RegisteredPools> {
         use self::pools_query::*;
 
-        let mut pools = Vec::new();
-        let mut skip = 0;
-
-        // Use offset-based pagination with Balancer API v3
-        loop {
-            let page = self
-                .client
-                .query::<Data>(
-                    QUERY,
-                    Some(json_map! {
-                        "first" => QUERY_PAGE_SIZE,
-                        "skip" => skip,
-                        "orderBy" => "totalLiquidity",
-                        "orderDirection" => "desc",
-                        "where" => json!({
-                            "chainIn": [self.chain],
-                            "poolTypeIn": ["WEIGHTED", "STABLE", "LIQUIDITY_BOOTSTRAPPING", "COMPOSABLE_STABLE", "GYROE", "GYRO"],
-                            "protocolVersionIn": [2]
-                        }),
-                    }),
-                )
-                .await?
-                .aggregator_pools;
-
-            let no_more_pages = page.len() != QUERY_PAGE_SIZE;
-            pools.extend(page);
-
-            if no_more_pages {
-                break;
+        let pools = balancer_api::paginate(QUERY_PAGE_SIZE, created_since, |cursor| {
+            let mut filter = json!({
+                "chainIn": [self.chain],
+                "poolTypeIn": ["WEIGHTED", "STABLE", "LIQUIDITY_BOOTSTRAPPING", "COMPOSABLE_STABLE", "GYROE", "GYRO"],
+                "protocolVersionIn": [2]
+            });
+            if let Some(cursor) = cursor {
+                // Includes the pools created at the cursor, see `paginate`.
+                filter["createTime"] = json!({ "gt": cursor.saturating_sub(1) });
             }
-
-            skip += QUERY_PAGE_SIZE;
-        }
+            let variables = Some(json_map! {
+                "first" => QUERY_PAGE_SIZE,
+                "orderBy" => "createTime",
+                "orderDirection" => "asc",
+                "where" => filter,
+            });
+            async move {
+                let data = balancer_api::retry_with_backoff(|| {
+                    self.client.query_without_retry::<Data>(QUERY, &variables)
+                })
+                .await?;
+                Ok(data.aggregator_pools)
+            }
+        })
+        .await?;
 
         Ok(RegisteredPools {
             fetched_block_number: 0, // Balancer API v3 doesn't support historical queries
@@ -163,7 +203,11 @@ impl BalancerApiClient {
 }
 
 /// Result of the registered pool query.
-#[derive(Debug, Default, Eq, PartialEq)]
+///
+/// This is also the format of the pool bundles that registries can get
+/// exported to and initialized from.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
 pub struct RegisteredPools {
     /// The block number that the data was fetched. Set to 0 for Balancer API v3
     /// since it doesn't support historical queries.
@@ -182,6 +226,21 @@ impl RegisteredPools {
         }
     }
 
+    /// Returns the creation time of the most recently created pool.
+    pub fn latest_create_time(&self) -> Option<u64> {
+        self.pools.iter().map(|pool| pool.create_time).max()
+    }
+
+    /// Adds the pools that are not registered yet and returns how many pools
+    /// were added.
+    pub fn extend_new(&mut self, pools: Vec<PoolData>) -> usize {
+        let ids: HashSet<_> = self.pools.iter().map(|pool| pool.id.clone()).collect();
+        let len = self.pools.len();
+        self.pools
+            .extend(pools.into_iter().filter(|pool| !ids.contains(&pool.id)));
+        self.pools.len() - len
+    }
+
     /// Groups registered pools by factory addresses.
     pub fn group_by_factory(self) -> HashMap<H160, RegisteredPools> {
         let fetched_block_number = self.fetched_block_number;
@@ -203,7 +262,7 @@ impl RegisteredPools {
 
 /// Pool data from the Balancer API v3.
 #[serde_as]
-#[derive(Debug, Deserialize, Eq, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct PoolData {
     pub id: String, // Can be 32-byte (V2) or 20-byte (V3) hex string
@@ -216,52 +275,125 @@ pub struct PoolData {
     pub pool_tokens: Vec<Token>,
     pub dynamic_data: DynamicData,
     pub create_time: u64,
-    #[serde(default, deserialize_with = "deserialize_optional_sbfp")]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_sbfp",
+        serialize_with = "serialize_optional_sbfp"
+    )]
     pub alpha: Option<SBfp>,
-    #[serde(default, deserialize_with = "deserialize_optional_sbfp")]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_sbfp",
+        serialize_with = "serialize_optional_sbfp"
+    )]
     pub beta: Option<SBfp>,
-    #[serde(default, deserialize_with = "deserialize_optional_sbfp")]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_sbfp",
+        serialize_with = "serialize_optional_sbfp"
+    )]
     pub c: Option<SBfp>,
-    #[serde(default, deserialize_with = "deserialize_optional_sbfp")]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_sbfp",
+        serialize_with = "serialize_optional_sbfp"
+    )]
     pub s: Option<SBfp>,
-    #[serde(default, deserialize_with = "deserialize_optional_sbfp")]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_sbfp",
+        serialize_with = "serialize_optional_sbfp"
+    )]
     pub lambda: Option<SBfp>,
-    #[serde(default, deserialize_with = "deserialize_optional_sbfp")]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_sbfp",
+        serialize_with = "serialize_optional_extended_sbfp"
+    )]
     pub tau_alpha_x: Option<SBfp>,
-    #[serde(default, deserialize_with = "deserialize_optional_sbfp")]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_sbfp",
+        serialize_with = "serialize_optional_extended_sbfp"
+    )]
     pub tau_alpha_y: Option<SBfp>,
-    #[serde(default, deserialize_with = "deserialize_optional_sbfp")]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_sbfp",
+        serialize_with = "serialize_optional_extended_sbfp"
+    )]
     pub tau_beta_x: Option<SBfp>,
-    #[serde(default, deserialize_with = "deserialize_optional_sbfp")]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_sbfp",
+        serialize_with = "serialize_optional_extended_sbfp"
+    )]
     pub tau_beta_y: Option<SBfp>,
-    #[serde(default, deserialize_with = "deserialize_optional_sbfp")]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_sbfp",
+        serialize_with = "serialize_optional_extended_sbfp"
+    )]
     pub u: Option<SBfp>,
-    #[serde(default, deserialize_with = "deserialize_optional_sbfp")]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_sbfp",
+        serialize_with = "serialize_optional_extended_sbfp"
+    )]
     pub v: Option<SBfp>,
-    #[serde(default, deserialize_with = "deserialize_optional_sbfp")]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_sbfp",
+        serialize_with = "serialize_optional_extended_sbfp"
+    )]
     pub w: Option<SBfp>,
-    #[serde(default, deserialize_with = "deserialize_optional_sbfp")]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_sbfp",
+        serialize_with = "serialize_optional_extended_sbfp"
+    )]
     pub z: Option<SBfp>,
-    #[serde(default, deserialize_with = "deserialize_optional_sbfp")]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_sbfp",
+        serialize_with = "serialize_optional_extended_sbfp"
+    )]
     pub d_sq: Option<SBfp>,
-    #[serde(default, deserialize_with = "deserialize_optional_sbfp")]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_sbfp",
+        serialize_with = "serialize_optional_sbfp"
+    )]
     pub sqrt_alpha: Option<SBfp>,
-    #[serde(default, deserialize_with = "deserialize_optional_sbfp")]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_sbfp",
+        serialize_with = "serialize_optional_sbfp"
+    )]
     pub sqrt_beta: Option<SBfp>,
-    #[serde(default, deserialize_with = "deserialize_optional_bfp")]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_bfp",
+        serialize_with = "serialize_optional_bfp"
+    )]
     pub root3_alpha: Option<Bfp>,
 }
 
 /// Dynamic data for pools from Balancer API v3.
-#[derive(Debug, Deserialize, Eq, PartialEq)]
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct DynamicData {
     pub swap_enabled: bool,
+    /// The USD value of the pool balances as estimated by the API.
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default)]
+    pub total_liquidity: Option<BigDecimal>,
 }
 
 /// Token data for pools.
 #[serde_as]
-#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq)]
 pub struct Token {
     pub address: H160,
     pub decimals: u8,
@@ -284,6 +416,16 @@ pub enum PoolType {
     GyroE,
 }
 
+impl ApiPool for PoolData {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn create_time(&self) -> u64 {
+        self.create_time
+    }
+}
+
 impl PoolData {
     /// Converts the API pool type string to our internal enum.
     pub fn pool_type_enum(&self) -> PoolType {
@@ -304,6 +446,11 @@ impl PoolData {
         self.dynamic_data.swap_enabled
     }
 
+    /// Returns the USD value of the pool balances, if the API knows it.
+    pub fn total_liquidity(&self) -> Option<&BigDecimal> {
+        self.dynamic_data.total_liquidity.as_ref()
+    }
+
     /// Returns the tokens with the correct field mapping.
     pub fn tokens(&self) -> Vec<Token> {
         self.pool_tokens.clone()
@@ -341,14 +488,12 @@ mod pools_query {
     pub const QUERY: &str = r#"
         query aggregatorPools(
             $first: Int,
-            $skip: Int,
             $orderBy: GqlPoolOrderBy,
             $orderDirection: GqlPoolOrderDirection,
             $where: GqlAggregatorPoolFilter
         ) {
             aggregatorPools(
                 first: $first
-                skip: $skip
                 orderBy: $orderBy
                 orderDirection: $orderDirection
                 where: $where
@@ -367,6 +512,7 @@ mod pools_query {
                 }
                 dynamicData {
                     swapEnabled
+                    totalLiquidity
                 }
                 createTime
                 alpha
@@ -428,7 +574,8 @@ mod tests {
                             },
                         ],
                         "dynamicData": {
-                            "swapEnabled": true
+                            "swapEnabled": true,
+                            "totalLiquidity": "1234.56"
                         },
                         "createTime": 1234567890
                     },
@@ -527,7 +674,10 @@ mod tests {
                                 price_rate_provider: None,
                             },
                         ],
-                        dynamic_data: DynamicData { swap_enabled: true },
+                        dynamic_data: DynamicData {
+                            swap_enabled: true,
+                            total_liquidity: Some("1234.56".parse().unwrap()),
+                        },
                         create_time: 1234567890,
                         alpha: None,
                         beta: None,
@@ -569,7 +719,10 @@ mod tests {
                                 price_rate_provider: None,
                             },
                         ],
-                        dynamic_data: DynamicData { swap_enabled: true },
+                        dynamic_data: DynamicData {
+                            swap_enabled: true,
+                            total_liquidity: None,
+                        },
                         create_time: 1234567890,
                         alpha: None,
                         beta: None,
@@ -611,7 +764,10 @@ mod tests {
                                 price_rate_provider: None,
                             },
                         ],
-                        dynamic_data: DynamicData { swap_enabled: true },
+                        dynamic_data: DynamicData {
+                            swap_enabled: true,
+                            total_liquidity: None,
+                        },
                         create_time: 1234567890,
                         alpha: None,
                         beta: None,
@@ -653,7 +809,10 @@ mod tests {
                                 price_rate_provider: None,
                             },
                         ],
-                        dynamic_data: DynamicData { swap_enabled: true },
+                        dynamic_data: DynamicData {
+                            swap_enabled: true,
+                            total_liquidity: None,
+                        },
                         create_time: 1234567890,
                         alpha: None,
                         beta: None,
@@ -845,7 +1004,10 @@ mod tests {
                     factory: H160([0x55; 20]),
                     chain: GqlChain::GNOSIS,
                     pool_tokens: vec![],
-                    dynamic_data: DynamicData { swap_enabled: true },
+                    dynamic_data: DynamicData {
+                        swap_enabled: true,
+                        total_liquidity: None,
+                    },
                     create_time: 0,
                     alpha: None,
                     beta: None,
@@ -874,7 +1036,10 @@ mod tests {
                     factory: H160([0x55; 20]),
                     chain: GqlChain::GNOSIS,
                     pool_tokens: vec![],
-                    dynamic_data: DynamicData { swap_enabled: true },
+                    dynamic_data: DynamicData {
+                        swap_enabled: true,
+                        total_liquidity: None,
+                    },
                     create_time: 0,
                     alpha: None,
                     beta: None,
@@ -903,7 +1068,10 @@ mod tests {
                     factory: H160([0x66; 20]),
                     chain: GqlChain::GNOSIS,
                     pool_tokens: vec![],
-                    dynamic_data: DynamicData { swap_enabled: true },
+                    dynamic_data: DynamicData {
+                        swap_enabled: true,
+                        total_liquidity: None,
+                    },
                     create_time: 0,
                     alpha: None,
                     beta: None,
@@ -931,4 +1099,48 @@ mod tests {
         assert_eq!(grouped[&H160([0x55; 20])].pools.len(), 2);
         assert_eq!(grouped[&H160([0x66; 20])].pools.len(), 1);
     }
+
+    #[test]
+    fn exported_pools_round_trip() {
+        use pools_query::*;
+
+        let data: Data = serde_json::from_value(json!({
+            "aggregatorPools": [
+                {
+                    "type": "GYROE",
+                    "address": "0x80fd5bc9d4fA6C22132f8bb2d9d30B01c3336FB3",
+                    "id": "0x1111111111111111111111111111111111111111111111111111111111111111",
+                    "protocolVersion": 2,
+                    "factory": "0x5555555555555555555555555555555555555555",
+                    "chain": "GNOSIS",
+                    "poolTokens": [
+                        {
+                            "address": "0x3333333333333333333333333333333333333333",
+                            "decimals": 18,
+                            "weight": "0.5",
+                            "priceRateProvider": null
+                        }
+                    ],
+                    "dynamicData": { "swapEnabled": true, "totalLiquidity": "1234.56" },
+                    "createTime": 1740124250,
+                    "alpha": "0.7",
+                    "c": "0.707106781186547524",
+                    "tauAlphaX": "-0.17378533390904767196396190604716688",
+                    "dSq": "0.9999999999999999988662409334210612",
+                    "root3Alpha": "0.5"
+                }
+            ]
+        }))
+        .unwrap();
+        let pools = RegisteredPools {
+            fetched_block_number: 42,
+            pools: data.aggregator_pools,
+        };
+
+        let exported = serde_json::to_string(&pools).unwrap();
+        assert_eq!(
+            serde_json::from_str::<RegisteredPools>(&exported).unwrap(),
+            pools
+        );
+    }
 }
@@ -19,7 +19,7 @@ use {
             Pool,
             PoolIndexing,
             PoolKind,
-            common::{self, PoolInfoFetcher},
+            common::{self, PoolInfoFetcher, PoolInfoFetching},
             gyro_2clp,
             gyro_3clp,
             gyro_e,
@@ -30,10 +30,18 @@ use {
     },
     crate::{
         ethrpc::Web3,
+        event_handling::Backfill,
+        multicall::Multicall,
         recent_block_cache::{Block, CacheConfig},
+        sources::{
+            balancer_snapshots::{Snapshot, SnapshotStore, Snapshotter},
+            chain_profile::ChainProfile,
+            rate_provider_cache::RateProviderCache,
+        },
         token_info::TokenInfoFetching,
     },
     anyhow::{Context, Result},
+    bigdecimal::ToPrimitive,
     clap::ValueEnum,
     contracts::alloy::{
         BalancerV2ComposableStablePoolFactory,
@@ -55,12 +63,16 @@ use {
         InstanceExt,
         Provider as DynProvider,
     },
-    ethcontract::{BlockId, H160, H256},
-    ethrpc::block_stream::{BlockRetrieving, CurrentBlockWatcher},
+    ethcontract::{BlockId, H160, H256, U256},
+    ethrpc::{
+        alloy::conversions::IntoLegacy,
+        block_stream::{BlockRetrieving, CurrentBlockWatcher},
+    },
+    futures::future,
     model::TokenPair,
     reqwest::{Client, Url},
     std::{
-        collections::{BTreeMap, HashSet},
+        collections::{BTreeMap, HashMap, HashSet},
         sync::Arc,
     },
     tracing::instrument,
@@ -119,6 +131,9 @@ pub struct StablePool {
     pub common: CommonPoolState,
     pub reserves: BTreeMap<H160, TokenState>,
     pub amplification_parameter: AmplificationParameter,
+    /// The BPT supply net of the BPT held by the vault. Only composable stable
+    /// pools have it, as they hold their own BPT.
+    pub actual_supply: Option<U256>,
 }
 
 impl StablePool {
@@ -132,6 +147,7 @@ impl StablePool {
             },
             reserves: stable_state.tokens.into_iter().collect(),
             amplification_parameter: stable_state.amplification_parameter,
+            actual_supply: stable_state.actual_supply,
         }
     }
 }
@@ -392,124 +408,47 @@ impl BalancerFactoryInstance {
 impl BalancerFactoryKind {
     /// Returns a vector with supported factories for the specified chain ID.
     pub fn for_chain(chain_id: u64) -> Vec<Self> {
-        match chain_id {
-            1 => vec![
-                // Mainnet
-                Self::Weighted,
-                Self::WeightedV3,
-                Self::WeightedV4,
-                Self::Weighted2Token,
-                Self::StableV2,
-                Self::LiquidityBootstrapping,
-                Self::NoProtocolFeeLiquidityBootstrapping,
-                Self::ComposableStable,
-                Self::ComposableStableV3,
-                Self::ComposableStableV4,
-                Self::ComposableStableV5,
-                Self::ComposableStableV6,
-                Self::GyroE,
-            ],
-            10 => vec![
-                // Optimism
-                Self::Weighted,
-                Self::WeightedV3,
-                Self::WeightedV4,
-                Self::Weighted2Token,
-                Self::StableV2,
-                Self::NoProtocolFeeLiquidityBootstrapping,
-                Self::ComposableStable,
-                Self::ComposableStableV3,
-                Self::ComposableStableV4,
-                Self::ComposableStableV5,
-                Self::ComposableStableV6,
-                Self::GyroE,
-            ],
-            56 => vec![
-                // BNB
-                Self::WeightedV3,
-                Self::WeightedV4,
-                Self::NoProtocolFeeLiquidityBootstrapping,
-                Self::ComposableStable,
-                Self::ComposableStableV3,
-                Self::ComposableStableV4,
-                Self::ComposableStableV5,
-                Self::ComposableStableV6,
-            ],
-            100 => vec![
-                // Gnosis
-                Self::WeightedV3,
-                Self::WeightedV4,
-                Self::StableV2,
-                Self::NoProtocolFeeLiquidityBootstrapping,
-                Self::ComposableStableV3,
-                Self::ComposableStableV4,
-                Self::ComposableStableV5,
-                Self::ComposableStableV6,
-                Self::GyroE,
-            ],
-            137 => vec![
-                // Polygon
-                Self::Weighted,
-                Self::WeightedV3,
-                Self::WeightedV4,
-                Self::Weighted2Token,
-                Self::StableV2,
-                Self::LiquidityBootstrapping,
-                Self::NoProtocolFeeLiquidityBootstrapping,
-                Self::ComposableStable,
-                Self::ComposableStableV3,
-                Self::ComposableStableV4,
-                Self::ComposableStableV5,
-                Self::ComposableStableV6,
-                Self::Gyro2CLP,
-                Self::Gyro3CLP,
-                Self::GyroE,
-            ],
-            8453 => vec![
-                // Base
-                Self::WeightedV4,
-                Self::NoProtocolFeeLiquidityBootstrapping,
-                Self::ComposableStableV5,
-                Self::ComposableStableV6,
-                Self::GyroE,
-            ],
-            42161 => vec![
-                // Arbitrum One
-                Self::Weighted,
-                Self::WeightedV3,
-                Self::WeightedV4,
-                Self::Weighted2Token,
-                Self::StableV2,
-                Self::LiquidityBootstrapping,
-                Self::NoProtocolFeeLiquidityBootstrapping,
-                Self::ComposableStable,
-                Self::ComposableStableV3,
-                Self::ComposableStableV4,
-                Self::ComposableStableV5,
-                Self::ComposableStableV6,
-                Self::Gyro2CLP,
-                Self::GyroE,
-            ],
-            43114 => vec![
-                // Avalanche
-                Self::WeightedV3,
-                Self::WeightedV4,
-                Self::NoProtocolFeeLiquidityBootstrapping,
-                Self::ComposableStableV4,
-                Self::ComposableStableV5,
-                Self::ComposableStableV6,
-                Self::GyroE,
-            ],
-            11155111 => vec![
-                // Sepolia
-                Self::WeightedV4,
-                Self::NoProtocolFeeLiquidityBootstrapping,
-                Self::ComposableStableV4,
-                Self::ComposableStableV5,
-                Self::ComposableStableV6,
-            ],
-            _ => Default::default(),
-        }
+        ChainProfile::for_chain(chain_id)
+            .map(|profile| profile.balancer_v2_factories.to_vec())
+            .unwrap_or_default()
+    }
+}
+
+/// Which pools to leave out of the registry at initialization.
+///
+/// Mainnet has thousands of Balancer V2 pools holding next to no liquidity.
+/// Indexing them grows the registry and the cost of refreshing pool state
+/// without ever yielding a useful route.
+#[derive(Clone, Debug, Default)]
+pub struct PoolPruning {
+    /// The minimum USD value of the pool balances, as reported by the Balancer
+    /// API, for a pool to be indexed. Pools the API reports no value for are
+    /// always indexed.
+    pub min_tvl: Option<f64>,
+    /// Pools that are indexed regardless of their value.
+    pub keep: HashSet<H256>,
+}
+
+impl PoolPruning {
+    /// Removes the pools below the minimum value from the registered pools and
+    /// returns the addresses of the removed pools grouped by factory.
+    fn prune(&self, registered: &mut RegisteredPools) -> HashMap<H160, HashSet<H160>> {
+        let Some(min_tvl) = self.min_tvl else {
+            return Default::default();
+        };
+        let mut pruned = HashMap::<_, HashSet<_>>::new();
+        registered.pools.retain(|pool| {
+            let keep = pool.id_as_h256().is_ok_and(|id| self.keep.contains(&id))
+                || pool
+                    .total_liquidity()
+                    .and_then(|tvl| tvl.to_f64())
+                    .is_none_or(|tvl| tvl >= min_tvl);
+            if !keep {
+                pruned.entry(pool.factory).or_default().insert(pool.address);
+            }
+            keep
+        });
+        pruned
     }
 }
 
@@ -517,6 +456,13 @@ impl BalancerFactoryKind {
 pub struct BalancerContracts {
     pub vault: BalancerV2Vault::Instance,
     pub factories: Vec<BalancerFactoryInstance>,
+    /// Batches the vault calls fetching the pool balances into Multicall3
+    /// calls. Without it, the balances of every pool are fetched with a
+    /// separate call.
+    pub multicall: Option<Multicall>,
+    /// Caches the rates of the rate providers of the pool tokens. Without it,
+    /// the rates are fetched for every pool state.
+    pub rate_providers: Option<RateProviderCache>,
 }
 
 impl BalancerContracts {
@@ -606,7 +552,12 @@ impl BalancerContracts {
             factories.push(instance);
         }
 
-        Ok(Self { vault, factories })
+        Ok(Self {
+            vault,
+            factories,
+            multicall: None,
+            rate_providers: None,
+        })
     }
 }
 
@@ -622,9 +573,45 @@ impl BalancerPoolFetcher {
         web3: Web3,
         contracts: &BalancerContracts,
         deny_listed_pool_ids: Vec<H256>,
+        pruning: PoolPruning,
         chain: GqlChain,
     ) -> Result<Self> {
         let pool_initializer = BalancerApiClient::from_subgraph_url(subgraph_url, client, chain)?;
+        Self::with_initializer(
+            pool_initializer,
+            block_retriever,
+            token_infos,
+            config,
+            block_stream,
+            web3,
+            contracts,
+            deny_listed_pool_ids,
+            pruning,
+            None,
+            Default::default(),
+        )
+        .await
+    }
+
+    /// Creates a fetcher whose registries get initialized by the specified
+    /// initializer instead of the Balancer API, e.g. from an exported pool
+    /// bundle. With a snapshot store, the registries resume from their
+    /// snapshots if there are any, and save snapshots as they index events.
+    /// The events of the history are fetched within the backfill limits.
+    #[expect(clippy::too_many_arguments)]
+    pub async fn with_initializer(
+        pool_initializer: impl PoolInitializing,
+        block_retriever: Arc<dyn BlockRetrieving>,
+        token_infos: Arc<dyn TokenInfoFetching>,
+        config: CacheConfig,
+        block_stream: CurrentBlockWatcher,
+        web3: Web3,
+        contracts: &BalancerContracts,
+        deny_listed_pool_ids: Vec<H256>,
+        pruning: PoolPruning,
+        snapshots: Option<Arc<SnapshotStore>>,
+        backfill: Backfill,
+    ) -> Result<Self> {
         let web3 = ethrpc::instrumented::instrument_with_label(&web3, "balancerV2".into());
         let fetcher = Arc::new(Cache::new(
             create_aggregate_pool_fetcher(
@@ -633,6 +620,9 @@ impl BalancerPoolFetcher {
                 block_retriever,
                 token_infos,
                 contracts,
+                &pruning,
+                snapshots,
+                backfill,
             )
             .await?,
             config,
@@ -703,39 +693,70 @@ impl BalancerPoolFetching for BalancerPoolFetcher {
 }
 
 /// Creates an aggregate fetcher for all supported pool factories.
+#[allow(clippy::too_many_arguments)]
 async fn create_aggregate_pool_fetcher(
     web3: Web3,
     pool_initializer: impl PoolInitializing,
     block_retriever: Arc<dyn BlockRetrieving>,
     token_infos: Arc<dyn TokenInfoFetching>,
     contracts: &BalancerContracts,
+    pruning: &PoolPruning,
+    snapshots: Option<Arc<SnapshotStore>>,
+    backfill: Backfill,
 ) -> Result<Aggregate> {
-    let registered_pools = pool_initializer.initialize_pools().await?;
-    let fetched_block_number = registered_pools.fetched_block_number;
-    let fetched_block_hash = web3
-        .eth()
-        .block(BlockId::Number(fetched_block_number.into()))
-        .await?
-        .context("failed to get block by block number")?
-        .hash
-        .context("missing hash from block")?;
-    let mut registered_pools_by_factory = registered_pools.group_by_factory();
+    let factories = contracts
+        .factories
+        .iter()
+        .map(|instance| (*instance.address()).into_legacy())
+        .collect::<Vec<_>>();
+    let restored = match &snapshots {
+        Some(store) => restore_snapshots(store, &factories).unwrap_or_else(|err| {
+            tracing::warn!(?err, "failed to restore Balancer V2 pool snapshots");
+            None
+        }),
+        None => None,
+    };
+    let mut initial_by_factory = match restored {
+        Some(restored) => {
+            let registered = RegisteredPools {
+                fetched_block_number: restored
+                    .values()
+                    .map(|snapshot| snapshot.registered.fetched_block_number)
+                    .min()
+                    .unwrap_or_default(),
+                pools: restored
+                    .values()
+                    .flat_map(|snapshot| snapshot.registered.pools.iter().cloned())
+                    .collect(),
+            };
+            tracing::info!(
+                pools = registered.pools.len(),
+                "resuming Balancer V2 pools from snapshots"
+            );
+            pool_initializer.restored(&registered);
+            restored
+        }
+        None => initialize_pools(&pool_initializer, pruning, &factories).await?,
+    };
 
     macro_rules! registry {
         ($factory:ident, $instance:expr_2021) => {{
-            use ethrpc::alloy::conversions::IntoLegacy;
             create_internal_pool_fetcher(
                 contracts.vault.clone(),
+                contracts.multicall.clone(),
+                contracts.rate_providers.clone(),
                 web3.clone(),
                 $factory::Instance::new(*$instance.address(), $instance.provider().clone()),
                 block_retriever.clone(),
                 token_infos.clone(),
                 $instance,
-                registered_pools_by_factory
+                initial_by_factory
                     .remove(&(*$instance.address()).into_legacy())
-                    .unwrap_or_else(|| RegisteredPools::empty(fetched_block_number)),
-                fetched_block_hash,
-            )?
+                    .unwrap_or_default(),
+                snapshots.clone(),
+                backfill,
+            )
+            .await?
         }};
     }
 
@@ -793,15 +814,12 @@ async fn create_aggregate_pool_fetcher(
 
     // Just to catch cases where new Balancer factories get added for a pool
     // kind, but we don't index it, log a warning for unused pools.
-    if !registered_pools_by_factory.is_empty() {
-        let total_count = registered_pools_by_factory
+    if !initial_by_factory.is_empty() {
+        let total_count = initial_by_factory
             .values()
-            .map(|registered| registered.pools.len())
+            .map(|initial| initial.registered.pools.len())
             .sum::<usize>();
-        let factories = registered_pools_by_factory
-            .keys()
-            .copied()
-            .collect::<Vec<_>>();
+        let factories = initial_by_factory.keys().copied().collect::<Vec<_>>();
         tracing::warn!(
             %total_count, ?factories,
             "found pools that don't correspond to any known Balancer pool factory",
@@ -811,37 +829,138 @@ async fn create_aggregate_pool_fetcher(
     Ok(Aggregate::new(fetchers))
 }
 
+/// Initializes the pools of the registries with the pool initializer, as the
+/// initial snapshots of the registries by factory.
+async fn initialize_pools(
+    pool_initializer: &impl PoolInitializing,
+    pruning: &PoolPruning,
+    factories: &[H160],
+) -> Result<HashMap<H160, Snapshot<RegisteredPools>>> {
+    let mut registered_pools = pool_initializer.initialize_pools().await?;
+    let mut pruned_by_factory = pruning.prune(&mut registered_pools);
+    if !pruned_by_factory.is_empty() {
+        let pruned = pruned_by_factory.values().map(HashSet::len).sum::<usize>();
+        tracing::info!(%pruned, "pruned Balancer V2 pools below the minimum TVL");
+    }
+    let fetched_block_number = registered_pools.fetched_block_number;
+    let mut registered_pools_by_factory = registered_pools.group_by_factory();
+    for factory in factories {
+        registered_pools_by_factory
+            .entry(*factory)
+            .or_insert_with(|| RegisteredPools::empty(fetched_block_number));
+    }
+
+    Ok(registered_pools_by_factory
+        .into_iter()
+        .map(|(factory, registered)| {
+            let initial = Snapshot {
+                block: fetched_block_number,
+                registered,
+                created: Vec::new(),
+                pruned: pruned_by_factory.remove(&factory).unwrap_or_default(),
+            };
+            (factory, initial)
+        })
+        .collect())
+}
+
+/// Loads the snapshots of the registries of all the factories, or `None` if
+/// any of them is missing.
+fn restore_snapshots(
+    store: &SnapshotStore,
+    factories: &[H160],
+) -> Result<Option<HashMap<H160, Snapshot<RegisteredPools>>>> {
+    factories
+        .iter()
+        .map(|factory| {
+            let snapshot = store.load(&snapshot_key(*factory))?;
+            Ok(snapshot.map(|snapshot| (*factory, snapshot)))
+        })
+        .collect()
+}
+
+/// The key the snapshots of the registry of a factory are stored under.
+fn snapshot_key(factory: H160) -> String {
+    format!("balancer_v2/{factory:#x}")
+}
+
 /// Helper method for creating a boxed `InternalPoolFetching` instance for the
 /// specified factory and parameters.
-fn create_internal_pool_fetcher<Factory>(
+#[allow(clippy::too_many_arguments)]
+async fn create_internal_pool_fetcher<Factory>(
     vault: BalancerV2Vault::Instance,
+    multicall: Option<Multicall>,
+    rate_providers: Option<RateProviderCache>,
     web3: Web3,
     factory: Factory,
     block_retriever: Arc<dyn BlockRetrieving>,
     token_infos: Arc<dyn TokenInfoFetching>,
     factory_instance: &BalancerFactoryInstance,
-    registered_pools: RegisteredPools,
-    fetched_block_hash: H256,
+    initial: Snapshot<RegisteredPools>,
+    snapshots: Option<Arc<SnapshotStore>>,
+    backfill: Backfill,
 ) -> Result<Box<dyn InternalPoolFetching>>
 where
     Factory: FactoryIndexing,
 {
-    let initial_pools = registered_pools
+    let fetcher = Arc::new(PoolInfoFetcher::new(
+        vault,
+        web3.clone(),
+        factory,
+        token_infos,
+        multicall,
+        rate_providers,
+    ));
+    let registered_pools = initial.registered;
+    let mut initial_pools = registered_pools
         .pools
         .iter()
         .map(|pool| Factory::PoolInfo::from_graph_data(pool, registered_pools.fetched_block_number))
-        .collect::<Result<_>>()?;
-    let start_sync_at_block = Some((registered_pools.fetched_block_number, fetched_block_hash));
+        .collect::<Result<Vec<_>>>()?;
+    // The pools indexed from events are not part of the registered pools, so
+    // their infos get fetched again.
+    initial_pools.extend(
+        future::try_join_all(
+            initial
+                .created
+                .iter()
+                .map(|(address, block_created)| fetcher.fetch_pool_info(*address, *block_created)),
+        )
+        .await?,
+    );
+    let start_sync_at_block = Some((initial.block, block_hash(&web3, initial.block).await?));
+    let snapshotter = snapshots.map(|store| {
+        let addresses = registered_pools
+            .pools
+            .iter()
+            .map(|pool| pool.address)
+            .collect();
+        let key = snapshot_key((*factory_instance.address()).into_legacy());
+        Snapshotter::new(store, key, registered_pools, addresses)
+    });
 
     Ok(Box::new(Registry::new(
         block_retriever,
-        Arc::new(PoolInfoFetcher::new(vault, web3, factory, token_infos)),
+        fetcher,
         factory_instance,
         initial_pools,
+        initial.pruned,
         start_sync_at_block,
+        snapshotter,
+        backfill,
     )))
 }
 
+/// Returns the hash of the block with the specified number.
+async fn block_hash(web3: &Web3, number: u64) -> Result<H256> {
+    web3.eth()
+        .block(BlockId::Number(number.into()))
+        .await?
+        .context("failed to get block by block number")?
+        .hash
+        .context("missing hash from block")
+}
+
 /// Extract the pool address from an ID.
 ///
 /// This takes advantage that the first 20 bytes of the ID is the address of
@@ -856,7 +975,12 @@ fn pool_address_from_id(pool_id: H256) -> H160 {
 
 #[cfg(test)]
 mod tests {
-    use {super::*, hex_literal::hex};
+    use {
+        super::*,
+        crate::sources::balancer_v2::graph_api::PoolData,
+        hex_literal::hex,
+        maplit::hashset,
+    };
 
     #[test]
     fn can_extract_address_from_pool_id() {
@@ -867,4 +991,50 @@ mod tests {
             addr!("36128d5436d2d70cab39c9af9cce146c38554ff0"),
         );
     }
+
+    #[test]
+    fn prunes_pools_below_minimum_tvl() {
+        let pool = |byte: u8, tvl: Option<&str>| -> PoolData {
+            serde_json::from_value(serde_json::json!({
+                "id": const_hex::encode_prefixed([byte; 32]),
+                "address": H160([byte; 20]),
+                "type": "WEIGHTED",
+                "protocolVersion": 2,
+                "factory": H160([0xfa; 20]),
+                "chain": "MAINNET",
+                "poolTokens": [],
+                "dynamicData": { "swapEnabled": true, "totalLiquidity": tvl },
+                "createTime": 0,
+            }))
+            .unwrap()
+        };
+        let mut registered = RegisteredPools {
+            fetched_block_number: 0,
+            pools: vec![
+                pool(1, Some("5000.0")),
+                pool(2, Some("12.5")),
+                pool(3, None),
+                pool(4, Some("0")),
+            ],
+        };
+        let pruning = PoolPruning {
+            min_tvl: Some(1000.),
+            keep: hashset! { H256([4; 32]) },
+        };
+
+        let pruned = pruning.prune(&mut registered);
+
+        assert_eq!(
+            registered
+                .pools
+                .iter()
+                .map(|pool| pool.address)
+                .collect::<Vec<_>>(),
+            vec![H160([1; 20]), H160([3; 20]), H160([4; 20])],
+        );
+        assert_eq!(
+            pruned,
+            HashMap::from([(H160([0xfa; 20]), hashset! { H160([2; 20]) })]),
+        );
+    }
 }
@@ -18,7 +18,14 @@
 use {
     crate::{
         event_handling::EventStoring,
-        sources::balancer_v2::pools::{FactoryIndexing, PoolIndexing, common},
+        sources::{
+            balancer_pair_cache::Version,
+            balancer_snapshots::Snapshotter,
+            balancer_v2::{
+                graph_api::RegisteredPools,
+                pools::{FactoryIndexing, PoolIndexing, common},
+            },
+        },
     },
     alloy::rpc::types::Log,
     anyhow::{Context, Result},
@@ -50,6 +57,13 @@ where
     /// The block the initial pools were fetched on. This block is considered
     /// reorg-safe and events prior to this block do not get replaced.
     initial_fetched_block: u64,
+    /// Changes whenever pools get added or removed.
+    version: Version,
+    /// Addresses of pools that are not indexed, e.g. because they hold too
+    /// little liquidity to be worth routing through.
+    pruned: HashSet<H160>,
+    /// Saves snapshots of the indexed pools, if configured.
+    snapshotter: Option<Snapshotter<RegisteredPools>>,
 }
 
 impl<Factory> PoolStorage<Factory>
@@ -66,6 +80,9 @@ where
                 pools_by_token: Default::default(),
                 pools: Default::default(),
                 initial_fetched_block: 0,
+                version: Default::default(),
+                pruned: Default::default(),
+                snapshotter: None,
             },
             |mut storage, pool| {
                 storage.initial_fetched_block =
@@ -77,6 +94,19 @@ where
         )
     }
 
+    /// Skips indexing the creations of the pools with the specified addresses.
+    pub fn with_pruned(mut self, pruned: HashSet<H160>) -> Self {
+        self.pruned = pruned;
+        self
+    }
+
+    /// Saves snapshots of the indexed pools whenever the indexed block
+    /// advances.
+    pub fn with_snapshotter(mut self, snapshotter: Option<Snapshotter<RegisteredPools>>) -> Self {
+        self.snapshotter = snapshotter;
+        self
+    }
+
     /// Returns all pools containing both tokens from `TokenPair`
     fn pool_ids_for_token_pair(
         &self,
@@ -103,6 +133,22 @@ where
             .collect()
     }
 
+    /// Returns the pools containing each of the token pairs.
+    pub fn pool_ids_by_token_pair(
+        &self,
+        token_pairs: &HashSet<TokenPair>,
+    ) -> HashMap<TokenPair, HashSet<H256>> {
+        token_pairs
+            .iter()
+            .map(|pair| (*pair, self.pool_ids_for_token_pair(pair).collect()))
+            .collect()
+    }
+
+    /// The version of the stored pools.
+    pub fn version(&self) -> &Version {
+        &self.version
+    }
+
     /// Returns a pool by ID or none if no such pool exists.
     pub fn pool_by_id(&self, pool_id: H256) -> Option<&Factory::PoolInfo> {
         self.pools.get(&pool_id)
@@ -133,11 +179,16 @@ where
         pool_creation: PoolCreated,
         block_created: u64,
     ) -> Result<()> {
+        let address = pool_creation.pool.into_legacy();
+        if self.pruned.contains(&address) {
+            return Ok(());
+        }
         let pool = self
             .pool_info_fetcher
-            .fetch_pool_info(pool_creation.pool.into_legacy(), block_created)
+            .fetch_pool_info(address, block_created)
             .await?;
         self.insert_pool(pool);
+        self.version.bump();
 
         Ok(())
     }
@@ -169,6 +220,7 @@ where
         for pool_set in self.pools_by_token.values_mut() {
             pool_set.retain(|pool_id| self.pools.contains_key(pool_id));
         }
+        self.version.bump();
     }
 
     pub fn last_event_block(&self) -> u64 {
@@ -219,9 +271,15 @@ where
         Ok(self.last_event_block())
     }
 
-    async fn persist_last_indexed_block(&mut self, _block: u64) -> Result<()> {
-        // storage is only in-memory so we don't need to persist anything here
-        Ok(())
+    async fn persist_last_indexed_block(&mut self, block: u64) -> Result<()> {
+        let Some(snapshotter) = &mut self.snapshotter else {
+            return Ok(());
+        };
+        let pools = self
+            .pools
+            .values()
+            .map(|pool| (pool.common().address, pool.common().block_created));
+        snapshotter.save(block, pools, &self.pruned)
     }
 }
 
@@ -371,6 +429,7 @@ mod tests {
         // Note that it is never expected that blocks for events will differ,
         // but in this test block_created for the pool is the first block it receives.
         assert_eq!(pool_store.last_event_block(), 2);
+        assert_eq!(pool_store.version().get(), n as u64);
         assert_eq!(
             pool_store.pools_by_token.get(&tokens[0]).unwrap(),
             &hashset! { pool_ids[0] }
@@ -406,6 +465,45 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn skips_pruned_pool_creations() {
+        let (pool_ids, pool_addresses, tokens, weights, creation_events) = pool_init_data(0, 1);
+
+        let indexed_pool = weighted::PoolInfo {
+            common: common::PoolInfo {
+                id: pool_ids[1],
+                address: pool_addresses[1],
+                tokens: vec![tokens[1], tokens[2]],
+                scaling_factors: vec![Bfp::exp10(0), Bfp::exp10(0)],
+                rate_providers: vec![H160::zero(), H160::zero()],
+                block_created: creation_events[1].1,
+            },
+            weights: vec![weights[1], weights[2]],
+        };
+
+        // Only the pool that isn't pruned gets fetched.
+        let mut mock_pool_fetcher = MockPoolInfoFetching::<MockFactoryIndexing>::new();
+        mock_pool_fetcher
+            .expect_fetch_pool_info()
+            .with(eq(pool_addresses[1]), eq(creation_events[1].1))
+            .times(1)
+            .returning(move |_, _| Ok(indexed_pool.clone()));
+
+        let mut pool_store = PoolStorage::new(Default::default(), Arc::new(mock_pool_fetcher))
+            .with_pruned(hashset! { pool_addresses[0] });
+        for (pool_created, block_created) in creation_events {
+            pool_store
+                .index_pool_creation(pool_created, block_created)
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(
+            pool_store.pools.keys().copied().collect::<HashSet<_>>(),
+            hashset! { pool_ids[1] }
+        );
+    }
+
     #[tokio::test]
     async fn replace_pool_events() {
         let start_block = 0;
@@ -4,12 +4,17 @@
 use {
     super::{internal::InternalPoolFetching, pool_storage::PoolStorage},
     crate::{
-        event_handling::{AlloyEventRetriever, AlloyEventRetrieving, EventHandler},
+        event_handling::{AlloyEventRetriever, AlloyEventRetrieving, Backfill, EventHandler},
         maintenance::Maintaining,
         recent_block_cache::Block,
-        sources::balancer_v2::{
-            pool_fetching::BalancerFactoryInstance,
-            pools::{FactoryIndexing, Pool, PoolStatus, common::PoolInfoFetching},
+        sources::{
+            balancer_pair_cache::PairCache,
+            balancer_snapshots::Snapshotter,
+            balancer_v2::{
+                graph_api::RegisteredPools,
+                pool_fetching::BalancerFactoryInstance,
+                pools::{FactoryIndexing, Pool, PoolStatus, common::PoolInfoFetching},
+            },
         },
     },
     BalancerV2BasePoolFactory::BalancerV2BasePoolFactory::BalancerV2BasePoolFactoryEvents,
@@ -23,7 +28,7 @@ use {
         alloy::BalancerV2BasePoolFactory::{self, BalancerV2BasePoolFactory::PoolCreated},
         errors::EthcontractErrorType,
     },
-    ethcontract::{BlockId, H256, errors::MethodError},
+    ethcontract::{BlockId, H160, H256, errors::MethodError},
     ethrpc::block_stream::{BlockNumberHash, BlockRetrieving},
     futures::future,
     model::TokenPair,
@@ -69,27 +74,45 @@ where
 {
     fetcher: Arc<dyn PoolInfoFetching<Factory>>,
     updater: PoolUpdater<Factory>,
+    /// The pools of recently requested token pairs.
+    pair_cache: PairCache<H256>,
 }
 
 impl<Factory> Registry<Factory>
 where
     Factory: FactoryIndexing,
 {
-    /// Returns a new pool registry for the specified factory.
+    /// Returns a new pool registry for the specified factory. Creations of the
+    /// `pruned` pools are not indexed. Snapshots of the pools are saved with
+    /// the snapshotter if there is one, and the events of the history are
+    /// fetched within the backfill limits.
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         block_retreiver: Arc<dyn BlockRetrieving>,
         fetcher: Arc<dyn PoolInfoFetching<Factory>>,
         factory_instance: &BalancerFactoryInstance,
         initial_pools: Vec<Factory::PoolInfo>,
+        pruned: HashSet<H160>,
         start_sync_at_block: Option<BlockNumberHash>,
+        snapshotter: Option<Snapshotter<RegisteredPools>>,
+        backfill: Backfill,
     ) -> Self {
-        let updater = Mutex::new(EventHandler::new(
+        let storage = PoolStorage::new(initial_pools, fetcher.clone())
+            .with_pruned(pruned)
+            .with_snapshotter(snapshotter);
+        let pair_cache = PairCache::new(storage.version().clone());
+        let updater = EventHandler::new(
             block_retreiver,
             AlloyEventRetriever(BasePoolFactoryContract(base_pool_factory(factory_instance))),
-            PoolStorage::new(initial_pools, fetcher.clone()),
+            storage,
             start_sync_at_block,
-        ));
-        Self { fetcher, updater }
+        )
+        .with_backfill(backfill);
+        Self {
+            fetcher,
+            updater: Mutex::new(updater),
+            pair_cache,
+        }
     }
 }
 
@@ -99,11 +122,21 @@ where
     Factory: FactoryIndexing,
 {
     async fn pool_ids_for_token_pairs(&self, token_pairs: HashSet<TokenPair>) -> HashSet<H256> {
-        self.updater
-            .lock()
-            .await
-            .store()
-            .pool_ids_for_token_pairs(&token_pairs)
+        if let Some(pool_ids) = self.pair_cache.get(&token_pairs) {
+            return pool_ids;
+        }
+
+        let (version, pool_ids) = {
+            let updater = self.updater.lock().await;
+            let storage = updater.store();
+            (
+                storage.version().get(),
+                storage.pool_ids_by_token_pair(&token_pairs),
+            )
+        };
+        let result = pool_ids.values().flatten().copied().collect();
+        self.pair_cache.insert(version, pool_ids);
+        result
     }
 
     async fn pools_by_id(&self, pool_ids: HashSet<H256>, block: Block) -> Result<Vec<Pool>> {
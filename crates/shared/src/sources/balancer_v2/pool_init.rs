@@ -11,6 +11,10 @@ use {
 #[async_trait::async_trait]
 pub trait PoolInitializing: Send + Sync {
     async fn initialize_pools(&self) -> Result<RegisteredPools>;
+
+    /// Gets called instead of `initialize_pools` when the registries resume
+    /// from snapshots, with the pools they got initialized with.
+    fn restored(&self, _pools: &RegisteredPools) {}
 }
 
 #[async_trait::async_trait]
@@ -3,23 +3,27 @@
 use {
     super::{FactoryIndexing, Pool, PoolIndexing as _, PoolStatus},
     crate::{
-        sources::balancer_v2::{
-            graph_api::{PoolData, PoolType},
-            swap::fixed_point::Bfp,
+        multicall::Multicall,
+        sources::{
+            balancer_v2::{
+                graph_api::{PoolData, PoolType},
+                swap::fixed_point::Bfp,
+            },
+            rate_provider_cache::{RateFetching as _, RateProviderCache},
         },
         token_info::TokenInfoFetching,
     },
     anyhow::{Context, Result, anyhow, ensure},
-    contracts::{
-        IRateProvider,
-        alloy::{BalancerV2BasePool, BalancerV2Vault},
+    contracts::alloy::{
+        BalancerV2BasePool,
+        BalancerV2Vault::{self, BalancerV2Vault::getPoolTokensCall},
     },
     ethcontract::{BlockId, H160, H256, U256},
     ethrpc::{
         Web3,
         alloy::conversions::{IntoAlloy, IntoLegacy},
     },
-    futures::{FutureExt as _, future::BoxFuture},
+    futures::{FutureExt as _, TryFutureExt as _, future::BoxFuture},
     std::{collections::BTreeMap, future::Future, sync::Arc},
     tokio::sync::oneshot,
 };
@@ -51,6 +55,9 @@ pub struct PoolInfoFetcher<Factory> {
     web3: Web3,
     factory: Factory,
     token_infos: Arc<dyn TokenInfoFetching>,
+    multicall: Option<Multicall>,
+    /// Caches the rates of the rate providers of the pool tokens, if set.
+    rate_providers: Option<RateProviderCache>,
 }
 
 impl<Factory> PoolInfoFetcher<Factory> {
@@ -59,12 +66,16 @@ impl<Factory> PoolInfoFetcher<Factory> {
         web3: Web3,
         factory: Factory,
         token_infos: Arc<dyn TokenInfoFetching>,
+        multicall: Option<Multicall>,
+        rate_providers: Option<RateProviderCache>,
     ) -> Self {
         Self {
             vault,
             web3,
             factory,
             token_infos,
+            multicall,
+            rate_providers,
         }
     }
 
@@ -158,24 +169,32 @@ impl<Factory> PoolInfoFetcher<Factory> {
                 .await
         };
 
+        let multicall = self.multicall.clone();
         let pool_tokens = async move {
-            vault
-                .getPoolTokens(pool_id.0.into())
-                .block(block)
-                .call()
-                .await
+            let pool_id = pool_id.0.into();
+            match multicall {
+                Some(multicall) => {
+                    let call = getPoolTokensCall { poolId: pool_id };
+                    multicall.call(*vault.address(), call, block).await
+                }
+                None => Ok(vault.getPoolTokens(pool_id).block(block).call().await?),
+            }
         };
 
         let rate_providers = pool.rate_providers.clone();
         let web3 = self.web3.clone();
+        let rate_cache = self.rate_providers.clone();
         let fetch_rates = async move {
             let mut rates = Vec::new();
             for rate_provider in rate_providers {
                 if rate_provider == H160::zero() {
                     rates.push(U256::exp10(18)); // Default rate of 1.0 as rate provider is not set
                 } else {
-                    let rate_contract = IRateProvider::at(&web3, rate_provider);
-                    match rate_contract.get_rate().block(legacy_block).call().await {
+                    let rate = match &rate_cache {
+                        Some(cache) => cache.get_rate(rate_provider, legacy_block).await,
+                        None => web3.fetch_rate(rate_provider, legacy_block).await,
+                    };
+                    match rate {
                         Ok(rate) => rates.push(rate),
                         Err(error) => {
                             tracing::debug!(
@@ -198,8 +217,12 @@ impl<Factory> PoolInfoFetcher<Factory> {
 
         let pool = pool.clone();
         async move {
-            let (paused, swap_fee, pool_tokens, rates) =
-                futures::try_join!(fetch_paused, fetch_swap_fee, pool_tokens, fetch_rates)?;
+            let (paused, swap_fee, pool_tokens, rates) = futures::try_join!(
+                fetch_paused.err_into(),
+                fetch_swap_fee.err_into(),
+                pool_tokens,
+                fetch_rates
+            )?;
             let swap_fee = Bfp::from_wei(swap_fee.into_legacy());
 
             let balances = pool_tokens.balances;
@@ -492,6 +515,8 @@ mod tests {
             web3: mock_web3_dyn_transport(),
             factory: MockFactoryIndexing::new(),
             token_infos: Arc::new(token_infos),
+            multicall: None,
+            rate_providers: None,
         };
         let pool_info = pool_info_fetcher
             .fetch_common_pool_info(pool.address().into_legacy(), 1337)
@@ -561,6 +586,8 @@ mod tests {
             web3: mock_web3_dyn_transport(),
             factory: MockFactoryIndexing::new(),
             token_infos: Arc::new(token_infos),
+            multicall: None,
+            rate_providers: None,
         };
         let pool_info = PoolInfo {
             id: pool_id,
@@ -651,6 +678,8 @@ mod tests {
             web3: mock_web3_dyn_transport(),
             factory: MockFactoryIndexing::new(),
             token_infos: Arc::new(token_infos),
+            multicall: None,
+            rate_providers: None,
         };
         let pool_info = PoolInfo {
             id: Default::default(),
@@ -781,6 +810,8 @@ mod tests {
             web3: mock_web3_dyn_transport(),
             factory,
             token_infos: Arc::new(MockTokenInfoFetching::new()),
+            multicall: None,
+            rate_providers: None,
         };
 
         let pool_status = pool_info_fetcher
@@ -855,6 +886,8 @@ mod tests {
             web3: mock_web3_dyn_transport(),
             factory,
             token_infos: Arc::new(MockTokenInfoFetching::new()),
+            multicall: None,
+            rate_providers: None,
         };
         let pool_info = weighted::PoolInfo {
             common: PoolInfo {
@@ -929,6 +962,8 @@ mod tests {
             web3: mock_web3_dyn_transport(),
             factory,
             token_infos: Arc::new(MockTokenInfoFetching::new()),
+            multicall: None,
+            rate_providers: None,
         };
         let pool_info = weighted::PoolInfo {
             common: PoolInfo {
@@ -967,6 +1002,8 @@ mod tests {
             web3: mock_web3_dyn_transport(),
             factory: MockFactoryIndexing::new(),
             token_infos: Arc::new(token_infos),
+            multicall: None,
+            rate_providers: None,
         };
         assert!(
             pool_info_fetcher
@@ -994,6 +1031,8 @@ mod tests {
             web3: mock_web3_dyn_transport(),
             factory: MockFactoryIndexing::new(),
             token_infos: Arc::new(token_infos),
+            multicall: None,
+            rate_providers: None,
         };
         assert!(pool_info_fetcher.scaling_factors(&[token]).await.is_err());
     }
@@ -1021,7 +1060,10 @@ mod tests {
                     price_rate_provider: None,
                 },
             ],
-            dynamic_data: DynamicData { swap_enabled: true },
+            dynamic_data: DynamicData {
+                swap_enabled: true,
+                total_liquidity: None,
+            },
             create_time: 0,
             alpha: None,
             beta: None,
@@ -1070,7 +1112,10 @@ mod tests {
                 weight: Some("1.337".parse().unwrap()),
                 price_rate_provider: None,
             }],
-            dynamic_data: DynamicData { swap_enabled: true },
+            dynamic_data: DynamicData {
+                swap_enabled: true,
+                total_liquidity: None,
+            },
             create_time: 0,
             alpha: None,
             beta: None,
@@ -1116,7 +1161,10 @@ mod tests {
                     price_rate_provider: None,
                 },
             ],
-            dynamic_data: DynamicData { swap_enabled: true },
+            dynamic_data: DynamicData {
+                swap_enabled: true,
+                total_liquidity: None,
+            },
             create_time: 0,
             alpha: None,
             beta: None,
@@ -55,7 +55,9 @@ impl FactoryIndexing for BalancerV2ComposableStablePoolFactory::Instance {
         let fetch_common = common_pool_state.map(Result::Ok);
         let scaling_factors_block = block.into_alloy();
         let amp_param_block = scaling_factors_block;
+        let actual_supply_block = scaling_factors_block;
         let pool_contract_clone = pool_contract.clone();
+        let pool_contract_supply = pool_contract.clone();
         let fetch_scaling_factors = async move {
             pool_contract
                 .getScalingFactors()
@@ -72,12 +74,21 @@ impl FactoryIndexing for BalancerV2ComposableStablePoolFactory::Instance {
                 .await
                 .map_err(anyhow::Error::from)
         };
+        let fetch_actual_supply = async move {
+            pool_contract_supply
+                .getActualSupply()
+                .block(actual_supply_block)
+                .call()
+                .await
+                .map_err(anyhow::Error::from)
+        };
 
         async move {
-            let (common, scaling_factors, amplification_parameter) = futures::try_join!(
+            let (common, scaling_factors, amplification_parameter, actual_supply) = futures::try_join!(
                 fetch_common,
                 fetch_scaling_factors,
-                fetch_amplification_parameter
+                fetch_amplification_parameter,
+                fetch_actual_supply
             )?;
             let amplification_parameter = {
                 AmplificationParameter::try_new(
@@ -107,6 +118,7 @@ impl FactoryIndexing for BalancerV2ComposableStablePoolFactory::Instance {
                     .collect(),
                 swap_fee: common.swap_fee,
                 amplification_parameter,
+                actual_supply: Some(actual_supply.into_legacy()),
             }))
         }
         .boxed()
@@ -144,7 +156,10 @@ mod tests {
                     price_rate_provider: None,
                 },
             ],
-            dynamic_data: DynamicData { swap_enabled: true },
+            dynamic_data: DynamicData {
+                swap_enabled: true,
+                total_liquidity: None,
+            },
             create_time: 0,
             alpha: None,
             beta: None,
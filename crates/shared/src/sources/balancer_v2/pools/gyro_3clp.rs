@@ -143,7 +143,10 @@ mod tests {
                     price_rate_provider: None,
                 },
             ],
-            dynamic_data: DynamicData { swap_enabled: true },
+            dynamic_data: DynamicData {
+                swap_enabled: true,
+                total_liquidity: None,
+            },
             create_time: 1234567890,
             alpha: None,
             beta: None,
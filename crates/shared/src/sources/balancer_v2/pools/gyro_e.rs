@@ -243,7 +243,10 @@ mod tests {
                     price_rate_provider: None,
                 },
             ],
-            dynamic_data: DynamicData { swap_enabled: true },
+            dynamic_data: DynamicData {
+                swap_enabled: true,
+                total_liquidity: None,
+            },
             create_time: 1234567890,
             alpha: Some(SBfp::from_wei(I256::from(1000))),
             beta: Some(SBfp::from_wei(I256::from(2000))),
@@ -297,7 +300,10 @@ mod tests {
                 weight: None,
                 price_rate_provider: None,
             }],
-            dynamic_data: DynamicData { swap_enabled: true },
+            dynamic_data: DynamicData {
+                swap_enabled: true,
+                total_liquidity: None,
+            },
             create_time: 1234567890,
             alpha: None,
             beta: None,
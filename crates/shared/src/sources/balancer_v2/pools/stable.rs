@@ -40,6 +40,9 @@ pub struct PoolState {
     pub tokens: BTreeMap<H160, common::TokenState>,
     pub swap_fee: Bfp,
     pub amplification_parameter: AmplificationParameter,
+    /// The BPT supply as returned by `getActualSupply()` of composable stable
+    /// pools.
+    pub actual_supply: Option<U256>,
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -122,6 +125,7 @@ impl FactoryIndexing for BalancerV2StablePoolFactoryV2::Instance {
                 tokens: common.tokens,
                 swap_fee: common.swap_fee,
                 amplification_parameter,
+                actual_supply: None,
             }))
         }
         .boxed()
@@ -159,7 +163,10 @@ mod tests {
                     price_rate_provider: None,
                 },
             ],
-            dynamic_data: DynamicData { swap_enabled: true },
+            dynamic_data: DynamicData {
+                swap_enabled: true,
+                total_liquidity: None,
+            },
             create_time: 0,
             alpha: None,
             beta: None,
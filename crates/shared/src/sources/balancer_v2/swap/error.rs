@@ -48,8 +48,12 @@ errors_from_codes!(
     (YOutOfBounds, 7),
     (ProductOutOfBounds, 8),
     (InvalidExponent, 9),
+    (OutOfBounds, 100),
+    (MaxSwapFeePercentage, 202),
     (MaxInRatio, 304),
     (MaxOutRatio, 305),
+    (MinBptInForTokenOut, 306),
+    (MaxOutBptForTokensIn, 307),
     (InvalidToken, 309),
     (StableInvariantDidntConverge, 321),
     (StableGetBalanceDidntConverge, 322),
@@ -5,6 +5,7 @@
 
 use {
     super::error::Error,
+    crate::sources::balancer_rounding,
     anyhow::{Context, Result, bail, ensure},
     ethcontract::U256,
     num::{BigInt, BigRational},
@@ -93,6 +94,12 @@ impl Debug for Bfp {
     }
 }
 
+impl fmt::Display for Bfp {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        Debug::fmt(self, formatter)
+    }
+}
+
 impl Bfp {
     #[cfg(test)]
     pub fn to_f64_lossy(self) -> f64 {
@@ -143,13 +150,19 @@ impl Bfp {
     }
 
     pub fn mul_down(self, other: Self) -> Result<Self, Error> {
-        Ok(Self(
-            self.0.checked_mul(other.0).ok_or(Error::MulOverflow)? / *ONE_18,
-        ))
+        let product = self.0.checked_mul(other.0).ok_or(Error::MulOverflow)?;
+        if balancer_rounding::nearest() {
+            return Ok(Self(balancer_rounding::div_nearest(product, *ONE_18)));
+        }
+
+        Ok(Self(product / *ONE_18))
     }
 
     pub fn mul_up(self, other: Self) -> Result<Self, Error> {
         let product = self.0.checked_mul(other.0).ok_or(Error::MulOverflow)?;
+        if balancer_rounding::nearest() {
+            return Ok(Self(balancer_rounding::div_nearest(product, *ONE_18)));
+        }
 
         Ok(if product.is_zero() {
             Bfp::zero()
@@ -160,12 +173,14 @@ impl Bfp {
 
     pub fn div_down(self, other: Self) -> Result<Self, Error> {
         if other.is_zero() {
-            Err(Error::ZeroDivision)
-        } else {
-            Ok(Self(
-                self.0.checked_mul(*ONE_18).ok_or(Error::DivInternal)? / other.0,
-            ))
+            return Err(Error::ZeroDivision);
+        }
+        let a_inflated = self.0.checked_mul(*ONE_18).ok_or(Error::DivInternal)?;
+        if balancer_rounding::nearest() {
+            return Ok(Self(balancer_rounding::div_nearest(a_inflated, other.0)));
         }
+
+        Ok(Self(a_inflated / other.0))
     }
 
     pub fn div_up(self, other: Self) -> Result<Self, Error> {
@@ -176,6 +191,9 @@ impl Bfp {
             Ok(Self::zero())
         } else {
             let a_inflated = self.0.checked_mul(*ONE_18).ok_or(Error::DivInternal)?;
+            if balancer_rounding::nearest() {
+                return Ok(Self(balancer_rounding::div_nearest(a_inflated, other.0)));
+            }
 
             Ok(Self(((a_inflated - 1) / other.0) + 1))
         }
@@ -191,11 +209,28 @@ impl Bfp {
 
     pub fn pow_up(self, exp: Self) -> Result<Self, Error> {
         let raw = Bfp(logexpmath::pow(self.0, exp.0)?);
+        if balancer_rounding::nearest() {
+            return Ok(raw);
+        }
         let max_error = raw.mul_up(*MAX_POW_RELATIVE_ERROR)?.add(Bfp(1.into()))?;
 
         raw.add(max_error)
     }
 
+    pub fn pow_down(self, exp: Self) -> Result<Self, Error> {
+        let raw = Bfp(logexpmath::pow(self.0, exp.0)?);
+        if balancer_rounding::nearest() {
+            return Ok(raw);
+        }
+        let max_error = raw.mul_up(*MAX_POW_RELATIVE_ERROR)?.add(Bfp(1.into()))?;
+
+        if raw < max_error {
+            Ok(Self::zero())
+        } else {
+            raw.sub(max_error)
+        }
+    }
+
     pub fn pow_up_v3(self, exp: Self) -> Result<Self, Error> {
         if exp == *ONE {
             Ok(self)
@@ -206,6 +241,9 @@ impl Bfp {
             square.mul_up(square)
         } else {
             let raw = Bfp(logexpmath::pow(self.0, exp.0)?);
+            if balancer_rounding::nearest() {
+                return Ok(raw);
+            }
             let max_error = raw.mul_up(*MAX_POW_RELATIVE_ERROR)?.add(Bfp(1.into()))?;
 
             raw.add(max_error)
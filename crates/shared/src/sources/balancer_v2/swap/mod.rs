@@ -34,7 +34,7 @@ pub mod gyro_e_math;
 mod math;
 pub mod signed_fixed_point;
 mod stable_math;
-mod weighted_math;
+pub mod weighted_math;
 
 const WEIGHTED_SWAP_GAS_COST: usize = 100_000;
 const STABLE_SWAP_GAS_COST: usize = 183_520;
@@ -42,14 +42,32 @@ const GYRO_2CLP_SWAP_GAS_COST: usize = 150_000; // Moderate gas cost for 2-CLP m
 const GYRO_3CLP_SWAP_GAS_COST: usize = 160_000; // Slightly higher than 2-CLP due to 3D invariant
 const GYRO_E_SWAP_GAS_COST: usize = 200_000; // Higher gas cost due to complex elliptic curve math
 
+/// Checks that the swap fee leaves a part of the swapped amount to the pool.
+/// Fees of 100% or more can't be set on-chain and would make the fee math
+/// divide by zero or underflow.
+fn check_swap_fee(swap_fee: Bfp) -> Result<(), Error> {
+    if swap_fee >= Bfp::one() {
+        return Err(Error::MaxSwapFeePercentage);
+    }
+    Ok(())
+}
+
 fn add_swap_fee_amount(amount: U256, swap_fee: Bfp) -> Result<U256, Error> {
     // https://github.com/balancer-labs/balancer-v2-monorepo/blob/6c9e24e22d0c46cca6dd15861d3d33da61a60b98/pkg/core/contracts/pools/BasePool.sol#L454-L457
+    check_swap_fee(swap_fee)?;
+    if swap_fee.is_zero() {
+        return Ok(amount);
+    }
     let amount_with_fees = Bfp::from_wei(amount).div_up(swap_fee.complement())?;
     Ok(amount_with_fees.as_uint256())
 }
 
 fn subtract_swap_fee_amount(amount: U256, swap_fee: Bfp) -> Result<U256, Error> {
     // https://github.com/balancer-labs/balancer-v2-monorepo/blob/6c9e24e22d0c46cca6dd15861d3d33da61a60b98/pkg/core/contracts/pools/BasePool.sol#L462-L466
+    check_swap_fee(swap_fee)?;
+    if swap_fee.is_zero() {
+        return Ok(amount);
+    }
     let amount = Bfp::from_wei(amount);
     let fee_amount = amount.mul_up(swap_fee)?;
     let amount_without_fees = amount.sub(fee_amount)?;
@@ -108,6 +126,31 @@ fn compute_rate_round_up(rate: U256) -> U256 {
     if rounded_rate == rate { rate } else { rate + 1 }
 }
 
+/// Returns the pool reserves after swapping `in_amount` of `in_token` for
+/// `out_amount` of `out_token` through the pool, so that subsequent swaps in
+/// the same settlement can be priced against the updated pool state. Returns
+/// `None` if the pool doesn't hold both tokens or the output exceeds the
+/// pool's balance.
+///
+/// The swap fee stays in the pool, so the full input amount gets added to its
+/// balance.
+fn apply_swap_to_reserves<T: Clone>(
+    reserves: &BTreeMap<H160, T>,
+    balance: impl Fn(&mut T) -> &mut U256,
+    (in_amount, in_token): (U256, H160),
+    (out_amount, out_token): (U256, H160),
+) -> Option<BTreeMap<H160, T>> {
+    if in_token == out_token {
+        return None;
+    }
+    let mut reserves = reserves.clone();
+    let in_balance = balance(reserves.get_mut(&in_token)?);
+    *in_balance = in_balance.checked_add(in_amount)?;
+    let out_balance = balance(reserves.get_mut(&out_token)?);
+    *out_balance = out_balance.checked_sub(out_amount)?;
+    Some(reserves)
+}
+
 impl TokenState {
     /// Converts the stored balance into its internal representation as a
     /// Balancer fixed point number.
@@ -168,6 +211,59 @@ pub struct WeightedPoolRef<'a> {
 }
 
 impl WeightedPoolRef<'_> {
+    /// Returns the reserves after the swap, see `apply_swap_to_reserves`.
+    pub fn apply_swap(
+        &self,
+        input: (U256, H160),
+        output: (U256, H160),
+    ) -> Option<BTreeMap<H160, WeightedTokenState>> {
+        apply_swap_to_reserves(
+            self.reserves,
+            |state| &mut state.common.balance,
+            input,
+            output,
+        )
+    }
+
+    /// Returns the amount of pool tokens minted for joining the pool with
+    /// `in_amount` of `in_token` only.
+    pub fn bpt_out_given_exact_token_in(
+        &self,
+        (in_amount, in_token): (U256, H160),
+        total_supply: U256,
+    ) -> Option<U256> {
+        let reserve = self.reserves.get(&in_token)?;
+        let bpt_out = weighted_math::calc_bpt_out_given_exact_token_in(
+            reserve.common.upscaled_balance().ok()?,
+            reserve.weight,
+            reserve.common.upscale(in_amount).ok()?,
+            Bfp::from_wei(total_supply),
+            self.swap_fee,
+        )
+        .ok()?;
+        Some(bpt_out.as_uint256())
+    }
+
+    /// Returns the amount of `out_token` received for exiting the pool with
+    /// `bpt_in` pool tokens into that token only.
+    pub fn token_out_given_exact_bpt_in(
+        &self,
+        out_token: H160,
+        bpt_in: U256,
+        total_supply: U256,
+    ) -> Option<U256> {
+        let reserve = self.reserves.get(&out_token)?;
+        let out_amount = weighted_math::calc_token_out_given_exact_bpt_in(
+            reserve.common.upscaled_balance().ok()?,
+            reserve.weight,
+            Bfp::from_wei(bpt_in),
+            Bfp::from_wei(total_supply),
+            self.swap_fee,
+        )
+        .ok()?;
+        reserve.common.downscale_down(out_amount).ok()
+    }
+
     fn get_amount_out_inner(
         &self,
         out_token: H160,
@@ -250,6 +346,9 @@ pub struct StablePoolRef<'a> {
     pub reserves: &'a BTreeMap<H160, TokenState>,
     pub swap_fee: Bfp,
     pub amplification_parameter: AmplificationParameter,
+    /// The BPT supply of composable stable pools, net of the BPT held by the
+    /// vault. Swapping with the BPT requires it.
+    pub actual_supply: Option<U256>,
 }
 
 #[derive(Debug)]
@@ -362,16 +461,108 @@ impl<'a> StablePoolRef<'a> {
         add_swap_fee_amount(amount_in_before_fee, self.swap_fee).ok()
     }
 
-    /// Comes from `_swapWithBpt`:
-    // https://etherscan.io/address/0xf9ac7B9dF2b3454E841110CcE5550bD5AC6f875F#code#F2#L301
-    fn swap_with_bpt(&self) -> Option<U256> {
-        // TODO: We currently do not implement swapping with BPT for composable
-        // stable pools.
-        None
+    /// Comes from `_swapWithBpt(true, ...)`, which joins the pool when buying
+    /// the BPT and exits it when selling the BPT:
+    /// https://etherscan.io/address/0xf9ac7B9dF2b3454E841110CcE5550bD5AC6f875F#code#F2#L301
+    fn bpt_swap_given_in(
+        &self,
+        out_token: H160,
+        (in_amount, in_token): (U256, H160),
+    ) -> Option<U256> {
+        let in_reserves = self.reserves.get(&in_token)?;
+        let out_reserves = self.reserves.get(&out_token)?;
+        let bpt_total_supply = Bfp::from_wei(self.actual_supply?);
+        let amplification_parameter = self.amplification_parameter_u256()?;
+        let in_amount = in_reserves.upscale(in_amount).ok()?;
+
+        let out_amount = if out_token == self.address {
+            let (token_index, balances) = self.upscale_balances_with_token_index(&in_token)?;
+            let mut amounts_in = vec![Bfp::zero(); balances.len()];
+            amounts_in[token_index] = in_amount;
+            stable_math::calc_bpt_out_given_exact_tokens_in(
+                amplification_parameter,
+                &balances,
+                &amounts_in,
+                bpt_total_supply,
+                self.swap_fee,
+            )
+        } else {
+            let (token_index, balances) = self.upscale_balances_with_token_index(&out_token)?;
+            stable_math::calc_token_out_given_exact_bpt_in(
+                amplification_parameter,
+                &balances,
+                token_index,
+                in_amount,
+                bpt_total_supply,
+                self.swap_fee,
+            )
+        }
+        .ok()?;
+        out_reserves.downscale_down(out_amount).ok()
+    }
+
+    /// Comes from `_swapWithBpt(false, ...)`:
+    /// https://etherscan.io/address/0xf9ac7B9dF2b3454E841110CcE5550bD5AC6f875F#code#F2#L301
+    fn bpt_swap_given_out(
+        &self,
+        in_token: H160,
+        (out_amount, out_token): (U256, H160),
+    ) -> Option<U256> {
+        let in_reserves = self.reserves.get(&in_token)?;
+        let out_reserves = self.reserves.get(&out_token)?;
+        let bpt_total_supply = Bfp::from_wei(self.actual_supply?);
+        let amplification_parameter = self.amplification_parameter_u256()?;
+        let out_amount = out_reserves.upscale(out_amount).ok()?;
+
+        let in_amount = if out_token == self.address {
+            let (token_index, balances) = self.upscale_balances_with_token_index(&in_token)?;
+            stable_math::calc_token_in_given_exact_bpt_out(
+                amplification_parameter,
+                &balances,
+                token_index,
+                out_amount,
+                bpt_total_supply,
+                self.swap_fee,
+            )
+        } else {
+            let (token_index, balances) = self.upscale_balances_with_token_index(&out_token)?;
+            let mut amounts_out = vec![Bfp::zero(); balances.len()];
+            amounts_out[token_index] = out_amount;
+            stable_math::calc_bpt_in_given_exact_tokens_out(
+                amplification_parameter,
+                &balances,
+                &amounts_out,
+                bpt_total_supply,
+                self.swap_fee,
+            )
+        }
+        .ok()?;
+        in_reserves.downscale_up(in_amount).ok()
+    }
+
+    /// Returns the index of `token` among the upscaled balances without the
+    /// BPT, or `None` if the pool doesn't contain it.
+    fn upscale_balances_with_token_index(&self, token: &H160) -> Option<(usize, Vec<Bfp>)> {
+        let token_index = self
+            .reserves_without_bpt()
+            .position(|(address, _)| address == *token)?;
+        let BalancesWithIndices { balances, .. } = self
+            .upscale_balances_with_token_indices(token, token)
+            .ok()?;
+        Some((token_index, balances))
     }
 }
 
 impl StablePoolRef<'_> {
+    /// Returns the reserves after the swap, see `apply_swap_to_reserves`.
+    pub fn apply_swap(
+        &self,
+        input: (U256, H160),
+        output: (U256, H160),
+    ) -> Option<BTreeMap<H160, TokenState>> {
+        apply_swap_to_reserves(self.reserves, |state| &mut state.balance, input, output)
+    }
+
     fn get_amount_out_inner(
         &self,
         out_token: H160,
@@ -379,7 +570,7 @@ impl StablePoolRef<'_> {
         in_token: H160,
     ) -> Option<U256> {
         if in_token == self.address || out_token == self.address {
-            self.swap_with_bpt()
+            self.bpt_swap_given_in(out_token, (in_amount, in_token))
         } else {
             self.regular_swap_given_in(out_token, (in_amount, in_token))
         }
@@ -400,14 +591,14 @@ impl BaselineSolvable for StablePoolRef<'_> {
         in_token: H160,
         (out_amount, out_token): (U256, H160),
     ) -> Option<U256> {
-        if in_token == self.address || out_token == self.address {
-            self.swap_with_bpt()
+        let in_amount = if in_token == self.address || out_token == self.address {
+            self.bpt_swap_given_out(in_token, (out_amount, out_token))?
         } else {
-            let in_amount = self.regular_swap_given_out(in_token, (out_amount, out_token))?;
-            converge_in_amount(in_amount, out_amount, |x| {
-                self.get_amount_out_inner(out_token, x, in_token)
-            })
-        }
+            self.regular_swap_given_out(in_token, (out_amount, out_token))?
+        };
+        converge_in_amount(in_amount, out_amount, |x| {
+            self.get_amount_out_inner(out_token, x, in_token)
+        })
     }
 
     async fn gas_cost(&self) -> usize {
@@ -458,6 +649,27 @@ impl WeightedPool {
             version: self.version,
         }
     }
+
+    /// See [`WeightedPoolRef::bpt_out_given_exact_token_in`].
+    pub fn bpt_out_given_exact_token_in(
+        &self,
+        input: (U256, H160),
+        total_supply: U256,
+    ) -> Option<U256> {
+        self.as_pool_ref()
+            .bpt_out_given_exact_token_in(input, total_supply)
+    }
+
+    /// See [`WeightedPoolRef::token_out_given_exact_bpt_in`].
+    pub fn token_out_given_exact_bpt_in(
+        &self,
+        out_token: H160,
+        bpt_in: U256,
+        total_supply: U256,
+    ) -> Option<U256> {
+        self.as_pool_ref()
+            .token_out_given_exact_bpt_in(out_token, bpt_in, total_supply)
+    }
 }
 
 impl BaselineSolvable for WeightedPool {
@@ -481,6 +693,7 @@ impl StablePool {
             reserves: &self.reserves,
             swap_fee: self.common.swap_fee,
             amplification_parameter: self.amplification_parameter,
+            actual_supply: self.actual_supply,
         }
     }
 
@@ -528,6 +741,15 @@ pub struct GyroEPoolRef<'a> {
 }
 
 impl GyroEPoolRef<'_> {
+    /// Returns the reserves after the swap, see `apply_swap_to_reserves`.
+    pub fn apply_swap(
+        &self,
+        input: (U256, H160),
+        output: (U256, H160),
+    ) -> Option<BTreeMap<H160, TokenState>> {
+        apply_swap_to_reserves(self.reserves, |state| &mut state.balance, input, output)
+    }
+
     fn get_amount_out_inner(
         &self,
         out_token: H160,
@@ -812,6 +1034,15 @@ pub struct Gyro2CLPPoolRef<'a> {
 }
 
 impl Gyro2CLPPoolRef<'_> {
+    /// Returns the reserves after the swap, see `apply_swap_to_reserves`.
+    pub fn apply_swap(
+        &self,
+        input: (U256, H160),
+        output: (U256, H160),
+    ) -> Option<BTreeMap<H160, TokenState>> {
+        apply_swap_to_reserves(self.reserves, |state| &mut state.balance, input, output)
+    }
+
     fn get_amount_out_inner(
         &self,
         out_token: H160,
@@ -1025,6 +1256,15 @@ pub struct Gyro3CLPPoolRef<'a> {
 }
 
 impl Gyro3CLPPoolRef<'_> {
+    /// Returns the reserves after the swap, see `apply_swap_to_reserves`.
+    pub fn apply_swap(
+        &self,
+        input: (U256, H160),
+        output: (U256, H160),
+    ) -> Option<BTreeMap<H160, TokenState>> {
+        apply_swap_to_reserves(self.reserves, |state| &mut state.balance, input, output)
+    }
+
     fn get_amount_out_inner(
         &self,
         out_token: H160,
@@ -1261,6 +1501,7 @@ mod tests {
             },
             reserves,
             amplification_parameter,
+            actual_supply: None,
         }
     }
 
@@ -1282,6 +1523,46 @@ mod tests {
         );
     }
 
+    #[test]
+    fn zero_swap_fee_keeps_amounts() {
+        let amount = U256::from(1_000_000_u64);
+        assert_eq!(add_swap_fee_amount(amount, Bfp::zero()).unwrap(), amount);
+        assert_eq!(
+            subtract_swap_fee_amount(amount, Bfp::zero()).unwrap(),
+            amount
+        );
+        assert_eq!(
+            add_swap_fee_amount(U256::MAX, Bfp::zero()).unwrap(),
+            U256::MAX
+        );
+    }
+
+    #[test]
+    fn rejects_swap_fees_of_100_percent_or_more() {
+        let amount = U256::from(1_000_000_u64);
+        for swap_fee in [Bfp::one(), Bfp::exp10(19)] {
+            assert_eq!(
+                add_swap_fee_amount(amount, swap_fee),
+                Err(Error::MaxSwapFeePercentage)
+            );
+            assert_eq!(
+                subtract_swap_fee_amount(amount, swap_fee),
+                Err(Error::MaxSwapFeePercentage)
+            );
+        }
+
+        // the highest representable fee below 100% still works
+        let swap_fee = Bfp::from_wei(U256::exp10(18) - 1);
+        assert_eq!(
+            subtract_swap_fee_amount(amount, swap_fee).unwrap(),
+            0.into()
+        );
+        assert_eq!(
+            add_swap_fee_amount(amount, swap_fee).unwrap(),
+            U256::from(1_000_000_u64) * U256::exp10(18)
+        );
+    }
+
     #[tokio::test]
     async fn weighted_get_amount_out() {
         // Values obtained from this transaction:
@@ -1329,6 +1610,54 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn weighted_apply_swap() {
+        let weth = H160::repeat_byte(21);
+        let dai = H160::repeat_byte(42);
+        let pool = create_weighted_pool_with(
+            vec![weth, dai],
+            vec![U256::exp10(21), U256::exp10(24)],
+            vec![bfp!("0.5"), bfp!("0.5")],
+            vec![Bfp::exp10(0), Bfp::exp10(0)],
+            3_000_000_000_000_000_i128.into(),
+        );
+        let pool = pool.as_pool_ref();
+
+        let in_amount = U256::exp10(18);
+        let out_amount = pool.get_amount_out(dai, (in_amount, weth)).await.unwrap();
+        let reserves = pool
+            .apply_swap((in_amount, weth), (out_amount, dai))
+            .unwrap();
+        assert_eq!(reserves[&weth].common.balance, U256::exp10(21) + in_amount);
+        assert_eq!(reserves[&dai].common.balance, U256::exp10(24) - out_amount);
+
+        // the second swap through the pool gets a worse price
+        let swapped = WeightedPoolRef {
+            reserves: &reserves,
+            ..pool
+        };
+        assert!(
+            swapped
+                .get_amount_out(dai, (in_amount, weth))
+                .await
+                .unwrap()
+                < out_amount
+        );
+
+        assert!(
+            pool.apply_swap((in_amount, weth), (U256::exp10(25), dai))
+                .is_none()
+        );
+        assert!(
+            pool.apply_swap((in_amount, weth), (out_amount, H160::zero()))
+                .is_none()
+        );
+        assert!(
+            pool.apply_swap((in_amount, weth), (out_amount, weth))
+                .is_none()
+        );
+    }
+
     #[test]
     fn construct_balances_and_token_indices() {
         let tokens: Vec<_> = (1..=3).map(H160::from_low_u64_be).collect();
@@ -1432,4 +1761,84 @@ mod tests {
         let res_out = pool.get_amount_in(usdc, (amount_out, dai)).await;
         assert_eq!(res_out.unwrap(), amount_in.into());
     }
+
+    #[tokio::test]
+    async fn stable_swaps_through_bpt() {
+        let dai = H160::from_low_u64_be(1);
+        let usdc = H160::from_low_u64_be(2);
+        let bpt = H160::from_low_u64_be(3);
+        let actual_supply = U256::from(2_000_000) * U256::exp10(18);
+        let mut pool = create_stable_pool_with(
+            vec![dai, usdc, bpt],
+            vec![
+                U256::from(1_000_000) * U256::exp10(18),
+                U256::from(1_000_000) * U256::exp10(6),
+                (U256::one() << 111) - actual_supply,
+            ],
+            AmplificationParameter::try_new(200_000.into(), 1000.into()).unwrap(),
+            vec![Bfp::exp10(0), Bfp::exp10(12), Bfp::exp10(0)],
+            100_000_000_000_000u128.into(),
+        );
+        pool.common.address = bpt;
+
+        // The BPT supply is needed for swapping with the BPT.
+        let amount_in = U256::from(1000) * U256::exp10(18);
+        assert!(pool.get_amount_out(bpt, (amount_in, dai)).await.is_none());
+        pool.actual_supply = Some(actual_supply);
+
+        let bpt_out = pool.get_amount_out(bpt, (amount_in, dai)).await.unwrap();
+        assert!(bpt_out > U256::from(999) * U256::exp10(18) && bpt_out < amount_in);
+        let dai_in = pool.get_amount_in(dai, (bpt_out, bpt)).await.unwrap();
+        assert!(dai_in > amount_in * 999 / 1000 && dai_in < amount_in * 1001 / 1000);
+
+        let usdc_out = pool.get_amount_out(usdc, (bpt_out, bpt)).await.unwrap();
+        assert!(usdc_out > U256::from(998_000_000) && usdc_out < U256::from(1_000_000_000));
+        let bpt_in = pool.get_amount_in(bpt, (usdc_out, usdc)).await.unwrap();
+        assert!(bpt_in > bpt_out * 999 / 1000 && bpt_in < bpt_out * 1001 / 1000);
+    }
+
+    #[test]
+    fn stable_bpt_swaps_match_reference_values() {
+        // Expected values computed with a Python port of the composable stable
+        // pool's BPT swaps, the StableMath.sol join and exit functions and the
+        // FixedPoint.sol rounding, as no archive node was at hand to replay
+        // on-chain swaps.
+        let dai = H160::from_low_u64_be(1);
+        let usdc = H160::from_low_u64_be(2);
+        let bpt = H160::from_low_u64_be(3);
+        let actual_supply = U256::from(2_000_000) * U256::exp10(18);
+        let mut pool = create_stable_pool_with(
+            vec![dai, usdc, bpt],
+            vec![
+                U256::from(1_000_000) * U256::exp10(18),
+                U256::from(1_000_000) * U256::exp10(6),
+                (U256::one() << 111) - actual_supply,
+            ],
+            AmplificationParameter::try_new(200_000.into(), 1000.into()).unwrap(),
+            vec![Bfp::exp10(0), Bfp::exp10(12), Bfp::exp10(0)],
+            100_000_000_000_000u128.into(),
+        );
+        pool.common.address = bpt;
+        pool.actual_supply = Some(actual_supply);
+        let pool = pool.as_pool_ref();
+
+        // Joining with DAI.
+        assert_eq!(
+            pool.bpt_swap_given_in(bpt, (U256::from(1000) * U256::exp10(18), dai)),
+            Some(999_948_756_964_458_000_000_u128.into())
+        );
+        assert_eq!(
+            pool.bpt_swap_given_out(dai, (U256::from(1000) * U256::exp10(18), bpt)),
+            Some(1_000_051_248_225_577_481_940_u128.into())
+        );
+        // Exiting to USDC.
+        assert_eq!(
+            pool.bpt_swap_given_in(usdc, (U256::from(1000) * U256::exp10(18), bpt)),
+            Some(999_948_755_u128.into())
+        );
+        assert_eq!(
+            pool.bpt_swap_given_out(bpt, (U256::from(1000) * U256::exp10(6), usdc)),
+            Some(1_000_051_249_528_594_000_000_u128.into())
+        );
+    }
 }
@@ -4,6 +4,7 @@
 
 use {
     super::error::Error,
+    crate::sources::balancer_rounding,
     anyhow::{Context, Result, bail},
     ethcontract::{I256, U256},
     num::{BigInt, Signed},
@@ -19,6 +20,10 @@ static ONE_18: LazyLock<BigInt> = LazyLock::new(|| BigInt::from(10).pow(18));
 static ONE_38: LazyLock<BigInt> = LazyLock::new(|| BigInt::from(10).pow(38));
 static E_19: LazyLock<BigInt> = LazyLock::new(|| BigInt::from(10).pow(19));
 
+// The bounds of the signed 256-bit integers the contracts compute with
+static I256_MIN: LazyLock<BigInt> = LazyLock::new(|| -(BigInt::from(1) << 255));
+static I256_MAX: LazyLock<BigInt> = LazyLock::new(|| (BigInt::from(1) << 255) - 1);
+
 static ONE_18_I256: LazyLock<I256> = LazyLock::new(|| I256::exp10(18));
 static ONE_38_I256: LazyLock<I256> = LazyLock::new(|| {
     // 1e38 = 1 followed by 38 zeros
@@ -101,37 +106,29 @@ impl SBfp {
 
     /// Convert to BigInt for use with SignedFixedPoint operations
     pub fn to_big_int(self) -> BigInt {
-        // Convert I256 to BigInt
+        // The raw bytes are the two's complement of the value, which also
+        // covers the minimum value whose magnitude doesn't fit in an I256
         let mut bytes = [0u8; 32];
-        self.0.to_big_endian(&mut bytes);
-        if self.0 >= I256::zero() {
-            BigInt::from_bytes_be(num::bigint::Sign::Plus, &bytes)
-        } else {
-            // For negative numbers, we need to handle two's complement
-            let mut positive_bytes = [0u8; 32];
-            (-self.0).to_big_endian(&mut positive_bytes);
-            -BigInt::from_bytes_be(num::bigint::Sign::Plus, &positive_bytes)
-        }
+        self.0.into_raw().to_big_endian(&mut bytes);
+        BigInt::from_signed_bytes_be(&bytes)
     }
 
-    /// Create from BigInt (result of SignedFixedPoint operations)
+    /// Create from BigInt (result of SignedFixedPoint operations), failing
+    /// for values outside of the I256 range instead of wrapping them
     pub fn from_big_int(value: &BigInt) -> Result<Self, Error> {
-        let (sign, bytes) = value.to_bytes_be();
-        if bytes.len() > 32 {
-            return Err(Error::MulOverflow); // Reuse existing error for overflow
+        if value < &*I256_MIN || value > &*I256_MAX {
+            return Err(Error::OutOfBounds);
         }
 
-        let mut padded = [0u8; 32];
-        let start = 32 - bytes.len();
-        padded[start..].copy_from_slice(&bytes);
-
-        let u_result = U256::from_big_endian(&padded);
-        let result = I256::from_raw(u_result);
-        Ok(Self(if sign == num::bigint::Sign::Minus {
-            -result
+        // Sign extend the two's complement bytes to 32 bytes
+        let bytes = value.to_signed_bytes_be();
+        let mut padded = if value.is_negative() {
+            [0xff; 32]
         } else {
-            result
-        }))
+            [0; 32]
+        };
+        padded[32 - bytes.len()..].copy_from_slice(&bytes);
+        Ok(Self(I256::from_raw(U256::from_big_endian(&padded))))
     }
 
     /// Perform signed addition using SignedFixedPoint
@@ -150,28 +147,58 @@ impl SBfp {
 
     /// Perform signed multiplication with downward magnitude rounding
     pub fn mul_down_mag(self, other: Self) -> Result<Self, Error> {
+        if balancer_rounding::nearest() {
+            return self.mul_nearest(other);
+        }
         let result = SignedFixedPoint::mul_down_mag(&self.to_big_int(), &other.to_big_int())?;
         Self::from_big_int(&result)
     }
 
     /// Perform signed multiplication with upward magnitude rounding
     pub fn mul_up_mag(self, other: Self) -> Result<Self, Error> {
+        if balancer_rounding::nearest() {
+            return self.mul_nearest(other);
+        }
         let result = SignedFixedPoint::mul_up_mag(&self.to_big_int(), &other.to_big_int())?;
         Self::from_big_int(&result)
     }
 
     /// Perform signed division with downward magnitude rounding
     pub fn div_down_mag(self, other: Self) -> Result<Self, Error> {
+        if balancer_rounding::nearest() {
+            return self.div_nearest(other);
+        }
         let result = SignedFixedPoint::div_down_mag(&self.to_big_int(), &other.to_big_int())?;
         Self::from_big_int(&result)
     }
 
     /// Perform signed division with upward magnitude rounding
     pub fn div_up_mag(self, other: Self) -> Result<Self, Error> {
+        if balancer_rounding::nearest() {
+            return self.div_nearest(other);
+        }
         let result = SignedFixedPoint::div_up_mag(&self.to_big_int(), &other.to_big_int())?;
         Self::from_big_int(&result)
     }
 
+    /// Multiplication rounding to the nearest value, for research builds.
+    fn mul_nearest(self, other: Self) -> Result<Self, Error> {
+        let product = self.to_big_int() * other.to_big_int();
+        Self::from_big_int(&balancer_rounding::div_nearest_signed(&product, &ONE_18))
+    }
+
+    /// Division rounding to the nearest value, for research builds.
+    fn div_nearest(self, other: Self) -> Result<Self, Error> {
+        if other.is_zero() {
+            return Err(Error::ZeroDivision);
+        }
+        let inflated = self.to_big_int() * &*ONE_18;
+        Self::from_big_int(&balancer_rounding::div_nearest_signed(
+            &inflated,
+            &other.to_big_int(),
+        ))
+    }
+
     /// Parse decimal string with specified precision level
     /// This preserves full mathematical precision without truncation
     pub fn from_str_with_precision(
@@ -231,6 +258,22 @@ impl SBfp {
         Ok(SBfp(result))
     }
 
+    /// Formats the value as a decimal string with the specified precision
+    /// level, the inverse of [`Self::from_str_with_precision`].
+    pub fn to_string_with_precision(self, precision: FixedPointPrecision) -> String {
+        let (scaling_factor, decimals) = match precision {
+            FixedPointPrecision::Standard18 => (&*ONE_18_I256, 18),
+            FixedPointPrecision::Extended38 => (&*ONE_38_I256, 38),
+        };
+        let abs_value = self.0.abs();
+        let sign = if self.0 < I256::zero() { "-" } else { "" };
+        format!(
+            "{sign}{}.{:0>decimals$}",
+            abs_value / *scaling_factor,
+            (abs_value % *scaling_factor).as_u128()
+        )
+    }
+
     /// Calculate complement: ONE - x, with bounds checking
     pub fn complement(self) -> Self {
         let result = SignedFixedPoint::complement(&self.to_big_int());
@@ -358,10 +401,21 @@ impl SignedFixedPoint {
         ONE_38.clone()
     }
 
+    /// Checks that an intermediate value fits in an I256, failing with the
+    /// error of the operation otherwise. The contracts compute with I256s,
+    /// so they revert where the unbounded BigInt arithmetic would silently
+    /// carry on with an amount that can't occur on-chain
+    fn bounded(value: BigInt, error: Error) -> Result<BigInt, Error> {
+        if value < *I256_MIN || value > *I256_MAX {
+            return Err(error);
+        }
+        Ok(value)
+    }
+
     /// Signed addition with overflow checking
     /// Equivalent to Python: add(a, b)
     pub fn add(a: &BigInt, b: &BigInt) -> Result<BigInt, Error> {
-        let c = a + b;
+        let c = Self::bounded(a + b, Error::AddOverflow)?;
 
         // Check for overflow: if b >= 0, then c >= a; if b < 0, then c < a
         if !((b >= &BigInt::from(0) && &c >= a) || (b < &BigInt::from(0) && &c < a)) {
@@ -383,7 +437,7 @@ impl SignedFixedPoint {
     /// Signed subtraction with overflow checking
     /// Equivalent to Python: sub(a, b)
     pub fn sub(a: &BigInt, b: &BigInt) -> Result<BigInt, Error> {
-        let c = a - b;
+        let c = Self::bounded(a - b, Error::SubOverflow)?;
 
         // Check for overflow: if b <= 0, then c >= a; if b > 0, then c < a
         if !((b <= &BigInt::from(0) && &c >= a) || (b > &BigInt::from(0) && &c < a)) {
@@ -395,7 +449,7 @@ impl SignedFixedPoint {
     /// Multiply with downward magnitude rounding
     /// Equivalent to Python: mul_down_mag(a, b)
     pub fn mul_down_mag(a: &BigInt, b: &BigInt) -> Result<BigInt, Error> {
-        let product = a * b;
+        let product = Self::bounded(a * b, Error::MulOverflow)?;
 
         // Check for overflow: a == 0 or product // a == b (using floor division like
         // Python)
@@ -420,7 +474,7 @@ impl SignedFixedPoint {
     /// Multiply with upward magnitude rounding
     /// Equivalent to Python: mul_up_mag(a, b)
     pub fn mul_up_mag(a: &BigInt, b: &BigInt) -> Result<BigInt, Error> {
-        let product = a * b;
+        let product = Self::bounded(a * b, Error::MulOverflow)?;
 
         // Check for overflow: a == 0 or product // a == b (using floor division like
         // Python)
@@ -460,7 +514,7 @@ impl SignedFixedPoint {
             return Ok(BigInt::from(0));
         }
 
-        let a_inflated = a * &*ONE_18;
+        let a_inflated = Self::bounded(a * &*ONE_18, Error::DivInternal)?;
         if Self::floor_div(&a_inflated, a) != *ONE_18 {
             return Err(Error::DivInternal);
         }
@@ -504,7 +558,7 @@ impl SignedFixedPoint {
             local_a = -a;
         }
 
-        let a_inflated = &local_a * &*ONE_18;
+        let a_inflated = Self::bounded(&local_a * &*ONE_18, Error::DivInternal)?;
         if Self::floor_div(&a_inflated, &local_a) != *ONE_18 {
             return Err(Error::DivInternal);
         }
@@ -543,7 +597,7 @@ impl SignedFixedPoint {
     /// Multiply with extra precision
     /// Equivalent to Python: mul_xp(a, b)
     pub fn mul_xp(a: &BigInt, b: &BigInt) -> Result<BigInt, Error> {
-        let product = a * b;
+        let product = Self::bounded(a * b, Error::MulOverflow)?;
 
         // Check for overflow: a == 0 or product // a == b (using floor division like
         // Python)
@@ -570,7 +624,7 @@ impl SignedFixedPoint {
             return Ok(BigInt::from(0));
         }
 
-        let a_inflated = a * &*ONE_38;
+        let a_inflated = Self::bounded(a * &*ONE_38, Error::DivInternal)?;
         if Self::floor_div(&a_inflated, a) != *ONE_38 {
             return Err(Error::DivInternal);
         }
@@ -591,13 +645,13 @@ impl SignedFixedPoint {
     /// rounding Equivalent to Python: mul_down_xp_to_np(a, b)
     pub fn mul_down_xp_to_np(a: &BigInt, b: &BigInt) -> Result<BigInt, Error> {
         let b1 = Self::floor_div(b, &E_19);
-        let prod1 = a * &b1;
+        let prod1 = Self::bounded(a * &b1, Error::MulOverflow)?;
         if !(a == &BigInt::from(0) || Self::floor_div(&prod1, a) == b1) {
             return Err(Error::MulOverflow);
         }
 
         let b2 = b % &*E_19;
-        let prod2 = a * &b2;
+        let prod2 = Self::bounded(a * &b2, Error::MulOverflow)?;
         if !(a == &BigInt::from(0) || Self::floor_div(&prod2, a) == b2) {
             return Err(Error::MulOverflow);
         }
@@ -631,13 +685,13 @@ impl SignedFixedPoint {
     /// rounding Equivalent to Python: mul_up_xp_to_np(a, b)
     pub fn mul_up_xp_to_np(a: &BigInt, b: &BigInt) -> Result<BigInt, Error> {
         let b1 = Self::floor_div(b, &E_19);
-        let prod1 = a * &b1;
+        let prod1 = Self::bounded(a * &b1, Error::MulOverflow)?;
         if !(a == &BigInt::from(0) || Self::floor_div(&prod1, a) == b1) {
             return Err(Error::MulOverflow);
         }
 
         let b2 = b % &*E_19;
-        let prod2 = a * &b2;
+        let prod2 = Self::bounded(a * &b2, Error::MulOverflow)?;
         if !(a == &BigInt::from(0) || Self::floor_div(&prod2, a) == b2) {
             return Err(Error::MulOverflow);
         }
@@ -690,7 +744,22 @@ impl SignedFixedPoint {
 
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use {super::*, proptest::prelude::*};
+
+    /// Any I256 value, including the bounds.
+    fn any_sbfp() -> impl Strategy<Value = SBfp> {
+        any::<[u8; 32]>().prop_map(|bytes| SBfp(I256::from_raw(U256::from_big_endian(&bytes))))
+    }
+
+    /// Values whose products and inflated quotients fit in an I256.
+    fn amount() -> impl Strategy<Value = SBfp> {
+        any::<i128>().prop_map(|value| SBfp(I256::from(value)))
+    }
+
+    /// The value if it fits in an I256, or the error otherwise.
+    fn bounded(value: BigInt, error: Error) -> Result<SBfp, Error> {
+        SBfp::from_big_int(&value).map_err(|_| error)
+    }
 
     #[test]
     fn test_constants() {
@@ -862,4 +931,86 @@ mod tests {
             SBfp::from_str_with_precision("42", FixedPointPrecision::Standard18).unwrap();
         assert_eq!(integer_value, expected_integer);
     }
+
+    #[test]
+    fn test_i256_bounds() {
+        let max = SBfp::from_big_int(&I256_MAX).unwrap();
+        let min = SBfp::from_big_int(&I256_MIN).unwrap();
+        assert_eq!(max.to_big_int(), *I256_MAX);
+        assert_eq!(min.to_big_int(), *I256_MIN);
+        assert_eq!(
+            SBfp::from_big_int(&(&*I256_MAX + 1)),
+            Err(Error::OutOfBounds)
+        );
+        assert_eq!(
+            SBfp::from_big_int(&(&*I256_MIN - 1)),
+            Err(Error::OutOfBounds)
+        );
+
+        let wei = SBfp::from_wei(I256::from(1));
+        let two = SBfp::one().add(SBfp::one()).unwrap();
+        assert_eq!(max.add(wei), Err(Error::AddOverflow));
+        assert_eq!(min.sub(wei), Err(Error::SubOverflow));
+        assert_eq!(max.mul_down_mag(two), Err(Error::MulOverflow));
+        assert_eq!(min.mul_up_mag(two), Err(Error::MulOverflow));
+        assert_eq!(max.div_down_mag(two), Err(Error::DivInternal));
+        assert_eq!(min.div_up_mag(two), Err(Error::DivInternal));
+    }
+
+    proptest! {
+        #[test]
+        fn test_big_int_round_trip(a in any_sbfp()) {
+            prop_assert_eq!(SBfp::from_big_int(&a.to_big_int()), Ok(a));
+        }
+
+        #[test]
+        fn test_add_sub_bounds(a in any_sbfp(), b in any_sbfp()) {
+            prop_assert_eq!(a.add(b), b.add(a));
+            prop_assert_eq!(
+                a.add(b),
+                bounded(a.to_big_int() + b.to_big_int(), Error::AddOverflow)
+            );
+            prop_assert_eq!(
+                a.sub(b),
+                bounded(a.to_big_int() - b.to_big_int(), Error::SubOverflow)
+            );
+        }
+
+        #[test]
+        fn test_mul_bounds(a in any_sbfp(), b in any_sbfp()) {
+            prop_assert_eq!(a.mul_down_mag(b), b.mul_down_mag(a));
+            prop_assert_eq!(a.mul_up_mag(b), b.mul_up_mag(a));
+
+            let overflows = SBfp::from_big_int(&(a.to_big_int() * b.to_big_int())).is_err();
+            for result in [a.mul_down_mag(b), a.mul_up_mag(b)] {
+                prop_assert_eq!(result.err(), overflows.then_some(Error::MulOverflow));
+            }
+        }
+
+        #[test]
+        fn test_rounding_magnitudes(a in amount(), b in amount()) {
+            let down = a.mul_down_mag(b).unwrap().to_big_int();
+            let up = a.mul_up_mag(b).unwrap().to_big_int();
+            prop_assert!(down.abs() <= up.abs() && up.abs() <= down.abs() + 1);
+            let product = a.to_big_int() * b.to_big_int();
+            if product >= BigInt::from(0) {
+                prop_assert!(&down * &*ONE_18 <= product && product <= &up * &*ONE_18);
+            }
+
+            prop_assume!(!b.is_zero());
+            let down = a.div_down_mag(b).unwrap().to_big_int();
+            let up = a.div_up_mag(b).unwrap().to_big_int();
+            prop_assert!(down.abs() <= up.abs() && up.abs() <= down.abs() + 1);
+        }
+
+        #[test]
+        fn test_rounding_monotonic(a in amount(), c in amount(), b in amount()) {
+            prop_assume!(b.is_positive());
+            let (low, high) = (a.min(c), a.max(c));
+            prop_assert!(low.mul_down_mag(b).unwrap() <= high.mul_down_mag(b).unwrap());
+            prop_assert!(low.mul_up_mag(b).unwrap() <= high.mul_up_mag(b).unwrap());
+            prop_assert!(low.div_down_mag(b).unwrap() <= high.div_down_mag(b).unwrap());
+            prop_assert!(low.div_up_mag(b).unwrap() <= high.div_up_mag(b).unwrap());
+        }
+    }
 }
@@ -127,6 +127,187 @@ pub fn calc_in_given_out(
         .add(Bfp::from_wei(1.into()))
 }
 
+/// Computes the BPT minted for joining with `amounts_in`. Amounts joined
+/// disproportionately to the pool balances pay the swap fee.
+///
+/// `_calcBptOutGivenExactTokensIn` in
+/// https://github.com/balancer-labs/balancer-v2-monorepo/blob/master/pkg/pool-stable/contracts/StableMath.sol
+pub fn calc_bpt_out_given_exact_tokens_in(
+    amplification_parameter: U256,
+    balances: &[Bfp],
+    amounts_in: &[Bfp],
+    bpt_total_supply: Bfp,
+    swap_fee: Bfp,
+) -> Result<Bfp, Error> {
+    if amounts_in.len() != balances.len() {
+        return Err(Error::InvalidToken);
+    }
+    let current_invariant = Bfp::from_wei(calculate_invariant(amplification_parameter, balances)?);
+    let sum_balances = balances
+        .iter()
+        .try_fold(Bfp::zero(), |sum, balance| sum.add(*balance))?;
+
+    let mut balance_ratios_with_fee = Vec::with_capacity(balances.len());
+    let mut invariant_ratio_with_fees = Bfp::zero();
+    for (balance, amount_in) in balances.iter().zip(amounts_in) {
+        let current_weight = balance.div_down(sum_balances)?;
+        let balance_ratio_with_fee = balance.add(*amount_in)?.div_down(*balance)?;
+        invariant_ratio_with_fees =
+            invariant_ratio_with_fees.add(balance_ratio_with_fee.mul_down(current_weight)?)?;
+        balance_ratios_with_fee.push(balance_ratio_with_fee);
+    }
+
+    let mut new_balances = Vec::with_capacity(balances.len());
+    for ((balance, amount_in), balance_ratio_with_fee) in
+        balances.iter().zip(amounts_in).zip(balance_ratios_with_fee)
+    {
+        let amount_in_without_fee = if balance_ratio_with_fee > invariant_ratio_with_fees {
+            let non_taxable_amount =
+                balance.mul_down(invariant_ratio_with_fees.sub(Bfp::one())?)?;
+            let taxable_amount = amount_in.sub(non_taxable_amount)?;
+            non_taxable_amount.add(taxable_amount.mul_down(swap_fee.complement())?)?
+        } else {
+            *amount_in
+        };
+        new_balances.push(balance.add(amount_in_without_fee)?);
+    }
+
+    let new_invariant = Bfp::from_wei(calculate_invariant(amplification_parameter, &new_balances)?);
+    let invariant_ratio = new_invariant.div_down(current_invariant)?;
+    if invariant_ratio > Bfp::one() {
+        bpt_total_supply.mul_down(invariant_ratio.sub(Bfp::one())?)
+    } else {
+        Ok(Bfp::zero())
+    }
+}
+
+/// Computes the amount of the token at `token_index` to join with for
+/// minting exactly `bpt_amount_out`, including the swap fee.
+///
+/// `_calcTokenInGivenExactBptOut` in
+/// https://github.com/balancer-labs/balancer-v2-monorepo/blob/master/pkg/pool-stable/contracts/StableMath.sol
+pub fn calc_token_in_given_exact_bpt_out(
+    amplification_parameter: U256,
+    balances: &[Bfp],
+    token_index: usize,
+    bpt_amount_out: Bfp,
+    bpt_total_supply: Bfp,
+    swap_fee: Bfp,
+) -> Result<Bfp, Error> {
+    if token_index >= balances.len() {
+        return Err(Error::InvalidToken);
+    }
+    let current_invariant = Bfp::from_wei(calculate_invariant(amplification_parameter, balances)?);
+    let new_invariant = bpt_total_supply
+        .add(bpt_amount_out)?
+        .div_up(bpt_total_supply)?
+        .mul_up(current_invariant)?;
+    let new_balance = get_token_balance_given_invariant_and_all_other_balances(
+        amplification_parameter,
+        balances,
+        new_invariant.as_uint256(),
+        token_index,
+    )?;
+    let amount_in_without_fee = new_balance.sub(balances[token_index])?;
+
+    let sum_balances = balances
+        .iter()
+        .try_fold(Bfp::zero(), |sum, balance| sum.add(*balance))?;
+    let current_weight = balances[token_index].div_down(sum_balances)?;
+    let taxable_amount = amount_in_without_fee.mul_up(current_weight.complement())?;
+    let non_taxable_amount = amount_in_without_fee.sub(taxable_amount)?;
+    non_taxable_amount.add(taxable_amount.div_up(swap_fee.complement())?)
+}
+
+/// Computes the BPT to burn for exiting with exactly `amounts_out`. Amounts
+/// exited disproportionately to the pool balances pay the swap fee.
+///
+/// `_calcBptInGivenExactTokensOut` in
+/// https://github.com/balancer-labs/balancer-v2-monorepo/blob/master/pkg/pool-stable/contracts/StableMath.sol
+pub fn calc_bpt_in_given_exact_tokens_out(
+    amplification_parameter: U256,
+    balances: &[Bfp],
+    amounts_out: &[Bfp],
+    bpt_total_supply: Bfp,
+    swap_fee: Bfp,
+) -> Result<Bfp, Error> {
+    if amounts_out.len() != balances.len() {
+        return Err(Error::InvalidToken);
+    }
+    let current_invariant = Bfp::from_wei(calculate_invariant(amplification_parameter, balances)?);
+    let sum_balances = balances
+        .iter()
+        .try_fold(Bfp::zero(), |sum, balance| sum.add(*balance))?;
+
+    let mut balance_ratios_without_fee = Vec::with_capacity(balances.len());
+    let mut invariant_ratio_without_fees = Bfp::zero();
+    for (balance, amount_out) in balances.iter().zip(amounts_out) {
+        let current_weight = balance.div_up(sum_balances)?;
+        let balance_ratio_without_fee = balance.sub(*amount_out)?.div_up(*balance)?;
+        invariant_ratio_without_fees =
+            invariant_ratio_without_fees.add(balance_ratio_without_fee.mul_up(current_weight)?)?;
+        balance_ratios_without_fee.push(balance_ratio_without_fee);
+    }
+
+    let mut new_balances = Vec::with_capacity(balances.len());
+    for ((balance, amount_out), balance_ratio_without_fee) in balances
+        .iter()
+        .zip(amounts_out)
+        .zip(balance_ratios_without_fee)
+    {
+        let amount_out_with_fee = if invariant_ratio_without_fees > balance_ratio_without_fee {
+            let non_taxable_amount = balance.mul_down(invariant_ratio_without_fees.complement())?;
+            let taxable_amount = amount_out.sub(non_taxable_amount)?;
+            non_taxable_amount.add(taxable_amount.div_up(swap_fee.complement())?)?
+        } else {
+            *amount_out
+        };
+        new_balances.push(balance.sub(amount_out_with_fee)?);
+    }
+
+    let new_invariant = Bfp::from_wei(calculate_invariant(amplification_parameter, &new_balances)?);
+    let invariant_ratio = new_invariant.div_down(current_invariant)?;
+    bpt_total_supply.mul_up(invariant_ratio.complement())
+}
+
+/// Computes the amount of the token at `token_index` received for burning
+/// exactly `bpt_amount_in`, net of the swap fee.
+///
+/// `_calcTokenOutGivenExactBptIn` in
+/// https://github.com/balancer-labs/balancer-v2-monorepo/blob/master/pkg/pool-stable/contracts/StableMath.sol
+pub fn calc_token_out_given_exact_bpt_in(
+    amplification_parameter: U256,
+    balances: &[Bfp],
+    token_index: usize,
+    bpt_amount_in: Bfp,
+    bpt_total_supply: Bfp,
+    swap_fee: Bfp,
+) -> Result<Bfp, Error> {
+    if token_index >= balances.len() {
+        return Err(Error::InvalidToken);
+    }
+    let current_invariant = Bfp::from_wei(calculate_invariant(amplification_parameter, balances)?);
+    let new_invariant = bpt_total_supply
+        .sub(bpt_amount_in)?
+        .div_up(bpt_total_supply)?
+        .mul_up(current_invariant)?;
+    let new_balance = get_token_balance_given_invariant_and_all_other_balances(
+        amplification_parameter,
+        balances,
+        new_invariant.as_uint256(),
+        token_index,
+    )?;
+    let amount_out_without_fee = balances[token_index].sub(new_balance)?;
+
+    let sum_balances = balances
+        .iter()
+        .try_fold(Bfp::zero(), |sum, balance| sum.add(*balance))?;
+    let current_weight = balances[token_index].div_down(sum_balances)?;
+    let taxable_amount = amount_out_without_fee.mul_up(current_weight.complement())?;
+    let non_taxable_amount = amount_out_without_fee.sub(taxable_amount)?;
+    non_taxable_amount.add(taxable_amount.mul_down(swap_fee.complement())?)
+}
+
 /// https://github.com/balancer-labs/balancer-v2-monorepo/blob/ad1442113b26ec22081c2047e2ec95355a7f12ba/pkg/pool-stable/contracts/StableMath.sol#L465-L516
 fn get_token_balance_given_invariant_and_all_other_balances(
     amplification_parameter: U256,
@@ -519,4 +700,158 @@ mod tests {
                 .le(&max_relative_error)
         );
     }
+
+    #[test]
+    fn proportional_join_mints_proportional_bpt() {
+        let amplification_parameter = U256::from(100) * *AMP_PRECISION;
+        let balances = [Bfp::from(10), Bfp::from(12)];
+        let amounts_in = [Bfp::from(1), Bfp::from_str("1.2").unwrap()];
+        let result = calc_bpt_out_given_exact_tokens_in(
+            amplification_parameter,
+            &balances,
+            &amounts_in,
+            Bfp::from(22),
+            Bfp::from_str("0.01").unwrap(),
+        )
+        .unwrap();
+        // Proportional joins don't pay swap fees.
+        assert!((result.to_f64_lossy() - 2.2).abs().le(&1e-9));
+    }
+
+    #[test]
+    fn single_token_joins_and_exits_are_consistent() {
+        let amplification_parameter = U256::from(100) * *AMP_PRECISION;
+        let balances = [Bfp::from(10), Bfp::from(12), Bfp::from(14)];
+        let bpt_total_supply = Bfp::from(36);
+        let swap_fee = Bfp::from_str("0.001").unwrap();
+        let amount = Bfp::from(1);
+        let amounts = [Bfp::zero(), amount, Bfp::zero()];
+        let max_relative_error = 0.001;
+
+        let bpt_out = calc_bpt_out_given_exact_tokens_in(
+            amplification_parameter,
+            &balances,
+            &amounts,
+            bpt_total_supply,
+            swap_fee,
+        )
+        .unwrap();
+        let amount_in = calc_token_in_given_exact_bpt_out(
+            amplification_parameter,
+            &balances,
+            1,
+            bpt_out,
+            bpt_total_supply,
+            swap_fee,
+        )
+        .unwrap();
+        assert!(bpt_out < amount);
+        assert!(
+            (amount_in.to_f64_lossy() - amount.to_f64_lossy())
+                .abs()
+                .le(&max_relative_error)
+        );
+
+        let bpt_in = calc_bpt_in_given_exact_tokens_out(
+            amplification_parameter,
+            &balances,
+            &amounts,
+            bpt_total_supply,
+            swap_fee,
+        )
+        .unwrap();
+        let amount_out = calc_token_out_given_exact_bpt_in(
+            amplification_parameter,
+            &balances,
+            1,
+            bpt_in,
+            bpt_total_supply,
+            swap_fee,
+        )
+        .unwrap();
+        assert!(bpt_in > bpt_out);
+        assert!(
+            (amount_out.to_f64_lossy() - amount.to_f64_lossy())
+                .abs()
+                .le(&max_relative_error)
+        );
+    }
+
+    #[test]
+    fn joins_and_exits_match_reference_values() {
+        // Expected values computed with a Python port of the referenced
+        // StableMath.sol functions and the FixedPoint.sol rounding, as no
+        // archive node was at hand to replay on-chain joins and exits.
+        let amplification_parameter = U256::from(200) * *AMP_PRECISION;
+        let balances = [1_000_000_u128, 1_200_000, 800_000]
+            .map(|balance| Bfp::from_wei(U256::from(balance) * U256::exp10(18)));
+        let bpt_total_supply = Bfp::from_wei(U256::from(2_990_000) * U256::exp10(18));
+        let swap_fee = Bfp::from_wei(U256::from(300_000_000_000_000_u128));
+        let amount = Bfp::from_wei(U256::from(10_000) * U256::exp10(18));
+        let only = |index: usize| {
+            let mut amounts = [Bfp::zero(); 3];
+            amounts[index] = amount;
+            amounts
+        };
+
+        assert_eq!(
+            calc_bpt_out_given_exact_tokens_in(
+                amplification_parameter,
+                &balances,
+                &only(0),
+                bpt_total_supply,
+                swap_fee,
+            )
+            .unwrap(),
+            Bfp::from_wei(9_964_502_259_032_777_690_000_u128.into())
+        );
+        // Proportional joins don't pay the swap fee.
+        assert_eq!(
+            calc_bpt_out_given_exact_tokens_in(
+                amplification_parameter,
+                &balances,
+                &[10_000_u128, 12_000, 8_000]
+                    .map(|amount| Bfp::from_wei(U256::from(amount) * U256::exp10(18))),
+                bpt_total_supply,
+                swap_fee,
+            )
+            .unwrap(),
+            Bfp::from_wei(29_899_999_999_999_997_010_000_u128.into())
+        );
+        assert_eq!(
+            calc_token_in_given_exact_bpt_out(
+                amplification_parameter,
+                &balances,
+                2,
+                amount,
+                bpt_total_supply,
+                swap_fee,
+            )
+            .unwrap(),
+            Bfp::from_wei(10_022_962_867_998_855_216_743_u128.into())
+        );
+        assert_eq!(
+            calc_bpt_in_given_exact_tokens_out(
+                amplification_parameter,
+                &balances,
+                &only(1),
+                bpt_total_supply,
+                swap_fee,
+            )
+            .unwrap(),
+            Bfp::from_wei(9_959_984_558_946_496_750_000_u128.into())
+        );
+        assert_eq!(
+            calc_token_out_given_exact_bpt_in(
+                amplification_parameter,
+                &balances,
+                0,
+                amount,
+                bpt_total_supply,
+                swap_fee,
+            )
+            .unwrap(),
+            Bfp::from_wei(10_031_263_367_845_816_503_522_u128.into())
+        );
+    }
 }
@@ -13,6 +13,11 @@ static MAX_IN_RATIO: LazyLock<Bfp> =
     LazyLock::new(|| Bfp::from_wei(U256::exp10(17).checked_mul(3_u32.into()).unwrap()));
 static MAX_OUT_RATIO: LazyLock<Bfp> =
     LazyLock::new(|| Bfp::from_wei(U256::exp10(17).checked_mul(3_u32.into()).unwrap()));
+// https://github.com/balancer-labs/balancer-v2-monorepo/blob/c18ff2686c61a8cbad72cdcfc65e9b11476fdbc3/pkg/pool-weighted/contracts/WeightedMath.sol#L50-L54
+static MAX_INVARIANT_RATIO: LazyLock<Bfp> =
+    LazyLock::new(|| Bfp::from_wei(U256::exp10(18).checked_mul(3_u32.into()).unwrap()));
+static MIN_INVARIANT_RATIO: LazyLock<Bfp> =
+    LazyLock::new(|| Bfp::from_wei(U256::exp10(17).checked_mul(7_u32.into()).unwrap()));
 
 /// https://github.com/balancer-labs/balancer-v2-monorepo/blob/6c9e24e22d0c46cca6dd15861d3d33da61a60b98/pkg/core/contracts/pools/weighted/WeightedMath.sol#L69-L100
 /// It is not possible for the following addition balance_in.add(amount_in) to
@@ -119,6 +124,80 @@ pub fn calc_in_given_out_v3(
     balance_in.mul_up(ratio)
 }
 
+/// Computes the pool tokens minted for joining with a single token.
+/// https://github.com/balancer-labs/balancer-v2-monorepo/blob/c18ff2686c61a8cbad72cdcfc65e9b11476fdbc3/pkg/pool-weighted/contracts/WeightedMath.sol#L148-L188
+pub fn calc_bpt_out_given_exact_token_in(
+    balance: Bfp,
+    normalized_weight: Bfp,
+    amount_in: Bfp,
+    bpt_total_supply: Bfp,
+    swap_fee: Bfp,
+) -> Result<Bfp, Error> {
+    // The amount in that keeps the pool balanced is not charged swap fees,
+    // only the excess over it is.
+    let balance_ratio_with_fee = balance.add(amount_in)?.div_down(balance)?;
+    let invariant_ratio_with_fees = balance_ratio_with_fee
+        .sub(Bfp::one())?
+        .mul_down(normalized_weight)?
+        .add(Bfp::one())?;
+
+    let amount_in_without_fee = if balance_ratio_with_fee > invariant_ratio_with_fees {
+        let non_taxable_amount = if invariant_ratio_with_fees > Bfp::one() {
+            balance.mul_down(invariant_ratio_with_fees.sub(Bfp::one())?)?
+        } else {
+            Bfp::zero()
+        };
+        let swap_fee = amount_in.sub(non_taxable_amount)?.mul_up(swap_fee)?;
+        amount_in.sub(swap_fee)?
+    } else {
+        if amount_in.is_zero() {
+            return Ok(Bfp::zero());
+        }
+        amount_in
+    };
+
+    let balance_ratio = balance.add(amount_in_without_fee)?.div_down(balance)?;
+    let invariant_ratio = balance_ratio.pow_down(normalized_weight)?;
+    if invariant_ratio > *MAX_INVARIANT_RATIO {
+        return Err(Error::MaxOutBptForTokensIn);
+    }
+
+    if invariant_ratio > Bfp::one() {
+        bpt_total_supply.mul_down(invariant_ratio.sub(Bfp::one())?)
+    } else {
+        Ok(Bfp::zero())
+    }
+}
+
+/// Computes the amount of a single token received for exiting with the
+/// specified amount of pool tokens.
+/// https://github.com/balancer-labs/balancer-v2-monorepo/blob/c18ff2686c61a8cbad72cdcfc65e9b11476fdbc3/pkg/pool-weighted/contracts/WeightedMath.sol#L272-L304
+pub fn calc_token_out_given_exact_bpt_in(
+    balance: Bfp,
+    normalized_weight: Bfp,
+    bpt_amount_in: Bfp,
+    bpt_total_supply: Bfp,
+    swap_fee: Bfp,
+) -> Result<Bfp, Error> {
+    let invariant_ratio = bpt_total_supply
+        .sub(bpt_amount_in)?
+        .div_up(bpt_total_supply)?;
+    if invariant_ratio < *MIN_INVARIANT_RATIO {
+        return Err(Error::MinBptInForTokenOut);
+    }
+
+    let balance_ratio = invariant_ratio.pow_up(Bfp::one().div_down(normalized_weight)?)?;
+    let amount_out_without_fee = balance.mul_down(balance_ratio.complement())?;
+
+    // Only the amount exceeding the token's share of the pool is charged swap
+    // fees.
+    let taxable_amount = amount_out_without_fee.mul_up(normalized_weight.complement())?;
+    let non_taxable_amount = amount_out_without_fee.sub(taxable_amount)?;
+    let taxable_amount_minus_fees = taxable_amount.mul_down(swap_fee.complement())?;
+
+    non_taxable_amount.add(taxable_amount_minus_fees)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -336,4 +415,73 @@ mod tests {
             "305".into()
         );
     }
+
+    #[test]
+    fn single_token_join_and_exit() {
+        let balance = Bfp::from_wei(U256::exp10(22));
+        let weight = "0.5".parse::<Bfp>().unwrap();
+        let supply = Bfp::from_wei(U256::exp10(21));
+        let fee = "0.01".parse::<Bfp>().unwrap();
+
+        // Joining with 1% of the balance in one of two tokens mints slightly
+        // less than 0.5% of the supply, due to the fees on the unbalanced part.
+        let amount_in = Bfp::from_wei(U256::exp10(20));
+        let bpt_out =
+            calc_bpt_out_given_exact_token_in(balance, weight, amount_in, supply, fee).unwrap();
+        let without_fees =
+            calc_bpt_out_given_exact_token_in(balance, weight, amount_in, supply, Bfp::zero())
+                .unwrap();
+        assert!(bpt_out < without_fees);
+        assert!(without_fees < Bfp::from_wei(U256::exp10(21) / 200));
+        assert!(without_fees > Bfp::from_wei(U256::exp10(21) / 201));
+
+        // Exiting with the minted pool tokens returns less than was put in.
+        let amount_out = calc_token_out_given_exact_bpt_in(
+            balance.add(amount_in).unwrap(),
+            weight,
+            bpt_out,
+            supply.add(bpt_out).unwrap(),
+            fee,
+        )
+        .unwrap();
+        assert!(amount_out < amount_in);
+        assert!(amount_out > Bfp::from_wei(U256::exp10(20) * 98 / 100));
+
+        assert_eq!(
+            calc_bpt_out_given_exact_token_in(balance, weight, Bfp::zero(), supply, fee).unwrap(),
+            Bfp::zero()
+        );
+    }
+
+    #[test]
+    fn single_token_join_and_exit_limits() {
+        let balance = Bfp::from_wei(U256::exp10(22));
+        let weight = "0.5".parse::<Bfp>().unwrap();
+        let supply = Bfp::from_wei(U256::exp10(21));
+
+        // Growing the invariant more than 3x is not allowed.
+        assert_eq!(
+            calc_bpt_out_given_exact_token_in(
+                balance,
+                weight,
+                Bfp::from_wei(U256::exp10(23)),
+                supply,
+                Bfp::zero(),
+            )
+            .unwrap_err(),
+            "307".into()
+        );
+        // Neither is burning more than 30% of the supply at once.
+        assert_eq!(
+            calc_token_out_given_exact_bpt_in(
+                balance,
+                weight,
+                Bfp::from_wei(U256::exp10(20) * 4),
+                supply,
+                Bfp::zero(),
+            )
+            .unwrap_err(),
+            "306".into()
+        );
+    }
 }
@@ -34,8 +34,6 @@ where
 
             // Parse valid decimal strings with automatic precision detection
             // (same logic as the original SBfp::Deserialize implementation)
-            use crate::sources::balancer_v3::swap::signed_fixed_point::FixedPointPrecision;
-
             let precision = if s.contains('.')
                 && s.split('.').nth(1).map_or(0, |decimals| decimals.len()) > 30
             {
@@ -51,16 +49,47 @@ where
     }
 }
 
+/// Serializes optional fixed point values as the decimal strings the API
+/// returns, so that exported pool bundles read back the same values.
+fn serialize_optional_sbfp<S>(value: &Option<SBfp>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    value
+        .map(|value| value.to_string_with_precision(FixedPointPrecision::Standard18))
+        .serialize(serializer)
+}
+
+/// Like [`serialize_optional_sbfp`] for the E-CLP parameters the API returns
+/// with 38 decimals.
+fn serialize_optional_extended_sbfp<S>(
+    value: &Option<SBfp>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    value
+        .map(|value| value.to_string_with_precision(FixedPointPrecision::Extended38))
+        .serialize(serializer)
+}
+
 use {
-    super::swap::{fixed_point::Bfp, signed_fixed_point::SBfp},
-    crate::subgraph::SubgraphClient,
+    super::swap::{
+        fixed_point::Bfp,
+        signed_fixed_point::{FixedPointPrecision, SBfp},
+    },
+    crate::{
+        sources::balancer_api::{self, ApiPool},
+        subgraph::SubgraphClient,
+    },
     anyhow::{Context, Result},
     ethcontract::H160,
     reqwest::{Client, Url},
-    serde::{Deserialize, Deserializer, Serialize},
+    serde::{Deserialize, Deserializer, Serialize, Serializer},
     serde_json::json,
     serde_with::{DisplayFromStr, serde_as},
-    std::collections::HashMap,
+    std::collections::{HashMap, HashSet},
 };
 
 /// Balancer V3 API client for fetching pool data.
@@ -100,42 +129,45 @@ impl BalancerApiClient {
 
     /// Retrieves all registered pools for the configured chain.
     pub async fn get_registered_pools(&self) -> Result<RegisteredPools> {
+        self.query_pools(None).await
+    }
+
+    /// Retrieves the registered pools created at or after the specified UNIX
+    /// timestamp, for resyncing pools that were initialized before without
+    /// fetching all pools again.
+    pub async fn get_pools_created_since(&self, create_time: u64) -> Result<RegisteredPools> {
+        self.query_pools(Some(create_time)).await
+    }
+
+    async fn query_pools(&self, created_since: Option<u64>) -> Result<RegisteredPools> {
         use self::pools_query::*;
 
-        let mut pools = Vec::new();
-        let mut skip = 0;
-
-        // Use offset-based pagination with Balancer V3 API
-        loop {
-            let page = self
-                .client
-                .query::<Data>(
-                    QUERY,
-                    Some(json_map! {
-                        "first" => QUERY_PAGE_SIZE,
-                        "skip" => skip,
-                        "orderBy" => "totalLiquidity",
-                        "orderDirection" => "desc",
-                        "where" => json!({
-                            "includeHooks": "STABLE_SURGE",
-                            "chainIn": [self.chain],
-                             "poolTypeIn": ["WEIGHTED", "STABLE", "GYROE", "RECLAMM", "QUANT_AMM_WEIGHTED", "GYRO"],
-                            "protocolVersionIn": [3] // V3 protocol
-                        }),
-                    }),
-                )
-                .await?
-                .aggregator_pools;
-
-            let no_more_pages = page.len() != QUERY_PAGE_SIZE;
-            pools.extend(page);
-
-            if no_more_pages {
-                break;
+        let pools = balancer_api::paginate(QUERY_PAGE_SIZE, created_since, |cursor| {
+            let mut filter = json!({
+                "includeHooks": "STABLE_SURGE",
+                "chainIn": [self.chain],
+                "poolTypeIn": ["WEIGHTED", "STABLE", "GYROE", "RECLAMM", "QUANT_AMM_WEIGHTED", "GYRO"],
+                "protocolVersionIn": [3] // V3 protocol
+            });
+            if let Some(cursor) = cursor {
+                // Includes the pools created at the cursor, see `paginate`.
+                filter["createTime"] = json!({ "gt": cursor.saturating_sub(1) });
             }
-
-            skip += QUERY_PAGE_SIZE;
-        }
+            let variables = Some(json_map! {
+                "first" => QUERY_PAGE_SIZE,
+                "orderBy" => "createTime",
+                "orderDirection" => "asc",
+                "where" => filter,
+            });
+            async move {
+                let data = balancer_api::retry_with_backoff(|| {
+                    self.client.query_without_retry::<Data>(QUERY, &variables)
+                })
+                .await?;
+                Ok(data.aggregator_pools)
+            }
+        })
+        .await?;
 
         Ok(RegisteredPools {
             fetched_block_number: 0, // Balancer V3 API doesn't support historical queries
@@ -145,7 +177,11 @@ impl BalancerApiClient {
 }
 
 /// Result of the registered pool query.
-#[derive(Debug, Default, Eq, PartialEq)]
+///
+/// This is also the format of the pool bundles that registries can get
+/// exported to and initialized from.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
 pub struct RegisteredPools {
     /// The block number that the data was fetched. Set to 0 for Balancer V3 API
     /// since it doesn't support historical queries.
@@ -164,6 +200,21 @@ impl RegisteredPools {
         }
     }
 
+    /// Returns the creation time of the most recently created pool.
+    pub fn latest_create_time(&self) -> Option<u64> {
+        self.pools.iter().map(|pool| pool.create_time).max()
+    }
+
+    /// Adds the pools that are not registered yet and returns how many pools
+    /// were added.
+    pub fn extend_new(&mut self, pools: Vec<PoolData>) -> usize {
+        let ids: HashSet<_> = self.pools.iter().map(|pool| pool.id.clone()).collect();
+        let len = self.pools.len();
+        self.pools
+            .extend(pools.into_iter().filter(|pool| !ids.contains(&pool.id)));
+        self.pools.len() - len
+    }
+
     /// Groups registered pools by factory addresses.
     pub fn group_by_factory(self) -> HashMap<H160, RegisteredPools> {
         let fetched_block_number = self.fetched_block_number;
@@ -185,7 +236,7 @@ impl RegisteredPools {
 
 /// Pool data from the Balancer V3 API.
 #[serde_as]
-#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct PoolData {
     pub id: String, // V3 uses 20-byte pool addresses as IDs
@@ -198,38 +249,102 @@ pub struct PoolData {
     pub pool_tokens: Vec<Token>,
     pub dynamic_data: DynamicData,
     pub create_time: u64,
-    #[serde(default, deserialize_with = "deserialize_optional_sbfp")]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_sbfp",
+        serialize_with = "serialize_optional_sbfp"
+    )]
     pub alpha: Option<SBfp>,
-    #[serde(default, deserialize_with = "deserialize_optional_sbfp")]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_sbfp",
+        serialize_with = "serialize_optional_sbfp"
+    )]
     pub beta: Option<SBfp>,
-    #[serde(default, deserialize_with = "deserialize_optional_sbfp")]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_sbfp",
+        serialize_with = "serialize_optional_sbfp"
+    )]
     pub c: Option<SBfp>,
-    #[serde(default, deserialize_with = "deserialize_optional_sbfp")]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_sbfp",
+        serialize_with = "serialize_optional_sbfp"
+    )]
     pub s: Option<SBfp>,
-    #[serde(default, deserialize_with = "deserialize_optional_sbfp")]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_sbfp",
+        serialize_with = "serialize_optional_sbfp"
+    )]
     pub lambda: Option<SBfp>,
-    #[serde(default, deserialize_with = "deserialize_optional_sbfp")]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_sbfp",
+        serialize_with = "serialize_optional_extended_sbfp"
+    )]
     pub tau_alpha_x: Option<SBfp>,
-    #[serde(default, deserialize_with = "deserialize_optional_sbfp")]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_sbfp",
+        serialize_with = "serialize_optional_extended_sbfp"
+    )]
     pub tau_alpha_y: Option<SBfp>,
-    #[serde(default, deserialize_with = "deserialize_optional_sbfp")]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_sbfp",
+        serialize_with = "serialize_optional_extended_sbfp"
+    )]
     pub tau_beta_x: Option<SBfp>,
-    #[serde(default, deserialize_with = "deserialize_optional_sbfp")]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_sbfp",
+        serialize_with = "serialize_optional_extended_sbfp"
+    )]
     pub tau_beta_y: Option<SBfp>,
-    #[serde(default, deserialize_with = "deserialize_optional_sbfp")]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_sbfp",
+        serialize_with = "serialize_optional_extended_sbfp"
+    )]
     pub u: Option<SBfp>,
-    #[serde(default, deserialize_with = "deserialize_optional_sbfp")]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_sbfp",
+        serialize_with = "serialize_optional_extended_sbfp"
+    )]
     pub v: Option<SBfp>,
-    #[serde(default, deserialize_with = "deserialize_optional_sbfp")]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_sbfp",
+        serialize_with = "serialize_optional_extended_sbfp"
+    )]
     pub w: Option<SBfp>,
-    #[serde(default, deserialize_with = "deserialize_optional_sbfp")]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_sbfp",
+        serialize_with = "serialize_optional_extended_sbfp"
+    )]
     pub z: Option<SBfp>,
-    #[serde(default, deserialize_with = "deserialize_optional_sbfp")]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_sbfp",
+        serialize_with = "serialize_optional_extended_sbfp"
+    )]
     pub d_sq: Option<SBfp>,
     /// Gyro 2-CLP-specific parameters
-    #[serde(default, deserialize_with = "deserialize_optional_sbfp")]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_sbfp",
+        serialize_with = "serialize_optional_sbfp"
+    )]
     pub sqrt_alpha: Option<SBfp>,
-    #[serde(default, deserialize_with = "deserialize_optional_sbfp")]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_sbfp",
+        serialize_with = "serialize_optional_sbfp"
+    )]
     pub sqrt_beta: Option<SBfp>,
     /// QuantAMM-specific parameters
     #[serde(default)]
@@ -240,7 +355,7 @@ pub struct PoolData {
 }
 
 #[serde_as]
-#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct QuantAmmWeightedParams {
     #[serde_as(as = "Option<DisplayFromStr>")]
@@ -250,7 +365,7 @@ pub struct QuantAmmWeightedParams {
 
 /// Hook configuration that matches the GraphQL response structure.
 #[serde_as]
-#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct HookConfig {
     pub address: H160,
@@ -260,7 +375,7 @@ pub struct HookConfig {
 
 /// Hook parameters for different hook types.
 #[serde_as]
-#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct HookParams {
     /// StableSurge hook parameters
@@ -273,7 +388,7 @@ pub struct HookParams {
 }
 
 /// Dynamic data for pools from Balancer V3 API.
-#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct DynamicData {
     pub swap_enabled: bool,
@@ -281,7 +396,7 @@ pub struct DynamicData {
 
 /// Token data for pools.
 #[serde_as]
-#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq)]
 pub struct Token {
     pub address: H160,
     pub decimals: u8,
@@ -293,6 +408,10 @@ pub struct Token {
 }
 
 /// Supported pool kinds for V3.
+///
+/// Gyroscope 3-CLP pools only exist on Balancer V2: Gyroscope didn't deploy
+/// a 3-CLP pool factory for Balancer V3, and the API reports the V3 2-CLP
+/// pools as `GYRO`.
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Hash)]
 pub enum PoolType {
     Weighted,         // BalancerV3WeightedPoolFactory
@@ -303,6 +422,16 @@ pub enum PoolType {
     QuantAmmWeighted, // BalancerV3QuantAMMWeightedPoolFactory
 }
 
+impl ApiPool for PoolData {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn create_time(&self) -> u64 {
+        self.create_time
+    }
+}
+
 impl PoolData {
     /// Converts the API pool type string to our internal enum.
     pub fn pool_type_enum(&self) -> PoolType {
@@ -355,14 +484,12 @@ mod pools_query {
     pub const QUERY: &str = r#"
         query aggregatorPools(
             $first: Int,
-            $skip: Int,
             $orderBy: GqlPoolOrderBy,
             $orderDirection: GqlPoolOrderDirection,
             $where: GqlAggregatorPoolFilter
         ) {
             aggregatorPools(
                 first: $first
-                skip: $skip
                 orderBy: $orderBy
                 orderDirection: $orderDirection
                 where: $where
@@ -43,7 +43,8 @@ pub mod pools;
 pub mod swap;
 
 pub use self::{
-    graph_api::GqlChain,
+    graph_api::{BalancerApiClient, GqlChain, RegisteredPools},
     pool_fetching::{BalancerFactoryKind, BalancerPoolFetcher, BalancerV3PoolFetching},
+    pool_init::PoolInitializing,
     pools::{Pool, PoolKind},
 };
@@ -19,7 +19,7 @@ use {
             Pool,
             PoolIndexing,
             PoolKind,
-            common::{self, PoolInfoFetcher},
+            common::{self, PoolInfoFetcher, PoolInfoFetching},
             gyro_2clp,
             gyro_e,
             quantamm,
@@ -32,7 +32,14 @@ use {
     },
     crate::{
         ethrpc::{Web3, Web3Transport},
+        event_handling::Backfill,
+        multicall::Multicall,
         recent_block_cache::{Block, CacheConfig},
+        sources::{
+            balancer_snapshots::{Snapshot, SnapshotStore, Snapshotter},
+            chain_profile::ChainProfile,
+            rate_provider_cache::RateProviderCache,
+        },
         token_info::TokenInfoFetching,
     },
     anyhow::{Context, Result},
@@ -41,6 +48,7 @@ use {
         BalancerV3Gyro2CLPPoolFactory,
         BalancerV3GyroECLPPoolFactory,
         BalancerV3QuantAMMWeightedPoolFactory,
+        BalancerV3ReClammPoolFactory,
         BalancerV3ReClammPoolFactoryV2,
         BalancerV3StablePoolFactory,
         BalancerV3StablePoolFactoryV2,
@@ -51,11 +59,15 @@ use {
         alloy::{BalancerV3BatchRouter, InstanceExt},
     },
     ethcontract::{BlockId, H160, H256, I256, Instance, U256, dyns::DynInstance},
-    ethrpc::block_stream::{BlockRetrieving, CurrentBlockWatcher},
+    ethrpc::{
+        alloy::conversions::IntoAlloy,
+        block_stream::{BlockRetrieving, CurrentBlockWatcher},
+    },
+    futures::future,
     model::TokenPair,
     reqwest::{Client, Url},
     std::{
-        collections::{BTreeMap, HashSet},
+        collections::{BTreeMap, HashMap, HashSet},
         sync::Arc,
     },
 };
@@ -63,7 +75,11 @@ pub use {
     common::TokenState,
     gyro_2clp::Version as Gyro2CLPPoolVersion,
     gyro_e::Version as GyroEPoolVersion,
-    quantamm::{TokenState as QuantAmmTokenState, Version as QuantAmmPoolVersion},
+    quantamm::{
+        Strategy as QuantAmmStrategy,
+        TokenState as QuantAmmTokenState,
+        Version as QuantAmmPoolVersion,
+    },
     reclamm::Version as ReClammPoolVersion,
     stable::{
         AmplificationParameter,
@@ -211,6 +227,7 @@ pub struct ReClammPool {
     pub end_fourth_root_price_ratio: Bfp,
     pub price_ratio_update_start_time: u64,
     pub price_ratio_update_end_time: u64,
+    pub current_timestamp: u64,
 }
 
 impl ReClammPool {
@@ -232,6 +249,7 @@ impl ReClammPool {
             end_fourth_root_price_ratio: reclamm_state.end_fourth_root_price_ratio,
             price_ratio_update_start_time: reclamm_state.price_ratio_update_start_time,
             price_ratio_update_end_time: reclamm_state.price_ratio_update_end_time,
+            current_timestamp: reclamm_state.current_timestamp,
         }
     }
 }
@@ -243,6 +261,7 @@ pub struct QuantAmmPool {
     pub version: QuantAmmPoolVersion,
     // QuantAMM-specific static data
     pub max_trade_size_ratio: Bfp,
+    pub strategy: QuantAmmStrategy,
     // QuantAMM-specific dynamic data
     pub first_four_weights_and_multipliers: Vec<I256>,
     pub second_four_weights_and_multipliers: Vec<I256>,
@@ -263,6 +282,7 @@ impl QuantAmmPool {
             reserves: quantamm_state.tokens.into_iter().collect(),
             version: quantamm_state.version,
             max_trade_size_ratio: quantamm_state.max_trade_size_ratio,
+            strategy: quantamm_state.strategy,
             first_four_weights_and_multipliers: quantamm_state.first_four_weights_and_multipliers,
             second_four_weights_and_multipliers: quantamm_state.second_four_weights_and_multipliers,
             last_update_time: quantamm_state.last_update_time,
@@ -401,6 +421,7 @@ pub enum BalancerFactoryKind {
     StableSurgeV2,
     Gyro2CLP,
     GyroE,
+    ReClammV1,
     ReClamm,
     QuantAmm,
 }
@@ -408,90 +429,9 @@ pub enum BalancerFactoryKind {
 impl BalancerFactoryKind {
     /// Returns a vector with supported factories for the specified chain ID.
     pub fn for_chain(chain_id: u64) -> Vec<Self> {
-        match chain_id {
-            // Mainnet
-            1 => vec![
-                Self::Weighted,
-                Self::Stable,
-                Self::StableV2,
-                Self::StableSurge,
-                Self::StableSurgeV2,
-                Self::Gyro2CLP,
-                Self::GyroE,
-                Self::ReClamm,
-                Self::QuantAmm,
-            ],
-            // Gnosis
-            100 => vec![
-                Self::Weighted,
-                Self::Stable,
-                Self::StableV2,
-                Self::StableSurge,
-                Self::StableSurgeV2,
-                Self::Gyro2CLP,
-                Self::GyroE,
-                Self::ReClamm,
-            ],
-            // Arbitrum
-            42161 => vec![
-                Self::Weighted,
-                Self::Stable,
-                Self::StableV2,
-                Self::StableSurge,
-                Self::StableSurgeV2,
-                Self::Gyro2CLP,
-                Self::GyroE,
-                Self::ReClamm,
-                Self::QuantAmm,
-            ],
-            // Optimism
-            10 => vec![
-                Self::Weighted,
-                Self::Stable,
-                Self::StableV2,
-                Self::StableSurge,
-                Self::StableSurgeV2,
-                Self::Gyro2CLP,
-                Self::GyroE,
-                Self::ReClamm,
-            ],
-            // Base
-            8453 => vec![
-                Self::Weighted,
-                Self::Stable,
-                Self::StableV2,
-                Self::StableSurge,
-                Self::StableSurgeV2,
-                Self::Gyro2CLP,
-                Self::GyroE,
-                Self::ReClamm,
-                Self::QuantAmm,
-            ],
-            // Sepolia
-            11155111 => vec![
-                Self::Weighted,
-                Self::Stable,
-                Self::StableV2,
-                Self::StableSurge,
-                Self::StableSurgeV2,
-                Self::Gyro2CLP,
-                Self::GyroE,
-                Self::ReClamm,
-                Self::QuantAmm,
-            ],
-            // Avalanche
-            43114 => vec![
-                Self::Weighted,
-                Self::Stable,
-                Self::StableV2,
-                Self::StableSurge,
-                Self::StableSurgeV2,
-                Self::Gyro2CLP,
-                Self::GyroE,
-                Self::ReClamm,
-            ],
-            _ => Default::default(),
-        }
+        ChainProfile::for_chain(chain_id)
+            .map(|profile| profile.balancer_v3_factories.to_vec())
+            .unwrap_or_default()
     }
 }
 
@@ -500,43 +440,93 @@ pub struct BalancerContracts {
     pub vault: BalancerV3Vault,
     pub batch_router: BalancerV3BatchRouter::Instance,
     pub factories: Vec<(BalancerFactoryKind, DynInstance)>,
+    /// Batches the vault calls fetching the pool states into Multicall3
+    /// calls. Without it, every pool state is fetched with separate calls.
+    pub multicall: Option<Multicall>,
+    /// Caches the rates of the rate providers of the pool tokens instead of
+    /// fetching them from the vault for every pool state.
+    pub rate_providers: Option<RateProviderCache>,
+}
+
+/// Raw Balancer V3 contract addresses used instead of the deployment metadata
+/// of the `contracts` crate. This allows indexing chains that are not (yet)
+/// listed there, e.g. testnets or freshly deployed L2s.
+#[derive(Clone, Debug, Default)]
+pub struct BalancerContractAddresses {
+    pub vault: Option<H160>,
+    pub batch_router: Option<H160>,
+    pub factories: HashMap<BalancerFactoryKind, H160>,
 }
 
 impl BalancerContracts {
-    pub async fn try_new(web3: &Web3, factory_kinds: Vec<BalancerFactoryKind>) -> Result<Self> {
+    /// Creates the contract instances for the specified factory kinds. Every
+    /// contract without a configured address in `addresses` is looked up in the
+    /// deployment metadata for the connected chain.
+    pub async fn try_new(
+        web3: &Web3,
+        factory_kinds: Vec<BalancerFactoryKind>,
+        addresses: &BalancerContractAddresses,
+    ) -> Result<Self> {
         let web3 = ethrpc::instrumented::instrument_with_label(web3, "balancerV3".into());
-        let vault = BalancerV3Vault::deployed(&web3)
-            .await
-            .context("Cannot retrieve balancer V3 vault")?;
-        let batch_router = BalancerV3BatchRouter::Instance::deployed(&web3.alloy)
-            .await
-            .context("Cannot retrieve balancer V3 batch router")?;
+        let vault = match addresses.vault {
+            Some(address) => BalancerV3Vault::at(&web3, address),
+            None => BalancerV3Vault::deployed(&web3)
+                .await
+                .context("Cannot retrieve balancer V3 vault")?,
+        };
+        let batch_router = match addresses.batch_router {
+            Some(address) => {
+                BalancerV3BatchRouter::Instance::new(address.into_alloy(), web3.alloy.clone())
+            }
+            None => BalancerV3BatchRouter::Instance::deployed(&web3.alloy)
+                .await
+                .context("Cannot retrieve balancer V3 batch router")?,
+        };
 
         macro_rules! instance {
-            ($factory:ident) => {{
-                $factory::deployed(&web3)
-                    .await
-                    .context(format!(
+            ($factory:ident, $kind:expr) => {{
+                let factory = match addresses.factories.get(&$kind) {
+                    Some(&address) => $factory::at(&web3, address),
+                    None => $factory::deployed(&web3).await.context(format!(
                         "Cannot retrieve Balancer V3 factory {}",
                         stringify!($factory)
-                    ))?
-                    .raw_instance()
-                    .clone()
+                    ))?,
+                };
+                factory.raw_instance().clone()
             }};
         }
 
         let mut factories = Vec::new();
         for factory_kind in factory_kinds {
             let factory_instance = match factory_kind {
-                BalancerFactoryKind::Weighted => instance!(BalancerV3WeightedPoolFactory),
-                BalancerFactoryKind::Stable => instance!(BalancerV3StablePoolFactory),
-                BalancerFactoryKind::StableV2 => instance!(BalancerV3StablePoolFactoryV2),
-                BalancerFactoryKind::StableSurge => instance!(BalancerV3StableSurgePoolFactory),
-                BalancerFactoryKind::StableSurgeV2 => instance!(BalancerV3StableSurgePoolFactoryV2),
-                BalancerFactoryKind::Gyro2CLP => instance!(BalancerV3Gyro2CLPPoolFactory),
-                BalancerFactoryKind::GyroE => instance!(BalancerV3GyroECLPPoolFactory),
-                BalancerFactoryKind::ReClamm => instance!(BalancerV3ReClammPoolFactoryV2),
-                BalancerFactoryKind::QuantAmm => instance!(BalancerV3QuantAMMWeightedPoolFactory),
+                BalancerFactoryKind::Weighted => {
+                    instance!(BalancerV3WeightedPoolFactory, factory_kind)
+                }
+                BalancerFactoryKind::Stable => instance!(BalancerV3StablePoolFactory, factory_kind),
+                BalancerFactoryKind::StableV2 => {
+                    instance!(BalancerV3StablePoolFactoryV2, factory_kind)
+                }
+                BalancerFactoryKind::StableSurge => {
+                    instance!(BalancerV3StableSurgePoolFactory, factory_kind)
+                }
+                BalancerFactoryKind::StableSurgeV2 => {
+                    instance!(BalancerV3StableSurgePoolFactoryV2, factory_kind)
+                }
+                BalancerFactoryKind::Gyro2CLP => {
+                    instance!(BalancerV3Gyro2CLPPoolFactory, factory_kind)
+                }
+                BalancerFactoryKind::GyroE => {
+                    instance!(BalancerV3GyroECLPPoolFactory, factory_kind)
+                }
+                BalancerFactoryKind::ReClammV1 => {
+                    instance!(BalancerV3ReClammPoolFactory, factory_kind)
+                }
+                BalancerFactoryKind::ReClamm => {
+                    instance!(BalancerV3ReClammPoolFactoryV2, factory_kind)
+                }
+                BalancerFactoryKind::QuantAmm => {
+                    instance!(BalancerV3QuantAMMWeightedPoolFactory, factory_kind)
+                }
             };
             factories.push((factory_kind, factory_instance));
         }
@@ -545,6 +535,8 @@ impl BalancerContracts {
             vault,
             batch_router,
             factories,
+            multicall: None,
+            rate_providers: None,
         })
     }
 }
@@ -564,6 +556,39 @@ impl BalancerPoolFetcher {
         chain: GqlChain,
     ) -> Result<Self> {
         let pool_initializer = BalancerApiClient::from_subgraph_url(subgraph_url, client, chain)?;
+        Self::with_initializer(
+            pool_initializer,
+            block_retriever,
+            token_infos,
+            config,
+            block_stream,
+            web3,
+            contracts,
+            deny_listed_pool_ids,
+            None,
+            Default::default(),
+        )
+        .await
+    }
+
+    /// Creates a fetcher whose registries get initialized by the specified
+    /// initializer instead of the Balancer API, e.g. from an exported pool
+    /// bundle. With a snapshot store, the registries resume from their
+    /// snapshots if there are any, and save snapshots as they index events.
+    /// The events of the history are fetched within the backfill limits.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn with_initializer(
+        pool_initializer: impl PoolInitializing,
+        block_retriever: Arc<dyn BlockRetrieving>,
+        token_infos: Arc<dyn TokenInfoFetching>,
+        config: CacheConfig,
+        block_stream: CurrentBlockWatcher,
+        web3: Web3,
+        contracts: &BalancerContracts,
+        deny_listed_pool_ids: Vec<H160>,
+        snapshots: Option<Arc<SnapshotStore>>,
+        backfill: Backfill,
+    ) -> Result<Self> {
         let web3 = ethrpc::instrumented::instrument_with_label(&web3, "balancerV3".into());
         let fetcher = Arc::new(Cache::new(
             create_aggregate_pool_fetcher(
@@ -572,6 +597,8 @@ impl BalancerPoolFetcher {
                 block_retriever,
                 token_infos,
                 contracts,
+                snapshots,
+                backfill,
             )
             .await?,
             config,
@@ -652,22 +679,51 @@ async fn create_aggregate_pool_fetcher(
     block_retriever: Arc<dyn BlockRetrieving>,
     token_infos: Arc<dyn TokenInfoFetching>,
     contracts: &BalancerContracts,
+    snapshots: Option<Arc<SnapshotStore>>,
+    backfill: Backfill,
 ) -> Result<Aggregate> {
-    let registered_pools = pool_initializer.initialize_pools().await?;
-    let fetched_block_number = registered_pools.fetched_block_number;
-    let fetched_block_hash = web3
-        .eth()
-        .block(BlockId::Number(fetched_block_number.into()))
-        .await?
-        .context("failed to get block by block number")?
-        .hash
-        .context("missing hash from block")?;
-    let mut registered_pools_by_factory = registered_pools.group_by_factory();
+    let factories = contracts
+        .factories
+        .iter()
+        .map(|(_, instance)| instance.address())
+        .collect::<Vec<_>>();
+    let restored = match &snapshots {
+        Some(store) => restore_snapshots(store, &factories).unwrap_or_else(|err| {
+            tracing::warn!(?err, "failed to restore Balancer V3 pool snapshots");
+            None
+        }),
+        None => None,
+    };
+    let mut initial_by_factory = match restored {
+        Some(restored) => {
+            let registered = RegisteredPools {
+                fetched_block_number: restored
+                    .values()
+                    .map(|snapshot| snapshot.registered.fetched_block_number)
+                    .min()
+                    .unwrap_or_default(),
+                pools: restored
+                    .values()
+                    .flat_map(|snapshot| snapshot.registered.pools.iter().cloned())
+                    .collect(),
+            };
+            tracing::info!(
+                pools = registered.pools.len(),
+                "resuming Balancer V3 pools from snapshots"
+            );
+            pool_initializer.restored(&registered);
+            restored
+        }
+        None => initialize_pools(&pool_initializer, &factories).await?,
+    };
 
     macro_rules! registry {
         ($factory:ident, $instance:expr_2021) => {{
             create_internal_pool_fetcher(
                 contracts.vault.clone(),
+                contracts.multicall.clone(),
+                contracts.rate_providers.clone(),
+                web3.clone(),
                 $factory::with_deployment_info(
                     &$instance.web3(),
                     $instance.address(),
@@ -676,11 +732,13 @@ async fn create_aggregate_pool_fetcher(
                 block_retriever.clone(),
                 token_infos.clone(),
                 $instance,
-                registered_pools_by_factory
+                initial_by_factory
                     .remove(&$instance.address())
-                    .unwrap_or_else(|| RegisteredPools::empty(fetched_block_number)),
-                fetched_block_hash,
-            )?
+                    .unwrap_or_default(),
+                snapshots.clone(),
+                backfill,
+            )
+            .await?
         }};
     }
 
@@ -708,6 +766,9 @@ async fn create_aggregate_pool_fetcher(
             BalancerFactoryKind::GyroE => {
                 registry!(BalancerV3GyroECLPPoolFactory, instance)
             }
+            BalancerFactoryKind::ReClammV1 => {
+                registry!(BalancerV3ReClammPoolFactory, instance)
+            }
             BalancerFactoryKind::ReClamm => {
                 registry!(BalancerV3ReClammPoolFactoryV2, instance)
             }
@@ -720,15 +781,12 @@ async fn create_aggregate_pool_fetcher(
 
     // Just to catch cases where new Balancer factories get added for a pool
     // kind, but we don't index it, log a warning for unused pools.
-    if !registered_pools_by_factory.is_empty() {
-        let total_count = registered_pools_by_factory
+    if !initial_by_factory.is_empty() {
+        let total_count = initial_by_factory
             .values()
-            .map(|registered| registered.pools.len())
+            .map(|initial| initial.registered.pools.len())
             .sum::<usize>();
-        let factories = registered_pools_by_factory
-            .keys()
-            .copied()
-            .collect::<Vec<_>>();
+        let factories = initial_by_factory.keys().copied().collect::<Vec<_>>();
         tracing::warn!(
             %total_count, ?factories,
             "found pools that don't correspond to any known Balancer V3 pool factory",
@@ -738,30 +796,123 @@ async fn create_aggregate_pool_fetcher(
     Ok(Aggregate::new(fetchers))
 }
 
-fn create_internal_pool_fetcher<Factory>(
+/// Initializes the pools of the registries with the pool initializer, as the
+/// initial snapshots of the registries by factory.
+async fn initialize_pools(
+    pool_initializer: &impl PoolInitializing,
+    factories: &[H160],
+) -> Result<HashMap<H160, Snapshot<RegisteredPools>>> {
+    let registered_pools = pool_initializer.initialize_pools().await?;
+    let fetched_block_number = registered_pools.fetched_block_number;
+    let mut registered_pools_by_factory = registered_pools.group_by_factory();
+    for factory in factories {
+        registered_pools_by_factory
+            .entry(*factory)
+            .or_insert_with(|| RegisteredPools::empty(fetched_block_number));
+    }
+
+    Ok(registered_pools_by_factory
+        .into_iter()
+        .map(|(factory, registered)| {
+            let initial = Snapshot {
+                block: fetched_block_number,
+                registered,
+                ..Default::default()
+            };
+            (factory, initial)
+        })
+        .collect())
+}
+
+/// Loads the snapshots of the registries of all the factories, or `None` if
+/// any of them is missing.
+fn restore_snapshots(
+    store: &SnapshotStore,
+    factories: &[H160],
+) -> Result<Option<HashMap<H160, Snapshot<RegisteredPools>>>> {
+    factories
+        .iter()
+        .map(|factory| {
+            let snapshot = store.load(&snapshot_key(*factory))?;
+            Ok(snapshot.map(|snapshot| (*factory, snapshot)))
+        })
+        .collect()
+}
+
+/// The key the snapshots of the registry of a factory are stored under.
+fn snapshot_key(factory: H160) -> String {
+    format!("balancer_v3/{factory:#x}")
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn create_internal_pool_fetcher<Factory>(
     vault: BalancerV3Vault,
+    multicall: Option<Multicall>,
+    rate_providers: Option<RateProviderCache>,
+    web3: Web3,
     factory: Factory,
     block_retriever: Arc<dyn BlockRetrieving>,
     token_infos: Arc<dyn TokenInfoFetching>,
     factory_instance: &Instance<Web3Transport>,
-    registered_pools: RegisteredPools,
-    fetched_block_hash: H256,
+    initial: Snapshot<RegisteredPools>,
+    snapshots: Option<Arc<SnapshotStore>>,
+    backfill: Backfill,
 ) -> Result<Box<dyn InternalPoolFetching>>
 where
     Factory: FactoryIndexing,
 {
-    let initial_pools = registered_pools
+    let fetcher = Arc::new(PoolInfoFetcher::new(
+        vault,
+        factory,
+        token_infos,
+        multicall,
+        rate_providers,
+    ));
+    let registered_pools = initial.registered;
+    let mut initial_pools = registered_pools
         .pools
         .iter()
         .map(|pool| Factory::PoolInfo::from_graph_data(pool, registered_pools.fetched_block_number))
-        .collect::<Result<_>>()?;
-    let start_sync_at_block = Some((registered_pools.fetched_block_number, fetched_block_hash));
+        .collect::<Result<Vec<_>>>()?;
+    // The pools indexed from events are not part of the registered pools, so
+    // their infos get fetched again.
+    initial_pools.extend(
+        future::try_join_all(
+            initial
+                .created
+                .iter()
+                .map(|(address, block_created)| fetcher.fetch_pool_info(*address, *block_created)),
+        )
+        .await?,
+    );
+    let start_sync_at_block = Some((initial.block, block_hash(&web3, initial.block).await?));
+    let snapshotter = snapshots.map(|store| {
+        let addresses = registered_pools
+            .pools
+            .iter()
+            .map(|pool| pool.address)
+            .collect();
+        let key = snapshot_key(factory_instance.address());
+        Snapshotter::new(store, key, registered_pools, addresses)
+    });
 
     Ok(Box::new(Registry::new(
         block_retriever,
-        Arc::new(PoolInfoFetcher::new(vault, factory, token_infos)),
+        fetcher,
         factory_instance,
         initial_pools,
         start_sync_at_block,
+        snapshotter,
+        backfill,
     )))
 }
+
+/// Returns the hash of the block with the specified number.
+async fn block_hash(web3: &Web3, number: u64) -> Result<H256> {
+    web3.eth()
+        .block(BlockId::Number(number.into()))
+        .await?
+        .context("failed to get block by block number")?
+        .hash
+        .context("missing hash from block")
+}
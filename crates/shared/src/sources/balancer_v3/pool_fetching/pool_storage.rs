@@ -18,7 +18,14 @@
 use {
     crate::{
         event_handling::EventStoring,
-        sources::balancer_v3::pools::{FactoryIndexing, PoolIndexing, common},
+        sources::{
+            balancer_pair_cache::Version,
+            balancer_snapshots::Snapshotter,
+            balancer_v3::{
+                graph_api::RegisteredPools,
+                pools::{FactoryIndexing, PoolIndexing, common},
+            },
+        },
     },
     anyhow::{Context, Result},
     contracts::balancer_v3_weighted_pool_factory::{
@@ -49,6 +56,10 @@ where
     /// The block the initial pools were fetched on. This block is considered
     /// reorg-safe and events prior to this block do not get replaced.
     initial_fetched_block: u64,
+    /// Changes whenever pools get added or removed.
+    version: Version,
+    /// Saves snapshots of the indexed pools, if configured.
+    snapshotter: Option<Snapshotter<RegisteredPools>>,
 }
 
 impl<Factory> PoolStorage<Factory>
@@ -65,6 +76,8 @@ where
                 pools_by_token: Default::default(),
                 pools: Default::default(),
                 initial_fetched_block: 0,
+                version: Default::default(),
+                snapshotter: None,
             },
             |mut storage, pool| {
                 storage.initial_fetched_block =
@@ -76,6 +89,13 @@ where
         )
     }
 
+    /// Saves snapshots of the indexed pools whenever the indexed block
+    /// advances.
+    pub fn with_snapshotter(mut self, snapshotter: Option<Snapshotter<RegisteredPools>>) -> Self {
+        self.snapshotter = snapshotter;
+        self
+    }
+
     /// Returns all pools containing both tokens from `TokenPair`
     fn pool_ids_for_token_pair(&self, token_pair: &TokenPair) -> impl Iterator<Item = H160> + '_ {
         let (token0, token1) = token_pair.get();
@@ -99,6 +119,22 @@ where
             .collect()
     }
 
+    /// Returns the pools containing each of the token pairs.
+    pub fn pool_ids_by_token_pair(
+        &self,
+        token_pairs: &HashSet<TokenPair>,
+    ) -> HashMap<TokenPair, HashSet<H160>> {
+        token_pairs
+            .iter()
+            .map(|pair| (*pair, self.pool_ids_for_token_pair(pair).collect()))
+            .collect()
+    }
+
+    /// The version of the stored pools.
+    pub fn version(&self) -> &Version {
+        &self.version
+    }
+
     /// Returns a pool by ID or none if no such pool exists.
     pub fn pool_by_id(&self, pool_id: H160) -> Option<&Factory::PoolInfo> {
         self.pools.get(&pool_id)
@@ -134,6 +170,7 @@ where
             .fetch_pool_info(pool_creation.pool, block_created)
             .await?;
         self.insert_pool(pool);
+        self.version.bump();
 
         Ok(())
     }
@@ -165,6 +202,7 @@ where
         for pool_set in self.pools_by_token.values_mut() {
             pool_set.retain(|pool_id| self.pools.contains_key(pool_id));
         }
+        self.version.bump();
     }
 
     pub fn last_event_block(&self) -> u64 {
@@ -216,8 +254,15 @@ where
         Ok(self.last_event_block())
     }
 
-    async fn persist_last_indexed_block(&mut self, _block: u64) -> Result<()> {
-        Ok(())
+    async fn persist_last_indexed_block(&mut self, block: u64) -> Result<()> {
+        let Some(snapshotter) = &mut self.snapshotter else {
+            return Ok(());
+        };
+        let pools = self
+            .pools
+            .values()
+            .map(|pool| (pool.common().address, pool.common().block_created));
+        snapshotter.save(block, pools, &Default::default())
     }
 }
 
@@ -4,14 +4,16 @@
 use {
     super::{internal::InternalPoolFetching, pool_storage::PoolStorage},
     crate::{
-        event_handling::{EthcontractEventRetrieving, EventHandler},
+        event_handling::{Backfill, EthcontractEventRetrieving, EventHandler},
         maintenance::Maintaining,
         recent_block_cache::Block,
-        sources::balancer_v3::pools::{
-            FactoryIndexing,
-            Pool,
-            PoolStatus,
-            common::PoolInfoFetching,
+        sources::{
+            balancer_pair_cache::PairCache,
+            balancer_snapshots::Snapshotter,
+            balancer_v3::{
+                graph_api::RegisteredPools,
+                pools::{FactoryIndexing, Pool, PoolStatus, common::PoolInfoFetching},
+            },
         },
     },
     anyhow::Result,
@@ -69,27 +71,41 @@ where
 {
     fetcher: Arc<dyn PoolInfoFetching<Factory>>,
     updater: PoolUpdater<Factory>,
+    /// The pools of recently requested token pairs.
+    pair_cache: PairCache<H160>,
 }
 
 impl<Factory> Registry<Factory>
 where
     Factory: FactoryIndexing,
 {
-    /// Returns a new pool registry for the specified factory.
+    /// Returns a new pool registry for the specified factory, saving
+    /// snapshots of its pools with the snapshotter if there is one. The events
+    /// of the history are fetched within the backfill limits.
     pub fn new(
         block_retreiver: Arc<dyn BlockRetrieving>,
         fetcher: Arc<dyn PoolInfoFetching<Factory>>,
         factory_instance: &Instance<Web3Transport>,
         initial_pools: Vec<Factory::PoolInfo>,
         start_sync_at_block: Option<BlockNumberHash>,
+        snapshotter: Option<Snapshotter<RegisteredPools>>,
+        backfill: Backfill,
     ) -> Self {
-        let updater = Mutex::new(EventHandler::new(
+        let storage =
+            PoolStorage::new(initial_pools, fetcher.clone()).with_snapshotter(snapshotter);
+        let pair_cache = PairCache::new(storage.version().clone());
+        let updater = EventHandler::new(
             block_retreiver,
             BasePoolFactoryContract(base_pool_factory(factory_instance)),
-            PoolStorage::new(initial_pools, fetcher.clone()),
+            storage,
             start_sync_at_block,
-        ));
-        Self { fetcher, updater }
+        )
+        .with_backfill(backfill);
+        Self {
+            fetcher,
+            updater: Mutex::new(updater),
+            pair_cache,
+        }
     }
 }
 
@@ -99,11 +115,21 @@ where
     Factory: FactoryIndexing,
 {
     async fn pool_ids_for_token_pairs(&self, token_pairs: HashSet<TokenPair>) -> HashSet<H160> {
-        self.updater
-            .lock()
-            .await
-            .store()
-            .pool_ids_for_token_pairs(&token_pairs)
+        if let Some(pool_ids) = self.pair_cache.get(&token_pairs) {
+            return pool_ids;
+        }
+
+        let (version, pool_ids) = {
+            let updater = self.updater.lock().await;
+            let storage = updater.store();
+            (
+                storage.version().get(),
+                storage.pool_ids_by_token_pair(&token_pairs),
+            )
+        };
+        let result = pool_ids.values().flatten().copied().collect();
+        self.pair_cache.insert(version, pool_ids);
+        result
     }
 
     async fn pools_by_id(&self, pool_ids: HashSet<H160>, block: Block) -> Result<Vec<Pool>> {
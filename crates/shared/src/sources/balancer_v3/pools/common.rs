@@ -3,16 +3,21 @@
 use {
     super::{FactoryIndexing, Pool, PoolIndexing as _, PoolStatus},
     crate::{
-        sources::balancer_v3::{
-            graph_api::{PoolData, PoolType},
-            swap::fixed_point::Bfp,
+        multicall::Multicall,
+        sources::{
+            balancer_v3::{
+                graph_api::{PoolData, PoolType},
+                swap::fixed_point::Bfp,
+            },
+            rate_provider_cache::RateProviderCache,
         },
         token_info::TokenInfoFetching,
     },
     anyhow::{Context, Result, anyhow, ensure},
-    contracts::BalancerV3Vault,
+    contracts::{BalancerV3Vault, alloy::BalancerV3Vault::BalancerV3Vault as VaultCalls},
     ethcontract::{BlockId, H160, U256},
-    futures::{FutureExt as _, future::BoxFuture},
+    ethrpc::alloy::conversions::{IntoAlloy, IntoLegacy},
+    futures::{FutureExt as _, TryFutureExt as _, future::BoxFuture},
     std::{collections::BTreeMap, future::Future, sync::Arc},
     tokio::sync::oneshot,
 };
@@ -43,6 +48,10 @@ pub struct PoolInfoFetcher<Factory> {
     vault: BalancerV3Vault,
     factory: Factory,
     token_infos: Arc<dyn TokenInfoFetching>,
+    /// Batches the vault calls fetching the pool states, if set.
+    multicall: Option<Multicall>,
+    /// Serves the token rates from cached rate provider rates, if set.
+    rate_providers: Option<RateProviderCache>,
 }
 
 impl<Factory> PoolInfoFetcher<Factory> {
@@ -50,11 +59,15 @@ impl<Factory> PoolInfoFetcher<Factory> {
         vault: BalancerV3Vault,
         factory: Factory,
         token_infos: Arc<dyn TokenInfoFetching>,
+        multicall: Option<Multicall>,
+        rate_providers: Option<RateProviderCache>,
     ) -> Self {
         Self {
             vault,
             factory,
             token_infos,
+            multicall,
+            rate_providers,
         }
     }
 
@@ -106,29 +119,154 @@ impl<Factory> PoolInfoFetcher<Factory> {
         })
     }
 
-    fn fetch_common_pool_state(
+    /// Fetches the token rates from the rate provider cache, if it is set.
+    /// Tokens without a rate provider have a rate of 1, just like the vault
+    /// reports for them.
+    fn fetch_cached_token_rates(
         &self,
         pool: &PoolInfo,
         block: BlockId,
-    ) -> BoxFuture<'static, Result<PoolState>> {
+    ) -> Option<BoxFuture<'static, Result<Vec<U256>>>> {
+        let cache = self.rate_providers.clone()?;
+        let rate_providers = pool.rate_providers.clone();
+        Some(
+            async move {
+                futures::future::try_join_all(rate_providers.into_iter().map(|provider| {
+                    let cache = cache.clone();
+                    async move {
+                        if provider.is_zero() {
+                            return Ok(U256::exp10(18));
+                        }
+                        cache.get_rate(provider, block).await
+                    }
+                }))
+                .await
+            }
+            .boxed(),
+        )
+    }
+
+    /// Fetches the state the vault keeps for the pool. With batching enabled,
+    /// the calls of all pools fetched together get aggregated into Multicall3
+    /// calls instead of being made one by one.
+    fn fetch_vault_state(
+        &self,
+        pool: &PoolInfo,
+        block: BlockId,
+    ) -> BoxFuture<'static, Result<VaultState>> {
+        let cached_token_rates = self.fetch_cached_token_rates(pool, block);
+        let pool = pool.address;
+        if let Some(multicall) = &self.multicall {
+            let vault = self.vault.address().into_alloy();
+            let pool = pool.into_alloy();
+            let block = block.into_alloy();
+            let fetch_paused = multicall.call(vault, VaultCalls::isPoolPausedCall { pool }, block);
+            let fetch_swap_fee = multicall.call(
+                vault,
+                VaultCalls::getStaticSwapFeePercentageCall { pool },
+                block,
+            );
+            let fetch_pool_data =
+                multicall.call(vault, VaultCalls::getPoolDataCall { pool }, block);
+            let fetch_token_rates = cached_token_rates.unwrap_or_else(|| {
+                multicall
+                    .call(vault, VaultCalls::getPoolTokenRatesCall { pool }, block)
+                    .map_ok(|rates| {
+                        rates
+                            .tokenRates
+                            .into_iter()
+                            .map(IntoLegacy::into_legacy)
+                            .collect::<Vec<_>>()
+                    })
+                    .boxed()
+            });
+
+            return async move {
+                let (paused, swap_fee, pool_data, token_rates) = futures::try_join!(
+                    fetch_paused,
+                    fetch_swap_fee,
+                    fetch_pool_data,
+                    fetch_token_rates
+                )?;
+                Ok(VaultState {
+                    paused,
+                    swap_fee: swap_fee.into_legacy(),
+                    pool_config_bits: pool_data.poolConfigBits.into_underlying().0,
+                    tokens: pool_data
+                        .tokens
+                        .into_iter()
+                        .map(IntoLegacy::into_legacy)
+                        .collect(),
+                    balances: pool_data
+                        .balancesRaw
+                        .into_iter()
+                        .map(IntoLegacy::into_legacy)
+                        .collect(),
+                    decimal_scaling_factors: pool_data
+                        .decimalScalingFactors
+                        .into_iter()
+                        .map(IntoLegacy::into_legacy)
+                        .collect(),
+                    token_rates,
+                })
+            }
+            .boxed();
+        }
+
         // Use V3 Vault isPoolPaused to get the paused status
-        let fetch_paused = self.vault.is_pool_paused(pool.address).block(block).call();
+        let fetch_paused = self.vault.is_pool_paused(pool).block(block).call();
 
         // Use V3 Vault getStaticSwapFeePercentage to get the swap fee
         let fetch_swap_fee = self
             .vault
-            .get_static_swap_fee_percentage(pool.address)
+            .get_static_swap_fee_percentage(pool)
             .block(block)
             .call();
 
         // Use V3 Vault getPoolData to get the pool data
-        let fetch_pool_data = self.vault.get_pool_data(pool.address).block(block).call();
+        let fetch_pool_data = self.vault.get_pool_data(pool).block(block).call();
+
+        let fetch_token_rates = cached_token_rates.unwrap_or_else(|| {
+            self.vault
+                .get_pool_token_rates(pool)
+                .block(block)
+                .call()
+                .map_ok(|(_, token_rates)| token_rates)
+                .err_into()
+                .boxed()
+        });
 
-        let fetch_token_rates = self
-            .vault
-            .get_pool_token_rates(pool.address)
-            .block(block)
-            .call();
+        async move {
+            let (paused, swap_fee, pool_data, token_rates) = futures::try_join!(
+                fetch_paused.err_into(),
+                fetch_swap_fee.err_into(),
+                fetch_pool_data.err_into(),
+                fetch_token_rates
+            )?;
+
+            // Pool Data: (pool_config_bits, tokens, token_infos, balances_raw,
+            // balances_live_scaled18, token_rates, decimal_scaling_factors)
+            let (pool_config_bits, tokens, _, balances, _, _, decimal_scaling_factors) = pool_data;
+
+            Ok(VaultState {
+                paused,
+                swap_fee,
+                pool_config_bits: pool_config_bits.0,
+                tokens,
+                balances,
+                decimal_scaling_factors,
+                token_rates,
+            })
+        }
+        .boxed()
+    }
+
+    fn fetch_common_pool_state(
+        &self,
+        pool: &PoolInfo,
+        block: BlockId,
+    ) -> BoxFuture<'static, Result<PoolState>> {
+        let fetch_vault_state = self.fetch_vault_state(pool, block);
 
         // Because of a `mockall` limitation, we **need** the future returned
         // here to be `'static`. This requires us to clone and move `pool` into
@@ -137,23 +275,19 @@ impl<Factory> PoolInfoFetcher<Factory> {
         let pool = pool.clone();
 
         async move {
-            // Get the paused status, swap fee, and pool data
-            let (paused, swap_fee, pool_data, token_rates) = futures::try_join!(
-                fetch_paused,
-                fetch_swap_fee,
-                fetch_pool_data,
-                fetch_token_rates
-            )?;
+            let VaultState {
+                paused,
+                swap_fee,
+                pool_config_bits,
+                tokens,
+                balances,
+                decimal_scaling_factors,
+                token_rates,
+            } = fetch_vault_state.await?;
 
             // Convert the swap fee to a Bfp
             let swap_fee = Bfp::from_wei(swap_fee);
 
-            // Pool Data: (pool_config_bits, tokens, token_infos, balances_raw,
-            // balances_live_scaled18, token_rates, decimal_scaling_factors)
-            let (_, tokens, _, balances, _, _, _) = pool_data;
-
-            let (_, token_rates) = token_rates;
-
             // Ensure the number of balances matches the number of tokens
             ensure!(
                 pool.tokens.len() == tokens.len(),
@@ -175,6 +309,27 @@ impl<Factory> PoolInfoFetcher<Factory> {
                     .map(|(&addr, &sf)| (addr, sf))
                     .collect();
 
+            // The scaling factors are derived from the token decimals reported
+            // by the token info fetcher. Warn if they disagree with the ones the
+            // vault uses, as swaps would be priced incorrectly.
+            for (address, &decimal_scaling_factor) in
+                itertools::izip!(&tokens, &decimal_scaling_factors)
+            {
+                let Some(&scaling_factor) = scaling_by_addr.get(address) else {
+                    continue;
+                };
+                if !matches_decimal_scaling_factor(scaling_factor, decimal_scaling_factor) {
+                    tracing::warn!(
+                        pool = ?pool.address,
+                        token = ?address,
+                        ?scaling_factor,
+                        %decimal_scaling_factor,
+                        "scaling factor does not match the vault decimal scaling factor; \
+                         consider overriding the token decimals",
+                    );
+                }
+            }
+
             let tokens = itertools::izip!(&tokens, balances, token_rates)
                 .map(|(&address, balance, rate)| {
                     let scaling_factor = *scaling_by_addr
@@ -193,6 +348,7 @@ impl<Factory> PoolInfoFetcher<Factory> {
 
             Ok(PoolState {
                 paused,
+                recovery_mode: is_recovery_mode(&pool_config_bits),
                 swap_fee,
                 tokens,
             })
@@ -234,6 +390,9 @@ where
             if common_pool_state.paused {
                 return Ok(PoolStatus::Paused);
             }
+            if common_pool_state.recovery_mode {
+                return Ok(PoolStatus::RecoveryMode);
+            }
             let pool_state = match pool_state.await? {
                 Some(state) => state,
                 None => return Ok(PoolStatus::Disabled),
@@ -248,6 +407,17 @@ where
     }
 }
 
+/// The state the vault keeps for a pool.
+struct VaultState {
+    paused: bool,
+    swap_fee: U256,
+    pool_config_bits: [u8; 32],
+    tokens: Vec<H160>,
+    balances: Vec<U256>,
+    decimal_scaling_factors: Vec<U256>,
+    token_rates: Vec<U256>,
+}
+
 /// Common pool data shared across all Balancer V3 pools.
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub struct PoolInfo {
@@ -299,6 +469,10 @@ impl PoolInfo {
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub struct PoolState {
     pub paused: bool,
+    /// Whether the pool is in recovery mode, in which case the vault stops
+    /// charging yield and protocol fees and the live balances it swaps against
+    /// diverge from the ones used for quoting.
+    pub recovery_mode: bool,
     pub swap_fee: Bfp,
     pub tokens: BTreeMap<H160, TokenState>,
 }
@@ -330,11 +504,31 @@ pub fn compute_scaling_rate(scaling_factor: Bfp) -> Result<U256> {
         .context("unsupported scaling factor of 0")
 }
 
+/// Offset of the recovery mode flag in the vault's `PoolConfigBits`.
+const POOL_RECOVERY_MODE_OFFSET: usize = 3;
+
+/// Returns whether the recovery mode flag is set in the pool config bits
+/// returned by the vault's `getPoolData`.
+///
+/// The config bits are a big-endian `bytes32`, so the flag lives in the last
+/// byte.
+pub fn is_recovery_mode(pool_config_bits: &[u8; 32]) -> bool {
+    pool_config_bits[31] & (1 << POOL_RECOVERY_MODE_OFFSET) != 0
+}
+
 /// Converts a token decimal count to its corresponding scaling factor.
 pub fn scaling_factor_from_decimals(decimals: u8) -> Result<Bfp> {
     Ok(Bfp::exp10(scaling_exponent_from_decimals(decimals)? as _))
 }
 
+/// Returns whether a scaling factor equals the raw `10^(18 - decimals)` decimal
+/// scaling factor the vault reports for a token.
+pub fn matches_decimal_scaling_factor(scaling_factor: Bfp, decimal_scaling_factor: U256) -> bool {
+    decimal_scaling_factor
+        .checked_mul(U256::exp10(18))
+        .is_some_and(|scaled| scaled == scaling_factor.as_uint256())
+}
+
 /// Converts a token decimal count to its corresponding scaling exponent.
 pub fn scaling_exponent_from_decimals(decimals: u8) -> Result<u8> {
     // Technically this should never fail for Balancer Pools since tokens
@@ -439,6 +633,8 @@ mod tests {
             vault: BalancerV3Vault::at(&web3, vault.address()),
             factory: MockFactoryIndexing::new(),
             token_infos: Arc::new(token_infos),
+            multicall: None,
+            rate_providers: None,
         };
         let pool_info = pool_info_fetcher
             .fetch_common_pool_info(pool.address(), 1337)
@@ -505,6 +701,8 @@ mod tests {
             vault: BalancerV3Vault::at(&web3, vault.address()),
             factory: MockFactoryIndexing::new(),
             token_infos: Arc::new(token_infos),
+            multicall: None,
+            rate_providers: None,
         };
         let pool_info = PoolInfo {
             id: mock_pool.address(),
@@ -527,6 +725,7 @@ mod tests {
             pool_state,
             PoolState {
                 paused: false,
+                recovery_mode: false,
                 swap_fee: bfp_v3!("0.003"),
                 tokens: btreemap! {
                     tokens[0] => TokenState {
@@ -593,6 +792,8 @@ mod tests {
             vault: BalancerV3Vault::at(&web3, vault.address()),
             factory: MockFactoryIndexing::new(),
             token_infos: Arc::new(token_infos),
+            multicall: None,
+            rate_providers: None,
         };
         let pool_info = PoolInfo {
             id: mock_pool.address(),
@@ -687,6 +888,8 @@ mod tests {
             vault: BalancerV3Vault::at(&web3, vault.address()),
             factory: mock_factory,
             token_infos: Arc::new(token_infos),
+            multicall: None,
+            rate_providers: None,
         };
         let pool_info = weighted::PoolInfo {
             common: PoolInfo {
@@ -801,6 +1004,8 @@ mod tests {
             vault: BalancerV3Vault::at(&web3, vault.address()),
             factory: mock_factory,
             token_infos: Arc::new(token_infos),
+            multicall: None,
+            rate_providers: None,
         };
         let pool_info = weighted::PoolInfo {
             common: PoolInfo {
@@ -828,6 +1033,87 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn fetch_specialized_pool_state_for_pool_in_recovery_mode() {
+        let mock = Mock::new(42);
+        let web3 = mock.web3();
+
+        let mock_pool = mock.deploy(BalancerV3WeightedPool::raw_contract().interface.abi.clone());
+
+        let mut pool_config_bits = [0u8; 32];
+        pool_config_bits[31] = 0b1011; // registered, initialized and in recovery mode
+
+        let vault = mock.deploy(BalancerV3Vault::raw_contract().interface.abi.clone());
+        vault
+            .expect_call(BalancerV3Vault::signatures().is_pool_paused())
+            .predicate((predicate::eq(mock_pool.address()),))
+            .returns(false);
+        vault
+            .expect_call(BalancerV3Vault::signatures().get_static_swap_fee_percentage())
+            .predicate((predicate::eq(mock_pool.address()),))
+            .returns(bfp_v3!("0.003").as_uint256());
+        vault
+            .expect_call(BalancerV3Vault::signatures().get_pool_data())
+            .predicate((predicate::eq(mock_pool.address()),))
+            .returns((
+                Bytes(pool_config_bits),             // pool_config_bits
+                vec![H160([1; 20]), H160([2; 20])],  // tokens
+                vec![(0u8, H160::zero(), false); 2], // token_infos
+                vec![U256::zero(), U256::zero()],    // balances_raw
+                vec![U256::zero(), U256::zero()],    // balances_live_scaled18
+                vec![U256::zero(), U256::zero()],    // token_rates
+                vec![U256::zero(), U256::zero()],    // decimal_scaling_factors
+            ));
+        vault
+            .expect_call(BalancerV3Vault::signatures().get_pool_token_rates())
+            .predicate((predicate::eq(mock_pool.address()),))
+            .returns((
+                vec![U256::zero(), U256::zero()],       // decimal_scaling_factors
+                vec![U256::exp10(18), U256::exp10(18)], // token_rates
+            ));
+
+        let mut mock_factory = MockFactoryIndexing::new();
+        mock_factory.expect_fetch_pool_state().returning(|_, _, _| {
+            Box::pin(future::ok(Some(weighted::PoolState {
+                tokens: btreemap! {},
+                swap_fee: bfp_v3!("0.003"),
+                version: weighted::Version::V1,
+            })))
+        });
+
+        let pool_info_fetcher = PoolInfoFetcher {
+            vault: BalancerV3Vault::at(&web3, vault.address()),
+            factory: mock_factory,
+            token_infos: Arc::new(MockTokenInfoFetching::new()),
+            multicall: None,
+            rate_providers: None,
+        };
+        let pool_info = weighted::PoolInfo {
+            common: PoolInfo {
+                id: mock_pool.address(),
+                address: mock_pool.address(),
+                tokens: vec![H160([1; 20]), H160([2; 20])],
+                scaling_factors: vec![Bfp::exp10(0), Bfp::exp10(0)],
+                rate_providers: vec![H160::zero(), H160::zero()],
+                block_created: 1337,
+            },
+            weights: vec![bfp_v3!("0.5"), bfp_v3!("0.5")],
+        };
+
+        let pool_status = {
+            let block = web3.eth().block_number().await.unwrap();
+            pool_info_fetcher
+                .fetch_pool(&pool_info, block.into())
+                .await
+                .unwrap()
+        };
+
+        match pool_status {
+            PoolStatus::RecoveryMode => {}
+            _ => panic!("expected pool in recovery mode"),
+        }
+    }
+
     #[tokio::test]
     async fn fetch_specialized_pool_state_for_disabled_pool() {
         let tokens = [H160([1; 20]), H160([2; 20])];
@@ -878,6 +1164,8 @@ mod tests {
             vault: BalancerV3Vault::at(&web3, vault.address()),
             factory: mock_factory,
             token_infos: Arc::new(token_infos),
+            multicall: None,
+            rate_providers: None,
         };
         let pool_info = weighted::PoolInfo {
             common: PoolInfo {
@@ -935,6 +1223,8 @@ mod tests {
             vault: BalancerV3Vault::at(&web3, vault.address()),
             factory: MockFactoryIndexing::new(),
             token_infos: Arc::new(token_infos),
+            multicall: None,
+            rate_providers: None,
         };
 
         let result = pool_info_fetcher
@@ -984,6 +1274,8 @@ mod tests {
             vault: BalancerV3Vault::at(&web3, vault.address()),
             factory: MockFactoryIndexing::new(),
             token_infos: Arc::new(token_infos),
+            multicall: None,
+            rate_providers: None,
         };
 
         let result = pool_info_fetcher
@@ -1146,10 +1438,31 @@ mod tests {
         assert!(scaling_factor_from_decimals(19).is_err());
     }
 
+    #[test]
+    fn matches_decimal_scaling_factor_of_vault() {
+        assert!(matches_decimal_scaling_factor(
+            scaling_factor_from_decimals(18).unwrap(),
+            U256::one()
+        ));
+        assert!(matches_decimal_scaling_factor(
+            scaling_factor_from_decimals(6).unwrap(),
+            U256::exp10(12)
+        ));
+        assert!(!matches_decimal_scaling_factor(
+            scaling_factor_from_decimals(18).unwrap(),
+            U256::exp10(12)
+        ));
+        assert!(!matches_decimal_scaling_factor(
+            scaling_factor_from_decimals(0).unwrap(),
+            U256::MAX
+        ));
+    }
+
     #[tokio::test]
     async fn share_pool_state_future() {
         let (shared_fut, shared_rx) = share_common_pool_state(future::ok(PoolState {
             paused: false,
+            recovery_mode: false,
             swap_fee: Bfp::from_wei(U256::from(3000)),
             tokens: btreemap! {},
         }));
@@ -1173,6 +1486,7 @@ mod tests {
     async fn share_pool_state_future_if_dropped() {
         let (shared_fut, _shared_rx) = share_common_pool_state(future::ok(PoolState {
             paused: false,
+            recovery_mode: false,
             swap_fee: Bfp::from_wei(U256::from(3000)),
             tokens: btreemap! {},
         }));
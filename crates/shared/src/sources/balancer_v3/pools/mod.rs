@@ -72,6 +72,9 @@ impl_from_state!(quantamm::PoolState, QuantAmm);
 pub enum PoolStatus {
     Active(Box<Pool>),
     Paused,
+    /// The pool is in recovery mode and gets skipped, as the vault's fee
+    /// accounting for swaps differs from the regular pool math.
+    RecoveryMode,
     Disabled,
 }
 
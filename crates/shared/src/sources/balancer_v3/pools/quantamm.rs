@@ -14,10 +14,30 @@ use {
     std::collections::BTreeMap,
 };
 
+/// The `getPoolDetail` category the strategy of a pool is described in.
+const STRATEGY_DETAIL_CATEGORY: &str = "overview";
+/// The `getPoolDetail` name of the weight update rule of a pool.
+const RULE_DETAIL_NAME: &str = "rule";
+/// The `getPoolDetail` name of the comma separated oracles a pool uses.
+const ORACLES_DETAIL_NAME: &str = "oracles";
+
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub struct PoolInfo {
     pub common: common::PoolInfo,
     pub max_trade_size_ratio: Bfp,
+    pub strategy: Strategy,
+}
+
+/// Strategy metadata of a QuantAMM pool. It does not affect the swap math,
+/// but lets solvers weigh pools by how their weights evolve.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Strategy {
+    /// The weight update rule, e.g. "momentum", if the pool describes it.
+    pub rule: Option<String>,
+    /// The oracles feeding the update rule, as described by the pool.
+    pub oracles: Vec<String>,
+    /// The minimum number of seconds between weight updates.
+    pub update_interval: u64,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -27,6 +47,7 @@ pub struct PoolState {
     pub version: Version,
     // QuantAMM-specific static data (needed for pool operations)
     pub max_trade_size_ratio: Bfp,
+    pub strategy: Strategy,
     // QuantAMM-specific dynamic data (raw, no calculations)
     pub first_four_weights_and_multipliers: Vec<I256>,
     pub second_four_weights_and_multipliers: Vec<I256>,
@@ -65,6 +86,8 @@ impl PoolIndexing for PoolInfo {
         Ok(PoolInfo {
             common: common::PoolInfo::for_type(PoolType::QuantAmmWeighted, pool, block_created)?,
             max_trade_size_ratio,
+            // Only available on-chain, see `specialize_pool_info`.
+            strategy: Strategy::default(),
         })
     }
 
@@ -94,9 +117,26 @@ impl FactoryIndexing for BalancerV3QuantAMMWeightedPoolFactory {
         // maxTradeSizeRatio
         let max_trade_size_ratio = Bfp::from_wei(immutable_data.8);
 
+        // The pool details are free-form metadata set at pool creation, so
+        // pools that don't describe their strategy are still indexed.
+        let detail = |name: &str| {
+            pool_contract
+                .get_pool_detail(STRATEGY_DETAIL_CATEGORY.to_string(), name.to_string())
+                .call()
+        };
+        let (rule, oracles) = futures::join!(detail(RULE_DETAIL_NAME), detail(ORACLES_DETAIL_NAME));
+        let strategy = Strategy {
+            rule: rule.ok().and_then(|(_, value)| non_empty(&value)),
+            oracles: oracles
+                .map(|(_, value)| value.split(',').filter_map(non_empty).collect())
+                .unwrap_or_default(),
+            update_interval: immutable_data.7,
+        };
+
         Ok(PoolInfo {
             common: pool,
             max_trade_size_ratio,
+            strategy,
         })
     }
 
@@ -111,6 +151,7 @@ impl FactoryIndexing for BalancerV3QuantAMMWeightedPoolFactory {
             pool_info.common.address,
         );
         let max_trade_size_ratio = pool_info.max_trade_size_ratio;
+        let strategy = pool_info.strategy.clone();
 
         let fetch_common = common_pool_state.map(Result::Ok);
         let fetch_dynamic = pool_contract
@@ -148,6 +189,7 @@ impl FactoryIndexing for BalancerV3QuantAMMWeightedPoolFactory {
                 swap_fee: common.swap_fee,
                 version: Version::V1,
                 max_trade_size_ratio,
+                strategy,
                 // Store raw multiplier data - calculations happen in swap logic
                 first_four_weights_and_multipliers,
                 second_four_weights_and_multipliers,
@@ -161,6 +203,12 @@ impl FactoryIndexing for BalancerV3QuantAMMWeightedPoolFactory {
     }
 }
 
+/// Trims a pool detail value, returning `None` for details that are not set.
+fn non_empty(value: &str) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
 // Re-export for external use, to match other pool modules
 pub type TokenState = common::TokenState;
 
@@ -237,10 +285,24 @@ mod tests {
                     block_created: 42,
                 },
                 max_trade_size_ratio: Bfp::from_wei(U256::from(100_000_000_000_000_000u128)),
+                strategy: Strategy::default(),
             },
         );
     }
 
+    #[test]
+    fn parses_pool_detail_values() {
+        assert_eq!(non_empty(" momentum "), Some("momentum".to_string()));
+        assert_eq!(non_empty(""), None);
+        assert_eq!(
+            "ChainlinkEthUsd, ,ChainlinkBtcUsd"
+                .split(',')
+                .filter_map(non_empty)
+                .collect::<Vec<_>>(),
+            vec!["ChainlinkEthUsd".to_string(), "ChainlinkBtcUsd".to_string()],
+        );
+    }
+
     #[test]
     fn errors_when_converting_wrong_pool_type() {
         let pool = PoolData {
@@ -6,10 +6,14 @@ use {
         graph_api::{PoolData, PoolType},
         swap::fixed_point::Bfp,
     },
-    anyhow::{Result, anyhow},
-    contracts::{BalancerV3ReClammPool, BalancerV3ReClammPoolFactoryV2},
-    ethcontract::{BlockId, H160, U256},
-    futures::{FutureExt as _, future::BoxFuture},
+    anyhow::{Context, Result, anyhow},
+    contracts::{
+        BalancerV3ReClammPool,
+        BalancerV3ReClammPoolFactory,
+        BalancerV3ReClammPoolFactoryV2,
+    },
+    ethcontract::{BlockId, H160, U256, dyns::DynWeb3},
+    futures::{FutureExt as _, TryFutureExt as _, future::BoxFuture},
     std::collections::BTreeMap,
 };
 
@@ -50,14 +54,38 @@ pub struct PoolState {
     pub end_fourth_root_price_ratio: Bfp,
     pub price_ratio_update_start_time: u64,
     pub price_ratio_update_end_time: u64,
+    /// The timestamp of the block the state was fetched at, up to which the
+    /// virtual balances drift since the last timestamp.
+    pub current_timestamp: u64,
 }
 
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
 pub enum Version {
+    V1, // BalancerV3ReClammPoolFactory
     #[default]
     V2, // BalancerV3ReClammPoolFactoryV2
 }
 
+/// ReCLAMM pools store the daily price shift base as `1 - exponent / 124649`.
+const PRICE_SHIFT_EXPONENT_INTERNAL_ADJUSTMENT: u64 = 124649;
+
+/// Computes the daily price shift base from the daily price shift exponent,
+/// for V1 pools which only expose the exponent.
+fn daily_price_shift_base(daily_price_shift_exponent: U256) -> Result<Bfp> {
+    Ok(Bfp::one().sub(Bfp::from_wei(
+        daily_price_shift_exponent / PRICE_SHIFT_EXPONENT_INTERNAL_ADJUSTMENT,
+    ))?)
+}
+
+/// Fetches the timestamp of the block the state of a pool is fetched at.
+fn fetch_block_timestamp(web3: DynWeb3, block: BlockId) -> BoxFuture<'static, Result<u64>> {
+    async move {
+        let block = web3.eth().block(block).await?.context("missing block")?;
+        Ok(block.timestamp.low_u64())
+    }
+    .boxed()
+}
+
 // Re-export for external use, to match other pool modules
 pub type TokenState = common::TokenState;
 
@@ -83,11 +111,14 @@ impl FactoryIndexing for BalancerV3ReClammPoolFactoryV2 {
         let fetch_dynamic = pool_contract
             .get_re_clamm_pool_dynamic_data()
             .block(block)
-            .call();
+            .call()
+            .err_into();
+        let fetch_current_timestamp = fetch_block_timestamp(self.raw_instance().web3(), block);
 
         async move {
             // Join the shared common state and pool-specific dynamic data
-            let (common, dynamic) = futures::try_join!(fetch_common, fetch_dynamic)?;
+            let (common, dynamic, current_timestamp) =
+                futures::try_join!(fetch_common, fetch_dynamic, fetch_current_timestamp)?;
 
             // dynamic is a tuple following ReClammPoolDynamicData ABI
             let (
@@ -123,6 +154,100 @@ impl FactoryIndexing for BalancerV3ReClammPoolFactoryV2 {
                 end_fourth_root_price_ratio: Bfp::from_wei(end_fourth_root_price_ratio_u256),
                 price_ratio_update_start_time: price_ratio_update_start_time_u32 as u64,
                 price_ratio_update_end_time: price_ratio_update_end_time_u32 as u64,
+                current_timestamp,
+            };
+
+            Ok(Some(pool_state))
+        }
+        .boxed()
+    }
+}
+
+#[async_trait::async_trait]
+impl FactoryIndexing for BalancerV3ReClammPoolFactory {
+    type PoolInfo = PoolInfo;
+    type PoolState = PoolState;
+
+    async fn specialize_pool_info(&self, pool: common::PoolInfo) -> Result<Self::PoolInfo> {
+        Ok(PoolInfo { common: pool })
+    }
+
+    fn fetch_pool_state(
+        &self,
+        pool_info: &Self::PoolInfo,
+        common_pool_state: BoxFuture<'static, common::PoolState>,
+        block: BlockId,
+    ) -> BoxFuture<'static, Result<Option<Self::PoolState>>> {
+        let pool_contract =
+            BalancerV3ReClammPool::at(&self.raw_instance().web3(), pool_info.common.address);
+
+        // The V1 dynamic data layout differs from V2, so read the parameters
+        // through their individual getters, which both versions share.
+        let fetch_common = common_pool_state.map(Result::Ok);
+        let fetch_last_virtual_balances = pool_contract
+            .get_last_virtual_balances()
+            .block(block)
+            .call()
+            .err_into();
+        let fetch_last_timestamp = pool_contract
+            .get_last_timestamp()
+            .block(block)
+            .call()
+            .err_into();
+        let fetch_daily_price_shift_exponent = pool_contract
+            .get_daily_price_shift_exponent()
+            .block(block)
+            .call()
+            .err_into();
+        let fetch_centeredness_margin = pool_contract
+            .get_centeredness_margin()
+            .block(block)
+            .call()
+            .err_into();
+        let fetch_price_ratio_state = pool_contract
+            .get_price_ratio_state()
+            .block(block)
+            .call()
+            .err_into();
+        let fetch_current_timestamp = fetch_block_timestamp(self.raw_instance().web3(), block);
+
+        async move {
+            let (
+                common,
+                (virtual_balance_a, virtual_balance_b),
+                last_timestamp,
+                daily_price_shift_exponent,
+                centeredness_margin,
+                (
+                    start_fourth_root_price_ratio,
+                    end_fourth_root_price_ratio,
+                    price_ratio_update_start_time,
+                    price_ratio_update_end_time,
+                ),
+                current_timestamp,
+            ) = futures::try_join!(
+                fetch_common,
+                fetch_last_virtual_balances,
+                fetch_last_timestamp,
+                fetch_daily_price_shift_exponent,
+                fetch_centeredness_margin,
+                fetch_price_ratio_state,
+                fetch_current_timestamp,
+            )?;
+
+            let pool_state = PoolState {
+                tokens: common.tokens,
+                swap_fee: common.swap_fee,
+                version: Version::V1,
+                last_virtual_balances: vec![virtual_balance_a, virtual_balance_b],
+                daily_price_shift_base: daily_price_shift_base(daily_price_shift_exponent)?,
+                last_timestamp: last_timestamp.into(),
+                centeredness_margin: Bfp::from_wei(centeredness_margin),
+                start_fourth_root_price_ratio: Bfp::from_wei(start_fourth_root_price_ratio),
+                end_fourth_root_price_ratio: Bfp::from_wei(end_fourth_root_price_ratio),
+                price_ratio_update_start_time: price_ratio_update_start_time.into(),
+                price_ratio_update_end_time: price_ratio_update_end_time.into(),
+                current_timestamp,
             };
 
             Ok(Some(pool_state))
@@ -198,4 +323,14 @@ mod tests {
             },
         );
     }
+
+    #[test]
+    fn derives_daily_price_shift_base_from_exponent() {
+        // A 100% daily price shift exponent.
+        assert_eq!(
+            daily_price_shift_base(Bfp::one().as_uint256()).unwrap(),
+            Bfp::from_wei(U256::from(999_991_977_472_743_464_u128)),
+        );
+        assert_eq!(daily_price_shift_base(U256::zero()).unwrap(), Bfp::one());
+    }
 }
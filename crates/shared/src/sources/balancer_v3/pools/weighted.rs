@@ -293,6 +293,7 @@ mod tests {
 
         let common_pool_state = common::PoolState {
             paused: false,
+            recovery_mode: false,
             swap_fee: Bfp::from_wei(3000u64.into()),
             tokens: btreemap! {
                 H160([0x11; 20]) => common::TokenState {
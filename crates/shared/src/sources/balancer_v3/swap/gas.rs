@@ -0,0 +1,148 @@
+//! Gas cost model of the swaps through Balancer V3 pools.
+//!
+//! The gas a swap uses depends on the math of the pool kind and varies between
+//! chains. The model starts from the default cost of every pool kind, which
+//! the configuration of a chain can override, and refines them with the gas
+//! measured for settlements swapping through a pool of the kind.
+
+use {
+    serde::{Deserialize, Serialize},
+    std::{
+        collections::HashMap,
+        sync::{LazyLock, RwLock},
+    },
+};
+
+/// The number of measurements needed before they replace the configured cost.
+const MIN_SAMPLES: usize = 10;
+
+/// The weight of a new measurement once the minimum number of samples is
+/// reached, so that the cost follows changes of the measured gas.
+const SMOOTHING: f64 = 0.1;
+
+/// The model consulted by the pools, configured once on startup.
+static MODEL: LazyLock<RwLock<GasCostModel>> = LazyLock::new(Default::default);
+
+/// The kinds of Balancer V3 pools with distinct swap math.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PoolKind {
+    Weighted,
+    Stable,
+    StableSurge,
+    #[serde(rename = "gyro-2clp")]
+    Gyro2CLP,
+    GyroE,
+    ReClamm,
+    QuantAmm,
+}
+
+impl PoolKind {
+    /// The approximate gas used by a swap through a pool of the kind.
+    fn default_cost(self) -> usize {
+        match self {
+            Self::Weighted => 100_000,
+            Self::Stable => 183_520,
+            Self::StableSurge => 100_000,
+            Self::Gyro2CLP => 100_000,
+            Self::GyroE => 100_000,
+            Self::ReClamm => 100_000,
+            // Higher than weighted pools due to the weight calculations.
+            Self::QuantAmm => 180_000,
+        }
+    }
+}
+
+/// The gas costs of swaps through the different kinds of pools.
+#[derive(Debug, Default)]
+pub struct GasCostModel {
+    configured: HashMap<PoolKind, usize>,
+    measured: HashMap<PoolKind, Measurement>,
+}
+
+#[derive(Debug)]
+struct Measurement {
+    samples: usize,
+    average: f64,
+}
+
+impl GasCostModel {
+    /// Creates a model with the specified costs, replacing the defaults of
+    /// the pool kinds.
+    pub fn new(configured: HashMap<PoolKind, usize>) -> Self {
+        Self {
+            configured,
+            measured: Default::default(),
+        }
+    }
+
+    /// The gas used by a swap through a pool of the kind. The measured gas is
+    /// used once there are enough measurements, and the configured or
+    /// default cost otherwise.
+    pub fn cost(&self, kind: PoolKind) -> usize {
+        match self.measured.get(&kind) {
+            Some(measurement) if measurement.samples >= MIN_SAMPLES => {
+                measurement.average.round() as usize
+            }
+            _ => self
+                .configured
+                .get(&kind)
+                .copied()
+                .unwrap_or_else(|| kind.default_cost()),
+        }
+    }
+
+    /// Records the gas measured for a swap through a pool of the kind. The
+    /// first measurements are averaged, later ones are smoothed
+    /// exponentially.
+    pub fn observe(&mut self, kind: PoolKind, gas: usize) {
+        let measurement = self.measured.entry(kind).or_insert(Measurement {
+            samples: 0,
+            average: 0.,
+        });
+        measurement.samples += 1;
+        let weight = (1. / measurement.samples as f64).max(SMOOTHING);
+        measurement.average += weight * (gas as f64 - measurement.average);
+    }
+}
+
+/// Replaces the model consulted by the pools.
+pub fn configure(model: GasCostModel) {
+    *MODEL.write().unwrap() = model;
+}
+
+/// The gas used by a swap through a pool of the kind, according to the
+/// configured model.
+pub fn cost(kind: PoolKind) -> usize {
+    MODEL.read().unwrap().cost(kind)
+}
+
+/// Records the gas measured for a swap through a pool of the kind with the
+/// configured model.
+pub fn observe(kind: PoolKind, gas: usize) {
+    MODEL.write().unwrap().observe(kind, gas);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refines_configured_costs_with_measurements() {
+        let mut model = GasCostModel::new(HashMap::from([(PoolKind::Stable, 90_000)]));
+        assert_eq!(model.cost(PoolKind::Weighted), 100_000);
+        assert_eq!(model.cost(PoolKind::Stable), 90_000);
+
+        for gas in [110_000, 130_000].into_iter().cycle().take(MIN_SAMPLES - 1) {
+            model.observe(PoolKind::Stable, gas);
+        }
+        assert_eq!(model.cost(PoolKind::Stable), 90_000);
+
+        model.observe(PoolKind::Stable, 130_000);
+        assert_eq!(model.cost(PoolKind::Stable), 120_000);
+        assert_eq!(model.cost(PoolKind::Weighted), 100_000);
+
+        model.observe(PoolKind::Stable, 220_000);
+        assert_eq!(model.cost(PoolKind::Stable), 130_000);
+    }
+}
@@ -29,11 +29,15 @@ use {
     std::collections::BTreeMap,
 };
 
+#[cfg(test)]
+mod conformance;
 mod error;
 pub mod fixed_point;
+pub mod gas;
 pub mod gyro_2clp_math;
 pub mod gyro_e_math;
 mod math;
+mod ordered_pair;
 pub mod quantamm_math;
 pub mod reclamm_math;
 pub mod signed_fixed_point;
@@ -41,21 +45,34 @@ mod stable_math;
 pub mod stable_surge_math;
 mod weighted_math;
 
-const WEIGHTED_SWAP_GAS_COST: usize = 100_000;
-const STABLE_SWAP_GAS_COST: usize = 183_520;
-const STABLE_SURGE_SWAP_GAS_COST: usize = 100_000;
-const GYRO_2CLP_SWAP_GAS_COST: usize = 100_000;
-const GYRO_E_SWAP_GAS_COST: usize = 100_000;
-const RECLAMM_SWAP_GAS_COST: usize = 100_000;
+pub use self::ordered_pair::{OrderedPair, OrderedSwap};
+
+/// Checks that the swap fee leaves a part of the swapped amount to the pool.
+/// Fees of 100% or more can't be set on-chain and would make the fee math
+/// divide by zero or underflow.
+fn check_swap_fee(swap_fee: Bfp) -> Result<(), Error> {
+    if swap_fee >= Bfp::one() {
+        return Err(Error::MaxSwapFeePercentage);
+    }
+    Ok(())
+}
 
 fn add_swap_fee_amount(amount: U256, swap_fee: Bfp) -> Result<U256, Error> {
     // https://github.com/balancer-labs/balancer-v2-monorepo/blob/6c9e24e22d0c46cca6dd15861d3d33da61a60b98/pkg/core/contracts/pools/BasePool.sol#L454-L457
+    check_swap_fee(swap_fee)?;
+    if swap_fee.is_zero() {
+        return Ok(amount);
+    }
     let amount_with_fees = Bfp::from_wei(amount).div_up(swap_fee.complement())?;
     Ok(amount_with_fees.as_uint256())
 }
 
 fn subtract_swap_fee_amount(amount: U256, swap_fee: Bfp) -> Result<U256, Error> {
     // https://github.com/balancer-labs/balancer-v2-monorepo/blob/6c9e24e22d0c46cca6dd15861d3d33da61a60b98/pkg/core/contracts/pools/BasePool.sol#L462-L466
+    check_swap_fee(swap_fee)?;
+    if swap_fee.is_zero() {
+        return Ok(amount);
+    }
     let amount = Bfp::from_wei(amount);
     let fee_amount = amount.mul_up(swap_fee)?;
     let amount_without_fees = amount.sub(fee_amount)?;
@@ -114,6 +131,42 @@ fn compute_rate_round_up(rate: U256) -> U256 {
     if rounded_rate == rate { rate } else { rate + 1 }
 }
 
+/// Returns the pool reserves after swapping `in_amount` of `in_token` for
+/// `out_amount` of `out_token` through the pool, so that subsequent swaps in
+/// the same settlement can be priced against the updated pool state. Returns
+/// `None` if the pool doesn't hold both tokens or the output exceeds the
+/// pool's balance.
+///
+/// The vault charges the `aggregate_swap_fee` share of the `swap_fee` taken
+/// from the input to the protocol and pool creator, so only the rest of the
+/// input is credited to the pool's balance.
+fn apply_swap_to_reserves<T: Clone>(
+    reserves: &BTreeMap<H160, T>,
+    balance: impl Fn(&mut T) -> &mut U256,
+    (in_amount, in_token): (U256, H160),
+    (out_amount, out_token): (U256, H160),
+    swap_fee: Bfp,
+    aggregate_swap_fee: Bfp,
+) -> Option<BTreeMap<H160, T>> {
+    if in_token == out_token {
+        return None;
+    }
+    let aggregate_fee_amount = Bfp::from_wei(in_amount)
+        .mul_up(swap_fee)
+        .ok()?
+        .mul_down(aggregate_swap_fee)
+        .ok()?
+        .as_uint256();
+    let mut reserves = reserves.clone();
+    let in_balance = balance(reserves.get_mut(&in_token)?);
+    *in_balance = in_balance
+        .checked_add(in_amount)?
+        .checked_sub(aggregate_fee_amount)?;
+    let out_balance = balance(reserves.get_mut(&out_token)?);
+    *out_balance = out_balance.checked_sub(out_amount)?;
+    Some(reserves)
+}
+
 impl TokenState {
     /// Converts the stored balance into its internal representation as a
     /// Balancer fixed point number.
@@ -174,6 +227,23 @@ pub struct WeightedPoolRef<'a> {
 }
 
 impl WeightedPoolRef<'_> {
+    /// Returns the reserves after the swap, see `apply_swap_to_reserves`.
+    pub fn apply_swap(
+        &self,
+        input: (U256, H160),
+        output: (U256, H160),
+        aggregate_swap_fee: Bfp,
+    ) -> Option<BTreeMap<H160, WeightedTokenState>> {
+        apply_swap_to_reserves(
+            self.reserves,
+            |state| &mut state.common.balance,
+            input,
+            output,
+            self.swap_fee,
+            aggregate_swap_fee,
+        )
+    }
+
     fn get_amount_out_inner(
         &self,
         out_token: H160,
@@ -237,7 +307,7 @@ impl BaselineSolvable for WeightedPoolRef<'_> {
     }
 
     async fn gas_cost(&self) -> usize {
-        WEIGHTED_SWAP_GAS_COST
+        gas::cost(gas::PoolKind::Weighted)
     }
 }
 
@@ -360,16 +430,32 @@ impl<'a> StablePoolRef<'a> {
         add_swap_fee_amount(amount_in_before_fee, self.swap_fee).ok()
     }
 
-    /// Comes from `_swapWithBpt`:
-    // https://etherscan.io/address/0xf9ac7B9dF2b3454E841110CcE5550bD5AC6f875F#code#F2#L301
+    /// Unlike Balancer V2 composable stable pools, Balancer V3 pools don't
+    /// register their BPT as a pool token, so the vault never swaps it. The
+    /// BPT can only be bought and sold by adding and removing liquidity.
     fn swap_with_bpt(&self) -> Option<U256> {
-        // TODO: We currently do not implement swapping with BPT for composable
-        // stable pools.
         None
     }
 }
 
 impl StablePoolRef<'_> {
+    /// Returns the reserves after the swap, see `apply_swap_to_reserves`.
+    pub fn apply_swap(
+        &self,
+        input: (U256, H160),
+        output: (U256, H160),
+        aggregate_swap_fee: Bfp,
+    ) -> Option<BTreeMap<H160, TokenState>> {
+        apply_swap_to_reserves(
+            self.reserves,
+            |state| &mut state.balance,
+            input,
+            output,
+            self.swap_fee,
+            aggregate_swap_fee,
+        )
+    }
+
     fn get_amount_out_inner(
         &self,
         out_token: H160,
@@ -409,7 +495,7 @@ impl BaselineSolvable for StablePoolRef<'_> {
     }
 
     async fn gas_cost(&self) -> usize {
-        STABLE_SWAP_GAS_COST
+        gas::cost(gas::PoolKind::Stable)
     }
 }
 
@@ -448,6 +534,62 @@ fn converge_in_amount(
     None
 }
 
+/// Stable surge pools charge a fee depending on the imbalance of the pool
+/// after the swap, which for exact in swaps includes the fee itself. The
+/// exact out fee is computed without it, so once a swap surges, selling the
+/// computed input amount yields far less than the exact out amount and bumping
+/// it by orders of magnitude like [`converge_in_amount`] grossly overpays.
+/// Instead, we search for the smallest sell amount yielding at least the exact
+/// out amount, starting from the exact out input amount.
+fn search_in_amount(
+    in_amount: U256,
+    exact_out_amount: U256,
+    get_amount_out: impl Fn(U256) -> Option<U256>,
+) -> Option<U256> {
+    let out_amount = get_amount_out(in_amount)?;
+    if out_amount >= exact_out_amount {
+        return Some(in_amount);
+    }
+    let sufficient = |in_amount| {
+        get_amount_out(in_amount).is_some_and(|out_amount| out_amount >= exact_out_amount)
+    };
+
+    // Find an upper bound by doubling the bump, starting from the out amount
+    // deficit converted to in tokens at the trading price.
+    let mut bump = (exact_out_amount - out_amount)
+        .checked_mul(in_amount)?
+        .ceil_div(&out_amount.max(U256::one()))
+        .max(U256::one());
+    let mut low = in_amount;
+    let mut high = None;
+    for _ in 0..MAX_SEARCH_DOUBLINGS {
+        let candidate = in_amount.checked_add(bump)?;
+        if sufficient(candidate) {
+            high = Some(candidate);
+            break;
+        }
+        low = candidate;
+        bump = bump.checked_mul(2.into())?;
+    }
+    let mut high = high?;
+
+    // Bisect between the largest insufficient and the smallest sufficient
+    // amount found so far.
+    while high - low > U256::one() {
+        let middle = low + (high - low) / 2;
+        if sufficient(middle) {
+            high = middle;
+        } else {
+            low = middle;
+        }
+    }
+    Some(high)
+}
+
+/// The number of times the bump of [`search_in_amount`] gets doubled before
+/// giving up, e.g. because the pool can't provide the exact out amount.
+const MAX_SEARCH_DOUBLINGS: usize = 64;
+
 impl WeightedPool {
     fn as_pool_ref(&self) -> WeightedPoolRef<'_> {
         WeightedPoolRef {
@@ -515,6 +657,25 @@ pub struct StableSurgePoolRef<'a> {
 }
 
 impl StableSurgePoolRef<'_> {
+    /// Returns the reserves after the swap, see `apply_swap_to_reserves`.
+    pub fn apply_swap(
+        &self,
+        input: (U256, H160),
+        output: (U256, H160),
+        aggregate_swap_fee: Bfp,
+    ) -> Option<BTreeMap<H160, StableTokenState>> {
+        // The surge fee depends on the imbalance the swap leaves behind.
+        let swap_fee = self.swap_given_in(output.1, input)?.effective_swap_fee;
+        apply_swap_to_reserves(
+            self.reserves,
+            |state| &mut state.balance,
+            input,
+            output,
+            swap_fee,
+            aggregate_swap_fee,
+        )
+    }
+
     fn get_balances_with_indices(
         &self,
         in_token: H160,
@@ -547,8 +708,17 @@ impl StableSurgePoolRef<'_> {
         out_token: H160,
         (in_amount, in_token): (U256, H160),
     ) -> Option<U256> {
-        let in_reserves = self.reserves.get(&in_token)?;
         let out_reserves = self.reserves.get(&out_token)?;
+        let result = self.swap_given_in(out_token, (in_amount, in_token))?;
+        out_reserves.downscale_down(result.amount_calculated).ok()
+    }
+
+    fn swap_given_in(
+        &self,
+        out_token: H160,
+        (in_amount, in_token): (U256, H160),
+    ) -> Option<stable_surge_math::StableSurgeSwapResult> {
+        let in_reserves = self.reserves.get(&in_token)?;
 
         let in_amount_upscaled = in_reserves.upscale(in_amount).ok()?;
         let balances_info = self.get_balances_with_indices(in_token, out_token)?;
@@ -567,15 +737,13 @@ impl StableSurgePoolRef<'_> {
         };
 
         // Calculate swap with surge fee logic
-        let result = pool_state
+        pool_state
             .calc_out_given_in_with_surge(
                 balances_info.token_index_in,
                 balances_info.token_index_out,
                 in_amount_upscaled,
             )
-            .ok()?;
-
-        out_reserves.downscale_down(result.amount_calculated).ok()
+            .ok()
     }
 
     fn regular_swap_given_out(
@@ -614,10 +782,8 @@ impl StableSurgePoolRef<'_> {
         in_reserves.downscale_up(result.amount_calculated).ok()
     }
 
-    /// Comes from `_swapWithBpt`:
-    /// https://etherscan.io/address/0xf9ac7B9dF2b3454E841110CcE5550bD5AC6f875F#code#F2#L301
+    /// See [`StablePoolRef::swap_with_bpt`].
     fn swap_with_bpt(&self) -> Option<U256> {
-        // TODO: We currently do not implement swapping with BPT for stable surge pools.
         None
     }
 
@@ -670,14 +836,14 @@ impl BaselineSolvable for StableSurgePoolRef<'_> {
             self.swap_with_bpt()
         } else {
             let in_amount = self.regular_swap_given_out(in_token, (out_amount, out_token))?;
-            converge_in_amount(in_amount, out_amount, |x| {
+            search_in_amount(in_amount, out_amount, |x| {
                 self.get_amount_out_inner(out_token, x, in_token)
             })
         }
     }
 
     async fn gas_cost(&self) -> usize {
-        STABLE_SURGE_SWAP_GAS_COST
+        gas::cost(gas::PoolKind::StableSurge)
     }
 }
 
@@ -735,6 +901,23 @@ pub struct GyroEPoolRef<'a> {
 }
 
 impl GyroEPoolRef<'_> {
+    /// Returns the reserves after the swap, see `apply_swap_to_reserves`.
+    pub fn apply_swap(
+        &self,
+        input: (U256, H160),
+        output: (U256, H160),
+        aggregate_swap_fee: Bfp,
+    ) -> Option<BTreeMap<H160, TokenState>> {
+        apply_swap_to_reserves(
+            self.reserves,
+            |state| &mut state.balance,
+            input,
+            output,
+            self.swap_fee,
+            aggregate_swap_fee,
+        )
+    }
+
     fn get_amount_out_inner(
         &self,
         out_token: H160,
@@ -748,12 +931,11 @@ impl GyroEPoolRef<'_> {
         // Apply swap fee to input amount
         let in_amount_minus_fees = subtract_swap_fee_amount(in_amount, self.swap_fee).ok()?;
 
-        // Determine token order (token0 vs token1)
-        let token_in_is_token0 = in_token < out_token;
+        let swap = OrderedSwap::new(in_token, out_token)?;
 
         // Convert reserves to the format expected by gyro_e_math
-        let _balances = if token_in_is_token0 {
-            vec![
+        let _balances = swap
+            .arrange(
                 in_reserves
                     .upscaled_balance()
                     .ok()?
@@ -764,21 +946,8 @@ impl GyroEPoolRef<'_> {
                     .ok()?
                     .as_uint256()
                     .to_big_int(),
-            ]
-        } else {
-            vec![
-                out_reserves
-                    .upscaled_balance()
-                    .ok()?
-                    .as_uint256()
-                    .to_big_int(),
-                in_reserves
-                    .upscaled_balance()
-                    .ok()?
-                    .as_uint256()
-                    .to_big_int(),
-            ]
-        };
+            )
+            .to_vec();
 
         // Convert input amount to BigInt
         let in_amount_scaled = in_reserves.upscale(in_amount_minus_fees).ok()?;
@@ -824,7 +993,7 @@ impl GyroEPoolRef<'_> {
         let out_amount_big_int = gyro_e_math::calc_out_given_in(
             &_balances,
             &_amount_in_big_int,
-            token_in_is_token0,
+            swap.token_in_is_token0(),
             &params,
             &derived,
             &invariant,
@@ -865,12 +1034,11 @@ impl BaselineSolvable for GyroEPoolRef<'_> {
         let in_reserves = self.reserves.get(&in_token)?;
         let out_reserves = self.reserves.get(&out_token)?;
 
-        // Determine token order
-        let token_in_is_token0 = in_token < out_token;
+        let swap = OrderedSwap::new(in_token, out_token)?;
 
         // Convert reserves to BigInt format
-        let balances = if token_in_is_token0 {
-            vec![
+        let balances = swap
+            .arrange(
                 in_reserves
                     .upscaled_balance()
                     .ok()?
@@ -881,21 +1049,8 @@ impl BaselineSolvable for GyroEPoolRef<'_> {
                     .ok()?
                     .as_uint256()
                     .to_big_int(),
-            ]
-        } else {
-            vec![
-                out_reserves
-                    .upscaled_balance()
-                    .ok()?
-                    .as_uint256()
-                    .to_big_int(),
-                in_reserves
-                    .upscaled_balance()
-                    .ok()?
-                    .as_uint256()
-                    .to_big_int(),
-            ]
-        };
+            )
+            .to_vec();
 
         // Scale the output amount
         let out_amount_scaled = out_reserves.upscale(out_amount).ok()?;
@@ -941,7 +1096,7 @@ impl BaselineSolvable for GyroEPoolRef<'_> {
         let in_amount_big_int = gyro_e_math::calc_in_given_out(
             &balances,
             &amount_out_big_int,
-            token_in_is_token0,
+            swap.token_in_is_token0(),
             &params,
             &derived,
             &invariant,
@@ -965,7 +1120,7 @@ impl BaselineSolvable for GyroEPoolRef<'_> {
     }
 
     async fn gas_cost(&self) -> usize {
-        GYRO_E_SWAP_GAS_COST
+        gas::cost(gas::PoolKind::GyroE)
     }
 }
 
@@ -1017,6 +1172,23 @@ pub struct Gyro2CLPPoolRef<'a> {
 }
 
 impl Gyro2CLPPoolRef<'_> {
+    /// Returns the reserves after the swap, see `apply_swap_to_reserves`.
+    pub fn apply_swap(
+        &self,
+        input: (U256, H160),
+        output: (U256, H160),
+        aggregate_swap_fee: Bfp,
+    ) -> Option<BTreeMap<H160, TokenState>> {
+        apply_swap_to_reserves(
+            self.reserves,
+            |state| &mut state.balance,
+            input,
+            output,
+            self.swap_fee,
+            aggregate_swap_fee,
+        )
+    }
+
     fn get_amount_out_inner(
         &self,
         out_token: H160,
@@ -1170,7 +1342,7 @@ impl BaselineSolvable for Gyro2CLPPoolRef<'_> {
     }
 
     async fn gas_cost(&self) -> usize {
-        GYRO_2CLP_SWAP_GAS_COST
+        gas::cost(gas::PoolKind::Gyro2CLP)
     }
 }
 
@@ -1212,17 +1384,17 @@ pub struct ReClammPoolRef<'a> {
     pub end_fourth_root_price_ratio: Bfp,
     pub price_ratio_update_start_time: u64,
     pub price_ratio_update_end_time: u64,
+    pub current_timestamp: u64,
 }
 
 impl ReClammPoolRef<'_> {
     fn compute_virtuals_and_balances(
         &self,
-        token0: H160,
-        token1: H160,
+        pair: OrderedPair,
         balances: &BTreeMap<H160, TokenState>,
     ) -> Option<([Bfp; 2], Bfp, Bfp, bool)> {
-        let r0 = balances.get(&token0)?;
-        let r1 = balances.get(&token1)?;
+        let r0 = balances.get(&pair.token0())?;
+        let r1 = balances.get(&pair.token1())?;
         let balances_scaled18 = [r0.upscaled_balance().ok()?, r1.upscaled_balance().ok()?];
         let prs = reclamm_math::PriceRatioState {
             price_ratio_update_start_time: self.price_ratio_update_start_time,
@@ -1230,8 +1402,11 @@ impl ReClammPoolRef<'_> {
             start_fourth_root_price_ratio: self.start_fourth_root_price_ratio,
             end_fourth_root_price_ratio: self.end_fourth_root_price_ratio,
         };
+        // The virtual balances drift with the time passed since the last
+        // update, which never goes back.
+        let current_timestamp = self.current_timestamp.max(self.last_timestamp);
         let (va, vb, changed) = reclamm_math::compute_current_virtual_balances(
-            self.last_timestamp,
+            current_timestamp,
             &balances_scaled18,
             self.last_virtual_balances[0],
             self.last_virtual_balances[1],
@@ -1250,11 +1425,7 @@ impl ReClammPoolRef<'_> {
         in_amount: U256,
         in_token: H160,
     ) -> Option<U256> {
-        let (token0, token1) = if in_token < out_token {
-            (in_token, out_token)
-        } else {
-            (out_token, in_token)
-        };
+        let swap = OrderedSwap::new(in_token, out_token)?;
         let in_reserves = self.reserves.get(&in_token)?;
         let out_reserves = self.reserves.get(&out_token)?;
 
@@ -1262,22 +1433,15 @@ impl ReClammPoolRef<'_> {
         let in_amount_minus_fees = subtract_swap_fee_amount(in_amount, self.swap_fee).ok()?;
 
         let (balances_scaled18, va, vb, _changed) =
-            self.compute_virtuals_and_balances(token0, token1, self.reserves)?;
-
-        // Map token indices based on address ordering
-        let (index_in, index_out) = if in_token == token0 {
-            (0usize, 1usize)
-        } else {
-            (1usize, 0usize)
-        };
+            self.compute_virtuals_and_balances(swap.pair(), self.reserves)?;
 
         let amount_in_scaled18 = in_reserves.upscale(in_amount_minus_fees).ok()?;
         let out_scaled = reclamm_math::compute_out_given_in(
             &balances_scaled18,
             va,
             vb,
-            index_in,
-            index_out,
+            swap.index_in(),
+            swap.index_out(),
             amount_in_scaled18,
         )
         .ok()?;
@@ -1290,30 +1454,20 @@ impl ReClammPoolRef<'_> {
         out_amount: U256,
         out_token: H160,
     ) -> Option<U256> {
-        let (token0, token1) = if in_token < out_token {
-            (in_token, out_token)
-        } else {
-            (out_token, in_token)
-        };
+        let swap = OrderedSwap::new(in_token, out_token)?;
         let in_reserves = self.reserves.get(&in_token)?;
         let out_reserves = self.reserves.get(&out_token)?;
 
         let (balances_scaled18, va, vb, _changed) =
-            self.compute_virtuals_and_balances(token0, token1, self.reserves)?;
-
-        let (index_in, index_out) = if in_token == token0 {
-            (0usize, 1usize)
-        } else {
-            (1usize, 0usize)
-        };
+            self.compute_virtuals_and_balances(swap.pair(), self.reserves)?;
 
         let out_amount_scaled18 = out_reserves.upscale(out_amount).ok()?;
         let in_scaled = reclamm_math::compute_in_given_out(
             &balances_scaled18,
             va,
             vb,
-            index_in,
-            index_out,
+            swap.index_in(),
+            swap.index_out(),
             out_amount_scaled18,
         )
         .ok()?;
@@ -1338,6 +1492,7 @@ impl ReClammPool {
             end_fourth_root_price_ratio: self.end_fourth_root_price_ratio,
             price_ratio_update_start_time: self.price_ratio_update_start_time,
             price_ratio_update_end_time: self.price_ratio_update_end_time,
+            current_timestamp: self.current_timestamp,
         }
     }
 }
@@ -1354,7 +1509,7 @@ impl BaselineSolvable for ReClammPool {
     }
 
     async fn gas_cost(&self) -> usize {
-        RECLAMM_SWAP_GAS_COST
+        gas::cost(gas::PoolKind::ReClamm)
     }
 }
 
@@ -1373,6 +1528,23 @@ pub struct QuantAmmPoolRef<'a> {
 }
 
 impl QuantAmmPoolRef<'_> {
+    /// Returns the reserves after the swap, see `apply_swap_to_reserves`.
+    pub fn apply_swap(
+        &self,
+        input: (U256, H160),
+        output: (U256, H160),
+        aggregate_swap_fee: Bfp,
+    ) -> Option<BTreeMap<H160, TokenState>> {
+        apply_swap_to_reserves(
+            self.reserves,
+            |state| &mut state.balance,
+            input,
+            output,
+            self.swap_fee,
+            aggregate_swap_fee,
+        )
+    }
+
     fn get_amount_out_inner(
         &self,
         out_token: H160,
@@ -1544,9 +1716,7 @@ impl BaselineSolvable for QuantAmmPoolRef<'_> {
     }
 
     async fn gas_cost(&self) -> usize {
-        // Approximate gas cost for QuantAMM swaps (higher than weighted due to weight
-        // calculations)
-        180_000
+        gas::cost(gas::PoolKind::QuantAmm)
     }
 }
 
@@ -1712,6 +1882,46 @@ mod tests {
         );
     }
 
+    #[test]
+    fn zero_swap_fee_keeps_amounts() {
+        let amount = U256::from(1_000_000_u64);
+        assert_eq!(add_swap_fee_amount(amount, Bfp::zero()).unwrap(), amount);
+        assert_eq!(
+            subtract_swap_fee_amount(amount, Bfp::zero()).unwrap(),
+            amount
+        );
+        assert_eq!(
+            add_swap_fee_amount(U256::MAX, Bfp::zero()).unwrap(),
+            U256::MAX
+        );
+    }
+
+    #[test]
+    fn rejects_swap_fees_of_100_percent_or_more() {
+        let amount = U256::from(1_000_000_u64);
+        for swap_fee in [Bfp::one(), Bfp::exp10(19)] {
+            assert_eq!(
+                add_swap_fee_amount(amount, swap_fee),
+                Err(Error::MaxSwapFeePercentage)
+            );
+            assert_eq!(
+                subtract_swap_fee_amount(amount, swap_fee),
+                Err(Error::MaxSwapFeePercentage)
+            );
+        }
+
+        // the highest representable fee below 100% still works
+        let swap_fee = Bfp::from_wei(U256::exp10(18) - 1);
+        assert_eq!(
+            subtract_swap_fee_amount(amount, swap_fee).unwrap(),
+            0.into()
+        );
+        assert_eq!(
+            add_swap_fee_amount(amount, swap_fee).unwrap(),
+            U256::from(1_000_000_u64) * U256::exp10(18)
+        );
+    }
+
     #[tokio::test]
     async fn weighted_get_amount_out() {
         // Values obtained from this transaction:
@@ -1759,6 +1969,130 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn weighted_apply_swap_charges_aggregate_fee() {
+        let weth = H160::repeat_byte(21);
+        let dai = H160::repeat_byte(42);
+        let pool = create_weighted_pool_with(
+            vec![weth, dai],
+            vec![U256::exp10(21), U256::exp10(24)],
+            vec![bfp_v3!("0.5"), bfp_v3!("0.5")],
+            vec![Bfp::exp10(0), Bfp::exp10(0)],
+            3_000_000_000_000_000_i128.into(),
+        );
+        let pool = pool.as_pool_ref();
+        let in_amount = U256::exp10(18);
+
+        // Two sequential swaps of 1 WETH, where the vault takes half of the
+        // 0.3% swap fee of each swap out of the pool.
+        let mut reserves = pool.reserves.clone();
+        let mut out_amounts = Vec::new();
+        for _ in 0..2 {
+            let pool = WeightedPoolRef {
+                reserves: &reserves,
+                ..pool
+            };
+            let out_amount = pool.get_amount_out(dai, (in_amount, weth)).await.unwrap();
+            reserves = pool
+                .apply_swap((in_amount, weth), (out_amount, dai), bfp_v3!("0.5"))
+                .unwrap();
+            out_amounts.push(out_amount);
+        }
+        assert_eq!(
+            reserves[&weth].common.balance,
+            U256::exp10(21) + (in_amount - 1_500_000_000_000_000_u64) * 2_u64
+        );
+        assert_eq!(
+            reserves[&dai].common.balance,
+            U256::exp10(24) - out_amounts[0] - out_amounts[1]
+        );
+
+        // Without the aggregate fee the full input stays in the pool, which
+        // makes the second swap worse.
+        let reserves = pool
+            .apply_swap((in_amount, weth), (out_amounts[0], dai), Bfp::zero())
+            .unwrap();
+        assert_eq!(reserves[&weth].common.balance, U256::exp10(21) + in_amount);
+        let swapped = WeightedPoolRef {
+            reserves: &reserves,
+            ..pool
+        };
+        assert!(
+            swapped
+                .get_amount_out(dai, (in_amount, weth))
+                .await
+                .unwrap()
+                < out_amounts[1]
+        );
+
+        assert!(
+            pool.apply_swap((in_amount, weth), (U256::exp10(25), dai), Bfp::zero())
+                .is_none()
+        );
+        assert!(
+            pool.apply_swap((in_amount, weth), (out_amounts[0], weth), Bfp::zero())
+                .is_none()
+        );
+    }
+
+    fn create_reclamm_pool(current_timestamp: u64) -> ReClammPool {
+        let reserves = [H160::repeat_byte(1), H160::repeat_byte(2)]
+            .into_iter()
+            .map(|token| {
+                let state = TokenState {
+                    balance: U256::exp10(21),
+                    scaling_factor: Bfp::exp10(0),
+                    rate: U256::exp10(18),
+                };
+                (token, state)
+            })
+            .collect();
+        ReClammPool {
+            common: CommonPoolState {
+                id: Default::default(),
+                address: H160::zero(),
+                swap_fee: Bfp::zero(),
+                paused: true,
+            },
+            reserves,
+            version: Default::default(),
+            last_virtual_balances: vec![U256::from(5_000) * U256::exp10(18); 2],
+            daily_price_shift_base: Bfp::one(),
+            last_timestamp: 1_000_000,
+            centeredness_margin: bfp_v3!("0.2"),
+            // The price ratio shrinks over the day after the last update,
+            // which grows the virtual balances.
+            start_fourth_root_price_ratio: bfp_v3!("1.2"),
+            end_fourth_root_price_ratio: bfp_v3!("1.1"),
+            price_ratio_update_start_time: 1_000_000,
+            price_ratio_update_end_time: 1_086_400,
+            current_timestamp,
+        }
+    }
+
+    #[tokio::test]
+    async fn reclamm_virtual_balances_drift_until_current_timestamp() {
+        let (token_in, token_out) = (H160::repeat_byte(1), H160::repeat_byte(2));
+        let input = (U256::exp10(19), token_in);
+
+        let stale = create_reclamm_pool(1_000_000)
+            .get_amount_out(token_out, input)
+            .await
+            .unwrap();
+        let current = create_reclamm_pool(1_086_400)
+            .get_amount_out(token_out, input)
+            .await
+            .unwrap();
+        assert!(current > stale);
+
+        // Timestamps before the last update don't move the virtual balances.
+        let unknown = create_reclamm_pool(0)
+            .get_amount_out(token_out, input)
+            .await
+            .unwrap();
+        assert_eq!(unknown, stale);
+    }
+
     #[test]
     fn construct_balances_and_token_indices() {
         let tokens: Vec<_> = (1..=3).map(H160::from_low_u64_be).collect();
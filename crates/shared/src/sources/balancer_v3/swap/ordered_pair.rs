@@ -0,0 +1,121 @@
+//! Canonical token ordering of two-token pools.
+//!
+//! Two-token pools like Gyro and ReClamm pools store their parameters and
+//! balances in token address order, while swaps are expressed in terms of an
+//! in and out token. Mapping between the two through these types instead of
+//! comparing addresses inline makes it impossible to mix up the in and out
+//! indices.
+
+use ethcontract::H160;
+
+/// Two distinct tokens sorted by address.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct OrderedPair([H160; 2]);
+
+impl OrderedPair {
+    /// Returns the ordered pair of the two tokens, or `None` if they are the
+    /// same token.
+    pub fn new(a: H160, b: H160) -> Option<Self> {
+        match a.cmp(&b) {
+            std::cmp::Ordering::Less => Some(Self([a, b])),
+            std::cmp::Ordering::Greater => Some(Self([b, a])),
+            std::cmp::Ordering::Equal => None,
+        }
+    }
+
+    pub fn token0(&self) -> H160 {
+        self.0[0]
+    }
+
+    pub fn token1(&self) -> H160 {
+        self.0[1]
+    }
+
+    /// Returns the index of the token in the pair, or `None` if the token is
+    /// not part of it.
+    pub fn index(&self, token: H160) -> Option<usize> {
+        self.0.iter().position(|t| *t == token)
+    }
+}
+
+/// A swap from one token of an ordered pair to the other.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OrderedSwap {
+    pair: OrderedPair,
+    token_in_is_token0: bool,
+}
+
+impl OrderedSwap {
+    /// Returns the swap from the in token to the out token, or `None` if they
+    /// are the same token.
+    pub fn new(in_token: H160, out_token: H160) -> Option<Self> {
+        let pair = OrderedPair::new(in_token, out_token)?;
+        Some(Self {
+            pair,
+            token_in_is_token0: pair.token0() == in_token,
+        })
+    }
+
+    pub fn pair(&self) -> OrderedPair {
+        self.pair
+    }
+
+    pub fn token_in_is_token0(&self) -> bool {
+        self.token_in_is_token0
+    }
+
+    /// The index of the in token in pair order.
+    pub fn index_in(&self) -> usize {
+        if self.token_in_is_token0 { 0 } else { 1 }
+    }
+
+    /// The index of the out token in pair order.
+    pub fn index_out(&self) -> usize {
+        1 - self.index_in()
+    }
+
+    /// Arranges values of the in and out token, e.g. balances, in pair order.
+    pub fn arrange<T>(&self, in_value: T, out_value: T) -> [T; 2] {
+        if self.token_in_is_token0 {
+            [in_value, out_value]
+        } else {
+            [out_value, in_value]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn orders_tokens_by_address() {
+        let (low, high) = (H160::from_low_u64_be(1), H160::from_low_u64_be(2));
+        for (a, b) in [(low, high), (high, low)] {
+            let pair = OrderedPair::new(a, b).unwrap();
+            assert_eq!((pair.token0(), pair.token1()), (low, high));
+            assert_eq!(pair.index(low), Some(0));
+            assert_eq!(pair.index(high), Some(1));
+            assert_eq!(pair.index(H160::from_low_u64_be(3)), None);
+        }
+        assert_eq!(OrderedPair::new(low, low), None);
+    }
+
+    #[test]
+    fn maps_swap_direction_to_indices() {
+        let (low, high) = (H160::from_low_u64_be(1), H160::from_low_u64_be(2));
+
+        let swap = OrderedSwap::new(low, high).unwrap();
+        assert!(swap.token_in_is_token0());
+        assert_eq!((swap.index_in(), swap.index_out()), (0, 1));
+        assert_eq!(swap.arrange("in", "out"), ["in", "out"]);
+
+        let swap = OrderedSwap::new(high, low).unwrap();
+        assert!(!swap.token_in_is_token0());
+        assert_eq!((swap.index_in(), swap.index_out()), (1, 0));
+        assert_eq!(swap.arrange("in", "out"), ["out", "in"]);
+        assert_eq!(swap.pair(), OrderedPair::new(low, high).unwrap());
+
+        assert_eq!(OrderedSwap::new(low, low), None);
+    }
+}
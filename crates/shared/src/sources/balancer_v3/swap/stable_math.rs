@@ -11,6 +11,9 @@ use {
 
 pub static AMP_PRECISION: LazyLock<U256> = LazyLock::new(|| U256::from(1000));
 
+/// Computes the balance of a token given the invariant and all other balances.
+type ComputeBalance = fn(U256, &[Bfp], U256, usize) -> Result<Bfp, Error>;
+
 /// https://github.com/balancer-labs/balancer-v2-monorepo/blob/9eb7e44a4e9ebbadfe3c6242a086118298cadc9f/pkg/pool-stable-phantom/contracts/StableMath.sol#L57-L119
 fn calculate_invariant(amplification_parameter: U256, balances: &[Bfp]) -> Result<U256, Error> {
     let mut sum = U256::zero();
@@ -65,6 +68,44 @@ pub fn calc_out_given_in(
     token_index_in: usize,
     token_index_out: usize,
     token_amount_in: Bfp,
+) -> Result<Bfp, Error> {
+    out_given_in(
+        amplification_parameter,
+        balances,
+        token_index_in,
+        token_index_out,
+        token_amount_in,
+        get_token_balance_given_invariant_and_all_other_balances,
+    )
+}
+
+/// Like [`calc_out_given_in`], but rounding intermediate results exactly like
+/// the Balancer V3 `StableMath` library:
+/// https://github.com/balancer/balancer-v3-monorepo/blob/main/pkg/solidity-utils/contracts/math/StableMath.sol
+pub fn compute_out_given_exact_in(
+    amplification_parameter: U256,
+    balances: &mut [Bfp],
+    token_index_in: usize,
+    token_index_out: usize,
+    token_amount_in: Bfp,
+) -> Result<Bfp, Error> {
+    out_given_in(
+        amplification_parameter,
+        balances,
+        token_index_in,
+        token_index_out,
+        token_amount_in,
+        compute_balance,
+    )
+}
+
+fn out_given_in(
+    amplification_parameter: U256,
+    balances: &mut [Bfp],
+    token_index_in: usize,
+    token_index_out: usize,
+    token_amount_in: Bfp,
+    compute_balance: ComputeBalance,
 ) -> Result<Bfp, Error> {
     // Ensure no index error at token indices provided.
     if token_index_out >= balances.len() || token_index_in >= balances.len() {
@@ -73,7 +114,7 @@ pub fn calc_out_given_in(
     let invariant = calculate_invariant(amplification_parameter, balances)?;
     balances[token_index_in] = balances[token_index_in].add(token_amount_in)?;
 
-    let final_balance_out = get_token_balance_given_invariant_and_all_other_balances(
+    let final_balance_out = compute_balance(
         amplification_parameter,
         balances,
         invariant,
@@ -99,6 +140,43 @@ pub fn calc_in_given_out(
     token_index_in: usize,
     token_index_out: usize,
     token_amount_out: Bfp,
+) -> Result<Bfp, Error> {
+    in_given_out(
+        amplification_parameter,
+        balances,
+        token_index_in,
+        token_index_out,
+        token_amount_out,
+        get_token_balance_given_invariant_and_all_other_balances,
+    )
+}
+
+/// Like [`calc_in_given_out`], but rounding intermediate results exactly like
+/// the Balancer V3 `StableMath` library.
+pub fn compute_in_given_exact_out(
+    amplification_parameter: U256,
+    balances: &mut [Bfp],
+    token_index_in: usize,
+    token_index_out: usize,
+    token_amount_out: Bfp,
+) -> Result<Bfp, Error> {
+    in_given_out(
+        amplification_parameter,
+        balances,
+        token_index_in,
+        token_index_out,
+        token_amount_out,
+        compute_balance,
+    )
+}
+
+fn in_given_out(
+    amplification_parameter: U256,
+    balances: &mut [Bfp],
+    token_index_in: usize,
+    token_index_out: usize,
+    token_amount_out: Bfp,
+    compute_balance: ComputeBalance,
 ) -> Result<Bfp, Error> {
     // Ensure no index error at token indices provided.
     if token_index_out >= balances.len() || token_index_in >= balances.len() {
@@ -107,12 +185,8 @@ pub fn calc_in_given_out(
     let invariant = calculate_invariant(amplification_parameter, balances)?;
     balances[token_index_out] = balances[token_index_out].sub(token_amount_out)?;
 
-    let final_balance_in = get_token_balance_given_invariant_and_all_other_balances(
-        amplification_parameter,
-        balances,
-        invariant,
-        token_index_in,
-    )?;
+    let final_balance_in =
+        compute_balance(amplification_parameter, balances, invariant, token_index_in)?;
 
     // No need to use checked arithmetic since `tokenAmountOut` was actually
     // subtracted from the same balance right before calling
@@ -189,6 +263,51 @@ fn get_token_balance_given_invariant_and_all_other_balances(
     Err(Error::StableGetBalanceDidntConverge)
 }
 
+/// Balancer V3 `StableMath.computeBalance`. It differs from the V2 version
+/// above in how `c` and `b` get rounded, which changes the computed balances
+/// by a few wei.
+fn compute_balance(
+    amplification_parameter: U256,
+    balances: &[Bfp],
+    invariant: U256,
+    token_index: usize,
+) -> Result<Bfp, Error> {
+    let num_tokens = U256::from(balances.len());
+    let amp_times_total = amplification_parameter.bmul(num_tokens)?;
+    let mut sum = balances[0].as_uint256();
+    let mut p_d = sum.bmul(num_tokens)?;
+    for balance_j in &balances[1..] {
+        p_d = p_d
+            .bmul(balance_j.as_uint256())?
+            .bmul(num_tokens)?
+            .bdiv_down(invariant)?;
+        sum = sum.badd(balance_j.as_uint256())?;
+    }
+    sum -= balances[token_index].as_uint256();
+    let inv2 = invariant.bmul(invariant)?;
+    // uint256 c = (inv2 * AMP_PRECISION).divUpRaw(ampTimesTotal * P_D) *
+    //     balances[tokenIndex];
+    let c = inv2
+        .bmul(*AMP_PRECISION)?
+        .bdiv_up(amp_times_total.bmul(p_d)?)?
+        .bmul(balances[token_index].as_uint256())?;
+    // uint256 b = sum + ((invariant * AMP_PRECISION) / ampTimesTotal);
+    let b = sum.badd(invariant.bmul(*AMP_PRECISION)?.bdiv_down(amp_times_total)?)?;
+    let mut token_balance = inv2.badd(c)?.bdiv_up(invariant.badd(b)?)?;
+    for _ in 0..255 {
+        let prev_token_balance = token_balance;
+        token_balance = token_balance
+            .bmul(token_balance)?
+            .badd(c)?
+            .bdiv_up(token_balance.bmul(2.into())?.badd(b)?.bsub(invariant)?)?;
+        match convergence_criteria(token_balance, prev_token_balance) {
+            None => continue,
+            Some(token_balance) => return Ok(Bfp::from_wei(token_balance)),
+        }
+    }
+    Err(Error::StableGetBalanceDidntConverge)
+}
+
 fn convergence_criteria(curr_value: U256, prev_value: U256) -> Option<U256> {
     let one = U256::one();
     if curr_value > prev_value {
@@ -10,7 +10,7 @@ use {
         error::Error,
         fixed_point::Bfp,
         math::BalU256,
-        stable_math::{calc_in_given_out, calc_out_given_in},
+        stable_math::{compute_in_given_exact_out, compute_out_given_exact_in},
     },
     ethcontract::U256,
 };
@@ -52,7 +52,7 @@ impl StableSurgePoolState {
         // Step 1: First, do a "preview" swap to see what the new balances would be
         // after the swap
         let mut balances_preview = self.balances.clone();
-        let preview_amount_out = calc_out_given_in(
+        let preview_amount_out = compute_out_given_exact_in(
             self.amplification_parameter,
             &mut balances_preview,
             token_index_in,
@@ -76,7 +76,7 @@ impl StableSurgePoolState {
         let amount_in_after_fee = token_amount_in.sub(fee_amount)?;
 
         // Step 5: Calculate final result with fee-adjusted input
-        let final_amount_out = calc_out_given_in(
+        let final_amount_out = compute_out_given_exact_in(
             self.amplification_parameter,
             &mut self.balances.clone(),
             token_index_in,
@@ -98,7 +98,7 @@ impl StableSurgePoolState {
         token_amount_out: Bfp,
     ) -> Result<StableSurgeSwapResult, Error> {
         // Step 1: Perform the base swap to get amount in before fees
-        let amount_in_before_fee = calc_in_given_out(
+        let amount_in_before_fee = compute_in_given_exact_out(
             self.amplification_parameter,
             &mut self.balances.clone(),
             token_index_in,
@@ -424,16 +424,7 @@ mod tests {
             .await
             .unwrap();
 
-        let expected = U256::from(78522716365403684u64);
-        let tolerance = expected / U256::from(100000); // 0.001% tolerance
-        assert!(
-            result.abs_diff(expected) <= tolerance,
-            "Expected: {}, Got: {}, Diff: {}, Tolerance: {}",
-            expected,
-            result,
-            result.abs_diff(expected),
-            tolerance
-        );
+        assert_eq!(result, U256::from(78522716365403684u64));
     }
 
     #[tokio::test]
@@ -448,16 +439,7 @@ mod tests {
             .await
             .unwrap();
 
-        let expected = U256::from(452983383563178802u64);
-        let tolerance = expected / U256::from(100000); // 0.001% tolerance
-        assert!(
-            result.abs_diff(expected) <= tolerance,
-            "Expected: {}, Got: {}, Diff: {}, Tolerance: {}",
-            expected,
-            result,
-            result.abs_diff(expected),
-            tolerance
-        );
+        assert_eq!(result, U256::from(452983383563178802u64));
     }
 
     #[tokio::test]
@@ -472,16 +454,7 @@ mod tests {
             .await
             .unwrap();
 
-        let expected = U256::from(3252130027531260u64);
-        let tolerance = expected / U256::from(100000); // 0.001% tolerance
-        assert!(
-            result.abs_diff(expected) <= tolerance,
-            "Expected: {}, Got: {}, Diff: {}, Tolerance: {}",
-            expected,
-            result,
-            result.abs_diff(expected),
-            tolerance
-        );
+        assert_eq!(result, U256::from(3252130027531260u64));
     }
 
     #[tokio::test]
@@ -547,6 +520,45 @@ mod tests {
         assert_eq!(result, U256::from(37594448u64));
     }
 
+    #[tokio::test]
+    async fn surging_exact_out_swaps_sell_the_smallest_sufficient_amount() {
+        let pool = create_stable_surge_pool_ts1();
+        let token_in = H160::from_low_u64_be(2);
+        let token_out = H160::from_low_u64_be(1);
+        let amount_out = U256::from(1000000000000000u64);
+
+        // Taking the scarce token out surges the fee, which is higher when
+        // selling the closed form exact out amount because of the fee itself.
+        let closed_form = pool
+            .as_pool_ref()
+            .regular_swap_given_out(token_in, (amount_out, token_out))
+            .unwrap();
+        assert!(
+            pool.get_amount_out(token_out, (closed_form, token_in))
+                .await
+                .unwrap()
+                < amount_out
+        );
+
+        let amount_in = pool
+            .get_amount_in(token_in, (amount_out, token_out))
+            .await
+            .unwrap();
+        assert!(amount_in > closed_form);
+        assert!(
+            pool.get_amount_out(token_out, (amount_in, token_in))
+                .await
+                .unwrap()
+                >= amount_out
+        );
+        assert!(
+            pool.get_amount_out(token_out, (amount_in - 1, token_in))
+                .await
+                .unwrap()
+                < amount_out
+        );
+    }
+
     #[tokio::test]
     async fn test_stable_surge_ts3_should_throw_error() {
         let pool = create_stable_surge_pool_ts3();
@@ -0,0 +1,392 @@
+//! Per-chain defaults of the baseline liquidity sources.
+//!
+//! Every supported chain has one profile listing the Balancer factories
+//! deployed on it, the default Balancer API URL, the native token wrapper and
+//! the tokens liquidity should always be fetched for. Supporting a new chain
+//! only requires adding its profile to [`PROFILES`].
+
+use {
+    crate::{
+        addr,
+        sources::{balancer_v2, balancer_v3},
+    },
+    ethcontract::H160,
+    reqwest::Url,
+};
+
+/// The Balancer API serving the pools of both Balancer V2 and V3 on all
+/// chains it supports.
+const BALANCER_API_URL: &str = "https://api-v3.balancer.fi/";
+
+/// The defaults of a single chain.
+#[derive(Debug)]
+pub struct ChainProfile {
+    pub chain_id: u64,
+    /// The wrapped native token, e.g. WETH on Mainnet.
+    pub native_wrapper: H160,
+    /// Tokens for which liquidity is always fetched, including the native
+    /// token wrapper.
+    pub base_tokens: &'static [H160],
+    /// The URL of the API indexing the Balancer pools, if any.
+    balancer_api_url: Option<&'static str>,
+    pub balancer_v2_factories: &'static [balancer_v2::BalancerFactoryKind],
+    pub balancer_v3_factories: &'static [balancer_v3::BalancerFactoryKind],
+}
+
+impl ChainProfile {
+    /// Returns the profile of the specified chain ID, if the chain is
+    /// supported.
+    pub fn for_chain(chain_id: u64) -> Option<&'static Self> {
+        PROFILES.iter().find(|profile| profile.chain_id == chain_id)
+    }
+
+    /// The default URL to fetch the Balancer pools of the chain from.
+    pub fn balancer_api_url(&self) -> Option<Url> {
+        self.balancer_api_url
+            .map(|url| url.parse().expect("profile URLs are valid"))
+    }
+}
+
+const MAINNET_WETH: H160 = addr!("c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2");
+const GNOSIS_WXDAI: H160 = addr!("e91d153e0b41518a2ce8dd3d7944fa863463a97d");
+const SEPOLIA_WETH: H160 = addr!("fff9976782d46cc05630d1f6ebab18b2324d6b14");
+const ARBITRUM_WETH: H160 = addr!("82af49447d8a07e3bd95bd0d56f35241523fbab1");
+const OP_STACK_WETH: H160 = addr!("4200000000000000000000000000000000000006");
+const AVALANCHE_WAVAX: H160 = addr!("b31f66aa3c1e785363f0875a1b74e27b85fd66c7");
+const BNB_WBNB: H160 = addr!("bb4cdb9cbd36b01bd1cbaebf2de08d9173bc095c");
+const POLYGON_WPOL: H160 = addr!("0d500b1d8e8ef31e21c99d1db9a6444d3adf1270");
+
+/// The profiles of all supported chains.
+pub const PROFILES: &[ChainProfile] = {
+    use {
+        balancer_v2::BalancerFactoryKind as V2,
+        balancer_v3::BalancerFactoryKind as V3,
+    };
+
+    &[
+        ChainProfile {
+            chain_id: 1,
+            native_wrapper: MAINNET_WETH,
+            base_tokens: &[
+                MAINNET_WETH,
+                // DAI
+                addr!("6b175474e89094c44da98b954eedeac495271d0f"),
+                // USDC
+                addr!("a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"),
+                // USDT
+                addr!("dac17f958d2ee523a2206206994597c13d831ec7"),
+                // WBTC
+                addr!("2260fac5e5542a773aa44fbcfedf7c193bc2c599"),
+            ],
+            balancer_api_url: Some(BALANCER_API_URL),
+            balancer_v2_factories: &[
+                V2::Weighted,
+                V2::WeightedV3,
+                V2::WeightedV4,
+                V2::Weighted2Token,
+                V2::StableV2,
+                V2::LiquidityBootstrapping,
+                V2::NoProtocolFeeLiquidityBootstrapping,
+                V2::ComposableStable,
+                V2::ComposableStableV3,
+                V2::ComposableStableV4,
+                V2::ComposableStableV5,
+                V2::ComposableStableV6,
+                V2::GyroE,
+            ],
+            balancer_v3_factories: &[
+                V3::Weighted,
+                V3::Stable,
+                V3::StableV2,
+                V3::StableSurge,
+                V3::StableSurgeV2,
+                V3::Gyro2CLP,
+                V3::GyroE,
+                V3::ReClamm,
+                V3::QuantAmm,
+            ],
+        },
+        ChainProfile {
+            chain_id: 10,
+            native_wrapper: OP_STACK_WETH,
+            base_tokens: &[
+                OP_STACK_WETH,
+                // USDC
+                addr!("0b2c639c533813f4aa9d7837caf62653d097ff85"),
+                // USDT
+                addr!("94b008aa00579c1307b0ef2c499ad98a8ce58e58"),
+            ],
+            balancer_api_url: Some(BALANCER_API_URL),
+            balancer_v2_factories: &[
+                V2::Weighted,
+                V2::WeightedV3,
+                V2::WeightedV4,
+                V2::Weighted2Token,
+                V2::StableV2,
+                V2::NoProtocolFeeLiquidityBootstrapping,
+                V2::ComposableStable,
+                V2::ComposableStableV3,
+                V2::ComposableStableV4,
+                V2::ComposableStableV5,
+                V2::ComposableStableV6,
+                V2::GyroE,
+            ],
+            balancer_v3_factories: &[
+                V3::Weighted,
+                V3::Stable,
+                V3::StableV2,
+                V3::StableSurge,
+                V3::StableSurgeV2,
+                V3::Gyro2CLP,
+                V3::GyroE,
+                V3::ReClamm,
+            ],
+        },
+        ChainProfile {
+            chain_id: 56,
+            native_wrapper: BNB_WBNB,
+            base_tokens: &[
+                BNB_WBNB,
+                // USDT
+                addr!("55d398326f99059ff775485246999027b3197955"),
+                // USDC
+                addr!("8ac76a51cc950d9822d68b83fe1ad97b32cd580d"),
+            ],
+            balancer_api_url: Some(BALANCER_API_URL),
+            balancer_v2_factories: &[
+                V2::WeightedV3,
+                V2::WeightedV4,
+                V2::NoProtocolFeeLiquidityBootstrapping,
+                V2::ComposableStable,
+                V2::ComposableStableV3,
+                V2::ComposableStableV4,
+                V2::ComposableStableV5,
+                V2::ComposableStableV6,
+            ],
+            balancer_v3_factories: &[],
+        },
+        ChainProfile {
+            chain_id: 100,
+            native_wrapper: GNOSIS_WXDAI,
+            base_tokens: &[
+                GNOSIS_WXDAI,
+                // USDC
+                addr!("ddafbb505ad214d7b80b1f830fccc89b60fb7a83"),
+                // WETH
+                addr!("6a023ccd1ff6f2045c3309768ead9e68f978f6e1"),
+                // GNO
+                addr!("9c58bacc331c9aa871afd802db6379a98e80cedb"),
+            ],
+            balancer_api_url: Some(BALANCER_API_URL),
+            balancer_v2_factories: &[
+                V2::WeightedV3,
+                V2::WeightedV4,
+                V2::StableV2,
+                V2::NoProtocolFeeLiquidityBootstrapping,
+                V2::ComposableStableV3,
+                V2::ComposableStableV4,
+                V2::ComposableStableV5,
+                V2::ComposableStableV6,
+                V2::GyroE,
+            ],
+            balancer_v3_factories: &[
+                V3::Weighted,
+                V3::Stable,
+                V3::StableV2,
+                V3::StableSurge,
+                V3::StableSurgeV2,
+                V3::Gyro2CLP,
+                V3::GyroE,
+                V3::ReClamm,
+            ],
+        },
+        ChainProfile {
+            chain_id: 137,
+            native_wrapper: POLYGON_WPOL,
+            base_tokens: &[
+                POLYGON_WPOL,
+                // USDC
+                addr!("3c499c542cef5e3811e1192ce70d8cc03d5c3359"),
+                // USDT
+                addr!("c2132d05d31c914a87c6611c10748aeb04b58e8f"),
+                // WETH
+                addr!("7ceb23fd6bc0add59e62ac25578270cff1b9f619"),
+            ],
+            balancer_api_url: Some(BALANCER_API_URL),
+            balancer_v2_factories: &[
+                V2::Weighted,
+                V2::WeightedV3,
+                V2::WeightedV4,
+                V2::Weighted2Token,
+                V2::StableV2,
+                V2::LiquidityBootstrapping,
+                V2::NoProtocolFeeLiquidityBootstrapping,
+                V2::ComposableStable,
+                V2::ComposableStableV3,
+                V2::ComposableStableV4,
+                V2::ComposableStableV5,
+                V2::ComposableStableV6,
+                V2::Gyro2CLP,
+                V2::Gyro3CLP,
+                V2::GyroE,
+            ],
+            balancer_v3_factories: &[],
+        },
+        ChainProfile {
+            chain_id: 8453,
+            native_wrapper: OP_STACK_WETH,
+            base_tokens: &[
+                OP_STACK_WETH,
+                // USDC
+                addr!("833589fcd6edb6e08f4c7c32d4f71b54bda02913"),
+            ],
+            balancer_api_url: Some(BALANCER_API_URL),
+            balancer_v2_factories: &[
+                V2::WeightedV4,
+                V2::NoProtocolFeeLiquidityBootstrapping,
+                V2::ComposableStableV5,
+                V2::ComposableStableV6,
+                V2::GyroE,
+            ],
+            balancer_v3_factories: &[
+                V3::Weighted,
+                V3::Stable,
+                V3::StableV2,
+                V3::StableSurge,
+                V3::StableSurgeV2,
+                V3::Gyro2CLP,
+                V3::GyroE,
+                V3::ReClamm,
+                V3::QuantAmm,
+            ],
+        },
+        ChainProfile {
+            chain_id: 42161,
+            native_wrapper: ARBITRUM_WETH,
+            base_tokens: &[
+                ARBITRUM_WETH,
+                // USDC
+                addr!("af88d065e77c8cc2239327c5edb3a432268e5831"),
+                // USDT
+                addr!("fd086bc7cd5c481dcc9c85ebe478a1c0b69fcbb9"),
+                // WBTC
+                addr!("2f2a2543b76a4166549f7aab2e75bef0aefc5b0f"),
+            ],
+            balancer_api_url: Some(BALANCER_API_URL),
+            balancer_v2_factories: &[
+                V2::Weighted,
+                V2::WeightedV3,
+                V2::WeightedV4,
+                V2::Weighted2Token,
+                V2::StableV2,
+                V2::LiquidityBootstrapping,
+                V2::NoProtocolFeeLiquidityBootstrapping,
+                V2::ComposableStable,
+                V2::ComposableStableV3,
+                V2::ComposableStableV4,
+                V2::ComposableStableV5,
+                V2::ComposableStableV6,
+                V2::Gyro2CLP,
+                V2::GyroE,
+            ],
+            balancer_v3_factories: &[
+                V3::Weighted,
+                V3::Stable,
+                V3::StableV2,
+                V3::StableSurge,
+                V3::StableSurgeV2,
+                V3::Gyro2CLP,
+                V3::GyroE,
+                V3::ReClamm,
+                V3::QuantAmm,
+            ],
+        },
+        ChainProfile {
+            chain_id: 43114,
+            native_wrapper: AVALANCHE_WAVAX,
+            base_tokens: &[
+                AVALANCHE_WAVAX,
+                // USDC
+                addr!("b97ef9ef8734c71904d8002f8b6bc66dd9c48a6e"),
+                // USDT
+                addr!("9702230a8ea53601f5cd2dc00fdbc13d4df4a8c7"),
+            ],
+            balancer_api_url: Some(BALANCER_API_URL),
+            balancer_v2_factories: &[
+                V2::WeightedV3,
+                V2::WeightedV4,
+                V2::NoProtocolFeeLiquidityBootstrapping,
+                V2::ComposableStableV4,
+                V2::ComposableStableV5,
+                V2::ComposableStableV6,
+                V2::GyroE,
+            ],
+            balancer_v3_factories: &[
+                V3::Weighted,
+                V3::Stable,
+                V3::StableV2,
+                V3::StableSurge,
+                V3::StableSurgeV2,
+                V3::Gyro2CLP,
+                V3::GyroE,
+                V3::ReClamm,
+            ],
+        },
+        ChainProfile {
+            chain_id: 11155111,
+            native_wrapper: SEPOLIA_WETH,
+            base_tokens: &[SEPOLIA_WETH],
+            balancer_api_url: Some(BALANCER_API_URL),
+            balancer_v2_factories: &[
+                V2::WeightedV4,
+                V2::NoProtocolFeeLiquidityBootstrapping,
+                V2::ComposableStableV4,
+                V2::ComposableStableV5,
+                V2::ComposableStableV6,
+            ],
+            balancer_v3_factories: &[
+                V3::Weighted,
+                V3::Stable,
+                V3::StableV2,
+                V3::StableSurge,
+                V3::StableSurgeV2,
+                V3::Gyro2CLP,
+                V3::GyroE,
+                V3::ReClamm,
+                V3::QuantAmm,
+            ],
+        },
+    ]
+};
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        contracts::alloy::WETH9,
+        ethrpc::alloy::conversions::IntoLegacy,
+        std::collections::HashSet,
+    };
+
+    #[test]
+    fn profiles_are_consistent() {
+        let mut chain_ids = HashSet::new();
+        for profile in PROFILES {
+            assert!(chain_ids.insert(profile.chain_id), "{profile:?}");
+            assert_eq!(
+                WETH9::deployment_address(&profile.chain_id).map(IntoLegacy::into_legacy),
+                Some(profile.native_wrapper),
+                "{profile:?}",
+            );
+            assert!(profile.base_tokens.contains(&profile.native_wrapper));
+            assert!(profile.balancer_api_url().is_some());
+        }
+    }
+
+    #[test]
+    fn unsupported_chains_have_no_defaults() {
+        assert!(ChainProfile::for_chain(31337).is_none());
+        assert!(balancer_v2::BalancerFactoryKind::for_chain(31337).is_empty());
+        assert!(balancer_v3::BalancerFactoryKind::for_chain(31337).is_empty());
+    }
+}
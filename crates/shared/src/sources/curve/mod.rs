@@ -0,0 +1,205 @@
+//! Curve StableSwap liquidity.
+//!
+//! Curve lists its StableSwap pools in a registry. The pools, including
+//! metapools which pair tokens with the LP token of a base pool, are quoted
+//! off-chain with the StableSwap invariant.
+//!
+//! CryptoSwap pools are not supported: they repeg their price scale as they
+//! get traded against, so they can only be quoted with eth_calls to the pools,
+//! which the solver engines don't do.
+
+pub mod pool_fetching;
+pub mod stable_swap;
+
+use {
+    self::stable_swap::{FEE_DENOMINATOR, PRECISION},
+    crate::baseline_solver::BaselineSolvable,
+    ethcontract::{H160, U256},
+    ethrpc::alloy::conversions::IntoAlloy,
+    hex_literal::hex,
+    itertools::Itertools,
+    model::TokenPair,
+};
+
+/// The address Curve pools use for native ETH.
+pub const ETH: H160 = H160(hex!("EeeeeEeeeEeEeeEeEeEeeEEEeeeeEeeeeeeeEEeE"));
+
+/// The state of a StableSwap pool.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Pool {
+    pub address: H160,
+    pub coins: Vec<H160>,
+    pub balances: Vec<U256>,
+    /// The rates normalizing the balances of the coins to 18 decimals. Coins
+    /// that accrue value, like lending pool tokens or the LP token of the base
+    /// pool of a metapool, are additionally scaled by their exchange rate.
+    pub rates: Vec<U256>,
+    /// The amplification parameter multiplied by
+    /// [`stable_swap::A_PRECISION`].
+    pub amplification: U256,
+    /// The swap fee relative to [`stable_swap::FEE_DENOMINATOR`].
+    pub fee: U256,
+    /// The base pool of metapools.
+    pub base_pool: Option<H160>,
+}
+
+impl Pool {
+    /// Rough estimate of an `exchange` of a StableSwap pool.
+    pub const POOL_SWAP_GAS_COST: usize = 130_000;
+
+    /// Returns the indices of the coins swapped, or `None` if the pool doesn't
+    /// trade the tokens.
+    pub fn indices(&self, in_token: H160, out_token: H160) -> Option<(usize, usize)> {
+        let i = self.coins.iter().position(|coin| *coin == in_token)?;
+        let j = self.coins.iter().position(|coin| *coin == out_token)?;
+        (i != j).then_some((i, j))
+    }
+
+    /// The token pairs traded by the pool. Native ETH can't be traded by the
+    /// settlement contract, so pairs with it are skipped.
+    pub fn token_pairs(&self) -> Vec<TokenPair> {
+        self.coins
+            .iter()
+            .filter(|coin| **coin != ETH)
+            .tuple_combinations()
+            .filter_map(|(a, b)| TokenPair::new(a.into_alloy(), b.into_alloy()))
+            .collect()
+    }
+
+    /// Computes the amount of coin `j` received for `dx` of coin `i`, the way
+    /// the `get_dy` function of the pool does.
+    pub fn get_dy(&self, i: usize, j: usize, dx: U256) -> Option<U256> {
+        let xp = stable_swap::xp(&self.balances, &self.rates)?;
+        let x = xp.get(i)?.checked_add(
+            dx.checked_mul(self.rates[i])?
+                .checked_div(PRECISION.into())?,
+        )?;
+        let y = stable_swap::get_y(i, j, x, &xp, self.amplification)?;
+        let dy = xp[j].checked_sub(y)?.checked_sub(U256::one())?;
+        let fee = self
+            .fee
+            .checked_mul(dy)?
+            .checked_div(FEE_DENOMINATOR.into())?;
+        dy.checked_sub(fee)?
+            .checked_mul(PRECISION.into())?
+            .checked_div(self.rates[j])
+    }
+
+    /// Computes the amount of coin `i` needed to receive at least `dy` of coin
+    /// `j`. Amounts are rounded in favour of the pool, so the result may
+    /// exceed the exact amount by a few wei.
+    pub fn get_dx(&self, i: usize, j: usize, dy: U256) -> Option<U256> {
+        let xp = stable_swap::xp(&self.balances, &self.rates)?;
+        let dy = ceil_div(dy.checked_mul(*self.rates.get(j)?)?, PRECISION.into())?;
+        let dy = ceil_div(
+            dy.checked_mul(FEE_DENOMINATOR.into())?,
+            U256::from(FEE_DENOMINATOR).checked_sub(self.fee)?,
+        )?;
+        let y = xp.get(j)?.checked_sub(dy)?.checked_sub(U256::one())?;
+        let x = stable_swap::get_y(j, i, y, &xp, self.amplification)?;
+        ceil_div(
+            x.checked_sub(xp[i])?.checked_mul(PRECISION.into())?,
+            self.rates[i],
+        )?
+        .checked_add(U256::one())
+    }
+}
+
+fn ceil_div(a: U256, b: U256) -> Option<U256> {
+    if b.is_zero() {
+        return None;
+    }
+    a.checked_add(b - 1)?.checked_div(b)
+}
+
+impl BaselineSolvable for Pool {
+    async fn get_amount_out(
+        &self,
+        out_token: H160,
+        (in_amount, in_token): (U256, H160),
+    ) -> Option<U256> {
+        let (i, j) = self.indices(in_token, out_token)?;
+        self.get_dy(i, j, in_amount)
+    }
+
+    async fn get_amount_in(
+        &self,
+        in_token: H160,
+        (out_amount, out_token): (U256, H160),
+    ) -> Option<U256> {
+        let (i, j) = self.indices(in_token, out_token)?;
+        self.get_dx(i, j, out_amount)
+    }
+
+    async fn gas_cost(&self) -> usize {
+        Self::POOL_SWAP_GAS_COST
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A pool like the 3pool with DAI, USDC and USDT balances of 1M, 1.2M and
+    /// 800k.
+    fn pool() -> Pool {
+        Pool {
+            address: H160::from_low_u64_be(1),
+            coins: vec![
+                H160::from_low_u64_be(2),
+                H160::from_low_u64_be(3),
+                H160::from_low_u64_be(4),
+            ],
+            balances: vec![
+                U256::exp10(24),
+                U256::from(1_200_000) * U256::exp10(6),
+                U256::from(800_000) * U256::exp10(6),
+            ],
+            rates: vec![U256::exp10(18), U256::exp10(30), U256::exp10(30)],
+            amplification: U256::from(2000 * stable_swap::A_PRECISION),
+            fee: U256::from(1_000_000),
+            base_pool: None,
+        }
+    }
+
+    #[test]
+    fn computes_amounts_out() {
+        // Computed with a Python port of the pool's Vyper code.
+        assert_eq!(
+            pool().get_dy(0, 1, U256::from(1000) * U256::exp10(18)),
+            Some(999_986_308.into())
+        );
+        assert_eq!(pool().get_dy(0, 0, 1.into()), None);
+    }
+
+    #[test]
+    fn computes_amounts_in() {
+        let pool = pool();
+        assert_eq!(
+            pool.get_dx(2, 0, U256::from(500) * U256::exp10(18)),
+            Some(499_985_111.into())
+        );
+        for (i, j, dy) in [
+            (0, 1, U256::from(999_986_308)),
+            (1, 2, U256::from(1)),
+            (2, 0, U256::from(123_456) * U256::exp10(15)),
+        ] {
+            let dx = pool.get_dx(i, j, dy).unwrap();
+            assert!(pool.get_dy(i, j, dx).unwrap() >= dy);
+        }
+    }
+
+    #[test]
+    fn finds_coin_indices() {
+        let pool = pool();
+        assert_eq!(
+            pool.indices(H160::from_low_u64_be(4), H160::from_low_u64_be(2)),
+            Some((2, 0))
+        );
+        assert_eq!(pool.indices(ETH, H160::from_low_u64_be(2)), None);
+        assert_eq!(
+            pool.indices(H160::from_low_u64_be(2), H160::from_low_u64_be(2)),
+            None
+        );
+    }
+}
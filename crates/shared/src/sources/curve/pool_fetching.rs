@@ -0,0 +1,269 @@
+//! Listing of the pools of the Curve registry and fetching of their state.
+//!
+//! The registry only ever appends pools, so pools get listed once, starting
+//! at the number of pools listed by the previous maintenance run. The state of
+//! the pools is read from the registry at the block it is fetched for.
+
+use {
+    super::{ETH, Pool, stable_swap::A_PRECISION},
+    crate::{maintenance::Maintaining, recent_block_cache::Block},
+    alloy::{eips::BlockId, primitives::Address},
+    anyhow::{Context, Result, ensure},
+    contracts::alloy::{CurveRegistry, CurveStableSwapPool},
+    ethcontract::{H160, U256},
+    ethrpc::alloy::conversions::{IntoAlloy, IntoLegacy},
+    futures::future::{join_all, try_join_all},
+    itertools::Itertools,
+    model::TokenPair,
+    std::collections::{HashMap, HashSet},
+    tokio::sync::Mutex,
+};
+
+#[derive(Clone, Debug)]
+struct Listed {
+    coins: Vec<H160>,
+    /// The base pool of metapools.
+    base_pool: Option<H160>,
+}
+
+/// The pools listed in the registry.
+#[derive(Default)]
+pub struct PoolStorage {
+    pools: HashMap<H160, Listed>,
+    pools_by_token_pair: HashMap<TokenPair, HashSet<H160>>,
+    /// The number of pools listed from the registry.
+    listed: usize,
+}
+
+impl PoolStorage {
+    /// Returns the pools trading any of the token pairs.
+    fn pools_by_token_pairs(&self, token_pairs: &HashSet<TokenPair>) -> Vec<(H160, Listed)> {
+        token_pairs
+            .iter()
+            .filter_map(|pair| self.pools_by_token_pair.get(pair))
+            .flatten()
+            .unique()
+            .map(|address| (*address, self.pools[address].clone()))
+            .collect()
+    }
+
+    /// Indexes the pool by all pairs of its coins. Native ETH can't be traded
+    /// by the settlement contract, so pairs with it are skipped.
+    fn insert(&mut self, address: H160, listed: Listed) {
+        for (a, b) in listed
+            .coins
+            .iter()
+            .filter(|coin| **coin != ETH)
+            .tuple_combinations()
+        {
+            if let Some(pair) = TokenPair::new(a.into_alloy(), b.into_alloy()) {
+                self.pools_by_token_pair
+                    .entry(pair)
+                    .or_default()
+                    .insert(address);
+            }
+        }
+        self.pools.insert(address, listed);
+    }
+}
+
+/// Lists the pools of the Curve registry and fetches the pools trading token
+/// pairs.
+pub struct CurvePoolFetcher {
+    registry: CurveRegistry::Instance,
+    pools: Mutex<PoolStorage>,
+}
+
+impl CurvePoolFetcher {
+    pub fn new(registry: CurveRegistry::Instance) -> Self {
+        Self {
+            registry,
+            pools: Default::default(),
+        }
+    }
+
+    /// Returns the pools trading any of the token pairs at the state of the
+    /// block. Pools whose state can't be fetched are skipped.
+    pub async fn fetch(&self, token_pairs: &HashSet<TokenPair>, block: Block) -> Vec<Pool> {
+        let listed = self.pools.lock().await.pools_by_token_pairs(token_pairs);
+        let block = BlockId::from(block);
+        join_all(listed.into_iter().map(|(address, listed)| async move {
+            self.pool(address, listed, block)
+                .await
+                .inspect_err(|err| tracing::warn!(?address, ?err, "failed to fetch Curve pool"))
+                .ok()
+        }))
+        .await
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    async fn pool(&self, address: H160, listed: Listed, block: BlockId) -> Result<Pool> {
+        let Listed { coins, base_pool } = listed;
+        let pool = address.into_alloy();
+        let (balances, rates, decimals, amplification, fees) = futures::try_join!(
+            self.registry.get_balances(pool).block(block).call(),
+            self.registry.get_rates(pool).block(block).call(),
+            self.registry.get_decimals(pool).block(block).call(),
+            self.registry.get_A(pool).block(block).call(),
+            self.registry.get_fees(pool).block(block).call(),
+        )?;
+
+        let mut rates = rates
+            .into_iter()
+            .zip(decimals)
+            .take(coins.len())
+            .map(|(rate, decimals)| normalized_rate(rate.into_legacy(), decimals.into_legacy()))
+            .collect::<Result<Vec<_>>>()?;
+        // The LP token of the base pool is worth its virtual price.
+        if let Some(base_pool) = base_pool {
+            let virtual_price = CurveStableSwapPool::Instance::new(
+                base_pool.into_alloy(),
+                self.registry.provider().clone(),
+            )
+            .get_virtual_price()
+            .block(block)
+            .call()
+            .await?;
+            *rates.last_mut().context("metapool without coins")? = virtual_price.into_legacy();
+        }
+
+        Ok(Pool {
+            address,
+            balances: balances
+                .into_iter()
+                .take(coins.len())
+                .map(IntoLegacy::into_legacy)
+                .collect(),
+            coins,
+            rates,
+            amplification: amplification
+                .into_legacy()
+                .checked_mul(A_PRECISION.into())
+                .context("amplification overflow")?,
+            fee: fees[0].into_legacy(),
+            base_pool,
+        })
+    }
+
+    /// Lists the pools added to the registry since the last run.
+    async fn list_new_pools(&self) -> Result<()> {
+        let listed = self.pools.lock().await.listed;
+        let count = self.registry.pool_count().call().await?.to::<usize>();
+        let pools = try_join_all((listed..count).map(|index| async move {
+            let address = self
+                .registry
+                .pool_list(alloy::primitives::U256::from(index))
+                .call()
+                .await?;
+            let (coins, is_meta) = futures::try_join!(
+                self.registry.get_coins(address).call(),
+                self.registry.is_meta(address).call(),
+            )?;
+            let coins = listed_coins(coins);
+            let base_pool = match (is_meta, coins.last()) {
+                (true, Some(lp_token)) => Some(
+                    self.registry
+                        .get_pool_from_lp_token(lp_token.into_alloy())
+                        .call()
+                        .await?
+                        .into_legacy(),
+                ),
+                _ => None,
+            };
+            anyhow::Ok((address.into_legacy(), Listed { coins, base_pool }))
+        }))
+        .await?;
+
+        let mut storage = self.pools.lock().await;
+        for (address, listed) in pools {
+            storage.insert(address, listed);
+        }
+        storage.listed = count.max(listed);
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl Maintaining for CurvePoolFetcher {
+    async fn run_maintenance(&self) -> Result<()> {
+        self.list_new_pools().await
+    }
+
+    fn name(&self) -> &str {
+        "CurvePoolFetcher"
+    }
+}
+
+/// The coins of a pool from the fixed size array of the registry, which
+/// pads them with the zero address.
+fn listed_coins(coins: [Address; 8]) -> Vec<H160> {
+    coins
+        .into_iter()
+        .take_while(|coin| !coin.is_zero())
+        .map(IntoLegacy::into_legacy)
+        .collect()
+}
+
+/// Scales the rate of a coin with the decimals so that it normalizes balances
+/// to 18 decimals.
+fn normalized_rate(rate: U256, decimals: U256) -> Result<U256> {
+    ensure!(decimals <= 18.into(), "coin with more than 18 decimals");
+    rate.checked_mul(U256::exp10(18 - decimals.as_usize()))
+        .context("rate overflow")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn listed(coins: &[u64], base_pool: Option<u64>) -> Listed {
+        Listed {
+            coins: coins.iter().copied().map(H160::from_low_u64_be).collect(),
+            base_pool: base_pool.map(H160::from_low_u64_be),
+        }
+    }
+
+    fn pair(a: u64, b: u64) -> TokenPair {
+        TokenPair::new(
+            H160::from_low_u64_be(a).into_alloy(),
+            H160::from_low_u64_be(b).into_alloy(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn indexes_pools_by_all_pairs_of_coins() {
+        let mut storage = PoolStorage::default();
+        storage.insert(H160::from_low_u64_be(10), listed(&[1, 2, 3], None));
+        storage.insert(H160::from_low_u64_be(11), listed(&[3, 4], Some(10)));
+        storage.insert(
+            H160::from_low_u64_be(12),
+            Listed {
+                coins: vec![ETH, H160::from_low_u64_be(4)],
+                base_pool: None,
+            },
+        );
+
+        let pools = storage.pools_by_token_pairs(&[pair(1, 3), pair(2, 3)].into());
+        assert_eq!(pools.len(), 1);
+        assert_eq!(pools[0].0, H160::from_low_u64_be(10));
+        let pools = storage.pools_by_token_pairs(&[pair(3, 4), pair(1, 4)].into());
+        assert_eq!(pools.len(), 1);
+        assert_eq!(pools[0].1.base_pool, Some(H160::from_low_u64_be(10)));
+        assert!(storage.pools_by_token_pair.keys().all(|pair| {
+            let (a, b) = pair.get();
+            a != ETH.into_alloy() && b != ETH.into_alloy()
+        }));
+    }
+
+    #[test]
+    fn normalizes_rates_to_18_decimals() {
+        assert_eq!(
+            normalized_rate(U256::exp10(18), 6.into()).unwrap(),
+            U256::exp10(30)
+        );
+        assert!(normalized_rate(U256::exp10(18), 24.into()).is_err());
+    }
+}
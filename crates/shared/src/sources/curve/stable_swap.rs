@@ -0,0 +1,188 @@
+//! The StableSwap invariant of Curve pools.
+//!
+//! The math is a port of the Vyper implementation of the StableSwap pools[^1],
+//! including its integer rounding, so that quotes match the pools exactly.
+//! Balances are normalized to 18 decimals and scaled by the rates of the coins
+//! before being plugged into the invariant.
+//!
+//! [^1]: <https://github.com/curvefi/curve-contract/blob/master/contracts/pool-templates/base/SwapTemplateBase.vy>
+
+use ethcontract::U256;
+
+/// The precision of the normalized balances and of the rates.
+pub const PRECISION: u128 = 1_000_000_000_000_000_000;
+/// The precision of the amplification parameter.
+pub const A_PRECISION: u128 = 100;
+/// The denominator of the fees.
+pub const FEE_DENOMINATOR: u128 = 10_000_000_000;
+
+/// The number of Newton iterations after which the invariant is considered not
+/// to converge.
+const MAX_ITERATIONS: usize = 255;
+
+/// Returns the normalized balances of the pool.
+pub fn xp(balances: &[U256], rates: &[U256]) -> Option<Vec<U256>> {
+    balances
+        .iter()
+        .zip(rates)
+        .map(|(balance, rate)| balance.checked_mul(*rate)?.checked_div(PRECISION.into()))
+        .collect()
+}
+
+/// Computes the invariant `D` of the normalized balances with Newton's method.
+/// `amp` is the amplification parameter multiplied by [`A_PRECISION`].
+pub fn get_d(xp: &[U256], amp: U256) -> Option<U256> {
+    let n = U256::from(xp.len());
+    let s = xp
+        .iter()
+        .try_fold(U256::zero(), |sum, x| sum.checked_add(*x))?;
+    if s.is_zero() {
+        return Some(U256::zero());
+    }
+
+    let ann = amp.checked_mul(n)?;
+    let mut d = s;
+    for _ in 0..MAX_ITERATIONS {
+        let mut d_p = d;
+        for x in xp {
+            d_p = d_p.checked_mul(d)?.checked_div(x.checked_mul(n)?)?;
+        }
+        let d_prev = d;
+        let numerator = ann
+            .checked_mul(s)?
+            .checked_div(A_PRECISION.into())?
+            .checked_add(d_p.checked_mul(n)?)?
+            .checked_mul(d)?;
+        let denominator = ann
+            .checked_sub(A_PRECISION.into())?
+            .checked_mul(d)?
+            .checked_div(A_PRECISION.into())?
+            .checked_add((n + 1).checked_mul(d_p)?)?;
+        d = numerator.checked_div(denominator)?;
+        if abs_diff(d, d_prev) <= U256::one() {
+            return Some(d);
+        }
+    }
+    None
+}
+
+/// Computes the normalized balance of coin `j` that keeps the invariant when
+/// the normalized balance of coin `i` changes to `x`.
+pub fn get_y(i: usize, j: usize, x: U256, xp: &[U256], amp: U256) -> Option<U256> {
+    if i == j || i >= xp.len() || j >= xp.len() {
+        return None;
+    }
+
+    let n = U256::from(xp.len());
+    let d = get_d(xp, amp)?;
+    let ann = amp.checked_mul(n)?;
+    let mut c = d;
+    let mut s = U256::zero();
+    for (k, balance) in xp.iter().enumerate() {
+        let x = match k {
+            k if k == i => x,
+            k if k == j => continue,
+            _ => *balance,
+        };
+        s = s.checked_add(x)?;
+        c = c.checked_mul(d)?.checked_div(x.checked_mul(n)?)?;
+    }
+    c = c
+        .checked_mul(d)?
+        .checked_mul(A_PRECISION.into())?
+        .checked_div(ann.checked_mul(n)?)?;
+    let b = s.checked_add(d.checked_mul(A_PRECISION.into())?.checked_div(ann)?)?;
+
+    let mut y = d;
+    for _ in 0..MAX_ITERATIONS {
+        let y_prev = y;
+        y = y
+            .checked_mul(y)?
+            .checked_add(c)?
+            .checked_div(y.checked_mul(2.into())?.checked_add(b)?.checked_sub(d)?)?;
+        if abs_diff(y, y_prev) <= U256::one() {
+            return Some(y);
+        }
+    }
+    None
+}
+
+fn abs_diff(a: U256, b: U256) -> U256 {
+    if a > b { a - b } else { b - a }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn amounts(amounts: &[&str]) -> Vec<U256> {
+        amounts
+            .iter()
+            .map(|amount| U256::from_dec_str(amount).unwrap())
+            .collect()
+    }
+
+    // The expected values are computed with a Python port of the Vyper
+    // implementation of the base pool template.
+
+    #[test]
+    fn normalizes_balances() {
+        let xp = xp(
+            &amounts(&["1000000000000000000000000", "1200000000000"]),
+            &amounts(&["1000000000000000000", "1000000000000000000000000000000"]),
+        )
+        .unwrap();
+        assert_eq!(
+            xp,
+            amounts(&["1000000000000000000000000", "1200000000000000000000000"])
+        );
+    }
+
+    #[test]
+    fn computes_invariant() {
+        let xp = amounts(&[
+            "1000000000000000000000000",
+            "1200000000000000000000000",
+            "800000000000000000000000",
+        ]);
+        assert_eq!(
+            get_d(&xp, U256::from(2000 * A_PRECISION)),
+            Some(U256::from_dec_str("2999979177656086521002857").unwrap())
+        );
+
+        let xp = amounts(&["5000000000000000000000000", "1000000000000000000000000"]);
+        assert_eq!(
+            get_d(&xp, U256::from(100 * A_PRECISION)),
+            Some(U256::from_dec_str("5976514208530120314931029").unwrap())
+        );
+
+        assert_eq!(
+            get_d(&[U256::zero(); 2], A_PRECISION.into()),
+            Some(U256::zero())
+        );
+    }
+
+    #[test]
+    fn computes_balance_keeping_invariant() {
+        let xp = amounts(&[
+            "1000000000000000000000000",
+            "1200000000000000000000000",
+            "800000000000000000000000",
+        ]);
+        let x = xp[0] + U256::exp10(21);
+        assert_eq!(
+            get_y(0, 1, x, &xp, U256::from(2000 * A_PRECISION)),
+            Some(U256::from_dec_str("1198999913682678752885393").unwrap())
+        );
+
+        let xp = amounts(&["5000000000000000000000000", "1000000000000000000000000"]);
+        let x = xp[1] + U256::exp10(23);
+        assert_eq!(
+            get_y(1, 0, x, &xp, U256::from(100 * A_PRECISION)),
+            Some(U256::from_dec_str("4896177121635521540960513").unwrap())
+        );
+
+        assert_eq!(get_y(0, 0, x, &xp, A_PRECISION.into()), None);
+        assert_eq!(get_y(0, 2, x, &xp, A_PRECISION.into()), None);
+    }
+}
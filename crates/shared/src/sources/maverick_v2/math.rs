@@ -0,0 +1,230 @@
+//! The tick math of Maverick V2 pools.
+//!
+//! The bins of a pool are aggregated per tick, and within a tick the pool
+//! trades like a concentrated liquidity position over the price range of the
+//! tick: the reserves of the tick are the real reserves of a constant product
+//! curve whose virtual reserves are shifted by the bounds of the range. Prices
+//! are the amount of token A per token B, so swapping token A in moves the
+//! price up and ticks below the active tick only hold token A while ticks
+//! above it only hold token B.
+//!
+//! Square root prices are 18 decimal fixed point numbers, liquidity is in
+//! units of the square root of the product of the token amounts. Amounts are
+//! rounded in favour of the pool, so quotes may be off by a few wei compared to
+//! the pools.
+//!
+//! [^1]: <https://docs.mav.xyz/technical-reference/maverick-v2>
+
+use primitive_types::{U256, U512};
+
+/// The fixed point precision of square root prices.
+pub const ONE: u128 = 1_000_000_000_000_000_000;
+
+/// The largest tick index multiplied by the tick spacing whose price can be
+/// represented.
+const MAX_TICK: u64 = 887_272;
+
+/// Returns the square root of the lower price bound of the tick, i.e. the
+/// square root of `1.0001^(tick * tick_spacing)`.
+pub fn tick_sqrt_price(tick_spacing: u32, tick: i32) -> Option<U256> {
+    let abs_tick = u64::from(tick.unsigned_abs()).checked_mul(tick_spacing.into())?;
+    if abs_tick > MAX_TICK {
+        return None;
+    }
+
+    // The square roots of `1.0001^-(2^i)` as Q128.128 numbers.
+    const RATIOS: [u128; 20] = [
+        0xfffcb933bd6fad37aa2d162d1a594001,
+        0xfff97272373d413259a46990580e213a,
+        0xfff2e50f5f656932ef12357cf3c7fdcc,
+        0xffe5caca7e10e4e61c3624eaa0941cd0,
+        0xffcb9843d60f6159c9db58835c926644,
+        0xff973b41fa98c081472e6896dfb254c0,
+        0xff2ea16466c96a3843ec78b326b52861,
+        0xfe5dee046a99a2a811c461f1969c3053,
+        0xfcbe86c7900a88aedcffc83b479aa3a4,
+        0xf987a7253ac413176f2b074cf7815e54,
+        0xf3392b0822b70005940c7a398e4b70f3,
+        0xe7159475a2c29b7443b29c7fa6e889d9,
+        0xd097f3bdfd2022b8845ad8f792aa5825,
+        0xa9f746462d870fdf8a65dc1f90e061e5,
+        0x70d869a156d2a1b890bb3df62baf32f7,
+        0x31be135f97d08fd981231505542fcfa6,
+        0x9aa508b5b7a84e1c677de54f3e99bc9,
+        0x5d6af8dedb81196699c329225ee604,
+        0x2216e584f5fa1ea926041bedfe98,
+        0x48a170391f7dc42444e8fa2,
+    ];
+    let mut ratio = U256::one() << 128;
+    for (bit, factor) in RATIOS.into_iter().enumerate() {
+        if abs_tick & (1 << bit) != 0 {
+            ratio = (ratio * U256::from(factor)) >> 128;
+        }
+    }
+    if tick > 0 {
+        ratio = U256::MAX / ratio;
+    }
+
+    ratio.checked_mul(ONE.into()).map(|ratio| ratio >> 128)
+}
+
+/// The reserves of a tick.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct TickReserves {
+    pub reserve_a: U256,
+    pub reserve_b: U256,
+}
+
+/// The virtual reserves of a tick, whose product is constant while trading
+/// within the tick.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct VirtualReserves {
+    pub a: U256,
+    pub b: U256,
+}
+
+impl TickReserves {
+    /// Computes the liquidity of the reserves over the price range of the tick
+    /// by solving `(a + L * sqrt_lower) * (b + L / sqrt_upper) = L^2` for `L`.
+    pub fn liquidity(&self, sqrt_lower: U256, sqrt_upper: U256) -> Option<U256> {
+        let (a, b) = (self.reserve_a, self.reserve_b);
+        let diff = sqrt_upper.checked_sub(sqrt_lower)?;
+        if diff.is_zero() {
+            return None;
+        }
+
+        // With `h = a / sqrt_upper + b * sqrt_lower` the positive root is
+        // `(h + sqrt(h^2 + 4 * a * b * diff / sqrt_upper)) * sqrt_upper / (2 *
+        // diff)`.
+        let h = a
+            .checked_mul(ONE.into())?
+            .checked_div(sqrt_upper)?
+            .checked_add(b.checked_mul(sqrt_lower)? / ONE)?;
+        let discriminant = h.full_mul(h).checked_add(
+            a.full_mul(b)
+                .checked_mul(U512::from(diff) * U512::from(4))?
+                .checked_div(sqrt_upper.into())?,
+        )?;
+        let root = U256::try_from(discriminant.integer_sqrt()).ok()?;
+        let liquidity = h
+            .checked_add(root)?
+            .full_mul(sqrt_upper)
+            .checked_div(U512::from(diff) * U512::from(2))?;
+        U256::try_from(liquidity).ok()
+    }
+
+    /// Computes the virtual reserves of the tick.
+    pub fn virtual_reserves(&self, sqrt_lower: U256, sqrt_upper: U256) -> Option<VirtualReserves> {
+        let liquidity = self.liquidity(sqrt_lower, sqrt_upper)?;
+        Some(VirtualReserves {
+            a: self
+                .reserve_a
+                .checked_add(liquidity.checked_mul(sqrt_lower)? / ONE)?,
+            b: self
+                .reserve_b
+                .checked_add(liquidity.checked_mul(ONE.into())?.checked_div(sqrt_upper)?)?,
+        })
+    }
+}
+
+/// Computes the output of swapping `amount_in` into constant product reserves
+/// of `reserve_in` and `reserve_out`.
+pub fn amount_out(reserve_in: U256, reserve_out: U256, amount_in: U256) -> Option<U256> {
+    let out = reserve_out
+        .full_mul(amount_in)
+        .checked_div(reserve_in.checked_add(amount_in)?.into())?;
+    U256::try_from(out).ok()
+}
+
+/// Computes the input needed to take `amount_out` out of constant product
+/// reserves of `reserve_in` and `reserve_out`.
+pub fn amount_in(reserve_in: U256, reserve_out: U256, amount_out: U256) -> Option<U256> {
+    let remaining = reserve_out.checked_sub(amount_out)?;
+    if remaining.is_zero() {
+        return None;
+    }
+    let product = reserve_in.full_mul(reserve_out);
+    let new_reserve_in = U256::try_from(ceil_div(product, remaining.into())?).ok()?;
+    new_reserve_in.checked_sub(reserve_in)
+}
+
+fn ceil_div(a: U512, b: U512) -> Option<U512> {
+    if b.is_zero() {
+        return None;
+    }
+    a.checked_add(b - U512::one())?.checked_div(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_f64(value: U256) -> f64 {
+        value.to_f64_lossy() / 1e18
+    }
+
+    #[test]
+    fn computes_tick_sqrt_prices() {
+        assert_eq!(tick_sqrt_price(10, 0), Some(ONE.into()));
+        for (tick_spacing, tick) in [(1, 1), (10, -7), (198, 35), (50, -1000), (1, 200_000)] {
+            let expected = 1.0001_f64.powf(f64::from(tick) * f64::from(tick_spacing) / 2.);
+            let sqrt_price = to_f64(tick_sqrt_price(tick_spacing, tick).unwrap());
+            assert!(
+                (sqrt_price / expected - 1.).abs() < 1e-10,
+                "{tick_spacing} {tick}: {sqrt_price} != {expected}"
+            );
+        }
+        assert_eq!(tick_sqrt_price(1, 887_273), None);
+        assert_eq!(tick_sqrt_price(2, -443_637), None);
+    }
+
+    #[test]
+    fn computes_liquidity_of_one_sided_ticks() {
+        let (lower, upper) = (
+            tick_sqrt_price(10, 0).unwrap(),
+            tick_sqrt_price(10, 1).unwrap(),
+        );
+        // A tick full of token A is at its upper price bound, so its reserve
+        // is `L * (sqrt_upper - sqrt_lower)`.
+        let reserves = TickReserves {
+            reserve_a: U256::exp10(18),
+            reserve_b: U256::zero(),
+        };
+        let liquidity = to_f64(reserves.liquidity(lower, upper).unwrap());
+        let expected = 1. / (to_f64(upper) - to_f64(lower));
+        assert!((liquidity / expected - 1.).abs() < 1e-9);
+
+        let virtual_reserves = reserves.virtual_reserves(lower, upper).unwrap();
+        let price = virtual_reserves.a.to_f64_lossy() / virtual_reserves.b.to_f64_lossy();
+        assert!((price / to_f64(upper).powi(2) - 1.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn virtual_reserves_price_within_the_tick() {
+        let (lower, upper) = (
+            tick_sqrt_price(10, 0).unwrap(),
+            tick_sqrt_price(10, 1).unwrap(),
+        );
+        let reserves = TickReserves {
+            reserve_a: U256::from(3) * U256::exp10(20),
+            reserve_b: U256::from(5) * U256::exp10(20),
+        };
+        let virtual_reserves = reserves.virtual_reserves(lower, upper).unwrap();
+        let price = virtual_reserves.a.to_f64_lossy() / virtual_reserves.b.to_f64_lossy();
+        assert!(to_f64(lower).powi(2) < price && price < to_f64(upper).powi(2));
+    }
+
+    #[test]
+    fn amounts_round_in_favour_of_the_pool() {
+        let (reserve_in, reserve_out) = (U256::from(1_000_000), U256::from(2_000_000));
+        assert_eq!(
+            amount_out(reserve_in, reserve_out, 1000.into()),
+            Some(1998.into())
+        );
+        assert_eq!(
+            amount_in(reserve_in, reserve_out, 1998.into()),
+            Some(1000.into())
+        );
+        assert_eq!(amount_in(reserve_in, reserve_out, reserve_out), None);
+    }
+}
@@ -0,0 +1,272 @@
+//! Maverick V2 liquidity.
+//!
+//! Maverick V2 pools hold their liquidity in bins, which sit at a tick and,
+//! depending on their kind, move along with the price. Swaps only depend on
+//! the reserves of the bins aggregated per tick, so pools are quoted off-chain
+//! from the reserves of the ticks around the active tick.
+
+pub mod math;
+pub mod pool_fetching;
+
+use {
+    self::math::{ONE, TickReserves},
+    crate::baseline_solver::BaselineSolvable,
+    ethcontract::{H160, U256},
+    ethrpc::alloy::conversions::IntoAlloy,
+    model::TokenPair,
+    std::collections::BTreeMap,
+};
+
+/// The state of a Maverick V2 pool.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Pool {
+    pub address: H160,
+    /// The lower of the two tokens of the pool.
+    pub token_a: H160,
+    pub token_b: H160,
+    pub tick_spacing: u32,
+    pub active_tick: i32,
+    /// The reserves of the ticks around the active tick. Ticks without
+    /// reserves may be missing, and the liquidity beyond the included ticks is
+    /// unknown, so swaps exceeding it can't be quoted.
+    pub ticks: BTreeMap<i32, TickReserves>,
+    /// The swap fee for swapping token A in, as an 18 decimal fixed point
+    /// fraction of the input amount.
+    pub fee_a_in: U256,
+    /// The swap fee for swapping token B in.
+    pub fee_b_in: U256,
+}
+
+impl Pool {
+    /// Rough estimate of a single pool swap through the Maverick V2 router.
+    pub const POOL_SWAP_GAS_COST: usize = 125_000;
+
+    /// The token pair traded by the pool.
+    pub fn tokens(&self) -> Option<TokenPair> {
+        TokenPair::new(self.token_a.into_alloy(), self.token_b.into_alloy())
+    }
+
+    /// Returns whether swapping `in_token` for `out_token` swaps token A in,
+    /// or `None` if the pool doesn't trade the tokens.
+    pub fn token_a_in(&self, in_token: H160, out_token: H160) -> Option<bool> {
+        if (in_token, out_token) == (self.token_a, self.token_b) {
+            Some(true)
+        } else if (in_token, out_token) == (self.token_b, self.token_a) {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    fn fee(&self, token_a_in: bool) -> U256 {
+        if token_a_in {
+            self.fee_a_in
+        } else {
+            self.fee_b_in
+        }
+    }
+
+    /// Returns the ticks a swap moves through in order, starting at the active
+    /// tick. Swapping token A in moves the price up.
+    fn swapped_ticks(
+        &self,
+        token_a_in: bool,
+    ) -> Box<dyn Iterator<Item = (&i32, &TickReserves)> + '_> {
+        if token_a_in {
+            Box::new(self.ticks.range(self.active_tick..))
+        } else {
+            Box::new(self.ticks.range(..=self.active_tick).rev())
+        }
+    }
+
+    /// Returns the constant product reserves of the input and output tokens
+    /// of a swap through the tick, as well as the real reserve of the output
+    /// token.
+    fn tick_reserves(
+        &self,
+        tick: i32,
+        reserves: &TickReserves,
+        token_a_in: bool,
+    ) -> Option<(U256, U256, U256)> {
+        let lower = math::tick_sqrt_price(self.tick_spacing, tick)?;
+        let upper = math::tick_sqrt_price(self.tick_spacing, tick.checked_add(1)?)?;
+        let virtual_reserves = reserves.virtual_reserves(lower, upper)?;
+        Some(if token_a_in {
+            (virtual_reserves.a, virtual_reserves.b, reserves.reserve_b)
+        } else {
+            (virtual_reserves.b, virtual_reserves.a, reserves.reserve_a)
+        })
+    }
+
+    /// Computes the output of swapping `amount_in` through the pool. The fee
+    /// is taken from the input before it gets swapped through the ticks.
+    pub fn amount_out(&self, token_a_in: bool, amount_in: U256) -> Option<U256> {
+        let fee = ceil_div(amount_in.checked_mul(self.fee(token_a_in))?, ONE.into())?;
+        let mut remaining = amount_in.checked_sub(fee)?;
+        let mut amount_out = U256::zero();
+        for (tick, reserves) in self.swapped_ticks(token_a_in) {
+            if remaining.is_zero() {
+                return Some(amount_out);
+            }
+            let (reserve_in, reserve_out, balance_out) =
+                self.tick_reserves(*tick, reserves, token_a_in)?;
+            if balance_out.is_zero() {
+                continue;
+            }
+
+            let out = math::amount_out(reserve_in, reserve_out, remaining)?;
+            if out < balance_out {
+                return amount_out.checked_add(out);
+            }
+            // The swap drains the tick and continues in the next one.
+            amount_out = amount_out.checked_add(balance_out)?;
+            let used = math::amount_in(reserve_in, reserve_out, balance_out)?;
+            remaining = remaining.saturating_sub(used);
+        }
+        remaining.is_zero().then_some(amount_out)
+    }
+
+    /// Computes the input needed to receive `amount_out` from the pool.
+    pub fn amount_in(&self, token_a_in: bool, amount_out: U256) -> Option<U256> {
+        let mut remaining = amount_out;
+        let mut amount_in = U256::zero();
+        for (tick, reserves) in self.swapped_ticks(token_a_in) {
+            if remaining.is_zero() {
+                break;
+            }
+            let (reserve_in, reserve_out, balance_out) =
+                self.tick_reserves(*tick, reserves, token_a_in)?;
+            if balance_out.is_zero() {
+                continue;
+            }
+
+            let out = remaining.min(balance_out);
+            amount_in = amount_in.checked_add(math::amount_in(reserve_in, reserve_out, out)?)?;
+            remaining -= out;
+        }
+        if !remaining.is_zero() {
+            return None;
+        }
+
+        ceil_div(
+            amount_in.checked_mul(ONE.into())?,
+            U256::from(ONE).checked_sub(self.fee(token_a_in))?,
+        )
+    }
+}
+
+fn ceil_div(a: U256, b: U256) -> Option<U256> {
+    if b.is_zero() {
+        return None;
+    }
+    a.checked_add(b - 1)?.checked_div(b)
+}
+
+impl BaselineSolvable for Pool {
+    async fn get_amount_out(
+        &self,
+        out_token: H160,
+        (in_amount, in_token): (U256, H160),
+    ) -> Option<U256> {
+        let token_a_in = self.token_a_in(in_token, out_token)?;
+        self.amount_out(token_a_in, in_amount)
+    }
+
+    async fn get_amount_in(
+        &self,
+        in_token: H160,
+        (out_amount, out_token): (U256, H160),
+    ) -> Option<U256> {
+        let token_a_in = self.token_a_in(in_token, out_token)?;
+        self.amount_in(token_a_in, out_amount)
+    }
+
+    async fn gas_cost(&self) -> usize {
+        Self::POOL_SWAP_GAS_COST
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A pool with 10 bps fees whose active tick at a price of about 1 holds
+    /// 100 of each token, with 1000 of token A below and 1000 of token B
+    /// above it.
+    fn pool() -> Pool {
+        let reserves = |a: u64, b: u64| TickReserves {
+            reserve_a: U256::from(a) * U256::exp10(18),
+            reserve_b: U256::from(b) * U256::exp10(18),
+        };
+        Pool {
+            address: H160::from_low_u64_be(1),
+            token_a: H160::from_low_u64_be(2),
+            token_b: H160::from_low_u64_be(3),
+            tick_spacing: 10,
+            active_tick: 0,
+            ticks: [
+                (-1, reserves(1000, 0)),
+                (0, reserves(100, 100)),
+                (1, reserves(0, 1000)),
+            ]
+            .into(),
+            fee_a_in: U256::exp10(15),
+            fee_b_in: U256::exp10(15),
+        }
+    }
+
+    #[test]
+    fn swaps_within_the_active_tick() {
+        let pool = pool();
+        let amount_in = U256::exp10(18);
+        let amount_out = pool.amount_out(true, amount_in).unwrap();
+        // The price is within [1, 1.001] A per B and the fee is 0.1%.
+        assert!(amount_out < U256::from(999) * U256::exp10(15));
+        assert!(amount_out > U256::from(997) * U256::exp10(15));
+
+        let required = pool.amount_in(true, amount_out).unwrap();
+        assert!(required <= amount_in);
+        assert!(amount_in - required < 10.into());
+    }
+
+    #[test]
+    fn swaps_across_ticks() {
+        let pool = pool();
+        for token_a_in in [true, false] {
+            // More than the output reserves of the active tick.
+            let amount_in = U256::from(500) * U256::exp10(18);
+            let amount_out = pool.amount_out(token_a_in, amount_in).unwrap();
+            assert!(amount_out > U256::from(100) * U256::exp10(18));
+            assert!(amount_out < U256::from(500) * U256::exp10(18));
+
+            let required = pool.amount_in(token_a_in, amount_out).unwrap();
+            assert!(required <= amount_in);
+            assert!(pool.amount_out(token_a_in, required).unwrap() >= amount_out);
+        }
+    }
+
+    #[test]
+    fn swaps_exceeding_the_known_ticks_are_not_quoted() {
+        let pool = pool();
+        assert_eq!(
+            pool.amount_out(true, U256::from(2000) * U256::exp10(18)),
+            None
+        );
+        assert_eq!(
+            pool.amount_in(false, U256::from(1101) * U256::exp10(18)),
+            None
+        );
+        assert!(
+            pool.amount_in(false, U256::from(1100) * U256::exp10(18))
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn finds_swap_direction() {
+        let pool = pool();
+        assert_eq!(pool.token_a_in(pool.token_a, pool.token_b), Some(true));
+        assert_eq!(pool.token_a_in(pool.token_b, pool.token_a), Some(false));
+        assert_eq!(pool.token_a_in(pool.token_a, pool.token_a), None);
+    }
+}
@@ -0,0 +1,342 @@
+//! Indexing of the pools of the Maverick V2 factory and fetching of their
+//! state.
+//!
+//! Pools are indexed from the `PoolCreated` events of the factory, starting at
+//! its deployment. The reserves of the ticks around the active tick of a pool
+//! are read at the block the pool is fetched for.
+
+use {
+    super::{Pool, math::TickReserves},
+    crate::{
+        event_handling::{AlloyEventRetriever, AlloyEventRetrieving, EventHandler, EventStoring},
+        maintenance::Maintaining,
+        recent_block_cache::Block,
+    },
+    alloy::{
+        eips::BlockId,
+        primitives::Address,
+        providers::DynProvider,
+        rpc::types::{Filter, Log},
+        sol_types::SolEvent,
+    },
+    anyhow::{Context, Result},
+    contracts::alloy::{
+        MaverickV2Factory::{
+            self,
+            MaverickV2Factory::{MaverickV2FactoryEvents, PoolCreated},
+        },
+        MaverickV2Pool,
+    },
+    ethcontract::H160,
+    ethrpc::{
+        alloy::conversions::{IntoAlloy, IntoLegacy},
+        block_stream::{BlockRetrieving, RangeInclusive},
+    },
+    futures::future::{join_all, try_join_all},
+    model::TokenPair,
+    std::{
+        collections::{HashMap, HashSet},
+        sync::Arc,
+    },
+    tokio::sync::Mutex,
+};
+
+/// The number of ticks on either side of the active tick whose reserves get
+/// fetched. Swaps moving the price further can't be quoted.
+const TICKS_AROUND_ACTIVE: i32 = 10;
+
+pub struct FactoryEventFetcher {
+    factory: Address,
+    provider: DynProvider,
+}
+
+impl AlloyEventRetrieving for FactoryEventFetcher {
+    type Event = MaverickV2FactoryEvents;
+
+    fn filter(&self) -> Filter {
+        Filter::new()
+            .address(self.factory)
+            .event_signature(PoolCreated::SIGNATURE_HASH)
+    }
+
+    fn provider(&self) -> &DynProvider {
+        &self.provider
+    }
+}
+
+/// A pool created by the factory.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Listed {
+    pub address: H160,
+    pub token_a: H160,
+    pub token_b: H160,
+    pub tick_spacing: u32,
+}
+
+impl Listed {
+    fn tokens(&self) -> Option<TokenPair> {
+        TokenPair::new(self.token_a.into_alloy(), self.token_b.into_alloy())
+    }
+}
+
+/// The pools created by the factory.
+pub struct PoolStorage {
+    /// The pools by their address and the block they were created in.
+    pools: HashMap<H160, (Listed, u64)>,
+    pools_by_token_pair: HashMap<TokenPair, HashSet<H160>>,
+    last_indexed_block: u64,
+}
+
+impl PoolStorage {
+    /// Creates an empty storage that starts indexing at the specified block.
+    pub fn new(start_block: u64) -> Self {
+        Self {
+            pools: Default::default(),
+            pools_by_token_pair: Default::default(),
+            last_indexed_block: start_block,
+        }
+    }
+
+    /// Returns the pools trading any of the token pairs.
+    pub fn pools_by_token_pairs(&self, token_pairs: &HashSet<TokenPair>) -> Vec<Listed> {
+        token_pairs
+            .iter()
+            .filter_map(|pair| self.pools_by_token_pair.get(pair))
+            .flatten()
+            .map(|address| self.pools[address].0)
+            .collect()
+    }
+
+    fn insert(&mut self, listed: Listed, block: u64) {
+        let Some(pair) = listed.tokens() else {
+            return;
+        };
+        self.pools.insert(listed.address, (listed, block));
+        self.pools_by_token_pair
+            .entry(pair)
+            .or_default()
+            .insert(listed.address);
+    }
+
+    /// Removes the pools created in or after the specified block.
+    fn remove_pools_newer_than_block(&mut self, block: u64) {
+        let removed = self
+            .pools
+            .values()
+            .filter(|(_, created)| *created >= block)
+            .map(|(listed, _)| *listed)
+            .collect::<Vec<_>>();
+        for listed in removed {
+            self.pools.remove(&listed.address);
+            if let Some(pair) = listed.tokens()
+                && let Some(addresses) = self.pools_by_token_pair.get_mut(&pair)
+            {
+                addresses.remove(&listed.address);
+                if addresses.is_empty() {
+                    self.pools_by_token_pair.remove(&pair);
+                }
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl EventStoring<(MaverickV2FactoryEvents, Log)> for PoolStorage {
+    async fn replace_events(
+        &mut self,
+        events: Vec<(MaverickV2FactoryEvents, Log)>,
+        range: RangeInclusive<u64>,
+    ) -> Result<()> {
+        self.remove_pools_newer_than_block(*range.start());
+        self.append_events(events).await
+    }
+
+    async fn append_events(&mut self, events: Vec<(MaverickV2FactoryEvents, Log)>) -> Result<()> {
+        for (event, log) in events {
+            let block = log.block_number.context("log block number is empty")?;
+            match event {
+                MaverickV2FactoryEvents::PoolCreated(event) => {
+                    let Ok(tick_spacing) = event.tickSpacing.try_into() else {
+                        tracing::debug!(pool = ?event.poolAddress, "unsupported tick spacing");
+                        continue;
+                    };
+                    self.insert(
+                        Listed {
+                            address: event.poolAddress.into_legacy(),
+                            token_a: event.tokenA.into_legacy(),
+                            token_b: event.tokenB.into_legacy(),
+                            tick_spacing,
+                        },
+                        block,
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn last_event_block(&self) -> Result<u64> {
+        Ok(self.last_indexed_block)
+    }
+
+    async fn persist_last_indexed_block(&mut self, block: u64) -> Result<()> {
+        self.last_indexed_block = block;
+        Ok(())
+    }
+}
+
+/// Indexes the pools of the factory and fetches the state of the pools trading
+/// token pairs.
+pub struct MaverickV2PoolFetcher {
+    events: Mutex<
+        EventHandler<
+            AlloyEventRetriever<FactoryEventFetcher>,
+            PoolStorage,
+            (MaverickV2FactoryEvents, Log),
+        >,
+    >,
+    provider: DynProvider,
+}
+
+impl MaverickV2PoolFetcher {
+    /// Creates a fetcher indexing the pools created by the factory after
+    /// `start_block`, usually the deployment block of the factory.
+    pub fn new(
+        factory: &MaverickV2Factory::Instance,
+        start_block: u64,
+        block_retriever: Arc<dyn BlockRetrieving>,
+    ) -> Self {
+        let events = Mutex::new(EventHandler::new(
+            block_retriever,
+            AlloyEventRetriever(FactoryEventFetcher {
+                factory: *factory.address(),
+                provider: factory.provider().clone(),
+            }),
+            PoolStorage::new(start_block),
+            None,
+        ));
+        Self {
+            events,
+            provider: factory.provider().clone(),
+        }
+    }
+
+    /// Returns the pools trading any of the token pairs at the state of the
+    /// block. Pools whose state can't be fetched are skipped.
+    pub async fn fetch(&self, token_pairs: &HashSet<TokenPair>, block: Block) -> Vec<Pool> {
+        let listed = self
+            .events
+            .lock()
+            .await
+            .store()
+            .pools_by_token_pairs(token_pairs);
+        let block = BlockId::from(block);
+        join_all(listed.into_iter().map(|listed| async move {
+            self.pool(listed, block)
+                .await
+                .inspect_err(|err| {
+                    tracing::warn!(
+                        address = ?listed.address,
+                        ?err,
+                        "failed to fetch Maverick V2 pool"
+                    )
+                })
+                .ok()
+        }))
+        .await
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    async fn pool(&self, listed: Listed, block: BlockId) -> Result<Pool> {
+        let pool =
+            MaverickV2Pool::Instance::new(listed.address.into_alloy(), self.provider.clone());
+        let (state, fee_a_in, fee_b_in) = futures::try_join!(
+            pool.getState().block(block).call(),
+            pool.fee(true).block(block).call(),
+            pool.fee(false).block(block).call(),
+        )?;
+
+        let active_tick = state.activeTick;
+        let ticks = try_join_all(
+            (active_tick.saturating_sub(TICKS_AROUND_ACTIVE)
+                ..=active_tick.saturating_add(TICKS_AROUND_ACTIVE))
+                .map(|tick| {
+                    let pool = &pool;
+                    async move {
+                        let state = pool.getTick(tick).block(block).call().await?;
+                        anyhow::Ok((
+                            tick,
+                            TickReserves {
+                                reserve_a: state.reserveA.into(),
+                                reserve_b: state.reserveB.into(),
+                            },
+                        ))
+                    }
+                }),
+        )
+        .await?;
+
+        Ok(Pool {
+            address: listed.address,
+            token_a: listed.token_a,
+            token_b: listed.token_b,
+            tick_spacing: listed.tick_spacing,
+            active_tick,
+            ticks: ticks
+                .into_iter()
+                .filter(|(_, reserves)| *reserves != TickReserves::default())
+                .collect(),
+            fee_a_in: fee_a_in.into_legacy(),
+            fee_b_in: fee_b_in.into_legacy(),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl Maintaining for MaverickV2PoolFetcher {
+    async fn run_maintenance(&self) -> Result<()> {
+        self.events.run_maintenance().await
+    }
+
+    fn name(&self) -> &str {
+        "MaverickV2PoolFetcher"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn listed(address: u64, token_a: u64, token_b: u64) -> Listed {
+        Listed {
+            address: H160::from_low_u64_be(address),
+            token_a: H160::from_low_u64_be(token_a),
+            token_b: H160::from_low_u64_be(token_b),
+            tick_spacing: 10,
+        }
+    }
+
+    fn pair(a: u64, b: u64) -> TokenPair {
+        listed(0, a, b).tokens().unwrap()
+    }
+
+    #[test]
+    fn removes_pools_of_reorged_blocks() {
+        let mut storage = PoolStorage::new(0);
+        storage.insert(listed(10, 1, 2), 10);
+        storage.insert(listed(11, 1, 2), 11);
+        storage.insert(listed(12, 2, 3), 12);
+
+        let pools = storage.pools_by_token_pairs(&[pair(1, 2), pair(2, 3)].into());
+        assert_eq!(pools.len(), 3);
+
+        storage.remove_pools_newer_than_block(11);
+        assert_eq!(
+            storage.pools_by_token_pairs(&[pair(1, 2), pair(2, 3)].into()),
+            vec![listed(10, 1, 2)]
+        );
+        assert!(storage.pools_by_token_pair.get(&pair(2, 3)).is_none());
+    }
+}
@@ -1,12 +1,21 @@
 //! Top-level module organizing all baseline liquidity sources.
 
+pub mod balancer_api;
+pub mod balancer_pair_cache;
+pub mod balancer_rounding;
+pub mod balancer_snapshots;
 pub mod balancer_v2;
 pub mod balancer_v3;
+pub mod chain_profile;
+pub mod curve;
 pub mod erc4626;
+pub mod maverick_v2;
+pub mod rate_provider_cache;
 pub mod swapr;
 pub mod uniswap_v2;
 pub mod uniswap_v3;
 pub mod uniswap_v3_pair_provider;
+pub mod uniswap_v4;
 
 use {
     self::uniswap_v2::pool_fetching::{Pool, PoolFetching},
@@ -0,0 +1,304 @@
+//! Caching of the rates of the rate providers of Balancer pools.
+//!
+//! The state of a pool with rate-provided tokens includes the current rate of
+//! every provider, and many pools share the same providers (e.g. the ones of
+//! liquid staking tokens). The rates are therefore cached per provider and
+//! block and shared between the Balancer V2 and V3 pool fetchers. Rates of
+//! recently requested providers get refreshed in the background on every new
+//! block, so that the pool fetches of that block find them in the cache.
+
+use {
+    crate::request_sharing::BoxRequestSharing,
+    anyhow::{Result, anyhow},
+    contracts::IRateProvider,
+    ethcontract::{BlockId, BlockNumber, H160, U256},
+    ethrpc::{
+        Web3,
+        block_stream::{CurrentBlockWatcher, into_stream},
+    },
+    futures::{FutureExt as _, StreamExt as _},
+    prometheus::{IntCounterVec, IntGauge},
+    std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+        time::{Duration, Instant},
+    },
+    tracing::Instrument as _,
+};
+
+/// Configuration of the [`RateProviderCache`].
+#[derive(Clone, Copy, Debug)]
+pub struct Config {
+    /// How long fetched rates get served from the cache. Providers that were
+    /// not requested for this long are no longer refreshed in the background.
+    pub ttl: Duration,
+    /// Rates fetched for blocks more than this many blocks behind the current
+    /// block get evicted.
+    pub max_age_blocks: u64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            ttl: Duration::from_secs(60),
+            max_age_blocks: 5,
+        }
+    }
+}
+
+/// Fetches the rate of a rate provider at a block.
+#[cfg_attr(test, mockall::automock)]
+#[async_trait::async_trait]
+pub trait RateFetching: Send + Sync {
+    async fn fetch_rate(&self, provider: H160, block: BlockId) -> Result<U256>;
+}
+
+#[async_trait::async_trait]
+impl RateFetching for Web3 {
+    async fn fetch_rate(&self, provider: H160, block: BlockId) -> Result<U256> {
+        Ok(IRateProvider::at(self, provider)
+            .get_rate()
+            .block(block)
+            .call()
+            .await?)
+    }
+}
+
+/// Cache of the rates of rate providers keyed by provider and block.
+#[derive(Clone)]
+pub struct RateProviderCache(Arc<Inner>);
+
+struct Inner {
+    fetcher: Arc<dyn RateFetching>,
+    config: Config,
+    rates: Mutex<Rates>,
+    requests: BoxRequestSharing<(H160, u64), Option<U256>>,
+}
+
+#[derive(Default)]
+struct Rates {
+    last_seen_block: u64,
+    entries: HashMap<(H160, u64), Entry>,
+    /// When the rate of each provider was last requested.
+    requested: HashMap<H160, Instant>,
+}
+
+struct Entry {
+    rate: U256,
+    fetched_at: Instant,
+}
+
+impl RateProviderCache {
+    pub fn new(fetcher: Arc<dyn RateFetching>, config: Config) -> Self {
+        Self(Arc::new(Inner {
+            fetcher,
+            config,
+            rates: Default::default(),
+            requests: BoxRequestSharing::labelled("rate_providers".into()),
+        }))
+    }
+
+    /// Returns the rate of the provider at the specified block. Only rates of
+    /// blocks specified by number get cached.
+    pub async fn get_rate(&self, provider: H160, block: BlockId) -> Result<U256> {
+        let BlockId::Number(BlockNumber::Number(number)) = block else {
+            return self.0.fetcher.fetch_rate(provider, block).await;
+        };
+        let number = number.as_u64();
+
+        let cached = {
+            let mut rates = self.0.rates.lock().unwrap();
+            rates.requested.insert(provider, Instant::now());
+            rates
+                .entries
+                .get(&(provider, number))
+                .filter(|entry| entry.fetched_at.elapsed() < self.0.config.ttl)
+                .map(|entry| entry.rate)
+        };
+        Metrics::get()
+            .balancer_rate_provider_cache_access
+            .with_label_values(&[if cached.is_some() { "hits" } else { "misses" }])
+            .inc();
+        if let Some(rate) = cached {
+            return Ok(rate);
+        }
+
+        self.0
+            .fetch(provider, number)
+            .await
+            .ok_or_else(|| anyhow!("failed to fetch rate of provider {provider:?}"))
+    }
+
+    /// Spawns a task refreshing the rates of the recently requested providers
+    /// on every new block.
+    pub fn spawn_refresh_task(&self, block_stream: CurrentBlockWatcher) {
+        let inner = self.0.clone();
+        let mut stream = into_stream(block_stream);
+        let task = async move {
+            while let Some(block) = stream.next().await {
+                let providers = inner.start_block(block.number);
+                futures::future::join_all(
+                    providers
+                        .into_iter()
+                        .map(|provider| inner.fetch(provider, block.number)),
+                )
+                .await;
+            }
+            tracing::error!("block stream terminated unexpectedly");
+        };
+        tokio::spawn(task.instrument(tracing::info_span!("rate_provider_cache")));
+    }
+}
+
+impl Inner {
+    /// Fetches the rate of the provider at the specified block, sharing the
+    /// request with concurrent fetches of the same rate.
+    async fn fetch(self: &Arc<Self>, provider: H160, block: u64) -> Option<U256> {
+        let inner = self.clone();
+        self.requests
+            .shared_or_else((provider, block), move |&(provider, block)| {
+                async move {
+                    let rate = inner
+                        .fetcher
+                        .fetch_rate(provider, BlockId::Number(block.into()))
+                        .await;
+                    match rate {
+                        Ok(rate) => {
+                            inner.insert(provider, block, rate);
+                            Some(rate)
+                        }
+                        Err(err) => {
+                            tracing::debug!(?provider, block, ?err, "failed to fetch rate");
+                            None
+                        }
+                    }
+                }
+                .boxed()
+            })
+            .await
+    }
+
+    fn insert(&self, provider: H160, block: u64, rate: U256) {
+        let mut rates = self.rates.lock().unwrap();
+        if block + self.config.max_age_blocks < rates.last_seen_block {
+            // The rate was fetched for a block that is already evicted.
+            return;
+        }
+        rates.entries.insert(
+            (provider, block),
+            Entry {
+                rate,
+                fetched_at: Instant::now(),
+            },
+        );
+        Metrics::get()
+            .balancer_rate_provider_cache_entries
+            .set(rates.entries.len() as i64);
+    }
+
+    /// Evicts the rates of old blocks and expired rates and returns the
+    /// providers that were requested recently enough to get refreshed.
+    fn start_block(&self, block: u64) -> Vec<H160> {
+        let ttl = self.config.ttl;
+        let oldest_block = block.saturating_sub(self.config.max_age_blocks);
+        let mut rates = self.rates.lock().unwrap();
+        rates.last_seen_block = rates.last_seen_block.max(block);
+        rates.entries.retain(|&(_, number), entry| {
+            number >= oldest_block && entry.fetched_at.elapsed() < ttl
+        });
+        rates
+            .requested
+            .retain(|_, requested_at| requested_at.elapsed() < ttl);
+        Metrics::get()
+            .balancer_rate_provider_cache_entries
+            .set(rates.entries.len() as i64);
+        rates
+            .requested
+            .keys()
+            .filter(|&&provider| !rates.entries.contains_key(&(provider, block)))
+            .copied()
+            .collect()
+    }
+}
+
+#[derive(prometheus_metric_storage::MetricStorage)]
+struct Metrics {
+    /// Balancer rate provider cache hits and misses.
+    #[metric(labels("result"))]
+    balancer_rate_provider_cache_access: IntCounterVec,
+
+    /// Number of rates cached by the Balancer rate provider cache.
+    balancer_rate_provider_cache_entries: IntGauge,
+}
+
+impl Metrics {
+    fn get() -> &'static Self {
+        Metrics::instance(observe::metrics::get_storage_registry()).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, mockall::predicate::eq};
+
+    fn block(number: u64) -> BlockId {
+        BlockId::Number(number.into())
+    }
+
+    #[tokio::test]
+    async fn caches_rates_per_provider_and_block() {
+        let provider = H160::from_low_u64_be(1);
+        let mut fetcher = MockRateFetching::new();
+        fetcher
+            .expect_fetch_rate()
+            .with(eq(provider), eq(block(10)))
+            .times(1)
+            .returning(|_, _| Ok(U256::from(2)));
+        fetcher
+            .expect_fetch_rate()
+            .with(eq(provider), eq(block(11)))
+            .times(1)
+            .returning(|_, _| Ok(U256::from(3)));
+        let cache = RateProviderCache::new(Arc::new(fetcher), Config::default());
+
+        assert_eq!(
+            cache.get_rate(provider, block(10)).await.unwrap(),
+            U256::from(2)
+        );
+        assert_eq!(
+            cache.get_rate(provider, block(10)).await.unwrap(),
+            U256::from(2)
+        );
+        assert_eq!(
+            cache.get_rate(provider, block(11)).await.unwrap(),
+            U256::from(3)
+        );
+
+        // The refresh of a new block evicts the rates of blocks that are too old
+        // and returns the providers to prefetch.
+        assert_eq!(cache.0.start_block(16), vec![provider]);
+        let rates = cache.0.rates.lock().unwrap();
+        assert!(!rates.entries.contains_key(&(provider, 10)));
+        assert!(rates.entries.contains_key(&(provider, 11)));
+    }
+
+    #[tokio::test]
+    async fn refetches_expired_rates() {
+        let provider = H160::from_low_u64_be(1);
+        let mut fetcher = MockRateFetching::new();
+        fetcher
+            .expect_fetch_rate()
+            .times(2)
+            .returning(|_, _| Ok(U256::from(2)));
+        let cache = RateProviderCache::new(
+            Arc::new(fetcher),
+            Config {
+                ttl: Duration::ZERO,
+                ..Default::default()
+            },
+        );
+
+        cache.get_rate(provider, block(10)).await.unwrap();
+        cache.get_rate(provider, block(10)).await.unwrap();
+    }
+}
@@ -3,6 +3,7 @@
 pub mod pair_provider;
 pub mod pool_cache;
 pub mod pool_fetching;
+pub mod pool_list;
 
 use {
     self::{
@@ -280,41 +280,51 @@ impl DefaultPoolReader {
 impl PoolReading for DefaultPoolReader {
     fn read_state(&self, pair: TokenPair, block: BlockId) -> BoxFuture<'_, Result<Option<Pool>>> {
         let pair_address = self.pair_provider.pair_address(&pair);
+        read_pool_state(&self.web3, pair, pair_address, block)
+    }
+}
 
-        // Fetch ERC20 token balances of the pools to sanity check with reserves
-        let token0 = ERC20::Instance::new(pair.get().0, self.web3.alloy.clone());
-        let token1 = ERC20::Instance::new(pair.get().1, self.web3.alloy.clone());
-
-        async move {
-            let fetch_token0_balance = token0
-                .balanceOf(pair_address.into_alloy())
-                .block(block.into_alloy());
-            let fetch_token1_balance = token1
-                .balanceOf(pair_address.into_alloy())
-                .block(block.into_alloy());
-
-            let pair_contract =
-                IUniswapLikePair::Instance::new(pair_address.into_alloy(), self.web3.alloy.clone());
-            let fetch_reserves = pair_contract.getReserves().block(block.into_alloy());
-
-            let (reserves, token0_balance, token1_balance) = futures::join!(
-                fetch_reserves.call().into_future(),
-                fetch_token0_balance.call().into_future(),
-                fetch_token1_balance.call().into_future()
-            );
+/// Reads the state of the Uniswap V2 like pool at the specified address,
+/// assuming a constant fee of 0.3%.
+pub fn read_pool_state(
+    web3: &Web3,
+    pair: TokenPair,
+    pair_address: H160,
+    block: BlockId,
+) -> BoxFuture<'_, Result<Option<Pool>>> {
+    // Fetch ERC20 token balances of the pools to sanity check with reserves
+    let token0 = ERC20::Instance::new(pair.get().0, web3.alloy.clone());
+    let token1 = ERC20::Instance::new(pair.get().1, web3.alloy.clone());
+
+    async move {
+        let fetch_token0_balance = token0
+            .balanceOf(pair_address.into_alloy())
+            .block(block.into_alloy());
+        let fetch_token1_balance = token1
+            .balanceOf(pair_address.into_alloy())
+            .block(block.into_alloy());
+
+        let pair_contract =
+            IUniswapLikePair::Instance::new(pair_address.into_alloy(), web3.alloy.clone());
+        let fetch_reserves = pair_contract.getReserves().block(block.into_alloy());
+
+        let (reserves, token0_balance, token1_balance) = futures::join!(
+            fetch_reserves.call().into_future(),
+            fetch_token0_balance.call().into_future(),
+            fetch_token1_balance.call().into_future()
+        );
 
-            handle_results(
-                FetchedPool {
-                    pair,
-                    reserves,
-                    token0_balance,
-                    token1_balance,
-                },
-                pair_address,
-            )
-        }
-        .boxed()
+        handle_results(
+            FetchedPool {
+                pair,
+                reserves,
+                token0_balance,
+                token1_balance,
+            },
+            pair_address,
+        )
     }
+    .boxed()
 }
 
 struct FetchedPool {
@@ -0,0 +1,222 @@
+//! Support for externally maintained lists of Uniswap V2 like pools.
+//!
+//! Some Uniswap V2 forks are deployed on chains without a subgraph, or use
+//! factories whose pair addresses can't be derived from an init code digest.
+//! For those, a community maintained pool list (in the spirit of token lists)
+//! can be used instead. Listed pools are only trusted after verifying on-chain
+//! that they are pairs of the expected factory trading the listed tokens.
+
+use {
+    super::pool_fetching::{Pool, PoolReading, read_pool_state},
+    crate::ethrpc::Web3,
+    alloy::primitives::Address,
+    anyhow::{Context, Result},
+    contracts::alloy::IUniswapLikePair,
+    ethcontract::{BlockId, H160},
+    ethrpc::alloy::{
+        conversions::{IntoAlloy, IntoLegacy},
+        errors::ignore_non_node_error,
+    },
+    futures::{FutureExt as _, future::BoxFuture},
+    model::TokenPair,
+    num::rational::Ratio,
+    serde::Deserialize,
+    std::{collections::HashMap, path::Path, sync::Arc},
+};
+
+/// The base amount for pool list fees representing 100%.
+const FEE_BASE: u32 = 10_000;
+
+/// The JSON format of a pool list.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct File {
+    pools: Vec<Entry>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Entry {
+    address: Address,
+    token0: Address,
+    token1: Address,
+    /// The swap fee of the pool in basis points.
+    fee: u32,
+}
+
+/// A pool taken from a pool list.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ListedPool {
+    pub address: H160,
+    pub fee: Ratio<u32>,
+}
+
+/// A collection of listed pools indexed by the token pair they trade.
+#[derive(Debug, Default)]
+pub struct PoolList(HashMap<TokenPair, ListedPool>);
+
+impl PoolList {
+    /// Parses a JSON pool list.
+    ///
+    /// Entries that don't describe a valid pool are skipped. If multiple pools
+    /// are listed for the same token pair, only the first one is used.
+    pub fn parse(json: &str) -> Result<Self> {
+        let file: File = serde_json::from_str(json).context("invalid pool list")?;
+
+        let mut pools = HashMap::new();
+        for entry in file.pools {
+            let Some(pair) = TokenPair::new(entry.token0, entry.token1) else {
+                tracing::warn!(?entry, "skipping listed pool with identical tokens");
+                continue;
+            };
+            if entry.fee >= FEE_BASE {
+                tracing::warn!(?entry, "skipping listed pool with invalid fee");
+                continue;
+            }
+            pools.entry(pair).or_insert(ListedPool {
+                address: entry.address.into_legacy(),
+                fee: Ratio::new(entry.fee, FEE_BASE),
+            });
+        }
+        Ok(Self(pools))
+    }
+
+    /// Reads and parses the JSON pool list at the specified path.
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read pool list {}", path.display()))?;
+        Self::parse(&json)
+    }
+
+    /// Verifies the listed pools on-chain, only keeping the ones that were
+    /// created by the specified factory and trade the listed tokens.
+    pub async fn validate(self, web3: &Web3, factory: H160) -> Result<Self> {
+        let checks = self.0.into_iter().map(|(pair, pool)| async move {
+            let valid = is_valid_pool(web3, factory, pair, pool.address).await?;
+            if !valid {
+                tracing::warn!(?pair, ?pool, "excluding listed pool failing validation");
+            }
+            Result::<_>::Ok(valid.then_some((pair, pool)))
+        });
+        let pools = futures::future::try_join_all(checks)
+            .await?
+            .into_iter()
+            .flatten()
+            .collect();
+        Ok(Self(pools))
+    }
+
+    pub fn get(&self, pair: &TokenPair) -> Option<&ListedPool> {
+        self.0.get(pair)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Checks that the contract at the specified address is a Uniswap V2 like pair
+/// of the specified factory for the expected tokens. Node errors are bubbled
+/// up while contract errors mark the pool as invalid.
+async fn is_valid_pool(web3: &Web3, factory: H160, pair: TokenPair, address: H160) -> Result<bool> {
+    let contract = IUniswapLikePair::Instance::new(address.into_alloy(), web3.alloy.clone());
+    let fetch_factory = contract.factory();
+    let fetch_token0 = contract.token0();
+    let fetch_token1 = contract.token1();
+    let (actual_factory, token0, token1) = futures::join!(
+        fetch_factory.call().into_future(),
+        fetch_token0.call().into_future(),
+        fetch_token1.call().into_future(),
+    );
+
+    let (Some(actual_factory), Some(token0), Some(token1)) = (
+        ignore_non_node_error(actual_factory)?,
+        ignore_non_node_error(token0)?,
+        ignore_non_node_error(token1)?,
+    ) else {
+        return Ok(false);
+    };
+    Ok(actual_factory == factory.into_alloy() && (token0, token1) == pair.get())
+}
+
+/// A pool reader that reads the state of listed pools instead of deriving the
+/// pool address from the token pair.
+pub struct ListedPoolReader {
+    pub pools: Arc<PoolList>,
+    pub web3: Web3,
+}
+
+impl PoolReading for ListedPoolReader {
+    fn read_state(&self, pair: TokenPair, block: BlockId) -> BoxFuture<'_, Result<Option<Pool>>> {
+        let Some(listed) = self.pools.get(&pair).copied() else {
+            return futures::future::ready(Ok(None)).boxed();
+        };
+        read_pool_state(&self.web3, pair, listed.address, block)
+            .map(move |pool| {
+                Ok(pool?.map(|pool| Pool {
+                    fee: listed.fee,
+                    ..pool
+                }))
+            })
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, alloy::primitives::address};
+
+    #[test]
+    fn parses_pool_list() {
+        let list = PoolList::parse(
+            r#"{
+                "name": "Example Pools",
+                "pools": [
+                    {
+                        "address": "0x1111111111111111111111111111111111111111",
+                        "token0": "0x0000000000000000000000000000000000000002",
+                        "token1": "0x0000000000000000000000000000000000000001",
+                        "fee": 25
+                    },
+                    {
+                        "address": "0x2222222222222222222222222222222222222222",
+                        "token0": "0x0000000000000000000000000000000000000001",
+                        "token1": "0x0000000000000000000000000000000000000002",
+                        "fee": 30
+                    },
+                    {
+                        "address": "0x3333333333333333333333333333333333333333",
+                        "token0": "0x0000000000000000000000000000000000000003",
+                        "token1": "0x0000000000000000000000000000000000000003",
+                        "fee": 30
+                    },
+                    {
+                        "address": "0x4444444444444444444444444444444444444444",
+                        "token0": "0x0000000000000000000000000000000000000003",
+                        "token1": "0x0000000000000000000000000000000000000004",
+                        "fee": 10000
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let pair = TokenPair::new(
+            address!("0000000000000000000000000000000000000001"),
+            address!("0000000000000000000000000000000000000002"),
+        )
+        .unwrap();
+        assert_eq!(list.len(), 1);
+        assert_eq!(
+            list.get(&pair),
+            Some(&ListedPool {
+                address: H160::repeat_byte(0x11),
+                fee: Ratio::new(25, 10_000),
+            })
+        );
+    }
+}
@@ -0,0 +1,239 @@
+//! Uniswap V4 liquidity.
+//!
+//! Uniswap V4 pools don't have contracts of their own, they all live in the
+//! PoolManager singleton and are identified by their [`PoolKey`]. A pool can
+//! be initialized with a hooks contract which the PoolManager calls around
+//! swaps, so hooks can change the outcome of swaps arbitrarily. Only pools
+//! whose hooks don't get called on swaps, or whose hooks are explicitly
+//! allowed, are considered routable.
+
+pub mod pool_fetching;
+
+use {
+    crate::baseline_solver::BaselineSolvable,
+    alloy::{
+        primitives::{Address, Bytes, keccak256},
+        sol_types::SolValue,
+    },
+    contracts::alloy::UniswapV4Quoter::{
+        self,
+        IV4Quoter::QuoteExactSingleParams,
+        UniswapV4Quoter::PoolKey as QuoterPoolKey,
+    },
+    ethcontract::{H160, H256, U256},
+    ethrpc::alloy::conversions::{IntoAlloy, IntoLegacy},
+    model::TokenPair,
+    std::{collections::HashSet, sync::Arc},
+};
+
+/// The key identifying a Uniswap V4 pool in the PoolManager.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct PoolKey {
+    /// The lower of the two currencies of the pool. The zero address stands
+    /// for native ETH.
+    pub currency0: H160,
+    pub currency1: H160,
+    /// The LP fee in hundredths of a basis point, or [`DYNAMIC_FEE_FLAG`] for
+    /// pools whose fee is set by their hooks.
+    pub fee: u32,
+    pub tick_spacing: i32,
+    pub hooks: H160,
+}
+
+/// The fee of pools whose LP fee is set by their hooks.
+pub const DYNAMIC_FEE_FLAG: u32 = 0x80_0000;
+
+impl PoolKey {
+    /// The ID of the pool in the PoolManager, which is the hash of its ABI
+    /// encoded key.
+    pub fn id(&self) -> H256 {
+        H256(keccak256(self.to_tuple().abi_encode()).0)
+    }
+
+    /// The key as a tuple that ABI encodes like the `PoolKey` struct of the
+    /// Uniswap V4 contracts. `uint24` and `int24` values are ABI encoded like
+    /// `uint32` and `int32` values.
+    pub fn to_tuple(&self) -> (Address, Address, u32, i32, Address) {
+        (
+            self.currency0.into_alloy(),
+            self.currency1.into_alloy(),
+            self.fee,
+            self.tick_spacing,
+            self.hooks.into_alloy(),
+        )
+    }
+
+    /// The token pair traded by the pool.
+    pub fn tokens(&self) -> Option<TokenPair> {
+        TokenPair::new(self.currency0.into_alloy(), self.currency1.into_alloy())
+    }
+
+    /// Returns whether swapping `in_token` for `out_token` swaps `currency0`
+    /// for `currency1`, or `None` if the pool doesn't trade the tokens.
+    pub fn zero_for_one(&self, in_token: H160, out_token: H160) -> Option<bool> {
+        if (in_token, out_token) == (self.currency0, self.currency1) {
+            Some(true)
+        } else if (in_token, out_token) == (self.currency1, self.currency0) {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    /// Returns whether the pool can be routed through by the settlement
+    /// contract. The settlement contract only holds ERC20 tokens, so pools of
+    /// native ETH are not routable, and neither are pools whose hooks get
+    /// called on swaps unless they are in `allowed_hooks`.
+    pub fn is_routable(&self, allowed_hooks: &HashSet<H160>) -> bool {
+        !self.currency0.is_zero()
+            && (!Hooks(self.hooks).on_swap() || allowed_hooks.contains(&self.hooks))
+    }
+
+    fn to_quoter(self) -> QuoterPoolKey {
+        QuoterPoolKey {
+            currency0: self.currency0.into_alloy(),
+            currency1: self.currency1.into_alloy(),
+            fee: self.fee.try_into().expect("fee < (2^24)"),
+            tickSpacing: self
+                .tick_spacing
+                .try_into()
+                .expect("tick spacing is an int24"),
+            hooks: self.hooks.into_alloy(),
+        }
+    }
+}
+
+/// The permissions of a hooks contract, which are encoded in the lowest 14
+/// bits of its address.
+///
+/// [^1]: <https://github.com/Uniswap/v4-core/blob/main/src/libraries/Hooks.sol>
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Hooks(pub H160);
+
+impl Hooks {
+    const BEFORE_SWAP: u16 = 1 << 7;
+    const AFTER_SWAP: u16 = 1 << 6;
+    const BEFORE_SWAP_RETURNS_DELTA: u16 = 1 << 3;
+    const AFTER_SWAP_RETURNS_DELTA: u16 = 1 << 2;
+
+    fn flags(&self) -> u16 {
+        let address = self.0.as_bytes();
+        u16::from_be_bytes([address[18], address[19]]) & 0x3fff
+    }
+
+    /// Returns whether the hooks get called by the PoolManager on swaps.
+    pub fn on_swap(&self) -> bool {
+        self.flags()
+            & (Self::BEFORE_SWAP
+                | Self::AFTER_SWAP
+                | Self::BEFORE_SWAP_RETURNS_DELTA
+                | Self::AFTER_SWAP_RETURNS_DELTA)
+            != 0
+    }
+}
+
+/// A Uniswap V4 pool quoted with eth_calls to the V4Quoter. Quotes simulate
+/// the swap in the PoolManager, so they include the effects of the pool's
+/// hooks at the current state of the chain.
+#[derive(Clone, Debug)]
+pub struct Pool {
+    pub quoter: Arc<UniswapV4Quoter::Instance>,
+    pub key: PoolKey,
+}
+
+impl Pool {
+    /// Rough estimate of a swap through the UniversalRouter, including the
+    /// Permit2 approval of the router.
+    pub const POOL_SWAP_GAS_COST: usize = 150_000;
+
+    fn params(&self, zero_for_one: bool, amount: U256) -> Option<QuoteExactSingleParams> {
+        Some(QuoteExactSingleParams {
+            poolKey: self.key.to_quoter(),
+            zeroForOne: zero_for_one,
+            exactAmount: u128::try_from(amount).ok()?,
+            hookData: Bytes::new(),
+        })
+    }
+}
+
+impl BaselineSolvable for Pool {
+    async fn get_amount_out(
+        &self,
+        out_token: H160,
+        (in_amount, in_token): (U256, H160),
+    ) -> Option<U256> {
+        let zero_for_one = self.key.zero_for_one(in_token, out_token)?;
+        self.quoter
+            .quoteExactInputSingle(self.params(zero_for_one, in_amount)?)
+            .call()
+            .await
+            .map(|result| result.amountOut.into_legacy())
+            .ok()
+    }
+
+    async fn get_amount_in(
+        &self,
+        in_token: H160,
+        (out_amount, out_token): (U256, H160),
+    ) -> Option<U256> {
+        let zero_for_one = self.key.zero_for_one(in_token, out_token)?;
+        self.quoter
+            .quoteExactOutputSingle(self.params(zero_for_one, out_amount)?)
+            .call()
+            .await
+            .map(|result| result.amountIn.into_legacy())
+            .ok()
+    }
+
+    async fn gas_cost(&self) -> usize {
+        Self::POOL_SWAP_GAS_COST
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, hex_literal::hex};
+
+    #[test]
+    fn computes_pool_ids() {
+        // The ETH/USDC 0.05% pool on mainnet.
+        let key = PoolKey {
+            currency0: H160::zero(),
+            currency1: H160(hex!("a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48")),
+            fee: 500,
+            tick_spacing: 10,
+            hooks: H160::zero(),
+        };
+        assert_eq!(
+            key.id(),
+            H256(hex!(
+                "21c67e77068de97969ba93d4aab21826d33ca12bb9f565d8496e8fda8a82ca27"
+            ))
+        );
+    }
+
+    #[test]
+    fn only_pools_without_swap_hooks_are_routable() {
+        let key = |hooks: u64| PoolKey {
+            currency0: H160::from_low_u64_be(1),
+            currency1: H160::from_low_u64_be(2),
+            fee: 3000,
+            tick_spacing: 60,
+            hooks: H160::from_low_u64_be(hooks),
+        };
+        let allowed = HashSet::new();
+
+        assert!(key(0).is_routable(&allowed));
+        // Hooks that only get called when adding liquidity.
+        assert!(key(0x1_0000 | 1 << 11).is_routable(&allowed));
+        // Hooks that get called before swaps.
+        assert!(!key(0x1_0000 | 1 << 7).is_routable(&allowed));
+        assert!(key(0x1_0000 | 1 << 7).is_routable(&[H160::from_low_u64_be(0x1_0080)].into()));
+
+        let native = PoolKey {
+            currency0: H160::zero(),
+            ..key(0)
+        };
+        assert!(!native.is_routable(&allowed));
+    }
+}
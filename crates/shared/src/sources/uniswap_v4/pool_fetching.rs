@@ -0,0 +1,261 @@
+//! Indexing of the pools initialized in the Uniswap V4 PoolManager.
+//!
+//! Pools are indexed from the `Initialize` events of the PoolManager, starting
+//! at its deployment. The state of the pools isn't indexed, since pools get
+//! quoted with eth_calls instead.
+
+use {
+    super::PoolKey,
+    crate::{
+        event_handling::{AlloyEventRetriever, AlloyEventRetrieving, EventHandler, EventStoring},
+        maintenance::Maintaining,
+    },
+    alloy::{
+        primitives::Address,
+        providers::DynProvider,
+        rpc::types::{Filter, Log},
+        sol_types::SolEvent,
+    },
+    anyhow::{Context, Result},
+    contracts::alloy::UniswapV4PoolManager::{
+        self,
+        UniswapV4PoolManager::{Initialize, UniswapV4PoolManagerEvents},
+    },
+    ethcontract::{H160, H256},
+    ethrpc::{
+        alloy::conversions::IntoLegacy,
+        block_stream::{BlockRetrieving, RangeInclusive},
+    },
+    model::TokenPair,
+    std::{
+        collections::{HashMap, HashSet},
+        sync::Arc,
+    },
+    tokio::sync::Mutex,
+};
+
+pub struct PoolManagerEventFetcher {
+    pool_manager: Address,
+    provider: DynProvider,
+}
+
+impl AlloyEventRetrieving for PoolManagerEventFetcher {
+    type Event = UniswapV4PoolManagerEvents;
+
+    fn filter(&self) -> Filter {
+        Filter::new()
+            .address(self.pool_manager)
+            .event_signature(Initialize::SIGNATURE_HASH)
+    }
+
+    fn provider(&self) -> &DynProvider {
+        &self.provider
+    }
+}
+
+/// The pools initialized in the PoolManager.
+pub struct PoolStorage {
+    /// The pools by their ID and the block they were initialized in.
+    pools: HashMap<H256, (PoolKey, u64)>,
+    pools_by_token_pair: HashMap<TokenPair, HashSet<H256>>,
+    last_indexed_block: u64,
+}
+
+impl PoolStorage {
+    /// Creates an empty storage that starts indexing at the specified block.
+    pub fn new(start_block: u64) -> Self {
+        Self {
+            pools: Default::default(),
+            pools_by_token_pair: Default::default(),
+            last_indexed_block: start_block,
+        }
+    }
+
+    /// Returns the pools trading any of the token pairs.
+    pub fn pools_by_token_pairs(&self, token_pairs: &HashSet<TokenPair>) -> Vec<PoolKey> {
+        token_pairs
+            .iter()
+            .filter_map(|pair| self.pools_by_token_pair.get(pair))
+            .flatten()
+            .map(|id| self.pools[id].0)
+            .collect()
+    }
+
+    fn insert(&mut self, key: PoolKey, block: u64) {
+        let Some(pair) = key.tokens() else {
+            return;
+        };
+        let id = key.id();
+        self.pools.insert(id, (key, block));
+        self.pools_by_token_pair.entry(pair).or_default().insert(id);
+    }
+
+    /// Removes the pools initialized in or after the specified block.
+    fn remove_pools_newer_than_block(&mut self, block: u64) {
+        let removed = self
+            .pools
+            .iter()
+            .filter(|(_, (_, initialized))| *initialized >= block)
+            .map(|(id, (key, _))| (*id, *key))
+            .collect::<Vec<_>>();
+        for (id, key) in removed {
+            self.pools.remove(&id);
+            if let Some(pair) = key.tokens()
+                && let Some(ids) = self.pools_by_token_pair.get_mut(&pair)
+            {
+                ids.remove(&id);
+                if ids.is_empty() {
+                    self.pools_by_token_pair.remove(&pair);
+                }
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl EventStoring<(UniswapV4PoolManagerEvents, Log)> for PoolStorage {
+    async fn replace_events(
+        &mut self,
+        events: Vec<(UniswapV4PoolManagerEvents, Log)>,
+        range: RangeInclusive<u64>,
+    ) -> Result<()> {
+        self.remove_pools_newer_than_block(*range.start());
+        self.append_events(events).await
+    }
+
+    async fn append_events(
+        &mut self,
+        events: Vec<(UniswapV4PoolManagerEvents, Log)>,
+    ) -> Result<()> {
+        for (event, log) in events {
+            let block = log.block_number.context("log block number is empty")?;
+            match event {
+                UniswapV4PoolManagerEvents::Initialize(event) => {
+                    self.insert(pool_key(&event), block);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn last_event_block(&self) -> Result<u64> {
+        Ok(self.last_indexed_block)
+    }
+
+    async fn persist_last_indexed_block(&mut self, block: u64) -> Result<()> {
+        self.last_indexed_block = block;
+        Ok(())
+    }
+}
+
+fn pool_key(event: &Initialize) -> PoolKey {
+    PoolKey {
+        currency0: event.currency0.into_legacy(),
+        currency1: event.currency1.into_legacy(),
+        fee: event.fee.to(),
+        tick_spacing: event.tickSpacing.as_i32(),
+        hooks: event.hooks.into_legacy(),
+    }
+}
+
+/// Indexes the pools of the PoolManager and returns the routable ones for
+/// token pairs.
+pub struct UniswapV4PoolFetcher {
+    events: Mutex<
+        EventHandler<
+            AlloyEventRetriever<PoolManagerEventFetcher>,
+            PoolStorage,
+            (UniswapV4PoolManagerEvents, Log),
+        >,
+    >,
+    /// Hooks that get called on swaps but are known to be safe to route
+    /// through.
+    allowed_hooks: HashSet<H160>,
+}
+
+impl UniswapV4PoolFetcher {
+    /// Creates a fetcher indexing the pools of the PoolManager initialized
+    /// after `start_block`, usually the deployment block of the PoolManager.
+    pub fn new(
+        pool_manager: &UniswapV4PoolManager::Instance,
+        start_block: u64,
+        block_retriever: Arc<dyn BlockRetrieving>,
+        allowed_hooks: HashSet<H160>,
+    ) -> Self {
+        let events = Mutex::new(EventHandler::new(
+            block_retriever,
+            AlloyEventRetriever(PoolManagerEventFetcher {
+                pool_manager: *pool_manager.address(),
+                provider: pool_manager.provider().clone(),
+            }),
+            PoolStorage::new(start_block),
+            None,
+        ));
+        Self {
+            events,
+            allowed_hooks,
+        }
+    }
+
+    /// Returns the routable pools trading any of the token pairs.
+    pub async fn fetch(&self, token_pairs: &HashSet<TokenPair>) -> Vec<PoolKey> {
+        let pools = self
+            .events
+            .lock()
+            .await
+            .store()
+            .pools_by_token_pairs(token_pairs);
+        pools
+            .into_iter()
+            .filter(|key| key.is_routable(&self.allowed_hooks))
+            .collect()
+    }
+}
+
+#[async_trait::async_trait]
+impl Maintaining for UniswapV4PoolFetcher {
+    async fn run_maintenance(&self) -> Result<()> {
+        self.events.run_maintenance().await
+    }
+
+    fn name(&self) -> &str {
+        "UniswapV4PoolFetcher"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(currency0: u64, currency1: u64, fee: u32) -> PoolKey {
+        PoolKey {
+            currency0: H160::from_low_u64_be(currency0),
+            currency1: H160::from_low_u64_be(currency1),
+            fee,
+            tick_spacing: 60,
+            hooks: H160::zero(),
+        }
+    }
+
+    fn pair(a: u64, b: u64) -> TokenPair {
+        key(a, b, 0).tokens().unwrap()
+    }
+
+    #[test]
+    fn removes_pools_of_reorged_blocks() {
+        let mut storage = PoolStorage::new(0);
+        storage.insert(key(1, 2, 500), 10);
+        storage.insert(key(1, 2, 3000), 11);
+        storage.insert(key(2, 3, 3000), 12);
+
+        let pools = storage.pools_by_token_pairs(&[pair(1, 2), pair(2, 3)].into());
+        assert_eq!(pools.len(), 3);
+
+        storage.remove_pools_newer_than_block(11);
+        assert_eq!(
+            storage.pools_by_token_pairs(&[pair(1, 2), pair(2, 3)].into()),
+            vec![key(1, 2, 500)]
+        );
+        assert!(storage.pools_by_token_pair.get(&pair(2, 3)).is_none());
+    }
+}
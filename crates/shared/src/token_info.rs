@@ -157,6 +157,53 @@ impl TokenInfoFetching for CachedTokenInfoFetcher {
     }
 }
 
+/// Token info fetcher that reports configured decimals for non-standard tokens,
+/// e.g. tokens whose `decimals()` getter is missing or reports a wrong value.
+pub struct DecimalsOverridingTokenInfoFetcher {
+    inner: Arc<dyn TokenInfoFetching>,
+    decimals: HashMap<H160, u8>,
+}
+
+impl DecimalsOverridingTokenInfoFetcher {
+    pub fn new(inner: Arc<dyn TokenInfoFetching>, decimals: HashMap<H160, u8>) -> Self {
+        Self { inner, decimals }
+    }
+
+    fn apply(&self, address: H160, info: TokenInfo) -> TokenInfo {
+        match self.decimals.get(&address) {
+            Some(&decimals) => TokenInfo {
+                decimals: Some(decimals),
+                ..info
+            },
+            None => info,
+        }
+    }
+}
+
+#[async_trait]
+impl TokenInfoFetching for DecimalsOverridingTokenInfoFetcher {
+    async fn get_token_info(&self, address: H160) -> Result<TokenInfo, Error> {
+        let info = self.inner.get_token_info(address).await;
+        match info {
+            Err(_) if self.decimals.contains_key(&address) => {
+                Ok(self.apply(address, TokenInfo::default()))
+            }
+            info => info.map(|info| self.apply(address, info)),
+        }
+    }
+
+    async fn get_token_infos(&self, addresses: &[H160]) -> HashMap<H160, TokenInfo> {
+        let mut infos = self.inner.get_token_infos(addresses).await;
+        for address in addresses {
+            if self.decimals.contains_key(address) {
+                let info = infos.remove(address).unwrap_or_default();
+                infos.insert(*address, self.apply(*address, info));
+            }
+        }
+        infos
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use {super::*, maplit::hashmap, mockall::predicate::*};
@@ -220,4 +267,62 @@ mod tests {
         let cached_token_infos = cached_token_info_fetcher.get_token_infos(&addresses).await;
         assert_eq!(token_infos, cached_token_infos);
     }
+
+    #[tokio::test]
+    async fn decimals_overriding_token_info_fetcher() {
+        let address = H160::from_low_u64_be;
+
+        let mut mock_token_info_fetcher = MockTokenInfoFetching::new();
+        mock_token_info_fetcher
+            .expect_get_token_infos()
+            .returning(move |_| {
+                hashmap! {
+                    address(0) => TokenInfo {
+                        decimals: Some(18),
+                        symbol: Some("CAT".to_string()),
+                    },
+                    address(1) => TokenInfo {
+                        decimals: Some(18),
+                        symbol: Some("DOG".to_string()),
+                    },
+                }
+            });
+        mock_token_info_fetcher
+            .expect_get_token_info()
+            .with(eq(address(2)))
+            .returning(|_| Err(Error("some error".to_string())));
+
+        let fetcher = DecimalsOverridingTokenInfoFetcher::new(
+            Arc::new(mock_token_info_fetcher),
+            hashmap! {
+                address(1) => 6,
+                address(2) => 8,
+            },
+        );
+
+        let token_infos = fetcher
+            .get_token_infos(&[address(0), address(1), address(2)])
+            .await;
+        assert_eq!(
+            token_infos,
+            hashmap! {
+                address(0) => TokenInfo {
+                    decimals: Some(18),
+                    symbol: Some("CAT".to_string()),
+                },
+                address(1) => TokenInfo {
+                    decimals: Some(6),
+                    symbol: Some("DOG".to_string()),
+                },
+                address(2) => TokenInfo {
+                    decimals: Some(8),
+                    symbol: None,
+                },
+            }
+        );
+        assert_eq!(
+            fetcher.get_token_info(address(2)).await.unwrap().decimals,
+            Some(8)
+        );
+    }
 }
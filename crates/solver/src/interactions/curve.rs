@@ -0,0 +1,67 @@
+//! Module for swap interactions with Curve pools.
+
+use {
+    alloy::{
+        primitives::{Address, U256},
+        sol_types::SolCall,
+    },
+    contracts::alloy::CurveStableSwapPool,
+    shared::interaction::{EncodedInteraction, Interaction},
+};
+
+/// Swaps `amount_in` of coin `i` for at least `min_amount_out` of coin `j`
+/// through a StableSwap pool. The pool transfers the input from the caller, so
+/// it needs an allowance.
+#[derive(Clone, Debug)]
+pub struct CurveStableSwapInteraction {
+    pub pool: Address,
+    pub i: i128,
+    pub j: i128,
+    pub amount_in: U256,
+    pub min_amount_out: U256,
+}
+
+impl Interaction for CurveStableSwapInteraction {
+    fn encode(&self) -> EncodedInteraction {
+        (
+            self.pool,
+            U256::ZERO,
+            CurveStableSwapPool::CurveStableSwapPool::exchangeCall {
+                i: self.i,
+                j: self.j,
+                dx: self.amount_in,
+                min_dy: self.min_amount_out,
+            }
+            .abi_encode()
+            .into(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_stable_swap_exchange() {
+        let interaction = CurveStableSwapInteraction {
+            pool: Address::repeat_byte(0x01),
+            i: 2,
+            j: 0,
+            amount_in: U256::from(1337),
+            min_amount_out: U256::from(42),
+        };
+
+        let (target, value, calldata) = interaction.encode();
+        assert_eq!(target, interaction.pool);
+        assert!(value.is_zero());
+        // exchange(int128,int128,uint256,uint256)
+        assert_eq!(calldata[..4], [0x3d, 0xf0, 0x21, 0x24]);
+
+        let call =
+            CurveStableSwapPool::CurveStableSwapPool::exchangeCall::abi_decode(&calldata).unwrap();
+        assert_eq!((call.i, call.j), (2, 0));
+        assert_eq!(call.dx, U256::from(1337));
+        assert_eq!(call.min_dy, U256::from(42));
+    }
+}
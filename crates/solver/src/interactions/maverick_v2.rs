@@ -0,0 +1,70 @@
+//! Module for swap interactions through the Maverick V2 router.
+
+use {
+    alloy::{
+        primitives::{Address, U256},
+        sol_types::SolCall,
+    },
+    contracts::alloy::MaverickV2Router,
+    shared::interaction::{EncodedInteraction, Interaction},
+};
+
+/// Swaps at most `amount_in_max` for exactly `amount_out` through a single
+/// pool. The router pulls the input tokens from the caller, so it needs an
+/// allowance.
+#[derive(Clone, Debug)]
+pub struct MaverickV2Interaction {
+    pub router: Address,
+    pub pool: Address,
+    pub recipient: Address,
+    pub token_a_in: bool,
+    pub amount_out: U256,
+    pub amount_in_max: U256,
+}
+
+impl Interaction for MaverickV2Interaction {
+    fn encode(&self) -> EncodedInteraction {
+        (
+            self.router,
+            U256::ZERO,
+            MaverickV2Router::MaverickV2Router::exactOutputSingleCall {
+                recipient: self.recipient,
+                pool: self.pool,
+                tokenAIn: self.token_a_in,
+                amountOut: self.amount_out,
+                amountInMaximum: self.amount_in_max,
+            }
+            .abi_encode()
+            .into(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_exact_output_single() {
+        let interaction = MaverickV2Interaction {
+            router: Address::repeat_byte(0x01),
+            pool: Address::repeat_byte(0x02),
+            recipient: Address::repeat_byte(0x03),
+            token_a_in: false,
+            amount_out: U256::from(42),
+            amount_in_max: U256::from(1337),
+        };
+
+        let (target, value, calldata) = interaction.encode();
+        assert_eq!(target, interaction.router);
+        assert!(value.is_zero());
+
+        let call = MaverickV2Router::MaverickV2Router::exactOutputSingleCall::abi_decode(&calldata)
+            .unwrap();
+        assert_eq!(call.recipient, interaction.recipient);
+        assert_eq!(call.pool, interaction.pool);
+        assert!(!call.tokenAIn);
+        assert_eq!(call.amountOut, U256::from(42));
+        assert_eq!(call.amountInMaximum, U256::from(1337));
+    }
+}
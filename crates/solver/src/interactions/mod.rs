@@ -1,20 +1,26 @@
 pub mod allowances;
 mod balancer_v2;
 mod balancer_v3;
+mod curve;
 mod erc20;
 pub mod erc4626;
+mod maverick_v2;
 mod uniswap_v2;
 mod uniswap_v3;
+mod uniswap_v4;
 mod weth;
 mod zeroex;
 
 pub use {
     balancer_v2::BalancerSwapGivenOutInteraction,
     balancer_v3::BalancerV3SwapGivenOutInteraction,
+    curve::CurveStableSwapInteraction,
     erc20::Erc20ApproveInteraction,
     erc4626::{MintExactSharesInteraction, WithdrawExactAssetsInteraction},
+    maverick_v2::MaverickV2Interaction,
     uniswap_v2::UniswapInteraction,
     uniswap_v3::UniswapV3Interaction,
+    uniswap_v4::{Permit2ApproveInteraction, UniswapV4Interaction},
     weth::UnwrapWethInteraction,
     zeroex::ZeroExInteraction,
 };
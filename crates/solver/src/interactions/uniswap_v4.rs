@@ -0,0 +1,160 @@
+//! Module for Uniswap V4 swap interactions through the UniversalRouter.
+
+use {
+    alloy::{
+        primitives::{Address, Bytes, U160, U256, aliases::U48},
+        sol_types::{SolCall, SolValue},
+    },
+    contracts::alloy::{Permit2, UniswapV4UniversalRouter},
+    shared::{
+        interaction::{EncodedInteraction, Interaction},
+        sources::uniswap_v4::PoolKey,
+    },
+    std::sync::LazyLock,
+};
+
+/// The UniversalRouter command executing V4Router actions.
+const V4_SWAP: u8 = 0x10;
+
+/// The V4Router actions of a swap of a single pool given the output amount.
+/// The input tokens are paid from the Permit2 allowance of the caller and the
+/// output tokens are sent to the caller.
+const SWAP_EXACT_OUT_SINGLE: u8 = 0x08;
+const SETTLE_ALL: u8 = 0x0c;
+const TAKE_ALL: u8 = 0x0f;
+
+/// An impossibly distant future timestamp for the deadline of the swaps.
+static NEVER: LazyLock<U256> = LazyLock::new(|| U256::from(1) << 255);
+
+#[derive(Clone, Debug)]
+pub struct UniswapV4Interaction {
+    pub router: Address,
+    pub key: PoolKey,
+    pub zero_for_one: bool,
+    pub amount_out: u128,
+    pub amount_in_max: u128,
+}
+
+impl UniswapV4Interaction {
+    fn currencies(&self) -> (Address, Address) {
+        let (currency0, currency1, ..) = self.key.to_tuple();
+        if self.zero_for_one {
+            (currency0, currency1)
+        } else {
+            (currency1, currency0)
+        }
+    }
+
+    /// Encodes the actions and their parameters the way the V4Router decodes
+    /// them from the input of the `V4_SWAP` command.
+    fn encode_actions(&self) -> Bytes {
+        let (currency_in, currency_out) = self.currencies();
+        let actions = Bytes::from(vec![SWAP_EXACT_OUT_SINGLE, SETTLE_ALL, TAKE_ALL]);
+        let params = vec![
+            Bytes::from(
+                (
+                    self.key.to_tuple(),
+                    self.zero_for_one,
+                    self.amount_out,
+                    self.amount_in_max,
+                    Bytes::new(),
+                )
+                    .abi_encode(),
+            ),
+            Bytes::from((currency_in, U256::from(self.amount_in_max)).abi_encode()),
+            Bytes::from((currency_out, U256::from(self.amount_out)).abi_encode()),
+        ];
+        (actions, params).abi_encode_params().into()
+    }
+}
+
+impl Interaction for UniswapV4Interaction {
+    fn encode(&self) -> EncodedInteraction {
+        (
+            self.router,
+            U256::ZERO,
+            UniswapV4UniversalRouter::UniswapV4UniversalRouter::executeCall {
+                commands: Bytes::from(vec![V4_SWAP]),
+                inputs: vec![self.encode_actions()],
+                deadline: *NEVER,
+            }
+            .abi_encode()
+            .into(),
+        )
+    }
+}
+
+/// Approves the spender to transfer tokens of the settlement contract through
+/// Permit2. The approval expires at the end of the block it gets executed in.
+#[derive(Clone, Debug)]
+pub struct Permit2ApproveInteraction {
+    pub permit2: Address,
+    pub token: Address,
+    pub spender: Address,
+    pub amount: u128,
+}
+
+impl Interaction for Permit2ApproveInteraction {
+    fn encode(&self) -> EncodedInteraction {
+        (
+            self.permit2,
+            U256::ZERO,
+            Permit2::Permit2::approveCall {
+                token: self.token,
+                spender: self.spender,
+                amount: U160::from(self.amount),
+                // Permit2 replaces an expiration of 0 with the current block
+                // timestamp.
+                expiration: U48::ZERO,
+            }
+            .abi_encode()
+            .into(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, ethcontract::H160};
+
+    #[test]
+    fn encode_swap_exact_out_single() {
+        let interaction = UniswapV4Interaction {
+            router: [0x01; 20].into(),
+            key: PoolKey {
+                currency0: H160([0x02; 20]),
+                currency1: H160([0x03; 20]),
+                fee: 3000,
+                tick_spacing: 60,
+                hooks: H160::zero(),
+            },
+            zero_for_one: false,
+            amount_out: 42,
+            amount_in_max: 1337,
+        };
+
+        let (target, value, calldata) = interaction.encode();
+        assert_eq!(target, interaction.router);
+        assert!(value.is_zero());
+
+        let call =
+            UniswapV4UniversalRouter::UniswapV4UniversalRouter::executeCall::abi_decode(&calldata)
+                .unwrap();
+        assert_eq!(call.commands.as_ref(), [V4_SWAP]);
+        assert_eq!(call.inputs.len(), 1);
+
+        let (actions, params) = <(Bytes, Vec<Bytes>)>::abi_decode_params(&call.inputs[0]).unwrap();
+        assert_eq!(
+            actions.as_ref(),
+            [SWAP_EXACT_OUT_SINGLE, SETTLE_ALL, TAKE_ALL]
+        );
+        assert_eq!(
+            <(Address, U256)>::abi_decode(&params[1]).unwrap(),
+            (Address::repeat_byte(0x03), U256::from(1337))
+        );
+        assert_eq!(
+            <(Address, U256)>::abi_decode(&params[2]).unwrap(),
+            (Address::repeat_byte(0x02), U256::from(42))
+        );
+    }
+}
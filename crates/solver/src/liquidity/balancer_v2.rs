@@ -106,6 +106,7 @@ impl BalancerV2Liquidity {
                 reserves: pool.reserves,
                 fee: pool.common.swap_fee,
                 amplification_parameter: pool.amplification_parameter,
+                actual_supply: pool.actual_supply,
                 settlement_handling: Arc::new(SettlementHandler {
                     pool_id: pool.common.id,
                     inner: inner.clone(),
@@ -462,6 +463,7 @@ mod tests {
                 paused: true,
             },
             amplification_parameter: AmplificationParameter::try_new(1.into(), 1.into()).unwrap(),
+            actual_supply: None,
             reserves: btreemap! {
                 H160([0x73; 20]) => TokenState {
                         balance: 1_000_000_000_000_000_000u128.into(),
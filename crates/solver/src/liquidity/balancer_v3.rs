@@ -198,6 +198,7 @@ impl BalancerV3Liquidity {
                 end_fourth_root_price_ratio: pool.end_fourth_root_price_ratio,
                 price_ratio_update_start_time: pool.price_ratio_update_start_time,
                 price_ratio_update_end_time: pool.price_ratio_update_end_time,
+                current_timestamp: pool.current_timestamp,
                 settlement_handling: Arc::new(SettlementHandler {
                     pool_id: pool.common.id,
                     inner: inner.clone(),
@@ -213,6 +214,7 @@ impl BalancerV3Liquidity {
                 fee: pool.common.swap_fee,
                 version: pool.version,
                 max_trade_size_ratio: pool.max_trade_size_ratio,
+                strategy: pool.strategy,
                 first_four_weights_and_multipliers: pool.first_four_weights_and_multipliers,
                 second_four_weights_and_multipliers: pool.second_four_weights_and_multipliers,
                 last_update_time: pool.last_update_time,
@@ -664,6 +666,7 @@ mod tests {
             end_fourth_root_price_ratio: "1".parse().unwrap(),
             price_ratio_update_start_time: 0,
             price_ratio_update_end_time: 0,
+            current_timestamp: 1,
         }];
 
         pool_fetcher
@@ -0,0 +1,219 @@
+use {
+    super::{AmmOrderExecution, CurvePool, SettlementHandling},
+    crate::{
+        interactions::{
+            CurveStableSwapInteraction,
+            allowances::{AllowanceManager, AllowanceManaging, Allowances, Approval},
+        },
+        liquidity::Liquidity,
+        liquidity_collector::LiquidityCollecting,
+        settlement::SettlementEncoder,
+    },
+    alloy::primitives::Address,
+    anyhow::{Context, Result},
+    ethrpc::alloy::conversions::{IntoAlloy, IntoLegacy},
+    futures::future::try_join_all,
+    model::TokenPair,
+    primitive_types::H160,
+    shared::{
+        ethrpc::Web3,
+        http_solver::model::TokenAmount,
+        recent_block_cache::Block,
+        sources::curve::{self, pool_fetching::CurvePoolFetcher},
+    },
+    std::{
+        collections::HashSet,
+        sync::{Arc, Mutex},
+    },
+    tracing::instrument,
+};
+
+pub struct CurveLiquidity {
+    pool_fetcher: Arc<CurvePoolFetcher>,
+    settlement_allowances: Box<dyn AllowanceManaging>,
+}
+
+pub struct CurveSettlementHandler {
+    pub pool: Address,
+    pub coins: Vec<H160>,
+    // Mapping of how much allowance the pool has per token to spend on behalf of the settlement
+    // contract. Pools pull their input tokens themselves, so every pool is its own spender.
+    allowances: Mutex<Allowances>,
+}
+
+impl CurveSettlementHandler {
+    pub fn new(pool: Address, coins: Vec<H160>, allowances: Mutex<Allowances>) -> Self {
+        Self {
+            pool,
+            coins,
+            allowances,
+        }
+    }
+}
+
+impl CurveLiquidity {
+    pub fn new(gpv2_settlement: Address, web3: Web3, pool_fetcher: Arc<CurvePoolFetcher>) -> Self {
+        let settlement_allowances =
+            Box::new(AllowanceManager::new(web3, gpv2_settlement.into_legacy()));
+        Self {
+            pool_fetcher,
+            settlement_allowances,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl LiquidityCollecting for CurveLiquidity {
+    /// Given a list of offchain orders returns the list of AMM liquidity to be
+    /// considered
+    #[instrument(name = "curve_liquidity", skip_all)]
+    async fn get_liquidity(
+        &self,
+        pairs: HashSet<TokenPair>,
+        block: Block,
+    ) -> Result<Vec<Liquidity>> {
+        let pools = self.pool_fetcher.fetch(&pairs, block).await;
+        let allowances = try_join_all(pools.iter().map(|pool| {
+            self.settlement_allowances.get_allowances(
+                pool.coins
+                    .iter()
+                    .copied()
+                    .filter(|coin| *coin != curve::ETH)
+                    .collect(),
+                pool.address,
+            )
+        }))
+        .await?;
+
+        let mut result = Vec::new();
+        for (pool, allowances) in pools.into_iter().zip(allowances) {
+            result.push(Liquidity::Curve(CurvePool {
+                settlement_handling: Arc::new(CurveSettlementHandler::new(
+                    pool.address.into_alloy(),
+                    pool.coins.clone(),
+                    Mutex::new(allowances),
+                )),
+                pool,
+            }));
+        }
+        Ok(result)
+    }
+}
+
+impl CurveSettlementHandler {
+    /// Returns the interactions swapping all of `token_amount_in` for at least
+    /// `token_amount_out`: the ERC20 approval of the pool if needed and the
+    /// swap itself.
+    pub fn settle(
+        &self,
+        token_amount_in: TokenAmount,
+        token_amount_out: TokenAmount,
+    ) -> Result<(Option<Approval>, CurveStableSwapInteraction)> {
+        let index = |token| {
+            self.coins
+                .iter()
+                .position(|coin| *coin == token)
+                .context("pool doesn't trade the tokens")
+        };
+        let (i, j) = (
+            index(token_amount_in.token)?,
+            index(token_amount_out.token)?,
+        );
+        anyhow::ensure!(i != j, "pool doesn't trade the tokens");
+
+        let approval = self
+            .allowances
+            .lock()
+            .expect("Thread holding mutex panicked")
+            .approve_token_or_default(token_amount_in.clone());
+
+        Ok((
+            approval,
+            CurveStableSwapInteraction {
+                pool: self.pool,
+                i: i.try_into()?,
+                j: j.try_into()?,
+                amount_in: token_amount_in.amount.into_alloy(),
+                min_amount_out: token_amount_out.amount.into_alloy(),
+            },
+        ))
+    }
+}
+
+impl SettlementHandling<CurvePool> for CurveSettlementHandler {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    // Creates the required interactions to convert the given input into output.
+    // Assumes slippage is already applied to the `input_max` field. The pools
+    // only swap exact inputs, so all of `input_max` is spent and any output
+    // above `output` stays in the settlement contract.
+    fn encode(&self, execution: AmmOrderExecution, encoder: &mut SettlementEncoder) -> Result<()> {
+        let (approval, swap) = self.settle(execution.input_max, execution.output)?;
+        if let Some(approval) = approval {
+            encoder.append_to_execution_plan_internalizable(
+                Arc::new(approval),
+                execution.internalizable,
+            );
+        }
+        encoder.append_to_execution_plan_internalizable(Arc::new(swap), execution.internalizable);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settles_through_the_pool() {
+        let coins = (1..=3).map(H160::from_low_u64_be).collect::<Vec<_>>();
+        let handler = CurveSettlementHandler::new(
+            Address::repeat_byte(0x01),
+            coins.clone(),
+            Mutex::new(Allowances::new(
+                Address::repeat_byte(0x01).into_legacy(),
+                maplit::hashmap! { coins[2] => 100.into() },
+            )),
+        );
+
+        let (approval, swap) = handler
+            .settle(
+                TokenAmount::new(coins[2], 99),
+                TokenAmount::new(coins[0], 42),
+            )
+            .unwrap();
+        assert_eq!(approval, None);
+        assert_eq!(swap.pool, handler.pool);
+        assert_eq!((swap.i, swap.j), (2, 0));
+        assert_eq!(
+            (swap.amount_in, swap.min_amount_out),
+            (
+                alloy::primitives::U256::from(99),
+                alloy::primitives::U256::from(42)
+            )
+        );
+
+        // Tokens without allowance for the pool get approved.
+        let (approval, swap) = handler
+            .settle(TokenAmount::new(coins[0], 1), TokenAmount::new(coins[1], 1))
+            .unwrap();
+        assert_ne!(approval, None);
+        assert_eq!((swap.i, swap.j), (0, 1));
+
+        assert!(
+            handler
+                .settle(
+                    TokenAmount::new(coins[0], 1),
+                    TokenAmount::new(H160::from_low_u64_be(4), 1)
+                )
+                .is_err()
+        );
+        assert!(
+            handler
+                .settle(TokenAmount::new(coins[0], 1), TokenAmount::new(coins[0], 1))
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,255 @@
+use {
+    super::{AmmOrderExecution, MaverickV2Pool, SettlementHandling},
+    crate::{
+        interactions::{
+            MaverickV2Interaction,
+            allowances::{AllowanceManager, AllowanceManaging, Allowances, Approval},
+        },
+        liquidity::Liquidity,
+        liquidity_collector::LiquidityCollecting,
+        settlement::SettlementEncoder,
+    },
+    alloy::primitives::Address,
+    anyhow::{Context, Result},
+    ethrpc::alloy::conversions::{IntoAlloy, IntoLegacy},
+    model::TokenPair,
+    primitive_types::H160,
+    shared::{
+        ethrpc::Web3,
+        http_solver::model::TokenAmount,
+        recent_block_cache::Block,
+        sources::maverick_v2::pool_fetching::MaverickV2PoolFetcher,
+    },
+    std::{
+        collections::HashSet,
+        sync::{Arc, Mutex},
+    },
+    tracing::instrument,
+};
+
+pub struct MaverickV2Liquidity {
+    inner: Arc<Inner>,
+    pool_fetcher: Arc<MaverickV2PoolFetcher>,
+    settlement_allowances: Box<dyn AllowanceManaging>,
+}
+
+pub struct Inner {
+    pub router: Address,
+    gpv2_settlement: Address,
+    // Mapping of how much allowance the router has per token to spend on behalf of the settlement
+    // contract
+    allowances: Mutex<Allowances>,
+}
+
+pub struct MaverickV2SettlementHandler {
+    pub inner: Arc<Inner>,
+    pub pool: Address,
+    pub token_a: H160,
+    pub token_b: H160,
+}
+
+impl MaverickV2SettlementHandler {
+    pub fn new(
+        router: Address,
+        gpv2_settlement: Address,
+        allowances: Mutex<Allowances>,
+        pool: Address,
+        token_a: H160,
+        token_b: H160,
+    ) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                router,
+                gpv2_settlement,
+                allowances,
+            }),
+            pool,
+            token_a,
+            token_b,
+        }
+    }
+}
+
+impl MaverickV2Liquidity {
+    pub fn new(
+        router: Address,
+        gpv2_settlement: Address,
+        web3: Web3,
+        pool_fetcher: Arc<MaverickV2PoolFetcher>,
+    ) -> Self {
+        let settlement_allowances =
+            Box::new(AllowanceManager::new(web3, gpv2_settlement.into_legacy()));
+        Self {
+            inner: Arc::new(Inner {
+                router,
+                gpv2_settlement,
+                allowances: Mutex::new(Allowances::empty(router.into_legacy())),
+            }),
+            pool_fetcher,
+            settlement_allowances,
+        }
+    }
+
+    async fn cache_allowances(&self, tokens: HashSet<H160>) -> Result<()> {
+        let router = self.inner.router;
+        let allowances = self
+            .settlement_allowances
+            .get_allowances(tokens, router.into_legacy())
+            .await?;
+
+        self.inner
+            .allowances
+            .lock()
+            .expect("Thread holding mutex panicked")
+            .extend(allowances)?;
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl LiquidityCollecting for MaverickV2Liquidity {
+    /// Given a list of offchain orders returns the list of AMM liquidity to be
+    /// considered
+    #[instrument(name = "maverick_v2_liquidity", skip_all)]
+    async fn get_liquidity(
+        &self,
+        pairs: HashSet<TokenPair>,
+        block: Block,
+    ) -> Result<Vec<Liquidity>> {
+        let mut tokens = HashSet::new();
+        let mut result = Vec::new();
+        for pool in self.pool_fetcher.fetch(&pairs, block).await {
+            let token_pair = pool.tokens().context("cant create pair")?;
+
+            tokens.insert(pool.token_a);
+            tokens.insert(pool.token_b);
+
+            result.push(Liquidity::MaverickV2(MaverickV2Pool {
+                tokens: token_pair,
+                settlement_handling: Arc::new(MaverickV2SettlementHandler {
+                    inner: self.inner.clone(),
+                    pool: pool.address.into_alloy(),
+                    token_a: pool.token_a,
+                    token_b: pool.token_b,
+                }),
+                pool,
+            }))
+        }
+        self.cache_allowances(tokens).await?;
+        Ok(result)
+    }
+}
+
+impl MaverickV2SettlementHandler {
+    /// Returns the interactions swapping at most `token_amount_in_max` for
+    /// `token_amount_out`: the ERC20 approval of the router if needed and the
+    /// swap itself.
+    pub fn settle(
+        &self,
+        token_amount_in_max: TokenAmount,
+        token_amount_out: TokenAmount,
+    ) -> Result<(Option<Approval>, MaverickV2Interaction)> {
+        let tokens = (token_amount_in_max.token, token_amount_out.token);
+        let token_a_in = if tokens == (self.token_a, self.token_b) {
+            true
+        } else if tokens == (self.token_b, self.token_a) {
+            false
+        } else {
+            anyhow::bail!("pool doesn't trade the tokens");
+        };
+
+        let approval = self
+            .inner
+            .allowances
+            .lock()
+            .expect("Thread holding mutex panicked")
+            .approve_token_or_default(token_amount_in_max.clone());
+
+        Ok((
+            approval,
+            MaverickV2Interaction {
+                router: self.inner.router,
+                pool: self.pool,
+                recipient: self.inner.gpv2_settlement,
+                token_a_in,
+                amount_out: token_amount_out.amount.into_alloy(),
+                amount_in_max: token_amount_in_max.amount.into_alloy(),
+            },
+        ))
+    }
+}
+
+impl SettlementHandling<MaverickV2Pool> for MaverickV2SettlementHandler {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    // Creates the required interactions to convert the given input into output.
+    // Assumes slippage is already applied to the `input_max` field.
+    fn encode(&self, execution: AmmOrderExecution, encoder: &mut SettlementEncoder) -> Result<()> {
+        let (approval, swap) = self.settle(execution.input_max, execution.output)?;
+        if let Some(approval) = approval {
+            encoder.append_to_execution_plan_internalizable(
+                Arc::new(approval),
+                execution.internalizable,
+            );
+        }
+        encoder.append_to_execution_plan_internalizable(Arc::new(swap), execution.internalizable);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settles_through_the_router() {
+        let token_a = H160::from_low_u64_be(1);
+        let token_b = H160::from_low_u64_be(2);
+        let handler = MaverickV2SettlementHandler::new(
+            Address::repeat_byte(0x01),
+            Address::repeat_byte(0x02),
+            Mutex::new(Allowances::new(
+                Address::repeat_byte(0x01).into_legacy(),
+                maplit::hashmap! { token_b => 100.into() },
+            )),
+            Address::repeat_byte(0x03),
+            token_a,
+            token_b,
+        );
+
+        let (approval, swap) = handler
+            .settle(TokenAmount::new(token_b, 99), TokenAmount::new(token_a, 42))
+            .unwrap();
+        assert_eq!(approval, None);
+        assert_eq!(swap.router, handler.inner.router);
+        assert_eq!(swap.pool, handler.pool);
+        assert_eq!(swap.recipient, Address::repeat_byte(0x02));
+        assert!(!swap.token_a_in);
+        assert_eq!(
+            (swap.amount_out, swap.amount_in_max),
+            (
+                alloy::primitives::U256::from(42),
+                alloy::primitives::U256::from(99)
+            )
+        );
+
+        // Tokens without allowance for the router get approved.
+        let (approval, swap) = handler
+            .settle(TokenAmount::new(token_a, 1), TokenAmount::new(token_b, 1))
+            .unwrap();
+        assert_ne!(approval, None);
+        assert!(swap.token_a_in);
+
+        assert!(
+            handler
+                .settle(
+                    TokenAmount::new(token_a, 1),
+                    TokenAmount::new(H160::from_low_u64_be(3), 1)
+                )
+                .is_err()
+        );
+    }
+}
@@ -1,9 +1,12 @@
 pub mod balancer_v2;
 pub mod balancer_v3;
+pub mod curve;
 pub mod erc4626;
+pub mod maverick_v2;
 pub mod slippage;
 pub mod uniswap_v2;
 pub mod uniswap_v3;
+pub mod uniswap_v4;
 pub mod zeroex;
 
 #[cfg(test)]
@@ -38,6 +41,7 @@ use {
                     Gyro2CLPPoolVersion as V3Gyro2CLPPoolVersion,
                     GyroEPoolVersion as V3GyroEPoolVersion,
                     QuantAmmPoolVersion as V3QuantAmmPoolVersion,
+                    QuantAmmStrategy as V3QuantAmmStrategy,
                     QuantAmmTokenState as V3QuantAmmTokenState,
                     ReClammPoolVersion as V3ReClammPoolVersion,
                     StablePoolVersion as V3StablePoolVersion,
@@ -48,8 +52,11 @@ use {
                 },
                 swap::{fixed_point::Bfp as V3Bfp, signed_fixed_point::SBfp as V3SBfp},
             },
+            curve,
+            maverick_v2,
             uniswap_v2::pool_fetching::Pool,
             uniswap_v3::pool_fetching::PoolInfo,
+            uniswap_v4::PoolKey,
         },
     },
     std::{collections::BTreeMap, sync::Arc},
@@ -75,6 +82,9 @@ pub enum Liquidity {
     BalancerV3QuantAmm(BalancerV3QuantAmmOrder),
     LimitOrder(LimitOrder),
     Concentrated(ConcentratedLiquidity),
+    UniswapV4(UniswapV4Pool),
+    MaverickV2(MaverickV2Pool),
+    Curve(CurvePool),
     Erc4626(Box<erc4626::Erc4626Order>),
 }
 
@@ -359,6 +369,9 @@ pub struct StablePoolOrder {
     pub reserves: BTreeMap<H160, TokenState>,
     pub fee: Bfp,
     pub amplification_parameter: AmplificationParameter,
+    /// The BPT supply of composable stable pools, net of the BPT held by the
+    /// vault.
+    pub actual_supply: Option<U256>,
     #[cfg_attr(test, derivative(PartialEq = "ignore"))]
     pub settlement_handling: Arc<dyn SettlementHandling<Self>>,
 }
@@ -530,6 +543,7 @@ pub struct BalancerV3ReClammOrder {
     pub end_fourth_root_price_ratio: V3Bfp,
     pub price_ratio_update_start_time: u64,
     pub price_ratio_update_end_time: u64,
+    pub current_timestamp: u64,
     #[cfg_attr(test, derivative(PartialEq = "ignore"))]
     pub settlement_handling: Arc<dyn SettlementHandling<Self>>,
 }
@@ -549,6 +563,7 @@ pub struct BalancerV3QuantAmmOrder {
     pub fee: V3Bfp,
     pub version: V3QuantAmmPoolVersion,
     pub max_trade_size_ratio: V3Bfp,
+    pub strategy: V3QuantAmmStrategy,
     pub first_four_weights_and_multipliers: Vec<ethcontract::I256>,
     pub second_four_weights_and_multipliers: Vec<ethcontract::I256>,
     pub last_update_time: u64,
@@ -742,6 +757,81 @@ impl Settleable for ConcentratedLiquidity {
     }
 }
 
+/// A Uniswap V4 pool of the PoolManager. The state of the pool isn't fetched,
+/// the pool gets quoted by the solvers instead.
+#[derive(Clone)]
+#[cfg_attr(test, derive(Derivative))]
+#[cfg_attr(test, derivative(PartialEq))]
+pub struct UniswapV4Pool {
+    pub tokens: TokenPair,
+    pub key: PoolKey,
+    #[cfg_attr(test, derivative(PartialEq = "ignore"))]
+    pub settlement_handling: Arc<dyn SettlementHandling<Self>>,
+}
+
+impl std::fmt::Debug for UniswapV4Pool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Uniswap V4 pool {:?}", self.key)
+    }
+}
+
+impl Settleable for UniswapV4Pool {
+    type Execution = AmmOrderExecution;
+
+    fn settlement_handling(&self) -> &dyn SettlementHandling<Self> {
+        &*self.settlement_handling
+    }
+}
+
+/// A Maverick V2 pool with the reserves of the ticks around its active tick.
+#[derive(Clone)]
+#[cfg_attr(test, derive(Derivative))]
+#[cfg_attr(test, derivative(PartialEq))]
+pub struct MaverickV2Pool {
+    pub tokens: TokenPair,
+    pub pool: maverick_v2::Pool,
+    #[cfg_attr(test, derivative(PartialEq = "ignore"))]
+    pub settlement_handling: Arc<dyn SettlementHandling<Self>>,
+}
+
+impl std::fmt::Debug for MaverickV2Pool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Maverick V2 pool {:?}", self.pool.address)
+    }
+}
+
+impl Settleable for MaverickV2Pool {
+    type Execution = AmmOrderExecution;
+
+    fn settlement_handling(&self) -> &dyn SettlementHandling<Self> {
+        &*self.settlement_handling
+    }
+}
+
+/// A Curve StableSwap pool trading all pairs of its coins.
+#[derive(Clone)]
+#[cfg_attr(test, derive(Derivative))]
+#[cfg_attr(test, derivative(PartialEq))]
+pub struct CurvePool {
+    pub pool: curve::Pool,
+    #[cfg_attr(test, derivative(PartialEq = "ignore"))]
+    pub settlement_handling: Arc<dyn SettlementHandling<Self>>,
+}
+
+impl std::fmt::Debug for CurvePool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Curve pool {:?}", self.pool.address)
+    }
+}
+
+impl Settleable for CurvePool {
+    type Execution = AmmOrderExecution;
+
+    fn settlement_handling(&self) -> &dyn SettlementHandling<Self> {
+        &*self.settlement_handling
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use {super::*, std::sync::Mutex};
@@ -0,0 +1,264 @@
+use {
+    super::{AmmOrderExecution, SettlementHandling, UniswapV4Pool},
+    crate::{
+        interactions::{
+            Permit2ApproveInteraction,
+            UniswapV4Interaction,
+            allowances::{AllowanceManager, AllowanceManaging, Allowances, Approval},
+        },
+        liquidity::Liquidity,
+        liquidity_collector::LiquidityCollecting,
+        settlement::SettlementEncoder,
+    },
+    alloy::primitives::Address,
+    anyhow::{Context, Result},
+    ethrpc::alloy::conversions::{IntoAlloy, IntoLegacy},
+    model::TokenPair,
+    primitive_types::H160,
+    shared::{
+        ethrpc::Web3,
+        http_solver::model::TokenAmount,
+        recent_block_cache::Block,
+        sources::uniswap_v4::{PoolKey, pool_fetching::UniswapV4PoolFetcher},
+    },
+    std::{
+        collections::HashSet,
+        sync::{Arc, Mutex},
+    },
+    tracing::instrument,
+};
+
+pub struct UniswapV4Liquidity {
+    inner: Arc<Inner>,
+    pool_fetcher: Arc<UniswapV4PoolFetcher>,
+    settlement_allowances: Box<dyn AllowanceManaging>,
+}
+
+pub struct Inner {
+    pub pool_manager: Address,
+    pub router: Address,
+    pub permit2: Address,
+    // Mapping of how much allowance Permit2 has per token to spend on behalf of the settlement
+    // contract
+    allowances: Mutex<Allowances>,
+}
+
+pub struct UniswapV4SettlementHandler {
+    pub inner: Arc<Inner>,
+    pub key: PoolKey,
+}
+
+impl UniswapV4SettlementHandler {
+    pub fn new(
+        pool_manager: Address,
+        router: Address,
+        permit2: Address,
+        allowances: Mutex<Allowances>,
+        key: PoolKey,
+    ) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                pool_manager,
+                router,
+                permit2,
+                allowances,
+            }),
+            key,
+        }
+    }
+}
+
+impl UniswapV4Liquidity {
+    pub fn new(
+        pool_manager: Address,
+        router: Address,
+        permit2: Address,
+        gpv2_settlement: Address,
+        web3: Web3,
+        pool_fetcher: Arc<UniswapV4PoolFetcher>,
+    ) -> Self {
+        let settlement_allowances =
+            Box::new(AllowanceManager::new(web3, gpv2_settlement.into_legacy()));
+        Self {
+            inner: Arc::new(Inner {
+                pool_manager,
+                router,
+                permit2,
+                allowances: Mutex::new(Allowances::empty(permit2.into_legacy())),
+            }),
+            pool_fetcher,
+            settlement_allowances,
+        }
+    }
+
+    async fn cache_allowances(&self, tokens: HashSet<H160>) -> Result<()> {
+        let permit2 = self.inner.permit2;
+        let allowances = self
+            .settlement_allowances
+            .get_allowances(tokens, permit2.into_legacy())
+            .await?;
+
+        self.inner
+            .allowances
+            .lock()
+            .expect("Thread holding mutex panicked")
+            .extend(allowances)?;
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl LiquidityCollecting for UniswapV4Liquidity {
+    /// Given a list of offchain orders returns the list of AMM liquidity to be
+    /// considered
+    #[instrument(name = "uniswap_v4_liquidity", skip_all)]
+    async fn get_liquidity(
+        &self,
+        pairs: HashSet<TokenPair>,
+        _block: Block,
+    ) -> Result<Vec<Liquidity>> {
+        let mut tokens = HashSet::new();
+        let mut result = Vec::new();
+        for key in self.pool_fetcher.fetch(&pairs).await {
+            let token_pair = key.tokens().context("cant create pair")?;
+
+            tokens.insert(key.currency0);
+            tokens.insert(key.currency1);
+
+            result.push(Liquidity::UniswapV4(UniswapV4Pool {
+                tokens: token_pair,
+                key,
+                settlement_handling: Arc::new(UniswapV4SettlementHandler {
+                    inner: self.inner.clone(),
+                    key,
+                }),
+            }))
+        }
+        self.cache_allowances(tokens).await?;
+        Ok(result)
+    }
+}
+
+impl UniswapV4SettlementHandler {
+    /// Returns the interactions swapping at most `token_amount_in_max` for
+    /// `token_amount_out`: the ERC20 approval of Permit2 if needed, the
+    /// Permit2 approval of the router and the swap itself.
+    pub fn settle(
+        &self,
+        token_amount_in_max: TokenAmount,
+        token_amount_out: TokenAmount,
+    ) -> Result<(
+        Option<Approval>,
+        Permit2ApproveInteraction,
+        UniswapV4Interaction,
+    )> {
+        let zero_for_one = self
+            .key
+            .zero_for_one(token_amount_in_max.token, token_amount_out.token)
+            .context("pool doesn't trade the tokens")?;
+        let amount_in_max =
+            u128::try_from(token_amount_in_max.amount).context("input amount overflows u128")?;
+        let amount_out =
+            u128::try_from(token_amount_out.amount).context("output amount overflows u128")?;
+
+        let approval = self
+            .inner
+            .allowances
+            .lock()
+            .expect("Thread holding mutex panicked")
+            .approve_token_or_default(token_amount_in_max.clone());
+
+        Ok((
+            approval,
+            Permit2ApproveInteraction {
+                permit2: self.inner.permit2,
+                token: token_amount_in_max.token.into_alloy(),
+                spender: self.inner.router,
+                amount: amount_in_max,
+            },
+            UniswapV4Interaction {
+                router: self.inner.router,
+                key: self.key,
+                zero_for_one,
+                amount_out,
+                amount_in_max,
+            },
+        ))
+    }
+}
+
+impl SettlementHandling<UniswapV4Pool> for UniswapV4SettlementHandler {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    // Creates the required interactions to convert the given input into output.
+    // Assumes slippage is already applied to the `input_max` field.
+    fn encode(&self, execution: AmmOrderExecution, encoder: &mut SettlementEncoder) -> Result<()> {
+        let (approval, permit, swap) = self.settle(execution.input_max, execution.output)?;
+        if let Some(approval) = approval {
+            encoder.append_to_execution_plan_internalizable(
+                Arc::new(approval),
+                execution.internalizable,
+            );
+        }
+        encoder.append_to_execution_plan_internalizable(Arc::new(permit), execution.internalizable);
+        encoder.append_to_execution_plan_internalizable(Arc::new(swap), execution.internalizable);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, ethcontract::U256};
+
+    #[test]
+    fn settles_through_permit2() {
+        let token_a = H160::from_low_u64_be(1);
+        let token_b = H160::from_low_u64_be(2);
+        let handler = UniswapV4SettlementHandler {
+            inner: Arc::new(Inner {
+                pool_manager: Address::repeat_byte(0x03),
+                router: Address::repeat_byte(0x01),
+                permit2: Address::repeat_byte(0x02),
+                allowances: Mutex::new(Allowances::new(
+                    Address::repeat_byte(0x02).into_legacy(),
+                    maplit::hashmap! { token_b => 100.into() },
+                )),
+            }),
+            key: PoolKey {
+                currency0: token_a,
+                currency1: token_b,
+                fee: 3000,
+                tick_spacing: 60,
+                hooks: H160::zero(),
+            },
+        };
+
+        let (approval, permit, swap) = handler
+            .settle(TokenAmount::new(token_b, 99), TokenAmount::new(token_a, 42))
+            .unwrap();
+        assert_eq!(approval, None);
+        assert_eq!(permit.token, token_b.into_alloy());
+        assert_eq!(permit.spender, handler.inner.router);
+        assert_eq!(permit.amount, 99);
+        assert!(!swap.zero_for_one);
+        assert_eq!((swap.amount_out, swap.amount_in_max), (42, 99));
+
+        // Tokens without allowance for Permit2 get approved.
+        let (approval, ..) = handler
+            .settle(TokenAmount::new(token_a, 1), TokenAmount::new(token_b, 1))
+            .unwrap();
+        assert_ne!(approval, None);
+
+        assert!(
+            handler
+                .settle(
+                    TokenAmount::new(token_a, U256::MAX),
+                    TokenAmount::new(token_b, 1)
+                )
+                .is_err()
+        );
+    }
+}
@@ -1,10 +1,11 @@
 use {
     crate::liquidity::Liquidity,
     anyhow::Result,
+    arc_swap::ArcSwapOption,
     model::TokenPair,
     shared::{baseline_solver::BaseTokens, recent_block_cache::Block},
     std::{collections::HashSet, future::Future, sync::Arc, time::Duration},
-    tokio::sync::RwLock,
+    tokio::task::AbortHandle,
     tracing::{Instrument, instrument},
 };
 
@@ -51,8 +52,15 @@ impl LiquidityCollecting for LiquidityCollector {
 /// succeeds. Until the liquidity source has been initialised no liquidity will
 /// be provided.
 /// Also allows to periodically re-initialize the liquidity source.
+///
+/// Re-initialization is double-buffered: the new liquidity source is built in
+/// the background while the current one keeps serving requests, and it only
+/// gets swapped in atomically once its initialisation completed. Initialisers
+/// are therefore expected to only resolve once their source is synced to the
+/// chain head, so that there is never a window in which a source serves empty
+/// or stale liquidity.
 pub struct BackgroundInitLiquiditySource<L> {
-    liquidity_source: Arc<RwLock<Option<L>>>,
+    liquidity_source: Arc<ArcSwapOption<L>>,
 }
 
 impl<L> BackgroundInitLiquiditySource<L> {
@@ -74,7 +82,7 @@ impl<L> BackgroundInitLiquiditySource<L> {
             .liquidity_enabled
             .with_label_values(&[label])
             .set(0);
-        let liquidity_source = Arc::new(RwLock::new(None));
+        let liquidity_source = Arc::new(ArcSwapOption::empty());
         let inner = liquidity_source.clone();
         let inner_label = label.to_owned();
         tokio::task::spawn(
@@ -89,7 +97,9 @@ impl<L> BackgroundInitLiquiditySource<L> {
                             tokio::time::sleep(retry_init_timeout).await;
                         }
                         Ok(source) => {
-                            *inner.write().await = Some(source);
+                            // Requests still using the previous source finish
+                            // on it, which then gets dropped once they are done.
+                            inner.store(Some(Arc::new(source)));
                             tracing::debug!("successfully (re)initialized liquidity source");
                             Metrics::get()
                                 .liquidity_enabled
@@ -121,13 +131,49 @@ where
         pairs: HashSet<TokenPair>,
         at_block: Block,
     ) -> Result<Vec<Liquidity>> {
-        match &*self.liquidity_source.read().await {
+        match self.liquidity_source.load_full() {
             Some(source) => source.get_liquidity(pairs, at_block).await,
             None => Ok(vec![]),
         }
     }
 }
 
+/// A liquidity source together with the background tasks keeping its state in
+/// sync with the chain. The tasks get stopped once the source is dropped, for
+/// example when it was replaced by a re-initialized one.
+pub struct MaintainedLiquiditySource<L> {
+    source: L,
+    tasks: Vec<AbortHandle>,
+}
+
+impl<L> MaintainedLiquiditySource<L> {
+    pub fn new(source: L, tasks: Vec<AbortHandle>) -> Self {
+        Self { source, tasks }
+    }
+}
+
+impl<L> Drop for MaintainedLiquiditySource<L> {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<L> LiquidityCollecting for MaintainedLiquiditySource<L>
+where
+    L: LiquidityCollecting,
+{
+    async fn get_liquidity(
+        &self,
+        pairs: HashSet<TokenPair>,
+        at_block: Block,
+    ) -> Result<Vec<Liquidity>> {
+        self.source.get_liquidity(pairs, at_block).await
+    }
+}
+
 #[derive(prometheus_metric_storage::MetricStorage)]
 struct Metrics {
     /// Tracks whether or not the graph based liquidity is currently enabled.
@@ -252,4 +298,55 @@ mod test {
             .with_label_values(&["fake_reinit"]);
         assert!((5..=6).contains(&gauge.get()));
     }
+
+    #[tokio::test]
+    async fn reinit_keeps_serving_previous_source() {
+        struct Generation(usize);
+        #[async_trait::async_trait]
+        impl LiquidityCollecting for Generation {
+            async fn get_liquidity(
+                &self,
+                _pairs: HashSet<TokenPair>,
+                _at_block: Block,
+            ) -> Result<Vec<Liquidity>> {
+                Err(anyhow::anyhow!("generation {}", self.0))
+            }
+        }
+
+        let counter = Arc::new(AtomicUsize::new(0));
+        let closure_counter = counter.clone();
+        let init = move || {
+            let closure_counter = closure_counter.clone();
+            async move {
+                let generation = closure_counter.fetch_add(1, Ordering::SeqCst);
+                // Subsequent initialisations take a long time to sync.
+                if generation > 0 {
+                    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+                }
+                Ok(Generation(generation))
+            }
+        };
+
+        let source = BackgroundInitLiquiditySource::new(
+            "fake_blue_green",
+            init,
+            Duration::from_millis(10),
+            Some(Duration::from_millis(10)),
+        );
+
+        // wait until the re-initialization started but has not finished yet
+        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+        assert_eq!(counter.load(Ordering::SeqCst), 2);
+        let liquidity = source
+            .get_liquidity(Default::default(), Block::Recent)
+            .await;
+        assert_eq!(liquidity.unwrap_err().to_string(), "generation 0");
+
+        // wait until the re-initialized source got swapped in
+        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        let liquidity = source
+            .get_liquidity(Default::default(), Block::Recent)
+            .await;
+        assert_eq!(liquidity.unwrap_err().to_string(), "generation 1");
+    }
 }
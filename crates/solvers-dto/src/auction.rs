@@ -12,6 +12,7 @@ use {
 #[serde_as]
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct Auction {
     #[serde_as(as = "Option<DisplayFromStr>")]
     pub id: Option<i64>,
@@ -22,11 +23,15 @@ pub struct Auction {
     pub effective_gas_price: U256,
     pub deadline: chrono::DateTime<chrono::Utc>,
     pub surplus_capturing_jit_order_owners: Vec<H160>,
+    #[serde_as(as = "Option<HexOrDecimalU256>")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas_limit: Option<U256>,
 }
 
 #[serde_as]
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct Order {
     #[serde_as(as = "serialize::Hex")]
     pub uid: [u8; 56],
@@ -58,6 +63,8 @@ pub struct Order {
     pub flashloan_hint: Option<FlashloanHint>,
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub wrappers: Vec<WrapperCall>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub route_pin: Option<RoutePin>,
     pub signing_scheme: SigningScheme,
     #[serde(with = "bytes_hex")]
     pub signature: Vec<u8>,
@@ -89,6 +96,7 @@ pub enum SellTokenSource {
 #[serde_as]
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct InteractionData {
     pub target: H160,
     #[serde_as(as = "HexOrDecimalU256")]
@@ -122,6 +130,7 @@ pub enum Class {
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub enum FeePolicy {
     #[serde(rename_all = "camelCase")]
     Surplus { factor: f64, max_volume_factor: f64 },
@@ -138,6 +147,7 @@ pub enum FeePolicy {
 #[serde_as]
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct Quote {
     #[serde_as(as = "HexOrDecimalU256")]
     pub sell_amount: U256,
@@ -150,6 +160,7 @@ pub struct Quote {
 #[serde_as]
 #[derive(Debug, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct Token {
     pub decimals: Option<u8>,
     pub symbol: Option<String>,
@@ -167,6 +178,9 @@ pub enum Liquidity {
     WeightedProduct(WeightedProductPool),
     Stable(StablePool),
     ConcentratedLiquidity(ConcentratedLiquidityPool),
+    UniswapV4(UniswapV4Pool),
+    MaverickV2(MaverickV2Pool),
+    Curve(CurvePool),
     GyroE(Box<GyroEPool>),
     Gyro2CLP(Gyro2CLPPool),
     Gyro3CLP(Gyro3CLPPool),
@@ -180,6 +194,7 @@ pub enum Liquidity {
 #[serde_as]
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct ConstantProductPool {
     pub id: String,
     pub address: H160,
@@ -193,6 +208,7 @@ pub struct ConstantProductPool {
 #[serde_as]
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct ConstantProductReserve {
     #[serde_as(as = "HexOrDecimalU256")]
     pub balance: U256,
@@ -201,6 +217,7 @@ pub struct ConstantProductReserve {
 #[serde_as]
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct WeightedProductPool {
     pub id: String,
     pub address: H160,
@@ -216,6 +233,7 @@ pub struct WeightedProductPool {
 #[serde_as]
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct WeightedProductReserve {
     #[serde_as(as = "HexOrDecimalU256")]
     pub balance: U256,
@@ -234,6 +252,7 @@ pub enum WeightedProductVersion {
 #[serde_as]
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct StablePool {
     pub id: String,
     pub address: H160,
@@ -244,11 +263,17 @@ pub struct StablePool {
     pub tokens: HashMap<H160, StableReserve>,
     pub amplification_parameter: BigDecimal,
     pub fee: BigDecimal,
+    /// The BPT supply of composable stable pools, net of the BPT held by the
+    /// vault, which allows swapping the pool's own BPT.
+    #[serde_as(as = "Option<HexOrDecimalU256>")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actual_supply: Option<U256>,
 }
 
 #[serde_as]
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct StableReserve {
     #[serde_as(as = "HexOrDecimalU256")]
     pub balance: U256,
@@ -259,6 +284,7 @@ pub struct StableReserve {
 #[serde_as]
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct StableSurgePool {
     pub id: String,
     pub address: H160,
@@ -278,6 +304,7 @@ pub struct StableSurgePool {
 #[serde_as]
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct ConcentratedLiquidityPool {
     pub id: String,
     pub address: H160,
@@ -295,9 +322,103 @@ pub struct ConcentratedLiquidityPool {
     pub fee: BigDecimal,
 }
 
+/// A Uniswap V4 pool, identified by its pool key in the PoolManager. The state
+/// of the pool isn't included, the pool has to be quoted on-chain.
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+pub struct UniswapV4Pool {
+    pub id: String,
+    /// The PoolManager holding the pool.
+    pub address: H160,
+    pub router: H160,
+    #[serde_as(as = "HexOrDecimalU256")]
+    pub gas_estimate: U256,
+    pub tokens: Vec<H160>,
+    /// The LP fee in hundredths of a basis point, or `0x800000` for pools
+    /// whose fee is set by their hooks.
+    pub fee: u32,
+    pub tick_spacing: i32,
+    pub hooks: H160,
+}
+
+/// A Maverick V2 pool with the reserves of its bins aggregated per tick. Only
+/// the ticks around the active tick are included, swaps moving the price
+/// beyond them can't be quoted.
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+pub struct MaverickV2Pool {
+    pub id: String,
+    pub address: H160,
+    pub router: H160,
+    #[serde_as(as = "HexOrDecimalU256")]
+    pub gas_estimate: U256,
+    /// The tokens A and B of the pool, in that order.
+    pub tokens: Vec<H160>,
+    pub tick_spacing: u32,
+    pub active_tick: i32,
+    #[serde_as(as = "HashMap<DisplayFromStr, _>")]
+    pub ticks: HashMap<i32, MaverickV2Tick>,
+    /// The fee for swapping token A in, as a fraction of the input amount.
+    pub fee_a_in: BigDecimal,
+    /// The fee for swapping token B in, as a fraction of the input amount.
+    pub fee_b_in: BigDecimal,
+}
+
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+pub struct MaverickV2Tick {
+    #[serde_as(as = "HexOrDecimalU256")]
+    pub reserve_a: U256,
+    #[serde_as(as = "HexOrDecimalU256")]
+    pub reserve_b: U256,
+}
+
+/// A Curve StableSwap pool. Metapools pair tokens with the LP token of a base
+/// pool, whose rate is the virtual price of the base pool.
 #[serde_as]
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+pub struct CurvePool {
+    pub id: String,
+    pub address: H160,
+    #[serde_as(as = "HexOrDecimalU256")]
+    pub gas_estimate: U256,
+    /// The coins of the pool in the order of their on-chain indices.
+    pub tokens: Vec<CurveCoin>,
+    /// The amplification parameter multiplied by its precision of 100.
+    #[serde_as(as = "HexOrDecimalU256")]
+    pub amplification: U256,
+    /// The swap fee relative to `10^10`.
+    #[serde_as(as = "HexOrDecimalU256")]
+    pub fee: U256,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_pool: Option<H160>,
+}
+
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+pub struct CurveCoin {
+    pub address: H160,
+    #[serde_as(as = "HexOrDecimalU256")]
+    pub balance: U256,
+    /// The rate normalizing the balance to 18 decimals.
+    #[serde_as(as = "HexOrDecimalU256")]
+    pub rate: U256,
+}
+
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct ForeignLimitOrder {
     pub id: String,
     pub address: H160,
@@ -318,6 +439,7 @@ pub struct ForeignLimitOrder {
 #[serde_as]
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct Erc4626Edge {
     pub id: String,
     #[serde_as(as = "HexOrDecimalU256")]
@@ -329,6 +451,7 @@ pub struct Erc4626Edge {
 #[serde_as]
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct GyroEPool {
     pub id: String,
     pub address: H160,
@@ -359,6 +482,7 @@ pub struct GyroEPool {
 #[serde_as]
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct GyroEReserve {
     #[serde_as(as = "HexOrDecimalU256")]
     pub balance: U256,
@@ -375,6 +499,7 @@ pub enum GyroEVersion {
 #[serde_as]
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct Gyro2CLPPool {
     pub id: String,
     pub address: H160,
@@ -393,6 +518,7 @@ pub struct Gyro2CLPPool {
 #[serde_as]
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct Gyro3CLPPool {
     pub id: String,
     pub address: H160,
@@ -410,6 +536,7 @@ pub struct Gyro3CLPPool {
 #[serde_as]
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct Gyro2CLPReserve {
     #[serde_as(as = "HexOrDecimalU256")]
     pub balance: U256,
@@ -426,6 +553,7 @@ pub enum Gyro2CLPVersion {
 #[serde_as]
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct Gyro3CLPReserve {
     #[serde_as(as = "HexOrDecimalU256")]
     pub balance: U256,
@@ -442,6 +570,7 @@ pub enum Gyro3CLPVersion {
 #[serde_as]
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct ReClammPool {
     pub id: String,
     pub address: H160,
@@ -458,11 +587,14 @@ pub struct ReClammPool {
     pub end_fourth_root_price_ratio: BigDecimal,
     pub price_ratio_update_start_time: u64,
     pub price_ratio_update_end_time: u64,
+    #[serde(default)]
+    pub current_timestamp: u64,
 }
 
 #[serde_as]
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct ReClammReserve {
     #[serde_as(as = "HexOrDecimalU256")]
     pub balance: U256,
@@ -473,6 +605,7 @@ pub struct ReClammReserve {
 #[serde_as]
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct QuantAmmPool {
     pub id: String,
     pub address: H160,
@@ -490,11 +623,24 @@ pub struct QuantAmmPool {
     pub last_update_time: u64,
     pub last_interop_time: u64,
     pub current_timestamp: u64,
+    // Extended strategy metadata, not needed for the swap math
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strategy: Option<QuantAmmStrategy>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+pub struct QuantAmmStrategy {
+    pub rule: Option<String>,
+    pub oracles: Vec<String>,
+    pub update_interval: u64,
 }
 
 #[serde_as]
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct QuantAmmReserve {
     #[serde_as(as = "HexOrDecimalU256")]
     pub balance: U256,
@@ -511,6 +657,7 @@ pub enum QuantAmmVersion {
 #[serde_as]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct FlashloanHint {
     pub liquidity_provider: H160,
     pub protocol_adapter: H160,
@@ -520,8 +667,20 @@ pub struct FlashloanHint {
     pub amount: U256,
 }
 
+/// Hint pinning the route of an order to specific liquidity, e.g. for
+/// debugging incidents or running directed experiments.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RoutePin {
+    /// Route only through liquidity of this kind, e.g. `constantProduct`.
+    Protocol(String),
+    /// Route only through the liquidity with this ID.
+    Liquidity(String),
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct WrapperCall {
     pub address: H160,
     #[serde(with = "bytes_hex")]
@@ -7,10 +7,68 @@ use {
     web3::types::{H160, U256},
 };
 
-#[derive(Debug, Serialize, Deserialize, Default)]
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct Solutions {
     pub solutions: Vec<Solution>,
+    /// Orders that the solver skipped because they are guaranteed to fail
+    /// settlement.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub skipped_orders: Vec<SkippedOrder>,
+    /// Why the auction went unsolved, if the solver returns no solutions.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub diagnostics: Option<Diagnostics>,
+    /// Whether the request deadline cut fetching liquidity or solving short,
+    /// i.e. the solutions may be incomplete.
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub deadline_exceeded: bool,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagnostics {
+    /// The reasons the orders of the auction were not solved.
+    pub orders: Vec<UnsolvedOrder>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnsolvedOrder {
+    pub order: OrderUid,
+    pub reason: UnsolvedReason,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum UnsolvedReason {
+    /// No liquidity connects the traded tokens within the solver's routing
+    /// constraints.
+    NoLiquidity,
+    /// Routes exist, but none of them satisfies the order's limit price.
+    LimitPriceUnreachable,
+    /// The auction deadline was reached before the order was solved.
+    DeadlineExhausted,
+    /// The order trades a token the solver refuses to route, so all pools
+    /// trading it are excluded.
+    AllPoolsDenied,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedOrder {
+    pub order: OrderUid,
+    pub reason: SkipReason,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SkipReason {
+    /// The order expires before it can be settled.
+    Expired,
+    /// The order signature can't be valid for its signing scheme.
+    InvalidSignature,
+    /// The owner doesn't have enough sell token balance or allowance.
+    InsufficientBalance,
 }
 
 #[serde_as]
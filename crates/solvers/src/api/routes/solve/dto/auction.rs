@@ -83,6 +83,15 @@ pub fn into_domain(auction: Auction) -> Result<auction::Auction, Error> {
         liquidity: auction
             .liquidity
             .iter()
+            // Uniswap V4 pools don't come with their state and can only be
+            // routed by solvers quoting them on-chain. Maverick V2 and Curve
+            // pools are only routed by solvers implementing their math.
+            .filter(|liquidity| {
+                !matches!(
+                    liquidity,
+                    Liquidity::UniswapV4(_) | Liquidity::MaverickV2(_) | Liquidity::Curve(_)
+                )
+            })
             .map(|liquidity| match liquidity {
                 Liquidity::ConstantProduct(liquidity) => {
                     constant_product_pool::to_domain(liquidity)
@@ -102,6 +111,9 @@ pub fn into_domain(auction: Auction) -> Result<auction::Auction, Error> {
                 Liquidity::ReClamm(liquidity) => reclamm_pool::to_domain(liquidity),
                 Liquidity::QuantAmm(liquidity) => quant_amm_pool::to_domain(liquidity),
                 Liquidity::StableSurge(liquidity) => stable_surge_pool::to_domain(liquidity),
+                Liquidity::UniswapV4(_) | Liquidity::MaverickV2(_) | Liquidity::Curve(_) => {
+                    unreachable!("filtered out above")
+                }
             })
             .try_collect()?,
         gas_price: auction::GasPrice(eth::Ether(auction.effective_gas_price)),
@@ -496,6 +508,7 @@ mod reclamm_pool {
                 .ok_or("invalid end_fourth_root_price_ratio")?,
                 price_ratio_update_start_time: pool.price_ratio_update_start_time,
                 price_ratio_update_end_time: pool.price_ratio_update_end_time,
+                current_timestamp: pool.current_timestamp,
             }),
         })
     }
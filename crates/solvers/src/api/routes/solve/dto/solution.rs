@@ -128,6 +128,9 @@ pub fn from_domain(solutions: &[solution::Solution]) -> super::Solutions {
                     .collect(),
             })
             .collect(),
+        skipped_orders: Default::default(),
+        diagnostics: Default::default(),
+        deadline_exceeded: false,
     }
 }
 
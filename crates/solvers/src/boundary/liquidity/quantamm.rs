@@ -39,6 +39,7 @@ pub fn to_boundary_pool(address: H160, pool: &liquidity::quantamm::Pool) -> Opti
         reserves,
         version: QuantAmmPoolVersion::V1,
         max_trade_size_ratio: to_fixed_point(&pool.max_trade_size_ratio)?,
+        strategy: Default::default(),
         first_four_weights_and_multipliers: pool
             .first_four_weights_and_multipliers
             .iter()
@@ -51,6 +51,7 @@ pub fn to_boundary_pool(address: H160, pool: &liquidity::stable::Pool) -> Option
         },
         reserves,
         amplification_parameter,
+        actual_supply: None,
     })
 }
 
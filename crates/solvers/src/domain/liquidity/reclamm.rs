@@ -13,6 +13,7 @@ pub struct Pool {
     pub end_fourth_root_price_ratio: eth::Rational,
     pub price_ratio_update_start_time: u64,
     pub price_ratio_update_end_time: u64,
+    pub current_timestamp: u64,
 }
 
 #[derive(Clone, Debug)]